@@ -171,7 +171,7 @@ pub fn reorg_short(benchmark: &mut Benchmark) {
 				let fork = store.fork(origin).unwrap();
 				fork.store().insert(block).unwrap();
 				fork.store().canonize(&hash).unwrap();
-				store.switch_to_fork(fork).unwrap();
+				store.switch_to_fork(&fork).unwrap();
 			},
 		}
 	}
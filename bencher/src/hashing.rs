@@ -0,0 +1,51 @@
+use chain::{Block, IndexedBlock};
+use test_data;
+
+use super::Benchmark;
+
+// hash <BLOCKS> blocks that has <TRANSACTIONS> transactions each with <OUTPUTS> outputs each
+fn build_blocks() -> Vec<Block> {
+	// params
+	const BLOCKS: usize = 10;
+	const TRANSACTIONS: usize = 2000;
+	const OUTPUTS: usize = 10;
+
+	let mut blocks = Vec::new();
+	for x in 0..BLOCKS {
+		let mut builder = test_data::block_builder()
+			.transaction().coinbase().lock_time(x as u32).output().value(5000000000).build().build();
+		for tx in 0..TRANSACTIONS {
+			let mut tx_builder = builder.transaction().lock_time(tx as u32);
+			for output in 0..OUTPUTS {
+				tx_builder = tx_builder.output().value(output as u64).build();
+			}
+			builder = tx_builder.build();
+		}
+		blocks.push(builder.merkled_header().nonce((x as u8).into()).build().build());
+	}
+	blocks
+}
+
+pub fn hash_serial(benchmark: &mut Benchmark) {
+	let blocks = build_blocks();
+	benchmark.samples(blocks.len());
+
+	// bench
+	benchmark.start();
+	for block in blocks {
+		IndexedBlock::from_raw(block);
+	}
+	benchmark.stop();
+}
+
+pub fn hash_parallel(benchmark: &mut Benchmark) {
+	let blocks = build_blocks();
+	benchmark.samples(blocks.len());
+
+	// bench
+	benchmark.start();
+	for block in blocks {
+		IndexedBlock::from_raw_parallel(block);
+	}
+	benchmark.stop();
+}
@@ -9,6 +9,7 @@ extern crate byteorder;
 extern crate primitives;
 
 mod database;
+mod hashing;
 mod verifier;
 
 use time::{PreciseTime, Duration};
@@ -71,6 +72,8 @@ macro_rules! benchmark {
 }
 
 fn main() {
+	benchmark!(hashing::hash_serial);
+	benchmark!(hashing::hash_parallel);
 	benchmark!(database::fetch);
 	benchmark!(database::write);
 	benchmark!(database::reorg_short);
@@ -0,0 +1,145 @@
+//! Monetary amounts with range-checked arithmetic.
+
+use std::fmt;
+use constants::SATOSHIS_IN_COIN;
+
+/// No amount larger than this (in zatoshis) is valid.
+pub const MAX_MONEY: i64 = 21_000_000 * SATOSHIS_IN_COIN as i64;
+
+/// Amount of zatoshis (satoshis).
+/// The absolute value of the amount never exceeds `MAX_MONEY`. Negative amounts are allowed,
+/// because value balances of shielded pools could be negative.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+impl Amount {
+	/// Zero amount.
+	pub fn zero() -> Self {
+		Amount(0)
+	}
+
+	/// Maximal valid amount.
+	pub fn max_value() -> Self {
+		Amount(MAX_MONEY)
+	}
+
+	/// Amount of given number of zatoshis. None if it is out of range.
+	pub fn from_zatoshis(zatoshis: i64) -> Option<Self> {
+		match zatoshis.checked_abs() {
+			Some(abs) if abs <= MAX_MONEY => Some(Amount(zatoshis)),
+			_ => None,
+		}
+	}
+
+	/// Amount of given (unsigned) number of zatoshis. None if it is out of range.
+	pub fn from_u64(zatoshis: u64) -> Option<Self> {
+		if zatoshis <= MAX_MONEY as u64 {
+			Some(Amount(zatoshis as i64))
+		} else {
+			None
+		}
+	}
+
+	/// Amount of given number of coins, rounded to the nearest zatoshi. None if it is out of range.
+	pub fn from_coins(coins: f64) -> Option<Self> {
+		let zatoshis = (coins * SATOSHIS_IN_COIN as f64).round();
+		if zatoshis.is_finite() && zatoshis.abs() <= MAX_MONEY as f64 {
+			Some(Amount(zatoshis as i64))
+		} else {
+			None
+		}
+	}
+
+	/// Number of zatoshis.
+	pub fn zatoshis(self) -> i64 {
+		self.0
+	}
+
+	/// Number of zatoshis. None if amount is negative.
+	pub fn to_u64(self) -> Option<u64> {
+		if self.0 >= 0 {
+			Some(self.0 as u64)
+		} else {
+			None
+		}
+	}
+
+	/// Number of coins.
+	pub fn to_coins(self) -> f64 {
+		self.0 as f64 / SATOSHIS_IN_COIN as f64
+	}
+
+	pub fn is_negative(self) -> bool {
+		self.0 < 0
+	}
+
+	/// Sum of amounts. None if result is out of range.
+	pub fn checked_add(self, other: Amount) -> Option<Amount> {
+		// |self| + |other| <= 2 * MAX_MONEY < i64::max_value() => can't overflow i64
+		Amount::from_zatoshis(self.0 + other.0)
+	}
+
+	/// Difference of amounts. None if result is out of range.
+	pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+		Amount::from_zatoshis(self.0 - other.0)
+	}
+
+	/// Sum of all amounts. None if any partial sum is out of range.
+	pub fn checked_sum<I>(amounts: I) -> Option<Amount> where I: IntoIterator<Item=Amount> {
+		amounts.into_iter().fold(Some(Amount::zero()), |sum, amount| sum.and_then(|sum| sum.checked_add(amount)))
+	}
+}
+
+impl ::std::ops::Neg for Amount {
+	type Output = Amount;
+
+	fn neg(self) -> Amount {
+		// range is symmetric => negation is always valid
+		Amount(-self.0)
+	}
+}
+
+impl fmt::Display for Amount {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let sign = if self.0 < 0 { "-" } else { "" };
+		let abs = self.0.abs() as u64;
+		write!(f, "{}{}.{:08}", sign, abs / SATOSHIS_IN_COIN, abs % SATOSHIS_IN_COIN)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Amount, MAX_MONEY};
+
+	#[test]
+	fn test_amount_range() {
+		assert_eq!(Amount::from_zatoshis(MAX_MONEY), Some(Amount::max_value()));
+		assert_eq!(Amount::from_zatoshis(-MAX_MONEY), Some(-Amount::max_value()));
+		assert_eq!(Amount::from_zatoshis(MAX_MONEY + 1), None);
+		assert_eq!(Amount::from_zatoshis(-MAX_MONEY - 1), None);
+		assert_eq!(Amount::from_u64(MAX_MONEY as u64 + 1), None);
+		assert_eq!(Amount::from_u64(::std::u64::MAX), None);
+		assert_eq!(Amount::from_zatoshis(-1).unwrap().to_u64(), None);
+	}
+
+	#[test]
+	fn test_amount_arithmetic() {
+		let one = Amount::from_zatoshis(1).unwrap();
+		assert_eq!(Amount::max_value().checked_add(one), None);
+		assert_eq!(Amount::max_value().checked_sub(one).and_then(|a| a.checked_add(one)), Some(Amount::max_value()));
+		assert_eq!((-Amount::max_value()).checked_sub(one), None);
+		assert_eq!(Amount::zero().checked_sub(one), Some(-one));
+		assert_eq!(Amount::checked_sum(vec![one, one, -one]), Some(one));
+		assert_eq!(Amount::checked_sum(vec![Amount::max_value(), one, -one]), None);
+	}
+
+	#[test]
+	fn test_amount_coins() {
+		assert_eq!(Amount::from_coins(0.1), Amount::from_zatoshis(10_000_000));
+		assert_eq!(Amount::from_coins(-21_000_000.0), Some(-Amount::max_value()));
+		assert_eq!(Amount::from_coins(21_000_000.00000001), None);
+		assert_eq!(Amount::from_coins(::std::f64::NAN), None);
+		assert_eq!(Amount::from_zatoshis(-150_000_000).unwrap().to_string(), "-1.50000000");
+		assert_eq!(Amount::from_coins(Amount::from_zatoshis(123).unwrap().to_coins()), Amount::from_zatoshis(123));
+	}
+}
@@ -8,8 +8,11 @@ use hash::H256;
 use merkle_root::merkle_root;
 
 #[derive(Debug, PartialEq, Clone, Serializable, Deserializable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct Block {
+	#[cfg_attr(feature = "serde", serde(flatten))]
 	pub block_header: BlockHeader,
+	#[cfg_attr(feature = "serde", serde(rename = "tx"))]
 	pub transactions: Vec<Transaction>,
 }
 
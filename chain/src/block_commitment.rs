@@ -0,0 +1,108 @@
+//! Commitment, stored in the `final_sapling_root` field of the block header.
+//! Its meaning depends on the network upgrade, active at the block height.
+
+use crypto::blake2b_personal;
+use hash::H256;
+use ser::Stream;
+use transaction::Transaction;
+use transaction_digest::transaction_auth_digest;
+
+/// Commitment of the block header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockCommitment {
+	/// Before Sapling: reserved field (hashReserved).
+	Reserved(H256),
+	/// Sapling and Blossom: root of the Sapling note commitment tree after applying the block (hashFinalSaplingRoot).
+	FinalSaplingRoot(H256),
+	/// Heartwood and Canopy: root of the chain history tree of previous blocks (hashChainHistoryRoot).
+	/// https://zips.z.cash/zip-0221
+	ChainHistoryRoot(H256),
+	/// NU5 and later: commitment to the chain history root and authorizing data of block transactions (hashBlockCommitments).
+	/// https://zips.z.cash/zip-0244#block-header-changes
+	BlockCommitments(H256),
+}
+
+impl BlockCommitment {
+	/// Value of the header field.
+	pub fn hash(&self) -> &H256 {
+		match *self {
+			BlockCommitment::Reserved(ref hash) |
+			BlockCommitment::FinalSaplingRoot(ref hash) |
+			BlockCommitment::ChainHistoryRoot(ref hash) |
+			BlockCommitment::BlockCommitments(ref hash) => hash,
+		}
+	}
+}
+
+/// Authorizing data commitment of legacy (pre-v5) transactions.
+const LEGACY_AUTH_DIGEST: [u8; 32] = [0xff; 32];
+
+/// Root of the tree of block transactions authorizing data commitments (hashAuthDataRoot).
+/// Unlike transactions merkle tree, this tree is padded with zero leaves to the power of two.
+pub fn auth_data_root<'a, I>(transactions: I) -> H256 where I: IntoIterator<Item=&'a Transaction> {
+	let mut row = transactions.into_iter()
+		.map(|tx| if tx.is_v5() { transaction_auth_digest(tx) } else { LEGACY_AUTH_DIGEST.into() })
+		.collect::<Vec<_>>();
+	if row.is_empty() {
+		return H256::default();
+	}
+
+	let width = row.len().next_power_of_two();
+	row.resize(width, H256::default());
+	while row.len() > 1 {
+		row = row.chunks(2)
+			.map(|pair| {
+				let mut stream = Stream::default();
+				stream.append_slice(&*pair[0]).append_slice(&*pair[1]);
+				blake2b_personal(b"ZcashAuthDatHash", &stream.out())
+			})
+			.collect();
+	}
+	row[0]
+}
+
+/// Value of the NU5 header commitment (hashBlockCommitments).
+pub fn block_commitments_hash(chain_history_root: &H256, auth_data_root: &H256) -> H256 {
+	let mut stream = Stream::default();
+	stream
+		.append(chain_history_root)
+		.append(auth_data_root)
+		.append(&H256::default());
+	blake2b_personal(b"ZcashBlockCommit", &stream.out())
+}
+
+#[cfg(test)]
+mod tests {
+	use crypto::blake2b_personal;
+	use hash::H256;
+	use transaction::Transaction;
+	use super::{auth_data_root, block_commitments_hash, BlockCommitment};
+
+	fn node(left: &H256, right: &H256) -> H256 {
+		let mut input = left.to_vec();
+		input.extend_from_slice(&**right);
+		blake2b_personal(b"ZcashAuthDatHash", &input)
+	}
+
+	#[test]
+	fn test_auth_data_root() {
+		let legacy: H256 = [0xff; 32].into();
+		let tx = Transaction::default();
+
+		assert_eq!(auth_data_root(Vec::<&Transaction>::new()), H256::default());
+		assert_eq!(auth_data_root(vec![&tx]), legacy);
+		assert_eq!(auth_data_root(vec![&tx, &tx]), node(&legacy, &legacy));
+		// tree is padded with zero leaves
+		assert_eq!(auth_data_root(vec![&tx, &tx, &tx]),
+			node(&node(&legacy, &legacy), &node(&legacy, &H256::default())));
+	}
+
+	#[test]
+	fn test_block_commitments_hash() {
+		let mut input = vec![1u8; 32];
+		input.extend_from_slice(&[2u8; 32]);
+		input.extend_from_slice(&[0u8; 32]);
+		assert_eq!(block_commitments_hash(&[1u8; 32].into(), &[2u8; 32].into()), blake2b_personal(b"ZcashBlockCommit", &input));
+		assert_eq!(BlockCommitment::ChainHistoryRoot(1.into()).hash(), &H256::from(1));
+	}
+}
@@ -0,0 +1,203 @@
+//! Streaming deserialization of blocks.
+
+use std::{cmp, io};
+use ser::{Reader, Error as ReaderError};
+use indexed_block::IndexedBlock;
+use indexed_header::IndexedBlockHeader;
+use indexed_transaction::IndexedTransaction;
+
+/// Maximal number of transactions, space for which is reserved in advance when the whole block is read.
+/// Number of transactions comes from untrusted source, so it can't be used to allocate memory.
+const MAX_PREALLOCATED_TRANSACTIONS: usize = 1024;
+
+/// Reads block from the `Read` source, one transaction at a time.
+///
+/// Header and transactions are hashed while they're read, so neither the serialized block, nor
+/// the serialized transactions are ever held in memory.
+pub struct BlockReader<R> {
+	reader: Reader<R>,
+	header: IndexedBlockHeader,
+	transactions_count: usize,
+	transactions_read: usize,
+}
+
+impl<R> BlockReader<R> where R: io::Read {
+	/// Reads block header and number of block transactions from the source. Source is read in
+	/// the strict mode, so non-canonical encodings are rejected.
+	pub fn new(read: R) -> Result<Self, ReaderError> {
+		BlockReader::from_reader(Reader::from_read(read).strict())
+	}
+
+	/// Reads block header and number of block transactions from the reader.
+	pub fn from_reader(mut reader: Reader<R>) -> Result<Self, ReaderError> {
+		let header = reader.read()?;
+		let transactions_count = read_transactions_count(&mut reader)?;
+
+		Ok(BlockReader {
+			reader: reader,
+			header: header,
+			transactions_count: transactions_count,
+			transactions_read: 0,
+		})
+	}
+
+	/// Header of the block.
+	pub fn header(&self) -> &IndexedBlockHeader {
+		&self.header
+	}
+
+	/// Number of block transactions, as it is declared in the block.
+	pub fn transactions_count(&self) -> usize {
+		self.transactions_count
+	}
+
+	/// Number of transactions that haven't been read yet.
+	pub fn remaining_transactions(&self) -> usize {
+		self.transactions_count - self.transactions_read
+	}
+
+	/// Reads the whole block from the reader. Unlike `BlockReader::new`, the reader isn't wrapped,
+	/// so if it is created over the shared buffer, block transactions may reference this buffer.
+	pub fn read_from(reader: &mut Reader<R>) -> Result<IndexedBlock, ReaderError> {
+		let header = reader.read()?;
+		let transactions_count = read_transactions_count(reader)?;
+		let transactions = read_transactions(reader, transactions_count)?;
+		Ok(IndexedBlock::new(header, transactions))
+	}
+
+	/// Read all remaining transactions and return the block.
+	pub fn read_block(mut self) -> Result<IndexedBlock, ReaderError> {
+		let remaining_transactions = self.remaining_transactions();
+		let transactions = read_transactions(&mut self.reader, remaining_transactions)?;
+		Ok(IndexedBlock::new(self.header, transactions))
+	}
+
+	/// Return the underlying reader. If not all transactions have been read, it is positioned
+	/// at the next transaction.
+	pub fn into_reader(self) -> Reader<R> {
+		self.reader
+	}
+}
+
+impl<R> Iterator for BlockReader<R> where R: io::Read {
+	type Item = Result<IndexedTransaction, ReaderError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.transactions_read == self.transactions_count {
+			return None;
+		}
+
+		let transaction = self.reader.read();
+		// position of the next transaction is unknown after failure => stop iteration
+		self.transactions_read = match transaction {
+			Ok(_) => self.transactions_read + 1,
+			Err(_) => self.transactions_count,
+		};
+		Some(transaction)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, Some(self.remaining_transactions()))
+	}
+}
+
+fn read_transactions_count<R>(reader: &mut Reader<R>) -> Result<usize, ReaderError> where R: io::Read {
+	let max_block_transactions = reader.limits().max_block_transactions;
+	reader.read_len(max_block_transactions, "max_block_transactions")
+}
+
+fn read_transactions<R>(reader: &mut Reader<R>, count: usize) -> Result<Vec<IndexedTransaction>, ReaderError> where R: io::Read {
+	let mut transactions = Vec::with_capacity(cmp::min(count, MAX_PREALLOCATED_TRANSACTIONS));
+	for _ in 0..count {
+		transactions.push(reader.read()?);
+	}
+	Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+	use bytes::SharedBytes;
+	use ser::{serialize, Reader, Limits, Error as ReaderError};
+	use block::Block;
+	use block_header::BlockHeader;
+	use indexed_block::IndexedBlock;
+	use transaction::Transaction;
+	use super::BlockReader;
+
+	fn block() -> Block {
+		let header = BlockHeader {
+			version: 4,
+			previous_header_hash: Default::default(),
+			merkle_root_hash: Default::default(),
+			final_sapling_root: Default::default(),
+			time: 0,
+			bits: 0.into(),
+			nonce: Default::default(),
+			solution: Default::default(),
+		};
+		let transactions = (0..3).map(|lock_time| Transaction { lock_time: lock_time, ..Default::default() }).collect();
+		Block::new(header, transactions)
+	}
+
+	#[test]
+	fn test_block_reader_yields_transactions() {
+		let block = block();
+		let expected = IndexedBlock::from_raw(block.clone());
+		let serialized = serialize(&block);
+
+		let mut reader = BlockReader::new(&serialized as &[u8]).unwrap();
+		assert_eq!(reader.header().hash, expected.header.hash);
+		assert_eq!(reader.transactions_count(), 3);
+
+		let first = reader.next().unwrap().unwrap();
+		assert_eq!(first.hash, expected.transactions[0].hash);
+		assert_eq!(reader.remaining_transactions(), 2);
+
+		let rest = reader.collect::<Result<Vec<_>, _>>().unwrap();
+		assert_eq!(rest.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>(),
+			expected.transactions[1..].iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_block_reader_read_block() {
+		let block = block();
+		let serialized = serialize(&block);
+		let read = BlockReader::new(&serialized as &[u8]).unwrap().read_block().unwrap();
+		assert_eq!(read.to_raw_block(), block);
+	}
+
+	#[test]
+	fn test_block_reader_read_from_shared_buffer() {
+		let block = block();
+		let serialized: SharedBytes = serialize(&block).into();
+		let mut reader = Reader::from_shared(serialized);
+		let read = BlockReader::read_from(&mut reader).unwrap();
+		assert_eq!(read.to_raw_block(), block);
+		assert!(reader.is_finished());
+	}
+
+	#[test]
+	fn test_block_reader_limits() {
+		let serialized = serialize(&block());
+
+		let reader = Reader::new(&serialized as &[u8]).with_limits(Limits { max_block_transactions: 2, ..Default::default() });
+		assert_eq!(BlockReader::from_reader(reader).err(), Some(ReaderError::LimitExceeded("max_block_transactions")));
+
+		let reader = Reader::new(&serialized as &[u8]).with_limits(Limits { max_block_transactions: 3, ..Default::default() });
+		assert_eq!(BlockReader::from_reader(reader).unwrap().read_block().unwrap().to_raw_block(), block());
+	}
+
+	#[test]
+	fn test_block_reader_truncated_block() {
+		let serialized = serialize(&block());
+		let truncated = &serialized[..serialized.len() - 1];
+
+		let mut reader = BlockReader::new(truncated).unwrap();
+		assert!(reader.next().unwrap().is_ok());
+		assert!(reader.next().unwrap().is_ok());
+		assert_eq!(reader.next().unwrap().unwrap_err(), ReaderError::UnexpectedEnd);
+		assert!(reader.next().is_none());
+
+		assert_eq!(BlockReader::new(truncated).unwrap().read_block().unwrap_err(), ReaderError::UnexpectedEnd);
+	}
+}
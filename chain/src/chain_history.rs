@@ -0,0 +1,368 @@
+//! Chain history tree, committed to by the headers of Heartwood and later blocks.
+//! https://zips.z.cash/zip-0221
+
+use std::io;
+use bigint::U256;
+use compact::Compact;
+use crypto::blake2b_personal;
+use hash::H256;
+use ser::{Serializable, Deserializable, Stream, Reader, Error as ReaderError, CompactInteger};
+use indexed_block::IndexedBlock;
+
+/// Orchard part of the history tree node. Only present in nodes of NU5 and later trees.
+#[derive(Debug, Clone, PartialEq, Serializable, Deserializable)]
+pub struct OrchardHistory {
+	/// Orchard note commitment tree root of the first block of the subtree.
+	pub earliest_root: H256,
+	/// Orchard note commitment tree root of the last block of the subtree.
+	pub latest_root: H256,
+	/// Number of transactions with Orchard actions in the subtree.
+	pub tx_count: u64,
+}
+
+/// Node of the chain history tree, summarizing blocks of its subtree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryNode {
+	/// Block hash for leaves, hash of children hashes for internal nodes.
+	pub subtree_commitment: H256,
+	pub earliest_timestamp: u32,
+	pub latest_timestamp: u32,
+	pub earliest_target: Compact,
+	pub latest_target: Compact,
+	/// Sapling note commitment tree root of the first block of the subtree.
+	pub earliest_sapling_root: H256,
+	/// Sapling note commitment tree root of the last block of the subtree.
+	pub latest_sapling_root: H256,
+	/// Sum of the work of all blocks of the subtree.
+	pub subtree_total_work: U256,
+	pub earliest_height: u32,
+	pub latest_height: u32,
+	/// Number of transactions with Sapling spends or outputs in the subtree.
+	pub sapling_tx_count: u64,
+	pub orchard: Option<OrchardHistory>,
+}
+
+impl HistoryNode {
+	/// Leaf node of the block. `sapling_root` and `orchard_root` are the roots of note commitment trees
+	/// after applying the block. Orchard root must be provided for blocks of NU5 and later trees.
+	pub fn leaf(block: &IndexedBlock, height: u32, sapling_root: H256, orchard_root: Option<H256>) -> Self {
+		let sapling_tx_count = block.transactions.iter()
+			.filter(|tx| tx.raw.sapling.as_ref().map(|sapling| !sapling.spends.is_empty() || !sapling.outputs.is_empty()).unwrap_or(false))
+			.count() as u64;
+
+		HistoryNode {
+			subtree_commitment: block.header.hash,
+			earliest_timestamp: block.header.raw.time,
+			latest_timestamp: block.header.raw.time,
+			earliest_target: block.header.raw.bits,
+			latest_target: block.header.raw.bits,
+			earliest_sapling_root: sapling_root,
+			latest_sapling_root: sapling_root,
+			subtree_total_work: block_work(block.header.raw.bits),
+			earliest_height: height,
+			latest_height: height,
+			sapling_tx_count: sapling_tx_count,
+			orchard: orchard_root.map(|orchard_root| OrchardHistory {
+				earliest_root: orchard_root,
+				latest_root: orchard_root,
+				tx_count: block.transactions.iter()
+					.filter(|tx| tx.raw.orchard.as_ref().map(|orchard| !orchard.actions.is_empty()).unwrap_or(false))
+					.count() as u64,
+			}),
+		}
+	}
+
+	/// Parent node of two adjacent subtrees.
+	pub fn combine(left: &HistoryNode, right: &HistoryNode, consensus_branch_id: u32) -> Self {
+		let mut children = Stream::default();
+		children
+			.append_slice(&*left.hash(consensus_branch_id))
+			.append_slice(&*right.hash(consensus_branch_id));
+
+		HistoryNode {
+			subtree_commitment: blake2b_personal(&personalization(consensus_branch_id), &children.out()),
+			earliest_timestamp: left.earliest_timestamp,
+			latest_timestamp: right.latest_timestamp,
+			earliest_target: left.earliest_target,
+			latest_target: right.latest_target,
+			earliest_sapling_root: left.earliest_sapling_root,
+			latest_sapling_root: right.latest_sapling_root,
+			subtree_total_work: left.subtree_total_work + right.subtree_total_work,
+			earliest_height: left.earliest_height,
+			latest_height: right.latest_height,
+			sapling_tx_count: left.sapling_tx_count + right.sapling_tx_count,
+			orchard: match (left.orchard.as_ref(), right.orchard.as_ref()) {
+				(Some(left), Some(right)) => Some(OrchardHistory {
+					earliest_root: left.earliest_root,
+					latest_root: right.latest_root,
+					tx_count: left.tx_count + right.tx_count,
+				}),
+				_ => None,
+			},
+		}
+	}
+
+	/// Number of blocks in the subtree.
+	pub fn leaves_count(&self) -> u32 {
+		self.latest_height - self.earliest_height + 1
+	}
+
+	/// Serialized node, as it is hashed.
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut total_work = [0u8; 32];
+		self.subtree_total_work.to_little_endian(&mut total_work);
+
+		let mut stream = Stream::default();
+		stream
+			.append(&self.subtree_commitment)
+			.append(&self.earliest_timestamp)
+			.append(&self.latest_timestamp)
+			.append(&self.earliest_target)
+			.append(&self.latest_target)
+			.append(&self.earliest_sapling_root)
+			.append(&self.latest_sapling_root)
+			.append_slice(&total_work)
+			.append(&CompactInteger::from(self.earliest_height))
+			.append(&CompactInteger::from(self.latest_height))
+			.append(&CompactInteger::from(self.sapling_tx_count));
+		if let Some(ref orchard) = self.orchard {
+			stream
+				.append(&orchard.earliest_root)
+				.append(&orchard.latest_root)
+				.append(&CompactInteger::from(orchard.tx_count));
+		}
+		stream.out().take()
+	}
+
+	/// Hash of the node in the tree of given consensus branch.
+	pub fn hash(&self, consensus_branch_id: u32) -> H256 {
+		blake2b_personal(&personalization(consensus_branch_id), &self.serialize())
+	}
+}
+
+impl Serializable for HistoryNode {
+	fn serialize(&self, stream: &mut Stream) {
+		let mut total_work = [0u8; 32];
+		self.subtree_total_work.to_little_endian(&mut total_work);
+
+		stream
+			.append(&self.subtree_commitment)
+			.append(&self.earliest_timestamp)
+			.append(&self.latest_timestamp)
+			.append(&self.earliest_target)
+			.append(&self.latest_target)
+			.append(&self.earliest_sapling_root)
+			.append(&self.latest_sapling_root)
+			.append_slice(&total_work)
+			.append(&self.earliest_height)
+			.append(&self.latest_height)
+			.append(&self.sapling_tx_count)
+			.append(&self.orchard);
+	}
+}
+
+impl Deserializable for HistoryNode {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let subtree_commitment = reader.read()?;
+		let earliest_timestamp = reader.read()?;
+		let latest_timestamp = reader.read()?;
+		let earliest_target = reader.read()?;
+		let latest_target = reader.read()?;
+		let earliest_sapling_root = reader.read()?;
+		let latest_sapling_root = reader.read()?;
+		let mut total_work = [0u8; 32];
+		reader.read_slice(&mut total_work)?;
+
+		Ok(HistoryNode {
+			subtree_commitment: subtree_commitment,
+			earliest_timestamp: earliest_timestamp,
+			latest_timestamp: latest_timestamp,
+			earliest_target: earliest_target,
+			latest_target: latest_target,
+			earliest_sapling_root: earliest_sapling_root,
+			latest_sapling_root: latest_sapling_root,
+			subtree_total_work: U256::from_little_endian(&total_work),
+			earliest_height: reader.read()?,
+			latest_height: reader.read()?,
+			sapling_tx_count: reader.read()?,
+			orchard: reader.read()?,
+		})
+	}
+}
+
+/// Merkle mountain range of blocks, mined since activation of the network upgrade.
+/// Only peaks (roots of perfect subtrees) are kept, which is enough to append blocks and compute the root.
+#[derive(Debug, Clone, PartialEq, Serializable, Deserializable)]
+pub struct HistoryTree {
+	consensus_branch_id: u32,
+	/// Peaks of the range, from the largest (leftmost) to the smallest (rightmost).
+	peaks: Vec<HistoryNode>,
+}
+
+impl HistoryTree {
+	/// Empty tree of given consensus branch.
+	pub fn new(consensus_branch_id: u32) -> Self {
+		HistoryTree {
+			consensus_branch_id: consensus_branch_id,
+			peaks: Vec::new(),
+		}
+	}
+
+	/// Consensus branch of the tree.
+	pub fn consensus_branch_id(&self) -> u32 {
+		self.consensus_branch_id
+	}
+
+	/// Number of blocks in the tree.
+	pub fn len(&self) -> u32 {
+		self.peaks.iter().map(HistoryNode::leaves_count).sum()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.peaks.is_empty()
+	}
+
+	/// Append leaf of the next block, merging equal-sized peaks.
+	pub fn append(&mut self, leaf: HistoryNode) {
+		let mut node = leaf;
+		while self.peaks.last().map(|peak| peak.leaves_count() == node.leaves_count()).unwrap_or(false) {
+			let left = self.peaks.pop().expect("checked in loop condition; qed");
+			node = HistoryNode::combine(&left, &node, self.consensus_branch_id);
+		}
+		self.peaks.push(node);
+	}
+
+	/// Root node of the tree: peaks, bagged from right to left. None if tree is empty.
+	pub fn root_node(&self) -> Option<HistoryNode> {
+		let mut peaks = self.peaks.iter().rev();
+		let last = peaks.next()?.clone();
+		Some(peaks.fold(last, |right, left| HistoryNode::combine(left, &right, self.consensus_branch_id)))
+	}
+
+	/// Root of the tree (hashChainHistoryRoot). All zeroes if tree is empty.
+	pub fn root(&self) -> H256 {
+		self.root_node()
+			.map(|root| root.hash(self.consensus_branch_id))
+			.unwrap_or_default()
+	}
+}
+
+/// Expected number of hashes, required to find block with given target: 2^256 / (target + 1).
+pub fn block_work(bits: Compact) -> U256 {
+	match bits.to_u256() {
+		// 2^256 doesn't fit into U256, but it is equal to (2^256 - target - 1) / (target + 1) + 1
+		Ok(target) => (!target / (target + U256::one())) + U256::one(),
+		Err(_) => U256::zero(),
+	}
+}
+
+fn personalization(consensus_branch_id: u32) -> [u8; 16] {
+	let mut personalization = [0u8; 16];
+	personalization[..12].copy_from_slice(b"ZcashHistory");
+	personalization[12] = consensus_branch_id as u8;
+	personalization[13] = (consensus_branch_id >> 8) as u8;
+	personalization[14] = (consensus_branch_id >> 16) as u8;
+	personalization[15] = (consensus_branch_id >> 24) as u8;
+	personalization
+}
+
+#[cfg(test)]
+mod tests {
+	use bigint::U256;
+	use block::Block;
+	use block_header::BlockHeader;
+	use compact::Compact;
+	use hash::H256;
+	use indexed_block::IndexedBlock;
+	use ser::{serialize, deserialize};
+	use super::{HistoryNode, HistoryTree, OrchardHistory, block_work};
+
+	const BRANCH_ID: u32 = 0xf5b9230b;
+
+	fn leaf(height: u32) -> HistoryNode {
+		let header = BlockHeader {
+			version: 4,
+			previous_header_hash: H256::from(height as u8),
+			merkle_root_hash: Default::default(),
+			final_sapling_root: Default::default(),
+			time: 1_000 + height,
+			bits: Compact::new(0x1f07ffff),
+			nonce: Default::default(),
+			solution: Default::default(),
+		};
+		let block = IndexedBlock::from_raw(Block::new(header, vec![Default::default()]));
+		HistoryNode::leaf(&block, height, H256::from(height as u8), None)
+	}
+
+	fn combine(left: &HistoryNode, right: &HistoryNode) -> HistoryNode {
+		HistoryNode::combine(left, right, BRANCH_ID)
+	}
+
+	#[test]
+	fn test_block_work() {
+		assert_eq!(block_work(Compact::new(0x1d00ffff)), U256::from(0x100010001u64));
+		assert_eq!(block_work(Compact::new(0x207fffff)), U256::from(2));
+	}
+
+	#[test]
+	fn test_history_node_combine() {
+		let (left, right) = (leaf(10), leaf(11));
+		let node = combine(&left, &right);
+		assert_eq!(node.earliest_height, 10);
+		assert_eq!(node.latest_height, 11);
+		assert_eq!(node.earliest_timestamp, 1_010);
+		assert_eq!(node.latest_timestamp, 1_011);
+		assert_eq!(node.latest_sapling_root, H256::from(11));
+		assert_eq!(node.subtree_total_work, left.subtree_total_work + right.subtree_total_work);
+		assert_eq!(node.leaves_count(), 2);
+		assert!(node.subtree_commitment != combine(&right, &left).subtree_commitment);
+	}
+
+	#[test]
+	fn test_history_node_serialize() {
+		// 32 (commitment) + 4 * 4 (timestamps, targets) + 2 * 32 (sapling roots) + 32 (work) + 3 * 1 (compact sizes)
+		assert_eq!(leaf(10).serialize().len(), 147);
+		assert_eq!(leaf(10).hash(BRANCH_ID).len(), 32);
+		assert!(leaf(10).hash(BRANCH_ID) != leaf(10).hash(0xe9ff75a6));
+	}
+
+	#[test]
+	fn test_history_tree_root() {
+		let leaves = (0..7).map(leaf).collect::<Vec<_>>();
+		let mut tree = HistoryTree::new(BRANCH_ID);
+		assert_eq!(tree.root(), H256::default());
+
+		tree.append(leaves[0].clone());
+		assert_eq!(tree.root(), leaves[0].hash(BRANCH_ID));
+
+		tree.append(leaves[1].clone());
+		tree.append(leaves[2].clone());
+		let ab = combine(&leaves[0], &leaves[1]);
+		assert_eq!(tree.root(), combine(&ab, &leaves[2]).hash(BRANCH_ID));
+
+		tree.append(leaves[3].clone());
+		let abcd = combine(&ab, &combine(&leaves[2], &leaves[3]));
+		assert_eq!(tree.root(), abcd.hash(BRANCH_ID));
+
+		// peaks are bagged from right to left
+		tree.append(leaves[4].clone());
+		tree.append(leaves[5].clone());
+		tree.append(leaves[6].clone());
+		let ef = combine(&leaves[4], &leaves[5]);
+		assert_eq!(tree.len(), 7);
+		assert_eq!(tree.root(), combine(&abcd, &combine(&ef, &leaves[6])).hash(BRANCH_ID));
+	}
+
+	#[test]
+	fn test_history_tree_serialization() {
+		let mut tree = HistoryTree::new(BRANCH_ID);
+		assert_eq!(deserialize::<_, HistoryTree>(serialize(&tree).as_ref()).unwrap(), tree);
+
+		let mut orchard_leaf = leaf(2);
+		orchard_leaf.orchard = Some(OrchardHistory { earliest_root: 1.into(), latest_root: 2.into(), tx_count: 3 });
+		tree.append(leaf(0));
+		tree.append(leaf(1));
+		tree.append(orchard_leaf);
+		assert_eq!(deserialize::<_, HistoryTree>(serialize(&tree).as_ref()).unwrap(), tree);
+	}
+}
@@ -1,14 +1,19 @@
-use std::cmp;
+use std::{cmp, io};
+use rayon::prelude::*;
 use hash::H256;
 use hex::FromHex;
-use ser::{Serializable, serialized_list_size, deserialize};
+use ser::{Serializable, Deserializable, Stream, Reader, Error as ReaderError, serialized_list_size, deserialize};
 use block::Block;
+use block_reader::BlockReader;
 use transaction::Transaction;
 use merkle_root::merkle_root;
 use indexed_header::IndexedBlockHeader;
 use indexed_transaction::IndexedTransaction;
 
-#[derive(Debug, Clone, Deserializable)]
+/// Minimal number of transactions in the block, starting from which transactions are hashed in parallel.
+pub const PARALLEL_HASHING_MIN_TRANSACTIONS: usize = 64;
+
+#[derive(Debug, Clone)]
 pub struct IndexedBlock {
 	pub header: IndexedBlockHeader,
 	pub transactions: Vec<IndexedTransaction>,
@@ -45,6 +50,21 @@ impl IndexedBlock {
 		)
 	}
 
+	/// Explicit conversion of the raw Block into IndexedBlock.
+	///
+	/// Same as `from_raw`, but transactions of large blocks are hashed in parallel.
+	pub fn from_raw_parallel(block: Block) -> Self {
+		if block.transactions.len() < PARALLEL_HASHING_MIN_TRANSACTIONS {
+			return Self::from_raw(block);
+		}
+
+		let Block { block_header, transactions } = block;
+		Self::new(
+			IndexedBlockHeader::from_raw(block_header),
+			transactions.into_par_iter().map(IndexedTransaction::from_raw).collect(),
+		)
+	}
+
 	pub fn hash(&self) -> &H256 {
 		&self.header.hash
 	}
@@ -69,8 +89,55 @@ impl IndexedBlock {
 	}
 }
 
+impl Serializable for IndexedBlock {
+	fn serialize(&self, stream: &mut Stream) {
+		let transactions = self.transactions.iter().map(|tx| &tx.raw).collect::<Vec<_>>();
+		stream
+			.append(&self.header.raw)
+			.append_list::<Transaction, &Transaction>(&transactions);
+	}
+}
+
+impl Deserializable for IndexedBlock {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		BlockReader::new(reader)?.read_block()
+	}
+}
+
 impl From<&'static str> for IndexedBlock {
 	fn from(s: &'static str) -> Self {
 		deserialize(&s.from_hex::<Vec<u8>>().unwrap() as &[u8]).unwrap()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use block::Block;
+	use block_header::BlockHeader;
+	use transaction::Transaction;
+	use super::{IndexedBlock, PARALLEL_HASHING_MIN_TRANSACTIONS};
+
+	#[test]
+	fn test_from_raw_parallel() {
+		let transactions = (0..PARALLEL_HASHING_MIN_TRANSACTIONS as u32 * 2)
+			.map(|lock_time| Transaction { lock_time: lock_time, ..Default::default() })
+			.collect();
+		let header = BlockHeader {
+			version: 4,
+			previous_header_hash: Default::default(),
+			merkle_root_hash: Default::default(),
+			final_sapling_root: Default::default(),
+			time: 0,
+			bits: 0.into(),
+			nonce: Default::default(),
+			solution: Default::default(),
+		};
+		let block = Block::new(header, transactions);
+
+		let serial = IndexedBlock::from_raw(block.clone());
+		let parallel = IndexedBlock::from_raw_parallel(block);
+		assert_eq!(serial.header.hash, parallel.header.hash);
+		assert_eq!(serial.transactions.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>(),
+			parallel.transactions.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>());
+	}
+}
@@ -60,9 +60,14 @@ impl Deserializable for IndexedTransaction {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
 		let data = try!(reader.read_and_hash::<Transaction>());
 		// TODO: use len
+		// hash of v5 transaction isn't a hash of its serialized form
+		let hash = match data.data.is_v5() {
+			true => transaction_hash(&data.data),
+			false => data.hash,
+		};
 		let tx = IndexedTransaction {
 			raw: data.data,
-			hash: data.hash,
+			hash: hash,
 		};
 
 		Ok(tx)
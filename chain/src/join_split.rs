@@ -18,16 +18,27 @@ pub struct JoinSplit {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct JoinSplitDescription {
+	#[cfg_attr(feature = "serde", serde(rename = "vpub_oldZat"))]
 	pub value_pub_old: u64,
+	#[cfg_attr(feature = "serde", serde(rename = "vpub_newZat"))]
 	pub value_pub_new: u64,
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_reversed"))]
 	pub anchor: [u8; 32],
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_reversed_pair"))]
 	pub nullifiers: [[u8; 32]; 2],
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_reversed_pair"))]
 	pub commitments: [[u8; 32]; 2],
+	#[cfg_attr(feature = "serde", serde(rename = "onetimePubKey", with = "::serde_helpers::hex_reversed"))]
 	pub ephemeral_key: [u8; 32],
+	#[cfg_attr(feature = "serde", serde(rename = "randomSeed", with = "::serde_helpers::hex_reversed"))]
 	pub random_seed: [u8; 32],
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_reversed_pair"))]
 	pub macs: [[u8; 32]; 2],
+	#[cfg_attr(feature = "serde", serde(rename = "proof"))]
 	pub zkproof: JoinSplitProof,
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_pair"))]
 	pub ciphertexts: [[u8; 601]; 2],
 }
 
@@ -128,6 +139,35 @@ impl PartialEq<JoinSplitProof> for JoinSplitProof {
 	}
 }
 
+/// Proof is serialized as hex string. Proof system is detected by the proof size when deserializing.
+#[cfg(feature = "serde")]
+impl ::serde_crate::Serialize for JoinSplitProof {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde_crate::Serializer {
+		match *self {
+			JoinSplitProof::PHGR(ref proof) => ::serde_helpers::hex_bytes::serialize(proof, serializer),
+			JoinSplitProof::Groth(ref proof) => ::serde_helpers::hex_bytes::serialize::<[u8; 192], _>(proof.into(), serializer),
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde_crate::Deserialize<'de> for JoinSplitProof {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde_crate::Deserializer<'de> {
+		use serde_crate::de::{Deserialize, Error};
+		use serde_helpers::ByteArray;
+		use hex::FromHex;
+
+		let s = String::deserialize(deserializer)?;
+		let proof: Vec<u8> = s.from_hex().map_err(|_| D::Error::custom(format!("invalid hex string: {}", s)))?;
+		if let Some(proof) = <[u8; 192]>::from_bytes(&proof) {
+			return Ok(JoinSplitProof::Groth(proof.into()));
+		}
+		<[u8; 296]>::from_bytes(&proof)
+			.map(JoinSplitProof::PHGR)
+			.ok_or_else(|| D::Error::invalid_length(proof.len(), &"PHGR or Groth16 proof"))
+	}
+}
+
 pub fn serialize_join_split(stream: &mut Stream, join_split: &Option<JoinSplit>) {
 	let len: CompactInteger = join_split.as_ref()
 		.map(|join_split| join_split.descriptions.len())
@@ -184,3 +224,58 @@ pub fn deserialize_join_split_description<T>(reader: &mut Reader<T>, use_groth:
 		ciphertexts: reader.read()?,
 	})
 }
+
+#[cfg(test)]
+mod tests {
+	use ser::{Stream, Reader};
+	use super::{JoinSplit, JoinSplitDescription, JoinSplitProof, serialize_join_split, deserialize_join_split};
+
+	fn round_trip(join_split: Option<JoinSplit>, use_groth: bool) -> Option<JoinSplit> {
+		let mut stream = Stream::default();
+		serialize_join_split(&mut stream, &join_split);
+		let bytes = stream.out();
+		let mut reader = Reader::new(bytes.as_ref());
+		let result = deserialize_join_split(&mut reader, use_groth).unwrap();
+		assert!(reader.is_finished());
+		result
+	}
+
+	#[test]
+	fn test_empty_join_split() {
+		let mut stream = Stream::default();
+		serialize_join_split(&mut stream, &None);
+		assert_eq!(stream.out().as_ref(), &[0u8]);
+		assert_eq!(round_trip(Some(JoinSplit::default()), true), None);
+	}
+
+	#[test]
+	fn test_join_split_round_trip() {
+		let description = JoinSplitDescription {
+			value_pub_old: 10,
+			value_pub_new: 20,
+			nullifiers: [[1; 32], [2; 32]],
+			commitments: [[3; 32], [4; 32]],
+			ciphertexts: [[5; 601], [6; 601]],
+			..Default::default()
+		};
+
+		// Sapling (v4) transactions are using Groth16 proofs
+		let join_split = Some(JoinSplit {
+			descriptions: vec![description.clone(), description.clone()],
+			pubkey: 7.into(),
+			sig: 8.into(),
+		});
+		assert_eq!(round_trip(join_split.clone(), true), join_split);
+
+		// Sprout (v2) and Overwinter (v3) transactions are using PHGR proofs
+		let join_split = Some(JoinSplit {
+			descriptions: vec![JoinSplitDescription {
+				zkproof: JoinSplitProof::PHGR([9; 296]),
+				..description
+			}],
+			pubkey: 7.into(),
+			sig: 8.into(),
+		});
+		assert_eq!(round_trip(join_split.clone(), false), join_split);
+	}
+}
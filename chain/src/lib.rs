@@ -1,20 +1,38 @@
 extern crate rustc_hex as hex;
 extern crate heapsize;
+extern crate rayon;
+extern crate bit_vec;
 extern crate primitives;
 extern crate bitcrypto as crypto;
 extern crate serialization as ser;
 #[macro_use]
 extern crate serialization_derive;
+#[cfg(feature = "serde")]
+extern crate serde_crate;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 pub mod constants;
 
+mod amount;
 mod block;
+mod block_commitment;
 mod block_header;
+mod block_reader;
+mod chain_history;
 mod solution;
 mod join_split;
 mod merkle_root;
+mod partial_merkle_tree;
+mod orchard;
 mod sapling;
 mod transaction;
+mod transaction_digest;
+#[cfg(feature = "serde")]
+mod serde_helpers;
 
 /// `IndexedBlock` extension
 mod read_and_hash;
@@ -24,16 +42,23 @@ mod indexed_transaction;
 
 pub use primitives::{hash, bytes, bigint, compact};
 
-pub use transaction::{BTC_TX_VERSION, SPROUT_TX_VERSION, OVERWINTER_TX_VERSION, SAPLING_TX_VERSION};
-pub use transaction::{OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID};
+pub use transaction::{BTC_TX_VERSION, SPROUT_TX_VERSION, OVERWINTER_TX_VERSION, SAPLING_TX_VERSION, NU5_TX_VERSION};
+pub use transaction::{OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID, NU5_TX_VERSION_GROUP_ID};
 
+pub use amount::{Amount, MAX_MONEY};
 pub use block::Block;
 pub use block_header::BlockHeader;
+pub use block_reader::BlockReader;
+pub use block_commitment::{BlockCommitment, auth_data_root, block_commitments_hash};
+pub use chain_history::{HistoryNode, HistoryTree, OrchardHistory, block_work};
 pub use solution::EquihashSolution;
 pub use join_split::{JoinSplit, JoinSplitDescription, JoinSplitProof};
 pub use merkle_root::{merkle_root, merkle_node_hash};
+pub use partial_merkle_tree::{PartialMerkleTree, ParsedPartialMerkleTree, build_partial_merkle_tree, parse_partial_merkle_tree};
 pub use sapling::{Sapling, SaplingSpendDescription, SaplingOutputDescription};
-pub use transaction::{Transaction, TransactionInput, TransactionOutput, OutPoint};
+pub use orchard::{Orchard, OrchardAction, ORCHARD_FLAG_SPENDS_ENABLED, ORCHARD_FLAG_OUTPUTS_ENABLED};
+pub use transaction_digest::{transaction_id_digest, transaction_auth_digest};
+pub use transaction::{Transaction, TransactionInput, TransactionOutput, OutPoint, fee_rate};
 
 pub use read_and_hash::{ReadAndHash, HashedData};
 pub use indexed_block::IndexedBlock;
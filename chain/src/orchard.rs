@@ -0,0 +1,230 @@
+//! Orchard shielded protocol, introduced by NU5.
+//! https://zips.z.cash/zip-0224
+//! https://zips.z.cash/zip-0225
+
+use std::{fmt, io};
+use hex::ToHex;
+use bytes::SharedBytes;
+use ser::{Error, Stream, Reader, CompactInteger};
+
+/// Spends are enabled flag.
+pub const ORCHARD_FLAG_SPENDS_ENABLED: u8 = 0b00000001;
+/// Outputs are enabled flag.
+pub const ORCHARD_FLAG_OUTPUTS_ENABLED: u8 = 0b00000010;
+
+/// Orchard bundle of the v5 transaction.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct Orchard {
+	/// Orchard actions. Every action spends one note and creates another one.
+	pub actions: Vec<OrchardAction>,
+	/// Flags of the bundle.
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::orchard_flags"))]
+	pub flags: u8,
+	/// The net value of Orchard spends minus outputs.
+	#[cfg_attr(feature = "serde", serde(rename = "valueBalanceZat"))]
+	pub value_balance: i64,
+	/// A root of the Orchard note commitment tree at some block height in the past.
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_bytes"))]
+	pub anchor: [u8; 32],
+	/// Encoding of the aggregated zk-SNARK proof for all actions. When transaction is read
+	/// from the shared buffer (p2p message), proof isn't copied out of this buffer.
+	pub proof: SharedBytes,
+	/// Orchard binding signature on the transaction sighash.
+	#[cfg_attr(feature = "serde", serde(rename = "bindingSig", with = "::serde_helpers::hex_bytes"))]
+	pub binding_sig: [u8; 64],
+}
+
+/// Single Orchard action description.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct OrchardAction {
+	/// Value commitment to the input note value minus the output note value.
+	#[cfg_attr(feature = "serde", serde(rename = "cv", with = "::serde_helpers::hex_bytes"))]
+	pub value_commitment: [u8; 32],
+	/// The nullifier for the input note.
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_bytes"))]
+	pub nullifier: [u8; 32],
+	/// Randomized public key that should be used to verify spend_auth_sig.
+	#[cfg_attr(feature = "serde", serde(rename = "rk", with = "::serde_helpers::hex_bytes"))]
+	pub randomized_key: [u8; 32],
+	/// The x-coordinate of the note commitment for the output note.
+	#[cfg_attr(feature = "serde", serde(rename = "cmx", with = "::serde_helpers::hex_bytes"))]
+	pub note_commitment: [u8; 32],
+	/// Key agreement public key, used to derive the key for encryption of the transmitted
+	/// note ciphertext.
+	#[cfg_attr(feature = "serde", serde(rename = "ephemeralKey", with = "::serde_helpers::hex_bytes"))]
+	pub ephemeral_key: [u8; 32],
+	/// Ciphertext component for the encrypted output note.
+	#[cfg_attr(feature = "serde", serde(rename = "encCiphertext", with = "::serde_helpers::hex_bytes"))]
+	pub enc_cipher_text: [u8; 580],
+	/// Ciphertext component that allows the holder of a full viewing key to recover the recipient
+	/// diversified transmission key and the ephemeral private key.
+	#[cfg_attr(feature = "serde", serde(rename = "outCiphertext", with = "::serde_helpers::hex_bytes"))]
+	pub out_cipher_text: [u8; 80],
+	/// Spend authorization signature.
+	#[cfg_attr(feature = "serde", serde(rename = "spendAuthSig", with = "::serde_helpers::hex_bytes"))]
+	pub spend_auth_sig: [u8; 64],
+}
+
+impl Orchard {
+	/// Returns true if spends are enabled in this bundle.
+	pub fn spends_enabled(&self) -> bool {
+		self.flags & ORCHARD_FLAG_SPENDS_ENABLED != 0
+	}
+
+	/// Returns true if outputs are enabled in this bundle.
+	pub fn outputs_enabled(&self) -> bool {
+		self.flags & ORCHARD_FLAG_OUTPUTS_ENABLED != 0
+	}
+}
+
+impl Default for Orchard {
+	fn default() -> Self {
+		Orchard {
+			actions: Default::default(),
+			flags: Default::default(),
+			value_balance: Default::default(),
+			anchor: Default::default(),
+			proof: Default::default(),
+			binding_sig: [0; 64],
+		}
+	}
+}
+
+impl fmt::Debug for Orchard {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Orchard")
+			.field("actions", &self.actions)
+			.field("flags", &self.flags)
+			.field("value_balance", &self.value_balance)
+			.field("anchor", &self.anchor.to_hex::<String>())
+			.field("proof", &self.proof)
+			.field("binding_sig", &self.binding_sig.to_hex::<String>())
+			.finish()
+	}
+}
+
+impl PartialEq<Orchard> for Orchard {
+	fn eq(&self, other: &Orchard) -> bool {
+		self.actions == other.actions
+			&& self.flags == other.flags
+			&& self.value_balance == other.value_balance
+			&& self.anchor == other.anchor
+			&& self.proof == other.proof
+			&& self.binding_sig.as_ref() == other.binding_sig.as_ref()
+	}
+}
+
+impl Default for OrchardAction {
+	fn default() -> Self {
+		OrchardAction {
+			value_commitment: Default::default(),
+			nullifier: Default::default(),
+			randomized_key: Default::default(),
+			note_commitment: Default::default(),
+			ephemeral_key: Default::default(),
+			enc_cipher_text: [0; 580],
+			out_cipher_text: [0; 80],
+			spend_auth_sig: [0; 64],
+		}
+	}
+}
+
+impl fmt::Debug for OrchardAction {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("OrchardAction")
+			.field("value_commitment", &self.value_commitment.to_hex::<String>())
+			.field("nullifier", &self.nullifier.to_hex::<String>())
+			.field("randomized_key", &self.randomized_key.to_hex::<String>())
+			.field("note_commitment", &self.note_commitment.to_hex::<String>())
+			.field("ephemeral_key", &self.ephemeral_key.to_hex::<String>())
+			.field("enc_cipher_text", &self.enc_cipher_text.to_hex::<String>())
+			.field("out_cipher_text", &self.out_cipher_text.to_hex::<String>())
+			.field("spend_auth_sig", &self.spend_auth_sig.to_hex::<String>())
+			.finish()
+	}
+}
+
+impl PartialEq<OrchardAction> for OrchardAction {
+	fn eq(&self, other: &OrchardAction) -> bool {
+		self.value_commitment == other.value_commitment
+			&& self.nullifier == other.nullifier
+			&& self.randomized_key == other.randomized_key
+			&& self.note_commitment == other.note_commitment
+			&& self.ephemeral_key == other.ephemeral_key
+			&& self.enc_cipher_text.as_ref() == other.enc_cipher_text.as_ref()
+			&& self.out_cipher_text.as_ref() == other.out_cipher_text.as_ref()
+			&& self.spend_auth_sig.as_ref() == other.spend_auth_sig.as_ref()
+	}
+}
+
+pub fn serialize_orchard(stream: &mut Stream, orchard: &Option<Orchard>) {
+	let len: CompactInteger = orchard.as_ref()
+		.map(|orchard| orchard.actions.len())
+		.unwrap_or_default()
+		.into();
+	stream.append(&len);
+
+	if let &Some(ref orchard) = orchard {
+		if !orchard.actions.is_empty() {
+			for action in &orchard.actions {
+				stream
+					.append(&action.value_commitment)
+					.append(&action.nullifier)
+					.append(&action.randomized_key)
+					.append(&action.note_commitment)
+					.append(&action.ephemeral_key)
+					.append(&action.enc_cipher_text)
+					.append(&action.out_cipher_text);
+			}
+			stream
+				.append(&orchard.flags)
+				.append(&orchard.value_balance)
+				.append(&orchard.anchor)
+				.append(&orchard.proof);
+			for action in &orchard.actions {
+				stream.append(&action.spend_auth_sig);
+			}
+			stream.append(&orchard.binding_sig);
+		}
+	}
+}
+
+pub fn deserialize_orchard<T>(reader: &mut Reader<T>) -> Result<Option<Orchard>, Error> where T: io::Read {
+	let len: usize = reader.read::<CompactInteger>()?.into();
+	if len == 0 {
+		return Ok(None);
+	}
+
+	let mut actions = (0..len)
+		.map(|_| Ok(OrchardAction {
+			value_commitment: reader.read()?,
+			nullifier: reader.read()?,
+			randomized_key: reader.read()?,
+			note_commitment: reader.read()?,
+			ephemeral_key: reader.read()?,
+			enc_cipher_text: reader.read()?,
+			out_cipher_text: reader.read()?,
+			spend_auth_sig: [0; 64],
+		}))
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	let flags = reader.read()?;
+	let value_balance = reader.read()?;
+	let anchor = reader.read()?;
+	let proof = reader.read()?;
+	for action in &mut actions {
+		action.spend_auth_sig = reader.read()?;
+	}
+	let binding_sig = reader.read()?;
+
+	Ok(Some(Orchard {
+		actions,
+		flags,
+		value_balance,
+		anchor,
+		proof,
+		binding_sig,
+	}))
+}
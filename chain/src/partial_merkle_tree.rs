@@ -1,9 +1,11 @@
 use std::cmp::min;
 use bit_vec::BitVec;
-use chain::merkle_node_hash;
-use primitives::hash::H256;
+use hash::H256;
+use merkle_root::merkle_node_hash;
 
-/// Partial merkle tree
+/// Partial merkle tree, used to prove that transactions are included in the block.
+/// https://github.com/bitcoin/bips/blob/master/bip-0037.mediawiki#partial-merkle-branch-format
+#[derive(Debug, Clone, PartialEq)]
 pub struct PartialMerkleTree {
 	/// Total number of transactions
 	pub tx_count: usize,
@@ -14,13 +16,13 @@ pub struct PartialMerkleTree {
 }
 
 /// Partial merkle tree parse result
-#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParsedPartialMerkleTree {
 	/// Merkle root
 	pub root: H256,
 	/// Matched hashes
 	pub hashes: Vec<H256>,
-	/// Match flags
+	/// Match flags of all transactions
 	pub flags: BitVec,
 }
 
@@ -29,8 +31,8 @@ pub fn build_partial_merkle_tree(tx_hashes: Vec<H256>, tx_matches: BitVec) -> Pa
 	PartialMerkleTreeBuilder::build(tx_hashes, tx_matches)
 }
 
-/// Parse partial merkle tree
-#[cfg(test)]
+/// Parse partial merkle tree, extracting matched hashes and computing merkle root.
+/// Root must be compared with the block header merkle root by the caller.
 pub fn parse_partial_merkle_tree(tree: PartialMerkleTree) -> Result<ParsedPartialMerkleTree, String> {
 	PartialMerkleTreeBuilder::parse(tree)
 }
@@ -58,9 +60,28 @@ impl PartialMerkleTree {
 			flags: flags,
 		}
 	}
+
+	/// Create new merkle tree with flags, serialized as in `merkleblock` message.
+	pub fn from_flag_bytes(tx_count: usize, hashes: Vec<H256>, flag_bytes: &[u8]) -> Self {
+		let flags = (0..flag_bytes.len() * 8)
+			.map(|bit| (flag_bytes[bit / 8] >> (bit % 8)) & 1 == 1)
+			.collect();
+		PartialMerkleTree::new(tx_count, hashes, flags)
+	}
+
+	/// Flags, serialized as in `merkleblock` message: first flag is the least significant bit of the
+	/// first byte (while `BitVec::to_bytes` puts it into the most significant bit).
+	pub fn flag_bytes(&self) -> Vec<u8> {
+		let mut bytes = vec![0u8; (self.flags.len() + 7) / 8];
+		for (bit, flag) in self.flags.iter().enumerate() {
+			if flag {
+				bytes[bit / 8] |= 1 << (bit % 8);
+			}
+		}
+		bytes
+	}
 }
 
-#[cfg(test)]
 impl ParsedPartialMerkleTree {
 	pub fn new(root: H256, hashes: Vec<H256>, flags: BitVec) -> Self {
 		ParsedPartialMerkleTree {
@@ -86,7 +107,6 @@ impl PartialMerkleTreeBuilder {
 		PartialMerkleTree::new(partial_merkle_tree.all_len, partial_merkle_tree.hashes, partial_merkle_tree.matches)
 	}
 
-	#[cfg(test)]
 	/// Parse partial merkle tree as described here:
 	/// https://bitcoin.org/en/developer-reference#parsing-a-merkleblock-message
 	pub fn parse(tree: PartialMerkleTree) -> Result<ParsedPartialMerkleTree, String> {
@@ -107,7 +127,6 @@ impl PartialMerkleTreeBuilder {
 		self.build_branch(tree_height, 0)
 	}
 
-	#[cfg(test)]
 	fn parse_tree(&mut self) -> Result<H256, String> {
 		if self.all_len == 0 {
 			return Err("no transactions".into());
@@ -125,7 +144,8 @@ impl PartialMerkleTreeBuilder {
 		let tree_height = self.tree_height();
 		let merkle_root = try!(self.parse_branch(tree_height, 0, &mut matches_used, &mut hashes_used));
 
-		if matches_used != self.matches.len() {
+		// flags are padded to the whole number of bytes in merkleblock message
+		if (matches_used + 7) / 8 != (self.matches.len() + 7) / 8 {
 			return Err("not all matches used".into());
 		}
 		if hashes_used != self.hashes.len() {
@@ -157,7 +177,6 @@ impl PartialMerkleTreeBuilder {
 		}
 	}
 
-	#[cfg(test)]
 	fn parse_branch(&mut self, height: usize, pos: usize, matches_used: &mut usize, hashes_used: &mut usize) -> Result<H256, String> {
 		if *matches_used >= self.matches.len() {
 			return Err("all matches used".into());
@@ -168,7 +187,7 @@ impl PartialMerkleTreeBuilder {
 
 		if height == 0 || !flag {
 			// we're at the leaf level || there is no match
-			if *hashes_used > self.hashes.len() {
+			if *hashes_used >= self.hashes.len() {
 				return Err("all hashes used".into());
 			}
 
@@ -232,28 +251,34 @@ impl PartialMerkleTreeBuilder {
 
 #[cfg(test)]
 mod tests {
-	extern crate test_data;
-
-	use chain::{Transaction, merkle_root};
-	use primitives::hash::H256;
-	use super::{build_partial_merkle_tree, parse_partial_merkle_tree};
+	use bit_vec::BitVec;
+	use crypto::dhash256;
+	use hash::H256;
+	use merkle_root::merkle_root;
+	use super::{PartialMerkleTree, build_partial_merkle_tree, parse_partial_merkle_tree};
+
+	fn test_hashes(count: usize) -> Vec<H256> {
+		(0..count).map(|n| dhash256(&[n as u8, (n >> 8) as u8])).collect()
+	}
 
 	#[test]
 	// test from core implementation (slow)
 	// https://github.com/bitcoin/bitcoin/blob/master/src/test/pmt_tests.cpp
 	fn test_build_merkle_block() {
-		use bit_vec::BitVec;
-		use rand::{Rng, SeedableRng, StdRng};
-
-		let rng_seed: &[_] = &[0, 0, 0, 0];
-		let mut rng: StdRng = SeedableRng::from_seed(rng_seed);
+		// xorshift, so that matches are the same on every run
+		let mut rng_state = 0x2545f491u32;
+		let mut next_random = move || {
+			rng_state ^= rng_state << 13;
+			rng_state ^= rng_state >> 17;
+			rng_state ^= rng_state << 5;
+			rng_state
+		};
 
 		// for some transactions counts
 		let tx_counts: Vec<usize> = vec![1, 4, 7, 17, 56, 100, 127, 256, 312, 513, 1000, 4095];
 		for tx_count in tx_counts {
 			// build block with given transactions number
-			let transactions: Vec<Transaction> = (0..tx_count).map(|n| test_data::TransactionBuilder::with_version(n as i32).into()).collect();
-			let hashes: Vec<_> = transactions.iter().map(|t| t.hash()).collect();
+			let hashes = test_hashes(tx_count);
 			let merkle_root = merkle_root(&hashes);
 
 			// mark different transactions as matched
@@ -261,7 +286,7 @@ mod tests {
 				let mut matches: BitVec = BitVec::with_capacity(tx_count);
 				let mut matched_hashes: Vec<H256> = Vec::with_capacity(tx_count);
 				for i in 0usize..tx_count {
-					let is_match = (rng.gen::<u32>() & ((1 << (seed_tweak / 2)) - 1)) == 0;
+					let is_match = (next_random() & ((1 << (seed_tweak / 2)) - 1)) == 0;
 					matches.push(is_match);
 					if is_match {
 						matched_hashes.push(hashes[i].clone());
@@ -270,6 +295,8 @@ mod tests {
 
 				// build partial merkle tree
 				let partial_tree = build_partial_merkle_tree(hashes.clone(), matches.clone());
+				// serialize flags as in merkleblock message
+				let partial_tree = PartialMerkleTree::from_flag_bytes(partial_tree.tx_count, partial_tree.hashes.clone(), &partial_tree.flag_bytes());
 				// parse tree back
 				let parsed_tree = parse_partial_merkle_tree(partial_tree).expect("no error");
 
@@ -279,4 +306,34 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn test_partial_merkle_tree_flag_bytes() {
+		let tree = PartialMerkleTree::new(3, vec![], [true, false, true, true, false, false, false, false, true].iter().cloned().collect());
+		assert_eq!(tree.flag_bytes(), vec![0b00001101, 0b00000001]);
+		let restored = PartialMerkleTree::from_flag_bytes(3, vec![], &tree.flag_bytes());
+		// padding bits are restored as unset flags
+		assert_eq!(restored.flags.len(), 16);
+		assert_eq!(restored.flag_bytes(), tree.flag_bytes());
+	}
+
+	#[test]
+	fn test_parse_malformed_partial_merkle_tree() {
+		let hashes = test_hashes(4);
+		let matches: BitVec = [false, true, false, false].iter().cloned().collect();
+		let tree = build_partial_merkle_tree(hashes, matches);
+
+		// missing hash
+		let mut truncated = tree.clone();
+		truncated.hashes.pop();
+		assert!(parse_partial_merkle_tree(truncated).is_err());
+
+		// extra hash
+		let mut extended = tree.clone();
+		extended.hashes.push(H256::default());
+		assert!(parse_partial_merkle_tree(extended).is_err());
+
+		// no transactions
+		assert!(parse_partial_merkle_tree(PartialMerkleTree::new(0, vec![], BitVec::new())).is_err());
+	}
 }
@@ -1,5 +1,6 @@
-use std::fmt;
+use std::{fmt, io};
 use hex::ToHex;
+use ser::{Error, Stream, Reader, CompactInteger};
 
 /// 
 #[derive(Clone)]
@@ -34,43 +35,57 @@ pub struct Sapling {
 
 /// Single Spend transfer description.
 #[derive(Clone, Serializable, Deserializable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct SaplingSpendDescription {
 	/// Value commitment to the value of the input note.
+	#[cfg_attr(feature = "serde", serde(rename = "cv", with = "::serde_helpers::hex_reversed"))]
 	pub value_commitment: [u8; 32],
 	/// An anchor for the output treestate of a previous block.
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_reversed"))]
 	pub anchor: [u8; 32],
 	/// The nullifier for the input note.
+	#[cfg_attr(feature = "serde", serde(with = "::serde_helpers::hex_reversed"))]
 	pub nullifier: [u8; 32],
 	/// Randomized public key that should be used to verify spend_auth_sig.
+	#[cfg_attr(feature = "serde", serde(rename = "rk", with = "::serde_helpers::hex_reversed"))]
 	pub randomized_key: [u8; 32],
 	/// Zero-knowledge proof with primary input
 	///   (value_commitment, anchor, nullifier, randomized_key)
 	/// for the spend statement.
+	#[cfg_attr(feature = "serde", serde(rename = "proof", with = "::serde_helpers::hex_bytes"))]
 	pub zkproof: [u8; 192],
 	/// Spend authorization signature. Is used to prove knowledge of the spending key
 	/// authorizing spending of an input note.
+	#[cfg_attr(feature = "serde", serde(rename = "spendAuthSig", with = "::serde_helpers::hex_bytes"))]
 	pub spend_auth_sig: [u8; 64],
 }
 
 /// Single Output transfer description.
 #[derive(Clone, Serializable, Deserializable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct SaplingOutputDescription {
 	/// Value commitment to the value of the output note.
+	#[cfg_attr(feature = "serde", serde(rename = "cv", with = "::serde_helpers::hex_reversed"))]
 	pub value_commitment: [u8; 32],
 	/// The note commitment for the output note.
+	#[cfg_attr(feature = "serde", serde(rename = "cmu", with = "::serde_helpers::hex_reversed"))]
 	pub note_commitment: [u8; 32],
 	/// Key agreement public key, used to derive the key for encryption of the transmitted
 	/// note ciphertext.
+	#[cfg_attr(feature = "serde", serde(rename = "ephemeralKey", with = "::serde_helpers::hex_reversed"))]
 	pub ephemeral_key: [u8; 32],
 	/// Ciphertext component for the encrypted output note.
+	#[cfg_attr(feature = "serde", serde(rename = "encCiphertext", with = "::serde_helpers::hex_bytes"))]
 	pub enc_cipher_text: [u8; 580],
 	/// Ciphertext component that allows the holder of a full viewing key to recover the recipient
 	/// diversified transmission key and the ephemeral private key (and therefore the entire note
 	/// plaintext).
+	#[cfg_attr(feature = "serde", serde(rename = "outCiphertext", with = "::serde_helpers::hex_bytes"))]
 	pub out_cipher_text: [u8; 80],
 	/// Zero-knowledge proof with primary input
 	///   (value_commitment, cm, ephemeral_key)
 	/// for the output statement.
+	#[cfg_attr(feature = "serde", serde(rename = "proof", with = "::serde_helpers::hex_bytes"))]
 	pub zkproof: [u8; 192],
 }
 
@@ -157,7 +172,7 @@ impl Default for SaplingOutputDescription {
 
 impl fmt::Debug for SaplingOutputDescription {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		f.debug_struct("SaplingSpendDescription")
+		f.debug_struct("SaplingOutputDescription")
 			.field("value_commitment", &self.value_commitment.to_hex::<String>())
 			.field("note_commitment", &self.note_commitment.to_hex::<String>())
 			.field("ephemeral_key", &self.ephemeral_key.to_hex::<String>())
@@ -178,3 +193,101 @@ impl PartialEq<SaplingOutputDescription> for SaplingOutputDescription {
 			&& self.zkproof.as_ref() == other.zkproof.as_ref()
 	}
 }
+
+/// Serialize Sapling part of the v5 transaction.
+/// https://zips.z.cash/zip-0225
+pub fn serialize_sapling_v5(stream: &mut Stream, sapling: &Option<Sapling>) {
+	let empty = Sapling::default();
+	let sapling = sapling.as_ref().unwrap_or(&empty);
+
+	stream.append(&CompactInteger::from(sapling.spends.len()));
+	for spend in &sapling.spends {
+		stream
+			.append(&spend.value_commitment)
+			.append(&spend.nullifier)
+			.append(&spend.randomized_key);
+	}
+	stream.append(&CompactInteger::from(sapling.outputs.len()));
+	for output in &sapling.outputs {
+		stream
+			.append(&output.value_commitment)
+			.append(&output.note_commitment)
+			.append(&output.ephemeral_key)
+			.append(&output.enc_cipher_text)
+			.append(&output.out_cipher_text);
+	}
+
+	if sapling.spends.is_empty() && sapling.outputs.is_empty() {
+		return;
+	}
+
+	stream.append(&sapling.balancing_value);
+	// all spends of v5 transaction share the same anchor
+	if let Some(spend) = sapling.spends.first() {
+		stream.append(&spend.anchor);
+	}
+	for spend in &sapling.spends {
+		stream.append(&spend.zkproof);
+	}
+	for spend in &sapling.spends {
+		stream.append(&spend.spend_auth_sig);
+	}
+	for output in &sapling.outputs {
+		stream.append(&output.zkproof);
+	}
+	stream.append(&sapling.binding_sig);
+}
+
+/// Deserialize Sapling part of the v5 transaction.
+/// https://zips.z.cash/zip-0225
+pub fn deserialize_sapling_v5<T>(reader: &mut Reader<T>) -> Result<Sapling, Error> where T: io::Read {
+	let spends_len: usize = reader.read::<CompactInteger>()?.into();
+	let mut spends = (0..spends_len)
+		.map(|_| Ok(SaplingSpendDescription {
+			value_commitment: reader.read()?,
+			nullifier: reader.read()?,
+			randomized_key: reader.read()?,
+			..Default::default()
+		}))
+		.collect::<Result<Vec<_>, Error>>()?;
+	let outputs_len: usize = reader.read::<CompactInteger>()?.into();
+	let mut outputs = (0..outputs_len)
+		.map(|_| Ok(SaplingOutputDescription {
+			value_commitment: reader.read()?,
+			note_commitment: reader.read()?,
+			ephemeral_key: reader.read()?,
+			enc_cipher_text: reader.read()?,
+			out_cipher_text: reader.read()?,
+			..Default::default()
+		}))
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	if spends.is_empty() && outputs.is_empty() {
+		return Ok(Sapling::default());
+	}
+
+	let balancing_value = reader.read()?;
+	if !spends.is_empty() {
+		let anchor: [u8; 32] = reader.read()?;
+		for spend in &mut spends {
+			spend.anchor = anchor;
+		}
+	}
+	for spend in &mut spends {
+		spend.zkproof = reader.read()?;
+	}
+	for spend in &mut spends {
+		spend.spend_auth_sig = reader.read()?;
+	}
+	for output in &mut outputs {
+		output.zkproof = reader.read()?;
+	}
+	let binding_sig = reader.read()?;
+
+	Ok(Sapling {
+		balancing_value,
+		spends,
+		outputs,
+		binding_sig,
+	})
+}
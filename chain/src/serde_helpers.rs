@@ -0,0 +1,286 @@
+//! Helpers for the JSON representation of chain types. Field names and encodings are the same
+//! as in the output of zcashd `getrawtransaction` && `getblock` RPCs.
+
+use hex::{ToHex, FromHex};
+use serde_crate::{Serialize, Serializer, Deserialize, Deserializer};
+use serde_crate::de::Error;
+
+/// Fixed-size byte array, serialized as hex string.
+pub trait ByteArray: Sized {
+	fn as_bytes(&self) -> &[u8];
+
+	fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_byte_array {
+	($($size: expr),*) => {
+		$(
+			impl ByteArray for [u8; $size] {
+				fn as_bytes(&self) -> &[u8] {
+					self
+				}
+
+				fn from_bytes(bytes: &[u8]) -> Option<Self> {
+					if bytes.len() != $size {
+						return None;
+					}
+
+					let mut array = [0u8; $size];
+					array.copy_from_slice(bytes);
+					Some(array)
+				}
+			}
+		)*
+	}
+}
+
+impl_byte_array!(32, 64, 80, 192, 296, 580, 601);
+
+fn to_hex<T: ByteArray>(value: &T, reversed: bool) -> String {
+	if reversed {
+		let mut bytes = value.as_bytes().to_vec();
+		bytes.reverse();
+		bytes.to_hex()
+	} else {
+		value.as_bytes().to_hex()
+	}
+}
+
+fn from_hex<T: ByteArray, E: Error>(s: &str, reversed: bool) -> Result<T, E> {
+	let mut bytes: Vec<u8> = s.from_hex().map_err(|_| E::custom(format!("invalid hex string: {}", s)))?;
+	if reversed {
+		bytes.reverse();
+	}
+	T::from_bytes(&bytes).ok_or_else(|| E::invalid_length(bytes.len(), &"fixed-size byte array"))
+}
+
+/// Byte array, serialized as hex string in the original byte order.
+pub mod hex_bytes {
+	use super::*;
+
+	pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error> where T: ByteArray, S: Serializer {
+		serializer.serialize_str(&to_hex(value, false))
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error> where T: ByteArray, D: Deserializer<'de> {
+		from_hex(&String::deserialize(deserializer)?, false)
+	}
+}
+
+/// Byte array, holding uint256 value. zcashd displays such values in reversed byte order.
+pub mod hex_reversed {
+	use super::*;
+
+	pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error> where T: ByteArray, S: Serializer {
+		serializer.serialize_str(&to_hex(value, true))
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error> where T: ByteArray, D: Deserializer<'de> {
+		from_hex(&String::deserialize(deserializer)?, true)
+	}
+}
+
+/// Pair of byte arrays, serialized as list of hex strings in the original byte order.
+pub mod hex_pair {
+	use super::*;
+
+	pub fn serialize<T, S>(value: &[T; 2], serializer: S) -> Result<S::Ok, S::Error> where T: ByteArray, S: Serializer {
+		[to_hex(&value[0], false), to_hex(&value[1], false)].serialize(serializer)
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<[T; 2], D::Error> where T: ByteArray, D: Deserializer<'de> {
+		let pair = <[String; 2]>::deserialize(deserializer)?;
+		Ok([from_hex(&pair[0], false)?, from_hex(&pair[1], false)?])
+	}
+}
+
+/// Pair of uint256 values, serialized as list of hex strings in reversed byte order.
+pub mod hex_reversed_pair {
+	use super::*;
+
+	pub fn serialize<T, S>(value: &[T; 2], serializer: S) -> Result<S::Ok, S::Error> where T: ByteArray, S: Serializer {
+		[to_hex(&value[0], true), to_hex(&value[1], true)].serialize(serializer)
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<[T; 2], D::Error> where T: ByteArray, D: Deserializer<'de> {
+		let pair = <[String; 2]>::deserialize(deserializer)?;
+		Ok([from_hex(&pair[0], true)?, from_hex(&pair[1], true)?])
+	}
+}
+
+/// 32-bit integer, serialized as hex string (like `versiongroupid`).
+pub mod hex_u32 {
+	use super::*;
+
+	pub fn serialize<S>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		serializer.serialize_str(&format!("{:08x}", value))
+	}
+
+	pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error> where D: Deserializer<'de> {
+		let s = String::deserialize(deserializer)?;
+		u32::from_str_radix(&s, 16).map_err(|_| D::Error::custom(format!("invalid hex integer: {}", s)))
+	}
+}
+
+/// Script, serialized as object with `hex` field. Other fields of zcashd script object (`asm`, `type`, ...)
+/// are ignored when deserializing.
+pub mod script {
+	use bytes::Bytes;
+	use super::*;
+
+	#[derive(Serialize)]
+	#[serde(crate = "serde_crate")]
+	struct ScriptRef<'a> {
+		hex: &'a Bytes,
+	}
+
+	#[derive(Deserialize)]
+	#[serde(crate = "serde_crate")]
+	struct Script {
+		hex: Bytes,
+	}
+
+	pub fn serialize<S>(value: &Bytes, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		ScriptRef { hex: value }.serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D>(deserializer: D) -> Result<Bytes, D::Error> where D: Deserializer<'de> {
+		Script::deserialize(deserializer).map(|script| script.hex)
+	}
+}
+
+/// Orchard bundle flags, serialized as object with `enableSpends` && `enableOutputs` fields.
+pub mod orchard_flags {
+	use orchard::{ORCHARD_FLAG_SPENDS_ENABLED, ORCHARD_FLAG_OUTPUTS_ENABLED};
+	use super::*;
+
+	#[derive(Serialize, Deserialize)]
+	#[serde(crate = "serde_crate")]
+	struct Flags {
+		#[serde(rename = "enableSpends")]
+		enable_spends: bool,
+		#[serde(rename = "enableOutputs")]
+		enable_outputs: bool,
+	}
+
+	pub fn serialize<S>(value: &u8, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		Flags {
+			enable_spends: value & ORCHARD_FLAG_SPENDS_ENABLED != 0,
+			enable_outputs: value & ORCHARD_FLAG_OUTPUTS_ENABLED != 0,
+		}.serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D>(deserializer: D) -> Result<u8, D::Error> where D: Deserializer<'de> {
+		let flags = Flags::deserialize(deserializer)?;
+		let mut value = 0;
+		if flags.enable_spends {
+			value |= ORCHARD_FLAG_SPENDS_ENABLED;
+		}
+		if flags.enable_outputs {
+			value |= ORCHARD_FLAG_OUTPUTS_ENABLED;
+		}
+		Ok(value)
+	}
+}
+
+pub fn is_zero(value: &u32) -> bool {
+	*value == 0
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use bytes::Bytes;
+	use compact::Compact;
+	use hash::H256;
+	use join_split::{JoinSplit, JoinSplitDescription};
+	use sapling::{Sapling, SaplingSpendDescription};
+	use transaction::{Transaction, TransactionInput, TransactionOutput, OutPoint, SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID};
+	use {Block, BlockHeader};
+
+	fn sapling_transaction() -> Transaction {
+		Transaction {
+			overwintered: true,
+			version: SAPLING_TX_VERSION,
+			version_group_id: SAPLING_TX_VERSION_GROUP_ID,
+			inputs: vec![TransactionInput {
+				previous_output: OutPoint { hash: H256::from(1), index: 2 },
+				script_sig: "0102".into(),
+				sequence: 3,
+			}],
+			outputs: vec![TransactionOutput { value: 50, script_pubkey: "76a9".into() }],
+			lock_time: 4,
+			expiry_height: 5,
+			join_split: Some(JoinSplit {
+				descriptions: vec![JoinSplitDescription { value_pub_old: 6, ..Default::default() }],
+				pubkey: H256::from(7),
+				sig: 8.into(),
+			}),
+			sapling: Some(Sapling {
+				balancing_value: -9,
+				spends: vec![SaplingSpendDescription { anchor: [10; 32], ..Default::default() }],
+				outputs: vec![],
+				binding_sig: [11; 64],
+			}),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_transaction_json_fields() {
+		let json = serde_json::to_value(&sapling_transaction()).unwrap();
+		assert_eq!(json["versiongroupid"], "892f2085");
+		assert_eq!(json["locktime"], 4);
+		assert_eq!(json["expiryheight"], 5);
+		assert_eq!(json["vin"][0]["txid"], "0000000000000000000000000000000000000000000000000000000000000001");
+		assert_eq!(json["vin"][0]["vout"], 2);
+		assert_eq!(json["vin"][0]["scriptSig"]["hex"], "0102");
+		assert_eq!(json["vout"][0]["valueZat"], 50);
+		assert_eq!(json["vout"][0]["scriptPubKey"]["hex"], "76a9");
+		assert_eq!(json["vjoinsplit"][0]["vpub_oldZat"], 6);
+		assert_eq!(json["joinSplitPubKey"], "0700000000000000000000000000000000000000000000000000000000000000");
+		assert_eq!(json["valueBalanceZat"], -9);
+		assert_eq!(json["vShieldedSpend"][0]["anchor"], "0a".repeat(32));
+		assert_eq!(json["vShieldedOutput"].as_array().unwrap().len(), 0);
+		assert_eq!(json["bindingSig"], "0b".repeat(64));
+		assert!(json.get("consensusbranchid").is_none());
+		assert!(json.get("orchard").is_none());
+	}
+
+	#[test]
+	fn test_transaction_json_roundtrip() {
+		let transaction = sapling_transaction();
+		let json = serde_json::to_string(&transaction).unwrap();
+		assert_eq!(serde_json::from_str::<Transaction>(&json).unwrap(), transaction);
+
+		let legacy = Transaction {
+			version: 1,
+			inputs: vec![TransactionInput::coinbase(Bytes::from("04ffff001d"))],
+			..Default::default()
+		};
+		let json = serde_json::to_value(&legacy).unwrap();
+		assert!(json.get("versiongroupid").is_none());
+		assert!(json.get("vjoinsplit").is_none());
+		assert_eq!(serde_json::from_value::<Transaction>(json).unwrap(), legacy);
+	}
+
+	#[test]
+	fn test_block_json() {
+		let block = Block::new(BlockHeader {
+			version: 4,
+			previous_header_hash: H256::from(1),
+			merkle_root_hash: H256::from(2),
+			final_sapling_root: H256::from(3),
+			time: 1_000,
+			bits: Compact::new(0x1f07ffff),
+			nonce: H256::from(4),
+			solution: Default::default(),
+		}, vec![sapling_transaction()]);
+
+		let json = serde_json::to_value(&block).unwrap();
+		assert_eq!(json["previousblockhash"], "0000000000000000000000000000000000000000000000000000000000000001");
+		assert_eq!(json["bits"], "1f07ffff");
+		assert_eq!(json["tx"][0]["version"], 4);
+		assert_eq!(serde_json::from_value::<Block>(json).unwrap(), block);
+	}
+}
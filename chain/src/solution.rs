@@ -2,11 +2,13 @@ use std::{fmt, io};
 use hex::ToHex;
 use ser::{Error, Serializable, Deserializable, Stream, Reader};
 
-/// Equihash solution size.
+/// Equihash solution size (for N=200, K=9 parameters, used by mainnet && testnet).
 pub const SOLUTION_SIZE: usize = 1344;
 
+/// Equihash solution. Its size depends on equihash parameters of the chain and is checked
+/// during header verification. `SOLUTION_SIZE` is the largest size supported.
 #[derive(Clone)]
-pub struct EquihashSolution([u8; SOLUTION_SIZE]);
+pub struct EquihashSolution(Vec<u8>);
 
 impl AsRef<[u8]> for EquihashSolution {
 	fn as_ref(&self) -> &[u8] {
@@ -16,13 +18,13 @@ impl AsRef<[u8]> for EquihashSolution {
 
 impl Default for EquihashSolution {
 	fn default() -> Self {
-		EquihashSolution([0; SOLUTION_SIZE])
+		EquihashSolution(vec![0; SOLUTION_SIZE])
 	}
 }
 
 impl PartialEq<EquihashSolution> for EquihashSolution {
 	fn eq(&self, other: &EquihashSolution) -> bool {
-		self.0.as_ref() == other.0.as_ref()
+		self.0 == other.0
 	}
 }
 
@@ -40,9 +42,29 @@ impl Serializable for EquihashSolution {
 
 impl Deserializable for EquihashSolution {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where Self: Sized, T: io::Read {
-		let v = reader.read_list_exact(SOLUTION_SIZE)?;
-		let mut sol = [0; SOLUTION_SIZE];
-		sol.copy_from_slice(&v);
-		Ok(EquihashSolution(sol))
+		let max_solution_size = reader.limits().max_solution_size;
+		reader.read_list_limited(max_solution_size, "max_solution_size").map(EquihashSolution)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl ::serde_crate::Serialize for EquihashSolution {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde_crate::Serializer {
+		serializer.serialize_str(&self.0.to_hex::<String>())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde_crate::Deserialize<'de> for EquihashSolution {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde_crate::Deserializer<'de> {
+		use serde_crate::de::{Deserialize, Error};
+		use hex::FromHex;
+
+		let s = String::deserialize(deserializer)?;
+		let solution: Vec<u8> = s.from_hex().map_err(|_| D::Error::custom(format!("invalid hex string: {}", s)))?;
+		if solution.len() > SOLUTION_SIZE {
+			return Err(D::Error::invalid_length(solution.len(), &"equihash solution"));
+		}
+		Ok(EquihashSolution(solution))
 	}
 }
@@ -10,8 +10,16 @@ use crypto::dhash256;
 use hash::H256;
 use constants::{SEQUENCE_FINAL, LOCKTIME_THRESHOLD};
 use join_split::{JoinSplit, deserialize_join_split, serialize_join_split};
-use sapling::Sapling;
+use sapling::{Sapling, deserialize_sapling_v5, serialize_sapling_v5};
+use orchard::{Orchard, deserialize_orchard, serialize_orchard};
+use transaction_digest::transaction_id_digest;
 use ser::{Error, Serializable, Deserializable, Stream, Reader};
+#[cfg(feature = "serde")]
+use hash::H512;
+#[cfg(feature = "serde")]
+use join_split::JoinSplitDescription;
+#[cfg(feature = "serde")]
+use sapling::{SaplingSpendDescription, SaplingOutputDescription};
 
 /// Original bitcoin transaction version.
 pub const BTC_TX_VERSION: i32 = 1;
@@ -21,15 +29,22 @@ pub const SPROUT_TX_VERSION: i32 = 2;
 pub const OVERWINTER_TX_VERSION: i32 = 3;
 /// Sapling-era transaction version.
 pub const SAPLING_TX_VERSION: i32 = 4;
+/// NU5-era transaction version.
+pub const NU5_TX_VERSION: i32 = 5;
 
 /// Overwinter version group id.
 pub const OVERWINTER_TX_VERSION_GROUP_ID: u32 = 0x03C48270;
 /// Sapling version group id.
 pub const SAPLING_TX_VERSION_GROUP_ID: u32 = 0x892F2085;
+/// NU5 version group id.
+pub const NU5_TX_VERSION_GROUP_ID: u32 = 0x26A7270A;
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serializable, Deserializable, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct OutPoint {
+	#[cfg_attr(feature = "serde", serde(rename = "txid"))]
 	pub hash: H256,
+	#[cfg_attr(feature = "serde", serde(rename = "vout"))]
 	pub index: u32,
 }
 
@@ -47,8 +62,11 @@ impl OutPoint {
 }
 
 #[derive(Debug, PartialEq, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct TransactionInput {
+	#[cfg_attr(feature = "serde", serde(flatten))]
 	pub previous_output: OutPoint,
+	#[cfg_attr(feature = "serde", serde(rename = "scriptSig", with = "::serde_helpers::script"))]
 	pub script_sig: Bytes,
 	pub sequence: u32,
 }
@@ -73,9 +91,12 @@ impl HeapSizeOf for TransactionInput {
 	}
 }
 
-#[derive(Debug, PartialEq, Clone, Serializable, Deserializable)]
+#[derive(Debug, PartialEq, Clone, Serializable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
 pub struct TransactionOutput {
+	#[cfg_attr(feature = "serde", serde(rename = "valueZat"))]
 	pub value: u64,
+	#[cfg_attr(feature = "serde", serde(rename = "scriptPubKey", with = "::serde_helpers::script"))]
 	pub script_pubkey: Bytes,
 }
 
@@ -99,12 +120,15 @@ pub struct Transaction {
 	pub overwintered: bool,
 	pub version: i32,
 	pub version_group_id: u32,
+	/// Consensus branch id the transaction is valid for. Only serialized in v5 transactions.
+	pub consensus_branch_id: u32,
 	pub inputs: Vec<TransactionInput>,
 	pub outputs: Vec<TransactionOutput>,
 	pub lock_time: u32,
 	pub expiry_height: u32,
 	pub join_split: Option<JoinSplit>,
 	pub sapling: Option<Sapling>,
+	pub orchard: Option<Orchard>,
 }
 
 impl From<&'static str> for Transaction {
@@ -129,6 +153,11 @@ impl Transaction {
 		version
 	}
 
+	/// Returns true if transaction has v5 format (ZIP 225).
+	pub fn is_v5(&self) -> bool {
+		self.overwintered && self.version == NU5_TX_VERSION
+	}
+
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn hash(&self) -> H256 {
 		transaction_hash(self)
@@ -182,6 +211,45 @@ impl Transaction {
 		self.inputs.iter().all(TransactionInput::is_final)
 	}
 
+	/// Serialized size of the transaction. All size-based limits && fees are computed from this size.
+	pub fn size(&self) -> usize {
+		self.serialized_size()
+	}
+
+	/// Feerate (in zatoshis per 1000 bytes) of the transaction, paying given fee.
+	pub fn fee_rate(&self, fee: u64) -> u64 {
+		fee_rate(fee, self.size())
+	}
+
+	/// Minimal fee, the transaction must pay to have given feerate (in zatoshis per 1000 bytes).
+	/// Rounded up, so that `fee_rate(minimum_fee(rate)) >= rate`.
+	pub fn minimum_fee(&self, fee_rate: u64) -> u64 {
+		(fee_rate.saturating_mul(self.size() as u64) + 999) / 1000
+	}
+
+	/// Nullifiers, revealed by Sprout join split descriptions of the transaction.
+	pub fn sprout_nullifiers(&self) -> Vec<H256> {
+		self.join_split.as_ref()
+			.map(|join_split| join_split.descriptions.iter()
+				.flat_map(|description| description.nullifiers.iter().map(|nullifier| H256::from(&nullifier[..])))
+				.collect())
+			.unwrap_or_default()
+	}
+
+	/// Nullifiers, revealed by Sapling spends of the transaction.
+	pub fn sapling_nullifiers(&self) -> Vec<H256> {
+		self.sapling.as_ref()
+			.map(|sapling| sapling.spends.iter().map(|spend| H256::from(&spend.nullifier[..])).collect())
+			.unwrap_or_default()
+	}
+
+	/// Nullifiers, revealed by Orchard actions of the transaction.
+	pub fn orchard_nullifiers(&self) -> Vec<H256> {
+		self.orchard.as_ref()
+			.map(|orchard| orchard.actions.iter().map(|action| H256::from(&action.nullifier[..])).collect())
+			.unwrap_or_default()
+	}
+
 	pub fn total_spends(&self) -> u64 {
 		let mut result = 0u64;
 		for output in self.outputs.iter() {
@@ -194,6 +262,14 @@ impl Transaction {
 	}
 }
 
+/// Feerate (in zatoshis per 1000 bytes) of transaction with given fee && serialized size.
+pub fn fee_rate(fee: u64, size: usize) -> u64 {
+	match size {
+		0 => 0,
+		size => fee.saturating_mul(1000) / size as u64,
+	}
+}
+
 impl Serializable for TransactionInput {
 	fn serialize(&self, stream: &mut Stream) {
 		stream
@@ -207,14 +283,53 @@ impl Deserializable for TransactionInput {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where Self: Sized, T: io::Read {
 		Ok(TransactionInput {
 			previous_output: reader.read()?,
-			script_sig: reader.read()?,
+			script_sig: read_script(reader)?,
 			sequence: reader.read()?,
 		})
 	}
 }
 
+impl Deserializable for TransactionOutput {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where Self: Sized, T: io::Read {
+		Ok(TransactionOutput {
+			value: reader.read()?,
+			script_pubkey: read_script(reader)?,
+		})
+	}
+}
+
+fn read_script<T>(reader: &mut Reader<T>) -> Result<Bytes, Error> where T: io::Read {
+	let max_script_size = reader.limits().max_script_size;
+	let len = reader.read_len(max_script_size, "max_script_size")?;
+	reader.read_bytes(len)
+}
+
+fn read_inputs<T>(reader: &mut Reader<T>) -> Result<Vec<TransactionInput>, Error> where T: io::Read {
+	let max_inputs = reader.limits().max_transaction_inputs;
+	reader.read_list_limited(max_inputs, "max_transaction_inputs")
+}
+
+fn read_outputs<T>(reader: &mut Reader<T>) -> Result<Vec<TransactionOutput>, Error> where T: io::Read {
+	let max_outputs = reader.limits().max_transaction_outputs;
+	reader.read_list_limited(max_outputs, "max_transaction_outputs")
+}
+
 impl Serializable for Transaction {
 	fn serialize(&self, stream: &mut Stream) {
+		if self.is_v5() {
+			stream
+				.append(&self.serialized_version())
+				.append(&self.version_group_id)
+				.append(&self.consensus_branch_id)
+				.append(&self.lock_time)
+				.append(&self.expiry_height)
+				.append_list(&self.inputs)
+				.append_list(&self.outputs);
+			serialize_sapling_v5(stream, &self.sapling);
+			serialize_orchard(stream, &self.orchard);
+			return;
+		}
+
 		stream.append(&self.serialized_version());
 		if self.overwintered {
 			stream.append(&self.version_group_id);
@@ -258,7 +373,12 @@ impl Deserializable for Transaction {
 		// overwintered, version (3), version group, inputs, outputs, lock_time, expiry height, joint split
 		//
 		// sapling format:
-		// overwintered, version (4), version group, inputs, outputs, lock_time, expiry height, joint split
+		// overwintered, version (4), version group, inputs, outputs, lock_time, expiry height, value balance,
+		// shielded spends, shielded outputs, joint split, binding sig (only if there are spends or outputs)
+		//
+		// nu5 format (ZIP 225):
+		// overwintered, version (5), version group, consensus branch id, lock_time, expiry height,
+		// inputs, outputs, sapling bundle, orchard bundle
 
 		let version: u32 = reader.read()?;
 		let overwintered = (version & 0x80000000) != 0;
@@ -273,13 +393,38 @@ impl Deserializable for Transaction {
 		// reject overwintered transactions of unknown versions
 		let is_overwinter_tx = overwintered && version == OVERWINTER_TX_VERSION && version_group_id == OVERWINTER_TX_VERSION_GROUP_ID;
 		let is_sapling_tx = overwintered && version == SAPLING_TX_VERSION && version_group_id == SAPLING_TX_VERSION_GROUP_ID;
-		if overwintered && !is_overwinter_tx && !is_sapling_tx {
+		let is_nu5_tx = overwintered && version == NU5_TX_VERSION && version_group_id == NU5_TX_VERSION_GROUP_ID;
+		if overwintered && !is_overwinter_tx && !is_sapling_tx && !is_nu5_tx {
 			return Err(Error::InvalidFormat(format!("Invalid overwinter transaction version: {}, version group: {}",
 				version, version_group_id)));
 		}
 
-		let inputs: Vec<TransactionInput> = reader.read_list()?;
-		let outputs = reader.read_list()?;
+		if is_nu5_tx {
+			let consensus_branch_id = reader.read()?;
+			let lock_time = reader.read()?;
+			let expiry_height = reader.read()?;
+			let inputs = read_inputs(reader)?;
+			let outputs = read_outputs(reader)?;
+			let sapling = deserialize_sapling_v5(reader)?;
+			let orchard = deserialize_orchard(reader)?;
+
+			return Ok(Transaction {
+				overwintered,
+				version,
+				version_group_id,
+				consensus_branch_id,
+				inputs,
+				outputs,
+				lock_time,
+				expiry_height,
+				join_split: None,
+				sapling: Some(sapling),
+				orchard,
+			});
+		}
+
+		let inputs = read_inputs(reader)?;
+		let outputs = read_outputs(reader)?;
 		let lock_time = reader.read()?;
 
 		let expiry_height = if is_overwinter_tx || is_sapling_tx {
@@ -319,26 +464,180 @@ impl Deserializable for Transaction {
 			overwintered,
 			version,
 			version_group_id,
+			consensus_branch_id: 0,
 			inputs,
 			outputs,
 			lock_time,
 			expiry_height,
 			join_split,
 			sapling,
+			orchard: None,
 		})
 	}
 }
 
+/// Transaction, as it is represented in the zcashd JSON. Shielded bundles are flattened into
+/// the transaction object.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_crate")]
+struct TransactionJson {
+	overwintered: bool,
+	version: i32,
+	#[serde(rename = "versiongroupid", with = "::serde_helpers::hex_u32", default, skip_serializing_if = "::serde_helpers::is_zero")]
+	version_group_id: u32,
+	#[serde(rename = "consensusbranchid", with = "::serde_helpers::hex_u32", default, skip_serializing_if = "::serde_helpers::is_zero")]
+	consensus_branch_id: u32,
+	#[serde(rename = "locktime")]
+	lock_time: u32,
+	#[serde(rename = "expiryheight", default)]
+	expiry_height: u32,
+	vin: Vec<TransactionInput>,
+	vout: Vec<TransactionOutput>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	vjoinsplit: Option<Vec<JoinSplitDescription>>,
+	#[serde(rename = "joinSplitPubKey", default, skip_serializing_if = "Option::is_none")]
+	join_split_pubkey: Option<Bytes>,
+	#[serde(rename = "joinSplitSig", default, skip_serializing_if = "Option::is_none")]
+	join_split_sig: Option<H512>,
+	#[serde(rename = "valueBalanceZat", default, skip_serializing_if = "Option::is_none")]
+	value_balance: Option<i64>,
+	#[serde(rename = "vShieldedSpend", default, skip_serializing_if = "Option::is_none")]
+	shielded_spends: Option<Vec<SaplingSpendDescription>>,
+	#[serde(rename = "vShieldedOutput", default, skip_serializing_if = "Option::is_none")]
+	shielded_outputs: Option<Vec<SaplingOutputDescription>>,
+	#[serde(rename = "bindingSig", default, skip_serializing_if = "Option::is_none")]
+	binding_sig: Option<Bytes>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	orchard: Option<Orchard>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> From<&'a Transaction> for TransactionJson {
+	fn from(transaction: &'a Transaction) -> Self {
+		let join_split = transaction.join_split.as_ref();
+		let sapling = transaction.sapling.as_ref();
+		let has_join_split = transaction.version >= SPROUT_TX_VERSION && !transaction.is_v5();
+		TransactionJson {
+			overwintered: transaction.overwintered,
+			version: transaction.version,
+			version_group_id: transaction.version_group_id,
+			consensus_branch_id: if transaction.is_v5() { transaction.consensus_branch_id } else { 0 },
+			lock_time: transaction.lock_time,
+			expiry_height: transaction.expiry_height,
+			vin: transaction.inputs.clone(),
+			vout: transaction.outputs.clone(),
+			vjoinsplit: if has_join_split {
+				Some(join_split.map(|join_split| join_split.descriptions.clone()).unwrap_or_default())
+			} else {
+				None
+			},
+			join_split_pubkey: join_split.map(|join_split| Bytes::from(&join_split.pubkey[..])),
+			join_split_sig: join_split.map(|join_split| join_split.sig.clone()),
+			value_balance: sapling.map(|sapling| sapling.balancing_value),
+			shielded_spends: sapling.map(|sapling| sapling.spends.clone()),
+			shielded_outputs: sapling.map(|sapling| sapling.outputs.clone()),
+			binding_sig: sapling
+				.filter(|sapling| !sapling.spends.is_empty() || !sapling.outputs.is_empty())
+				.map(|sapling| Bytes::from(&sapling.binding_sig[..])),
+			orchard: transaction.orchard.clone(),
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+impl TransactionJson {
+	fn into_transaction(self) -> Result<Transaction, String> {
+		let join_split = match self.vjoinsplit {
+			Some(ref descriptions) if !descriptions.is_empty() => {
+				let pubkey = match self.join_split_pubkey {
+					Some(ref pubkey) if pubkey.len() == 32 => H256::from(&pubkey[..]),
+					Some(_) => return Err("joinSplitPubKey must be 32 bytes long".into()),
+					None => return Err("joinSplitPubKey is required when vjoinsplit is not empty".into()),
+				};
+				let sig = self.join_split_sig.ok_or_else(|| "joinSplitSig is required when vjoinsplit is not empty".to_owned())?;
+				Some(JoinSplit {
+					descriptions: descriptions.clone(),
+					pubkey: pubkey,
+					sig: sig,
+				})
+			},
+			_ => None,
+		};
+
+		let sapling = match self.value_balance {
+			Some(balancing_value) => {
+				let mut binding_sig = [0u8; 64];
+				match self.binding_sig {
+					Some(ref sig) if sig.len() == 64 => binding_sig.copy_from_slice(sig),
+					Some(_) => return Err("bindingSig must be 64 bytes long".into()),
+					None => (),
+				}
+				Some(Sapling {
+					balancing_value: balancing_value,
+					spends: self.shielded_spends.unwrap_or_default(),
+					outputs: self.shielded_outputs.unwrap_or_default(),
+					binding_sig: binding_sig,
+				})
+			},
+			None => None,
+		};
+
+		Ok(Transaction {
+			overwintered: self.overwintered,
+			version: self.version,
+			version_group_id: self.version_group_id,
+			consensus_branch_id: self.consensus_branch_id,
+			inputs: self.vin,
+			outputs: self.vout,
+			lock_time: self.lock_time,
+			expiry_height: self.expiry_height,
+			join_split: join_split,
+			sapling: sapling,
+			orchard: self.orchard,
+		})
+	}
+}
+
+#[cfg(feature = "serde")]
+impl ::serde_crate::Serialize for Transaction {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde_crate::Serializer {
+		::serde_crate::Serialize::serialize(&TransactionJson::from(self), serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde_crate::Deserialize<'de> for Transaction {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde_crate::Deserializer<'de> {
+		use serde_crate::de::{Deserialize, Error};
+
+		TransactionJson::deserialize(deserializer)?
+			.into_transaction()
+			.map_err(D::Error::custom)
+	}
+}
+
 pub(crate) fn transaction_hash(transaction: &Transaction) -> H256 {
+	// v5 transactions are identified by non-malleable ZIP 244 digest
+	if transaction.is_v5() {
+		return transaction_id_digest(transaction);
+	}
+
 	dhash256(&serialize(transaction))
 }
 
 #[cfg(test)]
 mod tests {
-	use hex::ToHex;
+	use hex::{ToHex, FromHex};
 	use hash::H256;
-	use ser::{Serializable, serialize};
-	use super::Transaction;
+	use ser::{Serializable, Reader, Limits, Error, serialize, deserialize};
+	use indexed_transaction::IndexedTransaction;
+	use join_split::{JoinSplit, JoinSplitDescription, JoinSplitProof};
+	use orchard::{Orchard, OrchardAction, ORCHARD_FLAG_SPENDS_ENABLED};
+	use sapling::{Sapling, SaplingSpendDescription, SaplingOutputDescription};
+	use transaction_digest::{transaction_id_digest, transaction_auth_digest};
+	use super::{Transaction, TransactionInput, TransactionOutput, OVERWINTER_TX_VERSION_GROUP_ID,
+		NU5_TX_VERSION, NU5_TX_VERSION_GROUP_ID};
 
 	// real transaction from Zcash block 30003
 	// https://zcash.blockexplorer.com/api/rawtx/54c8acf69271dad83e9faa34284cda725caa5bea7378db92acf35becd0989463
@@ -393,8 +692,14 @@ mod tests {
 		assert_eq!(t.expiry_height, 0);
 		assert_eq!(t.inputs.len(), 1);
 		assert_eq!(t.outputs.len(), 0);
-		assert!(t.join_split.is_some());
 		assert!(t.sapling.is_none());
+		{
+			let join_split = t.join_split.as_ref().unwrap();
+			assert_eq!(join_split.descriptions.len(), 1);
+			assert_eq!(join_split.descriptions[0].value_pub_old, 14250000);
+			assert_eq!(join_split.descriptions[0].value_pub_new, 0);
+			assert!(match join_split.descriptions[0].zkproof { JoinSplitProof::PHGR(_) => true, _ => false });
+		}
 
 		// serialize && check tx
 		let t: String = serialize(&t).to_hex();
@@ -436,6 +741,109 @@ mod tests {
 		assert!(t.sapling.is_some());
 		assert_eq!(t.sapling.as_ref().unwrap().spends.len(), 1);
 		assert_eq!(t.sapling.as_ref().unwrap().outputs.len(), 1);
+
+		// serialize && check tx
+		let t: String = serialize(&t).to_hex();
+		assert_eq!(t, hex);
+	}
+
+	#[test]
+	fn test_overwinter_transaction() {
+		let hex = concat!(
+			"03000080",		// overwintered version 3
+			"7082c403",		// version group id
+			"01", "0000000000000000000000000000000000000000000000000000000000000000ffffffff", "020101", "ffffffff",
+			"01", "00e1f50500000000", "0151",
+			"00000000",		// lock time
+			"64000000",		// expiry height
+			"00",			// join splits
+		);
+
+		// deserialize && check tx
+		let t: Transaction = hex.into();
+		assert_eq!(t.overwintered, true);
+		assert_eq!(t.version, 3);
+		assert_eq!(t.version_group_id, OVERWINTER_TX_VERSION_GROUP_ID);
+		assert_eq!(t.inputs.len(), 1);
+		assert_eq!(t.outputs.len(), 1);
+		assert_eq!(t.lock_time, 0);
+		assert_eq!(t.expiry_height, 100);
+		assert!(t.sapling.is_none());
+
+		// serialize && check tx
+		let t: String = serialize(&t).to_hex();
+		assert_eq!(t, hex);
+
+		// version 3 with sapling version group id
+		let raw: Vec<u8> = hex.replace("7082c403", "85202f89").from_hex().unwrap();
+		assert!(deserialize::<_, Transaction>(&raw as &[u8]).is_err());
+	}
+
+	#[test]
+	fn test_nu5_transaction() {
+		let hex = concat!(
+			"05000080",		// overwintered version 5
+			"0a27a726",		// version group id
+			"b4d0d6c2",		// consensus branch id
+			"00000000",		// lock time
+			"00000000",		// expiry height
+			"00", "00",		// transparent inputs and outputs
+			"00", "00",		// sapling spends and outputs
+			"00",			// orchard actions
+		);
+
+		// deserialize && check tx
+		let t: Transaction = hex.into();
+		assert!(t.is_v5());
+		assert_eq!(t.version_group_id, NU5_TX_VERSION_GROUP_ID);
+		assert_eq!(t.consensus_branch_id, 0xc2d6d0b4);
+		assert_eq!(t.sapling, Some(Sapling::default()));
+		assert!(t.orchard.is_none());
+
+		// serialize && check tx
+		assert_eq!(serialize(&t).to_hex::<String>(), hex);
+
+		// v5 transaction with all bundles
+		let t = Transaction {
+			overwintered: true,
+			version: NU5_TX_VERSION,
+			version_group_id: NU5_TX_VERSION_GROUP_ID,
+			consensus_branch_id: 0xc2d6d0b4,
+			inputs: vec![TransactionInput::coinbase("0101".into())],
+			outputs: vec![TransactionOutput { value: 1000, script_pubkey: "51".into() }],
+			lock_time: 0,
+			expiry_height: 1687200,
+			join_split: None,
+			sapling: Some(Sapling {
+				balancing_value: 100,
+				spends: vec![SaplingSpendDescription { anchor: [1; 32], nullifier: [2; 32], ..Default::default() }; 2],
+				outputs: vec![SaplingOutputDescription { note_commitment: [3; 32], ..Default::default() }],
+				binding_sig: [4; 64],
+			}),
+			orchard: Some(Orchard {
+				actions: vec![OrchardAction { nullifier: [5; 32], spend_auth_sig: [6; 64], ..Default::default() }],
+				flags: ORCHARD_FLAG_SPENDS_ENABLED,
+				value_balance: -100,
+				anchor: [7; 32],
+				proof: vec![8; 10].into(),
+				binding_sig: [9; 64],
+			}),
+		};
+		let raw = serialize(&t);
+		assert_eq!(deserialize::<_, Transaction>(raw.as_ref()).unwrap(), t);
+
+		// v5 transaction is identified by ZIP 244 digest, which doesn't commit to authorizing data
+		let indexed: IndexedTransaction = deserialize(raw.as_ref()).unwrap();
+		assert_eq!(indexed.hash, transaction_id_digest(&t));
+		assert_eq!(t.hash(), transaction_id_digest(&t));
+		let mut unauthorized = t.clone();
+		unauthorized.inputs[0].script_sig = "0202".into();
+		unauthorized.orchard.as_mut().unwrap().binding_sig = [0; 64];
+		assert_eq!(unauthorized.hash(), t.hash());
+		assert!(transaction_auth_digest(&unauthorized) != transaction_auth_digest(&t));
+		// ... but commits to effecting data
+		unauthorized.orchard.as_mut().unwrap().value_balance = 0;
+		assert!(unauthorized.hash() != t.hash());
 	}
 
 	#[test]
@@ -445,10 +853,56 @@ mod tests {
 		assert_eq!(t.hash(), hash);
 	}
 
+	#[test]
+	fn test_transaction_limits() {
+		let raw: Vec<u8> = "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000".from_hex().unwrap();
+		let read = |limits: Limits| Reader::new(&raw).with_limits(limits).read::<Transaction>();
+
+		assert!(read(Limits::default()).is_ok());
+		assert_eq!(read(Limits { max_transaction_inputs: 0, ..Default::default() }), Err(Error::LimitExceeded("max_transaction_inputs")));
+		assert_eq!(read(Limits { max_transaction_outputs: 0, ..Default::default() }), Err(Error::LimitExceeded("max_transaction_outputs")));
+		// input script is 73 bytes long
+		assert_eq!(read(Limits { max_script_size: 72, ..Default::default() }), Err(Error::LimitExceeded("max_script_size")));
+		assert!(read(Limits { max_script_size: 73, ..Default::default() }).is_ok());
+	}
+
 	#[test]
 	fn test_transaction_serialized_len() {
 		let raw_tx: &'static str = "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000";
 		let tx: Transaction = raw_tx.into();
 		assert_eq!(tx.serialized_size(), raw_tx.len() / 2);
 	}
+
+	#[test]
+	fn test_transaction_fee_rate() {
+		let tx: Transaction = "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000".into();
+		assert_eq!(tx.size(), 158);
+		assert_eq!(tx.fee_rate(0), 0);
+		assert_eq!(tx.fee_rate(1_580), 10_000);
+		assert_eq!(tx.minimum_fee(10_000), 1_580);
+		assert_eq!(tx.minimum_fee(1_001), 159);
+		assert_eq!(tx.fee_rate(159), 1_006);
+		assert_eq!(super::fee_rate(1_000, 0), 0);
+	}
+
+	#[test]
+	fn test_transaction_nullifiers() {
+		let tx = Transaction::default();
+		assert!(tx.sprout_nullifiers().is_empty());
+		assert!(tx.sapling_nullifiers().is_empty());
+
+		let tx = Transaction {
+			join_split: Some(JoinSplit {
+				descriptions: vec![JoinSplitDescription { nullifiers: [[1; 32], [2; 32]], ..Default::default() }],
+				..Default::default()
+			}),
+			sapling: Some(Sapling {
+				spends: vec![SaplingSpendDescription { nullifier: [3; 32], ..Default::default() }],
+				..Default::default()
+			}),
+			..Default::default()
+		};
+		assert_eq!(tx.sprout_nullifiers(), vec![H256::from(&[1; 32][..]), H256::from(&[2; 32][..])]);
+		assert_eq!(tx.sapling_nullifiers(), vec![H256::from(&[3; 32][..])]);
+	}
 }
@@ -0,0 +1,212 @@
+//! Non-malleable transaction identifier and authorizing data commitment of v5 transactions.
+//! https://zips.z.cash/zip-0244
+
+use crypto::blake2b_personal;
+use hash::H256;
+use ser::Stream;
+use orchard::Orchard;
+use sapling::Sapling;
+use transaction::Transaction;
+
+/// Transaction identifier of v5 transaction.
+pub fn transaction_id_digest(tx: &Transaction) -> H256 {
+	let mut stream = Stream::default();
+	stream
+		.append_slice(&*header_digest(tx))
+		.append_slice(&*transparent_digest(tx))
+		.append_slice(&*sapling_digest(tx.sapling.as_ref()))
+		.append_slice(&*orchard_digest(tx.orchard.as_ref()));
+	blake2b_personal(&branch_personalization(b"ZcashTxHash_", tx.consensus_branch_id), &stream.out())
+}
+
+/// Commitment to the authorizing data (signatures and proofs) of v5 transaction.
+pub fn transaction_auth_digest(tx: &Transaction) -> H256 {
+	let mut stream = Stream::default();
+	stream
+		.append_slice(&*transparent_scripts_digest(tx))
+		.append_slice(&*sapling_auth_digest(tx.sapling.as_ref()))
+		.append_slice(&*orchard_auth_digest(tx.orchard.as_ref()));
+	blake2b_personal(&branch_personalization(b"ZTxAuthHash_", tx.consensus_branch_id), &stream.out())
+}
+
+fn branch_personalization(prefix: &[u8], consensus_branch_id: u32) -> [u8; 16] {
+	let mut personalization = [0u8; 16];
+	personalization[..12].copy_from_slice(prefix);
+	personalization[12] = consensus_branch_id as u8;
+	personalization[13] = (consensus_branch_id >> 8) as u8;
+	personalization[14] = (consensus_branch_id >> 16) as u8;
+	personalization[15] = (consensus_branch_id >> 24) as u8;
+	personalization
+}
+
+fn header_digest(tx: &Transaction) -> H256 {
+	let mut stream = Stream::default();
+	stream
+		.append(&tx.serialized_version())
+		.append(&tx.version_group_id)
+		.append(&tx.consensus_branch_id)
+		.append(&tx.lock_time)
+		.append(&tx.expiry_height);
+	blake2b_personal(b"ZTxIdHeadersHash", &stream.out())
+}
+
+fn transparent_digest(tx: &Transaction) -> H256 {
+	if tx.inputs.is_empty() && tx.outputs.is_empty() {
+		return blake2b_personal(b"ZTxIdTranspaHash", &[]);
+	}
+
+	let mut prevouts = Stream::default();
+	let mut sequences = Stream::default();
+	for input in &tx.inputs {
+		prevouts.append(&input.previous_output);
+		sequences.append(&input.sequence);
+	}
+	let mut outputs = Stream::default();
+	for output in &tx.outputs {
+		outputs.append(output);
+	}
+
+	let mut stream = Stream::default();
+	stream
+		.append_slice(&*blake2b_personal(b"ZTxIdPrevoutHash", &prevouts.out()))
+		.append_slice(&*blake2b_personal(b"ZTxIdSequencHash", &sequences.out()))
+		.append_slice(&*blake2b_personal(b"ZTxIdOutputsHash", &outputs.out()));
+	blake2b_personal(b"ZTxIdTranspaHash", &stream.out())
+}
+
+fn sapling_digest(sapling: Option<&Sapling>) -> H256 {
+	let sapling = match sapling {
+		Some(sapling) if !sapling.spends.is_empty() || !sapling.outputs.is_empty() => sapling,
+		_ => return blake2b_personal(b"ZTxIdSaplingHash", &[]),
+	};
+
+	let spends_digest = if sapling.spends.is_empty() {
+		blake2b_personal(b"ZTxIdSSpendsHash", &[])
+	} else {
+		let mut compact = Stream::default();
+		let mut noncompact = Stream::default();
+		for spend in &sapling.spends {
+			compact.append(&spend.nullifier);
+			noncompact
+				.append(&spend.value_commitment)
+				.append(&spend.anchor)
+				.append(&spend.randomized_key);
+		}
+
+		let mut stream = Stream::default();
+		stream
+			.append_slice(&*blake2b_personal(b"ZTxIdSSpendCHash", &compact.out()))
+			.append_slice(&*blake2b_personal(b"ZTxIdSSpendNHash", &noncompact.out()));
+		blake2b_personal(b"ZTxIdSSpendsHash", &stream.out())
+	};
+
+	let outputs_digest = if sapling.outputs.is_empty() {
+		blake2b_personal(b"ZTxIdSOutputHash", &[])
+	} else {
+		let mut compact = Stream::default();
+		let mut memos = Stream::default();
+		let mut noncompact = Stream::default();
+		for output in &sapling.outputs {
+			compact
+				.append(&output.note_commitment)
+				.append(&output.ephemeral_key)
+				.append_slice(&output.enc_cipher_text[..52]);
+			memos.append_slice(&output.enc_cipher_text[52..564]);
+			noncompact
+				.append(&output.value_commitment)
+				.append_slice(&output.enc_cipher_text[564..])
+				.append(&output.out_cipher_text);
+		}
+
+		let mut stream = Stream::default();
+		stream
+			.append_slice(&*blake2b_personal(b"ZTxIdSOutC__Hash", &compact.out()))
+			.append_slice(&*blake2b_personal(b"ZTxIdSOutM__Hash", &memos.out()))
+			.append_slice(&*blake2b_personal(b"ZTxIdSOutN__Hash", &noncompact.out()));
+		blake2b_personal(b"ZTxIdSOutputHash", &stream.out())
+	};
+
+	let mut stream = Stream::default();
+	stream
+		.append_slice(&*spends_digest)
+		.append_slice(&*outputs_digest)
+		.append(&sapling.balancing_value);
+	blake2b_personal(b"ZTxIdSaplingHash", &stream.out())
+}
+
+fn orchard_digest(orchard: Option<&Orchard>) -> H256 {
+	let orchard = match orchard {
+		Some(orchard) if !orchard.actions.is_empty() => orchard,
+		_ => return blake2b_personal(b"ZTxIdOrchardHash", &[]),
+	};
+
+	let mut compact = Stream::default();
+	let mut memos = Stream::default();
+	let mut noncompact = Stream::default();
+	for action in &orchard.actions {
+		compact
+			.append(&action.nullifier)
+			.append(&action.note_commitment)
+			.append(&action.ephemeral_key)
+			.append_slice(&action.enc_cipher_text[..52]);
+		memos.append_slice(&action.enc_cipher_text[52..564]);
+		noncompact
+			.append(&action.value_commitment)
+			.append(&action.randomized_key)
+			.append_slice(&action.enc_cipher_text[564..])
+			.append(&action.out_cipher_text);
+	}
+
+	let mut stream = Stream::default();
+	stream
+		.append_slice(&*blake2b_personal(b"ZTxIdOrcActCHash", &compact.out()))
+		.append_slice(&*blake2b_personal(b"ZTxIdOrcActMHash", &memos.out()))
+		.append_slice(&*blake2b_personal(b"ZTxIdOrcActNHash", &noncompact.out()))
+		.append(&orchard.flags)
+		.append(&orchard.value_balance)
+		.append(&orchard.anchor);
+	blake2b_personal(b"ZTxIdOrchardHash", &stream.out())
+}
+
+fn transparent_scripts_digest(tx: &Transaction) -> H256 {
+	let mut stream = Stream::default();
+	for input in &tx.inputs {
+		stream.append(&input.script_sig);
+	}
+	blake2b_personal(b"ZTxAuthTransHash", &stream.out())
+}
+
+fn sapling_auth_digest(sapling: Option<&Sapling>) -> H256 {
+	let sapling = match sapling {
+		Some(sapling) if !sapling.spends.is_empty() || !sapling.outputs.is_empty() => sapling,
+		_ => return blake2b_personal(b"ZTxAuthSapliHash", &[]),
+	};
+
+	let mut stream = Stream::default();
+	for spend in &sapling.spends {
+		stream.append(&spend.zkproof);
+	}
+	for spend in &sapling.spends {
+		stream.append(&spend.spend_auth_sig);
+	}
+	for output in &sapling.outputs {
+		stream.append(&output.zkproof);
+	}
+	stream.append(&sapling.binding_sig);
+	blake2b_personal(b"ZTxAuthSapliHash", &stream.out())
+}
+
+fn orchard_auth_digest(orchard: Option<&Orchard>) -> H256 {
+	let orchard = match orchard {
+		Some(orchard) if !orchard.actions.is_empty() => orchard,
+		_ => return blake2b_personal(b"ZTxAuthOrchaHash", &[]),
+	};
+
+	let mut stream = Stream::default();
+	stream.append_slice(&orchard.proof);
+	for action in &orchard.actions {
+		stream.append(&action.spend_auth_sig);
+	}
+	stream.append(&orchard.binding_sig);
+	blake2b_personal(b"ZTxAuthOrchaHash", &stream.out())
+}
@@ -0,0 +1,186 @@
+use std::net::SocketAddr;
+use rand::random;
+use chain::IndexedBlockHeader;
+use message::Payload;
+use message::common::InventoryVector;
+use message::types::{Block, GetData, GetHeaders, Headers, NotFound, Ping, Pong};
+use network::Network;
+use primitives::bytes::Bytes;
+use primitives::hash::H256;
+use connection::{Connection, message_with_invalid_checksum, header_with_payload_len, ping_payload};
+
+/// Maximal payload size, accepted by zcashd (MAX_PROTOCOL_MESSAGE_LENGTH).
+const MAX_PROTOCOL_MESSAGE_LENGTH: u32 = 2 * 1024 * 1024;
+
+/// Node that is tested.
+pub struct Target {
+	pub address: SocketAddr,
+	pub network: Network,
+}
+
+/// Single conformance check.
+pub struct Check {
+	/// Short name of the check.
+	pub name: &'static str,
+	/// What node is expected to do.
+	pub expectation: &'static str,
+	run: fn(&Target) -> Result<(), String>,
+}
+
+impl Check {
+	pub fn run(&self, target: &Target) -> Result<(), String> {
+		(self.run)(target)
+	}
+}
+
+/// All checks of the suite, in the order they are executed.
+pub fn all_checks() -> Vec<Check> {
+	vec![
+		Check {
+			name: "handshake",
+			expectation: "answers version with version and verack",
+			run: check_handshake,
+		},
+		Check {
+			name: "ping",
+			expectation: "answers ping with pong carrying the same nonce",
+			run: check_ping,
+		},
+		Check {
+			name: "getheaders",
+			expectation: "answers getheaders with connected headers following the locator",
+			run: check_getheaders,
+		},
+		Check {
+			name: "getdata-block",
+			expectation: "answers getdata for the genesis block with the block",
+			run: check_getdata_block,
+		},
+		Check {
+			name: "getdata-notfound",
+			expectation: "answers getdata for unknown transaction with notfound",
+			run: check_getdata_notfound,
+		},
+		Check {
+			name: "unknown-command",
+			expectation: "ignores messages with unknown command",
+			run: check_unknown_command,
+		},
+		Check {
+			name: "invalid-checksum",
+			expectation: "ignores message with invalid checksum or disconnects, keeps serving peers",
+			run: check_invalid_checksum,
+		},
+		Check {
+			name: "oversized-message",
+			expectation: "disconnects peer, announcing too large payload, keeps serving peers",
+			run: check_oversized_message,
+		},
+	]
+}
+
+fn connect(target: &Target) -> Result<Connection, String> {
+	Connection::open_with_handshake(&target.address, target.network.magic())
+}
+
+fn genesis_hash(target: &Target) -> H256 {
+	target.network.genesis_block().hash().clone()
+}
+
+/// Sends ping && waits for pong with the same nonce.
+fn ping_pong(connection: &mut Connection) -> Result<(), String> {
+	let nonce = random();
+	connection.send(&Ping::new(nonce))?;
+	loop {
+		let pong: Pong = connection.receive_expected()?;
+		if pong.nonce == nonce {
+			return Ok(());
+		}
+	}
+}
+
+/// Checks that node still accepts new connections.
+fn check_alive(target: &Target) -> Result<(), String> {
+	connect(target)
+		.and_then(|mut connection| ping_pong(&mut connection))
+		.map_err(|err| format!("node is not serving new peers: {}", err))
+}
+
+fn check_handshake(target: &Target) -> Result<(), String> {
+	let mut connection = Connection::open(&target.address, target.network.magic())?;
+	let version = connection.handshake()?;
+	if version.user_agent().is_none() {
+		return Err(format!("version message of protocol version {} has no user agent", version.version()));
+	}
+	Ok(())
+}
+
+fn check_ping(target: &Target) -> Result<(), String> {
+	ping_pong(&mut connect(target)?)
+}
+
+fn check_getheaders(target: &Target) -> Result<(), String> {
+	let mut connection = connect(target)?;
+	let genesis_hash = genesis_hash(target);
+	connection.send(&GetHeaders::with_block_locator_hashes(vec![genesis_hash.clone()]))?;
+	let headers: Headers = connection.receive_expected()?;
+
+	// node could know nothing but genesis => empty response is valid
+	let mut previous_hash = genesis_hash;
+	for header in headers.headers {
+		let header = IndexedBlockHeader::from_raw(header);
+		if header.raw.previous_header_hash != previous_hash {
+			return Err(format!("header {} does not follow {}", header.hash.to_reversed_str(), previous_hash.to_reversed_str()));
+		}
+		previous_hash = header.hash;
+	}
+	Ok(())
+}
+
+fn check_getdata_block(target: &Target) -> Result<(), String> {
+	let mut connection = connect(target)?;
+	let genesis_hash = genesis_hash(target);
+	connection.send(&GetData::with_inventory(vec![InventoryVector::block(genesis_hash.clone())]))?;
+	let block: Block = connection.receive_expected()?;
+	let block_hash = block.block.hash().clone();
+	if block_hash != genesis_hash {
+		return Err(format!("received block {} instead of genesis", block_hash.to_reversed_str()));
+	}
+	Ok(())
+}
+
+fn check_getdata_notfound(target: &Target) -> Result<(), String> {
+	let mut connection = connect(target)?;
+	let unknown = InventoryVector::tx(H256::from(random::<[u8; 32]>()));
+	connection.send(&GetData::with_inventory(vec![unknown.clone()]))?;
+	let notfound: NotFound = connection.receive_expected()?;
+	if !notfound.inventory.contains(&unknown) {
+		return Err("notfound does not contain requested transaction".into());
+	}
+	Ok(())
+}
+
+fn check_unknown_command(target: &Target) -> Result<(), String> {
+	let mut connection = connect(target)?;
+	connection.send_raw(&::message::to_raw_message(target.network.magic(), "zconformance".into(), &Bytes::default()))?;
+	ping_pong(&mut connection)
+}
+
+fn check_invalid_checksum(target: &Target) -> Result<(), String> {
+	let mut connection = connect(target)?;
+	connection.send_raw(&message_with_invalid_checksum(target.network.magic(), Ping::command(), &ping_payload(random())))?;
+	// either message is ignored and connection is still alive, or connection is closed
+	if ping_pong(&mut connection).is_err() && !connection.is_closed() {
+		return Err("node neither answered ping, nor closed connection".into());
+	}
+	check_alive(target)
+}
+
+fn check_oversized_message(target: &Target) -> Result<(), String> {
+	let mut connection = connect(target)?;
+	connection.send_raw(&header_with_payload_len(target.network.magic(), Ping::command(), MAX_PROTOCOL_MESSAGE_LENGTH + 1))?;
+	if !connection.is_closed() {
+		return Err(format!("connection is still open after announcing {} bytes payload", MAX_PROTOCOL_MESSAGE_LENGTH + 1));
+	}
+	check_alive(target)
+}
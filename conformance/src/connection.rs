@@ -0,0 +1,175 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use rand::random;
+use message::{Command, Payload, Message, MessageHeader, deserialize_payload};
+use message::common::{Services, NetAddress};
+use message::types::{self, Ping, Pong, Verack};
+use message::types::version::{Version, V0, V106, V70001};
+use network::Magic;
+use primitives::bytes::Bytes;
+
+/// Protocol version, announced to the tested node.
+pub const PROTOCOL_VERSION: u32 = 170_100;
+/// User agent, announced to the tested node.
+const USER_AGENT: &'static str = "/zebra-conformance:0.1.0/";
+/// Size of the message header.
+const MESSAGE_HEADER_SIZE: usize = 24;
+/// Number of seconds to wait for the response.
+const RESPONSE_TIMEOUT_SECS: u64 = 10;
+
+/// Blocking connection to the tested node.
+pub struct Connection {
+	stream: TcpStream,
+	address: SocketAddr,
+	magic: Magic,
+	/// Protocol version, negotiated during handshake.
+	version: u32,
+}
+
+impl Connection {
+	/// Open TCP connection to the node. No messages are sent.
+	pub fn open(address: &SocketAddr, magic: Magic) -> Result<Self, String> {
+		let timeout = Duration::from_secs(RESPONSE_TIMEOUT_SECS);
+		let stream = TcpStream::connect_timeout(address, timeout)
+			.and_then(|stream| stream.set_read_timeout(Some(timeout)).map(|_| stream))
+			.map_err(|err| format!("cannot connect: {}", err))?;
+
+		Ok(Connection {
+			stream: stream,
+			address: address.clone(),
+			magic: magic,
+			version: 0,
+		})
+	}
+
+	/// Open TCP connection to the node and perform handshake.
+	pub fn open_with_handshake(address: &SocketAddr, magic: Magic) -> Result<Self, String> {
+		let mut connection = Connection::open(address, magic)?;
+		connection.handshake()?;
+		Ok(connection)
+	}
+
+	/// Exchange version && verack messages. Returns version message of the node.
+	pub fn handshake(&mut self) -> Result<Version, String> {
+		let version = self.version_message();
+		self.send(&version)?;
+
+		let node_version: Version = self.receive_expected()?;
+		if node_version.nonce() == version.nonce() {
+			return Err("node has connected to itself".into());
+		}
+		self.version = ::std::cmp::min(PROTOCOL_VERSION, node_version.version());
+
+		self.send(&Verack)?;
+		let _: Verack = self.receive_expected()?;
+
+		Ok(node_version)
+	}
+
+	/// Send message to the node.
+	pub fn send<T>(&mut self, payload: &T) -> Result<(), String> where T: Payload {
+		let message = Message::new(self.magic, self.version, payload)
+			.map_err(|err| format!("cannot serialize {}: {}", T::command(), err))?;
+		self.send_raw(message.as_ref())
+	}
+
+	/// Send raw bytes to the node.
+	pub fn send_raw(&mut self, bytes: &[u8]) -> Result<(), String> {
+		self.stream.write_all(bytes)
+			.and_then(|_| self.stream.flush())
+			.map_err(|err| format!("cannot send: {}", err))
+	}
+
+	/// Receive next message from the node.
+	pub fn receive(&mut self) -> Result<(Command, Bytes), String> {
+		let mut header = [0u8; MESSAGE_HEADER_SIZE];
+		self.read_exact(&mut header)?;
+		let header = MessageHeader::deserialize(&header, self.magic)
+			.map_err(|err| format!("invalid message header: {}", err))?;
+
+		let mut payload = Bytes::new_with_len(header.len as usize);
+		self.read_exact(&mut payload)?;
+		Ok((header.command, payload))
+	}
+
+	/// Receive messages until the message of given type is received. Pings of the node are answered
+	/// and all other messages are ignored.
+	pub fn receive_expected<T>(&mut self) -> Result<T, String> where T: Payload {
+		loop {
+			let (command, payload) = self.receive()?;
+			if command == T::command() {
+				return deserialize_payload(&payload, self.version)
+					.map_err(|err| format!("cannot deserialize {}: {}", command, err));
+			}
+
+			if command == Ping::command() {
+				let ping: Ping = deserialize_payload(&payload, self.version)
+					.map_err(|err| format!("cannot deserialize ping: {}", err))?;
+				self.send(&Pong::new(ping.nonce))?;
+			}
+		}
+	}
+
+	/// Returns true if the node has closed the connection. Messages that are received while
+	/// waiting are ignored.
+	pub fn is_closed(&mut self) -> bool {
+		let mut buffer = [0u8; 1024];
+		loop {
+			match self.stream.read(&mut buffer) {
+				Ok(0) => return true,
+				Ok(_) => continue,
+				Err(ref err) if err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut => return false,
+				Err(_) => return true,
+			}
+		}
+	}
+
+	fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), String> {
+		self.stream.read_exact(buffer).map_err(|err| match err.kind() {
+			io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => "no response from node".to_owned(),
+			io::ErrorKind::UnexpectedEof => "connection closed by node".to_owned(),
+			_ => format!("cannot receive: {}", err),
+		})
+	}
+
+	fn version_message(&self) -> Version {
+		let services = Services::default().with_network(true);
+		Version::V70001(V0 {
+			version: PROTOCOL_VERSION,
+			services: services,
+			timestamp: ::time::get_time().sec,
+			receiver: NetAddress {
+				services: services,
+				address: self.address.ip().into(),
+				port: self.address.port().into(),
+			},
+		}, V106 {
+			from: NetAddress::default(),
+			nonce: random(),
+			user_agent: USER_AGENT.into(),
+			start_height: 0,
+		}, V70001 {
+			relay: false,
+		})
+	}
+}
+
+/// Raw message with given command and payload, but with corrupted checksum.
+pub fn message_with_invalid_checksum(magic: Magic, command: &'static str, payload: &Bytes) -> Bytes {
+	let mut raw = ::message::to_raw_message(magic, command.into(), payload);
+	// checksum occupies last 4 bytes of the header
+	raw[MESSAGE_HEADER_SIZE - 1] ^= 0xff;
+	raw
+}
+
+/// Raw message header, announcing payload of given length.
+pub fn header_with_payload_len(magic: Magic, command: &'static str, len: u32) -> Bytes {
+	let mut header = MessageHeader::for_data(magic, command.into(), &[]);
+	header.len = len;
+	::ser::serialize(&header)
+}
+
+pub fn ping_payload(nonce: u64) -> Bytes {
+	::message::serialize_payload(&types::Ping::new(nonce), 0).expect("ping is supported by every protocol version; qed")
+}
@@ -0,0 +1,76 @@
+//! Zcash p2p protocol conformance test suite.
+//!
+//! Connects to the node (zebra, zcashd or any other implementation), runs every check over
+//! a fresh connection and prints the conformance matrix:
+//!
+//! ```text
+//! conformance <ADDRESS> [mainnet|testnet|regtest]
+//! ```
+//!
+//! ADDRESS is either `ip:port`, or `ip` (then the default port of the network is used).
+//! Exits with non-zero code if any check has failed.
+
+extern crate chain;
+extern crate message;
+extern crate network;
+extern crate primitives;
+extern crate rand;
+extern crate serialization as ser;
+extern crate time;
+
+mod checks;
+mod connection;
+
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::process;
+use network::Network;
+use checks::{Target, all_checks};
+
+const USAGE: &'static str = "Usage: conformance <ADDRESS> [mainnet|testnet|regtest]";
+
+fn main() {
+	let target = match parse_args(env::args().skip(1).collect()) {
+		Ok(target) => target,
+		Err(err) => {
+			eprintln!("{}\n{}", err, USAGE);
+			process::exit(2);
+		},
+	};
+
+	println!("Checking {} ({:?})", target.address, target.network);
+	let mut failed = 0;
+	for check in all_checks() {
+		match check.run(&target) {
+			Ok(()) => println!("{:<20} PASS  {}", check.name, check.expectation),
+			Err(err) => {
+				failed += 1;
+				println!("{:<20} FAIL  {}: {}", check.name, check.expectation, err);
+			},
+		}
+	}
+
+	if failed != 0 {
+		println!("{} of {} checks failed", failed, all_checks().len());
+		process::exit(1);
+	}
+}
+
+fn parse_args(args: Vec<String>) -> Result<Target, String> {
+	let network = match args.get(1).map(String::as_str) {
+		None | Some("mainnet") => Network::Mainnet,
+		Some("testnet") => Network::Testnet,
+		Some("regtest") => Network::Regtest,
+		Some(network) => return Err(format!("Unknown network: {}", network)),
+	};
+
+	let address = args.get(0).ok_or_else(|| "Node address is required".to_owned())?;
+	let address = address.parse::<SocketAddr>()
+		.or_else(|_| address.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, network.port())))
+		.map_err(|_| format!("Invalid node address: {}", address))?;
+
+	Ok(Target {
+		address: address,
+		network: network,
+	})
+}
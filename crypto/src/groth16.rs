@@ -1,9 +1,11 @@
-use std::fmt;
+use std::{fmt, io};
 
 use hex::ToHex;
 
 use pairing::bls12_381::Bls12;
-use bellman::groth16::Proof as BellmanProof;
+use bellman::groth16::{Proof as BellmanProof, VerifyingKey as BellmanVerifyingKey, prepare_verifying_key};
+
+use Groth16VerifyingKey;
 
 #[derive(Clone)]
 pub struct Proof([u8; 192]);
@@ -49,6 +51,13 @@ impl<'a> Into<&'a [u8; 192]> for &'a Proof {
 	}
 }
 
+/// Read verifying key from the beginning of bellman parameters file (like zcashd `sapling-spend.params`).
+pub fn read_verifying_key<R: io::Read>(reader: R) -> Result<Groth16VerifyingKey, String> {
+	BellmanVerifyingKey::<Bls12>::read(reader)
+		.map(|vk| Groth16VerifyingKey(prepare_verifying_key(&vk)))
+		.map_err(|err| format!("Invalid verifying key: {}", err))
+}
+
 impl Proof {
 	pub fn to_bls_proof(&self) -> Result<BellmanProof<Bls12>, Error> {
 		BellmanProof::<Bls12>::read(&self.0[..])
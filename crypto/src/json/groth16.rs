@@ -158,4 +158,17 @@ mod tests {
 		let spend_vk_json = include_bytes!("../../../res/sapling-spend-verifying-key.json");
 		serde_json::from_slice::<VerifyingKey>(&spend_vk_json[..]).unwrap();
 	}
+
+	#[test]
+	fn params_key() {
+		let spend_vk_json = include_bytes!("../../../res/sapling-spend-verifying-key.json");
+		let spend_vk: BellmanVerifyingKey<Bls12> = serde_json::from_slice::<VerifyingKey>(&spend_vk_json[..]).unwrap().into();
+		let mut params = Vec::new();
+		spend_vk.write(&mut params).unwrap();
+		// the rest of parameters file is ignored
+		params.extend_from_slice(&[0u8; 100]);
+
+		::read_groth16_verifying_key(&params[..]).unwrap();
+		::read_groth16_verifying_key(&params[..100]).unwrap_err();
+	}
 }
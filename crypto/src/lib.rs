@@ -44,6 +44,7 @@ pub use pghr13::{VerifyingKey as Pghr13VerifyingKey, Proof as Pghr13Proof, verif
 pub use groth16::{
 	Proof as Groth16Proof,
 	Error as Groth16Error,
+	read_verifying_key as read_groth16_verifying_key,
 };
 
 pub mod curve {
@@ -1,41 +1,125 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::{io, mem};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use parking_lot::RwLock;
 use hash::H256;
 use bytes::Bytes;
 use chain::{
 	IndexedBlock, IndexedBlockHeader, IndexedTransaction,
-	OutPoint, TransactionOutput,
+	OutPoint, TransactionOutput, Transaction as ChainTransaction, Block as ChainBlock,
+	BlockCommitment, HistoryTree, HistoryNode,
 };
+use network::ConsensusParams;
 use ser::{
-	deserialize, serialize, List
+	deserialize, deserialize_iterator, serialize, List, CompactInteger, Error as ReaderError, ReadIterator,
 };
+use flat_file::{FlatFileStore, FlatFilePosition};
+use migrations;
 use kv::{
 	KeyValueDatabase, OverlayDatabase, Transaction as DBTransaction, Value, DiskDatabase,
 	DatabaseConfig, MemoryDatabase, AutoFlushingOverlayDatabase, KeyValue, Key, KeyState,
-	CacheDatabase,
+	CacheDatabase, CompactionProfile, CompactionStyle,
 };
 use kv::{
 	COL_COUNT, COL_BLOCK_HASHES, COL_BLOCK_HEADERS, COL_BLOCK_TRANSACTIONS, COL_TRANSACTIONS,
 	COL_TRANSACTIONS_META, COL_BLOCK_NUMBERS, COL_SAPLING_NULLIFIERS, COL_SPROUT_NULLIFIERS,
-	COL_SPROUT_BLOCK_ROOTS, COL_TREE_STATES,
+	COL_ORCHARD_NULLIFIERS, COL_SPROUT_BLOCK_ROOTS, COL_TREE_STATES,
 };
 use storage::{
 	BlockRef, Error, BlockHeaderProvider, BlockProvider, BlockOrigin, TransactionMeta,
 	TransactionMetaProvider, TransactionProvider, TransactionOutputProvider, BlockChain, Store,
 	SideChainOrigin, ForkChain, Forkable, CanonStore, BestBlock, NullifierTracker,
-	EpochTag, EpochRef, SproutTreeState, SaplingTreeState, TreeStateProvider,
+	EpochTag, EpochRef, SproutTreeState, SaplingTreeState, TreeStateProvider, ConfigStore,
+	ReadSnapshot, ShieldedStats, ShieldedStatsProvider, FeeStats, FeeStatsProvider,
+	DuplexTransactionOutputProvider, ChainEvent, ChainEventKind, ChainEventProvider, transaction_nullifiers,
+	ValuePools, ValuePoolsProvider, AddressIndexEntry, AddressIndexProvider, address_index_key,
+	SpentIndexEntry, SpentIndexProvider, SnapshotStore, SnapshotInfo, SnapshotRecord, SnapshotWriter,
+	SnapshotReader, BlockUndo, DatabaseInfo, DatabaseInfoProvider,
 };
 
-const KEY_BEST_BLOCK_NUMBER: &'static str = "best_block_number";
+pub const KEY_BEST_BLOCK_NUMBER: &'static str = "best_block_number";
 const KEY_BEST_BLOCK_HASH: &'static str = "best_block_hash";
+const KEY_LAST_CHAIN_EVENT: &'static str = "last_chain_event";
+const KEY_PRUNED_HEIGHT: &'static str = "pruned_height";
+pub const KEY_UNDO_PRUNED_HEIGHT: &'static str = "undo_pruned_height";
+
+const KEY_NETWORK: &'static str = "network";
 
 const MAX_FORK_ROUTE_PRESET: usize = 2048;
 
+/// Name of the directory (within the database directory) where raw blocks are stored.
+const BLOCK_FILES_DIR: &'static str = "blocks";
+/// Prefix of the raw blocks files.
+const BLOCK_FILES_PREFIX: &'static str = "blk";
+/// Maximal size of single raw blocks file.
+const MAX_BLOCK_FILE_SIZE: u32 = 128 * 1024 * 1024;
+
+/// Minimal number of last canon blocks, kept by the pruned database. Peers, advertising
+/// NODE_NETWORK_LIMITED service, are expected to serve at least this number of last blocks.
+pub const MIN_BLOCKS_TO_KEEP: u32 = 288;
+/// Minimal number of last canon blocks, which undo data is kept. Reorganizations of this depth
+/// are rejected by zcashd (its maximal reorg length is coinbase maturity - 1).
+pub const MIN_UNDO_HORIZON: u32 = 100;
+/// Number of last canon blocks, which undo data is kept by the disk database, unless configured otherwise.
+pub const DEFAULT_UNDO_HORIZON: u32 = 288;
+/// Blocks are pruned in batches of (at least) this number of blocks.
+const PRUNE_BATCH_SIZE: u32 = 1000;
+/// Snapshot records are written to the database in batches of this number of records.
+const SNAPSHOT_IMPORT_BATCH_SIZE: usize = 10_000;
+
+/// Tunable settings of the disk database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseSettings {
+	/// Total size of column caches (in MiB).
+	pub cache_size: usize,
+	/// Size of the write buffer (memtable) of every column (in MiB).
+	pub write_buffer_size: usize,
+	/// Compaction profile of the key-value store.
+	pub compaction: CompactionProfile,
+	/// Maximal number of files, opened by the key-value store.
+	pub max_open_files: i32,
+}
+
+impl DatabaseSettings {
+	/// Default settings with given total size of column caches (in MiB).
+	pub fn with_cache(cache_size: usize) -> Self {
+		DatabaseSettings {
+			cache_size: cache_size,
+			// RocksDB default
+			write_buffer_size: 64,
+			compaction: CompactionProfile::default(),
+			max_open_files: DatabaseConfig::default().max_open_files,
+		}
+	}
+}
+
 pub struct BlockChainDatabase<T> where T: KeyValueDatabase {
+	/// Read snapshots hold this lock for reading, canon chain modifications hold it for writing.
+	/// Must be acquired before `best_block`.
+	snapshot_lock: RwLock<()>,
 	best_block: RwLock<BestBlock>,
 	db: T,
+	/// Raw blocks storage. When present, transactions of blocks, extending the canon chain, are stored in
+	/// flat files and the database only holds their positions.
+	block_files: Option<Arc<FlatFileStore>>,
+	/// Database is the overlay of the fork. Forks could be rejected, so their blocks are never appended
+	/// to raw blocks files.
+	fork_overlay: bool,
+	/// Maintain index of canon transparent outputs by their scripts.
+	address_index: bool,
+	/// Maintain index of canon transaction inputs by outputs they spend.
+	spent_index: bool,
+	/// Number of last canon blocks, which data is kept. When None, data of all blocks is kept.
+	keep_blocks: Option<u32>,
+	/// Number of last canon blocks, which undo data is kept. When None, undo data of all blocks is kept.
+	undo_horizon: Option<u32>,
+	/// Consensus parameters of the network, which chain history trees are maintained. When None, chain
+	/// history trees aren't maintained.
+	chain_history: Option<ConsensusParams>,
+	/// Path && settings of the disk database.
+	disk: Option<(PathBuf, DatabaseSettings)>,
 }
 
 pub struct ForkChainDatabase<'a, T> where T: 'a + KeyValueDatabase {
@@ -59,8 +143,9 @@ mod cache {
 	pub const CACHE_BLOCK_HASHES: u32 = 5;
 	pub const CACHE_BLOCK_TRANSACTIONS: u32 = 10;
 	pub const CACHE_BLOCK_NUMBERS: u32 = 5;
-	pub const CACHE_SPROUT_NULLIFIERS: u32 = 5;
+	pub const CACHE_SPROUT_NULLIFIERS: u32 = 2;
 	pub const CACHE_SAPLING_NULLIFIERS: u32 = 5;
+	pub const CACHE_ORCHARD_NULLIFIERS: u32 = 3;
 	pub const CACHE_TREE_STATES: u32 = 10;
 	pub const CACHE_SPROUT_BLOCK_ROOTS: u32 = 5;
 
@@ -79,6 +164,7 @@ mod cache {
 			CACHE_BLOCK_NUMBERS +
 			CACHE_SPROUT_NULLIFIERS +
 			CACHE_SAPLING_NULLIFIERS +
+			CACHE_ORCHARD_NULLIFIERS +
 			CACHE_TREE_STATES +
 			CACHE_SPROUT_BLOCK_ROOTS
 		);
@@ -86,9 +172,14 @@ mod cache {
 }
 
 impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<DiskDatabase>>> {
-	pub fn open_at_path<P>(path: P, total_cache: usize) -> Result<Self, Error> where P: AsRef<Path> {
+	pub fn open_at_path<P>(path: P, settings: DatabaseSettings) -> Result<Self, Error> where P: AsRef<Path> {
 		fs::create_dir_all(path.as_ref()).map_err(|err| Error::DatabaseError(err.to_string()))?;
 		let mut cfg = DatabaseConfig::with_columns(Some(COL_COUNT));
+		cfg.write_buffer_size = Some(settings.write_buffer_size);
+		cfg.compaction = settings.compaction;
+		cfg.max_open_files = settings.max_open_files;
+
+		let total_cache = settings.cache_size;
 
 		cache::set(&mut cfg, total_cache, COL_TRANSACTIONS, cache::CACHE_TRANSACTIONS);
 		cache::set(&mut cfg, total_cache, COL_TRANSACTIONS_META, cache::CACHE_TRANSACTION_META);
@@ -100,6 +191,7 @@ impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<DiskDatabase>>
 
 		cache::set(&mut cfg, total_cache, COL_SPROUT_NULLIFIERS, cache::CACHE_SPROUT_NULLIFIERS);
 		cache::set(&mut cfg, total_cache, COL_SAPLING_NULLIFIERS, cache::CACHE_SAPLING_NULLIFIERS);
+		cache::set(&mut cfg, total_cache, COL_ORCHARD_NULLIFIERS, cache::CACHE_ORCHARD_NULLIFIERS);
 
 		cache::set(&mut cfg, total_cache, COL_TREE_STATES, cache::CACHE_TREE_STATES);
 
@@ -107,9 +199,60 @@ impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<DiskDatabase>>
 
 		cfg.bloom_filters.insert(Some(COL_TRANSACTIONS_META), 32);
 
-		match DiskDatabase::open(cfg, path) {
-			Ok(db) => Ok(Self::open_with_cache(db)),
-			Err(err) => Err(Error::DatabaseError(err))
+		let block_files = Self::open_block_files(path.as_ref())?;
+
+		let db = DiskDatabase::open(cfg, path.as_ref()).map_err(Error::DatabaseError)?;
+		let is_empty = BlockChainDatabase::<DiskDatabase>::read_best_block(&db).is_none();
+		migrations::upgrade(&db, &block_files, is_empty)?;
+		let mut db = Self::open_with_cache(db)
+			.with_block_files(block_files)
+			.with_undo_horizon(DEFAULT_UNDO_HORIZON);
+		db.disk = Some((path.as_ref().to_owned(), settings));
+		Ok(db)
+	}
+
+	/// Raw blocks files of the database at given path.
+	pub fn open_block_files<P>(path: P) -> Result<FlatFileStore, Error> where P: AsRef<Path> {
+		FlatFileStore::open(path.as_ref().join(BLOCK_FILES_DIR), BLOCK_FILES_PREFIX, MAX_BLOCK_FILE_SIZE)
+			.map_err(|err| Error::DatabaseError(err.to_string()))
+	}
+
+	/// Blocks of raw blocks files of the database at given path, in the order they have been stored (side chain
+	/// blocks included). Files are read directly, so blocks are available even if the database itself is corrupted.
+	pub fn stored_blocks<P>(path: P) -> Result<StoredBlocks, Error> where P: AsRef<Path> {
+		let block_files = Self::open_block_files(path)?;
+		let files = block_files.files().map_err(|err| Error::DatabaseError(err.to_string()))?;
+		Ok(StoredBlocks {
+			block_files: block_files,
+			files: files.into_iter(),
+			blocks: None,
+		})
+	}
+}
+
+/// Iterator over blocks of raw blocks files.
+pub struct StoredBlocks {
+	block_files: FlatFileStore,
+	files: ::std::vec::IntoIter<u32>,
+	blocks: Option<ReadIterator<io::BufReader<fs::File>, ChainBlock>>,
+}
+
+impl Iterator for StoredBlocks {
+	type Item = Result<IndexedBlock, Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some(block) = self.blocks.as_mut().and_then(Iterator::next) {
+				return Some(block
+					.map(Into::into)
+					.map_err(|err| Error::DatabaseError(format!("Invalid block in raw blocks files: {:?}", err))));
+			}
+
+			let number = self.files.next()?;
+			match fs::File::open(self.block_files.file_path(number)) {
+				Ok(file) => self.blocks = Some(deserialize_iterator(io::BufReader::new(file))),
+				Err(err) => return Some(Err(Error::DatabaseError(err.to_string()))),
+			}
 		}
 	}
 }
@@ -132,8 +275,17 @@ impl<T> BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<T>>> where
 		let db = CacheDatabase::new(AutoFlushingOverlayDatabase::new(db, 50));
 		let best_block = Self::read_best_block(&db).unwrap_or_default();
 		BlockChainDatabase {
+			snapshot_lock: RwLock::default(),
 			best_block: RwLock::new(best_block),
 			db: db,
+			block_files: None,
+			fork_overlay: false,
+			address_index: false,
+			spent_index: false,
+			keep_blocks: None,
+			undo_horizon: None,
+			chain_history: None,
+			disk: None,
 		}
 	}
 }
@@ -156,17 +308,74 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 	pub fn open(db: T) -> Self {
 		let best_block = Self::read_best_block(&db).unwrap_or_default();
 		BlockChainDatabase {
+			snapshot_lock: RwLock::default(),
 			best_block: RwLock::new(best_block),
 			db: db,
+			block_files: None,
+			fork_overlay: false,
+			address_index: false,
+			spent_index: false,
+			keep_blocks: None,
+			undo_horizon: None,
+			chain_history: None,
+			disk: None,
 		}
 	}
 
+	/// Store raw blocks in given flat files.
+	pub fn with_block_files(mut self, block_files: FlatFileStore) -> Self {
+		self.block_files = Some(Arc::new(block_files));
+		self
+	}
+
+	/// Maintain address index of blocks that are canonized from now on.
+	pub fn with_address_index(mut self) -> Self {
+		self.address_index = true;
+		self
+	}
+
+	/// Maintain spent index of blocks that are canonized from now on.
+	pub fn with_spent_index(mut self) -> Self {
+		self.spent_index = true;
+		self
+	}
+
+	/// Prune data of canon blocks, except for `keep_blocks` (at least `MIN_BLOCKS_TO_KEEP`) last blocks.
+	pub fn with_pruning(mut self, keep_blocks: u32) -> Self {
+		self.keep_blocks = Some(::std::cmp::max(keep_blocks, MIN_BLOCKS_TO_KEEP));
+		self
+	}
+
+	/// Prune undo data of canon blocks, except for `undo_horizon` (at least `MIN_UNDO_HORIZON`) last blocks.
+	pub fn with_undo_horizon(mut self, undo_horizon: u32) -> Self {
+		self.undo_horizon = Some(::std::cmp::max(undo_horizon, MIN_UNDO_HORIZON));
+		self
+	}
+
+	/// Maintain chain history trees (ZIP-221) of Heartwood && Canopy blocks of given network, so that
+	/// commitments of their headers could be verified. Trees aren't maintained for blocks, inserted before
+	/// trees were maintained, && for their descendants of the same network upgrade.
+	pub fn with_chain_history(mut self, consensus: ConsensusParams) -> Self {
+		self.chain_history = Some(consensus);
+		self
+	}
+
 	pub fn best_block(&self) -> BestBlock {
 		self.best_block.read().clone()
 	}
 
+	pub fn read_snapshot(&self) -> ReadSnapshot {
+		let guard = self.snapshot_lock.read();
+		ReadSnapshot::new(self.best_block(), self, guard)
+	}
+
 	pub fn fork(&self, side_chain: SideChainOrigin) -> Result<ForkChainDatabase<T>, Error> {
-		let overlay = BlockChainDatabase::open(OverlayDatabase::new(&self.db));
+		let mut overlay = BlockChainDatabase::open(OverlayDatabase::new(&self.db));
+		overlay.block_files = self.block_files.clone();
+		overlay.fork_overlay = true;
+		overlay.address_index = self.address_index;
+		overlay.spent_index = self.spent_index;
+		overlay.chain_history = self.chain_history.clone();
 
 		for hash in side_chain.decanonized_route.into_iter().rev() {
 			let decanonized_hash = overlay.decanonize()?;
@@ -184,10 +393,11 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		Ok(fork)
 	}
 
-	pub fn switch_to_fork(&self, fork: ForkChainDatabase<T>) -> Result<(), Error> {
+	pub fn switch_to_fork<F>(&self, fork: &F) -> Result<(), Error> where F: ForkChain + ?Sized {
+		let _snapshot_lock = self.snapshot_lock.write();
 		let mut best_block = self.best_block.write();
-		*best_block = fork.blockchain.best_block.read().clone();
-		fork.blockchain.db.flush().map_err(Error::DatabaseError)
+		*best_block = fork.store().best_block();
+		fork.flush()
 	}
 
 	pub fn block_origin(&self, header: &IndexedBlockHeader) -> Result<BlockOrigin, Error> {
@@ -265,13 +475,46 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 				.expect(&format!("Corrupted database - no sapling root for block {}", parent_hash))
 		};
 
-		let sapling_tree_root = block.header.raw.final_sapling_root;
 		let mut update = DBTransaction::new();
-		update.insert(KeyValue::BlockHeader(*block.hash(), block.header.raw));
+		update.insert(KeyValue::BlockHeader(*block.hash(), block.header.raw.clone()));
 		let tx_hashes = block.transactions.iter().map(|tx| tx.hash).collect::<Vec<_>>();
 		update.insert(KeyValue::BlockTransactions(block.header.hash, List::from(tx_hashes)));
+		let shielded_stats = ShieldedStats::from_transactions(block.transactions.iter().map(|tx| &tx.raw));
+		update.insert(KeyValue::ShieldedStats(block.header.hash, shielded_stats));
 
-		for tx in block.transactions.into_iter() {
+		let parent_value_pools = if parent_hash.is_zero() {
+			ValuePools::default()
+		} else {
+			self.block_value_pools(&parent_hash)
+				.expect(&format!("Corrupted database - no value pools for block {}", parent_hash))
+		};
+		// blocks are verified before insertion => pools could only be negative if the block is invalid
+		let value_pools = parent_value_pools.apply_transactions(block.transactions.iter().map(|tx| &tx.raw))
+			.map_err(|pool| Error::DatabaseError(format!("{} value pool is negative after block {}", pool.name(), block.header.hash.to_reversed_str())))?;
+		update.insert(KeyValue::ValuePools(block.header.hash, value_pools));
+
+		// side chain blocks are kept in the database, so that blocks, which are never canonized, don't
+		// occupy raw blocks files
+		let block_files = match self.block_files {
+			Some(ref block_files) if !self.fork_overlay && parent_hash == self.best_block.read().hash => Some(block_files),
+			_ => None,
+		};
+		let transaction_positions = match block_files {
+			Some(block_files) => {
+				let (raw_block, transactions) = serialize_block(&block);
+				let block_position = block_files.append(&raw_block)
+					.map_err(|err| Error::DatabaseError(err.to_string()))?;
+				// raw block must be on the disk before the database refers to it
+				block_files.flush().map_err(|err| Error::DatabaseError(err.to_string()))?;
+				update.insert(KeyValue::BlockPosition(block.header.hash, block_position));
+				Some(transactions.into_iter()
+					.map(|(offset, length)| block_position.slice(offset, length))
+					.collect::<Vec<_>>())
+			},
+			None => None,
+		};
+
+		for (tx_index, tx) in block.transactions.iter().enumerate() {
 
 			if let Some(ref js) = tx.raw.join_split {
 				for js_descriptor in js.descriptions.iter() {
@@ -293,7 +536,10 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 				}
 			}
 
-			update.insert(KeyValue::Transaction(tx.hash, tx.raw));
+			match transaction_positions {
+				Some(ref positions) => update.insert(KeyValue::TransactionPosition(tx.hash, positions[tx_index])),
+				None => update.insert(KeyValue::Transaction(tx.hash, tx.raw.clone())),
+			}
 		}
 
 		let sprout_tree_root = sprout_tree_state.root();
@@ -301,11 +547,58 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		update.insert(KeyValue::SproutTreeState(sprout_tree_root, sprout_tree_state));
 
 		// TODO: possible optimization is not to store sapling trees until sapling is activated
+		let sapling_tree_root = sapling_tree_state.root();
+		update.insert(KeyValue::SaplingBlockRoot(block.header.hash, sapling_tree_root));
 		update.insert(KeyValue::SaplingTreeState(sapling_tree_root, sapling_tree_state));
 
+		if let Some(history_tree) = self.next_history_tree(&block, sapling_tree_root) {
+			update.insert(KeyValue::HistoryTree(block.header.hash, history_tree));
+		}
+
 		self.db.write(update).map_err(Error::DatabaseError)
 	}
 
+	/// Chain history tree after the block with given sapling root. None if the tree of the block isn't
+	/// maintained: the tree of the parent is unknown, or the block isn't Heartwood or Canopy block. Trees
+	/// of NU5 blocks commit to orchard note commitment tree roots, which aren't tracked.
+	fn next_history_tree(&self, block: &IndexedBlock, sapling_root: H256) -> Option<HistoryTree> {
+		let consensus = self.chain_history.as_ref()?;
+		let parent_hash = &block.header.raw.previous_header_hash;
+		let height = if parent_hash.is_zero() {
+			0
+		} else {
+			self.block_height(parent_hash)? + 1
+		};
+
+		match consensus.block_commitment(&block.header.raw, height) {
+			BlockCommitment::ChainHistoryRoot(_) => (),
+			_ => return None,
+		}
+
+		// history tree is reset at every network upgrade
+		let mut history_tree = if consensus.is_activation_height(height) {
+			HistoryTree::new(consensus.consensus_branch_id(height))
+		} else {
+			self.history_tree_at_block(parent_hash)?
+		};
+		history_tree.append(HistoryNode::leaf(block, height, sapling_root, None));
+		Some(history_tree)
+	}
+
+	/// Height of the known (canon or side chain) block.
+	fn block_height(&self, hash: &H256) -> Option<u32> {
+		let mut hash = *hash;
+		let mut fork_len = 0;
+		loop {
+			if let Some(number) = self.block_number(&hash) {
+				return Some(number + fork_len);
+			}
+
+			hash = self.block_header(hash.into())?.raw.previous_header_hash;
+			fork_len += 1;
+		}
+	}
+
 	/// Rollbacks single best block.
 	fn rollback_best(&self) -> Result<H256, Error> {
 		let best_block_hash = self.best_block.read().hash.clone();
@@ -319,8 +612,12 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		let mut update = DBTransaction::new();
 		update.delete(Key::BlockHeader(decanonized_hash.clone()));
 		update.delete(Key::BlockTransactions(decanonized_hash.clone()));
+		update.delete(Key::ShieldedStats(decanonized_hash.clone()));
+		update.delete(Key::BlockPosition(decanonized_hash.clone()));
+		update.delete(Key::HistoryTree(decanonized_hash.clone()));
 		for tx_hash in tx_to_decanonize {
 			update.delete(Key::Transaction(tx_hash));
+			update.delete(Key::TransactionPosition(tx_hash));
 		}
 
 		self.db.write(update).map_err(Error::DatabaseError)?;
@@ -333,6 +630,7 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 	/// Block must be already inserted into db, and its parent must be current best block.
 	/// Updates meta data.
 	pub fn canonize(&self, hash: &H256) -> Result<(), Error> {
+		let _snapshot_lock = self.snapshot_lock.write();
 		let mut best_block = self.best_block.write();
 		let block = match self.block(hash.clone().into()) {
 			Some(block) => block,
@@ -370,44 +668,64 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_HASH, serialize(&new_best_block.hash)));
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_NUMBER, serialize(&new_best_block.number)));
 
+		// root of the sapling tree becomes a valid anchor, unless it is the root of some earlier canon block
+		let sapling_root = self.sapling_block_root(hash)
+			.expect(&format!("Corrupted database - no sapling root for block {}", hash));
+		if self.sapling_anchor_height(&sapling_root).is_none() {
+			update.insert(KeyValue::SaplingAnchor(sapling_root, new_best_block.number));
+		}
+
+		let parent_hash = block.header.raw.previous_header_hash;
+		let mut undo = if parent_hash.is_zero() {
+			BlockUndo {
+				nullifiers: Vec::new(),
+				spent_metas: Vec::new(),
+				parent_sprout_root: SproutTreeState::new().root(),
+				parent_sapling_root: SaplingTreeState::new().root(),
+				parent_value_pools: ValuePools::default(),
+			}
+		} else {
+			BlockUndo {
+				nullifiers: Vec::new(),
+				spent_metas: Vec::new(),
+				parent_sprout_root: self.sprout_block_root(&parent_hash)
+					.expect(&format!("Corrupted database - no sprout root for block {}", parent_hash)),
+				parent_sapling_root: self.sapling_block_root(&parent_hash)
+					.expect(&format!("Corrupted database - no sapling root for block {}", parent_hash)),
+				parent_value_pools: self.block_value_pools(&parent_hash)
+					.expect(&format!("Corrupted database - no value pools for block {}", parent_hash)),
+			}
+		};
+
+		// metas of earlier transactions with the same hashes are overwritten
+		for tx in &block.transactions {
+			if let Some(meta) = self.transaction_meta(&tx.hash) {
+				undo.spent_metas.push((tx.hash.clone(), meta));
+			}
+		}
+
 		let mut modified_meta: HashMap<H256, TransactionMeta> = HashMap::new();
+		let mut revealed_nullifiers = HashSet::new();
 		if let Some(tx) = block.transactions.first() {
 			let meta = TransactionMeta::new_coinbase(new_best_block.number, tx.raw.outputs.len());
 			modified_meta.insert(tx.hash.clone(), meta);
 		}
 
-		for tx in block.transactions.iter().skip(1) {
-			modified_meta.insert(tx.hash.clone(), TransactionMeta::new(new_best_block.number, tx.raw.outputs.len()));
-
-			if let Some(ref js) = tx.raw.join_split {
-				for js_descriptor in js.descriptions.iter() {
-					for nullifier in &js_descriptor.nullifiers[..] {
-						let nullifier_key = EpochRef::new(
-							EpochTag::Sprout,
-							H256::from(&nullifier[..])
-						);
-						if self.contains_nullifier(nullifier_key) {
-							error!(target: "db", "Duplicate sprout nullifer during canonization: {:?}", nullifier_key);
-							return Err(Error::CannotCanonize);
-						}
-						update.insert(KeyValue::Nullifier(nullifier_key));
-					}
+		// coinbase transaction could reveal (dummy) Orchard nullifiers too
+		for tx in &block.transactions {
+			for nullifier_key in transaction_nullifiers(&tx.raw) {
+				// nullifier could be revealed either earlier in the chain, or by previous transaction of this block
+				if self.contains_nullifier(nullifier_key) || !revealed_nullifiers.insert(nullifier_key) {
+					error!(target: "db", "Duplicate nullifer during canonization: {:?}", nullifier_key);
+					return Err(Error::CannotCanonize);
 				}
+				update.insert(KeyValue::Nullifier(nullifier_key));
+				undo.nullifiers.push(nullifier_key);
 			}
+		}
 
-			if let Some(ref sapling) = tx.raw.sapling {
-				for spend in &sapling.spends {
-					let nullifier_key = EpochRef::new(
-						EpochTag::Sapling,
-						H256::from(&spend.nullifier[..])
-					);
-					if self.contains_nullifier(nullifier_key) {
-						error!(target: "db", "Duplicate sapling nullifer during canonization: {:?}", nullifier_key);
-						return Err(Error::CannotCanonize);
-					}
-					update.insert(KeyValue::Nullifier(nullifier_key));
-				}
-			}
+		for tx in block.transactions.iter().skip(1) {
+			modified_meta.insert(tx.hash.clone(), TransactionMeta::new(new_best_block.number, tx.raw.outputs.len()));
 
 			for input in &tx.raw.inputs {
 				use std::collections::hash_map::Entry;
@@ -429,6 +747,7 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 								);
 								Error::CannotCanonize
 							})?;
+						undo.spent_metas.push((input.previous_output.hash.clone(), meta.clone()));
 						meta.denote_used(input.previous_output.index as usize);
 						entry.insert(meta);
 					}
@@ -440,12 +759,27 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 			update.insert(KeyValue::TransactionMeta(hash, meta));
 		}
 
+		// previous outputs are either in the block itself, or in the canon chain
+		let fee_stats = {
+			let output_provider = DuplexTransactionOutputProvider::new(&block, self);
+			FeeStats::from_transactions(&output_provider, block.transactions.iter()
+				.enumerate()
+				.skip(1)
+				.map(|(tx_index, tx)| (tx_index, &tx.raw)))
+		};
+		update.insert(KeyValue::FeeStats(new_best_block.hash.clone(), fee_stats));
+		update.insert(KeyValue::BlockUndo(new_best_block.hash.clone(), undo));
+		self.update_address_index(&mut update, &block, new_best_block.number, true);
+		self.update_spent_index(&mut update, &block, new_best_block.number, true);
+		self.append_chain_event(&mut update, ChainEventKind::Connected, &block, new_best_block.number);
+
 		self.db.write(update).map_err(Error::DatabaseError)?;
 		*best_block = new_best_block;
 		Ok(())
 	}
 
 	pub fn decanonize(&self) -> Result<H256, Error> {
+		let _snapshot_lock = self.snapshot_lock.write();
 		let mut best_block = self.best_block.write();
 		let block = match self.block(best_block.hash.clone().into()) {
 			Some(block) => block,
@@ -472,79 +806,552 @@ impl<T> BlockChainDatabase<T> where T: KeyValueDatabase {
 		let mut update = DBTransaction::new();
 		update.delete(Key::BlockHash(block_number));
 		update.delete(Key::BlockNumber(block_hash.clone()));
+		update.delete(Key::FeeStats(block_hash.clone()));
+		update.delete(Key::BlockUndo(block_hash.clone()));
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_HASH, serialize(&new_best_block.hash)));
 		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_NUMBER, serialize(&new_best_block.number)));
 
-		let mut modified_meta: HashMap<H256, TransactionMeta> = HashMap::new();
-		for tx in block.transactions.iter().skip(1) {
-			if let Some(ref js) = tx.raw.join_split {
-				for js_descriptor in js.descriptions.iter() {
-					for nullifier in &js_descriptor.nullifiers[..] {
-						let nullifier_key = EpochRef::new(
-							EpochTag::Sprout,
-							H256::from(&nullifier[..])
-						);
-						if !self.contains_nullifier(nullifier_key) {
-							error!(target: "db", "cannot decanonize, no sprout nullifier: {:?}", nullifier_key);
-							return Err(Error::CannotDecanonize);
-						}
-						update.delete(Key::Nullifier(nullifier_key));
-					}
-				}
+		// undo data of blocks below the undo horizon is pruned
+		let undo = match self.get(Key::BlockUndo(block_hash.clone())).and_then(Value::as_block_undo) {
+			Some(undo) => undo,
+			None => {
+				error!(target: "db", "Block undo data is not found during decanonization: {}", block_hash.reversed());
+				return Err(Error::CannotDecanonize);
+			},
+		};
+
+		for nullifier_key in undo.nullifiers {
+			update.delete(Key::Nullifier(nullifier_key));
+		}
+
+		let sapling_root = self.sapling_block_root(&block_hash)
+			.expect(&format!("Corrupted database - no sapling root for block {}", block_hash));
+		if self.sapling_anchor_height(&sapling_root) == Some(block_number) {
+			update.delete(Key::SaplingAnchor(sapling_root));
+		}
+
+		// parent data could have been pruned while the block was canonized
+		let parent_hash = block.header.raw.previous_header_hash;
+		if !parent_hash.is_zero() {
+			update.insert(KeyValue::SproutBlockRoot(parent_hash, undo.parent_sprout_root));
+			update.insert(KeyValue::SaplingBlockRoot(parent_hash, undo.parent_sapling_root));
+			update.insert(KeyValue::ValuePools(parent_hash, undo.parent_value_pools));
+		}
+
+		self.update_address_index(&mut update, &block, block_number, false);
+		self.update_spent_index(&mut update, &block, block_number, false);
+		self.append_chain_event(&mut update, ChainEventKind::Disconnected, &block, block_number);
+
+		for tx in block.transactions {
+			update.delete(Key::TransactionMeta(tx.hash));
+		}
+
+		// restored after metas of the block transactions are deleted, because earlier transaction could have the same hash
+		for (hash, meta) in undo.spent_metas {
+			update.insert(KeyValue::TransactionMeta(hash, meta));
+		}
+
+		self.db.write(update).map_err(Error::DatabaseError)?;
+		*best_block = new_best_block;
+		Ok(block_hash)
+	}
+
+	/// Block, read from raw blocks files if it is stored there (transactions are read without the transaction
+	/// index then).
+	pub fn stored_block(&self, hash: &H256) -> Option<IndexedBlock> {
+		match self.raw_block(hash) {
+			Some(raw_block) => Some(deserialize::<_, ChainBlock>(&*raw_block)
+				.expect(&format!("Corrupted block files - invalid block {}", hash))
+				.into()),
+			None => self.block(hash.clone().into()),
+		}
+	}
+
+	/// Settings && current size of the database.
+	pub fn database_info(&self) -> DatabaseInfo {
+		let mut info = DatabaseInfo::default();
+		if let Some((ref path, ref settings)) = self.disk {
+			info.on_disk = true;
+			// raw blocks files are in the subdirectory && aren't counted here
+			info.size = fs::read_dir(path).into_iter()
+				.flat_map(|entries| entries)
+				.filter_map(|entry| entry.and_then(|entry| entry.metadata()).ok())
+				.filter(|metadata| metadata.is_file())
+				.map(|metadata| metadata.len())
+				.sum();
+			info.cache_size = settings.cache_size;
+			info.write_buffer_size = settings.write_buffer_size;
+			info.compaction_style = match settings.compaction.style {
+				CompactionStyle::Level => "level".into(),
+				CompactionStyle::Universal => "universal".into(),
+			};
+			info.max_open_files = settings.max_open_files;
+		}
+
+		if let Some(ref block_files) = self.block_files {
+			let files = block_files.files().unwrap_or_default();
+			info.block_files = files.len();
+			info.block_files_size = files.into_iter()
+				.filter_map(|number| fs::metadata(block_files.file_path(number)).ok())
+				.map(|metadata| metadata.len())
+				.sum();
+		}
+
+		info
+	}
+
+	/// Raw block bytes, if block is stored in flat files.
+	pub fn raw_block(&self, hash: &H256) -> Option<Bytes> {
+		let block_files = self.block_files.as_ref()?;
+		let position = self.get(Key::BlockPosition(*hash)).and_then(Value::as_block_position)?;
+		read_block_files(block_files, &position)
+			.unwrap_or_else(|err| panic!("Corrupted block files - failed to read block {}: {}", hash, err))
+	}
+
+	/// All canonized nullifiers of given epoch. Reads the whole nullifiers set, so it should only be used for diagnostics.
+	pub fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, Error> {
+		self.db.nullifiers(epoch).map_err(Error::DatabaseError)
+	}
+
+	/// Number of the first canon block, which data isn't pruned.
+	pub fn pruned_height(&self) -> u32 {
+		self.get(Key::Meta(KEY_PRUNED_HEIGHT))
+			.and_then(Value::as_meta)
+			.map(|height| deserialize(&*height).expect("Inconsistent DB. Invalid pruned height."))
+			.unwrap_or(0)
+	}
+
+	/// Prune data of old canon blocks if pruning is enabled && enough blocks have been canonized
+	/// since the previous pruning.
+	pub fn prune(&self) -> Result<(), Error> {
+		let keep_blocks = match self.keep_blocks {
+			Some(keep_blocks) => keep_blocks,
+			None => return Ok(()),
+		};
+
+		let prune_to = (self.best_block.read().number + 1).saturating_sub(keep_blocks);
+		if prune_to < self.pruned_height() + PRUNE_BATCH_SIZE {
+			return Ok(());
+		}
+
+		self.prune_blocks(prune_to)
+	}
+
+	/// Prune data of canon blocks below given height. Headers, transaction metas, nullifiers && tree states
+	/// of pruned blocks are kept, but tree roots of pruned blocks are forgotten (so their tree states can't
+	/// be queried by block anymore, while still being valid anchors of shielded spends). Transactions are
+	/// kept while they have unspent outputs, or outputs spent by blocks that are not pruned (these are still
+	/// could be decanonized). Raw blocks files, containing pruned blocks only, are removed. Best block is
+	/// never pruned.
+	pub fn prune_blocks(&self, to: u32) -> Result<(), Error> {
+		let _snapshot_lock = self.snapshot_lock.write();
+		let best_block_number = self.best_block.read().number;
+		let from = self.pruned_height();
+		let to = ::std::cmp::min(to, best_block_number);
+		if to <= from {
+			return Ok(());
+		}
+
+		let canon_hash = |number: u32| self.block_hash(number)
+			.expect(&format!("Corrupted database - no canon block at height {}", number));
+
+		// outputs, spent by blocks that are not pruned, must be available if these blocks are decanonized
+		let mut recently_spent = HashSet::new();
+		for number in to..best_block_number + 1 {
+			for tx in self.block_transactions(canon_hash(number).into()).into_iter().skip(1) {
+				recently_spent.extend(tx.raw.inputs.into_iter().map(|input| input.previous_output.hash));
 			}
+		}
 
-			if let Some(ref sapling) = tx.raw.sapling {
-				for spend in &sapling.spends {
-					let nullifier_key = EpochRef::new(
-						EpochTag::Sapling,
-						H256::from(&spend.nullifier[..])
-					);
-					if !self.contains_nullifier(nullifier_key) {
-						error!(target: "db", "cannot decanonize, no sapling nullifier: {:?}", nullifier_key);
-						return Err(Error::CannotDecanonize);
-					}
-					update.delete(Key::Nullifier(nullifier_key));
+		// transactions of pruned blocks && transactions, spent by pruned blocks, are candidates for removal
+		let mut update = DBTransaction::new();
+		let mut candidates = HashSet::new();
+		for number in from..to {
+			let hash = canon_hash(number);
+			for tx in self.block_transactions(hash.into()).into_iter() {
+				candidates.extend(tx.raw.inputs.into_iter().map(|input| input.previous_output.hash));
+				candidates.insert(tx.hash);
+			}
+
+			update.delete(Key::BlockTransactions(hash));
+			update.delete(Key::BlockPosition(hash));
+			update.delete(Key::BlockUndo(hash));
+			update.delete(Key::SproutBlockRoot(hash));
+			update.delete(Key::SaplingBlockRoot(hash));
+			// pruned block can't be disconnected => history tree of its parent isn't required
+			if number != 0 {
+				update.delete(Key::HistoryTree(canon_hash(number - 1)));
+			}
+		}
+
+		for tx_hash in candidates {
+			match self.transaction_meta(&tx_hash) {
+				Some(ref meta) if meta.height() < to && meta.is_fully_spent() && !recently_spent.contains(&tx_hash) => {
+					update.delete(Key::Transaction(tx_hash));
+					update.delete(Key::TransactionPosition(tx_hash));
+				},
+				Some(ref meta) if meta.height() < to && self.get(Key::TransactionPosition(tx_hash)).is_some() => {
+					// raw blocks files are going to be removed => transaction is moved to the database
+					let tx = self.read_transaction(&tx_hash)
+						.expect(&format!("Corrupted block files - no transaction {}", tx_hash));
+					update.insert(KeyValue::Transaction(tx_hash, tx));
+					update.delete(Key::TransactionPosition(tx_hash));
+				},
+				_ => (),
+			}
+		}
+
+		update.insert(KeyValue::Meta(KEY_PRUNED_HEIGHT, serialize(&to)));
+		self.db.write(update).map_err(Error::DatabaseError)?;
+
+		// canon blocks are appended to raw blocks files in order => files before the file of the first
+		// not pruned block contain pruned (or decanonized) blocks only
+		let block_files = match self.block_files {
+			Some(ref block_files) => block_files,
+			None => return Ok(()),
+		};
+		let first_position = self.get(Key::BlockPosition(canon_hash(to)))
+			.and_then(Value::as_block_position);
+		if let Some(first_position) = first_position {
+			// moved transactions must be in the database before their files are removed
+			self.db.persist().map_err(Error::DatabaseError)?;
+			block_files.remove_before(first_position.file)
+				.map_err(|err| Error::DatabaseError(err.to_string()))?;
+		}
+
+		info!(target: "db", "Pruned data of blocks {}..{}", from, to);
+		Ok(())
+	}
+
+	/// Number of the first canon block, which undo data isn't pruned.
+	pub fn undo_pruned_height(&self) -> u32 {
+		let undo_pruned_height = self.get(Key::Meta(KEY_UNDO_PRUNED_HEIGHT))
+			.and_then(Value::as_meta)
+			.map(|height| deserialize(&*height).expect("Inconsistent DB. Invalid undo pruned height."))
+			.unwrap_or(0);
+		// undo data of pruned blocks is pruned alongside the blocks
+		::std::cmp::max(undo_pruned_height, self.pruned_height())
+	}
+
+	/// Prune undo data of old canon blocks if undo horizon is set && enough blocks have been canonized
+	/// since the previous pruning.
+	pub fn prune_undo(&self) -> Result<(), Error> {
+		let undo_horizon = match self.undo_horizon {
+			Some(undo_horizon) => undo_horizon,
+			None => return Ok(()),
+		};
+
+		let prune_to = (self.best_block.read().number + 1).saturating_sub(undo_horizon);
+		if prune_to < self.undo_pruned_height() + PRUNE_BATCH_SIZE {
+			return Ok(());
+		}
+
+		self.prune_undo_blocks(prune_to)
+	}
+
+	/// Prune undo data of canon blocks below given height, together with history trees of their parents.
+	/// Everything else is kept. Undo data is removed in batches of `PRUNE_BATCH_SIZE` blocks, so the first
+	/// pruning of an old database doesn't hold the whole update in memory. Undo data of the best block is
+	/// never pruned.
+	pub fn prune_undo_blocks(&self, to: u32) -> Result<(), Error> {
+		let _snapshot_lock = self.snapshot_lock.write();
+		let from = self.undo_pruned_height();
+		let to = ::std::cmp::min(to, self.best_block.read().number);
+		if to <= from {
+			return Ok(());
+		}
+
+		let mut batch_from = from;
+		while batch_from < to {
+			let batch_to = ::std::cmp::min(batch_from + PRUNE_BATCH_SIZE, to);
+			let mut update = DBTransaction::new();
+			for number in batch_from..batch_to {
+				let hash = self.block_hash(number)
+					.expect(&format!("Corrupted database - no canon block at height {}", number));
+				update.delete(Key::BlockUndo(hash));
+				// block without undo data can't be disconnected => history tree of its parent isn't required
+				if let Some(parent_hash) = number.checked_sub(1).and_then(|parent| self.block_hash(parent)) {
+					update.delete(Key::HistoryTree(parent_hash));
 				}
 			}
+			update.insert(KeyValue::Meta(KEY_UNDO_PRUNED_HEIGHT, serialize(&batch_to)));
+			self.db.write(update).map_err(Error::DatabaseError)?;
+			batch_from = batch_to;
+		}
 
-			for input in &tx.raw.inputs {
-				use std::collections::hash_map::Entry;
+		info!(target: "db", "Pruned undo data of blocks {}..{}", from, to);
+		Ok(())
+	}
 
-				match modified_meta.entry(input.previous_output.hash.clone()) {
-					Entry::Occupied(mut entry) => {
-						let meta = entry.get_mut();
-						meta.denote_unused(input.previous_output.index as usize);
-					},
-					Entry::Vacant(entry) => {
-						let mut meta = self.transaction_meta(&input.previous_output.hash)
-							.ok_or_else(|| {
-								error!(
-									target: "db",
-									"Cannot find tx meta during decanonization of tx {}: {}/{}",
-									tx.hash.reversed(),
-									input.previous_output.hash.reversed(),
-									input.previous_output.index,
-								);
-								Error::CannotDecanonize
-							})?;
-						meta.denote_unused(input.previous_output.index as usize);
-						entry.insert(meta);
-					}
+	/// Write snapshot of the chain state after the best block: headers of all canon blocks, transactions
+	/// with unspent outputs, nullifiers, note commitment trees of all canon blocks, value pools && the chain
+	/// history tree (if it is maintained). Pruned database can't be exported.
+	pub fn export_snapshot<W>(&self, writer: W) -> Result<SnapshotInfo, Error> where W: io::Write {
+		let _snapshot_lock = self.snapshot_lock.read();
+		if self.pruned_height() != 0 {
+			return Err(Error::DatabaseError("Pruned database can't be exported".into()));
+		}
+
+		let best_block = self.best_block();
+		let write_error = |err: io::Error| Error::DatabaseError(format!("Failed to write snapshot: {}", err));
+		let no_tree_state = |number: u32| Error::DatabaseError(format!("No note commitment tree of block {}", number));
+		let mut writer = SnapshotWriter::new(writer, best_block.number, &best_block.hash).map_err(write_error)?;
+
+		// tree is written when the root changes => every root of canon block is a valid anchor after import
+		let (mut sprout_root, mut sapling_root) = (None, None);
+		for number in 0..best_block.number + 1 {
+			let block = self.block(number.into())
+				.expect(&format!("Corrupted database - no canon block at height {}", number));
+			writer.write(&SnapshotRecord::Header(block.header.raw.clone())).map_err(write_error)?;
+
+			let block_sprout_root = self.sprout_block_root(&block.header.hash).ok_or_else(|| no_tree_state(number))?;
+			if sprout_root != Some(block_sprout_root) {
+				let tree = self.sprout_tree_at(&block_sprout_root).ok_or_else(|| no_tree_state(number))?;
+				writer.write(&SnapshotRecord::SproutTree(tree)).map_err(write_error)?;
+				sprout_root = Some(block_sprout_root);
+			}
+
+			let block_sapling_root = self.sapling_block_root(&block.header.hash).ok_or_else(|| no_tree_state(number))?;
+			if sapling_root != Some(block_sapling_root) {
+				let tree = self.sapling_tree_at(&block_sapling_root).ok_or_else(|| no_tree_state(number))?;
+				writer.write(&SnapshotRecord::SaplingTree(tree)).map_err(write_error)?;
+				sapling_root = Some(block_sapling_root);
+			}
+
+			for tx in block.transactions {
+				let meta = self.transaction_meta(&tx.hash)
+					.expect(&format!("Corrupted database - no meta of canon transaction {}", tx.hash));
+				if !meta.is_fully_spent() {
+					writer.write(&SnapshotRecord::Transaction(meta, tx.raw)).map_err(write_error)?;
 				}
 			}
+
+			if number % 10_000 == 0 {
+				info!(target: "db", "Exported snapshot up to block {}", number);
+			}
 		}
 
-		for (hash, meta) in modified_meta {
-			update.insert(KeyValue::TransactionMeta(hash, meta));
+		for epoch in &[EpochTag::Sprout, EpochTag::Sapling, EpochTag::Orchard] {
+			for nullifier in self.nullifiers(*epoch)? {
+				writer.write(&SnapshotRecord::Nullifier(EpochRef::new(*epoch, nullifier))).map_err(write_error)?;
+			}
 		}
 
-		for tx in block.transactions {
-			update.delete(Key::TransactionMeta(tx.hash));
+		let value_pools = self.block_value_pools(&best_block.hash)
+			.ok_or_else(|| Error::DatabaseError(format!("No value pools for block {}", best_block.hash)))?;
+		writer.write(&SnapshotRecord::ValuePools(value_pools)).map_err(write_error)?;
+
+		if let Some(history_tree) = self.history_tree_at_block(&best_block.hash) {
+			writer.write(&SnapshotRecord::HistoryTree(history_tree)).map_err(write_error)?;
 		}
 
+		Ok(SnapshotInfo {
+			height: best_block.number,
+			block_hash: best_block.hash,
+			digest: writer.finish().map_err(write_error)?,
+		})
+	}
+
+	/// Initialize empty database with the chain state from the snapshot. Blocks before the snapshot block
+	/// are imported as pruned. Best block is only updated after the whole snapshot is read && its digest is
+	/// verified, but records are written as they're read, so database must be removed if import fails.
+	pub fn import_snapshot<R>(&self, reader: R, genesis_hash: &H256) -> Result<SnapshotInfo, Error> where R: io::Read {
+		let _snapshot_lock = self.snapshot_lock.write();
+		let mut best_block = self.best_block.write();
+		if self.block_hash(0).is_some() {
+			return Err(Error::DatabaseError("Snapshot can only be imported into the empty database".into()));
+		}
+
+		let read_error = |err: ReaderError| Error::InvalidSnapshot(format!("{:?}", err));
+		let mut reader = SnapshotReader::new(reader).map_err(read_error)?;
+		let (height, block_hash) = (reader.height(), reader.block_hash().clone());
+
+		let (mut last_header, mut sprout_root, mut sapling_root, mut value_pools) = (None, None, None, None);
+		let mut history_tree = None;
+		let mut update = DBTransaction::new();
+		let mut pending_records = 0;
+		while let Some(record) = reader.read().map_err(read_error)? {
+			match record {
+				SnapshotRecord::Header(header) => {
+					let header = IndexedBlockHeader::from_raw(header);
+					let number = match last_header {
+						None if header.hash == *genesis_hash => 0,
+						Some((number, hash)) if header.raw.previous_header_hash == hash => number + 1,
+						_ => return Err(Error::InvalidSnapshot(format!("Unexpected header {}", header.hash.reversed()))),
+					};
+					update.insert(KeyValue::BlockHash(number, header.hash));
+					update.insert(KeyValue::BlockNumber(header.hash, number));
+					update.insert(KeyValue::BlockHeader(header.hash, header.raw));
+					last_header = Some((number, header.hash));
+				},
+				SnapshotRecord::SproutTree(tree) => {
+					let root = tree.root();
+					update.insert(KeyValue::SproutTreeState(root, tree));
+					sprout_root = Some(root);
+				},
+				SnapshotRecord::SaplingTree(tree) => {
+					// tree is written after the header of the block, which has changed the root
+					let root = tree.root();
+					let number = last_header.map(|(number, _)| number)
+						.ok_or_else(|| Error::InvalidSnapshot("Note commitment tree before headers".into()))?;
+					update.insert(KeyValue::SaplingTreeState(root, tree));
+					update.insert(KeyValue::SaplingAnchor(root, number));
+					sapling_root = Some(root);
+				},
+				SnapshotRecord::Transaction(meta, tx) => {
+					let tx = IndexedTransaction::from_raw(tx);
+					update.insert(KeyValue::TransactionMeta(tx.hash, meta));
+					update.insert(KeyValue::Transaction(tx.hash, tx.raw));
+				},
+				SnapshotRecord::Nullifier(nullifier) => update.insert(KeyValue::Nullifier(nullifier)),
+				SnapshotRecord::ValuePools(pools) => value_pools = Some(pools),
+				SnapshotRecord::HistoryTree(tree) => history_tree = Some(tree),
+			}
+
+			pending_records += 1;
+			if pending_records == SNAPSHOT_IMPORT_BATCH_SIZE {
+				self.db.write(mem::replace(&mut update, DBTransaction::new())).map_err(Error::DatabaseError)?;
+				pending_records = 0;
+			}
+		}
+
+		if last_header != Some((height, block_hash)) {
+			return Err(Error::InvalidSnapshot("Headers do not end with the snapshot block".into()));
+		}
+		let (sprout_root, sapling_root) = match (sprout_root, sapling_root) {
+			(Some(sprout_root), Some(sapling_root)) => (sprout_root, sapling_root),
+			_ => return Err(Error::InvalidSnapshot("Note commitment trees are missing".into())),
+		};
+
+		// trees of the snapshot block are the last trees of the snapshot
+		update.insert(KeyValue::SproutBlockRoot(block_hash, sprout_root));
+		update.insert(KeyValue::SaplingBlockRoot(block_hash, sapling_root));
+		let value_pools = value_pools.ok_or_else(|| Error::InvalidSnapshot("Value pools are missing".into()))?;
+		update.insert(KeyValue::ValuePools(block_hash, value_pools));
+		if let Some(history_tree) = history_tree {
+			update.insert(KeyValue::HistoryTree(block_hash, history_tree));
+		}
+
+		let new_best_block = BestBlock {
+			number: height,
+			hash: block_hash,
+		};
+		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_HASH, serialize(&new_best_block.hash)));
+		update.insert(KeyValue::Meta(KEY_BEST_BLOCK_NUMBER, serialize(&new_best_block.number)));
+		update.insert(KeyValue::Meta(KEY_PRUNED_HEIGHT, serialize(&(height + 1))));
 		self.db.write(update).map_err(Error::DatabaseError)?;
+		self.db.persist().map_err(Error::DatabaseError)?;
 		*best_block = new_best_block;
-		Ok(block_hash)
+
+		Ok(SnapshotInfo {
+			height: height,
+			block_hash: block_hash,
+			digest: reader.digest().cloned().expect("all records are read; qed"),
+		})
+	}
+
+	/// Raw transaction bytes, if transaction is stored in flat files.
+	fn raw_transaction(&self, hash: &H256) -> Option<Bytes> {
+		let block_files = self.block_files.as_ref()?;
+		let position = self.get(Key::TransactionPosition(*hash)).and_then(Value::as_transaction_position)?;
+		read_block_files(block_files, &position)
+			.unwrap_or_else(|err| panic!("Corrupted block files - failed to read transaction {}: {}", hash, err))
+	}
+
+	fn read_transaction(&self, hash: &H256) -> Option<ChainTransaction> {
+		self.get(Key::Transaction(*hash))
+			.and_then(Value::as_transaction)
+			.or_else(|| self.raw_transaction(hash)
+				.map(|raw| deserialize(&*raw).expect(&format!("Corrupted block files - invalid transaction {}", hash))))
+	}
+
+	/// Appends event to the chain events journal.
+	fn append_chain_event(&self, update: &mut DBTransaction, kind: ChainEventKind, block: &IndexedBlock, block_number: u32) {
+		let sequence = self.last_chain_event_sequence() + 1;
+		let event = ChainEvent {
+			sequence: sequence,
+			kind: kind,
+			block_hash: block.header.hash.clone(),
+			block_number: block_number,
+			transactions: block.transactions.iter().map(|tx| tx.hash.clone()).collect(),
+		};
+
+		update.insert(KeyValue::ChainEvent(sequence, event));
+		update.insert(KeyValue::Meta(KEY_LAST_CHAIN_EVENT, serialize(&sequence)));
+	}
+
+	/// Adds outputs of connected block to the address index && marks outputs, spent by the block, as spent.
+	/// Disconnected block is reverted the same way.
+	fn update_address_index(&self, update: &mut DBTransaction, block: &IndexedBlock, block_number: u32, connected: bool) {
+		if !self.address_index {
+			return;
+		}
+
+		// previous outputs are either in the block itself, or in the canon chain
+		let output_provider = DuplexTransactionOutputProvider::new(block, self);
+		let mut modified: HashMap<H256, Vec<AddressIndexEntry>> = HashMap::new();
+		for (tx_index, tx) in block.transactions.iter().enumerate() {
+			if !tx.raw.is_coinbase() {
+				for input in &tx.raw.inputs {
+					let previous_output = match output_provider.transaction_output(&input.previous_output, tx_index) {
+						Some(previous_output) => previous_output,
+						None => continue,
+					};
+
+					let key = address_index_key(&previous_output.script_pubkey);
+					let entries = modified.entry(key).or_insert_with(|| self.address_index_entries(&key));
+					if let Some(entry) = entries.iter_mut().find(|entry| entry.tx_hash == input.previous_output.hash
+						&& entry.output_index == input.previous_output.index) {
+						entry.spent = connected;
+					}
+				}
+			}
+
+			for (output_index, output) in tx.raw.outputs.iter().enumerate() {
+				let key = address_index_key(&output.script_pubkey);
+				let entries = modified.entry(key).or_insert_with(|| self.address_index_entries(&key));
+				if connected {
+					entries.push(AddressIndexEntry {
+						height: block_number,
+						tx_hash: tx.hash.clone(),
+						output_index: output_index as u32,
+						value: output.value,
+						spent: false,
+					});
+				} else {
+					entries.retain(|entry| entry.tx_hash != tx.hash || entry.output_index != output_index as u32);
+				}
+			}
+		}
+
+		for (key, entries) in modified {
+			if entries.is_empty() {
+				update.delete(Key::AddressIndex(key));
+			} else {
+				update.insert(KeyValue::AddressIndex(key, List::from(entries)));
+			}
+		}
+	}
+
+	/// Records inputs of connected block as spending their previous outputs. Disconnected block is reverted.
+	fn update_spent_index(&self, update: &mut DBTransaction, block: &IndexedBlock, block_number: u32, connected: bool) {
+		if !self.spent_index {
+			return;
+		}
+
+		for tx in block.transactions.iter().filter(|tx| !tx.raw.is_coinbase()) {
+			for (input_index, input) in tx.raw.inputs.iter().enumerate() {
+				if connected {
+					update.insert(KeyValue::SpentIndex(input.previous_output.clone(), SpentIndexEntry {
+						tx_hash: tx.hash.clone(),
+						input_index: input_index as u32,
+						height: block_number,
+					}));
+				} else {
+					update.delete(Key::SpentIndex(input.previous_output.clone()));
+				}
+			}
+		}
 	}
 
 	fn get(&self, key: Key) -> Option<Value> {
@@ -586,14 +1393,18 @@ impl<T> BlockProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
 	}
 
 	fn block(&self, block_ref: BlockRef) -> Option<IndexedBlock> {
-		self.resolve_hash(block_ref)
-			.and_then(|block_hash| {
-				self.block_header(block_hash.clone().into())
-					.map(|header| {
-						let transactions = self.block_transactions(block_hash.into());
-						IndexedBlock::new(header, transactions)
-					})
-			})
+		let block_hash = self.resolve_hash(block_ref)?;
+		let header = self.block_header(block_hash.clone().into())?;
+		// transactions of pruned blocks are unknown
+		let transaction_hashes: Vec<H256> = self.get(Key::BlockTransactions(block_hash))
+			.and_then(Value::as_block_transactions)?
+			.into();
+		// block with some transactions unreadable (e.g. their block file is removed) is unknown too
+		let transactions = transaction_hashes.into_iter()
+			.map(|hash| self.read_transaction(&hash)
+				.map(|tx| IndexedTransaction::new(hash, tx)))
+			.collect::<Option<Vec<_>>>()?;
+		Some(IndexedBlock::new(header, transactions))
 	}
 
 	fn contains_block(&self, block_ref: BlockRef) -> bool {
@@ -613,8 +1424,7 @@ impl<T> BlockProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn block_transactions(&self, block_ref: BlockRef) -> Vec<IndexedTransaction> {
 		self.block_transaction_hashes(block_ref)
 			.into_iter()
-			.filter_map(|hash| self.get(Key::Transaction(hash))
-				.and_then(Value::as_transaction)
+			.filter_map(|hash| self.read_transaction(&hash)
 				.map(|tx| IndexedTransaction::new(hash, tx)))
 			.collect()
 	}
@@ -629,12 +1439,12 @@ impl<T> TransactionMetaProvider for BlockChainDatabase<T> where T: KeyValueDatab
 
 impl<T> TransactionProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn transaction_bytes(&self, hash: &H256) -> Option<Bytes> {
-		self.transaction(hash).map(|tx| serialize(&tx.raw))
+		self.raw_transaction(hash)
+			.or_else(|| self.transaction(hash).map(|tx| serialize(&tx.raw)))
 	}
 
 	fn transaction(&self, hash: &H256) -> Option<IndexedTransaction> {
-		self.get(Key::Transaction(hash.clone()))
-			.and_then(Value::as_transaction)
+		self.read_transaction(hash)
 			.map(|tx| IndexedTransaction::new(*hash, tx))
 	}
 }
@@ -674,8 +1484,76 @@ impl<T> TreeStateProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
 	}
 
 	fn sapling_block_root(&self, block_hash: &H256) -> Option<H256> {
-		self.block_header(BlockRef::Hash(*block_hash))
-			.map(|header| header.raw.final_sapling_root)
+		self.get(Key::SaplingBlockRoot(*block_hash)).and_then(Value::as_sapling_block_root)
+	}
+
+	fn sapling_anchor_height(&self, root: &H256) -> Option<u32> {
+		self.get(Key::SaplingAnchor(*root)).and_then(Value::as_block_number)
+	}
+
+	fn history_tree_at_block(&self, block_hash: &H256) -> Option<HistoryTree> {
+		self.get(Key::HistoryTree(*block_hash)).and_then(Value::as_history_tree)
+	}
+}
+
+impl<T> ShieldedStatsProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn block_shielded_stats(&self, block_hash: &H256) -> Option<ShieldedStats> {
+		self.get(Key::ShieldedStats(*block_hash)).and_then(Value::as_shielded_stats)
+	}
+}
+
+impl<T> ValuePoolsProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn block_value_pools(&self, block_hash: &H256) -> Option<ValuePools> {
+		self.get(Key::ValuePools(*block_hash)).and_then(Value::as_value_pools)
+	}
+}
+
+impl<T> FeeStatsProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn block_fee_stats(&self, block_hash: &H256) -> Option<FeeStats> {
+		self.get(Key::FeeStats(*block_hash)).and_then(Value::as_fee_stats)
+	}
+}
+
+impl<T> ChainEventProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn last_chain_event_sequence(&self) -> u64 {
+		self.get(Key::Meta(KEY_LAST_CHAIN_EVENT))
+			.and_then(Value::as_meta)
+			.map(|sequence| deserialize(&*sequence).expect("Inconsistent DB. Invalid last chain event sequence."))
+			.unwrap_or(0)
+	}
+
+	fn chain_event(&self, sequence: u64) -> Option<ChainEvent> {
+		self.get(Key::ChainEvent(sequence)).and_then(Value::as_chain_event)
+	}
+}
+
+impl<T> AddressIndexProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn is_address_index_enabled(&self) -> bool {
+		self.address_index
+	}
+
+	fn address_index_entries(&self, key: &H256) -> Vec<AddressIndexEntry> {
+		self.get(Key::AddressIndex(*key))
+			.and_then(Value::as_address_index)
+			.map(List::into)
+			.unwrap_or_default()
+	}
+}
+
+impl<T> DatabaseInfoProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn database_info(&self) -> DatabaseInfo {
+		BlockChainDatabase::database_info(self)
+	}
+}
+
+impl<T> SpentIndexProvider for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn is_spent_index_enabled(&self) -> bool {
+		self.spent_index
+	}
+
+	fn spent_index_entry(&self, outpoint: &OutPoint) -> Option<SpentIndexEntry> {
+		self.get(Key::SpentIndex(outpoint.clone()))
+			.and_then(Value::as_spent_index)
 	}
 }
 
@@ -689,7 +1567,9 @@ impl<T> BlockChain for BlockChainDatabase<T> where T: KeyValueDatabase {
 	}
 
 	fn canonize(&self, block_hash: &H256) -> Result<(), Error> {
-		BlockChainDatabase::canonize(self, block_hash)
+		BlockChainDatabase::canonize(self, block_hash)?;
+		self.prune()?;
+		self.prune_undo()
 	}
 
 	fn decanonize(&self) -> Result<H256, Error> {
@@ -711,9 +1591,7 @@ impl<T> Forkable for BlockChainDatabase<T> where T: KeyValueDatabase {
 	}
 
 	fn switch_to_fork<'a>(&self, fork: Box<ForkChain + 'a>) -> Result<(), Error> {
-		let mut best_block = self.best_block.write();
-		*best_block = fork.store().best_block();
-		fork.flush()
+		BlockChainDatabase::switch_to_fork(self, &*fork)
 	}
 }
 
@@ -723,6 +1601,34 @@ impl<T> CanonStore for BlockChainDatabase<T> where T: KeyValueDatabase {
 	}
 }
 
+impl<T> ConfigStore for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn network(&self) -> Result<Option<String>, Error> {
+		match self.db.get(&Key::Configuration(KEY_NETWORK)).map(KeyState::into_option).map(|x| x.and_then(Value::as_configuration)) {
+			Ok(Some(network)) => String::from_utf8(network.into())
+				.map_err(|e| Error::DatabaseError(format!("{}", e)))
+				.map(Some),
+			Ok(None) => Ok(None),
+			Err(e) => Err(Error::DatabaseError(e)),
+		}
+	}
+
+	fn set_network(&self, network: &str) -> Result<(), Error> {
+		let mut update = DBTransaction::new();
+		update.insert(KeyValue::Configuration(KEY_NETWORK, network.as_bytes().into()));
+		self.db.write(update).map_err(Error::DatabaseError)
+	}
+}
+
+impl<T> SnapshotStore for BlockChainDatabase<T> where T: KeyValueDatabase {
+	fn export_snapshot(&self, writer: &mut io::Write) -> Result<SnapshotInfo, Error> {
+		BlockChainDatabase::export_snapshot(self, writer)
+	}
+
+	fn import_snapshot(&self, reader: &mut io::Read, genesis_hash: &H256) -> Result<SnapshotInfo, Error> {
+		BlockChainDatabase::import_snapshot(self, reader, genesis_hash)
+	}
+}
+
 impl<T> Store for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn best_block(&self) -> BestBlock {
 		BlockChainDatabase::best_block(self)
@@ -732,4 +1638,31 @@ impl<T> Store for BlockChainDatabase<T> where T: KeyValueDatabase {
 	fn best_header(&self) -> IndexedBlockHeader {
 		self.block_header(self.best_block().hash.into()).expect("best block header should be in db; qed")
 	}
+
+	fn read_snapshot(&self) -> ReadSnapshot {
+		BlockChainDatabase::read_snapshot(self)
+	}
+}
+
+/// Read record of raw blocks files. Records of removed (pruned) files && truncated records (that haven't
+/// reached the disk before the crash) are unavailable.
+fn read_block_files(block_files: &FlatFileStore, position: &FlatFilePosition) -> Result<Option<Bytes>, io::Error> {
+	match block_files.read(position) {
+		Ok(data) => Ok(Some(data)),
+		Err(ref err) if err.kind() == io::ErrorKind::NotFound || err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+/// Serializes block. Also returns (offset, length) of every transaction within serialized block.
+pub fn serialize_block(block: &IndexedBlock) -> (Bytes, Vec<(u32, u32)>) {
+	let mut raw_block = serialize(&block.header.raw).take();
+	raw_block.extend_from_slice(&serialize(&CompactInteger::from(block.transactions.len())));
+	let mut transactions = Vec::with_capacity(block.transactions.len());
+	for tx in &block.transactions {
+		let raw_tx = serialize(&tx.raw);
+		transactions.push((raw_block.len() as u32, raw_tx.len() as u32));
+		raw_block.extend_from_slice(&raw_tx);
+	}
+	(raw_block.into(), transactions)
 }
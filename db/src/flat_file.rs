@@ -0,0 +1,299 @@
+//! Append-only storage of raw data in a sequence of flat files.
+//!
+//! Records are appended to the last file until it reaches the maximal size, after which
+//! the next file is started. Records are never modified, so the position of the record
+//! (which is stored in the database) stays valid until the file is removed.
+
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use parking_lot::Mutex;
+use bytes::Bytes;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+
+/// Position of the record in the flat files.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FlatFilePosition {
+	/// Number of the file.
+	pub file: u32,
+	/// Offset of the record in the file.
+	pub offset: u32,
+	/// Length of the record.
+	pub length: u32,
+}
+
+impl FlatFilePosition {
+	/// Position of the part of this record.
+	pub fn slice(&self, offset: u32, length: u32) -> FlatFilePosition {
+		FlatFilePosition {
+			file: self.file,
+			offset: self.offset + offset,
+			length: length,
+		}
+	}
+}
+
+impl Serializable for FlatFilePosition {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.file)
+			.append(&self.offset)
+			.append(&self.length);
+	}
+}
+
+impl Deserializable for FlatFilePosition {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(FlatFilePosition {
+			file: reader.read()?,
+			offset: reader.read()?,
+			length: reader.read()?,
+		})
+	}
+}
+
+struct LastFile {
+	/// Number of the last file.
+	number: u32,
+	/// Current size of the last file.
+	size: u32,
+	/// Last file, opened for appending.
+	file: Option<fs::File>,
+}
+
+/// Append-only log of records, stored in files `<prefix>NNNNN.dat` of the directory.
+pub struct FlatFileStore {
+	/// Directory where files are stored.
+	dir: PathBuf,
+	/// File name prefix.
+	prefix: &'static str,
+	/// Maximal size of single file.
+	max_file_size: u32,
+	/// Last file, where new records are appended.
+	last_file: Mutex<LastFile>,
+}
+
+impl FlatFileStore {
+	/// Open files of given directory. The directory is created if it does not exist.
+	pub fn open<P>(dir: P, prefix: &'static str, max_file_size: u32) -> Result<Self, io::Error> where P: AsRef<Path> {
+		let dir = dir.as_ref().to_path_buf();
+		fs::create_dir_all(&dir)?;
+
+		let mut last_number = 0;
+		for entry in fs::read_dir(&dir)? {
+			let name = entry?.file_name();
+			let number = name.to_str()
+				.and_then(|name| file_number(prefix, name));
+			if let Some(number) = number {
+				last_number = ::std::cmp::max(last_number, number);
+			}
+		}
+
+		let last_size = match fs::metadata(dir.join(file_name(prefix, last_number))) {
+			Ok(metadata) => metadata.len() as u32,
+			Err(ref err) if err.kind() == io::ErrorKind::NotFound => 0,
+			Err(err) => return Err(err),
+		};
+
+		Ok(FlatFileStore {
+			dir: dir,
+			prefix: prefix,
+			max_file_size: max_file_size,
+			last_file: Mutex::new(LastFile {
+				number: last_number,
+				size: last_size,
+				file: None,
+			}),
+		})
+	}
+
+	/// Append record to the last file. Returns position of the record.
+	pub fn append(&self, data: &[u8]) -> Result<FlatFilePosition, io::Error> {
+		let length = data.len() as u32;
+		let mut last_file = self.last_file.lock();
+		if last_file.size != 0 && last_file.size as u64 + length as u64 > self.max_file_size as u64 {
+			// records of the previous file are never flushed after this
+			if let Some(file) = last_file.file.take() {
+				file.sync_data()?;
+			}
+			last_file.number += 1;
+			last_file.size = 0;
+		}
+
+		if last_file.file.is_none() {
+			let file = fs::OpenOptions::new()
+				.create(true)
+				.append(true)
+				.open(self.file_path(last_file.number))?;
+			last_file.file = Some(file);
+		}
+
+		let position = FlatFilePosition {
+			file: last_file.number,
+			offset: last_file.size,
+			length: length,
+		};
+
+		last_file.file.as_mut().expect("opened above; qed").write_all(data)?;
+		last_file.size += length;
+		Ok(position)
+	}
+
+	/// Remove all files with numbers below given number. Records of removed files can't be read anymore.
+	pub fn remove_before(&self, number: u32) -> Result<(), io::Error> {
+		let last_number = self.last_file.lock().number;
+		for entry in fs::read_dir(&self.dir)? {
+			let name = entry?.file_name();
+			let file_number = name.to_str()
+				.and_then(|name| file_number(self.prefix, name));
+			match file_number {
+				Some(file_number) if file_number < number && file_number != last_number => fs::remove_file(self.file_path(file_number))?,
+				_ => (),
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Numbers of all files, in ascending order.
+	pub fn files(&self) -> Result<Vec<u32>, io::Error> {
+		let mut files = Vec::new();
+		for entry in fs::read_dir(&self.dir)? {
+			let name = entry?.file_name();
+			if let Some(number) = name.to_str().and_then(|name| file_number(self.prefix, name)) {
+				files.push(number);
+			}
+		}
+
+		files.sort();
+		Ok(files)
+	}
+
+	/// Read record at given position.
+	pub fn read(&self, position: &FlatFilePosition) -> Result<Bytes, io::Error> {
+		let mut file = fs::File::open(self.file_path(position.file))?;
+		file.seek(SeekFrom::Start(position.offset as u64))?;
+		let mut data = vec![0u8; position.length as usize];
+		file.read_exact(&mut data)?;
+		Ok(data.into())
+	}
+
+	/// Flush all appended records to the disk.
+	pub fn flush(&self) -> Result<(), io::Error> {
+		match self.last_file.lock().file {
+			Some(ref file) => file.sync_data(),
+			None => Ok(()),
+		}
+	}
+
+	/// Path of the file with given number.
+	pub fn file_path(&self, number: u32) -> PathBuf {
+		self.dir.join(file_name(self.prefix, number))
+	}
+}
+
+fn file_name(prefix: &str, number: u32) -> String {
+	format!("{}{:05}.dat", prefix, number)
+}
+
+fn file_number(prefix: &str, name: &str) -> Option<u32> {
+	if !name.starts_with(prefix) || !name.ends_with(".dat") || name.len() < prefix.len() + 4 {
+		return None;
+	}
+
+	name[prefix.len()..name.len() - 4].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use std::io;
+	use self::tempdir::TempDir;
+	use ser::{serialize, deserialize};
+	use super::{FlatFileStore, FlatFilePosition, file_number};
+
+	#[test]
+	fn flat_file_store_append_and_read() {
+		let tempdir = TempDir::new("").unwrap();
+		let store = FlatFileStore::open(tempdir.path(), "blk", 10).unwrap();
+		let p1 = store.append(b"hello").unwrap();
+		let p2 = store.append(b"world").unwrap();
+		// does not fit into the first file
+		let p3 = store.append(b"!").unwrap();
+		// larger than the file, but the file is empty
+		let p4 = store.append(b"hello world").unwrap();
+
+		assert_eq!(p1, FlatFilePosition { file: 0, offset: 0, length: 5 });
+		assert_eq!(p2, FlatFilePosition { file: 0, offset: 5, length: 5 });
+		assert_eq!(p3, FlatFilePosition { file: 1, offset: 0, length: 1 });
+		assert_eq!(p4, FlatFilePosition { file: 2, offset: 0, length: 11 });
+
+		assert_eq!(&*store.read(&p1).unwrap(), b"hello");
+		assert_eq!(&*store.read(&p2).unwrap(), b"world");
+		assert_eq!(&*store.read(&p3).unwrap(), b"!");
+		assert_eq!(&*store.read(&p4).unwrap(), b"hello world");
+		assert_eq!(&*store.read(&p4.slice(6, 5)).unwrap(), b"world");
+	}
+
+	#[test]
+	fn flat_file_store_reopen() {
+		let tempdir = TempDir::new("").unwrap();
+		{
+			let store = FlatFileStore::open(tempdir.path(), "blk", 10).unwrap();
+			store.append(b"hello").unwrap();
+			store.append(b"world!").unwrap();
+			store.flush().unwrap();
+		}
+
+		let store = FlatFileStore::open(tempdir.path(), "blk", 10).unwrap();
+		let position = store.append(b"zcash").unwrap();
+		assert_eq!(position, FlatFilePosition { file: 1, offset: 6, length: 5 });
+		assert_eq!(&*store.read(&position).unwrap(), b"zcash");
+	}
+
+	#[test]
+	fn flat_file_store_remove_before() {
+		let tempdir = TempDir::new("").unwrap();
+		let store = FlatFileStore::open(tempdir.path(), "blk", 5).unwrap();
+		let p1 = store.append(b"hello").unwrap();
+		let p2 = store.append(b"world").unwrap();
+		let p3 = store.append(b"zcash").unwrap();
+
+		store.remove_before(2).unwrap();
+		assert_eq!(store.read(&p1).unwrap_err().kind(), io::ErrorKind::NotFound);
+		assert_eq!(store.read(&p2).unwrap_err().kind(), io::ErrorKind::NotFound);
+		assert_eq!(&*store.read(&p3).unwrap(), b"zcash");
+
+		// the last file is never removed
+		store.remove_before(10).unwrap();
+		assert_eq!(&*store.read(&p3).unwrap(), b"zcash");
+	}
+
+	#[test]
+	fn flat_file_store_files() {
+		let tempdir = TempDir::new("").unwrap();
+		let store = FlatFileStore::open(tempdir.path(), "blk", 5).unwrap();
+		store.append(b"hello").unwrap();
+		store.append(b"world").unwrap();
+		store.append(b"zcash").unwrap();
+		assert_eq!(store.files().unwrap(), vec![0, 1, 2]);
+
+		store.remove_before(1).unwrap();
+		assert_eq!(store.files().unwrap(), vec![1, 2]);
+	}
+
+	#[test]
+	fn flat_file_position_serialization() {
+		let position = FlatFilePosition { file: 1, offset: 2, length: 3 };
+		assert_eq!(deserialize::<_, FlatFilePosition>(serialize(&position).as_ref()).unwrap(), position);
+	}
+
+	#[test]
+	fn flat_file_number() {
+		assert_eq!(file_number("blk", "blk00042.dat"), Some(42));
+		assert_eq!(file_number("blk", "rev00042.dat"), None);
+		assert_eq!(file_number("blk", "blk.dat"), None);
+		assert_eq!(file_number("blk", "blk00042.tmp"), None);
+	}
+}
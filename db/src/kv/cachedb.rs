@@ -1,6 +1,7 @@
 use lru_cache::LruCache;
 use parking_lot::Mutex;
 use hash::H256;
+use storage::EpochTag;
 use chain::BlockHeader;
 use kv::{KeyValueDatabase, KeyState, Operation, KeyValue, Key, Value, Transaction};
 
@@ -44,4 +45,16 @@ impl<T> KeyValueDatabase for CacheDatabase<T> where T: KeyValueDatabase {
 		}
 		self.db.get(key)
 	}
+
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String> {
+		self.db.nullifiers(epoch)
+	}
+
+	fn block_hashes(&self) -> Result<Vec<H256>, String> {
+		self.db.block_hashes()
+	}
+
+	fn persist(&self) -> Result<(), String> {
+		self.db.persist()
+	}
 }
@@ -1,7 +1,20 @@
+use hash::H256;
+use storage::EpochTag;
 use kv::{Transaction, KeyState, Key, Value};
 
 pub trait KeyValueDatabase: Send + Sync {
 	fn write(&self, tx: Transaction) -> Result<(), String>;
 
 	fn get(&self, key: &Key) -> Result<KeyState<Value>, String>;
+
+	/// All nullifiers of given epoch. Reads the whole nullifiers set, so it should only be used for diagnostics.
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String>;
+
+	/// Hashes of all known blocks (canon && side chain). Reads the whole headers column, so it should only be used by migrations.
+	fn block_hashes(&self) -> Result<Vec<H256>, String>;
+
+	/// Write all buffered changes to the underlying database. Noop for unbuffered databases.
+	fn persist(&self) -> Result<(), String> {
+		Ok(())
+	}
 }
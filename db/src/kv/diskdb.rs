@@ -7,15 +7,38 @@ use rocksdb::{
 	Options, DBCompactionStyle, BlockBasedOptions, Cache, Column, ReadOptions
 };
 use bytes::Bytes;
+use hash::H256;
+use ser::deserialize;
+use storage::EpochTag;
 use kv::{Transaction, RawTransaction, RawOperation, Location, Value, KeyValueDatabase, Key, KeyState, RawKeyValue,
-RawKey};
+RawKey, nullifiers_column, COL_BLOCK_HEADERS};
 
 const DB_BACKGROUND_FLUSHES: i32 = 2;
 const DB_BACKGROUND_COMPACTIONS: i32 = 2;
 
+/// Compaction style of the database
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CompactionStyle {
+	/// Leveled compaction - lower space amplification
+	Level,
+	/// Universal compaction - lower write amplification
+	Universal,
+}
+
+impl CompactionStyle {
+	fn to_rocksdb(&self) -> DBCompactionStyle {
+		match *self {
+			CompactionStyle::Level => DBCompactionStyle::DBLevelCompaction,
+			CompactionStyle::Universal => DBCompactionStyle::DBUniversalCompaction,
+		}
+	}
+}
+
 /// Compaction profile for the database settings
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct CompactionProfile {
+	/// compaction style
+	pub style: CompactionStyle,
 	/// L0-L1 target file size
 	pub initial_file_size: u64,
 	/// L2-LN target file size multiplier
@@ -35,6 +58,7 @@ impl CompactionProfile {
 	/// Default profile suitable for SSD storage
 	pub fn ssd() -> CompactionProfile {
 		CompactionProfile {
+			style: CompactionStyle::Universal,
 			initial_file_size: 32 * 1024 * 1024,
 			file_size_multiplier: 2,
 			write_rate_limit: None,
@@ -44,6 +68,7 @@ impl CompactionProfile {
 	/// Slow HDD compaction profile
 	pub fn hdd() -> CompactionProfile {
 		CompactionProfile {
+			style: CompactionStyle::Universal,
 			initial_file_size: 192 * 1024 * 1024,
 			file_size_multiplier: 1,
 			write_rate_limit: Some(8 * 1024 * 1024),
@@ -58,6 +83,8 @@ pub struct DatabaseConfig {
 	pub max_open_files: i32,
 	/// Cache sizes (in MiB) for specific columns.
 	pub cache_sizes: HashMap<Option<u32>, usize>,
+	/// Write buffer (memtable) size (in MiB) of every column, if not default.
+	pub write_buffer_size: Option<usize>,
 	/// Specific number of bloom filter bits, if any
 	pub bloom_filters: HashMap<Option<u32>, u8>,
 	/// Compaction profile
@@ -87,6 +114,7 @@ impl Default for DatabaseConfig {
 	fn default() -> DatabaseConfig {
 		DatabaseConfig {
 			cache_sizes: HashMap::new(),
+			write_buffer_size: None,
 			bloom_filters: HashMap::new(),
 			max_open_files: 512,
 			compaction: CompactionProfile::default(),
@@ -132,6 +160,18 @@ impl KeyValueDatabase for Database {
 			None => Ok(KeyState::Unknown)
 		}
 	}
+
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String> {
+		self.iter(nullifiers_column(epoch).into())
+			.map(|(key, _)| deserialize(&*key).map_err(|e| format!("{:?}", e)))
+			.collect()
+	}
+
+	fn block_hashes(&self) -> Result<Vec<H256>, String> {
+		self.iter(COL_BLOCK_HEADERS.into())
+			.map(|(key, _)| deserialize(&*key).map_err(|e| format!("{:?}", e)))
+			.collect()
+	}
 }
 
 impl Database {
@@ -160,7 +200,7 @@ impl Database {
 		opts.set_max_background_compactions(DB_BACKGROUND_COMPACTIONS);
 
 		// compaction settings
-		opts.set_compaction_style(DBCompactionStyle::DBUniversalCompaction);
+		opts.set_compaction_style(config.compaction.style.to_rocksdb());
 		opts.set_target_file_size_base(config.compaction.initial_file_size);
 		opts.set_target_file_size_multiplier(config.compaction.file_size_multiplier);
 
@@ -170,9 +210,12 @@ impl Database {
 
 		for col in 0 .. config.columns.unwrap_or(0) {
 			let mut opts = Options::new();
-			opts.set_compaction_style(DBCompactionStyle::DBUniversalCompaction);
+			opts.set_compaction_style(config.compaction.style.to_rocksdb());
 			opts.set_target_file_size_base(config.compaction.initial_file_size);
 			opts.set_target_file_size_multiplier(config.compaction.file_size_multiplier);
+			if let Some(write_buffer_size) = config.write_buffer_size {
+				try!(opts.set_parsed_options(&format!("write_buffer_size={}", write_buffer_size * 1024 * 1024)));
+			}
 
 			let col_opt = config.columns.map(|_| col);
 
@@ -347,4 +390,13 @@ mod tests {
 		let _ = Database::open_default(tempdir.path()).unwrap();
 		test_db(DatabaseConfig::default());
 	}
+
+	#[test]
+	fn kvdb_with_tuned_settings() {
+		let mut config = DatabaseConfig::default();
+		config.compaction.style = CompactionStyle::Level;
+		config.write_buffer_size = Some(4);
+		config.max_open_files = 64;
+		test_db(config);
+	}
 }
@@ -5,15 +5,21 @@ use parking_lot::RwLock;
 use hash::H256;
 use bytes::Bytes;
 use ser::List;
-use chain::{Transaction as ChainTransaction, BlockHeader};
+use chain::{Transaction as ChainTransaction, BlockHeader, OutPoint, HistoryTree};
 use kv::{Transaction, Key, KeyState, Operation, Value, KeyValueDatabase, KeyValue};
-use storage::{TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState};
+use storage::{
+	TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState, ShieldedStats, FeeStats, ChainEvent,
+	ValuePools, AddressIndexEntry, SpentIndexEntry, BlockUndo,
+};
+use flat_file::FlatFilePosition;
 
 #[derive(Default, Debug)]
 struct InnerDatabase {
 	meta: HashMap<&'static str, KeyState<Bytes>>,
 	block_hash: HashMap<u32, KeyState<H256>>,
 	sprout_block_root: HashMap<H256, KeyState<H256>>,
+	sapling_block_root: HashMap<H256, KeyState<H256>>,
+	sapling_anchors: HashMap<H256, KeyState<u32>>,
 	block_header: HashMap<H256, KeyState<BlockHeader>>,
 	block_transactions: HashMap<H256, KeyState<List<H256>>>,
 	transaction: HashMap<H256, KeyState<ChainTransaction>>,
@@ -22,8 +28,19 @@ struct InnerDatabase {
 	configuration: HashMap<&'static str, KeyState<Bytes>>,
 	sprout_nullifiers: HashMap<H256, KeyState<()>>,
 	sapling_nullifiers: HashMap<H256, KeyState<()>>,
+	orchard_nullifiers: HashMap<H256, KeyState<()>>,
 	sprout_tree_state: HashMap<H256, KeyState<SproutTreeState>>,
 	sapling_tree_state: HashMap<H256, KeyState<SaplingTreeState>>,
+	shielded_stats: HashMap<H256, KeyState<ShieldedStats>>,
+	block_position: HashMap<H256, KeyState<FlatFilePosition>>,
+	transaction_position: HashMap<H256, KeyState<FlatFilePosition>>,
+	fee_stats: HashMap<H256, KeyState<FeeStats>>,
+	history_trees: HashMap<H256, KeyState<HistoryTree>>,
+	chain_events: HashMap<u64, KeyState<ChainEvent>>,
+	value_pools: HashMap<H256, KeyState<ValuePools>>,
+	address_index: HashMap<H256, KeyState<List<AddressIndexEntry>>>,
+	spent_index: HashMap<OutPoint, KeyState<SpentIndexEntry>>,
+	block_undo: HashMap<H256, KeyState<BlockUndo>>,
 }
 
 #[derive(Default, Debug)]
@@ -74,6 +91,14 @@ impl MemoryDatabase {
 				)
 			);
 
+		let orchard_nullifiers = replace(&mut db.orchard_nullifiers, HashMap::default()).into_iter()
+			.flat_map(|(key, state)|
+				state.into_operation(key,
+					|k, _| KeyValue::Nullifier(EpochRef::new(EpochTag::Orchard, k)),
+					|h| Key::Nullifier(EpochRef::new(EpochTag::Orchard, h))
+				)
+			);
+
 		let sprout_tree_state = replace(&mut db.sprout_tree_state, HashMap::default()).into_iter()
 			.flat_map(|(key, state)|
 				state.into_operation(key,
@@ -83,12 +108,48 @@ impl MemoryDatabase {
 		let sprout_block_root = replace(&mut db.sprout_block_root, HashMap::default()).into_iter()
 			.flat_map(|(key, state)| state.into_operation(key, KeyValue::SproutBlockRoot, Key::SproutBlockRoot));
 
+		let sapling_block_root = replace(&mut db.sapling_block_root, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::SaplingBlockRoot, Key::SaplingBlockRoot));
+
+		let sapling_anchors = replace(&mut db.sapling_anchors, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::SaplingAnchor, Key::SaplingAnchor));
+
 		let sapling_tree_state = replace(&mut db.sapling_tree_state, HashMap::default()).into_iter()
 			.flat_map(|(key, state)|
 				state.into_operation(key,
 					KeyValue::SaplingTreeState,
 					|k| Key::TreeRoot(EpochRef::new(EpochTag::Sapling, k))));
 
+		let shielded_stats = replace(&mut db.shielded_stats, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::ShieldedStats, Key::ShieldedStats));
+
+		let block_position = replace(&mut db.block_position, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::BlockPosition, Key::BlockPosition));
+
+		let transaction_position = replace(&mut db.transaction_position, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::TransactionPosition, Key::TransactionPosition));
+
+		let fee_stats = replace(&mut db.fee_stats, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::FeeStats, Key::FeeStats));
+
+		let history_trees = replace(&mut db.history_trees, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::HistoryTree, Key::HistoryTree));
+
+		let chain_events = replace(&mut db.chain_events, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::ChainEvent, Key::ChainEvent));
+
+		let value_pools = replace(&mut db.value_pools, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::ValuePools, Key::ValuePools));
+
+		let address_index = replace(&mut db.address_index, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::AddressIndex, Key::AddressIndex));
+
+		let spent_index = replace(&mut db.spent_index, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::SpentIndex, Key::SpentIndex));
+
+		let block_undo = replace(&mut db.block_undo, HashMap::default()).into_iter()
+			.flat_map(|(key, state)| state.into_operation(key, KeyValue::BlockUndo, Key::BlockUndo));
+
 		Transaction {
 			operations: meta
 				.chain(block_hash)
@@ -101,11 +162,53 @@ impl MemoryDatabase {
 				.chain(sprout_tree_state)
 				.chain(sapling_tree_state)
 				.chain(sprout_block_root)
+				.chain(sapling_block_root)
+				.chain(sapling_anchors)
 				.chain(sprout_nullifiers)
 				.chain(sapling_nullifiers)
+				.chain(orchard_nullifiers)
+				.chain(shielded_stats)
+				.chain(block_position)
+				.chain(transaction_position)
+				.chain(fee_stats)
+				.chain(history_trees)
+				.chain(chain_events)
+				.chain(value_pools)
+				.chain(address_index)
+				.chain(spent_index)
+				.chain(block_undo)
 				.collect()
 		}
 	}
+
+	/// Apply insertions && deletions of nullifiers of given epoch to nullifiers of underlying database.
+	pub fn apply_nullifiers(&self, epoch: EpochTag, nullifiers: Vec<H256>) -> Vec<H256> {
+		let db = self.db.read();
+		let states = match epoch {
+			EpochTag::Sprout => &db.sprout_nullifiers,
+			EpochTag::Sapling => &db.sapling_nullifiers,
+			EpochTag::Orchard => &db.orchard_nullifiers,
+		};
+
+		apply_keys(states, nullifiers)
+	}
+
+	/// Apply insertions && deletions of block headers to block hashes of underlying database.
+	pub fn apply_block_hashes(&self, block_hashes: Vec<H256>) -> Vec<H256> {
+		apply_keys(&self.db.read().block_header, block_hashes)
+	}
+}
+
+/// Apply insertions && deletions of given states to keys of underlying database.
+fn apply_keys<V>(states: &HashMap<H256, KeyState<V>>, mut keys: Vec<H256>) -> Vec<H256> {
+	keys.retain(|key| !states.contains_key(key));
+	keys.extend(states.iter()
+		.filter(|&(_, state)| match *state {
+			KeyState::Insert(_) => true,
+			_ => false,
+		})
+		.map(|(key, _)| *key));
+	keys
 }
 
 impl KeyValueDatabase for MemoryDatabase {
@@ -125,10 +228,23 @@ impl KeyValueDatabase for MemoryDatabase {
 					KeyValue::Nullifier(key) => match key.epoch() {
 						EpochTag::Sprout => { db.sprout_nullifiers.insert(*key.hash(), KeyState::Insert(())); },
 						EpochTag::Sapling => { db.sapling_nullifiers.insert(*key.hash(), KeyState::Insert(())); },
+						EpochTag::Orchard => { db.orchard_nullifiers.insert(*key.hash(), KeyState::Insert(())); },
 					},
 					KeyValue::SproutTreeState(key, value) => { db.sprout_tree_state.insert(key, KeyState::Insert(value)); },
 					KeyValue::SaplingTreeState(key, value) => { db.sapling_tree_state.insert(key, KeyState::Insert(value)); },
 					KeyValue::SproutBlockRoot(key, value) => { db.sprout_block_root.insert(key, KeyState::Insert(value)); },
+					KeyValue::SaplingBlockRoot(key, value) => { db.sapling_block_root.insert(key, KeyState::Insert(value)); },
+					KeyValue::SaplingAnchor(key, value) => { db.sapling_anchors.insert(key, KeyState::Insert(value)); },
+					KeyValue::ShieldedStats(key, value) => { db.shielded_stats.insert(key, KeyState::Insert(value)); },
+					KeyValue::BlockPosition(key, value) => { db.block_position.insert(key, KeyState::Insert(value)); },
+					KeyValue::TransactionPosition(key, value) => { db.transaction_position.insert(key, KeyState::Insert(value)); },
+					KeyValue::FeeStats(key, value) => { db.fee_stats.insert(key, KeyState::Insert(value)); },
+					KeyValue::HistoryTree(key, value) => { db.history_trees.insert(key, KeyState::Insert(value)); },
+					KeyValue::ChainEvent(key, value) => { db.chain_events.insert(key, KeyState::Insert(value)); },
+					KeyValue::ValuePools(key, value) => { db.value_pools.insert(key, KeyState::Insert(value)); },
+					KeyValue::AddressIndex(key, value) => { db.address_index.insert(key, KeyState::Insert(value)); },
+					KeyValue::SpentIndex(key, value) => { db.spent_index.insert(key, KeyState::Insert(value)); },
+					KeyValue::BlockUndo(key, value) => { db.block_undo.insert(key, KeyState::Insert(value)); },
 				},
 				Operation::Delete(delete) => match delete {
 					Key::Meta(key) => { db.meta.insert(key, KeyState::Delete); }
@@ -142,12 +258,27 @@ impl KeyValueDatabase for MemoryDatabase {
 					Key::Nullifier(key) => match key.epoch() {
 						EpochTag::Sprout => { db.sprout_nullifiers.insert(*key.hash(), KeyState::Delete); },
 						EpochTag::Sapling => { db.sapling_nullifiers.insert(*key.hash(), KeyState::Delete); },
+						EpochTag::Orchard => { db.orchard_nullifiers.insert(*key.hash(), KeyState::Delete); },
 					},
 					Key::TreeRoot(key) => match key.epoch() {
 						EpochTag::Sprout => { db.sprout_tree_state.insert(*key.hash(), KeyState::Delete); },
 						EpochTag::Sapling => { db.sapling_tree_state.insert(*key.hash(), KeyState::Delete); },
+						// Orchard tree states are not stored
+						EpochTag::Orchard => (),
 					},
 					Key::SproutBlockRoot(key) => { db.sprout_block_root.insert(key, KeyState::Delete); },
+					Key::SaplingBlockRoot(key) => { db.sapling_block_root.insert(key, KeyState::Delete); },
+					Key::SaplingAnchor(key) => { db.sapling_anchors.insert(key, KeyState::Delete); },
+					Key::ShieldedStats(key) => { db.shielded_stats.insert(key, KeyState::Delete); },
+					Key::BlockPosition(key) => { db.block_position.insert(key, KeyState::Delete); },
+					Key::TransactionPosition(key) => { db.transaction_position.insert(key, KeyState::Delete); },
+					Key::FeeStats(key) => { db.fee_stats.insert(key, KeyState::Delete); },
+					Key::HistoryTree(key) => { db.history_trees.insert(key, KeyState::Delete); },
+					Key::ChainEvent(key) => { db.chain_events.insert(key, KeyState::Delete); },
+					Key::ValuePools(key) => { db.value_pools.insert(key, KeyState::Delete); },
+					Key::AddressIndex(key) => { db.address_index.insert(key, KeyState::Delete); },
+					Key::SpentIndex(key) => { db.spent_index.insert(key, KeyState::Delete); },
+					Key::BlockUndo(key) => { db.block_undo.insert(key, KeyState::Delete); },
 				},
 			}
 		}
@@ -168,16 +299,38 @@ impl KeyValueDatabase for MemoryDatabase {
 			Key::Nullifier(ref key) => match key.epoch() {
 				EpochTag::Sprout => db.sprout_nullifiers.get(key.hash()).cloned().unwrap_or_default().map(|_| Value::Empty),
 				EpochTag::Sapling => db.sapling_nullifiers.get(key.hash()).cloned().unwrap_or_default().map(|_| Value::Empty),
+				EpochTag::Orchard => db.orchard_nullifiers.get(key.hash()).cloned().unwrap_or_default().map(|_| Value::Empty),
 			},
 			Key::TreeRoot(ref key) => match key.epoch() {
 				EpochTag::Sprout => db.sprout_tree_state.get(key.hash()).cloned().unwrap_or_default().map(Value::SproutTreeState),
 				EpochTag::Sapling => db.sapling_tree_state.get(key.hash()).cloned().unwrap_or_default().map(Value::SaplingTreeState),
+				EpochTag::Orchard => KeyState::Unknown,
 			},
 			Key::SproutBlockRoot(ref key) => db.sprout_block_root.get(key).cloned().unwrap_or_default().map(Value::SproutTreeRoot),
+			Key::SaplingBlockRoot(ref key) => db.sapling_block_root.get(key).cloned().unwrap_or_default().map(Value::SaplingTreeRoot),
+			Key::SaplingAnchor(ref key) => db.sapling_anchors.get(key).cloned().unwrap_or_default().map(Value::BlockNumber),
+			Key::ShieldedStats(ref key) => db.shielded_stats.get(key).cloned().unwrap_or_default().map(Value::ShieldedStats),
+			Key::BlockPosition(ref key) => db.block_position.get(key).cloned().unwrap_or_default().map(Value::BlockPosition),
+			Key::TransactionPosition(ref key) => db.transaction_position.get(key).cloned().unwrap_or_default().map(Value::TransactionPosition),
+			Key::FeeStats(ref key) => db.fee_stats.get(key).cloned().unwrap_or_default().map(Value::FeeStats),
+			Key::HistoryTree(ref key) => db.history_trees.get(key).cloned().unwrap_or_default().map(Value::HistoryTree),
+			Key::ChainEvent(ref key) => db.chain_events.get(key).cloned().unwrap_or_default().map(Value::ChainEvent),
+			Key::ValuePools(ref key) => db.value_pools.get(key).cloned().unwrap_or_default().map(Value::ValuePools),
+			Key::AddressIndex(ref key) => db.address_index.get(key).cloned().unwrap_or_default().map(Value::AddressIndex),
+			Key::SpentIndex(ref key) => db.spent_index.get(key).cloned().unwrap_or_default().map(Value::SpentIndex),
+			Key::BlockUndo(ref key) => db.block_undo.get(key).cloned().unwrap_or_default().map(Value::BlockUndo),
 		};
 
 		Ok(result)
 	}
+
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String> {
+		Ok(self.apply_nullifiers(epoch, Vec::new()))
+	}
+
+	fn block_hashes(&self) -> Result<Vec<H256>, String> {
+		Ok(self.apply_block_hashes(Vec::new()))
+	}
 }
 
 #[derive(Debug)]
@@ -209,4 +362,12 @@ impl KeyValueDatabase for SharedMemoryDatabase {
 	fn get(&self, key: &Key) -> Result<KeyState<Value>, String> {
 		self.db.get(key)
 	}
+
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String> {
+		self.db.nullifiers(epoch)
+	}
+
+	fn block_hashes(&self) -> Result<Vec<H256>, String> {
+		self.db.block_hashes()
+	}
 }
@@ -7,7 +7,7 @@ mod transaction;
 
 pub use self::cachedb::CacheDatabase;
 pub use self::db::KeyValueDatabase;
-pub use self::diskdb::{Database as DiskDatabase, DatabaseConfig, CompactionProfile};
+pub use self::diskdb::{Database as DiskDatabase, DatabaseConfig, CompactionProfile, CompactionStyle};
 pub use self::memorydb::{MemoryDatabase, SharedMemoryDatabase};
 pub use self::overlaydb::{OverlayDatabase, AutoFlushingOverlayDatabase};
 pub use self::transaction::{
@@ -15,5 +15,7 @@ pub use self::transaction::{
 	Key, Value, KeyValue, RawKeyValue, RawKey,
 	COL_COUNT, COL_META, COL_BLOCK_HASHES, COL_BLOCK_HEADERS, COL_BLOCK_TRANSACTIONS,
 	COL_TRANSACTIONS, COL_TRANSACTIONS_META, COL_BLOCK_NUMBERS, COL_SAPLING_NULLIFIERS,
-	COL_SPROUT_NULLIFIERS, COL_TREE_STATES, COL_SPROUT_BLOCK_ROOTS,
+	COL_SPROUT_NULLIFIERS, COL_ORCHARD_NULLIFIERS, COL_TREE_STATES, COL_SPROUT_BLOCK_ROOTS, COL_ADDRESS_INDEX,
+	COL_SPENT_INDEX,
+	nullifiers_column,
 };
@@ -1,4 +1,6 @@
 use parking_lot::Mutex;
+use hash::H256;
+use storage::EpochTag;
 use kv::{Transaction, Value, KeyValueDatabase, MemoryDatabase, KeyState, Key};
 
 pub struct OverlayDatabase<'a, T> where T: 'a + KeyValueDatabase {
@@ -30,6 +32,14 @@ impl<'a, T> KeyValueDatabase for OverlayDatabase<'a, T> where T: 'a + KeyValueDa
 			exists => Ok(exists)
 		}
 	}
+
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String> {
+		Ok(self.overlay.apply_nullifiers(epoch, self.db.nullifiers(epoch)?))
+	}
+
+	fn block_hashes(&self) -> Result<Vec<H256>, String> {
+		Ok(self.overlay.apply_block_hashes(self.db.block_hashes()?))
+	}
 }
 
 pub struct AutoFlushingOverlayDatabase<T> where T: KeyValueDatabase {
@@ -72,6 +82,21 @@ impl<T> KeyValueDatabase for AutoFlushingOverlayDatabase<T> where T: KeyValueDat
 			exists => Ok(exists)
 		}
 	}
+
+	fn nullifiers(&self, epoch: EpochTag) -> Result<Vec<H256>, String> {
+		Ok(self.overlay.apply_nullifiers(epoch, self.db.nullifiers(epoch)?))
+	}
+
+	fn block_hashes(&self) -> Result<Vec<H256>, String> {
+		Ok(self.overlay.apply_block_hashes(self.db.block_hashes()?))
+	}
+
+	fn persist(&self) -> Result<(), String> {
+		let mut operations = self.operations.lock();
+		self.flush()?;
+		*operations = 0;
+		self.db.persist()
+	}
 }
 
 impl<T> Drop for AutoFlushingOverlayDatabase<T> where T: KeyValueDatabase {
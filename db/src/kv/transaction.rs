@@ -1,10 +1,14 @@
 use bytes::Bytes;
 use hash::H256;
 use ser::{serialize, List, deserialize};
-use chain::{Transaction as ChainTransaction, BlockHeader};
-use storage::{TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState};
-
-pub const COL_COUNT: u32 = 16;
+use chain::{Transaction as ChainTransaction, BlockHeader, OutPoint, HistoryTree};
+use storage::{
+	TransactionMeta, EpochTag, EpochRef, SproutTreeState, SaplingTreeState, ShieldedStats, FeeStats, ChainEvent,
+	ValuePools, AddressIndexEntry, SpentIndexEntry, BlockUndo,
+};
+use flat_file::FlatFilePosition;
+
+pub const COL_COUNT: u32 = 25;
 pub const COL_META: u32 = 0;
 pub const COL_BLOCK_HASHES: u32 = 1;
 pub const COL_BLOCK_HEADERS: u32 = 2;
@@ -17,6 +21,28 @@ pub const COL_SAPLING_NULLIFIERS: u32 = 8;
 pub const COL_SPROUT_BLOCK_ROOTS: u32 = 9;
 pub const COL_TREE_STATES: u32 = 10;
 pub const COL_CONFIGURATION: u32 = 11;
+pub const COL_SHIELDED_STATS: u32 = 12;
+pub const COL_BLOCK_POSITIONS: u32 = 13;
+pub const COL_TRANSACTION_POSITIONS: u32 = 14;
+pub const COL_FEE_STATS: u32 = 15;
+pub const COL_HISTORY_TREES: u32 = 16;
+pub const COL_CHAIN_EVENTS: u32 = 17;
+pub const COL_SAPLING_BLOCK_ROOTS: u32 = 18;
+pub const COL_SAPLING_ANCHORS: u32 = 19;
+pub const COL_VALUE_POOLS: u32 = 20;
+pub const COL_ORCHARD_NULLIFIERS: u32 = 21;
+pub const COL_ADDRESS_INDEX: u32 = 22;
+pub const COL_SPENT_INDEX: u32 = 23;
+pub const COL_BLOCK_UNDO: u32 = 24;
+
+/// Column, where nullifiers of given epoch are stored.
+pub fn nullifiers_column(epoch: EpochTag) -> u32 {
+	match epoch {
+		EpochTag::Sprout => COL_SPROUT_NULLIFIERS,
+		EpochTag::Sapling => COL_SAPLING_NULLIFIERS,
+		EpochTag::Orchard => COL_ORCHARD_NULLIFIERS,
+	}
+}
 
 #[derive(Debug)]
 pub enum Operation {
@@ -38,6 +64,18 @@ pub enum KeyValue {
 	SproutTreeState(H256, SproutTreeState),
 	SaplingTreeState(H256, SaplingTreeState),
 	SproutBlockRoot(H256, H256),
+	SaplingBlockRoot(H256, H256),
+	SaplingAnchor(H256, u32),
+	ShieldedStats(H256, ShieldedStats),
+	BlockPosition(H256, FlatFilePosition),
+	TransactionPosition(H256, FlatFilePosition),
+	FeeStats(H256, FeeStats),
+	HistoryTree(H256, HistoryTree),
+	ChainEvent(u64, ChainEvent),
+	ValuePools(H256, ValuePools),
+	AddressIndex(H256, List<AddressIndexEntry>),
+	SpentIndex(OutPoint, SpentIndexEntry),
+	BlockUndo(H256, BlockUndo),
 }
 
 #[derive(Debug)]
@@ -53,6 +91,18 @@ pub enum Key {
 	Nullifier(EpochRef),
 	TreeRoot(EpochRef),
 	SproutBlockRoot(H256),
+	SaplingBlockRoot(H256),
+	SaplingAnchor(H256),
+	ShieldedStats(H256),
+	BlockPosition(H256),
+	TransactionPosition(H256),
+	FeeStats(H256),
+	HistoryTree(H256),
+	ChainEvent(u64),
+	ValuePools(H256),
+	AddressIndex(H256),
+	SpentIndex(OutPoint),
+	BlockUndo(H256),
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +119,17 @@ pub enum Value {
 	SproutTreeState(SproutTreeState),
 	SaplingTreeState(SaplingTreeState),
 	SproutTreeRoot(H256),
+	SaplingTreeRoot(H256),
+	ShieldedStats(ShieldedStats),
+	BlockPosition(FlatFilePosition),
+	TransactionPosition(FlatFilePosition),
+	FeeStats(FeeStats),
+	HistoryTree(HistoryTree),
+	ChainEvent(ChainEvent),
+	ValuePools(ValuePools),
+	AddressIndex(List<AddressIndexEntry>),
+	SpentIndex(SpentIndexEntry),
+	BlockUndo(BlockUndo),
 }
 
 impl Value {
@@ -86,8 +147,22 @@ impl Value {
 			Key::TreeRoot(tag) => match tag.epoch() {
 				EpochTag::Sprout => deserialize(bytes).map(Value::SproutTreeState),
 				EpochTag::Sapling => deserialize(bytes).map(Value::SaplingTreeState),
+				// Orchard tree states are not stored
+				EpochTag::Orchard => Err(::ser::Error::MalformedData),
 			},
 			Key::SproutBlockRoot(_) => deserialize(bytes).map(Value::SproutTreeRoot),
+			Key::SaplingBlockRoot(_) => deserialize(bytes).map(Value::SaplingTreeRoot),
+			Key::SaplingAnchor(_) => deserialize(bytes).map(Value::BlockNumber),
+			Key::ShieldedStats(_) => deserialize(bytes).map(Value::ShieldedStats),
+			Key::BlockPosition(_) => deserialize(bytes).map(Value::BlockPosition),
+			Key::TransactionPosition(_) => deserialize(bytes).map(Value::TransactionPosition),
+			Key::FeeStats(_) => deserialize(bytes).map(Value::FeeStats),
+			Key::HistoryTree(_) => deserialize(bytes).map(Value::HistoryTree),
+			Key::ChainEvent(_) => deserialize(bytes).map(Value::ChainEvent),
+			Key::ValuePools(_) => deserialize(bytes).map(Value::ValuePools),
+			Key::AddressIndex(_) => deserialize(bytes).map(Value::AddressIndex),
+			Key::SpentIndex(_) => deserialize(bytes).map(Value::SpentIndex),
+			Key::BlockUndo(_) => deserialize(bytes).map(Value::BlockUndo),
 		}.map_err(|e| format!("{:?}", e))
 	}
 
@@ -167,6 +242,83 @@ impl Value {
 			_ => None,
 		}
 	}
+
+	pub fn as_sapling_block_root(self) -> Option<H256> {
+		match self {
+			Value::SaplingTreeRoot(v) => Some(v),
+			_ => None,
+		}
+	}
+
+	pub fn as_shielded_stats(self) -> Option<ShieldedStats> {
+		match self {
+			Value::ShieldedStats(stats) => Some(stats),
+			_ => None,
+		}
+	}
+
+	pub fn as_fee_stats(self) -> Option<FeeStats> {
+		match self {
+			Value::FeeStats(stats) => Some(stats),
+			_ => None,
+		}
+	}
+
+	pub fn as_history_tree(self) -> Option<HistoryTree> {
+		match self {
+			Value::HistoryTree(tree) => Some(tree),
+			_ => None,
+		}
+	}
+
+	pub fn as_chain_event(self) -> Option<ChainEvent> {
+		match self {
+			Value::ChainEvent(event) => Some(event),
+			_ => None,
+		}
+	}
+
+	pub fn as_value_pools(self) -> Option<ValuePools> {
+		match self {
+			Value::ValuePools(pools) => Some(pools),
+			_ => None,
+		}
+	}
+
+	pub fn as_address_index(self) -> Option<List<AddressIndexEntry>> {
+		match self {
+			Value::AddressIndex(entries) => Some(entries),
+			_ => None,
+		}
+	}
+
+	pub fn as_spent_index(self) -> Option<SpentIndexEntry> {
+		match self {
+			Value::SpentIndex(entry) => Some(entry),
+			_ => None,
+		}
+	}
+
+	pub fn as_block_undo(self) -> Option<BlockUndo> {
+		match self {
+			Value::BlockUndo(undo) => Some(undo),
+			_ => None,
+		}
+	}
+
+	pub fn as_block_position(self) -> Option<FlatFilePosition> {
+		match self {
+			Value::BlockPosition(position) => Some(position),
+			_ => None,
+		}
+	}
+
+	pub fn as_transaction_position(self) -> Option<FlatFilePosition> {
+		match self {
+			Value::TransactionPosition(position) => Some(position),
+			_ => None,
+		}
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -268,15 +420,24 @@ impl<'a> From<&'a KeyValue> for RawKeyValue {
 			KeyValue::BlockTransactions(ref key, ref value) => (COL_BLOCK_TRANSACTIONS, serialize(key), serialize(value)),
 			KeyValue::Transaction(ref key, ref value) => (COL_TRANSACTIONS, serialize(key), serialize(value)),
 			KeyValue::TransactionMeta(ref key, ref value) => (COL_TRANSACTIONS_META, serialize(key), serialize(value)),
-			KeyValue::Nullifier(ref key) => match key.epoch() {
-				EpochTag::Sprout => (COL_SPROUT_NULLIFIERS, serialize(key.hash()), Bytes::new()),
-				EpochTag::Sapling => (COL_SAPLING_NULLIFIERS, serialize(key.hash()), Bytes::new()),
-			},
+			KeyValue::Nullifier(ref key) => (nullifiers_column(key.epoch()), serialize(key.hash()), Bytes::new()),
 			KeyValue::BlockNumber(ref key, ref value) => (COL_BLOCK_NUMBERS, serialize(key), serialize(value)),
 			KeyValue::SproutTreeState(ref key, ref value) => (COL_TREE_STATES, serialize(key), serialize(value)),
 			KeyValue::SaplingTreeState(ref key, ref value) => (COL_TREE_STATES, serialize(key), serialize(value)),
 			KeyValue::SproutBlockRoot(ref key, ref value) => (COL_SPROUT_BLOCK_ROOTS, serialize(key), serialize(value)),
+			KeyValue::SaplingBlockRoot(ref key, ref value) => (COL_SAPLING_BLOCK_ROOTS, serialize(key), serialize(value)),
+			KeyValue::SaplingAnchor(ref key, ref value) => (COL_SAPLING_ANCHORS, serialize(key), serialize(value)),
 			KeyValue::Configuration(ref key, ref value) => (COL_CONFIGURATION, serialize(key), serialize(value)),
+			KeyValue::ShieldedStats(ref key, ref value) => (COL_SHIELDED_STATS, serialize(key), serialize(value)),
+			KeyValue::BlockPosition(ref key, ref value) => (COL_BLOCK_POSITIONS, serialize(key), serialize(value)),
+			KeyValue::TransactionPosition(ref key, ref value) => (COL_TRANSACTION_POSITIONS, serialize(key), serialize(value)),
+			KeyValue::FeeStats(ref key, ref value) => (COL_FEE_STATS, serialize(key), serialize(value)),
+			KeyValue::HistoryTree(ref key, ref value) => (COL_HISTORY_TREES, serialize(key), serialize(value)),
+			KeyValue::ChainEvent(ref key, ref value) => (COL_CHAIN_EVENTS, serialize(key), serialize(value)),
+			KeyValue::ValuePools(ref key, ref value) => (COL_VALUE_POOLS, serialize(key), serialize(value)),
+			KeyValue::AddressIndex(ref key, ref value) => (COL_ADDRESS_INDEX, serialize(key), serialize(value)),
+			KeyValue::SpentIndex(ref key, ref value) => (COL_SPENT_INDEX, serialize(key), serialize(value)),
+			KeyValue::BlockUndo(ref key, ref value) => (COL_BLOCK_UNDO, serialize(key), serialize(value)),
 		};
 
 		RawKeyValue {
@@ -310,14 +471,23 @@ impl<'a> From<&'a Key> for RawKey {
 			Key::BlockTransactions(ref key) => (COL_BLOCK_TRANSACTIONS, serialize(key)),
 			Key::Transaction(ref key) => (COL_TRANSACTIONS, serialize(key)),
 			Key::TransactionMeta(ref key) => (COL_TRANSACTIONS_META, serialize(key)),
-			Key::Nullifier(ref key) => match key.epoch() {
-				EpochTag::Sprout => (COL_SPROUT_NULLIFIERS, serialize(key.hash())),
-				EpochTag::Sapling => (COL_SAPLING_NULLIFIERS, serialize(key.hash())),
-			},
+			Key::Nullifier(ref key) => (nullifiers_column(key.epoch()), serialize(key.hash())),
 			Key::TreeRoot(ref key) => (COL_TREE_STATES, serialize(key.hash())),
 			Key::BlockNumber(ref key) => (COL_BLOCK_NUMBERS, serialize(key)),
 			Key::SproutBlockRoot(ref key) => (COL_SPROUT_BLOCK_ROOTS, serialize(key)),
+			Key::SaplingBlockRoot(ref key) => (COL_SAPLING_BLOCK_ROOTS, serialize(key)),
+			Key::SaplingAnchor(ref key) => (COL_SAPLING_ANCHORS, serialize(key)),
 			Key::Configuration(ref key) => (COL_CONFIGURATION, serialize(key)),
+			Key::ShieldedStats(ref key) => (COL_SHIELDED_STATS, serialize(key)),
+			Key::BlockPosition(ref key) => (COL_BLOCK_POSITIONS, serialize(key)),
+			Key::TransactionPosition(ref key) => (COL_TRANSACTION_POSITIONS, serialize(key)),
+			Key::FeeStats(ref key) => (COL_FEE_STATS, serialize(key)),
+			Key::HistoryTree(ref key) => (COL_HISTORY_TREES, serialize(key)),
+			Key::ChainEvent(ref key) => (COL_CHAIN_EVENTS, serialize(key)),
+			Key::ValuePools(ref key) => (COL_VALUE_POOLS, serialize(key)),
+			Key::AddressIndex(ref key) => (COL_ADDRESS_INDEX, serialize(key)),
+			Key::SpentIndex(ref key) => (COL_SPENT_INDEX, serialize(key)),
+			Key::BlockUndo(ref key) => (COL_BLOCK_UNDO, serialize(key)),
 		};
 
 		RawKey {
@@ -9,10 +9,15 @@ extern crate lru_cache;
 extern crate primitives;
 extern crate serialization as ser;
 extern crate chain;
+extern crate network;
 extern crate storage;
 
 pub mod kv;
 mod block_chain_db;
+mod flat_file;
+mod migrations;
 
-pub use block_chain_db::{BlockChainDatabase, ForkChainDatabase};
+pub use block_chain_db::{BlockChainDatabase, ForkChainDatabase, StoredBlocks, DatabaseSettings, MIN_BLOCKS_TO_KEEP, MIN_UNDO_HORIZON, DEFAULT_UNDO_HORIZON};
+pub use flat_file::{FlatFileStore, FlatFilePosition};
+pub use migrations::DB_VERSION;
 pub use primitives::{hash, bytes};
\ No newline at end of file
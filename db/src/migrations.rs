@@ -0,0 +1,480 @@
+//! Versioning of the database schema.
+//!
+//! Version of the schema is stored in the configuration column. Databases, created before the version
+//! has been stored, have version 0. When the database is opened, migrations are applied one-by-one until
+//! the database reaches the current version. Databases of newer versions are never opened.
+//!
+//! Every migration backfills records of the new layout from blocks, that are already stored, so the rest
+//! of the code never has to deal with records of older layouts.
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use hash::H256;
+use chain::{BlockHeader, IndexedBlock, IndexedBlockHeader, IndexedTransaction};
+use ser::{serialize, deserialize};
+use storage::{
+	Error, EpochTag, EpochRef, ShieldedStats, SproutTreeState, SaplingTreeState, ValuePools, BlockUndo,
+	TransactionMeta, transaction_nullifiers,
+};
+use kv::{KeyValueDatabase, Transaction, Key, KeyValue, KeyState, Value};
+use flat_file::FlatFileStore;
+use block_chain_db::{serialize_block, KEY_BEST_BLOCK_NUMBER, KEY_UNDO_PRUNED_HEIGHT, DEFAULT_UNDO_HORIZON};
+
+/// Current version of the database schema.
+pub const DB_VERSION: u32 = 6;
+
+const KEY_DB_VERSION: &'static str = "db_version";
+
+/// Changes of migrations are written in batches of (at most) this number of blocks.
+const MIGRATION_BATCH_SIZE: usize = 1000;
+
+/// Migration of the database from the previous version to `version`.
+struct Migration {
+	version: u32,
+	description: &'static str,
+	migrate: fn(&KeyValueDatabase, &FlatFileStore) -> Result<(), String>,
+}
+
+/// Migrations, sorted by version. Every change of the database layout, which can't be read by the
+/// previous version of the code, must increase `DB_VERSION` && add the migration here.
+static MIGRATIONS: &'static [Migration] = &[
+	Migration {
+		version: 1,
+		description: "store shielded stats of blocks",
+		migrate: store_shielded_stats,
+	},
+	Migration {
+		version: 2,
+		description: "move raw blocks to flat files",
+		migrate: move_blocks_to_flat_files,
+	},
+	Migration {
+		version: 3,
+		description: "key sapling trees by their roots",
+		migrate: store_sapling_block_roots,
+	},
+	Migration {
+		version: 4,
+		description: "store value pools of blocks",
+		migrate: store_value_pools,
+	},
+	Migration {
+		version: 5,
+		description: "store orchard nullifiers",
+		migrate: store_orchard_nullifiers,
+	},
+	Migration {
+		version: 6,
+		description: "store undo data of last blocks",
+		migrate: store_block_undo,
+	},
+];
+
+/// Version of the database schema. None if the version isn't stored.
+pub fn db_version(db: &KeyValueDatabase) -> Result<Option<u32>, Error> {
+	match db.get(&Key::Configuration(KEY_DB_VERSION)).map(KeyState::into_option).map(|x| x.and_then(Value::as_configuration)) {
+		Ok(Some(version)) => deserialize(&*version)
+			.map(Some)
+			.map_err(|err| Error::DatabaseError(format!("Invalid database version: {:?}", err))),
+		Ok(None) => Ok(None),
+		Err(err) => Err(Error::DatabaseError(err)),
+	}
+}
+
+/// Upgrade the database (with its raw blocks files) to the current version. Fresh (empty) database is marked
+/// with the current version. Database of the newer version is refused.
+pub fn upgrade(db: &KeyValueDatabase, block_files: &FlatFileStore, is_empty: bool) -> Result<(), Error> {
+	let version = match db_version(db)? {
+		Some(version) => version,
+		None if is_empty => return write_version(db, DB_VERSION),
+		None => 0,
+	};
+
+	if version > DB_VERSION {
+		return Err(Error::UnsupportedDatabaseVersion(version, DB_VERSION));
+	}
+
+	// version is written after every migration, so interrupted upgrade is resumed from the last migration
+	for migration in MIGRATIONS.iter().filter(|migration| migration.version > version) {
+		info!(target: "db", "Upgrading database to version {}: {}", migration.version, migration.description);
+		(migration.migrate)(db, block_files).map_err(Error::DatabaseError)?;
+		write_version(db, migration.version)?;
+	}
+
+	Ok(())
+}
+
+fn write_version(db: &KeyValueDatabase, version: u32) -> Result<(), Error> {
+	let mut update = Transaction::new();
+	update.insert(KeyValue::Configuration(KEY_DB_VERSION, serialize(&version)));
+	db.write(update).map_err(Error::DatabaseError)
+}
+
+/// Shielded stats of every block are computed from its transactions.
+fn store_shielded_stats(db: &KeyValueDatabase, block_files: &FlatFileStore) -> Result<(), String> {
+	for_each_block(db, block_files, |hash, _, update| {
+		let transactions = block_transactions(db, block_files, hash)?;
+		let stats = ShieldedStats::from_transactions(transactions.iter().map(|tx| &tx.raw));
+		update.insert(KeyValue::ShieldedStats(*hash, stats));
+		Ok(())
+	})
+}
+
+/// Every block is appended to raw blocks files && its transactions are removed from the database.
+fn move_blocks_to_flat_files(db: &KeyValueDatabase, block_files: &FlatFileStore) -> Result<(), String> {
+	for_each_block(db, block_files, |hash, header, update| {
+		let header = IndexedBlockHeader::new(*hash, header.clone());
+		let block = IndexedBlock::new(header, block_transactions(db, block_files, hash)?);
+		let (raw_block, transactions) = serialize_block(&block);
+		let block_position = block_files.append(&raw_block).map_err(|err| err.to_string())?;
+		update.insert(KeyValue::BlockPosition(*hash, block_position));
+		for (tx, (offset, length)) in block.transactions.iter().zip(transactions) {
+			update.insert(KeyValue::TransactionPosition(tx.hash, block_position.slice(offset, length)));
+			update.delete(Key::Transaction(tx.hash));
+		}
+		Ok(())
+	})
+}
+
+/// Sapling tree of every block is rebuilt && stored under its root. Trees, that older versions have stored
+/// under the header field, are left in place. Roots of canon blocks are stored as valid anchors.
+fn store_sapling_block_roots(db: &KeyValueDatabase, block_files: &FlatFileStore) -> Result<(), String> {
+	let mut last_tree: Option<(H256, SaplingTreeState)> = None;
+	for_each_block(db, block_files, |hash, header, update| {
+		let parent_hash = header.previous_header_hash;
+		let parent_tree = last_tree.take()
+			.and_then(|(last_hash, tree)| if last_hash == parent_hash { Some(tree) } else { None });
+		let mut tree = match parent_tree {
+			Some(tree) => tree,
+			None if parent_hash.is_zero() => SaplingTreeState::new(),
+			None => {
+				let root = read(db, Key::SaplingBlockRoot(parent_hash))?
+					.and_then(Value::as_sapling_block_root)
+					.ok_or_else(|| format!("No sapling root of block {}", parent_hash.to_reversed_str()))?;
+				read(db, Key::TreeRoot(EpochRef::new(EpochTag::Sapling, root)))?
+					.and_then(Value::as_sapling_tree_state)
+					.ok_or_else(|| format!("No sapling tree of block {}", parent_hash.to_reversed_str()))?
+			},
+		};
+
+		let mut root_changed = parent_hash.is_zero();
+		for tx in block_transactions(db, block_files, hash)? {
+			if let Some(ref sapling) = tx.raw.sapling {
+				for output in &sapling.outputs {
+					tree.append(output.note_commitment.into())?;
+					root_changed = true;
+				}
+			}
+		}
+
+		let root = tree.root();
+		update.insert(KeyValue::SaplingBlockRoot(*hash, root));
+		update.insert(KeyValue::SaplingTreeState(root, tree.clone()));
+		// canon blocks are visited by height => block, which has changed the root, is the first canon block with it
+		if root_changed {
+			if let Some(number) = read(db, Key::BlockNumber(*hash))?.and_then(Value::as_block_number) {
+				update.insert(KeyValue::SaplingAnchor(root, number));
+			}
+		}
+		last_tree = Some((*hash, tree));
+		Ok(())
+	})
+}
+
+/// Value pools of every block are computed from value pools of its parent && its transactions.
+fn store_value_pools(db: &KeyValueDatabase, block_files: &FlatFileStore) -> Result<(), String> {
+	let mut last_pools: Option<(H256, ValuePools)> = None;
+	for_each_block(db, block_files, |hash, header, update| {
+		let parent_hash = header.previous_header_hash;
+		let parent_pools = last_pools.take()
+			.and_then(|(last_hash, pools)| if last_hash == parent_hash { Some(pools) } else { None });
+		let parent_pools = match parent_pools {
+			Some(pools) => pools,
+			None if parent_hash.is_zero() => ValuePools::default(),
+			None => read(db, Key::ValuePools(parent_hash))?
+				.and_then(Value::as_value_pools)
+				.ok_or_else(|| format!("No value pools of block {}", parent_hash.to_reversed_str()))?,
+		};
+
+		let transactions = block_transactions(db, block_files, hash)?;
+		let pools = parent_pools.apply_transactions(transactions.iter().map(|tx| &tx.raw))
+			.map_err(|pool| format!("{} value pool is negative after block {}", pool.name(), hash.to_reversed_str()))?;
+		update.insert(KeyValue::ValuePools(*hash, pools));
+		last_pools = Some((*hash, pools));
+		Ok(())
+	})
+}
+
+/// Orchard nullifiers of canon blocks are added to the nullifiers set.
+fn store_orchard_nullifiers(db: &KeyValueDatabase, block_files: &FlatFileStore) -> Result<(), String> {
+	for_each_block(db, block_files, |hash, _, update| {
+		if read(db, Key::BlockNumber(*hash))?.is_none() {
+			return Ok(());
+		}
+
+		for tx in block_transactions(db, block_files, hash)? {
+			for nullifier in tx.raw.orchard_nullifiers() {
+				update.insert(KeyValue::Nullifier(EpochRef::new(EpochTag::Orchard, nullifier)));
+			}
+		}
+		Ok(())
+	})
+}
+
+/// Undo data of last `DEFAULT_UNDO_HORIZON` canon blocks is computed by rolling transaction metas back from
+/// the best block. Undo data of deeper blocks is considered pruned.
+fn store_block_undo(db: &KeyValueDatabase, block_files: &FlatFileStore) -> Result<(), String> {
+	let best_number: u32 = match read(db, Key::Meta(KEY_BEST_BLOCK_NUMBER))?.and_then(Value::as_meta) {
+		Some(number) => deserialize(&*number).map_err(|err| format!("Invalid best block number: {:?}", err))?,
+		None => return Ok(()),
+	};
+	let undo_pruned_height = (best_number + 1).saturating_sub(DEFAULT_UNDO_HORIZON);
+
+	let mut update = Transaction::new();
+	// metas of transactions after the block, which undo data is computed. None if transaction isn't canon yet
+	let mut metas: HashMap<H256, Option<TransactionMeta>> = HashMap::new();
+	for number in (undo_pruned_height..best_number + 1).rev() {
+		let hash = canon_block_hash(db, number)?
+			.ok_or_else(|| format!("No canon block at height {}", number))?;
+		let parent_hash = block_header(db, &hash)?.previous_header_hash;
+		let transactions = block_transactions(db, block_files, &hash)?;
+
+		let mut undo = if parent_hash.is_zero() {
+			BlockUndo {
+				nullifiers: Vec::new(),
+				spent_metas: Vec::new(),
+				parent_sprout_root: SproutTreeState::new().root(),
+				parent_sapling_root: SaplingTreeState::new().root(),
+				parent_value_pools: ValuePools::default(),
+			}
+		} else {
+			BlockUndo {
+				nullifiers: Vec::new(),
+				spent_metas: Vec::new(),
+				parent_sprout_root: read(db, Key::SproutBlockRoot(parent_hash))?
+					.and_then(Value::as_sprout_block_root)
+					.ok_or_else(|| format!("No sprout root of block {}", parent_hash.to_reversed_str()))?,
+				parent_sapling_root: read(db, Key::SaplingBlockRoot(parent_hash))?
+					.and_then(Value::as_sapling_block_root)
+					.ok_or_else(|| format!("No sapling root of block {}", parent_hash.to_reversed_str()))?,
+				parent_value_pools: read(db, Key::ValuePools(parent_hash))?
+					.and_then(Value::as_value_pools)
+					.ok_or_else(|| format!("No value pools of block {}", parent_hash.to_reversed_str()))?,
+			}
+		};
+
+		for tx in &transactions {
+			undo.nullifiers.extend(transaction_nullifiers(&tx.raw));
+		}
+
+		// coinbase transactions commit to the block height => transactions of the block had no metas before it
+		let own_transactions: HashSet<H256> = transactions.iter().map(|tx| tx.hash).collect();
+		let mut spent_metas: Vec<(H256, TransactionMeta)> = Vec::new();
+		for tx in transactions.iter().skip(1) {
+			for input in &tx.raw.inputs {
+				let prevout_hash = input.previous_output.hash;
+				if own_transactions.contains(&prevout_hash) {
+					continue;
+				}
+
+				let position = match spent_metas.iter().position(|&(ref hash, _)| *hash == prevout_hash) {
+					Some(position) => position,
+					None => {
+						let meta = match metas.get(&prevout_hash) {
+							Some(meta) => meta.clone(),
+							None => read(db, Key::TransactionMeta(prevout_hash))?.and_then(Value::as_transaction_meta),
+						};
+						let meta = meta.ok_or_else(|| format!("No meta of transaction {}", prevout_hash.to_reversed_str()))?;
+						spent_metas.push((prevout_hash, meta));
+						spent_metas.len() - 1
+					},
+				};
+				spent_metas[position].1.denote_unused(input.previous_output.index as usize);
+			}
+		}
+
+		for tx_hash in own_transactions {
+			metas.insert(tx_hash, None);
+		}
+		for &(ref tx_hash, ref meta) in &spent_metas {
+			metas.insert(*tx_hash, Some(meta.clone()));
+		}
+
+		undo.spent_metas = spent_metas;
+		update.insert(KeyValue::BlockUndo(hash, undo));
+	}
+
+	update.insert(KeyValue::Meta(KEY_UNDO_PRUNED_HEIGHT, serialize(&undo_pruned_height)));
+	db.write(update)
+}
+
+fn read(db: &KeyValueDatabase, key: Key) -> Result<Option<Value>, String> {
+	db.get(&key).map(KeyState::into_option)
+}
+
+fn canon_block_hash(db: &KeyValueDatabase, number: u32) -> Result<Option<H256>, String> {
+	Ok(read(db, Key::BlockHash(number))?.and_then(Value::as_block_hash))
+}
+
+fn block_header(db: &KeyValueDatabase, hash: &H256) -> Result<BlockHeader, String> {
+	read(db, Key::BlockHeader(*hash))?
+		.and_then(Value::as_block_header)
+		.ok_or_else(|| format!("No header of block {}", hash.to_reversed_str()))
+}
+
+/// Transactions of the block, stored either in the database, or in raw blocks files.
+fn block_transactions(db: &KeyValueDatabase, block_files: &FlatFileStore, hash: &H256) -> Result<Vec<IndexedTransaction>, String> {
+	let tx_hashes: Vec<H256> = read(db, Key::BlockTransactions(*hash))?
+		.and_then(Value::as_block_transactions)
+		.ok_or_else(|| format!("No transactions of block {}", hash.to_reversed_str()))?
+		.into();
+
+	tx_hashes.into_iter()
+		.map(|tx_hash| {
+			if let Some(tx) = read(db, Key::Transaction(tx_hash))?.and_then(Value::as_transaction) {
+				return Ok(IndexedTransaction::new(tx_hash, tx));
+			}
+
+			let position = read(db, Key::TransactionPosition(tx_hash))?
+				.and_then(Value::as_transaction_position)
+				.ok_or_else(|| format!("No transaction {}", tx_hash.to_reversed_str()))?;
+			let raw = block_files.read(&position).map_err(|err| err.to_string())?;
+			let tx = deserialize(&*raw)
+				.map_err(|err| format!("Invalid transaction {}: {:?}", tx_hash.to_reversed_str(), err))?;
+			Ok(IndexedTransaction::new(tx_hash, tx))
+		})
+		.collect()
+}
+
+/// Hashes of side chain blocks, ordered so that parents come before their children.
+fn side_chain_blocks(db: &KeyValueDatabase) -> Result<Vec<H256>, String> {
+	let mut blocks = Vec::new();
+	for hash in db.block_hashes()? {
+		if read(db, Key::BlockNumber(hash))?.is_none() {
+			blocks.push((hash, block_header(db, &hash)?.previous_header_hash));
+		}
+	}
+
+	let side_chain: HashSet<H256> = blocks.iter().map(|&(hash, _)| hash).collect();
+	let mut ordered: Vec<H256> = Vec::with_capacity(blocks.len());
+	let mut visited = HashSet::new();
+	while !blocks.is_empty() {
+		let (ready, rest): (Vec<_>, Vec<_>) = blocks.into_iter()
+			.partition(|&(_, ref parent)| !side_chain.contains(parent) || visited.contains(parent));
+		if ready.is_empty() {
+			return Err("Side chain blocks have no known ancestors".into());
+		}
+
+		for (hash, _) in ready {
+			visited.insert(hash);
+			ordered.push(hash);
+		}
+		blocks = rest;
+	}
+
+	Ok(ordered)
+}
+
+/// Calls `f` for every known block: canon blocks by height, then side chain blocks, parents before children.
+fn for_each_block<F>(db: &KeyValueDatabase, block_files: &FlatFileStore, mut f: F) -> Result<(), String>
+	where F: FnMut(&H256, &BlockHeader, &mut Transaction) -> Result<(), String>
+{
+	let mut batch = Batch {
+		db: db,
+		block_files: block_files,
+		update: Transaction::new(),
+		blocks: 0,
+		last_block: H256::default(),
+	};
+
+	let mut number = 0;
+	while let Some(hash) = canon_block_hash(db, number)? {
+		batch.visit(&hash, &mut f)?;
+		number += 1;
+	}
+
+	for hash in side_chain_blocks(db)? {
+		batch.visit(&hash, &mut f)?;
+	}
+
+	batch.write()
+}
+
+/// Changes of the migration, that aren't written yet.
+struct Batch<'a> {
+	db: &'a KeyValueDatabase,
+	block_files: &'a FlatFileStore,
+	update: Transaction,
+	blocks: usize,
+	last_block: H256,
+}
+
+impl<'a> Batch<'a> {
+	fn visit<F>(&mut self, hash: &H256, f: &mut F) -> Result<(), String>
+		where F: FnMut(&H256, &BlockHeader, &mut Transaction) -> Result<(), String>
+	{
+		// records of the parent must be readable from the database, unless the parent is the last visited block
+		let header = block_header(self.db, hash)?;
+		if self.blocks == MIGRATION_BATCH_SIZE || (self.blocks != 0 && header.previous_header_hash != self.last_block) {
+			self.write()?;
+		}
+
+		f(hash, &header, &mut self.update)?;
+		self.blocks += 1;
+		self.last_block = *hash;
+		Ok(())
+	}
+
+	fn write(&mut self) -> Result<(), String> {
+		// raw blocks must be in their files before positions are written
+		self.block_files.flush().map_err(|err| err.to_string())?;
+		self.blocks = 0;
+		self.db.write(mem::replace(&mut self.update, Transaction::new()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use self::tempdir::TempDir;
+	use storage::Error;
+	use kv::MemoryDatabase;
+	use flat_file::FlatFileStore;
+	use super::{upgrade, db_version, write_version, DB_VERSION, MIGRATIONS};
+
+	fn block_files(dir: &TempDir) -> FlatFileStore {
+		FlatFileStore::open(dir.path(), "blk", 1024).unwrap()
+	}
+
+	#[test]
+	fn migrations_are_sorted() {
+		assert!(MIGRATIONS.windows(2).all(|pair| pair[0].version < pair[1].version));
+		assert_eq!(MIGRATIONS.last().map(|migration| migration.version), Some(DB_VERSION));
+	}
+
+	#[test]
+	fn fresh_database_gets_current_version() {
+		let dir = TempDir::new("").unwrap();
+		let db = MemoryDatabase::default();
+		upgrade(&db, &block_files(&dir), true).unwrap();
+		assert_eq!(db_version(&db).unwrap(), Some(DB_VERSION));
+	}
+
+	#[test]
+	fn unversioned_database_is_upgraded() {
+		let dir = TempDir::new("").unwrap();
+		let db = MemoryDatabase::default();
+		upgrade(&db, &block_files(&dir), false).unwrap();
+		assert_eq!(db_version(&db).unwrap(), Some(DB_VERSION));
+	}
+
+	#[test]
+	fn newer_database_is_refused() {
+		let dir = TempDir::new("").unwrap();
+		let db = MemoryDatabase::default();
+		write_version(&db, DB_VERSION + 1).unwrap();
+		assert_eq!(upgrade(&db, &block_files(&dir), false), Err(Error::UnsupportedDatabaseVersion(DB_VERSION + 1, DB_VERSION)));
+		assert_eq!(db_version(&db).unwrap(), Some(DB_VERSION + 1));
+	}
+}
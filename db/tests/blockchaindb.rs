@@ -1,12 +1,31 @@
 extern crate chain;
 extern crate storage;
 extern crate db;
+extern crate network;
 extern crate test_data;
+extern crate serialization as ser;
+extern crate tempdir;
 
-use chain::IndexedBlock;
-use storage::{ForkChain, BlockProvider, SideChainOrigin};
-use db::BlockChainDatabase;
-use db::kv::{MemoryDatabase, SharedMemoryDatabase};
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use chain::{IndexedBlock, Orchard, OrchardAction, OutPoint, Sapling, SaplingSpendDescription, SaplingOutputDescription};
+use ser::serialize;
+use tempdir::TempDir;
+use network::{ConsensusParams, Network, NetworkUpgrade};
+use storage::{
+	ForkChain, BlockProvider, BlockHeaderProvider, SideChainOrigin, ConfigStore, ShieldedStats, ShieldedStatsProvider,
+	TransactionProvider, BlockChain, FeeStatsProvider, ChainEventProvider, ChainEventKind, NullifierTracker,
+	EpochTag, EpochRef, TreeStateProvider, SaplingTreeState, AddressIndexProvider, AddressIndexEntry,
+	address_index_key, SpentIndexProvider, SpentIndexEntry, Error, ValuePoolsProvider, ValuePools,
+	TransactionMetaProvider, DatabaseInfo,
+};
+use db::{BlockChainDatabase, FlatFileStore, DatabaseSettings, DEFAULT_UNDO_HORIZON};
+use db::kv::{
+	MemoryDatabase, SharedMemoryDatabase, DiskDatabase, DatabaseConfig, CompactionStyle, KeyValueDatabase, KeyState,
+	Key, KeyValue, Transaction as DBTransaction, COL_COUNT,
+};
 
 #[test]
 fn insert_block() {
@@ -104,7 +123,7 @@ fn switch_to_simple_fork() {
 	assert_eq!(b1.hash(), &fork.store().best_block().hash);
 
 	fork.store().canonize(b2.hash()).unwrap();
-	store.switch_to_fork(fork).unwrap();
+	store.switch_to_fork(&fork).unwrap();
 
 	assert_eq!(2, store.best_block().number);
 	assert_eq!(b2.hash(), &store.best_block().hash);
@@ -128,3 +147,770 @@ fn switch_to_simple_fork() {
 	assert_eq!(store.best_block().hash, store.block_hash(2).unwrap());
 
 }
+
+#[test]
+fn network_config() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	assert_eq!(store.network(), Ok(None));
+
+	store.set_network("testnet").unwrap();
+	assert_eq!(store.network(), Ok(Some("testnet".to_owned())));
+}
+
+#[test]
+fn read_snapshot_blocks_canonization() {
+	let store = Arc::new(BlockChainDatabase::open(MemoryDatabase::default()));
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+
+	store.insert(b0.clone()).unwrap();
+	store.insert(b1.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+
+	let canonized = {
+		let snapshot = store.read_snapshot();
+		let canonize_store = store.clone();
+		let canonize_hash = b1.hash().clone();
+		let canonized = thread::spawn(move || canonize_store.canonize(&canonize_hash).unwrap());
+
+		// canonization waits until snapshot is dropped
+		thread::sleep(Duration::from_millis(100));
+		assert_eq!(snapshot.best_block().hash, *b0.hash());
+		assert_eq!(snapshot.store().block_hash(1), None);
+		assert_eq!(store.best_block().hash, *b0.hash());
+		canonized
+	};
+
+	canonized.join().unwrap();
+	assert_eq!(store.best_block().hash, *b1.hash());
+	assert_eq!(store.read_snapshot().best_block().hash, *b1.hash());
+}
+
+#[test]
+fn orchard_nullifiers_are_tracked() {
+	let store = BlockChainDatabase::open_with_cache(MemoryDatabase::default());
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let mut b1 = test_data::block_h1();
+	b1.transactions[0].orchard = Some(Orchard {
+		actions: vec![OrchardAction { nullifier: [1; 32], ..Default::default() }],
+		..Default::default()
+	});
+	let b1: IndexedBlock = b1.into();
+	let nullifier = EpochRef::new(EpochTag::Orchard, [1u8; 32].into());
+
+	store.insert(b0.clone()).unwrap();
+	store.insert(b1.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+	assert!(!store.contains_nullifier(nullifier));
+
+	store.canonize(b1.hash()).unwrap();
+	assert!(store.contains_nullifier(nullifier));
+	assert_eq!(store.nullifiers(EpochTag::Orchard).unwrap(), vec![*nullifier.hash()]);
+	assert!(store.nullifiers(EpochTag::Sapling).unwrap().is_empty());
+
+	store.decanonize().unwrap();
+	assert!(!store.contains_nullifier(nullifier));
+	assert!(store.nullifiers(EpochTag::Orchard).unwrap().is_empty());
+}
+
+#[test]
+fn block_roots_are_pruned() {
+	let store = BlockChainDatabase::init_test_chain(vec![
+		test_data::block_h0().into(),
+		test_data::block_h1().into(),
+		test_data::block_h2().into(),
+	]);
+	let b1 = test_data::block_h1().hash();
+	let b2 = test_data::block_h2().hash();
+	let sapling_root = store.sapling_block_root(&b1).unwrap();
+
+	store.prune_blocks(10).unwrap();
+	assert_eq!(store.sprout_block_root(&b1), None);
+	assert_eq!(store.sapling_block_root(&b1), None);
+	assert_eq!(store.sapling_block_root(&test_data::block_h0().hash()), None);
+	// best block root is kept && pruned roots are still valid anchors
+	assert_eq!(store.sapling_block_root(&b2), Some(sapling_root));
+	assert_eq!(store.sapling_tree_at(&sapling_root), Some(SaplingTreeState::new()));
+}
+
+#[test]
+fn sapling_state_is_reverted_on_reorg() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0 = test_data::block_builder()
+		.header().build()
+		.transaction().coinbase()
+			.output().value(100).build()
+			.build()
+		.build();
+	// b1 shields coinbase of b0 && spends sapling note
+	let mut b1 = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase()
+			.output().value(200).build()
+			.build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash()).index(0).build()
+			.build()
+		.build();
+	b1.transactions[1].sapling = Some(Sapling {
+		balancing_value: -100,
+		spends: vec![SaplingSpendDescription { nullifier: [1; 32], ..Default::default() }],
+		outputs: vec![SaplingOutputDescription { note_commitment: [2; 32], ..Default::default() }],
+		..Default::default()
+	});
+	let b2 = test_data::block_builder()
+		.header().parent(b1.hash()).build()
+		.transaction().coinbase()
+			.output().value(300).build()
+			.build()
+		.build();
+	// b1' has no shielded transactions
+	let b1_fork = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase()
+			.output().value(250).build()
+			.build()
+		.build();
+	let (b0_hash, b1_hash, b2_hash, b1_fork_hash) = (b0.hash(), b1.hash(), b2.hash(), b1_fork.hash());
+	let shielded_coinbase = b0.transactions[0].hash();
+	let nullifier = EpochRef::new(EpochTag::Sapling, [1u8; 32].into());
+
+	for block in vec![b0, b1, b2] {
+		let hash = block.hash();
+		store.insert(block.into()).unwrap();
+		store.canonize(&hash).unwrap();
+	}
+
+	let b0_sapling_root = store.sapling_block_root(&b0_hash).unwrap();
+	assert!(store.sapling_block_root(&b1_hash).unwrap() != b0_sapling_root);
+	assert!(store.contains_nullifier(nullifier));
+	assert_eq!(store.block_value_pools(&b2_hash).unwrap().sapling, 100);
+	assert!(store.transaction_meta(&shielded_coinbase).unwrap().is_fully_spent());
+
+	// old blocks are pruned, but their roots are restored from undo data when blocks are disconnected
+	store.prune_blocks(1).unwrap();
+	assert_eq!(store.sapling_block_root(&b0_hash), None);
+
+	assert_eq!(store.decanonize().unwrap(), b2_hash);
+	assert_eq!(store.decanonize().unwrap(), b1_hash);
+	assert!(!store.contains_nullifier(nullifier));
+	assert!(store.nullifiers(EpochTag::Sapling).unwrap().is_empty());
+	assert_eq!(store.sapling_block_root(&b0_hash), Some(b0_sapling_root));
+	assert_eq!(store.block_value_pools(&b0_hash), Some(ValuePools::default()));
+	assert!(!store.transaction_meta(&shielded_coinbase).unwrap().is_fully_spent());
+
+	// fork is built on top of the restored state
+	store.insert(b1_fork.into()).unwrap();
+	store.canonize(&b1_fork_hash).unwrap();
+	assert_eq!(store.sapling_block_root(&b1_fork_hash), Some(b0_sapling_root));
+	assert_eq!(store.block_value_pools(&b1_fork_hash).unwrap().sapling, 0);
+}
+
+#[test]
+fn blocks_are_pruned() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default()).with_pruning(0);
+	let b0 = test_data::block_builder()
+		.header().build()
+		.transaction().coinbase()
+			.output().value(100).build()
+			.build()
+		.build();
+	let b1 = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase()
+			.output().value(200).build()
+			.build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash()).index(0).build()
+			.output().value(60).build()
+			.build()
+		.build();
+	let b2 = test_data::block_builder()
+		.header().parent(b1.hash()).build()
+		.transaction().coinbase()
+			.output().value(300).build()
+			.build()
+		.transaction()
+			.input().hash(b1.transactions[0].hash()).index(0).build()
+			.output().value(150).build()
+			.build()
+		.build();
+	let (b0_hash, b1_hash, b2_hash) = (b0.hash(), b1.hash(), b2.hash());
+	let (spent_by_pruned, spent_by_kept, unspent) =
+		(b0.transactions[0].hash(), b1.transactions[0].hash(), b1.transactions[1].hash());
+
+	for block in vec![b0, b1, b2] {
+		let hash = block.hash();
+		store.insert(block.into()).unwrap();
+		store.canonize(&hash).unwrap();
+	}
+
+	// not enough blocks to prune
+	store.prune().unwrap();
+	assert_eq!(store.pruned_height(), 0);
+
+	store.prune_blocks(2).unwrap();
+	assert_eq!(store.pruned_height(), 2);
+	assert!(store.block(b0_hash.into()).is_none());
+	assert!(store.block(b1_hash.into()).is_none());
+	assert!(store.block(b2_hash.into()).is_some());
+	assert!(store.block_header(b1_hash.into()).is_some());
+
+	// fully spent transactions are removed, unless spent by blocks that are kept
+	assert!(store.transaction(&spent_by_pruned).is_none());
+	assert!(store.transaction(&spent_by_kept).is_some());
+	assert!(store.transaction(&unspent).is_some());
+
+	// pruned blocks can't be decanonized
+	store.decanonize().unwrap();
+	assert_eq!(store.best_block().hash, b1_hash);
+	assert!(store.decanonize().is_err());
+}
+
+#[test]
+fn block_undo_is_pruned() {
+	let shared_database = SharedMemoryDatabase::default();
+	let has_undo = |hash| shared_database.get(&Key::BlockUndo(hash)).map(KeyState::into_option).unwrap().is_some();
+	let b0 = test_data::block_builder().header().build().build();
+	let b1 = test_data::block_builder().header().parent(b0.hash()).build().build();
+	let b2 = test_data::block_builder().header().parent(b1.hash()).build().build();
+	let (b0_hash, b1_hash, b2_hash) = (b0.hash(), b1.hash(), b2.hash());
+
+	{
+		let store = BlockChainDatabase::open(shared_database.clone()).with_undo_horizon(0);
+		for block in vec![b0, b1, b2] {
+			let hash = block.hash();
+			store.insert(block.into()).unwrap();
+			store.canonize(&hash).unwrap();
+		}
+		assert!(has_undo(b0_hash.clone()));
+
+		// not enough blocks to prune
+		store.prune_undo().unwrap();
+		assert_eq!(store.undo_pruned_height(), 0);
+
+		store.prune_undo_blocks(2).unwrap();
+		assert_eq!(store.undo_pruned_height(), 2);
+		assert_eq!(store.pruned_height(), 0);
+		assert!(!has_undo(b0_hash.clone()));
+		assert!(!has_undo(b1_hash.clone()));
+		assert!(has_undo(b2_hash.clone()));
+
+		// blocks themselves are kept
+		assert!(store.block(b0_hash.clone().into()).is_some());
+		assert!(store.block(b1_hash.into()).is_some());
+	}
+
+	let store = BlockChainDatabase::open(shared_database.clone());
+	assert_eq!(store.undo_pruned_height(), 2);
+	assert!(has_undo(b2_hash));
+	assert!(store.block(b0_hash.into()).is_some());
+}
+
+#[test]
+fn snapshot_is_exported_and_imported() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0 = test_data::block_builder()
+		.header().build()
+		.transaction().coinbase()
+			.output().value(100).build()
+			.build()
+		.build();
+	let b1 = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase()
+			.output().value(200).build()
+			.build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash()).index(0).build()
+			.output().value(60).build()
+			.build()
+		.build();
+	let b2 = test_data::block_builder()
+		.header().parent(b1.hash()).build()
+		.transaction().coinbase()
+			.output().value(300).build()
+			.build()
+		.transaction()
+			.input().hash(b1.transactions[1].hash()).index(0).build()
+			.output().value(50).build()
+			.build()
+		.build();
+	let (b0_hash, b1_hash) = (b0.hash(), b1.hash());
+	let (spent, unspent) = (b0.transactions[0].hash(), b1.transactions[0].hash());
+
+	for block in vec![b0, b1] {
+		let hash = block.hash();
+		store.insert(block.into()).unwrap();
+		store.canonize(&hash).unwrap();
+	}
+
+	let mut snapshot = Vec::new();
+	let exported = store.export_snapshot(&mut snapshot).unwrap();
+	assert_eq!(exported.height, 1);
+	assert_eq!(exported.block_hash, b1_hash);
+
+	// snapshot of other chain is rejected
+	let imported_store = BlockChainDatabase::open(MemoryDatabase::default());
+	match imported_store.import_snapshot(&snapshot[..], &b1_hash) {
+		Err(Error::InvalidSnapshot(_)) => (),
+		result => panic!("unexpected import result: {:?}", result),
+	}
+
+	let imported_store = BlockChainDatabase::open(MemoryDatabase::default());
+	assert_eq!(imported_store.import_snapshot(&snapshot[..], &b0_hash).unwrap(), exported);
+	assert_eq!(imported_store.best_block(), store.best_block());
+	assert_eq!(imported_store.pruned_height(), 2);
+	assert!(imported_store.block_header(b0_hash.into()).is_some());
+	assert!(imported_store.block(b1_hash.into()).is_none());
+	assert!(imported_store.transaction(&spent).is_none());
+	assert!(imported_store.transaction(&unspent).is_some());
+	assert_eq!(imported_store.sapling_anchor_height(&SaplingTreeState::empty_root()), Some(0));
+	assert!(imported_store.import_snapshot(&snapshot[..], &b0_hash).is_err());
+
+	// chain is continued from the snapshot block
+	let b2_hash = b2.hash();
+	imported_store.insert(b2.into()).unwrap();
+	imported_store.canonize(&b2_hash).unwrap();
+	assert_eq!(imported_store.best_block().hash, b2_hash);
+}
+
+#[test]
+fn address_index_is_maintained() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default()).with_address_index();
+	let b0 = test_data::block_builder()
+		.header().build()
+		.transaction().coinbase()
+			.output().value(100).script_pubkey_bytes(vec![1u8].into()).build()
+			.build()
+		.build();
+	let b1 = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase().build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash()).index(0).build()
+			.output().value(60).script_pubkey_bytes(vec![2u8].into()).build()
+			.build()
+		.build();
+	let (b0_hash, b1_hash) = (b0.hash(), b1.hash());
+	let (received, spending) = (b0.transactions[0].hash(), b1.transactions[1].hash());
+
+	store.insert(b0.into()).unwrap();
+	store.insert(b1.into()).unwrap();
+	store.canonize(&b0_hash).unwrap();
+	store.canonize(&b1_hash).unwrap();
+
+	assert!(store.is_address_index_enabled());
+	assert_eq!(store.address_index_entries(&address_index_key(&[1u8])), vec![AddressIndexEntry {
+		height: 0,
+		tx_hash: received,
+		output_index: 0,
+		value: 100,
+		spent: true,
+	}]);
+	assert_eq!(store.address_index_entries(&address_index_key(&[2u8])), vec![AddressIndexEntry {
+		height: 1,
+		tx_hash: spending,
+		output_index: 0,
+		value: 60,
+		spent: false,
+	}]);
+
+	store.decanonize().unwrap();
+	assert!(!store.address_index_entries(&address_index_key(&[1u8]))[0].spent);
+	assert!(store.address_index_entries(&address_index_key(&[2u8])).is_empty());
+
+	// index isn't maintained unless enabled
+	let store = BlockChainDatabase::init_test_chain(vec![test_data::block_h0().into()]);
+	assert!(!store.is_address_index_enabled());
+	assert!(store.address_index_entries(&address_index_key(&test_data::block_h0().transactions[0].outputs[0].script_pubkey)).is_empty());
+}
+
+#[test]
+fn spent_index_is_maintained() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default()).with_spent_index();
+	let b0 = test_data::block_builder()
+		.header().build()
+		.transaction().coinbase()
+			.output().value(100).build()
+			.build()
+		.build();
+	let b1 = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase().build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash()).index(0).build()
+			.output().value(60).build()
+			.build()
+		.build();
+	let (b0_hash, b1_hash) = (b0.hash(), b1.hash());
+	let spent = OutPoint { hash: b0.transactions[0].hash(), index: 0 };
+	let spending = b1.transactions[1].hash();
+
+	store.insert(b0.into()).unwrap();
+	store.insert(b1.into()).unwrap();
+	store.canonize(&b0_hash).unwrap();
+	assert_eq!(store.spent_index_entry(&spent), None);
+
+	store.canonize(&b1_hash).unwrap();
+	assert!(store.is_spent_index_enabled());
+	assert_eq!(store.spent_index_entry(&spent), Some(SpentIndexEntry {
+		tx_hash: spending,
+		input_index: 0,
+		height: 1,
+	}));
+
+	store.decanonize().unwrap();
+	assert_eq!(store.spent_index_entry(&spent), None);
+	assert!(!BlockChainDatabase::open(MemoryDatabase::default()).is_spent_index_enabled());
+}
+
+#[test]
+fn shielded_stats_are_stored() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+
+	store.insert(b0.clone()).unwrap();
+	assert_eq!(store.block_shielded_stats(b0.hash()), Some(ShieldedStats::default()));
+	assert_eq!(store.block_shielded_stats(b1.hash()), None);
+}
+
+#[test]
+fn block_with_negative_value_pool_is_not_inserted() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let mut b1 = test_data::block_h1();
+	b1.transactions[0].sapling = Some(Sapling { balancing_value: 100, ..Default::default() });
+	let b1: IndexedBlock = b1.into();
+
+	store.insert(b0.clone()).unwrap();
+	assert!(store.insert(b1.clone()).is_err());
+	assert!(!store.contains_block(b1.hash().clone().into()));
+}
+
+#[test]
+fn fee_stats_are_stored_on_canonization() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0: IndexedBlock = test_data::block_builder().header().nonce(1.into()).build()
+		.transaction().output().value(1_000_000).build().build()
+		.build()
+		.into();
+	let b1: IndexedBlock = test_data::block_builder().header().parent(b0.hash().clone()).nonce(2.into()).build()
+		.transaction().coinbase().output().value(10).build().build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash.clone()).index(0).build()
+			.output().value(900_000).build()
+			.build()
+		.build()
+		.into();
+
+	store.insert(b0.clone()).unwrap();
+	store.insert(b1.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+	assert_eq!(store.block_fee_stats(b1.hash()), None);
+
+	store.canonize(b1.hash()).unwrap();
+	let stats = store.block_fee_stats(b1.hash()).unwrap();
+	assert_eq!(stats.transactions, 1);
+	assert_eq!(stats.total_fee, 100_000);
+	assert_eq!(stats.median_feerate, stats.average_feerate());
+
+	store.decanonize().unwrap();
+	assert_eq!(store.block_fee_stats(b1.hash()), None);
+}
+
+#[test]
+fn history_trees_are_maintained() {
+	// Heartwood is activated at block 1, Canopy is activated at block 3
+	let mut consensus = ConsensusParams::new(Network::Regtest);
+	for upgrade in &[NetworkUpgrade::Overwinter, NetworkUpgrade::Sapling, NetworkUpgrade::Blossom, NetworkUpgrade::Heartwood] {
+		consensus.upgrades.set_activation_height(*upgrade, Some(1));
+	}
+	consensus.upgrades.set_activation_height(NetworkUpgrade::Canopy, Some(3));
+	let store = BlockChainDatabase::open(MemoryDatabase::default()).with_chain_history(consensus);
+
+	let mut blocks: Vec<IndexedBlock> = Vec::new();
+	for nonce in 0..4u8 {
+		let block = match blocks.last() {
+			Some(parent) => test_data::block_builder().header().parent(*parent.hash()).nonce(nonce.into()).build().build(),
+			None => test_data::block_builder().header().nonce(nonce.into()).build().build(),
+		};
+		blocks.push(block.into());
+	}
+	let b2_fork: IndexedBlock = test_data::block_builder().header().parent(*blocks[1].hash()).nonce(10.into()).build().build().into();
+
+	for block in &blocks {
+		store.insert(block.clone()).unwrap();
+		store.canonize(block.hash()).unwrap();
+	}
+	store.insert(b2_fork.clone()).unwrap();
+
+	assert_eq!(store.history_tree_at_block(blocks[0].hash()), None);
+	assert_eq!(store.history_tree_at_block(blocks[1].hash()).unwrap().len(), 1);
+	let b2_tree = store.history_tree_at_block(blocks[2].hash()).unwrap();
+	assert_eq!(b2_tree.len(), 2);
+	assert_eq!(b2_tree.consensus_branch_id(), NetworkUpgrade::Heartwood.branch_id());
+	// tree is reset at Canopy activation
+	let b3_tree = store.history_tree_at_block(blocks[3].hash()).unwrap();
+	assert_eq!(b3_tree.len(), 1);
+	assert_eq!(b3_tree.consensus_branch_id(), NetworkUpgrade::Canopy.branch_id());
+	// side chain blocks have their own trees
+	let b2_fork_tree = store.history_tree_at_block(b2_fork.hash()).unwrap();
+	assert_eq!(b2_fork_tree.len(), 2);
+	assert!(b2_fork_tree.root() != b2_tree.root());
+
+	BlockChain::rollback_best(&store).unwrap();
+	assert_eq!(store.history_tree_at_block(blocks[3].hash()), None);
+	assert_eq!(store.history_tree_at_block(blocks[2].hash()), Some(b2_tree));
+}
+
+#[test]
+fn chain_events_are_journaled() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	assert_eq!(store.last_chain_event_sequence(), 0);
+
+	store.insert(b0.clone()).unwrap();
+	store.insert(b1.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+	store.canonize(b1.hash()).unwrap();
+	store.decanonize().unwrap();
+	assert_eq!(store.last_chain_event_sequence(), 3);
+
+	let events = store.chain_events(2, 10);
+	assert_eq!(events.len(), 2);
+	assert_eq!(events[0].sequence, 2);
+	assert_eq!(events[0].kind, ChainEventKind::Connected);
+	assert_eq!(&events[0].block_hash, b1.hash());
+	assert_eq!(events[0].block_number, 1);
+	assert_eq!(events[0].transactions, b1.transactions.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>());
+	assert_eq!(events[1].sequence, 3);
+	assert_eq!(events[1].kind, ChainEventKind::Disconnected);
+	assert_eq!(&events[1].block_hash, b1.hash());
+	assert_eq!(events[1].block_number, 1);
+	assert_eq!(store.chain_events(1, 1)[0].block_hash, *b0.hash());
+}
+
+#[test]
+fn blocks_are_stored_in_flat_files() {
+	let tempdir = TempDir::new("").unwrap();
+	let block_files = FlatFileStore::open(tempdir.path(), "blk", 1024 * 1024).unwrap();
+	let store = BlockChainDatabase::open(MemoryDatabase::default()).with_block_files(block_files);
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	let b1_fork: IndexedBlock = test_data::block_builder()
+		.header().parent(b0.hash().clone()).build()
+		.transaction().coinbase().build()
+		.build()
+		.into();
+
+	store.insert(b0.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+	store.insert(b1.clone()).unwrap();
+	store.canonize(b1.hash()).unwrap();
+
+	assert_eq!(store.raw_block(b1.hash()), Some(serialize(&b1.clone().to_raw_block())));
+	let tx = b1.transactions[0].clone();
+	assert_eq!(store.transaction_bytes(&tx.hash), Some(serialize(&tx.raw)));
+	assert_eq!(store.transaction(&tx.hash).map(|tx| tx.raw), Some(tx.raw.clone()));
+	assert_eq!(store.block(b1.hash().clone().into()).map(|block| block.to_raw_block()), Some(b1.to_raw_block()));
+
+	// side chain blocks are kept in the database
+	store.insert(b1_fork.clone()).unwrap();
+	assert_eq!(store.raw_block(b1_fork.hash()), None);
+	assert_eq!(store.block(b1_fork.hash().clone().into()).map(|block| block.to_raw_block()), Some(b1_fork.to_raw_block()));
+
+	// rolled back blocks are not accessible anymore
+	BlockChain::rollback_best(&store).unwrap();
+	assert_eq!(store.raw_block(b1.hash()), None);
+	assert_eq!(store.transaction(&tx.hash), None);
+}
+
+#[test]
+fn block_with_unreadable_transactions_is_unknown() {
+	let tempdir = TempDir::new("").unwrap();
+	// every block is appended to its own file
+	let block_files = FlatFileStore::open(tempdir.path(), "blk", 1).unwrap();
+	let store = BlockChainDatabase::open(MemoryDatabase::default()).with_block_files(block_files);
+	let b0: IndexedBlock = test_data::block_h0().into();
+	let b1: IndexedBlock = test_data::block_h1().into();
+	let b2: IndexedBlock = test_data::block_h2().into();
+
+	for block in &[&b0, &b1, &b2] {
+		store.insert((*block).clone()).unwrap();
+		store.canonize(block.hash()).unwrap();
+	}
+	assert!(store.block(b0.hash().clone().into()).is_some());
+
+	fs::remove_file(tempdir.path().join("blk00000.dat")).unwrap();
+	assert_eq!(store.block(b0.hash().clone().into()), None);
+	assert_eq!(store.block(b1.hash().clone().into()).map(|block| block.to_raw_block()), Some(b1.to_raw_block()));
+
+	// record hasn't reached the disk before the crash
+	let file = fs::OpenOptions::new().write(true).open(tempdir.path().join("blk00002.dat")).unwrap();
+	file.set_len(10).unwrap();
+	assert_eq!(store.raw_block(b2.hash()), None);
+	assert_eq!(store.block(b2.hash().clone().into()), None);
+}
+
+#[test]
+fn disk_database_prunes_undo_by_default() {
+	let tempdir = TempDir::new("").unwrap();
+	let store = BlockChainDatabase::open_at_path(tempdir.path(), DatabaseSettings::with_cache(16)).unwrap();
+
+	// enough blocks for the first batch of undo data to be pruned
+	let mut parent = None;
+	for _ in 0..DEFAULT_UNDO_HORIZON + 1000 {
+		let block = match parent {
+			Some(parent) => test_data::block_builder().header().parent(parent).build().build(),
+			None => test_data::block_builder().header().build().build(),
+		};
+		let hash = block.hash();
+		store.insert(block.into()).unwrap();
+		BlockChain::canonize(&store, &hash).unwrap();
+		parent = Some(hash);
+	}
+
+	assert_eq!(store.undo_pruned_height(), 1000);
+	assert_eq!(store.pruned_height(), 0);
+}
+
+#[test]
+fn unversioned_database_is_upgraded() {
+	let tempdir = TempDir::new("").unwrap();
+	let b0 = test_data::block_builder()
+		.header().build()
+		.transaction().coinbase()
+			.output().value(100).build()
+			.build()
+		.build();
+	// b1 reveals orchard nullifier && shields coinbase of b0
+	let mut b1 = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase()
+			.output().value(200).build()
+			.build()
+		.transaction()
+			.input().hash(b0.transactions[0].hash()).index(0).build()
+			.build()
+		.build();
+	b1.transactions[0].orchard = Some(Orchard {
+		actions: vec![OrchardAction { nullifier: [1; 32], ..Default::default() }],
+		..Default::default()
+	});
+	b1.transactions[1].sapling = Some(Sapling {
+		balancing_value: -100,
+		outputs: vec![SaplingOutputDescription { note_commitment: [2; 32], ..Default::default() }],
+		..Default::default()
+	});
+	let b2 = test_data::block_builder()
+		.header().parent(b1.hash()).build()
+		.transaction().coinbase()
+			.output().value(300).build()
+			.build()
+		.build();
+	let b1_fork = test_data::block_builder()
+		.header().parent(b0.hash()).build()
+		.transaction().coinbase()
+			.output().value(250).build()
+			.build()
+		.build();
+	let b2_fork = test_data::block_builder()
+		.header().parent(b1_fork.hash()).build()
+		.transaction().coinbase()
+			.output().value(350).build()
+			.build()
+		.build();
+	let shielded_coinbase = b0.transactions[0].hash();
+	let nullifier = EpochRef::new(EpochTag::Orchard, [1u8; 32].into());
+	let blocks: Vec<IndexedBlock> = vec![b0, b1, b2, b1_fork].into_iter().map(Into::into).collect();
+
+	let (sapling_roots, value_pools, shielded_stats) = {
+		let store = BlockChainDatabase::open_at_path(tempdir.path(), DatabaseSettings::with_cache(16)).unwrap();
+		for block in &blocks {
+			store.insert(block.clone()).unwrap();
+		}
+		for block in &blocks[..3] {
+			store.canonize(block.hash()).unwrap();
+		}
+		(
+			blocks.iter().map(|block| store.sapling_block_root(block.hash())).collect::<Vec<_>>(),
+			blocks.iter().map(|block| store.block_value_pools(block.hash())).collect::<Vec<_>>(),
+			blocks.iter().map(|block| store.block_shielded_stats(block.hash())).collect::<Vec<_>>(),
+		)
+	};
+
+	// database is rolled back to the layout, written before the database has been versioned
+	{
+		let db = DiskDatabase::open(DatabaseConfig::with_columns(Some(COL_COUNT)), tempdir.path()).unwrap();
+		let mut update = DBTransaction::new();
+		for block in &blocks {
+			update.delete(Key::ShieldedStats(*block.hash()));
+			update.delete(Key::SaplingBlockRoot(*block.hash()));
+			update.delete(Key::ValuePools(*block.hash()));
+			update.delete(Key::BlockUndo(*block.hash()));
+			update.delete(Key::BlockPosition(*block.hash()));
+			for tx in &block.transactions {
+				update.insert(KeyValue::Transaction(tx.hash, tx.raw.clone()));
+				update.delete(Key::TransactionPosition(tx.hash));
+			}
+		}
+		for root in sapling_roots.iter().filter_map(|root| *root) {
+			update.delete(Key::SaplingAnchor(root));
+		}
+		update.delete(Key::Nullifier(nullifier));
+		update.delete(Key::Configuration("db_version"));
+		db.write(update).unwrap();
+	}
+
+	let store = BlockChainDatabase::open_at_path(tempdir.path(), DatabaseSettings::with_cache(16)).unwrap();
+	for (index, block) in blocks.iter().enumerate() {
+		assert!(sapling_roots[index].is_some());
+		assert!(value_pools[index].is_some());
+		assert_eq!(store.sapling_block_root(block.hash()), sapling_roots[index]);
+		assert_eq!(store.block_value_pools(block.hash()), value_pools[index]);
+		assert_eq!(store.block_shielded_stats(block.hash()), shielded_stats[index]);
+		assert_eq!(store.raw_block(block.hash()), Some(serialize(&block.clone().to_raw_block())));
+	}
+	for tx in &blocks[1].transactions {
+		assert_eq!(store.transaction_bytes(&tx.hash), Some(serialize(&tx.raw)));
+	}
+	assert!(store.contains_nullifier(nullifier));
+	assert_eq!(store.sapling_anchor_height(&SaplingTreeState::empty_root()), Some(0));
+	assert_eq!(store.sapling_anchor_height(&sapling_roots[1].unwrap()), Some(1));
+
+	// side chain is continued && canon blocks are disconnected using the restored undo data
+	let b2_fork_hash = b2_fork.hash();
+	store.insert(b2_fork.into()).unwrap();
+	assert!(store.block_value_pools(&b2_fork_hash).is_some());
+	assert_eq!(store.decanonize().unwrap(), *blocks[2].hash());
+	assert_eq!(store.decanonize().unwrap(), *blocks[1].hash());
+	assert!(!store.contains_nullifier(nullifier));
+	assert!(!store.transaction_meta(&shielded_coinbase).unwrap().is_fully_spent());
+	assert_eq!(store.sapling_anchor_height(&sapling_roots[1].unwrap()), None);
+}
+
+#[test]
+fn disk_database_info() {
+	let store = BlockChainDatabase::open(MemoryDatabase::default());
+	assert_eq!(store.database_info(), DatabaseInfo::default());
+
+	let tempdir = TempDir::new("").unwrap();
+	let mut settings = DatabaseSettings::with_cache(16);
+	settings.write_buffer_size = 4;
+	settings.compaction.style = CompactionStyle::Level;
+	let store = BlockChainDatabase::open_at_path(tempdir.path(), settings).unwrap();
+	let b0: IndexedBlock = test_data::block_h0().into();
+	store.insert(b0.clone()).unwrap();
+	store.canonize(b0.hash()).unwrap();
+
+	let info = store.database_info();
+	assert!(info.on_disk);
+	assert_eq!(info.cache_size, 16);
+	assert_eq!(info.write_buffer_size, 4);
+	assert_eq!(info.compaction_style, "level");
+	assert_eq!(info.block_files, 1);
+	assert_eq!(info.block_files_size, serialize(&b0.to_raw_block()).len() as u64);
+}
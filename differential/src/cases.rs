@@ -0,0 +1,244 @@
+use rand::Rng;
+use serde_json::Value;
+use chain::{BlockHeader, Transaction, IndexedTransaction, merkle_root};
+use primitives::bytes::Bytes;
+use primitives::compact::Compact;
+use primitives::hash::H256;
+use ser::{Stream, CompactInteger, serialize, deserialize_strict};
+use zcashd::parse_hex;
+
+/// Change, applied to the block template to get the tested block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mutation {
+	/// Block is not changed.
+	None,
+	/// Block time is earlier than median time of previous blocks.
+	EarlyTime,
+	/// Random bit of difficulty bits is flipped.
+	Bits,
+	/// Block version is random.
+	Version,
+	/// Coinbase output value is increased.
+	CoinbaseValue,
+	/// Coinbase script is truncated or extended to random length.
+	CoinbaseScriptSize,
+	/// Coinbase lock time is random.
+	CoinbaseLockTime,
+	/// Coinbase transaction is included twice.
+	DuplicateCoinbase,
+	/// Coinbase transaction is removed.
+	NoCoinbase,
+	/// Random byte of serialized coinbase transaction is replaced, removed or inserted.
+	CoinbaseBytes,
+	/// Merkle root isn't updated after changing coinbase transaction.
+	StaleMerkleRoot,
+}
+
+const MUTATIONS: [Mutation; 11] = [
+	Mutation::None,
+	Mutation::EarlyTime,
+	Mutation::Bits,
+	Mutation::Version,
+	Mutation::CoinbaseValue,
+	Mutation::CoinbaseScriptSize,
+	Mutation::CoinbaseLockTime,
+	Mutation::DuplicateCoinbase,
+	Mutation::NoCoinbase,
+	Mutation::CoinbaseBytes,
+	Mutation::StaleMerkleRoot,
+];
+
+/// Block, extending the best block of zcashd, as it is proposed by zcashd `getblocktemplate`.
+pub struct BlockTemplate {
+	header: BlockHeader,
+	/// Coinbase transaction is the first transaction.
+	transactions: Vec<Transaction>,
+	/// Minimal valid block time.
+	min_time: u32,
+}
+
+impl BlockTemplate {
+	pub fn from_json(template: &Value) -> Result<Self, String> {
+		let coinbase = parse_transaction(&template["coinbasetxn"]["data"])?;
+		let transactions = template["transactions"].as_array()
+			.ok_or_else(|| "no transactions in block template".to_owned())?
+			.iter()
+			.map(|transaction| parse_transaction(&transaction["data"]))
+			.collect::<Result<Vec<_>, _>>()?;
+		let time = parse_u32(&template["curtime"])?;
+
+		let mut template = BlockTemplate {
+			header: BlockHeader {
+				version: parse_u32(&template["version"])?,
+				previous_header_hash: parse_hash(&template["previousblockhash"])?,
+				merkle_root_hash: Default::default(),
+				// the name of commitment field depends on the zcashd version && active network upgrade
+				final_sapling_root: ["blockcommitmentshash", "lightclientroothash", "finalsaplingroothash"].iter()
+					.map(|field| &template[*field])
+					.find(|value| !value.is_null())
+					.map(parse_hash)
+					.unwrap_or(Ok(Default::default()))?,
+				time: time,
+				bits: template["bits"].as_str()
+					.and_then(|bits| u32::from_str_radix(bits, 16).ok())
+					.map(Compact::new)
+					.ok_or_else(|| format!("invalid bits of block template: {}", template["bits"]))?,
+				nonce: Default::default(),
+				solution: Default::default(),
+			},
+			transactions: Some(coinbase).into_iter().chain(transactions).collect(),
+			min_time: if template["mintime"].is_null() { time } else { parse_u32(&template["mintime"])? },
+		};
+		template.header.merkle_root_hash = merkle_root(&template.transactions.iter()
+			.map(|tx| IndexedTransaction::from_raw(tx.clone()).hash)
+			.collect::<Vec<_>>());
+		Ok(template)
+	}
+
+	/// Serialized template block with random mutation applied.
+	pub fn block_case<R: Rng>(&self, rng: &mut R) -> (Mutation, Bytes) {
+		let mutation = *rng.choose(&MUTATIONS).expect("MUTATIONS is not empty; qed");
+		(mutation, self.mutated_block(mutation, rng))
+	}
+
+	/// Serialized template transaction with random byte replaced, removed or inserted.
+	pub fn transaction_case<R: Rng>(&self, rng: &mut R) -> Bytes {
+		let transaction = rng.choose(&self.transactions).expect("coinbase is always in template; qed");
+		mutated_bytes(serialize(transaction), rng)
+	}
+
+	fn mutated_block<R: Rng>(&self, mutation: Mutation, rng: &mut R) -> Bytes {
+		let mut header = self.header.clone();
+		let mut transactions = self.transactions.clone();
+		match mutation {
+			Mutation::None | Mutation::CoinbaseBytes | Mutation::StaleMerkleRoot => (),
+			Mutation::EarlyTime => header.time = self.min_time - rng.gen_range(1, 600),
+			Mutation::Bits => header.bits = Compact::new(u32::from(header.bits) ^ (1 << rng.gen_range(0, 32))),
+			Mutation::Version => header.version = rng.gen(),
+			Mutation::CoinbaseValue => transactions[0].outputs[0].value += rng.gen_range(1, 1_000_000),
+			Mutation::CoinbaseScriptSize => {
+				let size = rng.gen_range(0, 128);
+				let mut script_sig = transactions[0].inputs[0].script_sig.clone().take();
+				script_sig.resize(size, 0);
+				transactions[0].inputs[0].script_sig = script_sig.into();
+			},
+			Mutation::CoinbaseLockTime => transactions[0].lock_time = rng.gen(),
+			Mutation::DuplicateCoinbase => {
+				let coinbase = transactions[0].clone();
+				transactions.push(coinbase);
+			},
+			Mutation::NoCoinbase => {
+				transactions.remove(0);
+			},
+		}
+
+		let mut raw_transactions: Vec<Bytes> = transactions.iter().map(serialize).collect();
+		if mutation == Mutation::CoinbaseBytes || mutation == Mutation::StaleMerkleRoot {
+			raw_transactions[0] = mutated_bytes(raw_transactions[0].clone(), rng);
+		}
+
+		if mutation != Mutation::StaleMerkleRoot {
+			// transaction, that can't be decoded, is hashed as the original one
+			header.merkle_root_hash = merkle_root(&raw_transactions.iter()
+				.zip(transactions.iter())
+				.map(|(raw, original)| deserialize_strict::<_, Transaction>(&**raw)
+					.map(IndexedTransaction::from_raw)
+					.unwrap_or_else(|_| IndexedTransaction::from_raw(original.clone()))
+					.hash)
+				.collect::<Vec<H256>>());
+		}
+
+		let mut stream = Stream::default();
+		stream.append(&header).append(&CompactInteger::from(raw_transactions.len()));
+		for raw_transaction in &raw_transactions {
+			stream.append_slice(raw_transaction);
+		}
+		stream.out()
+	}
+}
+
+fn mutated_bytes<R: Rng>(bytes: Bytes, rng: &mut R) -> Bytes {
+	let mut bytes = bytes.take();
+	let index = rng.gen_range(0, bytes.len());
+	match rng.gen_range(0, 3) {
+		0 => bytes[index] = rng.gen(),
+		1 => {
+			bytes.remove(index);
+		},
+		_ => bytes.insert(index, rng.gen()),
+	}
+	bytes.into()
+}
+
+fn parse_transaction(value: &Value) -> Result<Transaction, String> {
+	deserialize_strict(&*parse_hex(value)?).map_err(|err| format!("cannot decode template transaction: {:?}", err))
+}
+
+fn parse_hash(value: &Value) -> Result<H256, String> {
+	value.as_str()
+		.and_then(|hash| hash.parse::<H256>().ok())
+		.map(|hash| hash.reversed())
+		.ok_or_else(|| format!("expected hash, got: {}", value))
+}
+
+fn parse_u32(value: &Value) -> Result<u32, String> {
+	value.as_u64()
+		.map(|value| value as u32)
+		.ok_or_else(|| format!("expected integer, got: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+	use hex::ToHex;
+	use rand::{SeedableRng, StdRng};
+	use chain::{Block, Transaction};
+	use ser::{serialize, deserialize};
+	use super::{BlockTemplate, Mutation};
+
+	fn template() -> BlockTemplate {
+		let coinbase: Transaction = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff025100ffffffff0250c30000000000002321027a46eb513588b01b37ea24303f4b628afd12cc20df789fede0921e43cad3e875acd43000000000000017a9147d46a730d31f97b1930d3368a967c309bd4d136a8700000000".into();
+		BlockTemplate::from_json(&json!({
+			"version": 4,
+			"previousblockhash": "029f11d80ef9765602235e1bc9727e3eb6ba20839319f761fee920d63401e327",
+			"finalsaplingroothash": "0000000000000000000000000000000000000000000000000000000000000000",
+			"transactions": [],
+			"coinbasetxn": { "data": serialize(&coinbase).to_hex::<String>() },
+			"curtime": 1_600_000_000,
+			"mintime": 1_599_999_000,
+			"bits": "200f0f0f",
+		})).unwrap()
+	}
+
+	#[test]
+	fn test_template_block() {
+		let mut rng: StdRng = SeedableRng::from_seed(&[42usize][..]);
+		let template = template();
+		let block: Block = deserialize(&*template.mutated_block(Mutation::None, &mut rng)).unwrap();
+		assert_eq!(block.block_header.previous_header_hash.to_reversed_str(), "029f11d80ef9765602235e1bc9727e3eb6ba20839319f761fee920d63401e327");
+		assert_eq!(u32::from(block.block_header.bits), 0x200f0f0f);
+		assert_eq!(block.block_header.merkle_root_hash, block.merkle_root());
+	}
+
+	#[test]
+	fn test_mutated_blocks() {
+		let mut rng: StdRng = SeedableRng::from_seed(&[42usize][..]);
+		let template = template();
+
+		let block: Block = deserialize(&*template.mutated_block(Mutation::EarlyTime, &mut rng)).unwrap();
+		assert!(block.block_header.time < 1_599_999_000);
+
+		let block: Block = deserialize(&*template.mutated_block(Mutation::DuplicateCoinbase, &mut rng)).unwrap();
+		assert_eq!(block.transactions.len(), 2);
+		assert_eq!(block.block_header.merkle_root_hash, block.merkle_root());
+
+		let block: Block = deserialize(&*template.mutated_block(Mutation::CoinbaseValue, &mut rng)).unwrap();
+		assert!(block.transactions[0].outputs[0].value > 50_000);
+		assert_eq!(block.block_header.merkle_root_hash, block.merkle_root());
+
+		// the same seed gives the same cases
+		let mut other_rng: StdRng = SeedableRng::from_seed(&[42usize][..]);
+		let mut rng: StdRng = SeedableRng::from_seed(&[42usize][..]);
+		assert_eq!(template.block_case(&mut rng), template.block_case(&mut other_rng));
+		assert_eq!(template.transaction_case(&mut rng), template.transaction_case(&mut other_rng));
+	}
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use chain::{Block, IndexedBlock, Transaction};
+use db::BlockChainDatabase;
+use db::kv::MemoryDatabase;
+use network::{ConsensusParams, Network, NetworkUpgrade};
+use primitives::bytes::Bytes;
+use ser::deserialize_strict;
+use verification::{BackwardsCompatibleChainVerifier, Verify, VerificationLevel};
+
+/// Copy of the zcashd chain, verified by zebra verification code.
+pub struct LocalChain {
+	store: Arc<BlockChainDatabase<MemoryDatabase>>,
+	consensus: ConsensusParams,
+	verifier: BackwardsCompatibleChainVerifier,
+}
+
+impl LocalChain {
+	pub fn new(network: Network) -> Self {
+		let store = Arc::new(BlockChainDatabase::open(MemoryDatabase::default()));
+		LocalChain {
+			store: store.clone(),
+			consensus: ConsensusParams::new(network),
+			verifier: BackwardsCompatibleChainVerifier::new(store, ConsensusParams::new(network)),
+		}
+	}
+
+	/// Check that zcashd schedules network upgrades (given as (branch id, height) pairs) at the same heights.
+	pub fn check_upgrades(&self, upgrades: &[(u32, u32)]) -> Result<(), String> {
+		for upgrade in NetworkUpgrade::all().iter().skip(1) {
+			let local = self.consensus.upgrades.activation_height(*upgrade);
+			let remote = upgrades.iter().find(|&&(branch_id, _)| branch_id == upgrade.branch_id()).map(|&(_, height)| height);
+			if local != remote {
+				return Err(format!("{} is activated at {:?} by zebra and at {:?} by zcashd", upgrade.name(), local, remote));
+			}
+		}
+		Ok(())
+	}
+
+	/// Append block of zcashd chain, without verification.
+	pub fn append(&self, block: &Bytes) -> Result<(), String> {
+		let block: Block = deserialize_strict(&**block).map_err(|err| format!("cannot decode zcashd block: {:?}", err))?;
+		let block = IndexedBlock::from_raw(block);
+		let hash = block.hash().clone();
+		self.store.insert(block)
+			.and_then(|_| self.store.canonize(&hash))
+			.map_err(|err| format!("cannot insert zcashd block {}: {:?}", hash.to_reversed_str(), err))
+	}
+
+	/// Fully verify block, extending the best block. Like zcashd block proposals, the header is not pre-verified
+	/// (i.e. proof of work isn't checked).
+	pub fn verify_block(&self, block: &Bytes) -> Result<(), String> {
+		let block: Block = deserialize_strict(&**block).map_err(|err| format!("{:?}", err))?;
+		self.verifier.verify(VerificationLevel::FULL | VerificationLevel::HINT_HEADER_PRE_VERIFIED, &IndexedBlock::from_raw(block))
+			.map_err(|err| format!("{:?}", err))
+	}
+
+	/// Returns error if transaction can't be decoded.
+	pub fn decode_transaction(&self, transaction: &Bytes) -> Result<(), String> {
+		deserialize_strict::<_, Transaction>(&**transaction)
+			.map(|_| ())
+			.map_err(|err| format!("{:?}", err))
+	}
+}
@@ -0,0 +1,154 @@
+//! Differential consensus testing of zebra verification code against zcashd.
+//!
+//! Copies the regtest chain of local zcashd into the in-memory zebra database, then generates
+//! block and transaction cases from zcashd block template and feeds every case to both implementations:
+//! blocks are checked by zcashd as `getblocktemplate` proposals and by zebra chain verifier,
+//! transactions are decoded by zcashd `decoderawtransaction` and by zebra deserializer.
+//! Every case, accepted by one implementation and rejected by other, is printed as divergence:
+//!
+//! ```text
+//! differential <RPC_ADDRESS> <USER:PASSWORD> [CASES] [SEED]
+//! ```
+//!
+//! zcashd must be started with `-regtest` (without `-nuparams`, because zebra regtest has no
+//! network upgrades scheduled) and with `-mineraddress`, so that it could generate block templates.
+//! Proof of work of blocks isn't checked by both implementations.
+//! Exits with non-zero code if any divergence is found.
+
+extern crate chain;
+extern crate db;
+extern crate network;
+extern crate primitives;
+extern crate rand;
+extern crate rustc_hex as hex;
+#[macro_use]
+extern crate serde_json;
+extern crate serialization as ser;
+extern crate verification;
+
+mod cases;
+mod local;
+mod zcashd;
+
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::process;
+use hex::ToHex;
+use rand::{SeedableRng, StdRng};
+use network::Network;
+use cases::BlockTemplate;
+use local::LocalChain;
+use zcashd::{Zcashd, ProposalResult};
+
+const USAGE: &'static str = "Usage: differential <RPC_ADDRESS> <USER:PASSWORD> [CASES] [SEED]";
+/// Number of generated cases of each kind, if not specified.
+const DEFAULT_CASES: u32 = 1000;
+
+struct Args {
+	address: SocketAddr,
+	user_password: String,
+	cases: u32,
+	seed: usize,
+}
+
+fn main() {
+	let args = match parse_args(env::args().skip(1).collect()) {
+		Ok(args) => args,
+		Err(err) => {
+			eprintln!("{}\n{}", err, USAGE);
+			process::exit(2);
+		},
+	};
+
+	match run(args) {
+		Ok(0) => (),
+		Ok(divergences) => {
+			println!("{} divergences found", divergences);
+			process::exit(1);
+		},
+		Err(err) => {
+			eprintln!("{}", err);
+			process::exit(2);
+		},
+	}
+}
+
+/// Run all cases. Returns number of divergences.
+fn run(args: Args) -> Result<u32, String> {
+	let zcashd = Zcashd::new(args.address, &args.user_password);
+	let local = LocalChain::new(Network::Regtest);
+	local.check_upgrades(&zcashd.upgrades()?)?;
+
+	let best_block = zcashd.block_count()?;
+	println!("Copying {} blocks of zcashd chain", best_block + 1);
+	for height in 0..best_block + 1 {
+		local.append(&zcashd.block(height)?)?;
+	}
+
+	let template = BlockTemplate::from_json(&zcashd.block_template()?)?;
+	let mut rng: StdRng = SeedableRng::from_seed(&[args.seed][..]);
+	println!("Running {} block and {} transaction cases with seed {}", args.cases, args.cases, args.seed);
+
+	let (mut divergences, mut inconclusive) = (0, 0);
+	for case in 0..args.cases {
+		let (mutation, block) = template.block_case(&mut rng);
+		let zcashd_result = match zcashd.propose_block(&block)? {
+			ProposalResult::Accepted => Ok(()),
+			ProposalResult::Rejected(reason) => Err(reason),
+			ProposalResult::Inconclusive(_) => {
+				inconclusive += 1;
+				continue;
+			},
+		};
+		let zebra_result = local.verify_block(&block);
+		if zcashd_result.is_ok() != zebra_result.is_ok() {
+			divergences += 1;
+			print_divergence(&format!("block #{} ({:?})", case, mutation), zcashd_result, zebra_result, block.to_hex());
+		}
+
+		let transaction = template.transaction_case(&mut rng);
+		let zcashd_result = zcashd.decode_transaction(&transaction);
+		let zebra_result = local.decode_transaction(&transaction);
+		if zcashd_result.is_ok() != zebra_result.is_ok() {
+			divergences += 1;
+			print_divergence(&format!("transaction #{}", case), zcashd_result, zebra_result, transaction.to_hex());
+		}
+	}
+
+	if inconclusive != 0 {
+		println!("{} block cases were inconclusive (has zcashd chain changed?)", inconclusive);
+	}
+	Ok(divergences)
+}
+
+fn print_divergence(case: &str, zcashd_result: Result<(), String>, zebra_result: Result<(), String>, raw: String) {
+	let outcome = |result: Result<(), String>| match result {
+		Ok(()) => "accepted".to_owned(),
+		Err(reason) => format!("rejected: {}", reason),
+	};
+
+	println!("DIVERGENCE {}\n\tzcashd: {}\n\tzebra: {}\n\t{}", case, outcome(zcashd_result), outcome(zebra_result), raw);
+}
+
+fn parse_args(args: Vec<String>) -> Result<Args, String> {
+	let address = args.get(0).ok_or_else(|| "zcashd RPC address is required".to_owned())?;
+	let address = address.parse::<SocketAddr>()
+		.or_else(|_| address.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, Network::Regtest.rpc_port())))
+		.map_err(|_| format!("Invalid zcashd RPC address: {}", address))?;
+	let user_password = args.get(1).ok_or_else(|| "zcashd RPC credentials are required".to_owned())?;
+	let cases = match args.get(2) {
+		Some(cases) => cases.parse().map_err(|_| format!("Invalid number of cases: {}", cases))?,
+		None => DEFAULT_CASES,
+	};
+	let seed = match args.get(3) {
+		Some(seed) => seed.parse().map_err(|_| format!("Invalid seed: {}", seed))?,
+		None => rand::random(),
+	};
+
+	Ok(Args {
+		address: address,
+		user_password: user_password.clone(),
+		cases: cases,
+		seed: seed,
+	})
+}
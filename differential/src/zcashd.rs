@@ -0,0 +1,153 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use hex::{ToHex, FromHex};
+use serde_json::{self, Value};
+use primitives::bytes::Bytes;
+
+/// Number of seconds to wait for the response.
+const RESPONSE_TIMEOUT_SECS: u64 = 60;
+
+/// Blocking JSON-RPC client of zcashd.
+pub struct Zcashd {
+	address: SocketAddr,
+	/// Base64-encoded `user:password`.
+	credentials: String,
+}
+
+/// Result of block proposal, checked by zcashd.
+#[derive(Debug, PartialEq)]
+pub enum ProposalResult {
+	Accepted,
+	Rejected(String),
+	/// zcashd can't decide (i.e. proposal doesn't extend its best block).
+	Inconclusive(String),
+}
+
+impl Zcashd {
+	pub fn new(address: SocketAddr, user_password: &str) -> Self {
+		Zcashd {
+			address: address,
+			credentials: base64(user_password.as_bytes()),
+		}
+	}
+
+	/// Call RPC method. Returns result of the call or error message.
+	pub fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+		let request = json!({
+			"jsonrpc": "1.0",
+			"id": "differential",
+			"method": method,
+			"params": params,
+		}).to_string();
+
+		let timeout = Duration::from_secs(RESPONSE_TIMEOUT_SECS);
+		let mut stream = TcpStream::connect_timeout(&self.address, timeout)
+			.and_then(|stream| stream.set_read_timeout(Some(timeout)).map(|_| stream))
+			.map_err(|err| format!("cannot connect to zcashd: {}", err))?;
+		write!(stream,
+			"POST / HTTP/1.1\r\nHost: {}\r\nAuthorization: Basic {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+			self.address, self.credentials, request.len(), request,
+		).map_err(|err| format!("cannot send request to zcashd: {}", err))?;
+
+		let mut response = String::new();
+		stream.read_to_string(&mut response).map_err(|err| format!("cannot read response of zcashd: {}", err))?;
+		let body = response.splitn(2, "\r\n\r\n").nth(1)
+			.ok_or_else(|| format!("invalid HTTP response of zcashd: {}", response))?;
+		let mut response: Value = serde_json::from_str(body)
+			.map_err(|err| format!("invalid JSON response of zcashd ({}): {}", err, body))?;
+
+		match response["error"].take() {
+			Value::Null => Ok(response["result"].take()),
+			error => Err(format!("{} failed: {}", method, error)),
+		}
+	}
+
+	pub fn block_count(&self) -> Result<u32, String> {
+		self.call("getblockcount", json!([]))?
+			.as_u64()
+			.map(|count| count as u32)
+			.ok_or_else(|| "getblockcount returned non-integer".into())
+	}
+
+	/// Serialized block at given height.
+	pub fn block(&self, height: u32) -> Result<Bytes, String> {
+		let hash = self.call("getblockhash", json!([height]))?;
+		self.call("getblock", json!([hash, 0]))
+			.and_then(|block| parse_hex(&block))
+	}
+
+	/// Activation heights of network upgrades, scheduled by zcashd, as (branch id, height) pairs.
+	pub fn upgrades(&self) -> Result<Vec<(u32, u32)>, String> {
+		let info = self.call("getblockchaininfo", json!([]))?;
+		let upgrades = match info["upgrades"].as_object() {
+			Some(upgrades) => upgrades,
+			None => return Ok(Vec::new()),
+		};
+
+		upgrades.iter()
+			.map(|(branch_id, upgrade)| {
+				let branch_id = u32::from_str_radix(branch_id, 16)
+					.map_err(|_| format!("invalid branch id: {}", branch_id))?;
+				let height = upgrade["activationheight"].as_u64()
+					.ok_or_else(|| format!("no activation height of upgrade {:08x}", branch_id))?;
+				Ok((branch_id, height as u32))
+			})
+			.collect()
+	}
+
+	pub fn block_template(&self) -> Result<Value, String> {
+		self.call("getblocktemplate", json!([]))
+	}
+
+	/// Check block that extends the best block of zcashd, without checking its proof of work.
+	pub fn propose_block(&self, block: &Bytes) -> Result<ProposalResult, String> {
+		match self.call("getblocktemplate", json!([{ "mode": "proposal", "data": block.to_hex::<String>() }])) {
+			Ok(Value::Null) => Ok(ProposalResult::Accepted),
+			Ok(Value::String(ref reason)) if reason.starts_with("inconclusive") => Ok(ProposalResult::Inconclusive(reason.clone())),
+			Ok(Value::String(reason)) => Ok(ProposalResult::Rejected(reason)),
+			Ok(result) => Err(format!("unexpected proposal result: {}", result)),
+			// undecodable block is reported as RPC error
+			Err(err) => Ok(ProposalResult::Rejected(err)),
+		}
+	}
+
+	/// Returns error if zcashd can't decode transaction.
+	pub fn decode_transaction(&self, transaction: &Bytes) -> Result<(), String> {
+		self.call("decoderawtransaction", json!([transaction.to_hex::<String>()])).map(|_| ())
+	}
+}
+
+pub fn parse_hex(value: &Value) -> Result<Bytes, String> {
+	value.as_str()
+		.and_then(|hex| hex.from_hex::<Vec<u8>>().ok())
+		.map(Into::into)
+		.ok_or_else(|| format!("expected hex string, got: {}", value))
+}
+
+fn base64(input: &[u8]) -> String {
+	const ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+	let mut output = String::new();
+	for chunk in input.chunks(3) {
+		let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let indices = [bytes[0] >> 2, (bytes[0] & 0x03) << 4 | bytes[1] >> 4, (bytes[1] & 0x0f) << 2 | bytes[2] >> 6, bytes[2] & 0x3f];
+		for (i, index) in indices.iter().enumerate() {
+			output.push(if i <= chunk.len() { ALPHABET[*index as usize] as char } else { '=' });
+		}
+	}
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use super::base64;
+
+	#[test]
+	fn test_base64() {
+		assert_eq!(base64(b""), "");
+		assert_eq!(base64(b"u"), "dQ==");
+		assert_eq!(base64(b"us"), "dXM=");
+		assert_eq!(base64(b"user:password"), "dXNlcjpwYXNzd29yZA==");
+	}
+}
@@ -1,4 +1,5 @@
 use std::{io, fs, path};
+use std::io::BufReader;
 use std::collections::BTreeSet;
 use ser::{ReadIterator, deserialize_iterator, Error as ReaderError};
 use block::Block;
@@ -8,13 +9,13 @@ pub fn open_blk_file<P>(path: P) -> Result<BlkFile, io::Error> where P: AsRef<pa
 	trace!("Opening blk file: {:?}", path.as_ref());
 	let file = try!(fs::File::open(path));
 	let blk_file = BlkFile {
-		reader: deserialize_iterator(file),
+		reader: deserialize_iterator(BufReader::new(file)),
 	};
 	Ok(blk_file)
 }
 
 pub struct BlkFile {
-	reader: ReadIterator<fs::File, Block>,
+	reader: ReadIterator<BufReader<fs::File>, Block>,
 }
 
 impl Iterator for BlkFile {
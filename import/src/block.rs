@@ -1,7 +1,7 @@
 use std::io;
 use hash::H32;
 use ser::{Deserializable, Reader, Error as ReaderError};
-use chain::IndexedBlock;
+use chain::{BlockReader, IndexedBlock};
 
 #[derive(Debug, PartialEq)]
 pub struct Block {
@@ -20,10 +20,15 @@ impl Deserializable for Block {
 		// not to have zero bytes in it AND block is always prefixed with magic.
 		reader.skip_while(&|byte| byte == 0)?;
 
+		let magic = reader.read()?;
+		let block_size = reader.read()?;
+		// transactions are hashed while they're read => no need to serialize them again
+		let block = BlockReader::new(reader)?.read_block()?;
+
 		Ok(Block {
-			magic: reader.read()?,
-			block_size: reader.read()?,
-			block: reader.read()?,
+			magic: magic,
+			block_size: block_size,
+			block: block,
 		})
 	}
 }
\ No newline at end of file
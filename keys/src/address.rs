@@ -11,8 +11,16 @@ use std::ops::Deref;
 use base58::{ToBase58, FromBase58};
 use crypto::checksum;
 use network::Network;
+use bytes::Bytes;
 use {DisplayLayout, Error, AddressHash};
 
+const OP_DUP: u8 = 0x76;
+const OP_HASH160: u8 = 0xa9;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_PUSHBYTES_20: u8 = 0x14;
+
 /// There are two transparent address formats currently in use.
 /// https://bitcoin.org/en/developer-reference#address-conversion
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -38,6 +46,46 @@ pub struct Address {
 	pub hash: AddressHash,
 }
 
+impl Address {
+	/// Returns script that pays to this address.
+	///
+	/// This is the only way the founders reward (and any other consensus-defined) output
+	/// script should be computed, so that consensus validation && block templates never diverge.
+	pub fn to_script_pubkey(&self) -> Bytes {
+		let mut script = Vec::with_capacity(25);
+		match self.kind {
+			Type::P2PKH => {
+				script.extend_from_slice(&[OP_DUP, OP_HASH160, OP_PUSHBYTES_20]);
+				script.extend_from_slice(&*self.hash);
+				script.extend_from_slice(&[OP_EQUALVERIFY, OP_CHECKSIG]);
+			},
+			Type::P2SH => {
+				script.extend_from_slice(&[OP_HASH160, OP_PUSHBYTES_20]);
+				script.extend_from_slice(&*self.hash);
+				script.push(OP_EQUAL);
+			},
+		}
+		script.into()
+	}
+
+	/// Parses address from the standard P2PKH or P2SH script.
+	pub fn from_script_pubkey(script: &[u8], network: Network) -> Option<Self> {
+		let (kind, hash) = match script.len() {
+			25 if script[..3] == [OP_DUP, OP_HASH160, OP_PUSHBYTES_20] && script[23..] == [OP_EQUALVERIFY, OP_CHECKSIG] =>
+				(Type::P2PKH, &script[3..23]),
+			23 if script[..2] == [OP_HASH160, OP_PUSHBYTES_20] && script[22] == OP_EQUAL =>
+				(Type::P2SH, &script[2..22]),
+			_ => return None,
+		};
+
+		Some(Address {
+			kind: kind,
+			network: network,
+			hash: AddressHash::from(hash),
+		})
+	}
+}
+
 pub struct AddressDisplayLayout([u8; 26]);
 
 impl Deref for AddressDisplayLayout {
@@ -145,4 +193,26 @@ mod tests {
 
 		assert_eq!(address, "t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into());
 	}
+
+	#[test]
+	fn test_address_to_script_pubkey() {
+		let address: Address = "t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into();
+		let script = address.to_script_pubkey();
+		assert_eq!(script, "76a914ff197b14e502ab41f3bc8ccb48c4abac9eab35bc88ac".into());
+		assert_eq!(Address::from_script_pubkey(&script, Network::Mainnet), Some(address));
+
+		// first mainnet founders address
+		let address: Address = "t3Vz22vK5z2LcKEdg16Yv4FFneEL1zg9ojd".into();
+		let script = address.to_script_pubkey();
+		assert_eq!(&script[..2], &[0xa9, 0x14]);
+		assert_eq!(&script[2..22], &*address.hash);
+		assert_eq!(script[22], 0x87);
+		assert_eq!(Address::from_script_pubkey(&script, Network::Mainnet), Some(address));
+	}
+
+	#[test]
+	fn test_address_from_nonstandard_script_pubkey() {
+		assert_eq!(Address::from_script_pubkey(&[], Network::Mainnet), None);
+		assert_eq!(Address::from_script_pubkey(&[0x51], Network::Mainnet), None);
+	}
 }
@@ -1,10 +1,191 @@
-use chain::{BlockHeader, ShortTransactionID};
+use std::io;
+use byteorder::{LittleEndian, ByteOrder};
+use chain::{BlockHeader, IndexedBlock, ShortTransactionID};
+use crypto::{sha256, siphash24};
+use hash::H256;
+use ser::{
+	Serializable, Stream, CompactInteger,
+	Deserializable, Reader, Error as ReaderError,
+};
 use common::PrefilledTransaction;
 
-#[derive(Debug, PartialEq, Serializable, Deserializable)]
+/// Header and short transactions ids of the compact block.
+/// https://github.com/bitcoin/bips/blob/master/bip-0152.mediawiki#HeaderAndShortIDs
+#[derive(Debug, PartialEq)]
 pub struct BlockHeaderAndIDs {
 	pub header: BlockHeader,
 	pub nonce: u64,
 	pub short_ids: Vec<ShortTransactionID>,
+	/// Prefilled transactions with absolute indexes (they're differentially encoded on the wire).
 	pub prefilled_transactions: Vec<PrefilledTransaction>,
 }
+
+impl BlockHeaderAndIDs {
+	/// Build compact block from the block. Transactions with given (sorted) indexes are prefilled,
+	/// all other transactions are replaced with short ids.
+	pub fn from_block(block: &IndexedBlock, nonce: u64, prefilled: &[usize]) -> Self {
+		let mut compact = BlockHeaderAndIDs {
+			header: block.header.raw.clone(),
+			nonce: nonce,
+			short_ids: Vec::new(),
+			prefilled_transactions: Vec::new(),
+		};
+
+		let (key0, key1) = compact.short_id_keys();
+		for (index, transaction) in block.transactions.iter().enumerate() {
+			if prefilled.contains(&index) {
+				compact.prefilled_transactions.push(PrefilledTransaction {
+					index: index,
+					transaction: transaction.raw.clone(),
+				});
+			} else {
+				compact.short_ids.push(short_transaction_id(key0, key1, &transaction.hash));
+			}
+		}
+
+		compact
+	}
+
+	/// SipHash keys, used to compute short transactions ids of this block: first two little-endian
+	/// 64-bit integers of the single-SHA256 of the serialized header with nonce appended.
+	pub fn short_id_keys(&self) -> (u64, u64) {
+		let mut stream = Stream::default();
+		stream
+			.append(&self.header)
+			.append(&self.nonce);
+		let keys_hash = sha256(&stream.out());
+		(LittleEndian::read_u64(&keys_hash[0..8]), LittleEndian::read_u64(&keys_hash[8..16]))
+	}
+}
+
+/// Short transaction id: SipHash-2-4 of the transaction hash, truncated to 6 lowest bytes.
+pub fn short_transaction_id(key0: u64, key1: u64, transaction_hash: &H256) -> ShortTransactionID {
+	let siphash = siphash24(key0, key1, &**transaction_hash);
+	let mut siphash_bytes = [0u8; 8];
+	LittleEndian::write_u64(&mut siphash_bytes, siphash);
+	ShortTransactionID::from(&siphash_bytes[0..6])
+}
+
+impl Serializable for BlockHeaderAndIDs {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.header)
+			.append(&self.nonce)
+			.append_list(&self.short_ids)
+			.append(&CompactInteger::from(self.prefilled_transactions.len()));
+
+		let mut next_index = 0;
+		for prefilled in &self.prefilled_transactions {
+			stream
+				.append(&CompactInteger::from(prefilled.index - next_index))
+				.append(&prefilled.transaction);
+			next_index = prefilled.index + 1;
+		}
+	}
+}
+
+impl Deserializable for BlockHeaderAndIDs {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let header = reader.read()?;
+		let nonce = reader.read()?;
+		let short_ids = reader.read_list()?;
+
+		let prefilled_len: usize = reader.read::<CompactInteger>()?.into();
+		let mut prefilled_transactions = Vec::new();
+		let mut next_index = 0usize;
+		for _ in 0..prefilled_len {
+			let index_diff: usize = reader.read::<CompactInteger>()?.into();
+			let index = next_index.checked_add(index_diff).ok_or(ReaderError::MalformedData)?;
+			prefilled_transactions.push(PrefilledTransaction {
+				index: index,
+				transaction: reader.read()?,
+			});
+			next_index = index.checked_add(1).ok_or(ReaderError::MalformedData)?;
+		}
+
+		Ok(BlockHeaderAndIDs {
+			header: header,
+			nonce: nonce,
+			short_ids: short_ids,
+			prefilled_transactions: prefilled_transactions,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Block, BlockHeader, IndexedBlock, Transaction};
+	use crypto::siphash24;
+	use ser::{serialize, deserialize};
+	use super::{BlockHeaderAndIDs, short_transaction_id};
+
+	fn test_block() -> IndexedBlock {
+		let header = BlockHeader {
+			version: 4,
+			previous_header_hash: 1.into(),
+			merkle_root_hash: 2.into(),
+			final_sapling_root: 3.into(),
+			time: 4,
+			bits: 5.into(),
+			nonce: 6.into(),
+			solution: Default::default(),
+		};
+		let transactions = (0..4).map(|lock_time| Transaction { lock_time: lock_time, ..Default::default() }).collect();
+		IndexedBlock::from_raw(Block::new(header, transactions))
+	}
+
+	#[test]
+	fn test_short_transaction_id() {
+		// short id is 6 lowest bytes of little-endian siphash
+		let hash = [1u8; 32].into();
+		let siphash = siphash24(2, 3, &[1u8; 32]);
+		let short_id = short_transaction_id(2, 3, &hash);
+		assert_eq!(&*short_id, &[
+			siphash as u8, (siphash >> 8) as u8, (siphash >> 16) as u8,
+			(siphash >> 24) as u8, (siphash >> 32) as u8, (siphash >> 40) as u8,
+		]);
+	}
+
+	#[test]
+	fn test_block_header_and_ids_from_block() {
+		let block = test_block();
+		let compact = BlockHeaderAndIDs::from_block(&block, 0x0102030405060708, &[0, 2]);
+		let (key0, key1) = compact.short_id_keys();
+
+		assert_eq!(compact.prefilled_transactions.iter().map(|tx| tx.index).collect::<Vec<_>>(), vec![0, 2]);
+		assert_eq!(compact.prefilled_transactions[1].transaction, block.transactions[2].raw);
+		assert_eq!(compact.short_ids, vec![
+			short_transaction_id(key0, key1, &block.transactions[1].hash),
+			short_transaction_id(key0, key1, &block.transactions[3].hash),
+		]);
+
+		// keys depend on nonce
+		let other = BlockHeaderAndIDs::from_block(&block, 0x0102030405060709, &[0, 2]);
+		assert!(other.short_id_keys() != (key0, key1));
+		assert!(other.short_ids != compact.short_ids);
+	}
+
+	#[test]
+	fn test_block_header_and_ids_differential_indexes() {
+		let block = test_block();
+		let compact = BlockHeaderAndIDs::from_block(&block, 0, &[0, 2, 3]);
+
+		// index of first prefilled transaction is absolute, following are differences
+		// with previous index + 1
+		let mut expected = serialize(&compact.header).take();
+		expected.extend_from_slice(&[0; 8]);
+		expected.push(1);
+		expected.extend_from_slice(&*compact.short_ids[0]);
+		expected.push(3);
+		expected.push(0);
+		expected.extend_from_slice(&serialize(&block.transactions[0].raw));
+		expected.push(1);
+		expected.extend_from_slice(&serialize(&block.transactions[2].raw));
+		expected.push(0);
+		expected.extend_from_slice(&serialize(&block.transactions[3].raw));
+
+		let serialized = serialize(&compact);
+		assert_eq!(serialized.take(), expected);
+		assert_eq!(deserialize::<_, BlockHeaderAndIDs>(serialize(&compact).as_ref()).unwrap(), compact);
+	}
+}
@@ -5,17 +5,25 @@ use ser::{
 	Deserializable, Reader, Error as ReaderError,
 };
 
+/// Request for transactions of the compact block.
+/// https://github.com/bitcoin/bips/blob/master/bip-0152.mediawiki#BlockTransactionsRequest
 #[derive(Debug, PartialEq)]
 pub struct BlockTransactionsRequest {
 	pub blockhash: H256,
+	/// Sorted absolute indexes of requested transactions (they're differentially encoded on the wire).
 	pub indexes: Vec<usize>,
 }
 
 impl Serializable for BlockTransactionsRequest {
 	fn serialize(&self, stream: &mut Stream) {
+		let mut next_index = 0;
 		let indexes: Vec<CompactInteger> = self.indexes
 			.iter()
-			.map(|x| (*x).into())
+			.map(|x| {
+				let index_diff = *x - next_index;
+				next_index = *x + 1;
+				index_diff.into()
+			})
 			.collect();
 
 		stream
@@ -27,13 +35,50 @@ impl Serializable for BlockTransactionsRequest {
 impl Deserializable for BlockTransactionsRequest {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
 		let blockhash = try!(reader.read());
-		let indexes: Vec<CompactInteger> = try!(reader.read_list());
+		let index_diffs: Vec<CompactInteger> = try!(reader.read_list());
+
+		let mut next_index = 0usize;
+		let mut indexes = Vec::with_capacity(index_diffs.len());
+		for index_diff in index_diffs {
+			let index = try!(next_index.checked_add(index_diff.into()).ok_or(ReaderError::MalformedData));
+			indexes.push(index);
+			next_index = try!(index.checked_add(1).ok_or(ReaderError::MalformedData));
+		}
 
 		let request = BlockTransactionsRequest {
 			blockhash: blockhash,
-			indexes: indexes.into_iter().map(Into::into).collect(),
+			indexes: indexes,
 		};
 
 		Ok(request)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use ser::{serialize, deserialize};
+	use super::BlockTransactionsRequest;
+
+	#[test]
+	fn test_block_transactions_request_differential_indexes() {
+		let request = BlockTransactionsRequest {
+			blockhash: 1.into(),
+			indexes: vec![0, 1, 5, 300],
+		};
+
+		let mut expected = serialize(&request.blockhash).take();
+		// 4 indexes: 0, 1 - (0 + 1), 5 - (1 + 1), 300 - (5 + 1)
+		expected.extend_from_slice(&[4, 0, 0, 3, 0xfd, 0x26, 0x01]);
+
+		assert_eq!(serialize(&request).take(), expected);
+		assert_eq!(deserialize::<_, BlockTransactionsRequest>(&expected as &[u8]).unwrap(), request);
+	}
+
+	#[test]
+	fn test_block_transactions_request_index_overflow() {
+		let mut raw = serialize(&BlockTransactionsRequest { blockhash: 1.into(), indexes: vec![] }).take();
+		raw.pop();
+		raw.extend_from_slice(&[2, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+		assert!(deserialize::<_, BlockTransactionsRequest>(&raw as &[u8]).is_err());
+	}
+}
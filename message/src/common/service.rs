@@ -50,6 +50,16 @@ impl Services {
 		self
 	}
 
+	/// Peer serves (at least) 288 last blocks, but not the whole chain.
+	pub fn network_limited(&self) -> bool {
+		self.bit_at(10)
+	}
+
+	pub fn with_network_limited(mut self, v: bool) -> Self {
+		self.set_bit(10, v);
+		self
+	}
+
 	pub fn includes(&self, other: &Self) -> bool {
 		self.0 & other.0 == other.0
 	}
@@ -82,4 +92,12 @@ mod test {
 		assert!(s1.includes(&s2));
 		assert!(!s2.includes(&s1));
 	}
+
+	#[test]
+	fn test_services_network_limited() {
+		let services = Services::default().with_network_limited(true);
+		assert!(services.network_limited());
+		assert!(!services.network());
+		assert_eq!(u64::from(services), 1 << 10);
+	}
 }
@@ -9,8 +9,10 @@ pub enum Error {
 	Deserialize,
 	/// Command has wrong format or is unsupported.
 	InvalidCommand,
-	/// Network magic comes from different network.
+	/// Network magic is not a magic of any known network.
 	InvalidMagic,
+	/// Network magic comes from different known network.
+	WrongNetwork,
 	/// Invalid checksum.
 	InvalidChecksum,
 	/// Invalid version.
@@ -35,6 +37,7 @@ impl error::Error for Error {
 			Error::Deserialize => "Message Deserialization Error",
 			Error::InvalidCommand => "Invalid Message Command",
 			Error::InvalidMagic => "Invalid Network Magic",
+			Error::WrongNetwork => "Peer is connected to different network",
 			Error::InvalidChecksum => "Invalid message chacksum",
 			Error::InvalidVersion => "Unsupported protocol version",
 		}
@@ -17,5 +17,5 @@ pub use primitives::{hash, bytes};
 
 pub use common::{Command, Services};
 pub use message::{Message, MessageHeader, Payload, to_raw_message};
-pub use serialization::{serialize_payload, deserialize_payload};
+pub use serialization::{serialize_payload, deserialize_payload, deserialize_shared_payload};
 pub use error::{Error, MessageResult};
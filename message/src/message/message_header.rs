@@ -1,7 +1,7 @@
 use hash::H32;
 use ser::{Serializable, Stream, Reader};
 use crypto::checksum;
-use network::Magic;
+use network::{Magic, Network};
 use common::Command;
 use Error;
 
@@ -31,10 +31,12 @@ impl MessageHeader {
 		}
 
 		let mut reader = Reader::new(data);
-		let magic: u32 = try!(reader.read());
-		let magic = Magic::from(magic);
+		let magic: Magic = try!(reader.read());
 		if expected != magic {
-			return Err(Error::InvalidMagic);
+			return Err(match Network::from_magic(magic) {
+				Some(_) => Error::WrongNetwork,
+				None => Error::InvalidMagic,
+			});
 		}
 
 		let header = MessageHeader {
@@ -64,6 +66,7 @@ mod tests {
 	use ser::serialize;
 	use network::Network;
 	use super::MessageHeader;
+	use Error;
 
 	#[test]
 	fn test_message_header_serialization() {
@@ -90,4 +93,12 @@ mod tests {
 
 		assert_eq!(expected, MessageHeader::deserialize(&raw, Network::Mainnet.magic()).unwrap());
 	}
+
+	#[test]
+	fn test_message_header_deserialization_wrong_network() {
+		let raw: Bytes = "24e927646164647200000000000000001f000000ed52399b".into();
+		assert_eq!(MessageHeader::deserialize(&raw, Network::Testnet.magic()), Err(Error::WrongNetwork));
+		let raw: Bytes = "f9beb4d96164647200000000000000001f000000ed52399b".into();
+		assert_eq!(MessageHeader::deserialize(&raw, Network::Testnet.magic()), Err(Error::InvalidMagic));
+	}
 }
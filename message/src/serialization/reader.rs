@@ -1,8 +1,19 @@
+use std::io;
 use ser::Reader;
+use bytes::SharedBytes;
 use {Payload, Error};
 
 pub fn deserialize_payload<T>(buffer: &[u8], version: u32) -> Result<T, Error> where T: Payload {
-	let mut reader = PayloadReader::new(buffer, version);
+	deserialize_with_reader(PayloadReader::new(buffer, version))
+}
+
+/// Deserialize payload, read into shared buffer. Large variable-length fields of the payload
+/// (like Orchard proofs) are not copied, but reference the buffer.
+pub fn deserialize_shared_payload<T>(buffer: SharedBytes, version: u32) -> Result<T, Error> where T: Payload {
+	deserialize_with_reader(PayloadReader::from_shared(buffer, version))
+}
+
+fn deserialize_with_reader<R, T>(mut reader: PayloadReader<R>) -> Result<T, Error> where R: io::Read, T: Payload {
 	let result = try!(reader.read());
 	if !reader.is_finished() {
 		return Err(Error::Deserialize);
@@ -19,11 +30,22 @@ pub struct PayloadReader<T> {
 impl<'a> PayloadReader<&'a [u8]> {
 	pub fn new(buffer: &'a [u8], version: u32) -> Self {
 		PayloadReader {
-			reader: Reader::new(buffer),
+			reader: Reader::new(buffer).strict(),
 			version: version,
 		}
 	}
+}
+
+impl PayloadReader<io::Empty> {
+	pub fn from_shared(buffer: SharedBytes, version: u32) -> Self {
+		PayloadReader {
+			reader: Reader::from_shared(buffer).strict(),
+			version: version,
+		}
+	}
+}
 
+impl<R> PayloadReader<R> where R: io::Read {
 	pub fn read<T>(&mut self) -> Result<T, Error> where T: Payload {
 		if T::version() > self.version {
 			return Err(Error::InvalidVersion);
@@ -1,15 +1,15 @@
 use std::io;
 use ser::{Stream, Reader};
-use chain::Block as ChainBlock;
+use chain::{BlockReader, IndexedBlock};
 use {Payload, MessageResult};
 
 #[derive(Debug, PartialEq)]
 pub struct Block {
-	pub block: ChainBlock,
+	pub block: IndexedBlock,
 }
 
 impl Block {
-	pub fn with_block(block: ChainBlock) -> Self {
+	pub fn with_block(block: IndexedBlock) -> Self {
 		Block {
 			block: block,
 		}
@@ -26,8 +26,10 @@ impl Payload for Block {
 	}
 
 	fn deserialize_payload<T>(reader: &mut Reader<T>, _version: u32) -> MessageResult<Self> where T: io::Read {
+		// transactions are hashed while they're read => no need to serialize them again
+		// payload reader is strict => block is read directly, so that it could reference shared payload buffer
 		let tx = Block {
-			block: try!(reader.read()),
+			block: try!(BlockReader::read_from(reader)),
 		};
 
 		Ok(tx)
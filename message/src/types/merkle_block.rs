@@ -2,7 +2,7 @@ use std::io;
 use hash::H256;
 use bytes::Bytes;
 use ser::{Stream, Reader};
-use chain::BlockHeader;
+use chain::{BlockHeader, PartialMerkleTree, parse_partial_merkle_tree};
 use {Payload, MessageResult};
 
 #[derive(Debug, PartialEq)]
@@ -13,6 +13,23 @@ pub struct MerkleBlock {
 	pub flags: Bytes,
 }
 
+impl MerkleBlock {
+	/// Partial merkle tree of the block.
+	pub fn partial_merkle_tree(&self) -> PartialMerkleTree {
+		PartialMerkleTree::from_flag_bytes(self.total_transactions as usize, self.hashes.clone(), &self.flags)
+	}
+
+	/// Verify that the partial merkle tree matches block header and return hashes of matched transactions.
+	pub fn matched_hashes(&self) -> Result<Vec<H256>, String> {
+		let parsed = parse_partial_merkle_tree(self.partial_merkle_tree())?;
+		if parsed.root != self.block_header.merkle_root_hash {
+			return Err("merkle root mismatch".into());
+		}
+
+		Ok(parsed.hashes)
+	}
+}
+
 impl Payload for MerkleBlock {
 	fn version() -> u32 {
 		70014
@@ -1,6 +1,7 @@
 pub mod addr;
 mod block;
 mod blocktxn;
+mod compactblock;
 mod feefilter;
 mod filteradd;
 mod filterclear;
@@ -26,6 +27,7 @@ pub mod version;
 pub use self::addr::Addr;
 pub use self::block::Block;
 pub use self::blocktxn::BlockTxn;
+pub use self::compactblock::CompactBlock;
 pub use self::feefilter::FeeFilter;
 pub use self::filterload::{FilterLoad, FILTERLOAD_MAX_FILTER_LEN, FILTERLOAD_MAX_HASH_FUNCS};
 pub use self::filterload::FilterFlags;
@@ -108,6 +108,14 @@ impl Version {
 			Version::V70001(_, ref v, _) => Some(v.user_agent.clone()),
 		}
 	}
+
+	pub fn start_height(&self) -> Option<i32> {
+		match *self {
+			Version::V0(_) => None,
+			Version::V106(_, ref v) |
+			Version::V70001(_, ref v, _) => Some(v.start_height),
+		}
+	}
 }
 
 #[derive(Debug, Default, PartialEq, Clone)]
@@ -2,13 +2,13 @@ use std::collections::HashSet;
 use primitives::hash::H256;
 use primitives::compact::Compact;
 use chain::{OutPoint, TransactionOutput, TransactionInput, IndexedTransaction, Transaction,
-	SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID};
+	SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID, fee_rate};
 use keys::Address;
 use storage::{SharedStore, TransactionOutputProvider, SaplingTreeState};
 use script::Builder;
-use network::ConsensusParams;
+use network::{ConsensusParams, BlockLimits};
 use memory_pool::{MemoryPool, OrderingStrategy, Entry};
-use verification::{work_required, transaction_sigops};
+use verification::{work_required, ValidationCost};
 
 const BLOCK_VERSION: u32 = 4;
 const BLOCK_HEADER_SIZE: u32 = 4 + 32 + 32 + 32 + 4 + 4 + 32 + 1344;
@@ -123,10 +123,8 @@ impl SizePolicy {
 pub struct BlockAssembler<'a> {
 	/// Miner address.
 	pub miner_address: &'a Address,
-	/// Maximal block size.
-	pub max_block_size: u32,
-	/// Maximal # of sigops in the block.
-	pub max_block_sigops: u32,
+	/// Limits of the block size && sigops. Could be lower than consensus limits.
+	pub limits: BlockLimits,
 }
 
 /// Iterator iterating over mempool transactions and yielding only those which fit the block
@@ -143,6 +141,8 @@ struct FittingTransactionsIterator<'a, T> {
 	block_size: SizePolicy,
 	/// Sigops policy decides if transactions sigops fits the block
 	sigops: SizePolicy,
+	/// Transactions with lower fee rate (in zatoshis per 1000 bytes) are not included
+	min_fee_rate: u64,
 	/// Previous entries are needed to get previous transaction outputs
 	previous_entries: Vec<&'a Entry>,
 	/// Hashes of ignored entries
@@ -157,6 +157,7 @@ impl<'a, T> FittingTransactionsIterator<'a, T> where T: Iterator<Item = &'a Entr
 		iter: T,
 		max_block_size: u32,
 		max_block_sigops: u32,
+		min_fee_rate: u64,
 		block_height: u32,
 		block_time: u32,
 	) -> Self {
@@ -168,6 +169,7 @@ impl<'a, T> FittingTransactionsIterator<'a, T> where T: Iterator<Item = &'a Entr
 			// reserve some space for header and transactions len field
 			block_size: SizePolicy::new(BLOCK_HEADER_SIZE + 4, max_block_size, 1_000, 50),
 			sigops: SizePolicy::new(0, max_block_sigops, 8, 50),
+			min_fee_rate: min_fee_rate,
 			previous_entries: Vec::new(),
 			ignored: HashSet::new(),
 			finished: false,
@@ -204,9 +206,10 @@ impl<'a, T> Iterator for FittingTransactionsIterator<'a, T> where T: Iterator<It
 				}
 			};
 
-			let transaction_size = entry.size as u32;
 			let bip16_active = true;
-			let sigops_count = transaction_sigops(&entry.transaction, self, bip16_active) as u32;
+			let cost = ValidationCost::transaction(&entry.transaction, self, bip16_active);
+			let transaction_size = cost.size as u32;
+			let sigops_count = cost.sigops as u32;
 
 			let size_step = self.block_size.decide(transaction_size);
 			let sigops_step = self.sigops.decide(sigops_count);
@@ -218,6 +221,12 @@ impl<'a, T> Iterator for FittingTransactionsIterator<'a, T> where T: Iterator<It
 			}
 			// check if any parent transaction has been ignored
 			if !self.ignored.is_empty() && entry.transaction.inputs.iter().any(|input| self.ignored.contains(&input.previous_output.hash)) {
+				self.ignored.insert(entry.hash.clone());
+				continue;
+			}
+			// check if transaction pays minimal relay fee
+			if fee_rate(entry.miner_fee, entry.size) < self.min_fee_rate {
+				self.ignored.insert(entry.hash.clone());
 				continue;
 			}
 
@@ -225,14 +234,14 @@ impl<'a, T> Iterator for FittingTransactionsIterator<'a, T> where T: Iterator<It
 			match size_step.and(sigops_step) {
 				NextStep::Append => {
 					self.block_size.apply(transaction_size);
-					self.sigops.apply(transaction_size);
+					self.sigops.apply(sigops_count);
 					self.previous_entries.push(entry);
 					return Some(entry);
 				},
 				NextStep::FinishAndAppend => {
 					self.finished = true;
 					self.block_size.apply(transaction_size);
-					self.sigops.apply(transaction_size);
+					self.sigops.apply(sigops_count);
 					self.previous_entries.push(entry);
 					return Some(entry);
 				},
@@ -277,8 +286,9 @@ impl<'a> BlockAssembler<'a> {
 		let tx_iter = FittingTransactionsIterator::new(
 			store.as_transaction_output_provider(),
 			mempool_iter,
-			self.max_block_size,
-			self.max_block_sigops,
+			self.limits.max_block_size as u32,
+			self.limits.max_block_sigops as u32,
+			mempool.standardness_policy().min_relay_fee_rate,
 			height,
 			time);
 		for entry in tx_iter {
@@ -304,6 +314,7 @@ impl<'a> BlockAssembler<'a> {
 			overwintered: true,
 			version: SAPLING_TX_VERSION,
 			version_group_id: SAPLING_TX_VERSION_GROUP_ID,
+			consensus_branch_id: 0,
 			inputs: vec![
 				TransactionInput::coinbase(Builder::default()
 					.push_i64(height.into())
@@ -313,20 +324,30 @@ impl<'a> BlockAssembler<'a> {
 			outputs: vec![
 				TransactionOutput {
 					value: miner_reward,
-					script_pubkey: Builder::build_p2pkh(&self.miner_address.hash).into(),
+					script_pubkey: self.miner_address.to_script_pubkey(),
 				},
 			],
 			lock_time: 0,
-			expiry_height: 0,
+			// since NU5 coinbase expires at the height of the block
+			expiry_height: if consensus.is_nu5_active(height) { height } else { 0 },
 			join_split: None,
 			sapling: None,
+			orchard: None,
 		};
 
 		// insert founder reward if required
-		if let Some(founder_address) = consensus.founder_address(height) {
+		if let Some(founder_address) = consensus.founders_reward_address(height) {
 			coinbase_tx.outputs.push(TransactionOutput {
 				value: consensus.founder_reward(height),
-				script_pubkey: Builder::build_p2sh(&founder_address.hash).into(),
+				script_pubkey: founder_address.to_script_pubkey(),
+			});
+		}
+
+		// insert funding stream outputs if required
+		for output in consensus.funding_stream_outputs(height) {
+			coinbase_tx.outputs.push(TransactionOutput {
+				value: output.value,
+				script_pubkey: output.address.to_script_pubkey(),
 			});
 		}
 
@@ -339,8 +360,8 @@ impl<'a> BlockAssembler<'a> {
 			height: height,
 			transactions: transactions,
 			coinbase_tx: IndexedTransaction::from_raw(coinbase_tx),
-			size_limit: self.max_block_size,
-			sigop_limit: self.max_block_sigops,
+			size_limit: self.limits.max_block_size as u32,
+			sigop_limit: self.limits.max_block_sigops as u32,
 		})
 	}
 }
@@ -354,9 +375,10 @@ mod tests {
 	use primitives::hash::H256;
 	use storage::SharedStore;
 	use chain::IndexedTransaction;
-	use network::{ConsensusParams, Network};
+	use network::{ConsensusParams, Network, BlockLimits};
 	use memory_pool::MemoryPool;
-	use fee::{FeeCalculator, NonZeroFeeCalculator};
+	use fee::{FeeCalculator, NonZeroFeeCalculator, FeePolicy};
+	use policy::StandardnessPolicy;
 	use self::test_data::{ChainBuilder, TransactionBuilder};
 	use super::{BlockAssembler, SizePolicy, NextStep, BlockTemplate};
 
@@ -421,8 +443,7 @@ mod tests {
 
 			(BlockAssembler {
 				miner_address: &"t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into(),
-				max_block_size: 0xffffffff,
-				max_block_sigops: 0xffffffff,
+				limits: BlockLimits { max_block_size: 0xffffffff, max_block_sigops: 0xffffffff },
 			}.create_new_block(&storage, &pool, 0, &consensus).unwrap(), hash0, hash1)
 		}
 
@@ -434,6 +455,25 @@ mod tests {
 		assert_eq!(block.transactions[1].hash, hash1);
 	}
 
+	#[test]
+	fn block_assembler_sigops_limit() {
+		let chain = &mut ChainBuilder::new();
+		TransactionBuilder::with_default_input(0).set_output(30).store(chain)
+			.into_input(0).set_output(50).store(chain);
+
+		let mut pool = MemoryPool::new();
+		let storage: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]));
+		pool.insert_verified(chain.at(0).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(1).into(), &NonZeroFeeCalculator);
+
+		// transactions have no sigops => they fit the block, even though their size is larger than sigops limit
+		let block = BlockAssembler {
+			miner_address: &"t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into(),
+			limits: BlockLimits { max_block_size: 0xffffffff, max_block_sigops: 10 },
+		}.create_new_block(&storage, &pool, 0, &ConsensusParams::new(Network::Mainnet)).unwrap();
+		assert_eq!(block.transactions.len(), 2);
+	}
+
 	#[test]
 	fn block_assembler_miner_fee() {
 		let input_tx = test_data::block_h1().transactions[0].clone();
@@ -448,12 +488,34 @@ mod tests {
 
 		let consensus = ConsensusParams::new(Network::Mainnet);
 		let block = BlockAssembler {
-			max_block_size: 0xffffffff,
-			max_block_sigops: 0xffffffff,
+			limits: BlockLimits { max_block_size: 0xffffffff, max_block_sigops: 0xffffffff },
 			miner_address: &"t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into(),
 		}.create_new_block(&storage, &pool, 0, &consensus).unwrap();
 
-		let expected_coinbase_value = consensus.block_reward(2) + expected_tx0_fee;
+		let expected_coinbase_value = consensus.block_subsidy(2) + expected_tx0_fee;
 		assert_eq!(block.coinbase_tx.raw.total_spends(), expected_coinbase_value);
 	}
+
+	#[test]
+	fn block_assembler_min_relay_fee_rate() {
+		let chain = &mut ChainBuilder::new();
+		TransactionBuilder::with_default_input(0).set_output(30).store(chain)
+			.into_input(0).set_output(50).store(chain);
+
+		// fee of NonZeroFeeCalculator is 100_000_000 + sum of outputs
+		let mut pool = MemoryPool::with_policies(FeePolicy::NonZero, StandardnessPolicy {
+			min_relay_fee_rate: chain.at(0).fee_rate(100_000_030) + 1,
+			..Default::default()
+		});
+		let storage: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]));
+		pool.insert_verified(chain.at(0).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(1).into(), &NonZeroFeeCalculator);
+
+		// child pays enough, but it can't be mined without its parent
+		let block = BlockAssembler {
+			miner_address: &"t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into(),
+			limits: BlockLimits::default(),
+		}.create_new_block(&storage, &pool, 0, &ConsensusParams::new(Network::Mainnet)).unwrap();
+		assert!(block.transactions.is_empty());
+	}
 }
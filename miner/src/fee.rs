@@ -1,9 +1,36 @@
-use chain::Transaction;
-use ser::Serializable;
+use chain::{Amount, Transaction};
 use storage::{TransactionOutputProvider, DuplexTransactionOutputProvider};
 use verification::checked_transaction_fee;
 use MemoryPool;
 
+/// Policy, deciding the minimal fee of transactions, accepted to the memory pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeePolicy {
+	/// Any non-zero fee is accepted.
+	NonZero,
+	/// ZIP-317 conventional fee is required.
+	#[cfg(feature = "zip317")]
+	Zip317,
+}
+
+impl Default for FeePolicy {
+	fn default() -> Self {
+		FeePolicy::NonZero
+	}
+}
+
+impl FeePolicy {
+	/// Minimal fee (in zatoshis) of given transaction.
+	#[cfg_attr(not(feature = "zip317"), allow(unused_variables))]
+	pub fn minimum_fee(&self, tx: &Transaction) -> u64 {
+		match *self {
+			FeePolicy::NonZero => 1,
+			#[cfg(feature = "zip317")]
+			FeePolicy::Zip317 => ::zip317::conventional_fee(tx),
+		}
+	}
+}
+
 /// Transaction fee calculator for memory pool
 pub trait MemoryPoolFeeCalculator {
 	/// Compute transaction fee
@@ -38,11 +65,14 @@ impl MemoryPoolFeeCalculator for NonZeroFeeCalculator {
 /// If any error occurs during computation, zero fee is returned. Normally, zero fee
 /// transactions are not accepted to the memory pool.
 pub fn transaction_fee(store: &TransactionOutputProvider, tx: &Transaction) -> u64 {
-	checked_transaction_fee(store, ::std::usize::MAX, tx).unwrap_or(0)
+	checked_transaction_fee(store, ::std::usize::MAX, tx).ok()
+		.and_then(Amount::to_u64)
+		.unwrap_or(0)
 }
 
+/// Compute feerate (in zatoshis per 1000 bytes, like in `feefilter` messages) of given (memory pool) transaction.
 pub fn transaction_fee_rate(store: &TransactionOutputProvider, tx: &Transaction) -> u64 {
-	transaction_fee(store, tx) / tx.serialized_size() as u64
+	tx.fee_rate(transaction_fee(store, tx))
 }
 
 #[cfg(test)]
@@ -50,7 +80,8 @@ mod tests {
 	use std::sync::Arc;
 	use storage::AsSubstore;
 	use db::BlockChainDatabase;
-	use super::transaction_fee_rate;
+	use chain::Transaction;
+	use super::{transaction_fee_rate, FeePolicy};
 
 	#[test]
 	fn transaction_fee_rate_works() {
@@ -75,6 +106,11 @@ mod tests {
 		let store = db.as_transaction_output_provider();
 
 		assert_eq!(transaction_fee_rate(store, &tx0), 0);
-		assert_eq!(transaction_fee_rate(store, &tx2), 4_901);
+		assert_eq!(transaction_fee_rate(store, &tx2), 4_901_960);
+	}
+
+	#[test]
+	fn fee_policy_minimum_fee() {
+		assert_eq!(FeePolicy::default().minimum_fee(&Transaction::default()), 1);
 	}
 }
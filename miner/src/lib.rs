@@ -15,11 +15,15 @@ extern crate verification;
 mod block_assembler;
 mod fee;
 mod memory_pool;
+mod policy;
+#[cfg(feature = "zip317")]
+pub mod zip317;
 
 pub use block_assembler::{BlockAssembler, BlockTemplate};
 pub use memory_pool::{MemoryPool, HashedOutPoint, Information as MemoryPoolInformation,
 	OrderingStrategy as MemoryPoolOrderingStrategy, DoubleSpendCheckResult, NonFinalDoubleSpendSet};
-pub use fee::{FeeCalculator, transaction_fee, transaction_fee_rate};
+pub use fee::{FeeCalculator, FeePolicy, transaction_fee, transaction_fee_rate};
+pub use policy::{StandardnessPolicy, PolicyError};
 
 #[cfg(feature = "test-helpers")]
 pub use fee::NonZeroFeeCalculator;
\ No newline at end of file
@@ -15,9 +15,10 @@ use std::collections::HashSet;
 use std::collections::BTreeSet;
 use std::collections::VecDeque;
 use std::hash::{Hash, Hasher};
-use ser::{Serializable, serialize};
+use ser::serialize;
 use heapsize::HeapSizeOf;
-use fee::MemoryPoolFeeCalculator;
+use fee::{MemoryPoolFeeCalculator, FeePolicy};
+use policy::{StandardnessPolicy, PolicyError};
 
 /// Transactions ordering strategy
 #[cfg_attr(feature="cargo-clippy", allow(enum_variant_names))]
@@ -45,6 +46,10 @@ pub struct Information {
 pub struct MemoryPool {
 	/// Transactions storage
 	storage: Storage,
+	/// Minimal fee policy
+	fee_policy: FeePolicy,
+	/// Standardness policy
+	standardness_policy: StandardnessPolicy,
 }
 
 /// Single entry
@@ -486,6 +491,33 @@ impl Storage {
 		Some(removed)
 	}
 
+	pub fn remove_expired(&mut self, height: u32) -> Vec<IndexedTransaction> {
+		let expired: Vec<H256> = self.by_hash.values()
+			.filter(|entry| entry.transaction.overwintered
+				&& entry.transaction.expiry_height != 0
+				&& entry.transaction.expiry_height < height)
+			.map(|entry| entry.hash.clone())
+			.collect();
+
+		let mut removed: Vec<IndexedTransaction> = Vec::new();
+		for hash in expired {
+			// expired transaction could have already been removed as a descendant of other expired transaction
+			if let Some(entry) = self.remove_by_hash(&hash) {
+				let outputs_len = entry.transaction.outputs.len();
+				removed.push(IndexedTransaction::new(entry.hash, entry.transaction));
+				for index in 0..outputs_len {
+					let outpoint = OutPoint {
+						hash: hash.clone(),
+						index: index as u32,
+					};
+					removed.extend(self.remove_by_prevout(&outpoint).unwrap_or_default());
+				}
+			}
+		}
+
+		removed
+	}
+
 	pub fn remove_by_parent_hash(&mut self, h: &H256) -> Option<Vec<IndexedTransaction>> {
 		// this code will run only when ancestor transaction is inserted
 		// in memory pool after its descendants
@@ -639,6 +671,8 @@ impl Default for MemoryPool {
 	fn default() -> Self {
 		MemoryPool {
 			storage: Storage::new(),
+			fee_policy: FeePolicy::default(),
+			standardness_policy: StandardnessPolicy::default(),
 		}
 	}
 }
@@ -649,6 +683,39 @@ impl MemoryPool {
 		MemoryPool::default()
 	}
 
+	/// Creates new memory pool, accepting transactions that pay at least the fee, required by the policy
+	pub fn with_fee_policy(fee_policy: FeePolicy) -> Self {
+		MemoryPool::with_policies(fee_policy, StandardnessPolicy::default())
+	}
+
+	/// Creates new memory pool, accepting transactions that satisfy both fee && standardness policies
+	pub fn with_policies(fee_policy: FeePolicy, standardness_policy: StandardnessPolicy) -> Self {
+		MemoryPool {
+			storage: Storage::new(),
+			fee_policy: fee_policy,
+			standardness_policy: standardness_policy,
+		}
+	}
+
+	/// Minimal fee policy of the memory pool
+	pub fn fee_policy(&self) -> FeePolicy {
+		self.fee_policy
+	}
+
+	/// Standardness policy of the memory pool
+	pub fn standardness_policy(&self) -> StandardnessPolicy {
+		self.standardness_policy
+	}
+
+	/// Check that verified transaction, paying given fee, satisfies policies of the memory pool
+	pub fn check_policies(&self, t: &Transaction, miner_fee: u64) -> Result<(), PolicyError> {
+		if miner_fee < self.fee_policy.minimum_fee(t) {
+			return Err(PolicyError::InsufficientFee);
+		}
+
+		self.standardness_policy.check(t, miner_fee)
+	}
+
 	/// Insert verified transaction to the `MemoryPool`
 	pub fn insert_verified<FC: MemoryPoolFeeCalculator>(&mut self, t: IndexedTransaction, fc: &FC) {
 		if let Some(entry) = self.make_entry(t, fc) {
@@ -686,6 +753,12 @@ impl MemoryPool {
 		self.storage.remove_by_prevout(prevout)
 	}
 
+	/// Removes transactions (and all their descendants), which can't be included in the block
+	/// at given height, because they have expired
+	pub fn remove_expired(&mut self, height: u32) -> Vec<IndexedTransaction> {
+		self.storage.remove_expired(height)
+	}
+
 	/// Reads single transaction by its hash.
 	pub fn read_by_hash(&self, h: &H256) -> Option<&Transaction> {
 		self.storage.read_by_hash(h)
@@ -757,8 +830,8 @@ impl MemoryPool {
 		let storage_index = self.get_storage_index();
 		let miner_fee = fc.calculate(self, &t.raw);
 
-		// do not accept any transactions that have negative OR zero fee
-		if miner_fee == 0 {
+		// do not accept any transactions that have negative OR zero fee (or fee, lesser than required by policy)
+		if miner_fee < self.fee_policy.minimum_fee(&t.raw) {
 			return None;
 		}
 		
@@ -791,7 +864,7 @@ impl MemoryPool {
 	}
 
 	fn get_transaction_size(&self, t: &Transaction) -> usize {
-		t.serialized_size()
+		t.size()
 	}
 
 	#[cfg(not(test))]
@@ -878,6 +951,8 @@ pub mod tests {
 	use chain::{Transaction, OutPoint};
 	use heapsize::HeapSizeOf;
 	use fee::NonZeroFeeCalculator;
+	use fee::FeePolicy;
+	use policy::{StandardnessPolicy, PolicyError};
 	use super::{MemoryPool, OrderingStrategy, DoubleSpendCheckResult};
 	use self::test_data::{ChainBuilder, TransactionBuilder};
 
@@ -1310,6 +1385,33 @@ pub mod tests {
 		assert_eq!(pool.information().transactions_count, 2);
 	}
 
+	#[test]
+	fn test_memory_pool_remove_expired() {
+		let chain = &mut ChainBuilder::new();
+
+		TransactionBuilder::with_output(10).set_overwintered(true).set_expiry_height(100).store(chain)	// transaction0
+			.into_input(0).add_output(20).store(chain)														// transaction0 -> transaction1
+			.reset().add_output(30).set_overwintered(true).set_expiry_height(200).store(chain)				// transaction2
+			.reset().add_output(40).store(chain);															// transaction3
+		let mut pool = MemoryPool::new();
+
+		pool.insert_verified(chain.at(0).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(1).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(2).into(), &NonZeroFeeCalculator);
+		pool.insert_verified(chain.at(3).into(), &NonZeroFeeCalculator);
+
+		// transaction could be included in the block at its expiry height
+		assert_eq!(pool.remove_expired(100), vec![]);
+		assert_eq!(pool.information().transactions_count, 4);
+
+		// descendants of expired transaction are also removed
+		assert_eq!(pool.remove_expired(101), vec![chain.at(0).into(), chain.at(1).into()]);
+		assert_eq!(pool.information().transactions_count, 2);
+
+		assert_eq!(pool.remove_expired(201), vec![chain.at(2).into()]);
+		assert_eq!(pool.information().transactions_count, 1);
+	}
+
 	#[test]
 	fn test_memory_pool_check_double_spend() {
 		let chain = &mut ChainBuilder::new();
@@ -1405,4 +1507,20 @@ pub mod tests {
 		assert!(memory_pool.is_spent(&out1));
 		assert!(!memory_pool.is_spent(&out2));
 	}
+
+	#[test]
+	fn test_memory_pool_check_policies() {
+		// output with empty script is non-standard
+		let tx: Transaction = TransactionBuilder::with_default_input(0).set_output(100_000).into();
+
+		let memory_pool = MemoryPool::new();
+		assert_eq!(memory_pool.check_policies(&tx, 0), Err(PolicyError::InsufficientFee));
+		assert_eq!(memory_pool.check_policies(&tx, 1_000), Err(PolicyError::NonStandard("scriptpubkey")));
+
+		let memory_pool = MemoryPool::with_policies(FeePolicy::NonZero, StandardnessPolicy {
+			accept_non_standard: true,
+			..Default::default()
+		});
+		assert_eq!(memory_pool.check_policies(&tx, 1_000), Ok(()));
+	}
 }
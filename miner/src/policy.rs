@@ -0,0 +1,220 @@
+//! Standardness policy of the memory pool.
+//!
+//! Unlike consensus rules, these rules are only applied to transactions, that are accepted to the memory pool
+//! (and relayed to other peers). Transactions, violating them, are still valid when mined.
+
+use chain::{Transaction, TransactionOutput};
+use script::{Script, ScriptType};
+use ser::Serializable;
+
+/// Default maximal size of standard transaction.
+pub const DEFAULT_MAX_STANDARD_TX_SIZE: usize = 100_000;
+/// Default maximal size of standard signature script. Large enough for 15-of-15 P2SH multisig with compressed keys.
+pub const DEFAULT_MAX_STANDARD_SCRIPT_SIG_SIZE: usize = 1_650;
+/// Default maximal size of null data (OP_RETURN) script, including opcodes.
+pub const DEFAULT_MAX_DATA_CARRIER_SIZE: usize = 83;
+/// Default minimal fee rate (in zatoshis per 1000 bytes) of relayed transactions.
+pub const DEFAULT_MIN_RELAY_FEE_RATE: u64 = 100;
+/// Default fee rate (in zatoshis per 1000 bytes), used to compute dust threshold.
+pub const DEFAULT_DUST_RELAY_FEE_RATE: u64 = 100;
+/// Maximal number of keys in standard bare multisig script.
+pub const MAX_STANDARD_MULTISIG_KEYS: u8 = 3;
+/// Size of the input, spending P2PKH output. Used to compute dust threshold.
+const SPENDING_INPUT_SIZE: usize = 148;
+
+/// Reason of transaction rejection by the standardness policy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyError {
+	/// Transaction fee is lower than minimal fee, required by the policy.
+	InsufficientFee,
+	/// Transaction isn't standard. The reason is named like in zcashd rejections.
+	NonStandard(&'static str),
+}
+
+/// Standardness policy of the memory pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StandardnessPolicy {
+	/// Accept non-standard transactions (fee rate is still checked).
+	pub accept_non_standard: bool,
+	/// Maximal size of standard transaction.
+	pub max_transaction_size: usize,
+	/// Maximal size of standard signature script.
+	pub max_script_sig_size: usize,
+	/// Maximal size of null data (OP_RETURN) script, including opcodes.
+	pub max_data_carrier_size: usize,
+	/// Minimal fee rate (in zatoshis per 1000 bytes) of transactions.
+	pub min_relay_fee_rate: u64,
+	/// Fee rate (in zatoshis per 1000 bytes), used to compute dust threshold. Output is dust, if spending it
+	/// costs more than 1/3 of its value.
+	pub dust_relay_fee_rate: u64,
+}
+
+impl Default for StandardnessPolicy {
+	fn default() -> Self {
+		StandardnessPolicy {
+			accept_non_standard: false,
+			max_transaction_size: DEFAULT_MAX_STANDARD_TX_SIZE,
+			max_script_sig_size: DEFAULT_MAX_STANDARD_SCRIPT_SIG_SIZE,
+			max_data_carrier_size: DEFAULT_MAX_DATA_CARRIER_SIZE,
+			min_relay_fee_rate: DEFAULT_MIN_RELAY_FEE_RATE,
+			dust_relay_fee_rate: DEFAULT_DUST_RELAY_FEE_RATE,
+		}
+	}
+}
+
+impl StandardnessPolicy {
+	/// Check transaction, paying given fee, against the policy.
+	pub fn check(&self, tx: &Transaction, fee: u64) -> Result<(), PolicyError> {
+		if !self.accept_non_standard {
+			self.check_standard(tx).map_err(PolicyError::NonStandard)?;
+		}
+
+		if fee < tx.minimum_fee(self.min_relay_fee_rate) {
+			return Err(PolicyError::InsufficientFee);
+		}
+
+		Ok(())
+	}
+
+	/// Minimal value of output, which isn't dust.
+	pub fn dust_threshold(&self, output: &TransactionOutput) -> u64 {
+		let spending_size = (output.serialized_size() + SPENDING_INPUT_SIZE) as u64;
+		3 * self.dust_relay_fee_rate.saturating_mul(spending_size) / 1000
+	}
+
+	fn check_standard(&self, tx: &Transaction) -> Result<(), &'static str> {
+		if tx.size() > self.max_transaction_size {
+			return Err("tx-size");
+		}
+
+		for input in &tx.inputs {
+			if input.script_sig.len() > self.max_script_sig_size {
+				return Err("scriptsig-size");
+			}
+
+			if !Script::new(input.script_sig.clone()).is_push_only() {
+				return Err("scriptsig-not-pushonly");
+			}
+		}
+
+		let mut data_carriers = 0;
+		for output in &tx.outputs {
+			let script_pubkey = Script::new(output.script_pubkey.clone());
+			match script_pubkey.script_type() {
+				ScriptType::NonStandard => return Err("scriptpubkey"),
+				ScriptType::Multisig if !is_standard_multisig(&script_pubkey) => return Err("scriptpubkey"),
+				ScriptType::NullData if script_pubkey.len() > self.max_data_carrier_size => return Err("scriptpubkey"),
+				ScriptType::NullData => data_carriers += 1,
+				_ if output.value < self.dust_threshold(output) => return Err("dust"),
+				_ => (),
+			}
+		}
+
+		if data_carriers > 1 {
+			return Err("multi-op-return");
+		}
+
+		Ok(())
+	}
+}
+
+/// Bare multisig is standard, if it requires at least one signature of at most `MAX_STANDARD_MULTISIG_KEYS` keys.
+fn is_standard_multisig(script: &Script) -> bool {
+	let keys = match script.get_opcode(script.len() - 2) {
+		Ok(opcode) if opcode.is_within_op_n() => opcode.decode_op_n(),
+		_ => return false,
+	};
+	let signatures = script.num_signatures_required();
+
+	signatures >= 1 && keys <= MAX_STANDARD_MULTISIG_KEYS
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, TransactionInput, TransactionOutput};
+	use keys::AddressHash;
+	use script::{Builder, Opcode};
+	use super::{StandardnessPolicy, PolicyError};
+
+	fn p2pkh_output(value: u64) -> TransactionOutput {
+		TransactionOutput {
+			value: value,
+			script_pubkey: Builder::build_p2pkh(&AddressHash::default()).to_bytes(),
+		}
+	}
+
+	fn transaction(outputs: Vec<TransactionOutput>) -> Transaction {
+		Transaction {
+			inputs: vec![TransactionInput {
+				script_sig: Builder::default().push_data(&[0u8; 72]).push_data(&[0u8; 33]).into_bytes(),
+				..Default::default()
+			}],
+			outputs: outputs,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn standard_transaction_is_accepted() {
+		let policy = StandardnessPolicy::default();
+		let tx = transaction(vec![p2pkh_output(100_000), p2pkh_output(54)]);
+		assert_eq!(policy.check(&tx, 1_000), Ok(()));
+	}
+
+	#[test]
+	fn low_fee_rate_is_rejected() {
+		let policy = StandardnessPolicy::default();
+		let tx = transaction(vec![p2pkh_output(100_000)]);
+		assert_eq!(policy.check(&tx, 0), Err(PolicyError::InsufficientFee));
+
+		let policy = StandardnessPolicy { min_relay_fee_rate: 0, ..Default::default() };
+		assert_eq!(policy.check(&tx, 0), Ok(()));
+	}
+
+	#[test]
+	fn dust_output_is_rejected() {
+		let policy = StandardnessPolicy::default();
+		assert_eq!(policy.dust_threshold(&p2pkh_output(0)), 54);
+
+		let tx = transaction(vec![p2pkh_output(53)]);
+		assert_eq!(policy.check(&tx, 1_000), Err(PolicyError::NonStandard("dust")));
+
+		let policy = StandardnessPolicy { dust_relay_fee_rate: 0, ..Default::default() };
+		assert_eq!(policy.check(&tx, 1_000), Ok(()));
+	}
+
+	#[test]
+	fn data_carriers_are_limited() {
+		let policy = StandardnessPolicy::default();
+		let data_carrier = |size: usize| TransactionOutput {
+			value: 0,
+			script_pubkey: Builder::default().push_opcode(Opcode::OP_RETURN).push_data(&vec![0u8; size]).into_bytes(),
+		};
+
+		let tx = transaction(vec![p2pkh_output(100_000), data_carrier(80)]);
+		assert_eq!(policy.check(&tx, 1_000), Ok(()));
+
+		let tx = transaction(vec![p2pkh_output(100_000), data_carrier(81)]);
+		assert_eq!(policy.check(&tx, 1_000), Err(PolicyError::NonStandard("scriptpubkey")));
+
+		let tx = transaction(vec![data_carrier(10), data_carrier(10)]);
+		assert_eq!(policy.check(&tx, 1_000), Err(PolicyError::NonStandard("multi-op-return")));
+	}
+
+	#[test]
+	fn non_standard_scripts_are_rejected() {
+		let policy = StandardnessPolicy::default();
+		let tx = transaction(vec![TransactionOutput {
+			value: 100_000,
+			script_pubkey: Builder::default().push_opcode(Opcode::OP_TRUE).into_bytes(),
+		}]);
+		assert_eq!(policy.check(&tx, 1_000), Err(PolicyError::NonStandard("scriptpubkey")));
+
+		let mut tx = transaction(vec![p2pkh_output(100_000)]);
+		tx.inputs[0].script_sig = Builder::default().push_opcode(Opcode::OP_DUP).into_bytes();
+		assert_eq!(policy.check(&tx, 1_000), Err(PolicyError::NonStandard("scriptsig-not-pushonly")));
+
+		let policy = StandardnessPolicy { accept_non_standard: true, ..Default::default() };
+		assert_eq!(policy.check(&tx, 1_000), Ok(()));
+	}
+}
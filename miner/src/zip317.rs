@@ -0,0 +1,77 @@
+//! Conventional transaction fee, proportional to the number of logical actions of the transaction.
+//! https://zips.z.cash/zip-0317
+
+use std::cmp;
+use chain::Transaction;
+use ser::Serializable;
+
+/// Fee for every logical action (in zatoshis).
+pub const MARGINAL_FEE: u64 = 5_000;
+/// Number of logical actions, that are paid by any transaction.
+pub const GRACE_ACTIONS: u64 = 2;
+/// Size of the standard P2PKH input. Bigger inputs are counted as several logical actions.
+pub const P2PKH_STANDARD_INPUT_SIZE: u64 = 150;
+/// Size of the standard P2PKH output. Bigger outputs are counted as several logical actions.
+pub const P2PKH_STANDARD_OUTPUT_SIZE: u64 = 34;
+
+/// Number of logical actions of the transaction.
+pub fn logical_actions(tx: &Transaction) -> u64 {
+	let inputs_size: usize = tx.inputs.iter().map(Serializable::serialized_size).sum();
+	let outputs_size: usize = tx.outputs.iter().map(Serializable::serialized_size).sum();
+	let transparent_actions = cmp::max(
+		div_ceil(inputs_size as u64, P2PKH_STANDARD_INPUT_SIZE),
+		div_ceil(outputs_size as u64, P2PKH_STANDARD_OUTPUT_SIZE),
+	);
+	let sprout_actions = tx.join_split.as_ref()
+		.map(|join_split| 2 * join_split.descriptions.len() as u64)
+		.unwrap_or_default();
+	let sapling_actions = tx.sapling.as_ref()
+		.map(|sapling| cmp::max(sapling.spends.len(), sapling.outputs.len()) as u64)
+		.unwrap_or_default();
+	let orchard_actions = tx.orchard.as_ref()
+		.map(|orchard| orchard.actions.len() as u64)
+		.unwrap_or_default();
+
+	transparent_actions + sprout_actions + sapling_actions + orchard_actions
+}
+
+/// Conventional fee of the transaction (in zatoshis).
+pub fn conventional_fee(tx: &Transaction) -> u64 {
+	MARGINAL_FEE * cmp::max(GRACE_ACTIONS, logical_actions(tx))
+}
+
+fn div_ceil(value: u64, divisor: u64) -> u64 {
+	(value + divisor - 1) / divisor
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, TransactionInput, TransactionOutput, JoinSplit, Sapling};
+	use super::{logical_actions, conventional_fee};
+
+	#[test]
+	fn test_logical_actions() {
+		// empty transaction still pays for grace actions
+		let tx = Transaction::default();
+		assert_eq!(logical_actions(&tx), 0);
+		assert_eq!(conventional_fee(&tx), 10_000);
+
+		// standard p2pkh input (148 bytes) && two p2pkh outputs (34 bytes each)
+		let tx = Transaction {
+			inputs: vec![TransactionInput { script_sig: vec![0u8; 107].into(), ..Default::default() }],
+			outputs: vec![TransactionOutput { script_pubkey: vec![0u8; 25].into(), ..Default::default() }; 2],
+			..Default::default()
+		};
+		assert_eq!(logical_actions(&tx), 2);
+		assert_eq!(conventional_fee(&tx), 10_000);
+
+		// every joinsplit is two actions, sapling actions are max of spends && outputs
+		let tx = Transaction {
+			join_split: Some(JoinSplit { descriptions: vec![Default::default()], ..Default::default() }),
+			sapling: Some(Sapling { spends: vec![Default::default()], outputs: vec![Default::default(); 3], ..Default::default() }),
+			..Default::default()
+		};
+		assert_eq!(logical_actions(&tx), 5);
+		assert_eq!(conventional_fee(&tx), 25_000);
+	}
+}
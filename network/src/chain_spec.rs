@@ -0,0 +1,145 @@
+//! Specification of the custom chain, loaded from JSON file.
+
+use hex::FromHex;
+use chain::{Block, IndexedBlock};
+use primitives::bigint::U256;
+use serialization::deserialize;
+use serde_json;
+use {Magic, Network};
+
+/// Network, consensus rules of which are used by the custom chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BaseNetwork {
+	/// Mainnet consensus rules (including network upgrades schedule).
+	Mainnet,
+	/// Testnet consensus rules (including network upgrades schedule).
+	Testnet,
+	/// Regtest consensus rules (no network upgrades are scheduled).
+	Regtest,
+}
+
+/// Custom chain specification.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainSpec {
+	/// Name of the chain.
+	pub name: String,
+	/// Network, consensus rules of which are used by the chain.
+	pub base: BaseNetwork,
+	/// Network magic.
+	pub magic: Magic,
+	/// Default p2p port.
+	pub port: u16,
+	/// Default RPC port.
+	pub rpc_port: u16,
+	/// Hex-encoded genesis block.
+	pub genesis_block: String,
+	/// Hex-encoded (big-endian) maximal proof-of-work target.
+	pub max_bits: String,
+	/// DNS seeds, which are used to discover peers of the chain.
+	#[serde(default)]
+	pub dns_seeds: Vec<String>,
+}
+
+impl ChainSpec {
+	/// Read chain specification from JSON. Returns error if specification is invalid.
+	pub fn from_json(json: &str) -> Result<ChainSpec, String> {
+		let spec: ChainSpec = serde_json::from_str(json)
+			.map_err(|err| format!("Invalid chain specification: {}", err))?;
+		spec.parse_genesis_block()?;
+		spec.parse_max_bits()?;
+		Ok(spec)
+	}
+
+	/// Network, consensus rules of which are used by the chain.
+	pub fn base_network(&self) -> Network {
+		match self.base {
+			BaseNetwork::Mainnet => Network::Mainnet,
+			BaseNetwork::Testnet => Network::Testnet,
+			BaseNetwork::Regtest => Network::Regtest,
+		}
+	}
+
+	/// Genesis block of the chain.
+	pub fn genesis_block(&self) -> IndexedBlock {
+		self.parse_genesis_block().expect("checked when specification is loaded; qed")
+	}
+
+	/// Maximal proof-of-work target of the chain.
+	pub fn max_bits(&self) -> U256 {
+		self.parse_max_bits().expect("checked when specification is loaded; qed")
+	}
+
+	fn parse_genesis_block(&self) -> Result<IndexedBlock, String> {
+		let raw: Vec<u8> = self.genesis_block.from_hex()
+			.map_err(|err| format!("Invalid genesis block: {}", err))?;
+		let block: Block = deserialize(&raw as &[u8])
+			.map_err(|err| format!("Invalid genesis block: {:?}", err))?;
+		Ok(block.into())
+	}
+
+	fn parse_max_bits(&self) -> Result<U256, String> {
+		let raw: Vec<u8> = self.max_bits.from_hex()
+			.map_err(|err| format!("Invalid max bits: {}", err))?;
+		if raw.len() != 32 {
+			return Err("Invalid max bits: 32 bytes are expected".into());
+		}
+
+		Ok(U256::from_big_endian(&raw))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hex::ToHex;
+	use serialization::serialize;
+	use {Magic, Network};
+	use super::{ChainSpec, BaseNetwork};
+
+	fn regtest_genesis_hex() -> String {
+		serialize(&Network::Regtest.genesis_block().to_raw_block()).to_hex()
+	}
+
+	#[test]
+	fn chain_spec_from_json() {
+		let json = format!(r#"{{
+			"name": "research",
+			"base": "regtest",
+			"magic": 305419896,
+			"port": 28233,
+			"rpc_port": 28232,
+			"genesis_block": "{}",
+			"max_bits": "0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f"
+		}}"#, regtest_genesis_hex());
+
+		let spec = ChainSpec::from_json(&json).unwrap();
+		assert_eq!(spec.name, "research");
+		assert_eq!(spec.base, BaseNetwork::Regtest);
+		assert_eq!(spec.base_network(), Network::Regtest);
+		assert_eq!(spec.magic, Magic(0x12345678));
+		assert!(spec.dns_seeds.is_empty());
+		assert_eq!(spec.genesis_block().hash(), Network::Regtest.genesis_block().hash());
+		assert_eq!(spec.max_bits(), Network::Regtest.max_bits());
+
+		let network = Network::custom(spec);
+		assert_eq!(network.magic(), Magic(0x12345678));
+		assert_eq!(network.port(), 28233);
+		assert_eq!(network.rpc_port(), 28232);
+		assert_eq!(network.genesis_block().hash(), Network::Regtest.genesis_block().hash());
+		assert_eq!(network.default_verification_edge(), Network::Regtest.genesis_block().hash().clone());
+	}
+
+	#[test]
+	fn chain_spec_from_invalid_json() {
+		// unknown base network
+		assert!(ChainSpec::from_json(&format!(r#"{{"name":"x","base":"other","magic":1,"port":1,"rpc_port":2,"genesis_block":"{}","max_bits":"{}"}}"#,
+			regtest_genesis_hex(), "00".repeat(32))).is_err());
+		// invalid genesis block
+		assert!(ChainSpec::from_json(&format!(r#"{{"name":"x","base":"regtest","magic":1,"port":1,"rpc_port":2,"genesis_block":"00","max_bits":"{}"}}"#,
+			"00".repeat(32))).is_err());
+		// invalid max bits
+		assert!(ChainSpec::from_json(&format!(r#"{{"name":"x","base":"regtest","magic":1,"port":1,"rpc_port":2,"genesis_block":"{}","max_bits":"00"}}"#,
+			regtest_genesis_hex())).is_err());
+	}
+}
@@ -1,5 +1,15 @@
+use std::{fs, io, path};
+use chain::{Amount, BlockHeader, BlockCommitment};
 use keys::Address;
-use {Network, Magic, Deployment, crypto};
+use {Network, NetworkUpgrade, UpgradeSchedule, Magic, Deployment, FundingStream, FundingStreamOutput, BlockLimits,
+	MAX_BLOCK_SIZE, MAX_TRANSACTION_SIZE_BEFORE_SAPLING, crypto};
+
+/// Ratio of pre-Blossom to post-Blossom target block spacing.
+/// https://zips.z.cash/zip-0208
+const BLOSSOM_POW_TARGET_SPACING_RATIO: u32 = 2;
+/// Number of funding stream address change intervals within post-Blossom halving interval.
+/// https://zips.z.cash/zip-0207
+const FUNDING_STREAM_ADDRESS_CHANGES_PER_HALVING: u32 = 48;
 
 lazy_static! {
 	static ref SAPLING_SPEND_VK: crypto::Groth16VerifyingKey = crypto::load_sapling_spend_verifying_key()
@@ -34,12 +44,9 @@ pub struct ConsensusParams {
 	/// BIP68, BIP112, BIP113 deployment
 	pub csv_deployment: Option<Deployment>,
 
-	/// Height of Overwinter activation.
-	/// Details: https://zcash.readthedocs.io/en/latest/rtd_pages/nu_dev_guide.html#overwinter
-	pub overwinter_height: u32,
-	/// Height of Sapling activation.
-	/// Details: https://zcash.readthedocs.io/en/latest/rtd_pages/nu_dev_guide.html#sapling
-	pub sapling_height: u32,
+	/// Activation heights of network upgrades.
+	/// Details: https://zcash.readthedocs.io/en/latest/rtd_pages/nu_dev_guide.html
+	pub upgrades: UpgradeSchedule,
 
 	/// Interval (in blocks) to calculate average work.
 	pub pow_averaging_window: u32,
@@ -47,9 +54,10 @@ pub struct ConsensusParams {
 	pub pow_max_adjust_down: u32,
 	/// % of possible up adjustment of work.
 	pub pow_max_adjust_up: u32,
-	/// Optimal blocks interval (in seconds).
+	/// Optimal blocks interval (in seconds) before Blossom activation.
 	pub pow_target_spacing: u32,
-	/// Allow minimal difficulty after block at given height.
+	/// Blocks after block at given height are allowed to have minimal difficulty if they
+	/// are mined more than 6 target spacings after their parent.
 	pub pow_allow_min_difficulty_after_height: Option<u32>,
 
 	/// 'Slow start' interval parameter.
@@ -68,10 +76,21 @@ pub struct ConsensusParams {
 	/// For details on what's founders' reward, refer to:
 	/// https://z.cash/support/faq/#founders-reward
 	pub founders_addresses: Vec<Address>,
+	/// Funding streams, that replace founders reward after Canopy activation.
+	///
+	/// For details, refer to:
+	/// https://zips.z.cash/zip-0207
+	/// https://zips.z.cash/zip-0214
+	pub funding_streams: Vec<FundingStream>,
+	/// Transparent coinbase outputs could only be spent by transactions without transparent outputs.
+	pub coinbase_must_be_shielded: bool,
 
 	/// Equihash (N, K) parameters.
 	pub equihash_params: Option<(u32, u32)>,
 
+	/// Limits of block size && signature operations.
+	pub block_limits: BlockLimits,
+
 	/// Active key for pghr13 joinsplit verification
 	pub joinsplit_verification_key: crypto::Pghr13VerifyingKey,
 
@@ -84,6 +103,13 @@ pub struct ConsensusParams {
 	pub sapling_output_verifying_key: &'static crypto::Groth16VerifyingKey,
 }
 
+fn load_verifying_key(path: &path::Path) -> Result<&'static crypto::Groth16VerifyingKey, String> {
+	let file = fs::File::open(path).map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+	crypto::read_groth16_verifying_key(io::BufReader::new(file))
+		.map(|key| &*Box::leak(Box::new(key)))
+		.map_err(|err| format!("Unable to read {}: {}", path.display(), err))
+}
+
 fn mainnet_pghr_verification_key() -> crypto::Pghr13VerifyingKey {
 	crypto::json::pghr13::decode(include_bytes!("../../res/sprout-verifying-key.json")).expect("verifying key json invalid").into()
 }
@@ -139,7 +165,11 @@ fn unitest_pghr_verification_key() -> crypto::Pghr13VerifyingKey {
 impl ConsensusParams {
 	pub fn new(network: Network) -> Self {
 		match network {
-			Network::Mainnet | Network::Other(_) => ConsensusParams {
+			Network::Custom(spec) => ConsensusParams {
+				network: network,
+				..ConsensusParams::new(spec.base_network())
+			},
+			Network::Mainnet => ConsensusParams {
 				network: network,
 				bip16_time: 0,
 				bip34_height: 1,
@@ -149,8 +179,7 @@ impl ConsensusParams {
 				miner_confirmation_window: 2016,
 				csv_deployment: None,
 
-				overwinter_height: 347500,
-				sapling_height: 419200,
+				upgrades: UpgradeSchedule::new(network),
 
 				pow_averaging_window: 17,
 				pow_max_adjust_down: 32,
@@ -211,8 +240,13 @@ impl ConsensusParams {
 					"t3Pcm737EsVkGTbhsu2NekKtJeG92mvYyoN".into(),
 				],
 
+				funding_streams: FundingStream::for_network(network),
+				coinbase_must_be_shielded: true,
+
 				equihash_params: Some((200, 9)),
 
+				block_limits: BlockLimits::default(),
+
 				joinsplit_verification_key: mainnet_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
 
@@ -229,8 +263,7 @@ impl ConsensusParams {
 				miner_confirmation_window: 2016,
 				csv_deployment: None,
 
-				overwinter_height: 207500,
-				sapling_height: 280000,
+				upgrades: UpgradeSchedule::new(network),
 
 				pow_averaging_window: 17,
 				pow_max_adjust_down: 32,
@@ -291,8 +324,13 @@ impl ConsensusParams {
 					"t2UV2wr1PTaUiybpkV3FdSdGxUJeZdZztyt".into(),
 				],
 
+				funding_streams: FundingStream::for_network(network),
+				coinbase_must_be_shielded: true,
+
 				equihash_params: Some((200, 9)),
 
+				block_limits: BlockLimits::default(),
+
 				joinsplit_verification_key: testnet_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
 
@@ -310,8 +348,7 @@ impl ConsensusParams {
 				miner_confirmation_window: 144,
 				csv_deployment: None,
 
-				overwinter_height: ::std::u32::MAX,
-				sapling_height: ::std::u32::MAX,
+				upgrades: UpgradeSchedule::new(network),
 
 				pow_averaging_window: 17,
 				pow_max_adjust_down: 0,
@@ -325,7 +362,12 @@ impl ConsensusParams {
 					"t2FwcEhFdNXuFMv1tcYwaBJtYVtMj8b1uTg".into(),
 				],
 
-				equihash_params: Some((200, 9)),
+				funding_streams: FundingStream::for_network(network),
+				coinbase_must_be_shielded: false,
+
+				equihash_params: Some((48, 5)),
+
+				block_limits: BlockLimits::default(),
 
 				joinsplit_verification_key: regtest_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
@@ -343,8 +385,7 @@ impl ConsensusParams {
 				miner_confirmation_window: 144,
 				csv_deployment: None,
 
-				overwinter_height: ::std::u32::MAX,
-				sapling_height: ::std::u32::MAX,
+				upgrades: UpgradeSchedule::new(network),
 
 				pow_averaging_window: 17,
 				pow_max_adjust_down: 0,
@@ -358,8 +399,13 @@ impl ConsensusParams {
 					"t2FwcEhFdNXuFMv1tcYwaBJtYVtMj8b1uTg".into(),
 				],
 
+				funding_streams: FundingStream::for_network(network),
+				coinbase_must_be_shielded: false,
+
 				equihash_params: None,
 
+				block_limits: BlockLimits::default(),
+
 				joinsplit_verification_key: unitest_pghr_verification_key(),
 				joinsplit_groth16_verification_key: &JOINSPLIT_GROTH16_VK,
 
@@ -369,141 +415,444 @@ impl ConsensusParams {
 		}
 	}
 
+	/// Replace embedded Sapling verifying keys with keys from zcashd parameters files
+	/// (`sapling-spend.params` && `sapling-output.params`) in given directory.
+	pub fn load_sapling_verifying_keys(&mut self, params_dir: &path::Path) -> Result<(), String> {
+		self.sapling_spend_verifying_key = load_verifying_key(&params_dir.join("sapling-spend.params"))?;
+		self.sapling_output_verifying_key = load_verifying_key(&params_dir.join("sapling-output.params"))?;
+		Ok(())
+	}
+
 	pub fn magic(&self) -> Magic {
 		self.network.magic()
 	}
 
-	pub fn averaging_window_timespan(&self) -> u32 {
-		self.pow_averaging_window * self.pow_target_spacing
+	/// Expected duration (in seconds) of the difficulty averaging window, ending at given height.
+	pub fn averaging_window_timespan(&self, height: u32) -> u32 {
+		self.pow_averaging_window * self.target_spacing(height)
 	}
 
-	pub fn min_actual_timespan(&self) -> u32 {
-		(self.averaging_window_timespan() * (100 - self.pow_max_adjust_up)) / 100
+	/// Optimal blocks interval (in seconds) at given height. Blossom has halved the interval.
+	/// https://zips.z.cash/zip-0208
+	pub fn target_spacing(&self, height: u32) -> u32 {
+		if self.upgrades.is_active(NetworkUpgrade::Blossom, height) {
+			self.pow_target_spacing / BLOSSOM_POW_TARGET_SPACING_RATIO
+		} else {
+			self.pow_target_spacing
+		}
 	}
 
-	pub fn max_actual_timespan(&self) -> u32 {
-		(self.averaging_window_timespan() * (100 + self.pow_max_adjust_down)) / 100
+	pub fn min_actual_timespan(&self, height: u32) -> u32 {
+		(self.averaging_window_timespan(height) * (100 - self.pow_max_adjust_up)) / 100
 	}
 
-	pub fn min_block_version(&self) -> u32 {
-		4
+	pub fn max_actual_timespan(&self, height: u32) -> u32 {
+		(self.averaging_window_timespan(height) * (100 + self.pow_max_adjust_down)) / 100
 	}
 
-	pub fn max_block_size(&self) -> usize {
-		2_000_000
+	/// Size (in bytes) of the Equihash solution, if Equihash is used by the network.
+	pub fn equihash_solution_size(&self) -> Option<usize> {
+		self.equihash_params.map(|(n, k)| (1usize << k) * (n as usize / (k as usize + 1) + 1) / 8)
 	}
 
-	pub fn max_block_sigops(&self) -> usize {
-		20_000
+	pub fn min_block_version(&self) -> u32 {
+		4
 	}
 
-	pub fn max_transaction_value(&self) -> i64 {
-		21_000_000 * 100_000_000 // No amount larger than this (in satoshi) is valid
+	pub fn max_transaction_value(&self) -> Amount {
+		Amount::max_value()
 	}
 
 	pub fn absolute_max_transaction_size(&self) -> usize {
-		2_000_000
+		MAX_BLOCK_SIZE
 	}
 
 	pub fn max_transaction_size(&self, height: u32) -> usize {
-		if height >= self.sapling_height {
-			2_000_000
+		if self.is_sapling_active(height) {
+			MAX_BLOCK_SIZE
 		} else {
-			100_000
+			MAX_TRANSACTION_SIZE_BEFORE_SAPLING
 		}
 	}
 
-	pub fn transaction_expiry_height_threshold(&self) -> u32 {
-		500_000_000
-	}
-
 	pub fn is_overwinter_active(&self, height: u32) -> bool {
-		height >= self.overwinter_height
+		self.upgrades.is_active(NetworkUpgrade::Overwinter, height)
 	}
 
 	pub fn is_sapling_active(&self, height: u32) -> bool {
-		height >= self.sapling_height
+		self.upgrades.is_active(NetworkUpgrade::Sapling, height)
+	}
+
+	pub fn is_nu5_active(&self, height: u32) -> bool {
+		self.upgrades.is_active(NetworkUpgrade::Nu5, height)
+	}
+
+	/// Network upgrade that is active at given height.
+	pub fn network_upgrade(&self, height: u32) -> NetworkUpgrade {
+		self.upgrades.active_at(height)
+	}
+
+	/// Returns true if some network upgrade activates at given height.
+	pub fn is_activation_height(&self, height: u32) -> bool {
+		self.upgrades.activation_height(self.network_upgrade(height)) == Some(height)
+	}
+
+	/// Meaning of the commitment field of the header of the block at given height.
+	pub fn block_commitment(&self, header: &BlockHeader, height: u32) -> BlockCommitment {
+		let hash = header.final_sapling_root;
+		match self.network_upgrade(height) {
+			NetworkUpgrade::Sprout | NetworkUpgrade::Overwinter => BlockCommitment::Reserved(hash),
+			NetworkUpgrade::Sapling | NetworkUpgrade::Blossom => BlockCommitment::FinalSaplingRoot(hash),
+			NetworkUpgrade::Heartwood | NetworkUpgrade::Canopy => BlockCommitment::ChainHistoryRoot(hash),
+			NetworkUpgrade::Nu5 => BlockCommitment::BlockCommitments(hash),
+		}
+	}
+
+	/// Network upgrades that are scheduled on this network, with their activation heights.
+	pub fn network_upgrades(&self) -> Vec<(NetworkUpgrade, u32)> {
+		self.upgrades.scheduled()
 	}
 
-	/// Block subsidy (total block reward).
-	pub fn block_reward(&self, height: u32) -> u64 {
-		let mut reward = 1_250_000_000u64;
+	/// BIP9 deployments that are defined on this network.
+	pub fn deployments(&self) -> Vec<Deployment> {
+		self.csv_deployment.into_iter().collect()
+	}
+
+	/// Number of subsidy halvings that have happened before the block at given height. Blocks of the
+	/// slow start interval are mined before the first halving interval starts.
+	///
+	/// After Blossom activation, halving interval is multiplied by the ratio of pre-Blossom to
+	/// post-Blossom target block spacing, so that halvings are happening at the same time.
+	/// https://zips.z.cash/zip-0208
+	pub fn halving(&self, height: u32) -> u32 {
+		let slow_start_shift = self.subsidy_slow_start_interval / 2;
+		match self.upgrades.activation_height(NetworkUpgrade::Blossom) {
+			Some(blossom_height) if height >= blossom_height => {
+				// halvings = (blossom_height - slow_start_shift) / pre_blossom_halving_interval
+				//   + (height - blossom_height) / post_blossom_halving_interval
+				// is computed in units of post-Blossom halving interval to avoid rounding errors
+				let post_blossom_halving_interval = self.subsidy_halving_interval as u64 * BLOSSOM_POW_TARGET_SPACING_RATIO as u64;
+				let scaled_halvings = blossom_height.saturating_sub(slow_start_shift) as u64 * BLOSSOM_POW_TARGET_SPACING_RATIO as u64
+					+ (height - blossom_height) as u64;
+				(scaled_halvings / post_blossom_halving_interval) as u32
+			},
+			_ => height.saturating_sub(slow_start_shift) / self.subsidy_halving_interval,
+		}
+	}
+
+	/// Block subsidy (total block reward, excluding fees) at given height.
+	///
+	/// Subsidy is linearly ramped up during slow start interval, then halved every
+	/// `subsidy_halving_interval` blocks. Post-Blossom subsidy is divided by the ratio
+	/// of pre-Blossom to post-Blossom target block spacing.
+	pub fn block_subsidy(&self, height: u32) -> u64 {
+		let mut subsidy = 1_250_000_000u64;
 		if height < self.subsidy_slow_start_interval / 2 {
-			reward /= self.subsidy_slow_start_interval as u64;
-			reward *= height as u64;
+			subsidy /= self.subsidy_slow_start_interval as u64;
+			return subsidy * height as u64;
 		} else if height < self.subsidy_slow_start_interval {
-			reward /= self.subsidy_slow_start_interval as u64;
-			reward *= height as u64 + 1;
-		} else {
-			let halvings = (height - self.subsidy_slow_start_interval / 2) / self.subsidy_halving_interval;
-			if halvings >= 64 {
-				return 0;
-			}
+			subsidy /= self.subsidy_slow_start_interval as u64;
+			return subsidy * (height as u64 + 1);
+		}
+
+		let halvings = self.halving(height);
+		if halvings >= 64 {
+			return 0;
+		}
 
-			reward >>= halvings as u64;
+		if self.upgrades.is_active(NetworkUpgrade::Blossom, height) {
+			subsidy /= BLOSSOM_POW_TARGET_SPACING_RATIO as u64;
 		}
 
-		reward
+		subsidy >> halvings as u64
 	}
 
 	/// Block reward (goes to miner) at given height.
 	pub fn miner_reward(&self, height: u32) -> u64 {
-		let mut miner_reward = self.block_reward(height);
-		if self.founder_address(height).is_some() {
+		let mut miner_reward = self.block_subsidy(height);
+		if self.founders_reward_address(height).is_some() {
 			miner_reward -= self.founder_reward(height);
 		}
+		for output in self.funding_stream_outputs(height) {
+			miner_reward -= output.value;
+		}
 		miner_reward
 	}
 
 	/// Founders reward (goes to founders) at given height.
 	pub fn founder_reward(&self, height: u32) -> u64 {
-		self.block_reward(height) / 5
+		self.block_subsidy(height) / 5
+	}
+
+	/// Height of the last block that pays founders reward, as seen from the block at given height.
+	///
+	/// Blossom has halved target block spacing, so blocks after Blossom activation are paying
+	/// founders reward for twice as many blocks.
+	/// https://zips.z.cash/zip-0208
+	pub fn last_founders_reward_block_height(&self, height: u32) -> u32 {
+		let slow_start_shift = self.subsidy_slow_start_interval / 2;
+		match self.upgrades.activation_height(NetworkUpgrade::Blossom) {
+			Some(blossom_height) if height >= blossom_height => blossom_height
+				+ self.subsidy_halving_interval * BLOSSOM_POW_TARGET_SPACING_RATIO
+				- (blossom_height - slow_start_shift) * BLOSSOM_POW_TARGET_SPACING_RATIO
+				- 1,
+			_ => self.subsidy_halving_interval + slow_start_shift - 1,
+		}
 	}
 
 	/// Address (transparent) where founders reward goes at given height.
-	pub fn founder_address(&self, height: u32) -> Option<Address> {
-		let last_founder_reward_block_height = self.subsidy_halving_interval + self.subsidy_slow_start_interval / 2 - 1;
-		if height == 0 || height > last_founder_reward_block_height {
+	///
+	/// Founders reward is paid by every block (except genesis) until the first halving or
+	/// Canopy activation, whichever comes first. The address is rotated at fixed intervals,
+	/// so that every address receives equal share of the reward.
+	/// https://zips.z.cash/protocol/protocol.pdf#foundersreward
+	pub fn founders_reward_address(&self, height: u32) -> Option<Address> {
+		if height == 0
+			|| height > self.last_founders_reward_block_height(height)
+			|| self.upgrades.is_active(NetworkUpgrade::Canopy, height) {
 			return None;
 		}
 
+		// post-Blossom blocks are mapped to the pre-Blossom heights with the same time
+		let address_height = match self.upgrades.activation_height(NetworkUpgrade::Blossom) {
+			Some(blossom_height) if height >= blossom_height =>
+				blossom_height + (height - blossom_height) / BLOSSOM_POW_TARGET_SPACING_RATIO,
+			_ => height,
+		};
+
+		let last_pre_blossom_height = self.subsidy_halving_interval + self.subsidy_slow_start_interval / 2 - 1;
 		let founders_len = self.founders_addresses.len() as u32;
-		let address_change_interval = (last_founder_reward_block_height + founders_len) / founders_len;
-		let address_index = height / address_change_interval;
+		let address_change_interval = (last_pre_blossom_height + founders_len) / founders_len;
+		let address_index = address_height / address_change_interval;
 		Some(self.founders_addresses[address_index as usize].clone())
 	}
 
-	pub fn consensus_branch_id(&self, height: u32) -> u32 {
-		// sapling upgrade
-		if height >= self.sapling_height {
-			return 0x76b809bb;
+	/// Outputs that the coinbase transaction of the block at given height must have to pay funding streams.
+	pub fn funding_stream_outputs(&self, height: u32) -> Vec<FundingStreamOutput> {
+		if !self.upgrades.is_active(NetworkUpgrade::Canopy, height) {
+			return Vec::new();
 		}
 
-		// overwinter upgrade
-		if height >= self.overwinter_height {
-			return 0x5ba81b19;
-		}
+		let block_subsidy = self.block_subsidy(height);
+		self.funding_streams.iter()
+			.filter(|stream| stream.is_active(height))
+			.map(|stream| {
+				let address_index = self.funding_stream_address_index(height)
+					- self.funding_stream_address_index(stream.start_height);
+				FundingStreamOutput {
+					receiver: stream.receiver,
+					address: stream.addresses[address_index as usize].clone(),
+					value: stream.value(block_subsidy),
+				}
+			})
+			.collect()
+	}
 
-		// sprout
-		0
+	/// Index of the funding stream address change interval that contains given height.
+	/// Intervals are aligned to the first halving.
+	fn funding_stream_address_index(&self, height: u32) -> u32 {
+		let post_blossom_halving_interval = self.subsidy_halving_interval * BLOSSOM_POW_TARGET_SPACING_RATIO;
+		let address_change_interval = post_blossom_halving_interval / FUNDING_STREAM_ADDRESS_CHANGES_PER_HALVING;
+		// founders reward ends right before the first halving
+		let first_halving_height = self.last_founders_reward_block_height(::std::u32::MAX) + 1;
+		(height + post_blossom_halving_interval - first_halving_height) / address_change_interval
+	}
+
+	/// Consensus branch id that is used for signature hashing at given height.
+	pub fn consensus_branch_id(&self, height: u32) -> u32 {
+		self.network_upgrade(height).branch_id()
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use FundingStreamReceiver;
+
+	#[test]
+	fn block_subsidy() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.block_subsidy(1), 62_500);
+		assert_eq!(consensus.block_subsidy(10_000), 625_062_500);
+		assert_eq!(consensus.block_subsidy(20_000), 1_250_000_000);
+		// Blossom activation
+		assert_eq!(consensus.block_subsidy(653_599), 1_250_000_000);
+		assert_eq!(consensus.block_subsidy(653_600), 625_000_000);
+		// first halving
+		assert_eq!(consensus.block_subsidy(1_046_399), 625_000_000);
+		assert_eq!(consensus.block_subsidy(1_046_400), 312_500_000);
+		assert_eq!(consensus.block_subsidy(2_000_000), 312_500_000);
+		// second halving
+		assert_eq!(consensus.block_subsidy(2_726_399), 312_500_000);
+		assert_eq!(consensus.block_subsidy(2_726_400), 156_250_000);
+		assert_eq!(consensus.block_subsidy(20_000_000), 152_587);
+		assert_eq!(consensus.block_subsidy(30_000_000), 2_384);
+		assert_eq!(consensus.block_subsidy(200_000_000), 0);
+
+		// no slow start and no Blossom on regtest
+		let consensus = ConsensusParams::new(Network::Regtest);
+		assert_eq!(consensus.block_subsidy(0), 1_250_000_000);
+		assert_eq!(consensus.block_subsidy(149), 1_250_000_000);
+		assert_eq!(consensus.block_subsidy(150), 625_000_000);
+	}
+
+	#[test]
+	fn halving() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		// slow start interval
+		assert_eq!(consensus.halving(0), 0);
+		assert_eq!(consensus.halving(9_999), 0);
+		assert_eq!(consensus.halving(10_000), 0);
+		assert_eq!(consensus.halving(1_046_399), 0);
+		assert_eq!(consensus.halving(1_046_400), 1);
+		assert_eq!(consensus.halving(2_726_400), 2);
+
+		// testnet Blossom has activated at different height, but first halving is at the same height
+		let consensus = ConsensusParams::new(Network::Testnet);
+		assert_eq!(consensus.halving(1_116_000 - 1), 0);
+		assert_eq!(consensus.halving(1_116_000), 1);
+	}
+
+	#[test]
+	fn equihash_solution_size() {
+		assert_eq!(ConsensusParams::new(Network::Mainnet).equihash_solution_size(), Some(1344));
+		assert_eq!(ConsensusParams::new(Network::Testnet).equihash_solution_size(), Some(1344));
+		assert_eq!(ConsensusParams::new(Network::Regtest).equihash_solution_size(), Some(36));
+		assert_eq!(ConsensusParams::new(Network::Unitest).equihash_solution_size(), None);
+	}
+
+	#[test]
+	fn target_spacing() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.target_spacing(653_599), 150);
+		assert_eq!(consensus.target_spacing(653_600), 75);
+		assert_eq!(consensus.averaging_window_timespan(653_599), 17 * 150);
+		assert_eq!(consensus.averaging_window_timespan(653_600), 17 * 75);
+		assert_eq!(consensus.min_actual_timespan(653_600), 17 * 75 * 84 / 100);
+		assert_eq!(consensus.max_actual_timespan(653_600), 17 * 75 * 132 / 100);
+	}
+
+	#[test]
+	fn network_upgrades() {
+		assert_eq!(ConsensusParams::new(Network::Mainnet).network_upgrades(), vec![
+			(NetworkUpgrade::Overwinter, 347500),
+			(NetworkUpgrade::Sapling, 419200),
+			(NetworkUpgrade::Blossom, 653600),
+			(NetworkUpgrade::Heartwood, 903000),
+			(NetworkUpgrade::Canopy, 1046400),
+			(NetworkUpgrade::Nu5, 1687104),
+		]);
+		assert_eq!(ConsensusParams::new(Network::Testnet).network_upgrades(), vec![
+			(NetworkUpgrade::Overwinter, 207500),
+			(NetworkUpgrade::Sapling, 280000),
+			(NetworkUpgrade::Blossom, 584000),
+			(NetworkUpgrade::Heartwood, 903800),
+			(NetworkUpgrade::Canopy, 1028500),
+			(NetworkUpgrade::Nu5, 1842420),
+		]);
+		assert_eq!(ConsensusParams::new(Network::Regtest).network_upgrades(), vec![]);
+	}
+
+	#[test]
+	fn last_founders_reward_block_height() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.last_founders_reward_block_height(1), 849_999);
+		assert_eq!(consensus.last_founders_reward_block_height(653_599), 849_999);
+		assert_eq!(consensus.last_founders_reward_block_height(653_600), 1_046_399);
+
+		let consensus = ConsensusParams::new(Network::Testnet);
+		assert_eq!(consensus.last_founders_reward_block_height(584_000), 1_115_999);
+
+		let consensus = ConsensusParams::new(Network::Regtest);
+		assert_eq!(consensus.last_founders_reward_block_height(1), 149);
+	}
+
+	#[test]
+	fn founders_reward_address() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let addresses = &consensus.founders_addresses;
+		assert_eq!(addresses.len(), 48);
+		assert_eq!(consensus.founders_reward_address(0), None);
+		assert_eq!(consensus.founders_reward_address(1), Some(addresses[0].clone()));
+		assert_eq!(consensus.founders_reward_address(17_708), Some(addresses[0].clone()));
+		assert_eq!(consensus.founders_reward_address(17_709), Some(addresses[1].clone()));
+		assert_eq!(consensus.founders_reward_address(653_599), Some(addresses[36].clone()));
+		// after Blossom, address is changed every 2 * 17_709 blocks
+		assert_eq!(consensus.founders_reward_address(700_000), Some(addresses[38].clone()));
+		assert_eq!(consensus.founders_reward_address(1_046_399), Some(addresses[47].clone()));
+		assert_eq!(consensus.founders_reward_address(1_046_400), None);
+
+		// testnet founders reward ends at Canopy activation, before the first halving
+		let consensus = ConsensusParams::new(Network::Testnet);
+		assert!(consensus.founders_reward_address(1_028_499).is_some());
+		assert_eq!(consensus.founders_reward_address(1_028_500), None);
+
+		let consensus = ConsensusParams::new(Network::Regtest);
+		assert_eq!(consensus.founders_reward_address(149), Some(consensus.founders_addresses[0].clone()));
+		assert_eq!(consensus.founders_reward_address(150), None);
+	}
+
+	#[test]
+	fn consensus_branch_id() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.consensus_branch_id(0), 0);
+		assert_eq!(consensus.consensus_branch_id(347500), 0x5ba81b19);
+		assert_eq!(consensus.consensus_branch_id(419200), 0x76b809bb);
+		assert_eq!(consensus.consensus_branch_id(653600), 0x2bb40e60);
+		assert_eq!(consensus.consensus_branch_id(903000), 0xf5b9230b);
+		assert_eq!(consensus.consensus_branch_id(1046400), 0xe9ff75a6);
+		assert_eq!(consensus.consensus_branch_id(1687104), 0xc2d6d0b4);
+		assert_eq!(ConsensusParams::new(Network::Testnet).consensus_branch_id(280000), 0x76b809bb);
+		assert_eq!(ConsensusParams::new(Network::Regtest).consensus_branch_id(280000), 0);
+	}
+
+	#[test]
+	fn funding_stream_outputs() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(consensus.funding_stream_outputs(1_046_399), vec![]);
+
+		let outputs = consensus.funding_stream_outputs(1_046_400);
+		let block_subsidy = consensus.block_subsidy(1_046_400);
+		assert_eq!(outputs.len(), 3);
+		assert_eq!(outputs[0].receiver, FundingStreamReceiver::Ecc);
+		assert_eq!(outputs[0].address, "t3LmX1cxWPPPqL4TZHx42HU3U5ghbFjRiif".into());
+		assert_eq!(outputs[0].value, block_subsidy * 7 / 100);
+		assert_eq!(outputs[1].receiver, FundingStreamReceiver::ZcashFoundation);
+		assert_eq!(outputs[1].address, "t3dvVE3SQEi7kqNzwrfNePxZ1d4hUyztBA1".into());
+		assert_eq!(outputs[1].value, block_subsidy * 5 / 100);
+		assert_eq!(outputs[2].receiver, FundingStreamReceiver::MajorGrants);
+		assert_eq!(outputs[2].address, "t3XyYW8yBFRuMnfvm5KLGFbEVz25kckZXym".into());
+		assert_eq!(outputs[2].value, block_subsidy * 8 / 100);
+		assert_eq!(consensus.miner_reward(1_046_400), block_subsidy - outputs.iter().map(|o| o.value).sum::<u64>());
+
+		// ECC address is changed every 35_000 blocks
+		assert_eq!(consensus.funding_stream_outputs(1_081_399)[0].address, consensus.funding_streams[0].addresses[0]);
+		assert_eq!(consensus.funding_stream_outputs(1_081_400)[0].address, consensus.funding_streams[0].addresses[1]);
+		assert_eq!(consensus.funding_stream_outputs(2_726_399)[0].address, consensus.funding_streams[0].addresses[47]);
+		assert_eq!(consensus.funding_stream_outputs(2_726_400), vec![]);
+
+		// testnet streams start before the first halving => address intervals are aligned to the halving
+		let consensus = ConsensusParams::new(Network::Testnet);
+		assert_eq!(consensus.funding_stream_outputs(1_028_500)[0].address, consensus.funding_streams[0].addresses[0]);
+		assert_eq!(consensus.funding_stream_outputs(1_115_999)[0].address, consensus.funding_streams[0].addresses[2]);
+		assert_eq!(consensus.funding_stream_outputs(1_116_000)[0].address, consensus.funding_streams[0].addresses[3]);
+		assert_eq!(consensus.funding_stream_outputs(2_795_999)[0].address, consensus.funding_streams[0].addresses[50]);
+
+		assert_eq!(ConsensusParams::new(Network::Regtest).funding_stream_outputs(1_046_400), vec![]);
+	}
 
 	#[test]
-	fn block_reward() {
+	fn block_commitment() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
-		assert_eq!(consensus.block_reward(1), 62_500);
-		assert_eq!(consensus.block_reward(10_000), 625_062_500);
-		assert_eq!(consensus.block_reward(20_000), 1_250_000_000);
-		assert_eq!(consensus.block_reward(1_000_000), 625_000_000);
-		assert_eq!(consensus.block_reward(2_000_000), 312_500_000);
-		assert_eq!(consensus.block_reward(3_000_000), 156_250_000);
-		assert_eq!(consensus.block_reward(4_000_000), 78_125_000);
-		assert_eq!(consensus.block_reward(20_000_000), 149);
-		assert_eq!(consensus.block_reward(30_000_000), 0);
+		let mut header = Network::Mainnet.genesis_block().header.raw;
+		header.final_sapling_root = 1.into();
+		assert_eq!(consensus.block_commitment(&header, 419_199), BlockCommitment::Reserved(1.into()));
+		assert_eq!(consensus.block_commitment(&header, 419_200), BlockCommitment::FinalSaplingRoot(1.into()));
+		assert_eq!(consensus.block_commitment(&header, 903_000), BlockCommitment::ChainHistoryRoot(1.into()));
+		assert_eq!(consensus.block_commitment(&header, 1_687_104), BlockCommitment::BlockCommitments(1.into()));
+
+		assert!(consensus.is_activation_height(903_000));
+		assert!(consensus.is_activation_height(1_046_400));
+		assert!(!consensus.is_activation_height(1_046_401));
 	}
 }
@@ -0,0 +1,216 @@
+//! Zcash funding streams, that replace founders reward after Canopy activation.
+//! https://zips.z.cash/zip-0207
+//! https://zips.z.cash/zip-0214
+
+use keys::Address;
+use Network;
+
+/// Recipient of the funding stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FundingStreamReceiver {
+	/// Electric Coin Company.
+	Ecc,
+	/// Zcash Foundation.
+	ZcashFoundation,
+	/// Major Grants fund.
+	MajorGrants,
+}
+
+impl FundingStreamReceiver {
+	/// Human-readable name of the receiver.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			FundingStreamReceiver::Ecc => "Electric Coin Company",
+			FundingStreamReceiver::ZcashFoundation => "Zcash Foundation",
+			FundingStreamReceiver::MajorGrants => "Major Grants",
+		}
+	}
+}
+
+/// Single funding stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingStream {
+	/// Recipient of the stream.
+	pub receiver: FundingStreamReceiver,
+	/// Numerator of the block subsidy fraction that goes to the stream.
+	pub numerator: u64,
+	/// Denominator of the block subsidy fraction that goes to the stream.
+	pub denominator: u64,
+	/// Height of the first block that pays to the stream.
+	pub start_height: u32,
+	/// Height of the first block that doesn't pay to the stream.
+	pub end_height: u32,
+	/// Addresses of the stream, one for every address change interval since stream start.
+	pub addresses: Vec<Address>,
+}
+
+/// Coinbase output that pays to the funding stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingStreamOutput {
+	/// Recipient of the output.
+	pub receiver: FundingStreamReceiver,
+	/// Address that must receive the value.
+	pub address: Address,
+	/// Value of the output.
+	pub value: u64,
+}
+
+impl FundingStream {
+	/// Funding streams of given network.
+	pub fn for_network(network: Network) -> Vec<FundingStream> {
+		match network {
+			Network::Custom(spec) => FundingStream::for_network(spec.base_network()),
+			Network::Mainnet => {
+				let (start_height, end_height) = (1_046_400, 2_726_400);
+				vec![
+					FundingStream::new(FundingStreamReceiver::Ecc, 7, start_height, end_height, mainnet_ecc_addresses()),
+					FundingStream::new(FundingStreamReceiver::ZcashFoundation, 5, start_height, end_height,
+						vec!["t3dvVE3SQEi7kqNzwrfNePxZ1d4hUyztBA1".into(); 48]),
+					FundingStream::new(FundingStreamReceiver::MajorGrants, 8, start_height, end_height,
+						vec!["t3XyYW8yBFRuMnfvm5KLGFbEVz25kckZXym".into(); 48]),
+				]
+			},
+			Network::Testnet => {
+				let (start_height, end_height) = (1_028_500, 2_796_000);
+				vec![
+					FundingStream::new(FundingStreamReceiver::Ecc, 7, start_height, end_height, testnet_ecc_addresses()),
+					FundingStream::new(FundingStreamReceiver::ZcashFoundation, 5, start_height, end_height,
+						vec!["t27eWDgjFYJGVXmzrXeVjnb5J3uXDM9xH9v".into(); 51]),
+					FundingStream::new(FundingStreamReceiver::MajorGrants, 8, start_height, end_height,
+						vec!["t2Gvxv2uNM7hbbACjNox4H6DjByoKZ2Fa3P".into(); 51]),
+				]
+			},
+			Network::Regtest | Network::Unitest => Vec::new(),
+		}
+	}
+
+	fn new(receiver: FundingStreamReceiver, percent: u64, start_height: u32, end_height: u32, addresses: Vec<Address>) -> Self {
+		FundingStream {
+			receiver: receiver,
+			numerator: percent,
+			denominator: 100,
+			start_height: start_height,
+			end_height: end_height,
+			addresses: addresses,
+		}
+	}
+
+	/// Returns true if block at given height pays to this stream.
+	pub fn is_active(&self, height: u32) -> bool {
+		height >= self.start_height && height < self.end_height
+	}
+
+	/// Value that goes to this stream from the given block subsidy.
+	pub fn value(&self, block_subsidy: u64) -> u64 {
+		block_subsidy * self.numerator / self.denominator
+	}
+}
+
+fn mainnet_ecc_addresses() -> Vec<Address> {
+	vec![
+			"t3LmX1cxWPPPqL4TZHx42HU3U5ghbFjRiif",
+			"t3Toxk1vJQ6UjWQ42tUJz2rV2feUWkpbTDs",
+			"t3ZBdBe4iokmsjdhMuwkxEdqMCFN16YxKe6",
+			"t3ZuaJziLM8xZ32rjDUzVjVtyYdDSz8GLWB",
+			"t3bAtYWa4bi8VrtvqySxnbr5uqcG9czQGTZ",
+			"t3dktADfb5Rmxncpe1HS5BRS5Gcj7MZWYBi",
+			"t3hgskquvKKoCtvxw86yN7q8bzwRxNgUZmc",
+			"t3R1VrLzwcxAZzkX4mX3KGbWpNsgtYtMntj",
+			"t3ff6fhemqPMVujD3AQurxRxTdvS1pPSaa2",
+			"t3cEUQFG3KYnFG6qYhPxSNgGi3HDjUPwC3J",
+			"t3WR9F5U4QvUFqqx9zFmwT6xFqduqRRXnaa",
+			"t3PYc1LWngrdUrJJbHkYPCKvJuvJjcm85Ch",
+			"t3bgkjiUeatWNkhxY3cWyLbTxKksAfk561R",
+			"t3Z5rrR8zahxUpZ8itmCKhMSfxiKjUp5Dk5",
+			"t3PU1j7YW3fJ67jUbkGhSRto8qK2qXCUiW3",
+			"t3S3yaT7EwNLaFZCamfsxxKwamQW2aRGEkh",
+			"t3eutXKJ9tEaPSxZpmowhzKhPfJvmtwTEZK",
+			"t3gbTb7brxLdVVghSPSd3ycGxzHbUpukeDm",
+			"t3UCKW2LrHFqPMQFEbZn6FpjqnhAAbfpMYR",
+			"t3NyHsrnYbqaySoQqEQRyTWkjvM2PLkU7Uu",
+			"t3QEFL6acxuZwiXtW3YvV6njDVGjJ1qeaRo",
+			"t3PdBRr2S1XTDzrV8bnZkXF3SJcrzHWe1wj",
+			"t3ZWyRPpWRo23pKxTLtWsnfEKeq9T4XPxKM",
+			"t3he6QytKCTydhpztykFsSsb9PmBT5JBZLi",
+			"t3VWxWDsLb2TURNEP6tA1ZSeQzUmPKFNxRY",
+			"t3NmWLvZkbciNAipauzsFRMxoZGqmtJksbz",
+			"t3cKr4YxVPvPBG1mCvzaoTTdBNokohsRJ8n",
+			"t3T3smGZn6BoSFXWWXa1RaoQdcyaFjMfuYK",
+			"t3gkDUe9Gm4GGpjMk86TiJZqhztBVMiUSSA",
+			"t3eretuBeBXFHe5jAqeSpUS1cpxVh51fAeb",
+			"t3dN8g9zi2UGJdixGe9txeSxeofLS9t3yFQ",
+			"t3S799pq9sYBFwccRecoTJ3SvQXRHPrHqvx",
+			"t3fhYnv1S5dXwau7GED3c1XErzt4n4vDxmf",
+			"t3cmE3vsBc5xfDJKXXZdpydCPSdZqt6AcNi",
+			"t3h5fPdjJVHaH4HwynYDM5BB3J7uQaoUwKi",
+			"t3Ma35c68BgRX8sdLDJ6WR1PCrKiWHG4Da9",
+			"t3LokMKPL1J8rkJZvVpfuH7dLu6oUWqZKQK",
+			"t3WFFGbEbhJWnASZxVLw2iTJBZfJGGX73mM",
+			"t3L8GLEsUn4QHNaRYcX3EGyXmQ8kjpT1zTa",
+			"t3PgfByBhaBSkH8uq4nYJ9ZBX4NhGCJBVYm",
+			"t3WecsqKDhWXD4JAgBVcnaCC2itzyNZhJrv",
+			"t3ZG9cSfopnsMQupKW5v9sTotjcP5P6RTbn",
+			"t3hC1Ywb5zDwUYYV8LwhvF5rZ6m49jxXSG5",
+			"t3VgMqDL15ZcyQDeqBsBW3W6rzfftrWP2yB",
+			"t3LC94Y6BwLoDtBoK2NuewaEbnko1zvR9rm",
+			"t3cWCUZJR3GtALaTcatrrpNJ3MGbMFVLRwQ",
+			"t3YYF4rPLVxDcF9hHFsXyc5Yq1TFfbojCY6",
+			"t3XHAGxRP2FNfhAjxGjxbrQPYtQQjc3RCQD",
+	].into_iter().map(Into::into).collect()
+}
+
+fn testnet_ecc_addresses() -> Vec<Address> {
+	vec![
+			"t26ovBdKAJLtrvBsE2QGF4nqBkEuptuPFZz",
+			"t26ovBdKAJLtrvBsE2QGF4nqBkEuptuPFZz",
+			"t26ovBdKAJLtrvBsE2QGF4nqBkEuptuPFZz",
+			"t26ovBdKAJLtrvBsE2QGF4nqBkEuptuPFZz",
+			"t2NNHrgPpE388atmWSF4DxAb3xAoW5Yp45M",
+			"t2VMN28itPyMeMHBEd9Z1hm6YLkQcGA1Wwe",
+			"t2CHa1TtdfUV8UYhNm7oxbzRyfr8616BYh2",
+			"t2F77xtr28U96Z2bC53ZEdTnQSUAyDuoa67",
+			"t2ARrzhbgcpoVBDPivUuj6PzXzDkTBPqfcT",
+			"t278aQ8XbvFR15mecRguiJDQQVRNnkU8kJw",
+			"t2Dp1BGnZsrTXZoEWLyjHmg3EPvmwBnPDGB",
+			"t2KzeqXgf4ju33hiSqCuKDb8iHjPCjMq9iL",
+			"t2Nyxqv1BiWY1eUSiuxVw36oveawYuo18tr",
+			"t2DKFk5JRsVoiuinK8Ti6eM4Yp7v8BbfTyH",
+			"t2CUaBca4k1x36SC4q8Nc8eBoqkMpF3CaLg",
+			"t296SiKL7L5wvFmEdMxVLz1oYgd6fTfcbZj",
+			"t29fBCFbhgsjL3XYEZ1yk1TUh7eTusB6dPg",
+			"t2FGofLJXa419A76Gpf5ncxQB4gQXiQMXjK",
+			"t2ExfrnRVnRiXDvxerQ8nZbcUQvNvAJA6Qu",
+			"t28JUffLp47eKPRHKvwSPzX27i9ow8LSXHx",
+			"t2JXWPtrtyL861rFWMZVtm3yfgxAf4H7uPA",
+			"t2QdgbJoWfYHgyvEDEZBjHmgkr9yNJff3Hi",
+			"t2QW43nkco8r32ZGRN6iw6eSzyDjkMwCV3n",
+			"t2DgYDXMJTYLwNcxighQ9RCgPxMVATRcUdC",
+			"t2Bop7dg33HGZx3wunnQzi2R2ntfpjuti3M",
+			"t2HVeEwovcLq9RstAbYkqngXNEsCe2vjJh9",
+			"t2HxbP5keQSx7p592zWQ5bJ5GrMmGDsV2Xa",
+			"t2TJzUg2matao3mztBRJoWnJY6ekUau6tPD",
+			"t29pMzxmo6wod25YhswcjKv3AFRNiBZHuhj",
+			"t2QBQMRiJKYjshJpE6RhbF7GLo51yE6d4wZ",
+			"t2F5RqnqguzZeiLtYHFx4yYfy6pDnut7tw5",
+			"t2CHvyZANE7XCtg8AhZnrcHCC7Ys1jJhK13",
+			"t2BRzpMdrGWZJ2upsaNQv6fSbkbTy7EitLo",
+			"t2BFixHGQMAWDY67LyTN514xRAB94iEjXp3",
+			"t2Uvz1iVPzBEWfQBH1p7NZJsFhD74tKaG8V",
+			"t2CmFDj5q6rJSRZeHf1SdrowinyMNcj438n",
+			"t2ErNvWEReTfPDBaNizjMPVssz66aVZh1hZ",
+			"t2GeJQ8wBUiHKDVzVM5ZtKfY5reCg7CnASs",
+			"t2L2eFtkKv1G6j55kLytKXTGuir4raAy3yr",
+			"t2EK2b87dpPazb7VvmEGc8iR6SJ289RywGL",
+			"t2DJ7RKeZJxdA4nZn8hRGXE8NUyTzjujph9",
+			"t2K1pXo4eByuWpKLkssyMLe8QKUbxnfFC3H",
+			"t2TB4mbSpuAcCWkH94Leb27FnRxo16AEHDg",
+			"t2Phx4gVL4YRnNsH3jM1M7jE4Fo329E66Na",
+			"t2VQZGmeNomN8c3USefeLL9nmU6M8x8CVzC",
+			"t2RicCvTVTY5y9JkreSRv3Xs8q2K67YxHLi",
+			"t2JrSLxTGc8wtPDe9hwbaeUjCrCfc4iZnDD",
+			"t2Uh9Au1PDDSw117sAbGivKREkmMxVC5tZo",
+			"t2FDwoJKLeEBMTy3oP7RLQ1Fihhvz49a3Bv",
+			"t2FY18mrgtb7QLeHA8ShnxLXuW8cNQ2n1v8",
+			"t2L15TkDYum7dnQRBqfvWdRe8Yw3jVy9z7g",
+	].into_iter().map(Into::into).collect()
+}
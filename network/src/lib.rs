@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate serde_derive;
 
 extern crate chain;
 extern crate primitives;
@@ -7,13 +9,25 @@ extern crate serialization;
 extern crate bitcrypto as crypto;
 extern crate keys;
 extern crate rustc_hex as hex;
+extern crate serde;
+extern crate serde_json;
 
+mod chain_spec;
 mod consensus;
 mod deployments;
+mod funding_streams;
+mod limits;
+mod magic;
 mod network;
+mod upgrade;
 
 pub use primitives::{hash, compact};
 
+pub use chain_spec::{ChainSpec, BaseNetwork};
 pub use consensus::ConsensusParams;
 pub use deployments::Deployment;
-pub use network::{Magic, Network};
+pub use funding_streams::{FundingStream, FundingStreamReceiver, FundingStreamOutput};
+pub use limits::{BlockLimits, MAX_BLOCK_SIZE, MAX_BLOCK_SIGOPS, MAX_TRANSACTION_SIZE_BEFORE_SAPLING};
+pub use magic::Magic;
+pub use network::Network;
+pub use upgrade::{NetworkUpgrade, UpgradeSchedule, consensus_branch_id};
@@ -0,0 +1,49 @@
+//! Limits of resources, consumed by blocks && transactions.
+
+/// Maximal serialized size (in bytes) of the block.
+pub const MAX_BLOCK_SIZE: usize = 2_000_000;
+/// Maximal number of signature operations in the block.
+pub const MAX_BLOCK_SIGOPS: usize = 20_000;
+/// Maximal serialized size (in bytes) of the transaction before Sapling activation.
+/// Since Sapling, transaction is only limited by the block size.
+pub const MAX_TRANSACTION_SIZE_BEFORE_SAPLING: usize = 100_000;
+
+/// Block-level limits. Used by both block verifier and block assembler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockLimits {
+	/// Maximal serialized size (in bytes) of the block.
+	pub max_block_size: usize,
+	/// Maximal number of signature operations in the block.
+	pub max_block_sigops: usize,
+}
+
+impl Default for BlockLimits {
+	fn default() -> Self {
+		BlockLimits {
+			max_block_size: MAX_BLOCK_SIZE,
+			max_block_sigops: MAX_BLOCK_SIGOPS,
+		}
+	}
+}
+
+impl BlockLimits {
+	/// Limits with block size capped at given size. The cap is ignored if it is above the current limit,
+	/// so that blocks, assembled with capped limits, are always valid.
+	pub fn with_max_block_size(self, max_block_size: usize) -> Self {
+		BlockLimits {
+			max_block_size: ::std::cmp::min(self.max_block_size, max_block_size),
+			..self
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{BlockLimits, MAX_BLOCK_SIZE};
+
+	#[test]
+	fn test_block_limits_with_max_block_size() {
+		assert_eq!(BlockLimits::default().with_max_block_size(1_000_000).max_block_size, 1_000_000);
+		assert_eq!(BlockLimits::default().with_max_block_size(MAX_BLOCK_SIZE + 1).max_block_size, MAX_BLOCK_SIZE);
+	}
+}
@@ -0,0 +1,85 @@
+//! Network magic: the 4 bytes that are prefixing every p2p message.
+
+use std::{fmt, io};
+use std::str::FromStr;
+use serialization::{Serializable, Deserializable, Stream, Reader, Error as ReaderError};
+
+/// Network magic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub struct Magic(pub u32);
+
+impl Magic {
+	/// Magic from its wire (little-endian) representation.
+	pub fn from_le_bytes(bytes: [u8; 4]) -> Magic {
+		Magic(bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24)
+	}
+
+	/// Wire (little-endian) representation of the magic.
+	pub fn to_le_bytes(&self) -> [u8; 4] {
+		[self.0 as u8, (self.0 >> 8) as u8, (self.0 >> 16) as u8, (self.0 >> 24) as u8]
+	}
+}
+
+impl fmt::Display for Magic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:08x}", self.0)
+	}
+}
+
+impl FromStr for Magic {
+	type Err = String;
+
+	/// Parses hex-encoded magic, optionally prefixed with `0x`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let hex = if s.starts_with("0x") { &s[2..] } else { s };
+		if hex.is_empty() || hex.len() > 8 {
+			return Err(format!("Invalid network magic: {}", s));
+		}
+
+		u32::from_str_radix(hex, 16)
+			.map(Magic)
+			.map_err(|_| format!("Invalid network magic: {}", s))
+	}
+}
+
+impl Serializable for Magic {
+	fn serialize(&self, stream: &mut Stream) {
+		stream.append(&self.0);
+	}
+
+	fn serialized_size(&self) -> usize {
+		4
+	}
+}
+
+impl Deserializable for Magic {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		reader.read().map(Magic)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serialization::{serialize, deserialize};
+	use super::Magic;
+
+	#[test]
+	fn magic_le_bytes() {
+		let magic = Magic::from_le_bytes([0x24, 0xe9, 0x27, 0x64]);
+		assert_eq!(magic, Magic(0x6427e924));
+		assert_eq!(magic.to_le_bytes(), [0x24, 0xe9, 0x27, 0x64]);
+		assert_eq!(serialize(&magic).as_ref(), &[0x24, 0xe9, 0x27, 0x64]);
+		assert_eq!(deserialize::<_, Magic>(&[0x24, 0xe9, 0x27, 0x64][..]).unwrap(), magic);
+	}
+
+	#[test]
+	fn magic_display_and_from_str() {
+		assert_eq!(Magic(0x6427e924).to_string(), "6427e924");
+		assert_eq!(Magic(0x1).to_string(), "00000001");
+		assert_eq!("6427e924".parse::<Magic>(), Ok(Magic(0x6427e924)));
+		assert_eq!("0x6427e924".parse::<Magic>(), Ok(Magic(0x6427e924)));
+		assert!("".parse::<Magic>().is_err());
+		assert!("6427e924ff".parse::<Magic>().is_err());
+		assert!("xyz".parse::<Magic>().is_err());
+	}
+}
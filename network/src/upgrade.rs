@@ -0,0 +1,190 @@
+//! Zcash network upgrades.
+//! https://zcash.readthedocs.io/en/latest/rtd_pages/nu_dev_guide.html
+
+use {Network, ConsensusParams};
+
+/// Zcash network upgrade.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum NetworkUpgrade {
+	/// Consensus rules at the genesis block.
+	Sprout,
+	/// https://zips.z.cash/zip-0201
+	Overwinter,
+	/// https://zips.z.cash/zip-0205
+	Sapling,
+	/// https://zips.z.cash/zip-0206
+	Blossom,
+	/// https://zips.z.cash/zip-0250
+	Heartwood,
+	/// https://zips.z.cash/zip-0251
+	Canopy,
+	/// https://zips.z.cash/zip-0252
+	Nu5,
+}
+
+/// All network upgrades, in activation order.
+const NETWORK_UPGRADES: [NetworkUpgrade; 7] = [
+	NetworkUpgrade::Sprout,
+	NetworkUpgrade::Overwinter,
+	NetworkUpgrade::Sapling,
+	NetworkUpgrade::Blossom,
+	NetworkUpgrade::Heartwood,
+	NetworkUpgrade::Canopy,
+	NetworkUpgrade::Nu5,
+];
+
+/// Activation height of upgrade that is never activated.
+const NEVER: u32 = ::std::u32::MAX;
+
+impl NetworkUpgrade {
+	/// All network upgrades, in activation order.
+	pub fn all() -> &'static [NetworkUpgrade] {
+		&NETWORK_UPGRADES
+	}
+
+	/// Human-readable name of the upgrade.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			NetworkUpgrade::Sprout => "Sprout",
+			NetworkUpgrade::Overwinter => "Overwinter",
+			NetworkUpgrade::Sapling => "Sapling",
+			NetworkUpgrade::Blossom => "Blossom",
+			NetworkUpgrade::Heartwood => "Heartwood",
+			NetworkUpgrade::Canopy => "Canopy",
+			NetworkUpgrade::Nu5 => "NU5",
+		}
+	}
+
+	/// Consensus branch id of the upgrade.
+	/// https://zips.z.cash/zip-0200
+	pub fn branch_id(&self) -> u32 {
+		match *self {
+			NetworkUpgrade::Sprout => 0,
+			NetworkUpgrade::Overwinter => 0x5ba81b19,
+			NetworkUpgrade::Sapling => 0x76b809bb,
+			NetworkUpgrade::Blossom => 0x2bb40e60,
+			NetworkUpgrade::Heartwood => 0xf5b9230b,
+			NetworkUpgrade::Canopy => 0xe9ff75a6,
+			NetworkUpgrade::Nu5 => 0xc2d6d0b4,
+		}
+	}
+
+	fn index(&self) -> usize {
+		*self as usize
+	}
+}
+
+/// Activation heights of network upgrades.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpgradeSchedule {
+	/// Activation height of every upgrade, indexed by upgrade. `NEVER` if upgrade isn't scheduled.
+	heights: [u32; 7],
+}
+
+impl UpgradeSchedule {
+	/// Upgrade schedule of given network.
+	pub fn new(network: Network) -> Self {
+		let heights = match network {
+			Network::Custom(spec) => return UpgradeSchedule::new(spec.base_network()),
+			Network::Mainnet => [0, 347500, 419200, 653600, 903000, 1046400, 1687104],
+			Network::Testnet => [0, 207500, 280000, 584000, 903800, 1028500, 1842420],
+			Network::Regtest | Network::Unitest => [0, NEVER, NEVER, NEVER, NEVER, NEVER, NEVER],
+		};
+
+		UpgradeSchedule {
+			heights: heights,
+		}
+	}
+
+	/// Activation height of given upgrade. None if upgrade isn't scheduled.
+	pub fn activation_height(&self, upgrade: NetworkUpgrade) -> Option<u32> {
+		match self.heights[upgrade.index()] {
+			NEVER => None,
+			height => Some(height),
+		}
+	}
+
+	/// Returns true if given upgrade is active at given height.
+	pub fn is_active(&self, upgrade: NetworkUpgrade, height: u32) -> bool {
+		height >= self.heights[upgrade.index()]
+	}
+
+	/// Latest network upgrade that is active at given height.
+	pub fn active_at(&self, height: u32) -> NetworkUpgrade {
+		NetworkUpgrade::all().iter()
+			.rev()
+			.cloned()
+			.find(|upgrade| self.is_active(*upgrade, height))
+			.unwrap_or(NetworkUpgrade::Sprout)
+	}
+
+	/// Upgrades (excluding Sprout) that are scheduled, with their activation heights.
+	pub fn scheduled(&self) -> Vec<(NetworkUpgrade, u32)> {
+		NetworkUpgrade::all().iter()
+			.skip(1)
+			.filter_map(|upgrade| self.activation_height(*upgrade).map(|height| (*upgrade, height)))
+			.collect()
+	}
+
+	/// Override activation height of given upgrade. None unschedules the upgrade.
+	pub fn set_activation_height(&mut self, upgrade: NetworkUpgrade, height: Option<u32>) {
+		if upgrade != NetworkUpgrade::Sprout {
+			self.heights[upgrade.index()] = height.unwrap_or(NEVER);
+		}
+	}
+}
+
+/// Consensus branch id of the network upgrade that is active at given height of given network.
+/// This is the branch id that must be used for signature hashing of transactions from the block at this height.
+pub fn consensus_branch_id(height: u32, network: Network) -> u32 {
+	ConsensusParams::new(network).consensus_branch_id(height)
+}
+
+#[cfg(test)]
+mod tests {
+	use Network;
+	use super::{NetworkUpgrade, UpgradeSchedule, consensus_branch_id};
+
+	#[test]
+	fn test_upgrade_schedule_active_at() {
+		let schedule = UpgradeSchedule::new(Network::Mainnet);
+		assert_eq!(schedule.active_at(0), NetworkUpgrade::Sprout);
+		assert_eq!(schedule.active_at(347499), NetworkUpgrade::Sprout);
+		assert_eq!(schedule.active_at(347500), NetworkUpgrade::Overwinter);
+		assert_eq!(schedule.active_at(419200), NetworkUpgrade::Sapling);
+		assert_eq!(schedule.active_at(653600), NetworkUpgrade::Blossom);
+		assert_eq!(schedule.active_at(903000), NetworkUpgrade::Heartwood);
+		assert_eq!(schedule.active_at(1046400), NetworkUpgrade::Canopy);
+		assert_eq!(schedule.active_at(1687104), NetworkUpgrade::Nu5);
+		assert_eq!(schedule.active_at(::std::u32::MAX), NetworkUpgrade::Nu5);
+
+		let schedule = UpgradeSchedule::new(Network::Testnet);
+		assert_eq!(schedule.active_at(280000), NetworkUpgrade::Sapling);
+		assert_eq!(schedule.active_at(1842419), NetworkUpgrade::Canopy);
+
+		let schedule = UpgradeSchedule::new(Network::Regtest);
+		assert_eq!(schedule.active_at(::std::u32::MAX), NetworkUpgrade::Sprout);
+	}
+
+	#[test]
+	fn test_consensus_branch_id() {
+		assert_eq!(consensus_branch_id(0, Network::Mainnet), 0);
+		assert_eq!(consensus_branch_id(653600, Network::Mainnet), 0x2bb40e60);
+		assert_eq!(consensus_branch_id(280000, Network::Testnet), 0x76b809bb);
+		assert_eq!(consensus_branch_id(280000, Network::Regtest), 0);
+	}
+
+	#[test]
+	fn test_upgrade_schedule_scheduled() {
+		assert_eq!(UpgradeSchedule::new(Network::Regtest).scheduled(), vec![]);
+		assert_eq!(UpgradeSchedule::new(Network::Mainnet).scheduled().len(), 6);
+
+		let mut schedule = UpgradeSchedule::new(Network::Regtest);
+		schedule.set_activation_height(NetworkUpgrade::Overwinter, Some(10));
+		assert_eq!(schedule.scheduled(), vec![(NetworkUpgrade::Overwinter, 10)]);
+		assert!(!schedule.is_active(NetworkUpgrade::Overwinter, 9));
+		assert!(schedule.is_active(NetworkUpgrade::Overwinter, 10));
+		assert!(!schedule.is_active(NetworkUpgrade::Sapling, 10));
+		assert!(schedule.is_active(NetworkUpgrade::Sprout, 0));
+	}
+}
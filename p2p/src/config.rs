@@ -1,16 +1,24 @@
 use std::{net, path};
 use message::common::Services;
 use net::Config as NetConfig;
-use util::InternetProtocol;
+use util::{InternetProtocol, MisbehaviourPolicy};
 
 #[derive(Debug, Clone)]
 pub struct Config {
 	/// Number of threads used by p2p thread pool.
 	pub threads: usize,
+	/// Accept inbound connections and advertise known addresses to peers.
+	pub listen: bool,
 	/// Number of inbound connections.
 	pub inbound_connections: u32,
 	/// Number of outbound connections.
 	pub outbound_connections: u32,
+	/// Maximum number of concurrent inbound connections from single IP address.
+	pub inbound_connections_per_ip: u32,
+	/// Maximum number of inbound connection attempts from single subnet per minute.
+	pub inbound_attempts_per_subnet: u32,
+	/// Inbound connections from these addresses are never throttled.
+	pub whitelist: Vec<net::IpAddr>,
 	/// Configuration for every connection.
 	pub connection: NetConfig,
 	/// Connect only to these nodes.
@@ -23,4 +31,15 @@ pub struct Config {
 	pub preferable_services: Services,
 	/// Internet protocol.
 	pub internet_protocol: InternetProtocol,
+	/// Penalties for peers misbehaviour.
+	pub misbehaviour: MisbehaviourPolicy,
+	/// Network policy file, which overrides `misbehaviour` and `whitelist` and could be reloaded at runtime.
+	pub policy_path: Option<path::PathBuf>,
+	/// Record messages, received by sync sessions, to this file.
+	pub record_path: Option<path::PathBuf>,
+	/// Crawl the network: instead of keeping outbound connections, probe every known address
+	/// with the version handshake and record reachable nodes.
+	pub crawler: bool,
+	/// Periodically dump nodes, found by crawler, to this file.
+	pub crawler_dump_path: Option<path::PathBuf>,
 }
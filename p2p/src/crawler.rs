@@ -0,0 +1,214 @@
+//! Crawler mode: every known address is probed with the version handshake, and reachable nodes
+//! are recorded, so that the node could serve as a network seeder.
+
+use std::{fs, io};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+use message::common::Services;
+use message::types;
+use util::time::{Time, RealTime};
+
+/// Reachable nodes are probed again after this number of seconds.
+const RECRAWL_INTERVAL: i64 = 60 * 60;
+/// Unreachable nodes are probed again after this number of seconds.
+const RETRY_INTERVAL: i64 = 4 * 60 * 60;
+
+/// Results of probing single address.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CrawledNode {
+	pub address: SocketAddr,
+	/// Services from the last successful handshake.
+	pub services: Services,
+	/// Protocol version from the last successful handshake.
+	pub version: u32,
+	pub user_agent: String,
+	/// Best block height, announced in the last successful handshake.
+	pub start_height: i32,
+	/// Time of the last handshake attempt.
+	pub last_attempt: i64,
+	/// Time of the last successful handshake (0 if there were none).
+	pub last_success: i64,
+	pub attempts: u32,
+	pub successes: u32,
+}
+
+impl CrawledNode {
+	fn new(address: SocketAddr) -> Self {
+		CrawledNode {
+			address: address,
+			services: Services::default(),
+			version: 0,
+			user_agent: String::new(),
+			start_height: 0,
+			last_attempt: 0,
+			last_success: 0,
+			attempts: 0,
+			successes: 0,
+		}
+	}
+
+	/// True if the last handshake with the node has succeeded.
+	pub fn is_reachable(&self) -> bool {
+		self.successes != 0 && self.last_success >= self.last_attempt
+	}
+}
+
+/// Table of probed addresses.
+#[derive(Default, Debug)]
+pub struct Crawler<T = RealTime> where T: Time {
+	/// Time source.
+	time: T,
+	/// Probed nodes by address.
+	nodes: HashMap<SocketAddr, CrawledNode>,
+}
+
+impl<T> Crawler<T> where T: Time {
+	/// Returns up to `limit` addresses to probe next. Addresses that have never been probed go
+	/// first, followed by addresses that were probed longest time ago. Recently probed addresses
+	/// are skipped.
+	pub fn next_addresses<I>(&self, known: I, except: &HashSet<SocketAddr>, limit: usize) -> Vec<SocketAddr> where I: IntoIterator<Item=SocketAddr> {
+		let now = self.time.get().sec;
+		let mut candidates = known.into_iter()
+			.filter(|address| !except.contains(address))
+			.filter_map(|address| match self.nodes.get(&address) {
+				None => Some((None, address)),
+				Some(node) => {
+					let interval = if node.is_reachable() { RECRAWL_INTERVAL } else { RETRY_INTERVAL };
+					if node.last_attempt + interval <= now {
+						Some((Some(node.last_attempt), address))
+					} else {
+						None
+					}
+				},
+			})
+			.collect::<Vec<_>>();
+		candidates.sort();
+		candidates.into_iter().map(|(_, address)| address).take(limit).collect()
+	}
+
+	/// Notes that handshake with the node has been started.
+	pub fn note_attempt(&mut self, address: SocketAddr) {
+		let now = self.time.get().sec;
+		let node = self.nodes.entry(address).or_insert_with(|| CrawledNode::new(address));
+		node.last_attempt = now;
+		node.attempts += 1;
+	}
+
+	/// Notes that handshake with the node has succeeded.
+	pub fn note_success(&mut self, address: SocketAddr, version_message: &types::Version) {
+		let now = self.time.get().sec;
+		let node = self.nodes.entry(address).or_insert_with(|| CrawledNode::new(address));
+		node.services = version_message.services();
+		node.version = version_message.version();
+		node.user_agent = version_message.user_agent().unwrap_or_default();
+		node.start_height = version_message.start_height().unwrap_or(0);
+		node.last_success = now;
+		node.successes += 1;
+	}
+
+	/// Returns all probed nodes, sorted by address.
+	pub fn nodes(&self) -> Vec<CrawledNode> {
+		let mut nodes = self.nodes.values().cloned().collect::<Vec<_>>();
+		nodes.sort_by_key(|node| node.address);
+		nodes
+	}
+
+	/// Writes probed nodes in the format of `dnsseed.dump` file of DNS seeders: one node per line,
+	/// reachable nodes first.
+	pub fn save<W>(&self, mut write: W) -> io::Result<()> where W: io::Write {
+		let mut nodes = self.nodes();
+		nodes.sort_by_key(|node| !node.is_reachable());
+
+		writeln!(write, "# address good lastSuccess attempts successes blocks svcs version \"useragent\"")?;
+		for node in nodes {
+			writeln!(write, "{} {} {} {} {} {} {:016x} {} \"{}\"",
+				node.address,
+				node.is_reachable() as u8,
+				node.last_success,
+				node.attempts,
+				node.successes,
+				node.start_height,
+				u64::from(node.services),
+				node.version,
+				node.user_agent.replace('"', ""),
+			)?;
+		}
+		Ok(())
+	}
+
+	pub fn save_to_file<P>(&self, path: P) -> io::Result<()> where P: AsRef<Path> {
+		let mut file = io::BufWriter::new(fs::File::create(path)?);
+		self.save(&mut file)?;
+		file.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use std::net::SocketAddr;
+	use message::common::Services;
+	use message::types;
+	use util::time::IncrementalTime;
+	use super::{Crawler, RECRAWL_INTERVAL};
+
+	fn version_message(start_height: i32) -> types::Version {
+		types::Version::V106(types::version::V0 {
+			version: 170100,
+			services: Services::default().with_network(true),
+			..Default::default()
+		}, types::version::V106 {
+			from: Default::default(),
+			nonce: 0,
+			user_agent: "/MagicBean:5.0.0/".into(),
+			start_height: start_height,
+		})
+	}
+
+	#[test]
+	fn test_crawler_next_addresses() {
+		let s0: SocketAddr = "127.0.0.1:8233".parse().unwrap();
+		let s1: SocketAddr = "127.0.0.2:8233".parse().unwrap();
+		let s2: SocketAddr = "127.0.0.3:8233".parse().unwrap();
+		let mut crawler = Crawler::<IncrementalTime>::default();
+
+		crawler.note_attempt(s0);
+		crawler.note_success(s0, &version_message(100));
+		crawler.note_attempt(s1);
+
+		// never probed addresses first, recently probed are skipped
+		assert_eq!(crawler.next_addresses(vec![s0, s1, s2], &HashSet::new(), 10), vec![s2]);
+		assert_eq!(crawler.next_addresses(vec![s0, s1, s2], &vec![s2].into_iter().collect(), 10), vec![]);
+
+		// reachable node is probed again after recrawl interval
+		for _ in 0..RECRAWL_INTERVAL {
+			crawler.time.get();
+		}
+		assert_eq!(crawler.next_addresses(vec![s0, s1, s2], &HashSet::new(), 10), vec![s2, s0]);
+		assert_eq!(crawler.next_addresses(vec![s0, s1, s2], &HashSet::new(), 1), vec![s2]);
+	}
+
+	#[test]
+	fn test_crawler_save() {
+		let s0: SocketAddr = "127.0.0.1:8233".parse().unwrap();
+		let s1: SocketAddr = "127.0.0.2:8233".parse().unwrap();
+		let mut crawler = Crawler::<IncrementalTime>::default();
+		crawler.note_attempt(s1);
+		crawler.note_attempt(s0);
+		crawler.note_success(s0, &version_message(100));
+
+		let nodes = crawler.nodes();
+		assert!(nodes[0].is_reachable());
+		assert!(!nodes[1].is_reachable());
+
+		let mut dump = Vec::new();
+		crawler.save(&mut dump).unwrap();
+		assert_eq!(String::from_utf8(dump).unwrap(), "\
+# address good lastSuccess attempts successes blocks svcs version \"useragent\"
+127.0.0.1:8233 1 2 1 1 100 0000000000000001 170100 \"/MagicBean:5.0.0/\"
+127.0.0.2:8233 0 0 1 0 0 0000000000000000 0 \"\"
+");
+	}
+}
@@ -26,6 +26,9 @@ mod util;
 mod config;
 mod event_loop;
 mod p2p;
+mod recorder;
+mod crawler;
+mod policy;
 
 pub use primitives::{hash, bytes};
 
@@ -33,10 +36,15 @@ pub use config::Config;
 pub use net::Config as NetConfig;
 pub use p2p::{P2P, Context};
 pub use event_loop::{event_loop, forever};
-pub use util::{NodeTableError, PeerId, PeerInfo, InternetProtocol, Direction};
+pub use util::{NodeTableError, PeerId, PeerInfo, InternetProtocol, Direction, ConnectionType, Misbehaviour, MisbehaviourPolicy};
+pub use net::PeerStats;
 pub use protocol::{
 	InboundSyncConnection, InboundSyncConnectionRef,
 	InboundSyncConnectionState, InboundSyncConnectionStateRef,
 	OutboundSyncConnection, OutboundSyncConnectionRef,
 	LocalSyncNode, LocalSyncNodeRef,
+	dispatch_sync_message,
 };
+pub use recorder::{MessageRecorder, RecordedEvent, read_recording};
+pub use crawler::CrawledNode;
+pub use policy::NetworkPolicy;
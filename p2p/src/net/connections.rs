@@ -6,7 +6,8 @@ use parking_lot::RwLock;
 use net::{Connection, Channel};
 use p2p::Context;
 use session::{SessionFactory};
-use util::{Direction, PeerInfo};
+use util::{ConnectionType, PeerInfo};
+use net::PeerStats;
 use PeerId;
 
 const SYNCHRONOUS_RESPONSES: bool = true;
@@ -40,6 +41,11 @@ impl Connections {
 		self.channels().values().map(|channel| channel.peer_info()).collect()
 	}
 
+	/// Returns info and traffic statistics of every peer.
+	pub fn info_with_stats(&self) -> Vec<(PeerInfo, PeerStats)> {
+		self.channels().values().map(|channel| (channel.peer_info(), channel.session().stats().lock().clone())).collect()
+	}
+
 	/// Returns number of connections.
 	pub fn count(&self) -> usize {
 		self.channels.read().len()
@@ -47,14 +53,15 @@ impl Connections {
 
 	/// Stores new channel.
 	/// Returns a shared pointer to it.
-	pub fn store<T>(&self, context: Arc<Context>, connection: Connection, direction: Direction) -> Arc<Channel> where T: SessionFactory {
+	pub fn store<T>(&self, context: Arc<Context>, connection: Connection, connection_type: ConnectionType) -> Arc<Channel> where T: SessionFactory {
 		let id = self.peer_counter.fetch_add(1, Ordering::AcqRel);
 
 		let peer_info = PeerInfo {
 			id: id,
 			address: connection.address,
 			user_agent: connection.version_message.user_agent().unwrap_or("unknown".into()),
-			direction: direction,
+			direction: connection_type.direction(),
+			connection_type: connection_type,
 			version: connection.version,
 			version_message: connection.version_message,
 			magic: connection.magic,
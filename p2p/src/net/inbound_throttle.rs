@@ -0,0 +1,160 @@
+use std::net::IpAddr;
+use std::collections::{HashMap, HashSet};
+use parking_lot::{Mutex, RwLock};
+
+/// Length of the connection attempts rate window (in seconds).
+const ATTEMPTS_WINDOW: f64 = 60.0;
+
+/// Limits number of concurrent inbound connections from single IP address
+/// and rate of inbound connection attempts from single subnet.
+pub struct InboundThrottle {
+	/// Maximum number of concurrent inbound connections from single IP address.
+	max_connections_per_ip: u32,
+	/// Maximum number of inbound connection attempts from single subnet per minute.
+	max_attempts_per_subnet: u32,
+	/// Addresses that are never throttled.
+	whitelist: RwLock<HashSet<IpAddr>>,
+	/// Throttle state.
+	state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+	/// Number of open inbound connections by IP address.
+	connections: HashMap<IpAddr, u32>,
+	/// Start of the current window && number of connection attempts within this window by subnet.
+	attempts: HashMap<IpAddr, (f64, u32)>,
+}
+
+impl InboundThrottle {
+	pub fn new(max_connections_per_ip: u32, max_attempts_per_subnet: u32, whitelist: Vec<IpAddr>) -> Self {
+		InboundThrottle {
+			max_connections_per_ip: max_connections_per_ip,
+			max_attempts_per_subnet: max_attempts_per_subnet,
+			whitelist: RwLock::new(whitelist.into_iter().collect()),
+			state: Mutex::default(),
+		}
+	}
+
+	/// Notes new inbound connection attempt.
+	/// Returns false if connection must be rejected. Rejected connections must not be closed with
+	/// `note_close_inbound_connection`.
+	pub fn note_new_inbound_connection(&self, ip: IpAddr, now: f64) -> bool {
+		if self.whitelist.read().contains(&ip) {
+			return true;
+		}
+
+		let mut state = self.state.lock();
+
+		let attempts = state.attempts.entry(subnet(ip)).or_insert((now, 0));
+		if now - attempts.0 >= ATTEMPTS_WINDOW {
+			*attempts = (now, 0);
+		}
+		attempts.1 += 1;
+		if attempts.1 > self.max_attempts_per_subnet {
+			trace!("Throttling inbound connection from {}: too many connection attempts", ip);
+			return false;
+		}
+
+		let connections = state.connections.entry(ip).or_insert(0);
+		if *connections >= self.max_connections_per_ip {
+			trace!("Throttling inbound connection from {}: too many connections", ip);
+			return false;
+		}
+		*connections += 1;
+
+		true
+	}
+
+	/// Notes that accepted inbound connection has been closed.
+	/// Whitelist isn't checked here, since it could have changed after the connection was accepted.
+	pub fn note_close_inbound_connection(&self, ip: IpAddr) {
+		let mut state = self.state.lock();
+		let remove = match state.connections.get_mut(&ip) {
+			Some(connections) => {
+				*connections -= 1;
+				*connections == 0
+			},
+			None => false,
+		};
+		if remove {
+			state.connections.remove(&ip);
+		}
+	}
+
+	/// Replaces addresses that are never throttled.
+	pub fn set_whitelist(&self, whitelist: Vec<IpAddr>) {
+		*self.whitelist.write() = whitelist.into_iter().collect();
+	}
+
+	/// Forgets about connection attempts that are out of current rate window.
+	pub fn prune(&self, now: f64) {
+		self.state.lock().attempts.retain(|_, &mut (window_start, _)| now - window_start < ATTEMPTS_WINDOW);
+	}
+}
+
+/// Returns subnet (/24 for IPv4, /64 for IPv6) the address belongs to.
+fn subnet(ip: IpAddr) -> IpAddr {
+	match ip {
+		IpAddr::V4(ip) => {
+			let o = ip.octets();
+			IpAddr::V4([o[0], o[1], o[2], 0].into())
+		},
+		IpAddr::V6(ip) => {
+			let s = ip.segments();
+			IpAddr::V6([s[0], s[1], s[2], s[3], 0, 0, 0, 0].into())
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::InboundThrottle;
+
+	#[test]
+	fn test_inbound_throttle_connections_per_ip() {
+		let throttle = InboundThrottle::new(2, 100, vec![]);
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(!throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(throttle.note_new_inbound_connection("1.2.3.5".parse().unwrap(), 0.0));
+		throttle.note_close_inbound_connection("1.2.3.4".parse().unwrap());
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+	}
+
+	#[test]
+	fn test_inbound_throttle_attempts_per_subnet() {
+		let throttle = InboundThrottle::new(100, 2, vec![]);
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(throttle.note_new_inbound_connection("1.2.3.5".parse().unwrap(), 10.0));
+		assert!(!throttle.note_new_inbound_connection("1.2.3.6".parse().unwrap(), 20.0));
+		assert!(throttle.note_new_inbound_connection("1.2.4.4".parse().unwrap(), 20.0));
+		// next window
+		assert!(throttle.note_new_inbound_connection("1.2.3.6".parse().unwrap(), 60.0));
+	}
+
+	#[test]
+	fn test_inbound_throttle_whitelist() {
+		let throttle = InboundThrottle::new(1, 1, vec!["1.2.3.4".parse().unwrap()]);
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(throttle.note_new_inbound_connection("1.2.3.5".parse().unwrap(), 0.0));
+		assert!(!throttle.note_new_inbound_connection("1.2.3.6".parse().unwrap(), 0.0));
+	}
+
+	#[test]
+	fn test_inbound_throttle_set_whitelist() {
+		let throttle = InboundThrottle::new(1, 100, vec![]);
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+		assert!(!throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+
+		throttle.set_whitelist(vec!["1.2.3.4".parse().unwrap()]);
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+
+		// connection, accepted before the address has been whitelisted, is still forgotten
+		throttle.note_close_inbound_connection("1.2.3.4".parse().unwrap());
+		throttle.set_whitelist(vec![]);
+		assert!(throttle.note_new_inbound_connection("1.2.3.4".parse().unwrap(), 0.0));
+	}
+}
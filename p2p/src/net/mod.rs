@@ -5,6 +5,7 @@ mod connect;
 mod connection;
 mod connection_counter;
 mod connections;
+mod inbound_throttle;
 mod peer_context;
 mod stats;
 
@@ -15,5 +16,6 @@ pub use self::connect::{Connect, connect};
 pub use self::connection::Connection;
 pub use self::connection_counter::ConnectionCounter;
 pub use self::connections::Connections;
+pub use self::inbound_throttle::InboundThrottle;
 pub use self::peer_context::PeerContext;
 pub use self::stats::PeerStats;
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use util::interval::{Interval, RealInterval};
 
 use message::{Command, Payload};
-use message::types::{Ping, Pong};
+use message::types::{Ping, Pong, Addr, GetAddr};
 
 // delay somewhere near communication timeout
 const ENORMOUS_PING_DELAY: f64 = 10f64;
@@ -128,6 +128,14 @@ impl<I: Interval> PeerStats<I> {
 		}
 	}
 
+	/// True if addresses have been requested from the peer, or the peer has requested or announced addresses.
+	pub fn is_addr_relay_active(&self) -> bool {
+		let getaddr = Command::from(GetAddr::command());
+		self.send_avg.contains_key(&getaddr) ||
+			self.recv_avg.contains_key(&getaddr) ||
+			self.recv_avg.contains_key(&Command::from(Addr::command()))
+	}
+
 	pub fn avg<T>(&self, dir: Flow, cmd: T) -> u64
 		where T: Into<Command>
 	{
@@ -189,4 +197,19 @@ mod tests {
 
 		assert_eq!(stats.avg(Flow::Receive, "inv"), 2500);
 	}
+
+	#[test]
+	fn addr_relay() {
+		let mut stats = PeerStats::<RealInterval>::default();
+		stats.report_recv("inv".into(), 100);
+		stats.report_send("addr".into(), 100);
+		assert!(!stats.is_addr_relay_active());
+
+		stats.report_recv("addr".into(), 100);
+		assert!(stats.is_addr_relay_active());
+
+		let mut stats = PeerStats::<RealInterval>::default();
+		stats.report_send("getaddr".into(), 0);
+		assert!(stats.is_addr_relay_active());
+	}
 }
@@ -13,12 +13,15 @@ use ns_dns_tokio::DnsResolver;
 use message::{Payload, MessageResult, Message};
 use message::common::Services;
 use message::types::addr::AddressEntry;
-use net::{connect, Connections, Channel, Config as NetConfig, accept_connection, ConnectionCounter};
-use util::{NodeTable, Node, NodeTableError, Direction};
-use session::{SessionFactory, SeednodeSessionFactory, NormalSessionFactory};
+use net::{connect, Connections, Channel, Config as NetConfig, accept_connection, ConnectionCounter, InboundThrottle};
+use util::{NodeTable, Node, NodeTableError, Direction, ConnectionType, Misbehaviour, MisbehaviourPolicy};
+use session::{SessionFactory, SeednodeSessionFactory, NormalSessionFactory, CrawlerSessionFactory};
 use {Config, PeerId};
 use protocol::{LocalSyncNodeRef, InboundSyncConnectionRef, OutboundSyncConnectionRef};
 use io::DeadlineStatus;
+use recorder::MessageRecorder;
+use crawler::{Crawler, CrawledNode};
+use policy::NetworkPolicy;
 
 pub type BoxedEmptyFuture = Box<Future<Item=(), Error=()> + Send>;
 
@@ -28,6 +31,8 @@ pub struct Context {
 	connections: Connections,
 	/// Connection counter.
 	connection_counter: ConnectionCounter,
+	/// Inbound connections throttle.
+	inbound_throttle: InboundThrottle,
 	/// Node Table.
 	node_table: RwLock<NodeTable>,
 	/// Thread pool handle.
@@ -36,6 +41,12 @@ pub struct Context {
 	remote: Remote,
 	/// Local synchronization node.
 	local_sync_node: LocalSyncNodeRef,
+	/// Recorder of received sync messages.
+	recorder: Option<MessageRecorder>,
+	/// Results of probing known addresses (only in crawler mode).
+	crawler: Option<RwLock<Crawler>>,
+	/// Penalties for peers misbehaviour (could be changed at runtime).
+	misbehaviour: RwLock<MisbehaviourPolicy>,
 	/// Node table path.
 	config: Config,
 }
@@ -43,16 +54,29 @@ pub struct Context {
 impl Context {
 	/// Creates new context with reference to local sync node, thread pool and event loop.
 	pub fn new(local_sync_node: LocalSyncNodeRef, pool_handle: CpuPool, remote: Remote, config: Config) -> Result<Self, Box<error::Error>> {
+		let recorder = match config.record_path {
+			Some(ref path) => Some(try!(MessageRecorder::create(path, config.connection.magic))),
+			None => None,
+		};
+
 		let context = Context {
 			connections: Default::default(),
 			connection_counter: ConnectionCounter::new(config.inbound_connections, config.outbound_connections),
+			inbound_throttle: InboundThrottle::new(config.inbound_connections_per_ip, config.inbound_attempts_per_subnet, config.whitelist.clone()),
 			node_table: RwLock::new(try!(NodeTable::from_file(config.preferable_services, &config.node_table_path))),
 			pool: pool_handle,
 			remote: remote,
 			local_sync_node: local_sync_node,
+			recorder: recorder,
+			crawler: if config.crawler { Some(RwLock::new(Crawler::default())) } else { None },
+			misbehaviour: RwLock::new(config.misbehaviour.clone()),
 			config: config,
 		};
 
+		if context.config.policy_path.is_some() {
+			try!(context.reload_policy());
+		}
+
 		Ok(context)
 	}
 
@@ -80,6 +104,11 @@ impl Context {
 	}
 
 	/// Returns addresses of recently active nodes. Sorted and limited to 1000.
+	/// Returns true if node accepts inbound connections and advertises known addresses to peers.
+	pub fn is_listening(&self) -> bool {
+		self.config.listen
+	}
+
 	pub fn node_table_entries(&self) -> Vec<Node> {
 		self.node_table.read().recently_active_nodes(self.config.internet_protocol)
 	}
@@ -90,10 +119,30 @@ impl Context {
 		self.node_table.write().insert_many(nodes);
 	}
 
-	/// Penalize node.
-	pub fn penalize_node(&self, addr: &SocketAddr) {
-		trace!("Penalizing node {}", addr);
-		self.node_table.write().note_failure(addr);
+	/// Penalize node for misbehaviour.
+	pub fn penalize_node(&self, addr: &SocketAddr, misbehaviour: Misbehaviour) {
+		trace!("Penalizing node {} for {:?} misbehaviour", addr, misbehaviour);
+		self.node_table.write().note_misbehaviour(addr, self.misbehaviour.read().penalty(misbehaviour));
+	}
+
+	/// Re-reads network policy from the policy file and applies it. Settings that are missing from
+	/// the file are reset to values from the node configuration.
+	pub fn reload_policy(&self) -> Result<(), String> {
+		let path = self.config.policy_path.as_ref().ok_or_else(|| "Policy file is not configured".to_owned())?;
+		let defaults = NetworkPolicy {
+			misbehaviour: self.config.misbehaviour.clone(),
+			whitelist: self.config.whitelist.clone(),
+		};
+		let policy = NetworkPolicy::from_file(path, &defaults)?;
+		info!("Applying network policy from {}: {:?}", path.display(), policy);
+		*self.misbehaviour.write() = policy.misbehaviour;
+		self.inbound_throttle.set_whitelist(policy.whitelist);
+		Ok(())
+	}
+
+	/// Notes that node has delivered requested block.
+	pub fn note_block_delivered(&self, addr: &SocketAddr) {
+		self.node_table.write().note_block_delivered(addr);
 	}
 
 	/// Adds node to table.
@@ -125,9 +174,16 @@ impl Context {
 					channel.session().maintain();
 				}
 
+				context.inbound_throttle.prune(::time::precise_time_s());
+				context.node_table.write().decay_misbehaviour(context.misbehaviour.read().decay_interval);
+
 				let needed = context.connection_counter.outbound_connections_needed() as usize;
-				if needed != 0 {
-					let used_addresses = context.connections.addresses();
+				if needed != 0 && context.crawler.is_some() {
+					Context::crawl(context.clone(), needed);
+				} else if needed != 0 {
+					// never connect to banned nodes
+					let mut used_addresses = context.connections.addresses();
+					used_addresses.extend(context.node_table.read().banned_nodes(context.misbehaviour.read().ban_score));
 					let peers = context.node_table.read().nodes_with_services(&Services::default(), context.config.internet_protocol, &used_addresses, needed);
 					let addresses = peers.into_iter()
 						.map(|peer| peer.address())
@@ -135,7 +191,7 @@ impl Context {
 
 					trace!("Creating {} more outbound connections", addresses.len());
 					for address in addresses {
-						Context::connect::<NormalSessionFactory>(context.clone(), address);
+						Context::connect::<NormalSessionFactory>(context.clone(), address, ConnectionType::OutboundFullRelay);
 					}
 				}
 
@@ -143,6 +199,12 @@ impl Context {
 					error!("Saving node table to disk failed");
 				}
 
+				if let (Some(crawler), Some(path)) = (context.crawler.as_ref(), context.config.crawler_dump_path.as_ref()) {
+					if let Err(err) = crawler.read().save_to_file(path) {
+						error!("Saving crawler dump to {} failed: {}", path.display(), err);
+					}
+				}
+
 				Ok(())
 			})
 			.for_each(|_| Ok(()))
@@ -150,8 +212,25 @@ impl Context {
 		c.spawn(interval);
 	}
 
+	/// Opens up to `needed` crawler connections to the known addresses that haven't been probed recently.
+	fn crawl(context: Arc<Context>, needed: usize) {
+		let crawler = context.crawler.as_ref().expect("crawl is only called in crawler mode; qed");
+		let mut except = context.connections.addresses();
+		except.extend(context.node_table.read().banned_nodes(context.misbehaviour.read().ban_score));
+		let known = context.node_table.read().nodes().into_iter()
+			.map(|node| node.address())
+			.filter(|address| context.config.internet_protocol.is_allowed(address))
+			.collect::<Vec<_>>();
+		let addresses = crawler.read().next_addresses(known, &except, needed);
+
+		trace!("Probing {} addresses", addresses.len());
+		for address in addresses {
+			Context::connect::<CrawlerSessionFactory>(context.clone(), address, ConnectionType::AddrFetch);
+		}
+	}
+
 	/// Connect to socket using given context and handle.
-	fn connect_future<T>(context: Arc<Context>, socket: net::SocketAddr, handle: &Handle, config: &NetConfig) -> BoxedEmptyFuture where T: SessionFactory {
+	fn connect_future<T>(context: Arc<Context>, socket: net::SocketAddr, connection_type: ConnectionType, handle: &Handle, config: &NetConfig) -> BoxedEmptyFuture where T: SessionFactory {
 		trace!("Trying to connect to: {}", socket);
 		let connection = connect(&socket, handle, config);
 		Box::new(connection.then(move |result| {
@@ -159,8 +238,15 @@ impl Context {
 				Ok(DeadlineStatus::Meet(Ok(connection))) => {
 					// successful handshake
 					trace!("Connected to {}", connection.address);
-					context.node_table.write().insert(connection.address, connection.services);
-					let channel = context.connections.store::<T>(context.clone(), connection, Direction::Outbound);
+					{
+						let mut node_table = context.node_table.write();
+						node_table.insert(connection.address, connection.services);
+						node_table.note_connected(&connection.address);
+					}
+					if let Some(ref crawler) = context.crawler {
+						crawler.write().note_success(connection.address, &connection.version_message);
+					}
+					let channel = context.connections.store::<T>(context.clone(), connection, connection_type);
 
 					// initialize session and then start reading messages
 					channel.session().initialize();
@@ -195,16 +281,20 @@ impl Context {
 	}
 
 	/// Connect to socket using given context.
-	pub fn connect<T>(context: Arc<Context>, socket: net::SocketAddr) where T: SessionFactory {
+	pub fn connect<T>(context: Arc<Context>, socket: net::SocketAddr, connection_type: ConnectionType) where T: SessionFactory {
 		context.connection_counter.note_new_outbound_connection();
+		if let Some(ref crawler) = context.crawler {
+			crawler.write().note_attempt(socket);
+		}
 		context.remote.clone().spawn(move |handle| {
 			let config = context.config.clone();
-			context.pool.clone().spawn(Context::connect_future::<T>(context, socket, handle, &config.connection))
+			context.pool.clone().spawn(Context::connect_future::<T>(context, socket, connection_type, handle, &config.connection))
 		})
 	}
 
+	/// Connect to the node, requested by user.
 	pub fn connect_normal(context: Arc<Context>, socket: net::SocketAddr) {
-		Self::connect::<NormalSessionFactory>(context, socket)
+		Self::connect::<NormalSessionFactory>(context, socket, ConnectionType::Manual)
 	}
 
 	pub fn accept_connection_future(context: Arc<Context>, stream: TcpStream, socket: net::SocketAddr, handle: &Handle, config: NetConfig) -> BoxedEmptyFuture {
@@ -213,8 +303,12 @@ impl Context {
 				Ok(DeadlineStatus::Meet(Ok(connection))) => {
 					// successful handshake
 					trace!("Accepted connection from {}", connection.address);
-					context.node_table.write().insert(connection.address, connection.services);
-					let channel = context.connections.store::<NormalSessionFactory>(context.clone(), connection, Direction::Inbound);
+					{
+						let mut node_table = context.node_table.write();
+						node_table.insert(connection.address, connection.services);
+						node_table.note_connected(&connection.address);
+					}
+					let channel = context.connections.store::<NormalSessionFactory>(context.clone(), connection, ConnectionType::Inbound);
 
 					// initialize session and then start reading messages
 					channel.session().initialize();
@@ -225,7 +319,7 @@ impl Context {
 					trace!("Accepting handshake from {} failed with error: {}", socket, err);
 					// TODO: close socket
 					context.node_table.write().note_failure(&socket);
-					context.connection_counter.note_close_inbound_connection();
+					context.note_close_inbound_connection(&socket);
 					Box::new(finished(Ok(())))
 				},
 				Ok(DeadlineStatus::Timeout) => {
@@ -233,14 +327,14 @@ impl Context {
 					trace!("Accepting handshake from {} timed out", socket);
 					// TODO: close socket
 					context.node_table.write().note_failure(&socket);
-					context.connection_counter.note_close_inbound_connection();
+					context.note_close_inbound_connection(&socket);
 					Box::new(finished(Ok(())))
 				},
 				Err(_) => {
 					// network error
 					trace!("Accepting handshake from {} failed with network error", socket);
 					context.node_table.write().note_failure(&socket);
-					context.connection_counter.note_close_inbound_connection();
+					context.note_close_inbound_connection(&socket);
 					Box::new(finished(Ok(())))
 				}
 			}
@@ -255,6 +349,12 @@ impl Context {
 		})
 	}
 
+	/// Decreases inbound connections counters.
+	fn note_close_inbound_connection(&self, address: &net::SocketAddr) {
+		self.connection_counter.note_close_inbound_connection();
+		self.inbound_throttle.note_close_inbound_connection(address.ip());
+	}
+
 	/// Starts tcp server and listens for incoming connections.
 	pub fn listen(context: Arc<Context>, handle: &Handle, config: NetConfig) -> Result<BoxedEmptyFuture, io::Error> {
 		trace!("Starting tcp server");
@@ -264,7 +364,8 @@ impl Context {
 				// because we acquire atomic value twice,
 				// it may happen that accept slightly more connections than we need
 				// we don't mind
-				if context.connection_counter.inbound_connections_needed() > 0 {
+				if context.connection_counter.inbound_connections_needed() > 0 &&
+					context.inbound_throttle.note_new_inbound_connection(socket.ip(), ::time::precise_time_s()) {
 					Context::accept_connection(context.clone(), stream, socket, config.clone());
 				} else {
 					// ignore result
@@ -368,8 +469,9 @@ impl Context {
 			channel.session().on_close();
 			trace!("Disconnecting from {}", info.address);
 			channel.shutdown();
+			self.node_table.write().note_disconnected(&info.address);
 			match info.direction {
-				Direction::Inbound => self.connection_counter.note_close_inbound_connection(),
+				Direction::Inbound => self.note_close_inbound_connection(&info.address),
 				Direction::Outbound => self.connection_counter.note_close_outbound_connection(),
 			}
 		}
@@ -382,9 +484,13 @@ impl Context {
 			channel.session().on_close();
 			trace!("Disconnecting from {} caused by {}", info.address, error.description());
 			channel.shutdown();
-			self.node_table.write().note_failure(&info.address);
+			{
+				let mut node_table = self.node_table.write();
+				node_table.note_failure(&info.address);
+				node_table.note_disconnected(&info.address);
+			}
 			match info.direction {
-				Direction::Inbound => self.connection_counter.note_close_inbound_connection(),
+				Direction::Inbound => self.note_close_inbound_connection(&info.address),
 				Direction::Outbound => self.connection_counter.note_close_outbound_connection(),
 			}
 		}
@@ -394,6 +500,11 @@ impl Context {
 		self.local_sync_node.create_sync_session(start_height, services, outbound_connection)
 	}
 
+	/// Returns recorder of received sync messages, if recording is enabled.
+	pub fn recorder(&self) -> Option<&MessageRecorder> {
+		self.recorder.as_ref()
+	}
+
 	pub fn connections(&self) -> &Connections {
 		&self.connections
 	}
@@ -401,6 +512,11 @@ impl Context {
 	pub fn nodes(&self) -> Vec<Node> {
 		self.node_table.read().nodes()
 	}
+
+	/// Returns nodes, probed by crawler. None if node isn't running in crawler mode.
+	pub fn crawled_nodes(&self) -> Option<Vec<CrawledNode>> {
+		self.crawler.as_ref().map(|crawler| crawler.read().nodes())
+	}
 }
 
 pub struct P2P {
@@ -448,7 +564,7 @@ impl P2P {
 
 	pub fn run(&self) -> Result<(), Box<error::Error>> {
 		for peer in &self.config.peers {
-			self.connect::<NormalSessionFactory>(*peer);
+			self.connect::<NormalSessionFactory>(*peer, ConnectionType::Manual);
 		}
 
 		let resolver = try!(DnsResolver::system_config(&self.event_loop_handle));
@@ -457,13 +573,15 @@ impl P2P {
 		}
 
 		Context::autoconnect(self.context.clone(), &self.event_loop_handle);
-		try!(self.listen());
+		if self.config.listen {
+			try!(self.listen());
+		}
 		Ok(())
 	}
 
 	/// Attempts to connect to the specified node
-	pub fn connect<T>(&self, addr: net::SocketAddr) where T: SessionFactory {
-		Context::connect::<T>(self.context.clone(), addr);
+	pub fn connect<T>(&self, addr: net::SocketAddr, connection_type: ConnectionType) where T: SessionFactory {
+		Context::connect::<T>(self.context.clone(), addr, connection_type);
 	}
 
 	pub fn connect_to_seednode(&self, resolver: &Resolver, seednode: &str) {
@@ -474,7 +592,7 @@ impl P2P {
 				Ok(address) => match address.pick_one() {
 					Some(socket) => {
 						trace!("Dns lookup of seednode {} finished. Connecting to {}", owned_seednode, socket);
-						Context::connect::<SeednodeSessionFactory>(context, socket);
+						Context::connect::<SeednodeSessionFactory>(context, socket, ConnectionType::AddrFetch);
 					},
 					None => {
 						trace!("Dns lookup of seednode {} resolved with no results", owned_seednode);
@@ -0,0 +1,96 @@
+//! Network policy settings, which could be reloaded without restarting the node.
+
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use util::MisbehaviourPolicy;
+
+/// Network policy settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkPolicy {
+	/// Penalties for peers misbehaviour.
+	pub misbehaviour: MisbehaviourPolicy,
+	/// Inbound connections from these addresses are never throttled.
+	pub whitelist: Vec<IpAddr>,
+}
+
+impl NetworkPolicy {
+	/// Read policy from the file. Settings that are missing from the file are taken from `defaults`.
+	pub fn from_file<P>(path: P, defaults: &NetworkPolicy) -> Result<Self, String> where P: AsRef<Path> {
+		let contents = fs::read_to_string(path.as_ref())
+			.map_err(|err| format!("Unable to read policy file {}: {}", path.as_ref().display(), err))?;
+		NetworkPolicy::parse(&contents, defaults)
+	}
+
+	/// Parse policy from `key=value` lines. Keys are named after corresponding command line options:
+	/// `ban-score`, `ban-decay` and `whitelist` (which could be specified multiple times).
+	/// Empty lines and lines starting with `#` are ignored.
+	pub fn parse(contents: &str, defaults: &NetworkPolicy) -> Result<Self, String> {
+		let mut policy = defaults.clone();
+		let mut whitelist = None;
+		for (line_index, line) in contents.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let mut parts = line.splitn(2, '=');
+			let key = parts.next().expect("splitn always yields at least one item; qed").trim();
+			let value = parts.next()
+				.ok_or_else(|| format!("Line {}: expected key=value", line_index + 1))?
+				.trim();
+			match key {
+				"ban-score" => policy.misbehaviour.ban_score = value.parse()
+					.map_err(|_| format!("Line {}: invalid ban-score", line_index + 1))?,
+				"ban-decay" => policy.misbehaviour.decay_interval = value.parse()
+					.map_err(|_| format!("Line {}: invalid ban-decay", line_index + 1))?,
+				"whitelist" => whitelist.get_or_insert_with(Vec::new).push(value.parse()
+					.map_err(|_| format!("Line {}: invalid whitelist address: {}", line_index + 1, value))?),
+				_ => return Err(format!("Line {}: unknown policy setting: {}", line_index + 1, key)),
+			}
+		}
+
+		if let Some(whitelist) = whitelist {
+			policy.whitelist = whitelist;
+		}
+
+		Ok(policy)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::MisbehaviourPolicy;
+	use super::NetworkPolicy;
+
+	fn defaults() -> NetworkPolicy {
+		NetworkPolicy {
+			misbehaviour: MisbehaviourPolicy::default(),
+			whitelist: vec!["127.0.0.1".parse().unwrap()],
+		}
+	}
+
+	#[test]
+	fn test_network_policy_parse() {
+		let policy = NetworkPolicy::parse("
+			# stricter bans
+			ban-score = 50
+			whitelist=10.0.0.1
+			whitelist=::1
+		", &defaults()).unwrap();
+
+		assert_eq!(policy.misbehaviour.ban_score, 50);
+		assert_eq!(policy.misbehaviour.decay_interval, MisbehaviourPolicy::default().decay_interval);
+		assert_eq!(policy.whitelist, vec!["10.0.0.1".parse().unwrap(), "::1".parse().unwrap()]);
+
+		assert_eq!(NetworkPolicy::parse("", &defaults()), Ok(defaults()));
+	}
+
+	#[test]
+	fn test_network_policy_parse_errors() {
+		assert!(NetworkPolicy::parse("ban-score", &defaults()).is_err());
+		assert!(NetworkPolicy::parse("ban-score=-1", &defaults()).is_err());
+		assert!(NetworkPolicy::parse("whitelist=localhost", &defaults()).is_err());
+		assert!(NetworkPolicy::parse("maxconnections=10", &defaults()).is_err());
+	}
+}
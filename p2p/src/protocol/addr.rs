@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use std::time::Duration;
-use bytes::Bytes;
+use bytes::SharedBytes;
 use message::{Error, Command, deserialize_payload, Payload};
 use message::types::{GetAddr, Addr};
 use protocol::Protocol;
@@ -30,11 +30,16 @@ impl Protocol for AddrProtocol {
 		}
 	}
 
-	fn on_message(&mut self, command: &Command, payload: &Bytes) -> Result<(), Error> {
+	fn on_message(&mut self, command: &Command, payload: &SharedBytes) -> Result<(), Error> {
 		// normal nodes send addr message only after they receive getaddr message
 		// meanwhile seednodes, surprisingly, send addr message even before they are asked for it
 		if command == &GetAddr::command() {
 			let _: GetAddr = try!(deserialize_payload(payload, self.context.info().version));
+			// nodes that are not listening never advertise addresses
+			if !self.context.global().is_listening() {
+				return Ok(());
+			}
+
 			let entries = self.context.global().node_table_entries().into_iter().map(Into::into).collect();
 			let addr = Addr::new(entries);
 			self.context.send_response_inline(&addr);
@@ -77,7 +82,7 @@ impl SeednodeProtocol {
 }
 
 impl Protocol for SeednodeProtocol {
-	fn on_message(&mut self, command: &Command, _payload: &Bytes) -> Result<(), Error> {
+	fn on_message(&mut self, command: &Command, _payload: &SharedBytes) -> Result<(), Error> {
 		// Seednodes send addr message more than once with different addresses.
 		// We can't disconnect after first read. Let's delay it by 60 seconds.
 		if !self.disconnecting && command == &Addr::command() {
@@ -91,3 +96,32 @@ impl Protocol for SeednodeProtocol {
 		Ok(())
 	}
 }
+
+pub struct CrawlerProtocol {
+	/// Context
+	context: Arc<PeerContext>,
+}
+
+impl CrawlerProtocol {
+	pub fn new(context: Arc<PeerContext>) -> Self {
+		CrawlerProtocol {
+			context: context,
+		}
+	}
+}
+
+impl Protocol for CrawlerProtocol {
+	fn initialize(&mut self) {
+		// connection is closed by AddrProtocol once addresses are received, but some nodes
+		// never answer getaddr => close connection after 30 seconds anyway
+		let context = self.context.global().clone();
+		let peer = self.context.info().id;
+		self.context.global().execute_after(Duration::new(30, 0), move || {
+			context.close_channel(peer);
+		});
+	}
+
+	fn on_message(&mut self, _command: &Command, _payload: &SharedBytes) -> Result<(), Error> {
+		Ok(())
+	}
+}
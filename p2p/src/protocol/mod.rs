@@ -2,17 +2,18 @@ mod addr;
 mod ping;
 mod sync;
 
-use bytes::Bytes;
+use bytes::SharedBytes;
 use message::Error;
 use message::common::Command;
 
-pub use self::addr::{AddrProtocol, SeednodeProtocol};
+pub use self::addr::{AddrProtocol, SeednodeProtocol, CrawlerProtocol};
 pub use self::ping::PingProtocol;
 pub use self::sync::{SyncProtocol,
 	InboundSyncConnection, InboundSyncConnectionRef,
 	InboundSyncConnectionState, InboundSyncConnectionStateRef,
 	OutboundSyncConnection, OutboundSyncConnectionRef,
 	LocalSyncNode, LocalSyncNodeRef,
+	dispatch_sync_message,
 };
 
 pub trait Protocol: Send {
@@ -23,7 +24,7 @@ pub trait Protocol: Send {
 	fn maintain(&mut self) {}
 
 	/// Handle the message.
-	fn on_message(&mut self, command: &Command, payload: &Bytes) -> Result<(), Error>;
+	fn on_message(&mut self, command: &Command, payload: &SharedBytes) -> Result<(), Error>;
 
 	/// On disconnect.
 	fn on_close(&mut self) {}
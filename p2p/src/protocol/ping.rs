@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use time;
-use bytes::Bytes;
+use bytes::SharedBytes;
 use message::{Error, Payload, deserialize_payload};
 use message::types::{Ping, Pong};
 use message::common::Command;
@@ -73,7 +73,7 @@ impl Protocol for PingProtocol {
 		}
 	}
 
-	fn on_message(&mut self, command: &Command, payload: &Bytes) -> Result<(), Error> {
+	fn on_message(&mut self, command: &Command, payload: &SharedBytes) -> Result<(), Error> {
 		// we have received new message => do not close connection because of timeout
 		self.state = State::WaitingTimeout(time::precise_time_s());
 		
@@ -1,8 +1,11 @@
 use std::sync::Arc;
-use bytes::Bytes;
-use message::{Command, Error, Payload, Services, types, deserialize_payload};
+use bytes::SharedBytes;
+use primitives::timings::{self, Stage};
+use message::{Command, Error, Payload, Services, types, deserialize_payload, deserialize_shared_payload};
 use protocol::Protocol;
 use net::PeerContext;
+use util::Misbehaviour;
+use recorder::RecordedEvent;
 
 pub type InboundSyncConnectionRef = Box<InboundSyncConnection>;
 pub type OutboundSyncConnectionRef = Arc<OutboundSyncConnection>;
@@ -56,7 +59,9 @@ pub trait OutboundSyncConnection : Send + Sync {
 	fn send_feefilter(&self, message: &types::FeeFilter);
 	fn send_notfound(&self, message: &types::NotFound);
 	fn ignored(&self, id: u32);
-	fn close(&self);
+	fn note_block_delivered(&self);
+	/// Penalize peer for misbehaviour and close connection.
+	fn close(&self, misbehaviour: Misbehaviour);
 }
 
 struct OutboundSync {
@@ -140,8 +145,12 @@ impl OutboundSyncConnection for OutboundSync {
 		self.context.ignore_response(id);
 	}
 
-	fn close(&self) {
-		self.context.global().penalize_node(&self.context.info().address);
+	fn note_block_delivered(&self) {
+		self.context.global().note_block_delivered(&self.context.info().address);
+	}
+
+	fn close(&self, misbehaviour: Misbehaviour) {
+		self.context.global().penalize_node(&self.context.info().address, misbehaviour);
 		self.context.close()
 	}
 }
@@ -168,109 +177,147 @@ impl SyncProtocol {
 impl Protocol for SyncProtocol {
 	fn initialize(&mut self) {
 		let info = self.context.info();
-		self.inbound_connection.start_sync_session(
-			format!("{}/{}", info.address, info.user_agent),
-			info.version_message.clone()
-		);
-	}
-
-	fn on_message(&mut self, command: &Command, payload: &Bytes) -> Result<(), Error> {
-		let version = self.context.info().version;
-		if command == &types::Inv::command() {
-			// we are synchronizing => we ask only for blocks with known headers => there are no useful blocks hashes for us
-			// we are synchronizing => we ignore all transactions until it is completed => there are no useful transactions hashes for us
-			if self.state.synchronizing() {
-				return Ok(());
-			}
-
-			let message: types::Inv = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_inventory(message);
+		let peer_name = format!("{}/{}", info.address, info.user_agent);
+		if let Some(recorder) = self.context.global().recorder() {
+			recorder.record(&RecordedEvent::Connect {
+				peer: info.id,
+				name: peer_name.clone(),
+				version: info.version,
+				version_message: info.version_message.clone(),
+			});
 		}
-		else if command == &types::GetData::command() {
-			if self.state.synchronizing() {
-				return Ok(());
-			}
 
-			let message: types::GetData = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_getdata(message);
-		}
-		else if command == &types::GetBlocks::command() {
-			if self.state.synchronizing() {
-				return Ok(());
-			}
+		self.inbound_connection.start_sync_session(peer_name, info.version_message.clone());
+	}
 
-			let message: types::GetBlocks = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_getblocks(message);
-		}
-		else if command == &types::GetHeaders::command() {
-			if self.state.synchronizing() {
-				return Ok(());
-			}
-
-			let message: types::GetHeaders = try!(deserialize_payload(payload, version));
-			let id = self.context.declare_response();
-			trace!("declared response {} for request: {}", id, types::GetHeaders::command());
-			self.inbound_connection.on_getheaders(message, id);
-		}
-		else if command == &types::Tx::command() {
-			// we ignore all transactions while synchronizing, as memory pool contains
-			// only verified transactions && we can not verify on-top transactions while
-			// we are not on the top
-			if self.state.synchronizing() {
-				return Ok(());
-			}
-
-			let message: types::Tx = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_transaction(message);
-		}
-		else if command == &types::Block::command() {
-			let message: types::Block = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_block(message);
+	fn on_message(&mut self, command: &Command, payload: &SharedBytes) -> Result<(), Error> {
+		let info = self.context.info();
+		if let Some(recorder) = self.context.global().recorder() {
+			recorder.record(&RecordedEvent::Message {
+				peer: info.id,
+				command: command.clone(),
+				payload: payload.clone(),
+			});
 		}
-		else if command == &types::MemPool::command() {
-			if self.state.synchronizing() {
-				return Ok(());
-			}
 
-			let message: types::MemPool = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_mempool(message);
-		}
-		else if command == &types::Headers::command() {
-			let message: types::Headers = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_headers(message);
-		}
-		else if command == &types::FilterLoad::command() {
-			let message: types::FilterLoad = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_filterload(message);
-		}
-		else if command == &types::FilterAdd::command() {
-			let message: types::FilterAdd = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_filteradd(message);
+		let context = &self.context;
+		dispatch_sync_message(&*self.inbound_connection, &*self.state, command, payload, info.version, || context.declare_response())
+	}
+
+	fn on_close(&mut self) {
+		if let Some(recorder) = self.context.global().recorder() {
+			recorder.record(&RecordedEvent::Disconnect {
+				peer: self.context.info().id,
+			});
 		}
-		else if command == &types::FilterClear::command() {
-			let message: types::FilterClear = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_filterclear(message);
+
+		self.inbound_connection.close_session()
+	}
+}
+
+/// Deserializes sync message and passes it to the inbound connection. `declare_response` is called
+/// to get identifier of the response for requests, which must be answered in order.
+pub fn dispatch_sync_message<F>(
+	inbound_connection: &InboundSyncConnection,
+	state: &InboundSyncConnectionState,
+	command: &Command,
+	payload: &SharedBytes,
+	version: u32,
+	declare_response: F,
+) -> Result<(), Error> where F: FnOnce() -> u32 {
+	if command == &types::Inv::command() {
+		// we are synchronizing => we ask only for blocks with known headers => there are no useful blocks hashes for us
+		// we are synchronizing => we ignore all transactions until it is completed => there are no useful transactions hashes for us
+		if state.synchronizing() {
+			return Ok(());
 		}
-		else if command == &types::MerkleBlock::command() {
-			let message: types::MerkleBlock = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_merkleblock(message);
+
+		let message: types::Inv = try!(deserialize_payload(payload, version));
+		inbound_connection.on_inventory(message);
+	}
+	else if command == &types::GetData::command() {
+		if state.synchronizing() {
+			return Ok(());
 		}
-		else if command == &types::SendHeaders::command() {
-			let message: types::SendHeaders = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_sendheaders(message);
+
+		let message: types::GetData = try!(deserialize_payload(payload, version));
+		inbound_connection.on_getdata(message);
+	}
+	else if command == &types::GetBlocks::command() {
+		if state.synchronizing() {
+			return Ok(());
 		}
-		else if command == &types::FeeFilter::command() {
-			let message: types::FeeFilter = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_feefilter(message);
+
+		let message: types::GetBlocks = try!(deserialize_payload(payload, version));
+		inbound_connection.on_getblocks(message);
+	}
+	else if command == &types::GetHeaders::command() {
+		if state.synchronizing() {
+			return Ok(());
 		}
-		else if command == &types::NotFound::command() {
-			let message: types::NotFound = try!(deserialize_payload(payload, version));
-			self.inbound_connection.on_notfound(message);
+
+		let message: types::GetHeaders = try!(deserialize_payload(payload, version));
+		let id = declare_response();
+		trace!("declared response {} for request: {}", id, types::GetHeaders::command());
+		inbound_connection.on_getheaders(message, id);
+	}
+	else if command == &types::Tx::command() {
+		// we ignore all transactions while synchronizing, as memory pool contains
+		// only verified transactions && we can not verify on-top transactions while
+		// we are not on the top
+		if state.synchronizing() {
+			return Ok(());
 		}
-		Ok(())
+
+		let message: types::Tx = try!(deserialize_shared_payload(payload.clone(), version));
+		inbound_connection.on_transaction(message);
 	}
+	else if command == &types::Block::command() {
+		let message: types::Block = {
+			let _timer = timings::start(Stage::Deserialization);
+			try!(deserialize_shared_payload(payload.clone(), version))
+		};
+		inbound_connection.on_block(message);
+	}
+	else if command == &types::MemPool::command() {
+		if state.synchronizing() {
+			return Ok(());
+		}
 
-	fn on_close(&mut self) {
-		self.inbound_connection.close_session()
+		let message: types::MemPool = try!(deserialize_payload(payload, version));
+		inbound_connection.on_mempool(message);
+	}
+	else if command == &types::Headers::command() {
+		let message: types::Headers = try!(deserialize_payload(payload, version));
+		inbound_connection.on_headers(message);
+	}
+	else if command == &types::FilterLoad::command() {
+		let message: types::FilterLoad = try!(deserialize_payload(payload, version));
+		inbound_connection.on_filterload(message);
+	}
+	else if command == &types::FilterAdd::command() {
+		let message: types::FilterAdd = try!(deserialize_payload(payload, version));
+		inbound_connection.on_filteradd(message);
+	}
+	else if command == &types::FilterClear::command() {
+		let message: types::FilterClear = try!(deserialize_payload(payload, version));
+		inbound_connection.on_filterclear(message);
+	}
+	else if command == &types::MerkleBlock::command() {
+		let message: types::MerkleBlock = try!(deserialize_payload(payload, version));
+		inbound_connection.on_merkleblock(message);
+	}
+	else if command == &types::SendHeaders::command() {
+		let message: types::SendHeaders = try!(deserialize_payload(payload, version));
+		inbound_connection.on_sendheaders(message);
+	}
+	else if command == &types::FeeFilter::command() {
+		let message: types::FeeFilter = try!(deserialize_payload(payload, version));
+		inbound_connection.on_feefilter(message);
+	}
+	else if command == &types::NotFound::command() {
+		let message: types::NotFound = try!(deserialize_payload(payload, version));
+		inbound_connection.on_notfound(message);
 	}
+	Ok(())
 }
@@ -0,0 +1,215 @@
+//! Recording of received p2p messages, so that sessions could be replayed later.
+
+use std::{fs, io};
+use std::io::{Read, Write};
+use std::path::Path;
+use parking_lot::Mutex;
+use bytes::{Bytes, SharedBytes};
+use message::{Command, serialize_payload, deserialize_payload};
+use message::types;
+use network::Magic;
+use ser::{Serializable, Deserializable, Stream, Reader, Error as ReaderError};
+use util::PeerId;
+
+/// First bytes of every recording.
+const RECORDING_PREFIX: &'static [u8; 4] = b"zrec";
+/// Version of the recording format.
+const RECORDING_FORMAT_VERSION: u8 = 1;
+
+const TAG_CONNECT: u8 = 0;
+const TAG_MESSAGE: u8 = 1;
+const TAG_DISCONNECT: u8 = 2;
+
+/// Single recorded p2p event.
+#[derive(Debug, PartialEq, Clone)]
+pub enum RecordedEvent {
+	/// Session with the peer has started.
+	Connect {
+		peer: PeerId,
+		name: String,
+		/// Negotiated protocol version.
+		version: u32,
+		version_message: types::Version,
+	},
+	/// Message has been received from the peer.
+	Message {
+		peer: PeerId,
+		command: Command,
+		payload: SharedBytes,
+	},
+	/// Session with the peer has been closed.
+	Disconnect {
+		peer: PeerId,
+	},
+}
+
+/// Appends received p2p events to the recording file.
+pub struct MessageRecorder {
+	file: Mutex<io::BufWriter<fs::File>>,
+}
+
+impl MessageRecorder {
+	/// Create new recording (truncating existing file, if any).
+	pub fn create<P>(path: P, magic: Magic) -> io::Result<Self> where P: AsRef<Path> {
+		let mut file = io::BufWriter::new(fs::File::create(path)?);
+		let mut header = Stream::default();
+		header
+			.append_slice(RECORDING_PREFIX)
+			.append(&RECORDING_FORMAT_VERSION)
+			.append(&magic);
+		file.write_all(&header.out())?;
+		file.flush()?;
+
+		Ok(MessageRecorder {
+			file: Mutex::new(file),
+		})
+	}
+
+	/// Append event to the recording. Every event is flushed immediately, so that
+	/// the recording remains usable even if node crashes.
+	pub fn record(&self, event: &RecordedEvent) {
+		let bytes = ::ser::serialize(event);
+		let mut file = self.file.lock();
+		if let Err(err) = file.write_all(&bytes).and_then(|_| file.flush()) {
+			error!("Failed to record p2p event: {}", err);
+		}
+	}
+}
+
+/// Read all events from the recording. Returns network magic of the recorded sessions along with events.
+pub fn read_recording<P>(path: P) -> Result<(Magic, Vec<RecordedEvent>), String> where P: AsRef<Path> {
+	let mut buffer = Vec::new();
+	fs::File::open(path)
+		.and_then(|mut file| file.read_to_end(&mut buffer))
+		.map_err(|err| format!("Cannot read recording: {}", err))?;
+
+	if buffer.len() < RECORDING_PREFIX.len() || &buffer[..RECORDING_PREFIX.len()] != &RECORDING_PREFIX[..] {
+		return Err("Not a p2p recording".into());
+	}
+
+	let mut reader = Reader::new(&buffer[RECORDING_PREFIX.len()..]);
+	let format_version: u8 = reader.read()
+		.map_err(|err| format!("Invalid recording header: {:?}", err))?;
+	if format_version != RECORDING_FORMAT_VERSION {
+		return Err(format!("Unsupported recording format version: {}", format_version));
+	}
+	let magic: Magic = reader.read()
+		.map_err(|err| format!("Invalid recording header: {:?}", err))?;
+
+	let mut events = Vec::new();
+	while !reader.is_finished() {
+		let event = reader.read()
+			.map_err(|err| format!("Invalid recorded event #{}: {:?}", events.len(), err))?;
+		events.push(event);
+	}
+
+	Ok((magic, events))
+}
+
+impl Serializable for RecordedEvent {
+	fn serialize(&self, stream: &mut Stream) {
+		match *self {
+			RecordedEvent::Connect { peer, ref name, version, ref version_message } => {
+				let version_message = serialize_payload(version_message, 0)
+					.expect("version message is supported by every protocol version; qed");
+				stream
+					.append(&TAG_CONNECT)
+					.append(&(peer as u32))
+					.append(name)
+					.append(&version)
+					.append(&version_message);
+			},
+			RecordedEvent::Message { peer, ref command, ref payload } => {
+				stream
+					.append(&TAG_MESSAGE)
+					.append(&(peer as u32))
+					.append(command)
+					.append(payload);
+			},
+			RecordedEvent::Disconnect { peer } => {
+				stream
+					.append(&TAG_DISCONNECT)
+					.append(&(peer as u32));
+			},
+		}
+	}
+}
+
+impl Deserializable for RecordedEvent {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let tag: u8 = reader.read()?;
+		let peer = reader.read::<u32>()? as PeerId;
+		match tag {
+			TAG_CONNECT => {
+				let name = reader.read()?;
+				let version = reader.read()?;
+				let version_message: Bytes = reader.read()?;
+				Ok(RecordedEvent::Connect {
+					peer: peer,
+					name: name,
+					version: version,
+					version_message: deserialize_payload(&version_message, 0).map_err(|_| ReaderError::MalformedData)?,
+				})
+			},
+			TAG_MESSAGE => Ok(RecordedEvent::Message {
+				peer: peer,
+				command: reader.read()?,
+				payload: reader.read()?,
+			}),
+			TAG_DISCONNECT => Ok(RecordedEvent::Disconnect {
+				peer: peer,
+			}),
+			_ => Err(ReaderError::MalformedData),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use message::types;
+	use network::Magic;
+	use self::tempdir::TempDir;
+	use super::{MessageRecorder, RecordedEvent, read_recording};
+
+	#[test]
+	fn recording_round_trip() {
+		let dir = TempDir::new("p2p-recording").unwrap();
+		let path = dir.path().join("session.rec");
+		let events = vec![
+			RecordedEvent::Connect {
+				peer: 1,
+				name: "127.0.0.1:8233/MagicBean:2.0.0".into(),
+				version: 170_100,
+				version_message: types::Version::default(),
+			},
+			RecordedEvent::Message {
+				peer: 1,
+				command: "ping".into(),
+				payload: vec![1, 2, 3, 4, 5, 6, 7, 8].into(),
+			},
+			RecordedEvent::Disconnect {
+				peer: 1,
+			},
+		];
+
+		{
+			let recorder = MessageRecorder::create(&path, Magic(0x6427e924)).unwrap();
+			for event in &events {
+				recorder.record(event);
+			}
+		}
+
+		assert_eq!(read_recording(&path), Ok((Magic(0x6427e924), events)));
+	}
+
+	#[test]
+	fn read_invalid_recording() {
+		let dir = TempDir::new("p2p-recording").unwrap();
+		let path = dir.path().join("session.rec");
+		::std::fs::write(&path, b"nodes.csv").unwrap();
+		assert!(read_recording(&path).is_err());
+		assert!(read_recording(dir.path().join("missing.rec")).is_err());
+	}
+}
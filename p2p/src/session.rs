@@ -1,10 +1,10 @@
 use std::sync::Arc;
 use parking_lot::Mutex;
-use bytes::Bytes;
+use bytes::{Bytes, SharedBytes};
 use message::{Command, Error};
 use p2p::Context;
 use net::{PeerContext, PeerStats};
-use protocol::{Protocol, PingProtocol, SyncProtocol, AddrProtocol, SeednodeProtocol};
+use protocol::{Protocol, PingProtocol, SyncProtocol, AddrProtocol, SeednodeProtocol, CrawlerProtocol};
 use util::PeerInfo;
 
 pub trait SessionFactory {
@@ -23,6 +23,18 @@ impl SessionFactory for SeednodeSessionFactory {
 	}
 }
 
+pub struct CrawlerSessionFactory;
+
+impl SessionFactory for CrawlerSessionFactory {
+	fn new_session(context: Arc<Context>, info: PeerInfo, synchronous: bool) -> Session {
+		let peer_context = Arc::new(PeerContext::new(context, info, synchronous));
+		let ping = PingProtocol::new(peer_context.clone()).boxed();
+		let addr = AddrProtocol::new(peer_context.clone(), true).boxed();
+		let crawler = CrawlerProtocol::new(peer_context.clone()).boxed();
+		Session::new(peer_context, vec![ping, addr, crawler])
+	}
+}
+
 pub struct NormalSessionFactory;
 
 impl SessionFactory for NormalSessionFactory {
@@ -63,6 +75,9 @@ impl Session {
 	pub fn on_message(&self, command: Command, payload: Bytes) -> Result<(), Error> {
 		self.stats().lock().report_recv(command.clone(), payload.len());
 
+		// payload is moved into the shared buffer, so protocols could reference it without copying
+		let payload = SharedBytes::from(payload);
+
 		self.protocols.lock()
 			.iter_mut()
 			.map(|protocol| {
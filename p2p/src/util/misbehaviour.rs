@@ -0,0 +1,63 @@
+/// Kind of peer misbehaviour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Misbehaviour {
+	/// Peer has violated p2p protocol (i.e. sent unrequested or malformed message).
+	Protocol,
+	/// Peer has provided us with data that breaks consensus rules (i.e. invalid block).
+	Consensus,
+	/// Peer is wasting our resources (i.e. sends oversized messages or doesn't respond to requests).
+	Resource,
+}
+
+/// Penalties for peers misbehaviour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MisbehaviourPolicy {
+	/// Score added for protocol violation.
+	pub protocol_penalty: u32,
+	/// Score added for consensus rules violation.
+	pub consensus_penalty: u32,
+	/// Score added for resources abuse.
+	pub resource_penalty: u32,
+	/// Peers with misbehaviour score greater than or equal to this are not connected to.
+	pub ban_score: u32,
+	/// Misbehaviour score of every peer is decreased by one every `decay_interval` seconds.
+	pub decay_interval: u32,
+}
+
+impl Default for MisbehaviourPolicy {
+	fn default() -> Self {
+		MisbehaviourPolicy {
+			protocol_penalty: 10,
+			consensus_penalty: 100,
+			resource_penalty: 20,
+			ban_score: 100,
+			decay_interval: 60,
+		}
+	}
+}
+
+impl MisbehaviourPolicy {
+	/// Score that is added to the peer misbehaviour score for given misbehaviour.
+	pub fn penalty(&self, misbehaviour: Misbehaviour) -> u32 {
+		match misbehaviour {
+			Misbehaviour::Protocol => self.protocol_penalty,
+			Misbehaviour::Consensus => self.consensus_penalty,
+			Misbehaviour::Resource => self.resource_penalty,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Misbehaviour, MisbehaviourPolicy};
+
+	#[test]
+	fn test_misbehaviour_policy_penalty() {
+		let policy = MisbehaviourPolicy::default();
+		assert_eq!(policy.penalty(Misbehaviour::Protocol), 10);
+		assert_eq!(policy.penalty(Misbehaviour::Consensus), 100);
+		assert_eq!(policy.penalty(Misbehaviour::Resource), 20);
+		// single consensus violation is enough for ban
+		assert!(policy.penalty(Misbehaviour::Consensus) >= policy.ban_score);
+	}
+}
@@ -2,13 +2,15 @@ pub mod nonce;
 pub mod time;
 pub mod interval;
 mod internet_protocol;
+mod misbehaviour;
 mod node_table;
 mod peer;
 mod response_queue;
 mod synchronizer;
 
 pub use self::internet_protocol::InternetProtocol;
+pub use self::misbehaviour::{Misbehaviour, MisbehaviourPolicy};
 pub use self::node_table::{NodeTable, NodeTableError, Node};
-pub use self::peer::{PeerId, PeerInfo, Direction};
+pub use self::peer::{PeerId, PeerInfo, Direction, ConnectionType};
 pub use self::response_queue::{ResponseQueue, Responses};
 pub use self::synchronizer::{Synchronizer, ConfigurableSynchronizer};
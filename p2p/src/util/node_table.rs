@@ -9,6 +9,9 @@ use message::types::addr::AddressEntry;
 use util::time::{Time, RealTime};
 use util::InternetProtocol;
 
+/// Number of seconds of uptime single block delivery is equivalent to.
+const BLOCK_DELIVERY_UPTIME: u64 = 60;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Node {
 	/// Node address.
@@ -21,12 +24,28 @@ pub struct Node {
 	is_preferable: bool,
 	/// Node failures counter.
 	failures: u32,
+	/// Number of requested blocks this node has delivered to us.
+	blocks: u32,
+	/// Misbehaviour score of the node. Decays over time.
+	misbehaviour_score: u32,
+	/// Total time we have been connected to this node (in seconds).
+	uptime: u64,
 }
 
 impl Node {
 	pub fn address(&self) -> SocketAddr {
 		self.addr
 	}
+
+	/// Node penalty. Nodes with lower penalty are selected first.
+	fn penalty(&self) -> u64 {
+		self.failures as u64 + self.misbehaviour_score as u64
+	}
+
+	/// Long-term node reputation. Among nodes with equal penalty, nodes with better reputation are selected first.
+	fn reputation(&self) -> u64 {
+		self.uptime + self.blocks as u64 * BLOCK_DELIVERY_UPTIME
+	}
 }
 
 impl From<Node> for AddressEntry {
@@ -53,34 +72,23 @@ impl From<Node> for NodeByScore {
 
 impl PartialOrd for NodeByScore {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		if self.0.failures == other.0.failures {
-			if self.0.is_preferable == other.0.is_preferable {
-				if other.0.time == self.0.time {
-					other.0.partial_cmp(&self.0)
-				}
-				else {
-					other.0.time.partial_cmp(&self.0.time)
-				}
-			} else if self.0.is_preferable {
-				return Some(Ordering::Less)
-			} else {
-				Some(Ordering::Greater)
-			}
-		} else {
-			self.0.failures.partial_cmp(&other.0.failures)
-		}
+		Some(self.cmp(other))
 	}
 }
 
 impl Ord for NodeByScore {
 	fn cmp(&self, other: &Self) -> Ordering {
-		if self.0.failures == other.0.failures {
+		if self.0.penalty() == other.0.penalty() {
 			if self.0.is_preferable == other.0.is_preferable {
-				if other.0.time == self.0.time {
-					other.0.cmp(&self.0)
-				}
-				else {
-					other.0.time.cmp(&self.0.time)
+				if self.0.reputation() == other.0.reputation() {
+					if other.0.time == self.0.time {
+						other.0.cmp(&self.0)
+					}
+					else {
+						other.0.time.cmp(&self.0.time)
+					}
+				} else {
+					other.0.reputation().cmp(&self.0.reputation())
 				}
 			} else if self.0.is_preferable {
 				return Ordering::Less
@@ -88,7 +96,7 @@ impl Ord for NodeByScore {
 				Ordering::Greater
 			}
 		} else {
-			self.0.failures.cmp(&other.0.failures)
+			self.0.penalty().cmp(&other.0.penalty())
 		}
 	}
 }
@@ -179,6 +187,10 @@ pub struct NodeTable<T = RealTime> where T: Time {
 	by_score: BTreeSet<NodeByScore>,
 	/// Nodes sorted by time.
 	by_time: BTreeSet<NodeByTime>,
+	/// Connection start time of currently connected nodes.
+	connected: HashMap<SocketAddr, i64>,
+	/// Time of last misbehaviour scores decay.
+	last_decay: Option<i64>,
 }
 
 impl NodeTable {
@@ -229,6 +241,9 @@ impl<T> NodeTable<T> where T: Time {
 					services: services,
 					is_preferable: services.includes(&self.preferable_services),
 					failures: 0,
+					blocks: 0,
+					misbehaviour_score: 0,
+					uptime: 0,
 				};
 				self.by_score.insert(node.clone().into());
 				self.by_time.insert(node.clone().into());
@@ -283,6 +298,9 @@ impl<T> NodeTable<T> where T: Time {
 				services: addr.address.services,
 				is_preferable: addr.address.services.includes(&self.preferable_services),
 				failures: 0,
+				blocks: 0,
+				misbehaviour_score: 0,
+				uptime: 0,
 			};
 
 			match self.by_addr.entry(node.addr) {
@@ -353,21 +371,74 @@ impl<T> NodeTable<T> where T: Time {
 
 	/// Marks address as recently used.
 	pub fn note_used(&mut self, addr: &SocketAddr) {
-		if let Some(ref mut node) = self.by_addr.get_mut(addr) {
-			assert!(self.by_score.remove(&node.clone().into()));
-			assert!(self.by_time.remove(&node.clone().into()));
-			node.time = self.time.get().sec;
-			self.by_score.insert(node.clone().into());
-			self.by_time.insert(node.clone().into());
-		}
+		let now = self.time.get().sec;
+		self.update(addr, |node| node.time = now);
 	}
 
 	/// Notes failure.
 	pub fn note_failure(&mut self, addr: &SocketAddr) {
-		if let Some(ref mut node) = self.by_addr.get_mut(addr) {
+		self.update(addr, |node| node.failures += 1);
+	}
+
+	/// Notes that node has misbehaved. Penalty is added to the node misbehaviour score.
+	pub fn note_misbehaviour(&mut self, addr: &SocketAddr, penalty: u32) {
+		self.update(addr, |node| node.misbehaviour_score = node.misbehaviour_score.saturating_add(penalty));
+	}
+
+	/// Decreases misbehaviour score of every node by one for every `interval` seconds passed since last decay.
+	pub fn decay_misbehaviour(&mut self, interval: u32) {
+		let now = self.time.get().sec;
+		let last_decay = *self.last_decay.get_or_insert(now);
+		let interval = ::std::cmp::max(interval, 1) as i64;
+		let steps = (now - last_decay).max(0) / interval;
+		if steps == 0 {
+			return;
+		}
+
+		self.last_decay = Some(last_decay + steps * interval);
+		let decay = ::std::cmp::min(steps, ::std::u32::MAX as i64) as u32;
+		let misbehaving: Vec<_> = self.by_addr.values()
+			.filter(|node| node.misbehaviour_score != 0)
+			.map(|node| node.addr)
+			.collect();
+		for addr in misbehaving {
+			self.update(&addr, |node| node.misbehaviour_score = node.misbehaviour_score.saturating_sub(decay));
+		}
+	}
+
+	/// Returns addresses of nodes with misbehaviour score greater than or equal to `ban_score`.
+	pub fn banned_nodes(&self, ban_score: u32) -> HashSet<SocketAddr> {
+		self.by_addr.values()
+			.filter(|node| node.misbehaviour_score >= ban_score)
+			.map(|node| node.addr)
+			.collect()
+	}
+
+	/// Notes that node has delivered requested block.
+	pub fn note_block_delivered(&mut self, addr: &SocketAddr) {
+		self.update(addr, |node| node.blocks += 1);
+	}
+
+	/// Notes that connection to the node has been established.
+	pub fn note_connected(&mut self, addr: &SocketAddr) {
+		let now = self.time.get().sec;
+		self.connected.insert(*addr, now);
+	}
+
+	/// Notes that connection to the node has been closed.
+	pub fn note_disconnected(&mut self, addr: &SocketAddr) {
+		if let Some(connected_since) = self.connected.remove(addr) {
+			let uptime = (self.time.get().sec - connected_since).max(0) as u64;
+			self.update(addr, |node| node.uptime += uptime);
+		}
+	}
+
+	/// Updates node with given address, keeping indices consistent.
+	fn update<F>(&mut self, addr: &SocketAddr, update: F) where F: FnOnce(&mut Node) {
+		if let Some(node) = self.by_addr.get_mut(addr) {
 			assert!(self.by_score.remove(&node.clone().into()));
 			assert!(self.by_time.remove(&node.clone().into()));
-			node.failures += 1;
+			update(node);
 			self.by_score.insert(node.clone().into());
 			self.by_time.insert(node.clone().into());
 		}
@@ -383,9 +454,14 @@ impl<T> NodeTable<T> where T: Time {
 			.take(1000);
 
 		let err = || io::Error::new(io::ErrorKind::Other, "Write csv error");
+		let now = self.time.get().sec;
 
 		for n in iter {
-			let record = (n.addr.to_string(), n.time, u64::from(n.services), n.failures);
+			// also account uptime of the current connection
+			let uptime = n.uptime + self.connected.get(&n.addr)
+				.map(|connected_since| (now - connected_since).max(0) as u64)
+				.unwrap_or(0);
+			let record = (n.addr.to_string(), n.time, u64::from(n.services), n.failures, n.blocks, n.misbehaviour_score, uptime);
 			try!(writer.serialize(record).map_err(|_| err()));
 		}
 
@@ -396,6 +472,8 @@ impl<T> NodeTable<T> where T: Time {
 	pub fn load<R>(preferable_services: Services, read: R) -> Result<Self, io::Error> where R: io::Read, T: Default {
 		let mut rdr = csv::ReaderBuilder::new()
 			.has_headers(false)
+			// node tables saved by older versions have no reputation columns
+			.flexible(true)
 			.delimiter(b' ')
 			.from_reader(read);
 
@@ -404,8 +482,13 @@ impl<T> NodeTable<T> where T: Time {
 
 		let err = || io::Error::new(io::ErrorKind::Other, "Load csv error");
 
-		for row in rdr.deserialize() {
-			let (addr, time, services, failures): (String, i64, u64, u32) = try!(row.map_err(|_| err()));
+		for row in rdr.records() {
+			let row = try!(row.map_err(|_| err()));
+			let (addr, time, services, failures, blocks, misbehaviour_score, uptime): (String, i64, u64, u32, u32, u32, u64) =
+				try!(row.deserialize(None)
+					.or_else(|_| row.deserialize(None)
+						.map(|(addr, time, services, failures): (String, i64, u64, u32)| (addr, time, services, failures, 0, 0, 0)))
+					.map_err(|_| err()));
 
 			let services = services.into();
 			let node = Node {
@@ -414,6 +497,9 @@ impl<T> NodeTable<T> where T: Time {
 				services: services,
 				is_preferable: services.includes(&preferable_services),
 				failures: failures,
+				blocks: blocks,
+				misbehaviour_score: misbehaviour_score,
+				uptime: uptime,
 			};
 
 			node_table.by_score.insert(node.clone().into());
@@ -594,14 +680,97 @@ mod tests {
 
 		let s = String::from_utf8(db).unwrap();
 		assert_eq!(
-"127.0.0.1:8001 7 0 0
-127.0.0.1:8004 6 0 0
-127.0.0.1:8000 0 0 0
-127.0.0.1:8002 5 0 1
-127.0.0.1:8003 3 0 1
+"127.0.0.1:8001 7 0 0 0 0 0
+127.0.0.1:8004 6 0 0 0 0 0
+127.0.0.1:8000 0 0 0 0 0 0
+127.0.0.1:8002 5 0 1 0 0 0
+127.0.0.1:8003 3 0 1 0 0 0
 ".to_string(), s);
 	}
 
+	#[test]
+	fn test_load_without_reputation() {
+		let db = "127.0.0.1:8001 7 0 0\n127.0.0.1:8002 5 0 1\n";
+		let table = NodeTable::<ZeroTime>::load(Services::default(), db.as_bytes()).unwrap();
+		let nodes = table.nodes_with_services(&Services::default(), InternetProtocol::default(), &HashSet::new(), 10);
+		assert_eq!(nodes.len(), 2);
+		assert_eq!(nodes[0].addr, "127.0.0.1:8001".parse().unwrap());
+		assert_eq!(nodes[0].blocks, 0);
+		assert_eq!(nodes[0].misbehaviour_score, 0);
+		assert_eq!(nodes[0].uptime, 0);
+		assert_eq!(nodes[1].addr, "127.0.0.1:8002".parse().unwrap());
+		assert_eq!(nodes[1].failures, 1);
+	}
+
+	#[test]
+	fn test_reputation_survives_save_and_load() {
+		let s0: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+		let s1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+		let s2: SocketAddr = "127.0.0.1:8002".parse().unwrap();
+		let mut table = NodeTable::<IncrementalTime>::default();
+		table.insert(s0, Services::default());
+		table.insert(s1, Services::default());
+		table.insert(s2, Services::default());
+		// s0 has been connected for a while and has delivered some blocks
+		table.note_connected(&s0);
+		table.note_block_delivered(&s0);
+		table.note_block_delivered(&s0);
+		table.note_disconnected(&s0);
+		// s2 has provided us with invalid data
+		table.note_misbehaviour(&s2, 10);
+
+		let mut db = Vec::new();
+		table.save(&mut db).unwrap();
+		let loaded_table = NodeTable::<IncrementalTime>::load(Services::default(), &db as &[u8]).unwrap();
+		assert_eq!(table.by_addr, loaded_table.by_addr);
+		assert_eq!(table.by_score, loaded_table.by_score);
+
+		// s1 is the most recently seen node, but s0 has better reputation
+		let nodes = loaded_table.nodes_with_services(&Services::default(), InternetProtocol::default(), &HashSet::new(), 10);
+		assert_eq!(nodes[0].addr, s0);
+		assert_eq!(nodes[0].blocks, 2);
+		assert_eq!(nodes[0].uptime, 1);
+		assert_eq!(nodes[1].addr, s1);
+		assert_eq!(nodes[2].addr, s2);
+		assert_eq!(nodes[2].misbehaviour_score, 10);
+	}
+
+	#[test]
+	fn test_save_accounts_current_connection_uptime() {
+		let s0: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+		let mut table = NodeTable::<IncrementalTime>::default();
+		table.insert(s0, Services::default());
+		table.note_connected(&s0);
+
+		let mut db = Vec::new();
+		table.save(&mut db).unwrap();
+		assert_eq!(String::from_utf8(db).unwrap(), "127.0.0.1:8000 0 0 0 0 0 1\n".to_string());
+	}
+
+	#[test]
+	fn test_misbehaviour_decay() {
+		let s0: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+		let s1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+		let mut table = NodeTable::<IncrementalTime>::default();
+		// time 0: decay starts
+		table.decay_misbehaviour(2);
+		table.insert(s0, Services::default());
+		table.insert(s1, Services::default());
+		table.note_misbehaviour(&s0, 3);
+		table.note_misbehaviour(&s1, 1);
+		assert_eq!(table.banned_nodes(3), vec![s0].into_iter().collect());
+
+		// time 3: one decay interval has passed
+		table.decay_misbehaviour(2);
+		assert_eq!(table.banned_nodes(3), HashSet::new());
+		assert_eq!(table.banned_nodes(2), vec![s0].into_iter().collect());
+		let nodes = table.nodes_with_services(&Services::default(), InternetProtocol::default(), &HashSet::new(), 2);
+		assert_eq!(nodes[0].addr, s1);
+		assert_eq!(nodes[0].misbehaviour_score, 0);
+		assert_eq!(nodes[1].addr, s0);
+		assert_eq!(nodes[1].misbehaviour_score, 2);
+	}
+
 	#[test]
 	fn test_preferable_services() {
 		let s0: SocketAddr = "127.0.0.1:8000".parse().unwrap();
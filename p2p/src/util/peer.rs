@@ -10,12 +10,35 @@ pub enum Direction {
 	Outbound,
 }
 
+/// Purpose of the connection.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConnectionType {
+	/// Connection, initiated by the peer.
+	Inbound,
+	/// Outbound connection, opened automatically to relay blocks, transactions and addresses.
+	OutboundFullRelay,
+	/// Outbound connection to the node, requested by user (`connect` option or `addnode` RPC).
+	Manual,
+	/// Short-lived outbound connection to the seednode, opened to fetch addresses.
+	AddrFetch,
+}
+
+impl ConnectionType {
+	pub fn direction(&self) -> Direction {
+		match *self {
+			ConnectionType::Inbound => Direction::Inbound,
+			ConnectionType::OutboundFullRelay | ConnectionType::Manual | ConnectionType::AddrFetch => Direction::Outbound,
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct PeerInfo {
 	pub id: PeerId,
 	pub address: SocketAddr,
 	pub user_agent: String,
 	pub direction: Direction,
+	pub connection_type: ConnectionType,
 	pub version: u32,
 	pub version_message: types::Version,
 	pub magic: Magic,
@@ -1,6 +1,7 @@
 //! Wrapper around `Vec<u8>`
 
-use std::{ops, str, fmt, io, marker};
+use std::{ops, str, fmt, io, marker, hash};
+use std::sync::Arc;
 use hex::{ToHex, FromHex, FromHexError};
 use heapsize::HeapSizeOf;
 
@@ -114,6 +115,155 @@ impl AsMut<[u8]> for Bytes {
 	}
 }
 
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Bytes {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+		serializer.serialize_str(&self.0.to_hex::<String>())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Bytes {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+		use serde::de::{Deserialize, Error, Unexpected};
+
+		let s = String::deserialize(deserializer)?;
+		s.parse().map_err(|_| D::Error::invalid_value(Unexpected::Str(&s), &"hex-encoded bytes"))
+	}
+}
+
+/// Immutable slice of reference-counted buffer. Cloning and slicing never copy the data.
+///
+/// Slice keeps the whole buffer alive, so it shouldn't be stored for a long time if it
+/// only references small part of a large buffer.
+#[derive(Default, Clone)]
+pub struct SharedBytes {
+	buffer: Arc<Vec<u8>>,
+	start: usize,
+	end: usize,
+}
+
+impl SharedBytes {
+	pub fn new() -> Self {
+		SharedBytes::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.end - self.start
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.start == self.end
+	}
+
+	/// Returns slice of `[start; end)` bytes, sharing the same buffer.
+	pub fn slice(&self, start: usize, end: usize) -> SharedBytes {
+		assert!(start <= end && end <= self.len(), "slice range is out of bounds");
+
+		SharedBytes {
+			buffer: self.buffer.clone(),
+			start: self.start + start,
+			end: self.start + end,
+		}
+	}
+
+	/// Splits slice in two at given index. `self` is left with `[at; len)` bytes
+	/// and `[0; at)` bytes are returned. Both slices share the same buffer.
+	pub fn split_to(&mut self, at: usize) -> SharedBytes {
+		let head = self.slice(0, at);
+		self.start += at;
+		head
+	}
+
+	/// Copy bytes into the new buffer.
+	pub fn to_bytes(&self) -> Bytes {
+		Bytes(self.to_vec())
+	}
+}
+
+impl HeapSizeOf for SharedBytes {
+	fn heap_size_of_children(&self) -> usize {
+		// buffer is shared => only the referenced part is accounted
+		self.len()
+	}
+}
+
+impl From<Vec<u8>> for SharedBytes {
+	fn from(v: Vec<u8>) -> Self {
+		SharedBytes {
+			start: 0,
+			end: v.len(),
+			buffer: Arc::new(v),
+		}
+	}
+}
+
+impl From<Bytes> for SharedBytes {
+	fn from(bytes: Bytes) -> Self {
+		bytes.0.into()
+	}
+}
+
+impl<'a> From<&'a [u8]> for SharedBytes {
+	fn from(v: &[u8]) -> Self {
+		v.to_vec().into()
+	}
+}
+
+impl From<&'static str> for SharedBytes {
+	fn from(s: &'static str) -> Self {
+		Bytes::from(s).into()
+	}
+}
+
+impl PartialEq for SharedBytes {
+	fn eq(&self, other: &Self) -> bool {
+		**self == **other
+	}
+}
+
+impl Eq for SharedBytes {}
+
+impl hash::Hash for SharedBytes {
+	fn hash<H: hash::Hasher>(&self, state: &mut H) {
+		(**self).hash(state)
+	}
+}
+
+impl fmt::Debug for SharedBytes {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&(&**self).to_hex::<String>())
+	}
+}
+
+impl ops::Deref for SharedBytes {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		&self.buffer[self.start..self.end]
+	}
+}
+
+impl AsRef<[u8]> for SharedBytes {
+	fn as_ref(&self) -> &[u8] {
+		self
+	}
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for SharedBytes {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+		serializer.serialize_str(&(&**self).to_hex::<String>())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for SharedBytes {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+		<Bytes as ::serde::Deserialize>::deserialize(deserializer).map(Into::into)
+	}
+}
+
 /// Wrapper around `Vec<u8>` which represent associated type
 #[derive(Default, PartialEq, Clone)]
 pub struct TaggedBytes<T> {
@@ -162,7 +312,7 @@ impl<T> AsMut<[u8]> for TaggedBytes<T> {
 
 #[cfg(test)]
 mod tests {
-	use super::Bytes;
+	use super::{Bytes, SharedBytes};
 
 	#[test]
 	fn test_bytes_from_hex() {
@@ -175,4 +325,16 @@ mod tests {
 		let bytes: Bytes = "0145".into();
 		assert_eq!(format!("{:?}", bytes), "0145".to_owned());
 	}
+
+	#[test]
+	fn test_shared_bytes_slicing() {
+		let mut bytes: SharedBytes = "01020304".into();
+		let head = bytes.split_to(1);
+		assert_eq!(head, "01".into());
+		assert_eq!(bytes, "020304".into());
+		assert_eq!(bytes.slice(1, 2), "03".into());
+		assert_eq!(bytes.slice(1, 1).len(), 0);
+		assert_eq!(bytes.to_bytes(), "020304".into());
+		assert_eq!(format!("{:?}", bytes), "020304".to_owned());
+	}
 }
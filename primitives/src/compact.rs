@@ -100,6 +100,26 @@ impl Compact {
 	}
 }
 
+/// Compact is serialized as hex string of 4 bytes, the same way `bits` are displayed by zcashd.
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Compact {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+		serializer.serialize_str(&format!("{:08x}", self.0))
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Compact {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+		use serde::de::{Deserialize, Error, Unexpected};
+
+		let s = String::deserialize(deserializer)?;
+		u32::from_str_radix(&s, 16)
+			.map(Compact)
+			.map_err(|_| D::Error::invalid_value(Unexpected::Str(&s), &"hex-encoded compact target"))
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use bigint::U256;
@@ -176,3 +176,45 @@ impl H256 {
 		self.reversed().to_string()
 	}
 }
+
+/// Hashes are serialized as hex strings. `H256` is serialized in reversed byte order, the same way
+/// zcashd displays block and transaction hashes.
+#[cfg(feature = "serde")]
+macro_rules! impl_hash_serde {
+	($name: ident, $reversed: expr) => {
+		impl ::serde::Serialize for $name {
+			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+				let hash = if $reversed { self.reversed() } else { self.clone() };
+				serializer.serialize_str(&hash.to_string())
+			}
+		}
+
+		impl<'de> ::serde::Deserialize<'de> for $name {
+			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+				use serde::de::{Deserialize, Error, Unexpected};
+
+				let s = String::deserialize(deserializer)?;
+				let hash: $name = s.parse()
+					.map_err(|_| D::Error::invalid_value(Unexpected::Str(&s), &concat!("hex-encoded ", stringify!($name))))?;
+				Ok(if $reversed { hash.reversed() } else { hash })
+			}
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+impl_hash_serde!(H32, false);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H48, false);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H96, false);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H160, false);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H256, true);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H264, false);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H512, false);
+#[cfg(feature = "serde")]
+impl_hash_serde!(H520, false);
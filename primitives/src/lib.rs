@@ -3,7 +3,10 @@ extern crate byteorder;
 extern crate heapsize;
 extern crate rustc_hex as hex;
 pub extern crate bigint;
+#[cfg(feature = "serde")]
+extern crate serde;
 
 pub mod bytes;
 pub mod compact;
 pub mod hash;
+pub mod timings;
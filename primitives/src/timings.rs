@@ -0,0 +1,156 @@
+//! Lightweight per-stage timers of block validation.
+//!
+//! Time, spent in every stage, is accumulated in process-wide counters && is aggregated
+//! per `BLOCKS_PER_WINDOW` processed blocks. Stages, which are executed by several threads
+//! at once, report the sum of time, spent by all threads.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of blocks, the timings are aggregated for.
+pub const BLOCKS_PER_WINDOW: usize = 1000;
+
+/// Stage of block processing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+	/// Deserialization of blocks, received from peers.
+	Deserialization = 0,
+	/// Verification of transparent input scripts.
+	ScriptVerification = 1,
+	/// Verification of Sprout && Sapling proofs.
+	ProofVerification = 2,
+	/// Writing of verified blocks to the database.
+	DatabaseWrite = 3,
+}
+
+/// All stages in processing order.
+pub const STAGES: [Stage; 4] = [
+	Stage::Deserialization,
+	Stage::ScriptVerification,
+	Stage::ProofVerification,
+	Stage::DatabaseWrite,
+];
+
+impl Stage {
+	/// Short name of the stage.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Stage::Deserialization => "deserialization",
+			Stage::ScriptVerification => "scriptverification",
+			Stage::ProofVerification => "proofverification",
+			Stage::DatabaseWrite => "databasewrite",
+		}
+	}
+}
+
+/// Nanoseconds, spent in every stage since the current window has started.
+static CURRENT_WINDOW: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+/// Nanoseconds, spent in every stage during the last complete window.
+static LAST_WINDOW: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+/// Nanoseconds, spent in every stage since the start.
+static TOTAL: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+/// Number of blocks, processed since the current window has started.
+static CURRENT_WINDOW_BLOCKS: AtomicUsize = AtomicUsize::new(0);
+/// Number of complete windows.
+static WINDOWS: AtomicUsize = AtomicUsize::new(0);
+
+/// Timer of the single stage. Elapsed time is recorded when the timer is dropped.
+pub struct StageTimer {
+	stage: Stage,
+	started: Instant,
+}
+
+impl Drop for StageTimer {
+	fn drop(&mut self) {
+		record(self.stage, self.started.elapsed());
+	}
+}
+
+/// Start timer of the stage.
+pub fn start(stage: Stage) -> StageTimer {
+	StageTimer {
+		stage: stage,
+		started: Instant::now(),
+	}
+}
+
+/// Record time, spent in the stage.
+pub fn record(stage: Stage, duration: Duration) {
+	let nanos = duration.as_secs().saturating_mul(1_000_000_000).saturating_add(duration.subsec_nanos() as u64);
+	CURRENT_WINDOW[stage as usize].fetch_add(nanos, Ordering::Relaxed);
+	TOTAL[stage as usize].fetch_add(nanos, Ordering::Relaxed);
+}
+
+/// Note that the block has been processed. Every `BLOCKS_PER_WINDOW` blocks the current window is closed.
+pub fn note_block() {
+	if CURRENT_WINDOW_BLOCKS.fetch_add(1, Ordering::SeqCst) + 1 == BLOCKS_PER_WINDOW {
+		for stage in STAGES.iter() {
+			let nanos = CURRENT_WINDOW[*stage as usize].swap(0, Ordering::Relaxed);
+			LAST_WINDOW[*stage as usize].store(nanos, Ordering::Relaxed);
+		}
+		CURRENT_WINDOW_BLOCKS.fetch_sub(BLOCKS_PER_WINDOW, Ordering::SeqCst);
+		WINDOWS.fetch_add(1, Ordering::SeqCst);
+	}
+}
+
+/// Time, spent in the single stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StageTimings {
+	pub stage: Stage,
+	/// Time, spent since the current window has started.
+	pub current_window: Duration,
+	/// Time, spent during the last complete window.
+	pub last_window: Duration,
+	/// Time, spent since the start.
+	pub total: Duration,
+}
+
+/// Snapshot of all stage timers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timings {
+	/// Number of complete windows.
+	pub windows: usize,
+	/// Number of blocks, processed since the current window has started.
+	pub current_window_blocks: usize,
+	pub stages: Vec<StageTimings>,
+}
+
+/// Read all stage timers.
+pub fn timings() -> Timings {
+	let duration = |nanos: u64| Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32);
+	Timings {
+		windows: WINDOWS.load(Ordering::SeqCst),
+		current_window_blocks: CURRENT_WINDOW_BLOCKS.load(Ordering::SeqCst),
+		stages: STAGES.iter().map(|stage| StageTimings {
+			stage: *stage,
+			current_window: duration(CURRENT_WINDOW[*stage as usize].load(Ordering::Relaxed)),
+			last_window: duration(LAST_WINDOW[*stage as usize].load(Ordering::Relaxed)),
+			total: duration(TOTAL[*stage as usize].load(Ordering::Relaxed)),
+		}).collect(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::{Stage, BLOCKS_PER_WINDOW, record, note_block, timings};
+
+	#[test]
+	fn stage_timings_are_aggregated_per_window() {
+		record(Stage::ProofVerification, Duration::new(2, 500));
+		let before = timings();
+		assert_eq!(before.stages[2].stage, Stage::ProofVerification);
+		assert!(before.stages[2].current_window >= Duration::new(2, 500));
+		assert!(before.stages[2].total >= Duration::new(2, 500));
+
+		for _ in 0..BLOCKS_PER_WINDOW {
+			note_block();
+		}
+
+		let after = timings();
+		assert_eq!(after.windows, before.windows + 1);
+		assert_eq!(after.current_window_blocks, before.current_window_blocks);
+		assert!(after.stages[2].last_window >= Duration::new(2, 500));
+		assert!(after.stages[2].total >= Duration::new(2, 500));
+	}
+}
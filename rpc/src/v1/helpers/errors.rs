@@ -4,16 +4,27 @@ mod codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
 	pub const UNKNOWN: i64 = -32000;
 	pub const EXECUTION_ERROR: i64 = -32015;
+	pub const NODE_IS_SYNCHRONIZING: i64 = -32090;
+	pub const TRANSACTION_MISSING_INPUTS: i64 = -32091;
+	pub const TRANSACTION_INSUFFICIENT_FEE: i64 = -32092;
+	pub const TRANSACTION_EXPIRED: i64 = -32093;
+	pub const TRANSACTION_NON_STANDARD: i64 = -32094;
+	pub const TRANSACTION_REJECTED: i64 = -32095;
 	pub const TRANSACTION_NOT_FOUND: i64 = -32096;
 	pub const TRANSACTION_OUTPUT_NOT_FOUND: i64 = -32097;
 	pub const TRANSACTION_OF_SIDE_BRANCH: i64 = -32098;
 	pub const BLOCK_NOT_FOUND: i64 = -32099;
+	pub const HEADER_REJECTED: i64 = -32100;
+	pub const ADDRESS_INDEX_DISABLED: i64 = -32101;
+	pub const SPENT_INDEX_DISABLED: i64 = -32102;
+	pub const SPENT_INFO_NOT_FOUND: i64 = -32103;
 	pub const NODE_ALREADY_ADDED: i64 = -32150;
 	pub const NODE_NOT_ADDED: i64 = -32151;
 }
 
 use std::fmt;
 use jsonrpc_core::{Error, ErrorCode, Value};
+use sync::{TransactionRejection, HeaderRejection};
 
 macro_rules! rpc_unimplemented {
 	() => (Err(::v1::helpers::errors::unimplemented(None)))
@@ -83,6 +94,76 @@ pub fn transaction_of_side_branch<T: fmt::Debug>(data: T) -> Error {
 	}
 }
 
+pub fn transaction_rejected(rejection: TransactionRejection) -> Error {
+	let (code, message, data) = match rejection {
+		TransactionRejection::Synchronizing =>
+			(codes::NODE_IS_SYNCHRONIZING, "Node is not yet synchronized", None),
+		TransactionRejection::MissingInputs =>
+			(codes::TRANSACTION_MISSING_INPUTS, "Missing transaction inputs", None),
+		TransactionRejection::InsufficientFee =>
+			(codes::TRANSACTION_INSUFFICIENT_FEE, "Transaction fee is too low", None),
+		TransactionRejection::Expired =>
+			(codes::TRANSACTION_EXPIRED, "Transaction is expired", None),
+		TransactionRejection::NonStandard(reason) =>
+			(codes::TRANSACTION_NON_STANDARD, "Transaction is not standard", Some(reason)),
+		TransactionRejection::Consensus(reason) =>
+			(codes::TRANSACTION_REJECTED, "Transaction violates consensus rules", Some(reason)),
+	};
+
+	Error {
+		code: ErrorCode::ServerError(code),
+		message: message.into(),
+		data: data.map(Value::String),
+	}
+}
+
+pub fn header_rejected(rejection: HeaderRejection) -> Error {
+	let (message, reason) = match rejection {
+		HeaderRejection::Duplicate =>
+			("Header is already known", "duplicate".to_owned()),
+		HeaderRejection::DuplicateInvalid =>
+			("Header is already known to be invalid", "duplicate-invalid".to_owned()),
+		HeaderRejection::UnknownParent =>
+			("Parent header is unknown", "prev-blk-not-found".to_owned()),
+		HeaderRejection::InvalidParent =>
+			("Parent header is invalid", "bad-prevblk".to_owned()),
+		HeaderRejection::CheckpointMismatch(height) =>
+			("Header contradicts checkpoint", format!("checkpoint-mismatch at height {}", height)),
+		HeaderRejection::Invalid(reason) =>
+			("Header violates consensus rules", reason),
+	};
+
+	Error {
+		code: ErrorCode::ServerError(codes::HEADER_REJECTED),
+		message: message.into(),
+		data: Some(Value::String(reason)),
+	}
+}
+
+pub fn address_index_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::ADDRESS_INDEX_DISABLED),
+		message: "Address index is not enabled (start the node with --addressindex)".into(),
+		data: None,
+	}
+}
+
+pub fn spent_index_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::SPENT_INDEX_DISABLED),
+		message: "Spent index is not enabled (start the node with --spentindex)".into(),
+		data: None,
+	}
+}
+
+pub fn spent_info_not_found<T: fmt::Debug>(data: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::SPENT_INFO_NOT_FOUND),
+		message: "Output is not spent by the canon chain transaction".into(),
+		data: Some(Value::String(format!("{:?}", data))),
+	}
+}
+
 pub fn node_already_added() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NODE_ALREADY_ADDED),
@@ -1,19 +1,38 @@
+use std::collections::HashSet;
 use v1::traits::BlockChain;
 use v1::types::{BlockRef, GetBlockResponse, VerboseBlock, RawBlock};
 use v1::types::{GetTxOutResponse, TransactionOutputScript};
 use v1::types::GetTxOutSetInfoResponse;
+use v1::types::{GetShieldedStatsResponse, ShieldedPoolStats};
+use v1::types::{GetBlockChainInfoResponse, NetworkUpgradeInfo, NetworkUpgradeStatus, SoftforkInfo, ConsensusInfo,
+	ValuePoolInfo};
+use v1::types::{GetConsensusParamsResponse, NetworkUpgradeParams, FundingStreamParams};
+use v1::types::{GetBlockStatsResponse, GetFeeHistoryResponse, BlockFeeRates};
+use v1::types::{GetChainEventsResponse, ChainEventInfo, ChainEventType};
+use v1::types::GetValidationTimingsResponse;
+use v1::types::{GetTreeStateResponse, TreeStateInfo, TreeCommitments};
+use v1::types::{AddressRequest, GetAddressBalanceResponse};
+use v1::types::{SpentInfoRequest, GetSpentInfoResponse};
+use v1::types::GetDbInfoResponse;
 use v1::types::H256;
 use keys::{self, Address};
 use v1::helpers::errors::{block_not_found, block_at_height_not_found, transaction_not_found,
-	transaction_output_not_found, transaction_of_side_branch, invalid_params};
+	transaction_output_not_found, transaction_of_side_branch, invalid_params, address_index_disabled,
+	spent_index_disabled, spent_info_not_found, execution};
 use jsonrpc_core::Error;
 use storage;
-use global_script::Script;
+use global_script::{Script, Builder as ScriptBuilder};
 use chain::OutPoint;
 use verification;
 use ser::serialize;
-use network::{Network, ConsensusParams};
+use network::{Network, NetworkUpgrade, ConsensusParams};
 use primitives::hash::H256 as GlobalH256;
+use primitives::timings;
+
+/// Maximal number of blocks, fee history of which could be requested at once.
+const MAX_FEE_HISTORY_BLOCKS: u32 = 1008;
+/// Maximal number of chain events that could be requested at once.
+const MAX_CHAIN_EVENTS: u32 = 1000;
 
 pub struct BlockChainClient<T: BlockChainClientCoreApi> {
 	core: T,
@@ -21,17 +40,30 @@ pub struct BlockChainClient<T: BlockChainClientCoreApi> {
 
 pub trait BlockChainClientCoreApi: Send + Sync + 'static {
 	fn best_block_hash(&self) -> GlobalH256;
+	fn blockchain_info(&self) -> GetBlockChainInfoResponse;
+	fn consensus_params(&self) -> GetConsensusParamsResponse;
 	fn block_count(&self) -> u32;
 	fn block_hash(&self, height: u32) -> Option<GlobalH256>;
 	fn difficulty(&self) -> f64;
 	fn raw_block(&self, hash: GlobalH256) -> Option<RawBlock>;
 	fn verbose_block(&self, hash: GlobalH256) -> Option<VerboseBlock>;
 	fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error>;
+	fn shielded_stats(&self, from: u32, to: Option<u32>) -> Result<GetShieldedStatsResponse, Error>;
+	fn block_stats(&self, hash: GlobalH256) -> Option<GetBlockStatsResponse>;
+	fn fee_history(&self, blocks: u32, to: Option<u32>) -> Result<GetFeeHistoryResponse, Error>;
+	fn chain_events(&self, from_sequence: u64, count: u32) -> Result<GetChainEventsResponse, Error>;
+	fn validation_timings(&self) -> GetValidationTimingsResponse;
+	fn tree_state(&self, hash: GlobalH256) -> Option<GetTreeStateResponse>;
+	fn address_balance(&self, addresses: Vec<Address>) -> Result<GetAddressBalanceResponse, Error>;
+	fn address_tx_ids(&self, addresses: Vec<Address>, start: Option<u32>, end: Option<u32>) -> Result<Vec<GlobalH256>, Error>;
+	fn spent_info(&self, outpoint: OutPoint) -> Result<GetSpentInfoResponse, Error>;
+	fn db_info(&self) -> GetDbInfoResponse;
 }
 
 pub struct BlockChainClientCore {
 	consensus: ConsensusParams,
 	storage: storage::SharedStore,
+	deployments: verification::Deployments,
 }
 
 impl BlockChainClientCore {
@@ -39,7 +71,35 @@ impl BlockChainClientCore {
 		BlockChainClientCore {
 			consensus: consensus,
 			storage: storage,
+			deployments: verification::Deployments::new(),
+		}
+	}
+
+	fn block_fee_stats(&self, block_hash: &GlobalH256) -> storage::FeeStats {
+		// blocks that have been canonized before fee stats were introduced have no stored stats
+		self.storage.block_fee_stats(block_hash).unwrap_or_else(|| {
+			let transactions = self.storage.block_transactions(block_hash.clone().into());
+			storage::FeeStats::from_transactions(self.storage.as_transaction_output_provider(),
+				transactions.iter().enumerate().skip(1).map(|(tx_index, tx)| (tx_index, &tx.raw)))
+		})
+	}
+
+	/// Address index entries of all given addresses.
+	fn address_index_entries(&self, addresses: &[Address]) -> Result<Vec<storage::AddressIndexEntry>, Error> {
+		let index = self.storage.as_address_index_provider();
+		if !index.is_address_index_enabled() {
+			return Err(address_index_disabled());
 		}
+
+		Ok(addresses.iter()
+			.flat_map(|address| {
+				let script = match address.kind {
+					keys::Type::P2PKH => ScriptBuilder::build_p2pkh(&address.hash),
+					keys::Type::P2SH => ScriptBuilder::build_p2sh(&address.hash),
+				};
+				index.address_index_entries(&storage::address_index_key(&script.to_bytes()))
+			})
+			.collect())
 	}
 }
 
@@ -48,6 +108,120 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 		self.storage.best_block().hash
 	}
 
+	fn blockchain_info(&self) -> GetBlockChainInfoResponse {
+		let snapshot = self.storage.read_snapshot();
+		let best_block = snapshot.best_block();
+		let best_header = self.storage.block_header(best_block.hash.clone().into())
+			.expect("best block header should be in db; qed");
+
+		let upgrades = self.consensus.network_upgrades().into_iter()
+			.map(|(upgrade, activation_height)| {
+				let status = if best_block.number >= activation_height {
+					NetworkUpgradeStatus::Active
+				} else {
+					NetworkUpgradeStatus::Pending
+				};
+
+				(format!("{:08x}", upgrade.branch_id()), NetworkUpgradeInfo {
+					name: upgrade.name().to_owned(),
+					activationheight: activation_height,
+					status: status,
+				})
+			})
+			.collect();
+
+		// deployment state is computed for the block that is going to be appended to the best chain
+		let softforks = self.consensus.deployments().into_iter()
+			.map(|deployment| {
+				let state = self.deployments.threshold_state(
+					deployment,
+					best_block.number + 1,
+					self.storage.as_block_header_provider(),
+					&self.consensus);
+
+				(deployment.name.to_owned(), SoftforkInfo {
+					status: state.name().to_owned(),
+					bit: deployment.bit,
+					start_time: deployment.start_time,
+					timeout: deployment.timeout,
+				})
+			})
+			.collect();
+
+		let value_pools = self.storage.block_value_pools(&best_block.hash);
+		let value_pools = [storage::ValuePool::Sprout, storage::ValuePool::Sapling, storage::ValuePool::Orchard].iter()
+			.map(|pool| {
+				let value = value_pools.map(|value_pools| match *pool {
+					storage::ValuePool::Sprout => value_pools.sprout,
+					storage::ValuePool::Sapling => value_pools.sapling,
+					storage::ValuePool::Orchard => value_pools.orchard,
+				});
+
+				ValuePoolInfo {
+					id: pool.name().to_owned(),
+					monitored: value.is_some(),
+					chain_value: value.map(|value| 0.00000001f64 * (value as f64)),
+					chain_value_zat: value,
+				}
+			})
+			.collect();
+
+		GetBlockChainInfoResponse {
+			chain: chain_name(self.consensus.network).to_owned(),
+			blocks: best_block.number,
+			bestblockhash: best_block.hash.clone().into(),
+			difficulty: best_header.raw.bits.to_f64(self.consensus.network.max_bits().into()),
+			upgrades: upgrades,
+			softforks: softforks,
+			consensus: ConsensusInfo {
+				chaintip: format!("{:08x}", self.consensus.consensus_branch_id(best_block.number)),
+				nextblock: format!("{:08x}", self.consensus.consensus_branch_id(best_block.number + 1)),
+			},
+			value_pools: value_pools,
+		}
+	}
+
+	fn consensus_params(&self) -> GetConsensusParamsResponse {
+		let consensus = &self.consensus;
+		let upgrades = consensus.network_upgrades().into_iter()
+			.map(|(upgrade, activation_height)| (format!("{:08x}", upgrade.branch_id()), NetworkUpgradeParams {
+				name: upgrade.name().to_owned(),
+				activationheight: activation_height,
+			}))
+			.collect();
+		let funding_streams = consensus.funding_streams.iter()
+			.map(|stream| FundingStreamParams {
+				recipient: stream.receiver.name().to_owned(),
+				numerator: stream.numerator,
+				denominator: stream.denominator,
+				startheight: stream.start_height,
+				endheight: stream.end_height,
+				addresses: stream.addresses.iter().map(|address| address.to_string()).collect(),
+			})
+			.collect();
+
+		GetConsensusParamsResponse {
+			chain: chain_name(consensus.network).to_owned(),
+			powlimit: consensus.network.max_bits().into(),
+			powaveragingwindow: consensus.pow_averaging_window,
+			powmaxadjustdown: consensus.pow_max_adjust_down,
+			powmaxadjustup: consensus.pow_max_adjust_up,
+			powtargetspacing: consensus.pow_target_spacing,
+			postblossompowtargetspacing: consensus.upgrades.activation_height(NetworkUpgrade::Blossom)
+				.map(|height| consensus.target_spacing(height)),
+			powallowmindifficultyafterheight: consensus.pow_allow_min_difficulty_after_height,
+			equihashn: consensus.equihash_params.map(|(n, _)| n),
+			equihashk: consensus.equihash_params.map(|(_, k)| k),
+			subsidyslowstartinterval: consensus.subsidy_slow_start_interval,
+			subsidyhalvinginterval: consensus.subsidy_halving_interval,
+			maxblocksize: consensus.block_limits.max_block_size,
+			maxblocksigops: consensus.block_limits.max_block_sigops,
+			maxtransactionvalue: consensus.max_transaction_value().zatoshis(),
+			upgrades: upgrades,
+			fundingstreams: funding_streams,
+		}
+	}
+
 	fn block_count(&self) -> u32 {
 		self.storage.best_block().number
 	}
@@ -57,11 +231,12 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 	}
 
 	fn difficulty(&self) -> f64 {
-		let best_block = self.storage.best_block();
+		let snapshot = self.storage.read_snapshot();
+		let best_block = snapshot.best_block();
 		let now = ::time::get_time().sec as u32;
 
 		let next_work_required = verification::work_required(
-			best_block.hash,
+			best_block.hash.clone(),
 			now,
 			best_block.number + 1,
 			self.storage.as_block_header_provider(),
@@ -78,11 +253,13 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 	}
 
 	fn verbose_block(&self, hash: GlobalH256) -> Option<VerboseBlock> {
+		let snapshot = self.storage.read_snapshot();
+		let best_block = snapshot.best_block();
 		self.storage.block(hash.into())
 			.map(|block| {
 				let height = self.storage.block_number(block.hash());
 				let confirmations = match height {
-					Some(block_number) => (self.storage.best_block().number - block_number + 1) as i64,
+					Some(block_number) => (best_block.number - block_number + 1) as i64,
 					None => -1,
 				};
 				let block_size = block.size();
@@ -99,6 +276,7 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 					merkleroot: block.header.raw.merkle_root_hash.clone().into(),
 					finalsaplingroot: block.header.raw.final_sapling_root.into(),
 					nonce: block.header.raw.nonce.clone().into(),
+					solution: block.header.raw.solution.as_ref().into(),
 					time: block.header.raw.time,
 					tx: block.transactions.into_iter().map(|t| t.hash.into()).collect(),
 					version: block.header.raw.version,
@@ -107,6 +285,8 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 	}
 
 	fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
+		let snapshot = self.storage.read_snapshot();
+		let best_block = snapshot.best_block();
 		let transaction = match self.storage.transaction(&prev_out.hash) {
 			Some(transaction) => transaction,
 			// no transaction => no response
@@ -129,7 +309,6 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 			None => return Err(transaction_not_found(prev_out.hash)),
 		};
 
-		let best_block = self.storage.best_block();
 		if best_block.number < meta.height() {
 			// this is possible during reorgs
 			return Err(transaction_not_found(prev_out.hash));
@@ -164,6 +343,230 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
 			coinbase: transaction.raw.is_coinbase(),
 		})
 	}
+
+	fn shielded_stats(&self, from: u32, to: Option<u32>) -> Result<GetShieldedStatsResponse, Error> {
+		// snapshot is only held while the last block of the range is found, so that long ranges don't block
+		// canonization. Other blocks are found by their parent hashes => they're still blocks of the snapshot
+		let (to, mut block_hash) = {
+			let snapshot = self.storage.read_snapshot();
+			let best_block = snapshot.best_block();
+			let to = to.unwrap_or(best_block.number);
+			if to > best_block.number {
+				return Err(block_at_height_not_found(to));
+			}
+			if from > to {
+				return Err(invalid_params("from", from));
+			}
+
+			let block_hash = snapshot.store().block_hash(to)
+				.ok_or_else(|| block_at_height_not_found(to))?;
+			(to, block_hash)
+		};
+
+		let mut stats = storage::ShieldedStats::default();
+		for height in (from..to + 1).rev() {
+			// blocks, imported from the snapshot, have no stats
+			let block_stats = self.storage.block_shielded_stats(&block_hash)
+				.ok_or_else(|| execution(format!("Shielded stats of block {} are unknown", height)))?;
+			stats.add(&block_stats);
+
+			if height != from {
+				block_hash = self.storage.block_header(block_hash.into())
+					.ok_or_else(|| block_at_height_not_found(height - 1))?
+					.raw.previous_header_hash;
+			}
+		}
+
+		Ok(GetShieldedStatsResponse {
+			from: from,
+			to: to,
+			sprout: ShieldedPoolStats {
+				transactions: stats.sprout_transactions,
+				valuein: 0.00000001f64 * (stats.sprout_value_in as f64),
+				valueout: 0.00000001f64 * (stats.sprout_value_out as f64),
+			},
+			sapling: ShieldedPoolStats {
+				transactions: stats.sapling_transactions,
+				valuein: 0.00000001f64 * (stats.sapling_value_in as f64),
+				valueout: 0.00000001f64 * (stats.sapling_value_out as f64),
+			},
+			shielding: stats.shielding_transactions,
+			deshielding: stats.deshielding_transactions,
+			fullyshielded: stats.fully_shielded_transactions,
+		})
+	}
+
+	fn block_stats(&self, hash: GlobalH256) -> Option<GetBlockStatsResponse> {
+		let height = self.storage.block_number(&hash)?;
+		let txs = self.storage.block_transaction_hashes(hash.clone().into()).len() as u32;
+		let stats = self.block_fee_stats(&hash);
+		Some(GetBlockStatsResponse {
+			hash: hash.into(),
+			height: height,
+			txs: txs,
+			totalfee: stats.total_fee,
+			totalsize: stats.total_size,
+			avgfeerate: stats.average_feerate(),
+			minfeerate: stats.min_feerate,
+			medianfeerate: stats.median_feerate,
+			feerate90: stats.percentile_90_feerate,
+			maxfeerate: stats.max_feerate,
+		})
+	}
+
+	fn fee_history(&self, blocks: u32, to: Option<u32>) -> Result<GetFeeHistoryResponse, Error> {
+		if blocks == 0 || blocks > MAX_FEE_HISTORY_BLOCKS {
+			return Err(invalid_params("blocks", blocks));
+		}
+
+		let snapshot = self.storage.read_snapshot();
+		let best_block = snapshot.best_block();
+		let to = to.unwrap_or(best_block.number);
+		if to > best_block.number {
+			return Err(block_at_height_not_found(to));
+		}
+
+		let from = to.saturating_sub(blocks - 1);
+		let blocks = (from..to + 1)
+			.map(|height| {
+				let block_hash = self.storage.block_hash(height)
+					.ok_or_else(|| block_at_height_not_found(height))?;
+				let stats = self.block_fee_stats(&block_hash);
+				Ok(BlockFeeRates {
+					height: height,
+					txs: stats.transactions,
+					minfeerate: stats.min_feerate,
+					medianfeerate: stats.median_feerate,
+					feerate90: stats.percentile_90_feerate,
+				})
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		Ok(GetFeeHistoryResponse {
+			from: from,
+			to: to,
+			blocks: blocks,
+		})
+	}
+
+	fn chain_events(&self, from_sequence: u64, count: u32) -> Result<GetChainEventsResponse, Error> {
+		if from_sequence == 0 {
+			return Err(invalid_params("fromsequence", from_sequence));
+		}
+		if count == 0 || count > MAX_CHAIN_EVENTS {
+			return Err(invalid_params("count", count));
+		}
+
+		let events = self.storage.as_chain_event_provider();
+		Ok(GetChainEventsResponse {
+			lastsequence: events.last_chain_event_sequence(),
+			events: events.chain_events(from_sequence, count as usize).into_iter()
+				.map(|event| ChainEventInfo {
+					sequence: event.sequence,
+					event_type: match event.kind {
+						storage::ChainEventKind::Connected => ChainEventType::Connected,
+						storage::ChainEventKind::Disconnected => ChainEventType::Disconnected,
+					},
+					hash: event.block_hash.into(),
+					height: event.block_number,
+					tx: event.transactions.into_iter().map(Into::into).collect(),
+				})
+				.collect(),
+		})
+	}
+
+	fn validation_timings(&self) -> GetValidationTimingsResponse {
+		GetValidationTimingsResponse::new(timings::timings())
+	}
+
+	fn tree_state(&self, hash: GlobalH256) -> Option<GetTreeStateResponse> {
+		let height = self.storage.block_number(&hash)?;
+		let header = self.storage.block_header(hash.clone().into())?;
+		// roots of pruned blocks are unknown
+		let tree_states = self.storage.as_tree_state_provider();
+		let sprout_root = tree_states.sprout_block_root(&hash)?;
+		let sprout_tree = tree_states.sprout_tree_at(&sprout_root)?;
+		let sapling_root = tree_states.sapling_block_root(&hash)?;
+		let sapling_tree = tree_states.sapling_tree_at(&sapling_root)?;
+
+		Some(GetTreeStateResponse {
+			hash: hash.into(),
+			height: height,
+			time: header.raw.time,
+			sprout: TreeStateInfo {
+				commitments: TreeCommitments {
+					final_root: sprout_root.into(),
+					final_state: sprout_tree.to_zcashd_bytes().into(),
+				},
+			},
+			sapling: TreeStateInfo {
+				commitments: TreeCommitments {
+					final_root: sapling_root.into(),
+					final_state: sapling_tree.to_zcashd_bytes().into(),
+				},
+			},
+		})
+	}
+
+	fn address_balance(&self, addresses: Vec<Address>) -> Result<GetAddressBalanceResponse, Error> {
+		let entries = self.address_index_entries(&addresses)?;
+		Ok(GetAddressBalanceResponse {
+			balance: entries.iter().filter(|entry| !entry.spent).map(|entry| entry.value).sum(),
+			received: entries.iter().map(|entry| entry.value).sum(),
+		})
+	}
+
+	fn address_tx_ids(&self, addresses: Vec<Address>, start: Option<u32>, end: Option<u32>) -> Result<Vec<GlobalH256>, Error> {
+		let mut entries = self.address_index_entries(&addresses)?;
+		entries.retain(|entry| entry.height >= start.unwrap_or(0) && entry.height <= end.unwrap_or(u32::max_value()));
+		entries.sort_by_key(|entry| entry.height);
+
+		let mut tx_ids = HashSet::new();
+		Ok(entries.into_iter()
+			.map(|entry| entry.tx_hash)
+			.filter(|tx_hash| tx_ids.insert(tx_hash.clone()))
+			.collect())
+	}
+
+	fn spent_info(&self, outpoint: OutPoint) -> Result<GetSpentInfoResponse, Error> {
+		let index = self.storage.as_spent_index_provider();
+		if !index.is_spent_index_enabled() {
+			return Err(spent_index_disabled());
+		}
+
+		index.spent_index_entry(&outpoint)
+			.map(|entry| GetSpentInfoResponse {
+				txid: entry.tx_hash.into(),
+				index: entry.input_index,
+				height: entry.height,
+			})
+			.ok_or(spent_info_not_found(outpoint))
+	}
+
+	fn db_info(&self) -> GetDbInfoResponse {
+		let info = self.storage.as_database_info_provider().database_info();
+		GetDbInfoResponse {
+			ondisk: info.on_disk,
+			size: info.size,
+			blockfiles: info.block_files,
+			blockfilessize: info.block_files_size,
+			cachesize: info.cache_size,
+			writebuffersize: info.write_buffer_size,
+			compactionstyle: info.compaction_style,
+			maxopenfiles: info.max_open_files,
+		}
+	}
+}
+
+/// Name of the network, as reported by RPC.
+fn chain_name(network: Network) -> &'static str {
+	match network {
+		Network::Mainnet => "main",
+		Network::Testnet => "test",
+		Network::Regtest => "regtest",
+		Network::Unitest => "unitest",
+		Network::Custom(spec) => spec.name.as_str(),
+	}
 }
 
 impl<T> BlockChainClient<T> where T: BlockChainClientCoreApi {
@@ -179,6 +582,16 @@ impl<T> BlockChain for BlockChainClient<T> where T: BlockChainClientCoreApi {
 		Ok(self.core.best_block_hash().reversed().into())
 	}
 
+	fn blockchain_info(&self) -> Result<GetBlockChainInfoResponse, Error> {
+		let mut info = self.core.blockchain_info();
+		info.bestblockhash = info.bestblockhash.reversed();
+		Ok(info)
+	}
+
+	fn consensus_params(&self) -> Result<GetConsensusParamsResponse, Error> {
+		Ok(self.core.consensus_params())
+	}
+
 	fn block_count(&self) -> Result<u32, Error> {
 		Ok(self.core.block_count())
 	}
@@ -245,6 +658,90 @@ impl<T> BlockChain for BlockChainClient<T> where T: BlockChainClientCoreApi {
 	fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error> {
 		rpc_unimplemented!()
 	}
+
+	fn shielded_stats(&self, from: u32, to: Option<u32>) -> Result<GetShieldedStatsResponse, Error> {
+		self.core.shielded_stats(from, to)
+	}
+
+	fn block_stats(&self, block: BlockRef) -> Result<GetBlockStatsResponse, Error> {
+		let global_hash = match block {
+			BlockRef::Number(number) => self.core
+				.block_hash(number)
+				.ok_or(block_not_found(number))?,
+			BlockRef::Hash(hash) => {
+				let h: GlobalH256 = hash.into();
+				h.reversed()
+			},
+		};
+
+		self.core.block_stats(global_hash)
+			.map(|mut stats| {
+				stats.hash = stats.hash.reversed();
+				stats
+			})
+			.ok_or(block_not_found(global_hash.reversed()))
+	}
+
+	fn fee_history(&self, blocks: u32, to: Option<u32>) -> Result<GetFeeHistoryResponse, Error> {
+		self.core.fee_history(blocks, to)
+	}
+
+	fn chain_events(&self, from_sequence: u64, count: Option<u32>) -> Result<GetChainEventsResponse, Error> {
+		let mut response = self.core.chain_events(from_sequence, count.unwrap_or(MAX_CHAIN_EVENTS))?;
+		for event in &mut response.events {
+			event.hash = event.hash.reversed();
+			event.tx = event.tx.iter().map(|h| h.reversed()).collect();
+		}
+		Ok(response)
+	}
+
+	fn validation_timings(&self) -> Result<GetValidationTimingsResponse, Error> {
+		Ok(self.core.validation_timings())
+	}
+
+	fn tree_state(&self, block: BlockRef) -> Result<GetTreeStateResponse, Error> {
+		let global_hash = match block {
+			BlockRef::Number(number) => self.core
+				.block_hash(number)
+				.ok_or(block_not_found(number))?,
+			BlockRef::Hash(hash) => {
+				let h: GlobalH256 = hash.into();
+				h.reversed()
+			},
+		};
+
+		self.core.tree_state(global_hash)
+			.map(|mut tree_state| {
+				tree_state.hash = tree_state.hash.reversed();
+				tree_state.sprout.commitments.final_root = tree_state.sprout.commitments.final_root.reversed();
+				tree_state.sapling.commitments.final_root = tree_state.sapling.commitments.final_root.reversed();
+				tree_state
+			})
+			.ok_or(block_not_found(global_hash.reversed()))
+	}
+
+	fn address_balance(&self, request: AddressRequest) -> Result<GetAddressBalanceResponse, Error> {
+		self.core.address_balance(request.addresses)
+	}
+
+	fn address_tx_ids(&self, request: AddressRequest) -> Result<Vec<H256>, Error> {
+		self.core.address_tx_ids(request.addresses, request.start, request.end)
+			.map(|tx_ids| tx_ids.into_iter().map(|tx_id| tx_id.reversed().into()).collect())
+	}
+
+	fn spent_info(&self, request: SpentInfoRequest) -> Result<GetSpentInfoResponse, Error> {
+		let transaction_hash: GlobalH256 = request.txid.into();
+		self.core.spent_info(OutPoint { hash: transaction_hash.reversed(), index: request.index })
+			.map(|mut response| {
+				let spending_hash: GlobalH256 = response.txid.into();
+				response.txid = spending_hash.reversed().into();
+				response
+			})
+	}
+
+	fn db_info(&self) -> Result<GetDbInfoResponse, Error> {
+		Ok(self.core.db_info())
+	}
 }
 
 #[cfg(test)]
@@ -252,9 +749,11 @@ pub mod tests {
 	extern crate test_data;
 
 	use std::sync::Arc;
+	use std::collections::BTreeMap;
 	use jsonrpc_core::IoHandler;
 	use jsonrpc_core::Error;
 	use db::{BlockChainDatabase};
+	use db::kv::MemoryDatabase;
 	use primitives::bytes::Bytes as GlobalBytes;
 	use primitives::hash::H256 as GlobalH256;
 	use v1::types::{VerboseBlock, RawBlock};
@@ -264,8 +763,11 @@ pub mod tests {
 	use v1::types::Bytes;
 	use v1::types::H256;
 	use v1::types::ScriptType;
+	use v1::types::StageTimings;
+	use v1::types::U256;
 	use chain::OutPoint;
 	use network::Network;
+	use storage::{SproutTreeState, SaplingTreeState};
 	use super::*;
 
 	#[derive(Default)]
@@ -278,6 +780,39 @@ pub mod tests {
 			test_data::genesis().hash()
 		}
 
+		fn blockchain_info(&self) -> GetBlockChainInfoResponse {
+			let mut upgrades = BTreeMap::new();
+			upgrades.insert("5ba81b19".to_owned(), NetworkUpgradeInfo {
+				name: "Overwinter".to_owned(),
+				activationheight: 347500,
+				status: NetworkUpgradeStatus::Pending,
+			});
+
+			GetBlockChainInfoResponse {
+				chain: "main".to_owned(),
+				blocks: 1,
+				bestblockhash: test_data::genesis().hash().into(),
+				difficulty: 1.0,
+				upgrades: upgrades,
+				softforks: BTreeMap::new(),
+				consensus: ConsensusInfo {
+					chaintip: "00000000".to_owned(),
+					nextblock: "00000000".to_owned(),
+				},
+				value_pools: Vec::new(),
+			}
+		}
+
+		fn consensus_params(&self) -> GetConsensusParamsResponse {
+			GetConsensusParamsResponse {
+				chain: "main".to_owned(),
+				powtargetspacing: 150,
+				equihashn: Some(200),
+				equihashk: Some(9),
+				..Default::default()
+			}
+		}
+
 		fn block_count(&self) -> u32 {
 			1
 		}
@@ -309,6 +844,7 @@ pub mod tests {
 				tx: vec!["d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9b".into()],
 				time: 1231469744,
 				nonce: 42.into(),
+				solution: Bytes::new(vec![0x42]),
 				bits: 486604799,
 				difficulty: 1.0,
 				previousblockhash: Some("4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000".into()),
@@ -333,6 +869,124 @@ pub mod tests {
 				coinbase: false,
 			})
 		}
+
+		fn shielded_stats(&self, from: u32, to: Option<u32>) -> Result<GetShieldedStatsResponse, Error> {
+			Ok(GetShieldedStatsResponse {
+				from: from,
+				to: to.unwrap_or(from),
+				sprout: ShieldedPoolStats {
+					transactions: 1,
+					valuein: 0.5,
+					valueout: 0.0,
+				},
+				shielding: 1,
+				..Default::default()
+			})
+		}
+
+		fn block_stats(&self, hash: GlobalH256) -> Option<GetBlockStatsResponse> {
+			Some(GetBlockStatsResponse {
+				hash: hash.into(),
+				height: 2,
+				txs: 2,
+				totalfee: 1_000,
+				totalsize: 250,
+				avgfeerate: 4_000,
+				minfeerate: 4_000,
+				medianfeerate: 4_000,
+				feerate90: 4_000,
+				maxfeerate: 4_000,
+			})
+		}
+
+		fn fee_history(&self, blocks: u32, to: Option<u32>) -> Result<GetFeeHistoryResponse, Error> {
+			let to = to.unwrap_or(10);
+			Ok(GetFeeHistoryResponse {
+				from: to + 1 - blocks,
+				to: to,
+				blocks: (to + 1 - blocks..to + 1).map(|height| BlockFeeRates {
+					height: height,
+					txs: 1,
+					minfeerate: 1_000,
+					medianfeerate: 1_000,
+					feerate90: 1_000,
+				}).collect(),
+			})
+		}
+
+		fn chain_events(&self, from_sequence: u64, _count: u32) -> Result<GetChainEventsResponse, Error> {
+			Ok(GetChainEventsResponse {
+				lastsequence: from_sequence,
+				events: vec![ChainEventInfo {
+					sequence: from_sequence,
+					event_type: ChainEventType::Connected,
+					hash: test_data::genesis().hash().into(),
+					height: 0,
+					tx: vec![H256::from(1)],
+				}],
+			})
+		}
+
+		fn validation_timings(&self) -> GetValidationTimingsResponse {
+			GetValidationTimingsResponse {
+				windowblocks: 1000,
+				windows: 2,
+				currentblocks: 10,
+				stages: vec![StageTimings {
+					name: "databasewrite".to_owned(),
+					current: 5,
+					last: 400,
+					total: 900,
+				}],
+			}
+		}
+
+		fn tree_state(&self, hash: GlobalH256) -> Option<GetTreeStateResponse> {
+			Some(GetTreeStateResponse {
+				hash: hash.into(),
+				height: 2,
+				time: 1_600_000_000,
+				sprout: TreeStateInfo {
+					commitments: TreeCommitments {
+						final_root: H256::from(1),
+						final_state: Bytes::new(vec![0, 0, 0]),
+					},
+				},
+				sapling: TreeStateInfo::default(),
+			})
+		}
+
+		fn address_balance(&self, _addresses: Vec<Address>) -> Result<GetAddressBalanceResponse, Error> {
+			Ok(GetAddressBalanceResponse {
+				balance: 1_000,
+				received: 5_000,
+			})
+		}
+
+		fn address_tx_ids(&self, _addresses: Vec<Address>, _start: Option<u32>, _end: Option<u32>) -> Result<Vec<GlobalH256>, Error> {
+			Ok(vec![GlobalH256::from(1)])
+		}
+
+		fn spent_info(&self, _outpoint: OutPoint) -> Result<GetSpentInfoResponse, Error> {
+			Ok(GetSpentInfoResponse {
+				txid: H256::from(1),
+				index: 2,
+				height: 100,
+			})
+		}
+
+		fn db_info(&self) -> GetDbInfoResponse {
+			GetDbInfoResponse {
+				ondisk: true,
+				size: 1_000,
+				blockfiles: 2,
+				blockfilessize: 2_000,
+				cachesize: 512,
+				writebuffersize: 64,
+				compactionstyle: "universal".into(),
+				maxopenfiles: 512,
+			}
+		}
 	}
 
 	impl BlockChainClientCoreApi for ErrorBlockChainClientCore {
@@ -340,6 +994,14 @@ pub mod tests {
 			test_data::genesis().hash()
 		}
 
+		fn blockchain_info(&self) -> GetBlockChainInfoResponse {
+			GetBlockChainInfoResponse::default()
+		}
+
+		fn consensus_params(&self) -> GetConsensusParamsResponse {
+			GetConsensusParamsResponse::default()
+		}
+
 		fn block_count(&self) -> u32 {
 			1
 		}
@@ -363,6 +1025,46 @@ pub mod tests {
 		fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
 			Err(block_not_found(prev_out.hash))
 		}
+
+		fn shielded_stats(&self, _from: u32, to: Option<u32>) -> Result<GetShieldedStatsResponse, Error> {
+			Err(block_at_height_not_found(to.unwrap_or_default()))
+		}
+
+		fn block_stats(&self, _hash: GlobalH256) -> Option<GetBlockStatsResponse> {
+			None
+		}
+
+		fn fee_history(&self, _blocks: u32, to: Option<u32>) -> Result<GetFeeHistoryResponse, Error> {
+			Err(block_at_height_not_found(to.unwrap_or_default()))
+		}
+
+		fn chain_events(&self, _from_sequence: u64, count: u32) -> Result<GetChainEventsResponse, Error> {
+			Err(invalid_params("count", count))
+		}
+
+		fn validation_timings(&self) -> GetValidationTimingsResponse {
+			GetValidationTimingsResponse::default()
+		}
+
+		fn tree_state(&self, _hash: GlobalH256) -> Option<GetTreeStateResponse> {
+			None
+		}
+
+		fn address_balance(&self, _addresses: Vec<Address>) -> Result<GetAddressBalanceResponse, Error> {
+			Err(address_index_disabled())
+		}
+
+		fn address_tx_ids(&self, _addresses: Vec<Address>, _start: Option<u32>, _end: Option<u32>) -> Result<Vec<GlobalH256>, Error> {
+			Err(address_index_disabled())
+		}
+
+		fn spent_info(&self, _outpoint: OutPoint) -> Result<GetSpentInfoResponse, Error> {
+			Err(spent_index_disabled())
+		}
+
+		fn db_info(&self) -> GetDbInfoResponse {
+			GetDbInfoResponse::default()
+		}
 	}
 
 	#[test]
@@ -382,6 +1084,116 @@ pub mod tests {
 		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":"00040fe8ec8471911baa1db1266ea15dd06b4a8a5c453883c000b031973dce08","id":1}"#);
 	}
 
+	#[test]
+	fn blockchain_info_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockchaininfo",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"chain":"main","blocks":1,"bestblockhash":"00040fe8ec8471911baa1db1266ea15dd06b4a8a5c453883c000b031973dce08","difficulty":1.0,"upgrades":{"5ba81b19":{"name":"Overwinter","activationheight":347500,"status":"pending"}},"softforks":{},"consensus":{"chaintip":"00000000","nextblock":"00000000"},"valuePools":[]},"id":1}"#);
+	}
+
+	#[test]
+	fn blockchain_info_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		let info = core.blockchain_info();
+		assert_eq!(info.chain, "main");
+		assert_eq!(info.blocks, 1);
+		assert_eq!(info.bestblockhash, "8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700".into());
+		assert_eq!(info.upgrades.len(), 6);
+		assert_eq!(info.upgrades["5ba81b19"], NetworkUpgradeInfo {
+			name: "Overwinter".to_owned(),
+			activationheight: 347500,
+			status: NetworkUpgradeStatus::Pending,
+		});
+		assert_eq!(info.upgrades["76b809bb"], NetworkUpgradeInfo {
+			name: "Sapling".to_owned(),
+			activationheight: 419200,
+			status: NetworkUpgradeStatus::Pending,
+		});
+		assert_eq!(info.upgrades["c2d6d0b4"], NetworkUpgradeInfo {
+			name: "NU5".to_owned(),
+			activationheight: 1687104,
+			status: NetworkUpgradeStatus::Pending,
+		});
+		assert!(info.softforks.is_empty());
+		assert_eq!(info.consensus, ConsensusInfo {
+			chaintip: "00000000".to_owned(),
+			nextblock: "00000000".to_owned(),
+		});
+		assert_eq!(info.value_pools.len(), 3);
+		assert_eq!(info.value_pools[0], ValuePoolInfo {
+			id: "sprout".to_owned(),
+			monitored: true,
+			chain_value: Some(0.0),
+			chain_value_zat: Some(0),
+		});
+	}
+
+	#[test]
+	fn consensus_params_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getconsensusparams",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"chain":"main","powlimit":"0","powaveragingwindow":0,"powmaxadjustdown":0,"powmaxadjustup":0,"powtargetspacing":150,"postblossompowtargetspacing":null,"powallowmindifficultyafterheight":null,"equihashn":200,"equihashk":9,"subsidyslowstartinterval":0,"subsidyhalvinginterval":0,"maxblocksize":0,"maxblocksigops":0,"maxtransactionvalue":0,"upgrades":{},"fundingstreams":[]},"id":1}"#);
+	}
+
+	#[test]
+	fn consensus_params_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		let params = core.consensus_params();
+		assert_eq!(params.chain, "main");
+		assert_eq!(params.powlimit, U256::from(Network::Mainnet.max_bits()));
+		assert_eq!(params.powtargetspacing, 150);
+		assert_eq!(params.postblossompowtargetspacing, Some(75));
+		assert_eq!(params.equihashn, Some(200));
+		assert_eq!(params.equihashk, Some(9));
+		assert_eq!(params.subsidyhalvinginterval, 840000);
+		assert_eq!(params.maxblocksize, 2000000);
+		assert_eq!(params.upgrades.len(), 6);
+		assert_eq!(params.upgrades["76b809bb"], NetworkUpgradeParams {
+			name: "Sapling".to_owned(),
+			activationheight: 419200,
+		});
+		assert_eq!(params.fundingstreams.len(), 3);
+		assert_eq!(params.fundingstreams[2].recipient, "Major Grants");
+		assert_eq!(params.fundingstreams[2].numerator, 8);
+		assert_eq!(params.fundingstreams[2].startheight, 1046400);
+		assert_eq!(params.fundingstreams[2].addresses[0], "t3XyYW8yBFRuMnfvm5KLGFbEVz25kckZXym");
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Regtest), Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()])));
+		let params = core.consensus_params();
+		assert_eq!(params.chain, "regtest");
+		assert_eq!(params.postblossompowtargetspacing, None);
+		assert!(params.fundingstreams.is_empty());
+	}
+
 	#[test]
 	fn block_count_success() {
 		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
@@ -475,6 +1287,7 @@ pub mod tests {
 			tx: vec!["0946edb9c083c9942d92305444527765fad789c438c717783276a9f7fbf61b85".into()],
 			time: 1477671596,
 			nonce: "7534e8cf161ff2e49d54bdb3bfbcde8cdbf2fc5963c9ec7d86aed4a67e975790".into(),
+			solution: test_data::block_h1().header().solution.as_ref().into(),
 			bits: 520617983,
 			difficulty: 1.0,
 			previousblockhash: Some("08ce3d9731b000c08338455c8a4a6bd05da16e26b11daa1b917184ece80f0400".into()),
@@ -495,6 +1308,7 @@ pub mod tests {
 			tx: vec!["f4b084a7c2fc5a5aa2985f2bcb1d4a9a65562a589d628b0d869c5f1c8dd07489".into()],
 			time: 1477671626,
 			nonce: "a5556cd346010000000000000000000000000000000000000000000000000002".into(),
+			solution: test_data::block_h2().header().solution.as_ref().into(),
 			bits: 520617983,
 			difficulty: 1.0,
 			previousblockhash: Some("8392336da29773c56b1649ab555156ceb7e700ad7c230ea7a4571c7e22bc0700".into()),
@@ -544,7 +1358,7 @@ pub mod tests {
 		let mut handler = IoHandler::new();
 		handler.extend_with(client.to_delegate());
 
-		let expected = r#"{"jsonrpc":"2.0","result":{"bits":486604799,"confirmations":1,"difficulty":1.0,"finalsaplingroot":"02000000000000000000000000000000000000000000000000000146d36c55a5","hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":"2a00000000000000000000000000000000000000000000000000000000000000","previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","size":215,"time":1231469744,"tx":["9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5"],"version":1},"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","result":{"bits":486604799,"confirmations":1,"difficulty":1.0,"finalsaplingroot":"02000000000000000000000000000000000000000000000000000146d36c55a5","hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":"2a00000000000000000000000000000000000000000000000000000000000000","previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","size":215,"solution":"42","time":1231469744,"tx":["9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5"],"version":1},"id":1}"#;
 
 		let sample = handler.handle_request_sync(&(r#"
 			{
@@ -607,7 +1421,7 @@ pub mod tests {
 					addresses: vec!["t1KstPVzcNEK4ZeauQ6cogoqxQBMDSiRnGr".into()]
 				},
 				version: 1,
-				coinbase: true
+				coinbase: true,
 			}));
 	}
 
@@ -644,4 +1458,470 @@ pub mod tests {
 
 		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#);
 	}
+
+	#[test]
+	fn shielded_stats_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getshieldedstats",
+				"params": [10, 20],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"from":10,"to":20,"sprout":{"transactions":1,"valuein":0.5,"valueout":0.0},"sapling":{"transactions":0,"valuein":0.0,"valueout":0.0},"shielding":1,"deshielding":0,"fullyshielded":0},"id":1}"#);
+	}
+
+	#[test]
+	fn shielded_stats_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getshieldedstats",
+				"params": [10, 20],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block at given height is not found","data":"20"},"id":1}"#);
+	}
+
+	#[test]
+	fn shielded_stats_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		assert_eq!(core.shielded_stats(0, None), Ok(GetShieldedStatsResponse {
+			from: 0,
+			to: 1,
+			..Default::default()
+		}));
+		assert_eq!(core.shielded_stats(0, Some(2)), Err(block_at_height_not_found(2)));
+		assert_eq!(core.shielded_stats(1, Some(0)), Err(invalid_params("from", 1)));
+	}
+
+	#[test]
+	fn block_stats_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockstats",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"txs":2,"totalfee":1000,"totalsize":250,"avgfeerate":4000,"minfeerate":4000,"medianfeerate":4000,"feerate90":4000,"maxfeerate":4000},"id":1}"#);
+	}
+
+	#[test]
+	fn block_stats_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockstats",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#);
+	}
+
+	#[test]
+	fn fee_history_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getfeehistory",
+				"params": [2, 20],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"from":19,"to":20,"blocks":[{"height":19,"txs":1,"minfeerate":1000,"medianfeerate":1000,"feerate90":1000},{"height":20,"txs":1,"minfeerate":1000,"medianfeerate":1000,"feerate90":1000}]},"id":1}"#);
+	}
+
+	#[test]
+	fn fee_history_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getfeehistory",
+				"params": [2, 20],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block at given height is not found","data":"20"},"id":1}"#);
+	}
+
+	#[test]
+	fn chain_events_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getchainevents",
+				"params": [5],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"lastsequence":5,"events":[{"sequence":5,"type":"connected","hash":"00040fe8ec8471911baa1db1266ea15dd06b4a8a5c453883c000b031973dce08","height":0,"tx":["0000000000000000000000000000000000000000000000000000000000000001"]}]},"id":1}"#);
+	}
+
+	#[test]
+	fn chain_events_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getchainevents",
+				"params": [1, 0],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: count","data":"0"},"id":1}"#);
+	}
+
+	#[test]
+	fn validation_timings_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getvalidationtimings",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"windowblocks":1000,"windows":2,"currentblocks":10,"stages":[{"name":"databasewrite","current":5,"last":400,"total":900}]},"id":1}"#);
+	}
+
+	#[test]
+	fn chain_events_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		let response = core.chain_events(2, 10).unwrap();
+		assert_eq!(response.lastsequence, 2);
+		assert_eq!(response.events, vec![ChainEventInfo {
+			sequence: 2,
+			event_type: ChainEventType::Connected,
+			hash: test_data::block_h1().hash().into(),
+			height: 1,
+			tx: vec![test_data::block_h1().transactions[0].hash().into()],
+		}]);
+		assert_eq!(core.chain_events(3, 10).unwrap().events, vec![]);
+		assert_eq!(core.chain_events(0, 10), Err(invalid_params("fromsequence", 0)));
+		assert_eq!(core.chain_events(1, MAX_CHAIN_EVENTS + 1), Err(invalid_params("count", MAX_CHAIN_EVENTS + 1)));
+	}
+
+	#[test]
+	fn tree_state_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "z_gettreestate",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"time":1600000000,"sprout":{"commitments":{"finalRoot":"0000000000000000000000000000000000000000000000000000000000000001","finalState":"000000"}},"sapling":{"commitments":{"finalRoot":"0000000000000000000000000000000000000000000000000000000000000000","finalState":""}}},"id":1}"#);
+	}
+
+	#[test]
+	fn tree_state_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "z_gettreestate",
+				"params": ["000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#);
+	}
+
+	#[test]
+	fn address_balance_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddressbalance",
+				"params": [{"addresses": ["t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe"]}],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"balance":1000,"received":5000},"id":1}"#);
+	}
+
+	#[test]
+	fn address_balance_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddressbalance",
+				"params": [{"addresses": ["t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe"]}],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32101,"message":"Address index is not enabled (start the node with --addressindex)"},"id":1}"#);
+	}
+
+	#[test]
+	fn address_tx_ids_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddresstxids",
+				"params": [{"addresses": ["t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe"], "start": 1, "end": 10}],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":["0000000000000000000000000000000000000000000000000000000000000001"],"id":1}"#);
+	}
+
+	#[test]
+	fn address_index_contents() {
+		let address: Address = "t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe".into();
+		let script = ScriptBuilder::build_p2pkh(&address.hash).to_bytes();
+		let genesis = test_data::block_builder()
+			.header().build()
+			.transaction().coinbase()
+				.output().value(100).script_pubkey_bytes(script.clone()).build()
+				.build()
+			.build();
+		let block = test_data::block_builder()
+			.header().parent(genesis.hash()).build()
+			.transaction().coinbase()
+				.output().value(40).script_pubkey_bytes(script.clone()).build()
+				.build()
+			.transaction()
+				.input().hash(genesis.transactions[0].hash()).index(0).build()
+				.output().value(60).build()
+				.build()
+			.build();
+		let coinbase_hash = block.transactions[0].hash();
+
+		let storage = Arc::new(BlockChainDatabase::open(MemoryDatabase::default()).with_address_index());
+		for block in vec![genesis.clone(), block] {
+			let hash = block.hash();
+			storage.insert(block.into()).unwrap();
+			storage.canonize(&hash).unwrap();
+		}
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		assert_eq!(core.address_balance(vec![address.clone()]), Ok(GetAddressBalanceResponse {
+			balance: 40,
+			received: 140,
+		}));
+		assert_eq!(core.address_tx_ids(vec![address.clone()], None, None), Ok(vec![genesis.transactions[0].hash(), coinbase_hash]));
+		assert_eq!(core.address_tx_ids(vec![address.clone()], Some(1), None), Ok(vec![coinbase_hash]));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), Arc::new(BlockChainDatabase::init_test_chain(vec![])));
+		assert_eq!(core.address_balance(vec![address]), Err(address_index_disabled()));
+	}
+
+	#[test]
+	fn spent_info_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getspentinfo",
+				"params": [{"txid": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", "index": 0}],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"txid":"0000000000000000000000000000000000000000000000000000000000000001","index":2,"height":100},"id":1}"#);
+	}
+
+	#[test]
+	fn spent_info_error() {
+		let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getspentinfo",
+				"params": [{"txid": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", "index": 0}],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32102,"message":"Spent index is not enabled (start the node with --spentindex)"},"id":1}"#);
+	}
+
+	#[test]
+	fn spent_info_contents() {
+		let genesis = test_data::block_builder()
+			.header().build()
+			.transaction().coinbase()
+				.output().value(100).build()
+				.build()
+			.build();
+		let block = test_data::block_builder()
+			.header().parent(genesis.hash()).build()
+			.transaction().coinbase().build()
+			.transaction()
+				.input().hash(genesis.transactions[0].hash()).index(0).build()
+				.output().value(60).build()
+				.build()
+			.build();
+		let spent = OutPoint { hash: genesis.transactions[0].hash(), index: 0 };
+		let spending_hash = block.transactions[1].hash();
+
+		let storage = Arc::new(BlockChainDatabase::open(MemoryDatabase::default()).with_spent_index());
+		for block in vec![genesis, block] {
+			let hash = block.hash();
+			storage.insert(block.into()).unwrap();
+			storage.canonize(&hash).unwrap();
+		}
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		assert_eq!(core.spent_info(spent.clone()), Ok(GetSpentInfoResponse {
+			txid: spending_hash.into(),
+			index: 0,
+			height: 1,
+		}));
+		assert_eq!(core.spent_info(OutPoint { hash: spending_hash, index: 0 }), Err(spent_info_not_found(OutPoint { hash: spending_hash, index: 0 })));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), Arc::new(BlockChainDatabase::init_test_chain(vec![])));
+		assert_eq!(core.spent_info(spent), Err(spent_index_disabled()));
+	}
+
+	#[test]
+	fn db_info_success() {
+		let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getdbinfo",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"ondisk":true,"size":1000,"blockfiles":2,"blockfilessize":2000,"cachesize":512,"writebuffersize":64,"compactionstyle":"universal","maxopenfiles":512},"id":1}"#);
+	}
+
+	#[test]
+	fn db_info_contents() {
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), Arc::new(BlockChainDatabase::init_test_chain(vec![])));
+		assert_eq!(core.db_info(), GetDbInfoResponse::default());
+	}
+
+	#[test]
+	fn tree_state_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage.clone());
+		let tree_state = core.tree_state(test_data::block_h1().hash()).unwrap();
+		assert_eq!(tree_state.height, 1);
+		assert_eq!(tree_state.time, test_data::block_h1().block_header.time);
+		assert_eq!(tree_state.sprout.commitments.final_root, SproutTreeState::empty_root().into());
+		assert_eq!(tree_state.sprout.commitments.final_state, Bytes::new(vec![0, 0, 0]));
+		assert_eq!(tree_state.sapling.commitments.final_root, SaplingTreeState::empty_root().into());
+		assert_eq!(core.tree_state(test_data::block_h2().hash()), None);
+
+		// tree states of pruned blocks are unknown
+		storage.prune_blocks(1).unwrap();
+		assert_eq!(core.tree_state(test_data::genesis().hash()), None);
+	}
+
+	#[test]
+	fn fee_stats_contents() {
+		let storage = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let core = BlockChainClientCore::new(ConsensusParams::new(Network::Mainnet), storage);
+		assert_eq!(core.block_stats(test_data::block_h1().hash()), Some(GetBlockStatsResponse {
+			hash: test_data::block_h1().hash().into(),
+			height: 1,
+			txs: 1,
+			..Default::default()
+		}));
+		assert_eq!(core.block_stats(test_data::block_h2().hash()), None);
+
+		let history = core.fee_history(10, None).unwrap();
+		assert_eq!((history.from, history.to), (0, 1));
+		assert_eq!(history.blocks.iter().map(|block| block.height).collect::<Vec<_>>(), vec![0, 1]);
+		assert_eq!(core.fee_history(1, Some(2)), Err(block_at_height_not_found(2)));
+		assert_eq!(core.fee_history(0, None), Err(invalid_params("blocks", 0)));
+		assert_eq!(core.fee_history(MAX_FEE_HISTORY_BLOCKS + 1, None), Err(invalid_params("blocks", MAX_FEE_HISTORY_BLOCKS + 1)));
+	}
 }
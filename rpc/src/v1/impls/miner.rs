@@ -1,17 +1,24 @@
-use v1::helpers::errors::execution;
+use v1::helpers::errors::{execution, invalid_params, header_rejected};
 use v1::traits::Miner;
-use v1::types::{BlockTemplate, BlockTemplateRequest};
+use v1::types::{BlockTemplate, BlockTemplateRequest, BlockArrivalStats, Bytes};
 use jsonrpc_core::Error;
+use chain::{BlockHeader, IndexedBlockHeader};
+use ser::deserialize_strict;
 use keys::Address;
 use sync;
 use miner;
 
+/// Default window of block arrival statistics
+const DEFAULT_BLOCK_ARRIVAL_STATS_WINDOW: u32 = 60 * 60;
+
 pub struct MinerClient<T: MinerClientCoreApi> {
 	core: T,
 }
 
 pub trait MinerClientCoreApi: Send + Sync + 'static {
 	fn get_block_template(&self) -> Result<miner::BlockTemplate, String>;
+	fn block_arrival_information(&self, window: u32) -> sync::BlockArrivalInformation;
+	fn accept_header(&self, header: BlockHeader) -> Result<(), sync::HeaderRejection>;
 }
 
 pub struct MinerClientCore {
@@ -34,6 +41,14 @@ impl MinerClientCoreApi for MinerClientCore {
 			.ok_or_else(|| "miner address not set".into())
 			.and_then(|miner_address| self.local_sync_node.get_block_template(miner_address))
 	}
+
+	fn block_arrival_information(&self, window: u32) -> sync::BlockArrivalInformation {
+		self.local_sync_node.block_arrival_information(window)
+	}
+
+	fn accept_header(&self, header: BlockHeader) -> Result<(), sync::HeaderRejection> {
+		self.local_sync_node.accept_header(IndexedBlockHeader::from_raw(header))
+	}
 }
 
 impl<T> MinerClient<T> where T: MinerClientCoreApi {
@@ -50,19 +65,35 @@ impl<T> Miner for MinerClient<T> where T: MinerClientCoreApi {
 			.map(Into::into)
 			.map_err(|err| execution(&err))
 	}
+
+	fn block_arrival_stats(&self, window: Option<u32>) -> Result<BlockArrivalStats, Error> {
+		let window = window.unwrap_or(DEFAULT_BLOCK_ARRIVAL_STATS_WINDOW);
+		Ok(BlockArrivalStats::new(window, self.core.block_arrival_information(window)))
+	}
+
+	fn submit_header(&self, raw_header: Bytes) -> Result<(), Error> {
+		let raw_header: Vec<u8> = raw_header.into();
+		let header = try!(deserialize_strict(&raw_header as &[u8]).map_err(|e| invalid_params("header", e)));
+		self.core.accept_header(header).map_err(header_rejected)
+	}
 }
 
 #[cfg(test)]
 pub mod tests {
+	extern crate test_data;
+
 	use jsonrpc_core::IoHandler;
 	use v1::traits::Miner;
 	use primitives::hash::H256;
 	use chain;
 	use miner;
+	use ser::serialize;
 	use super::*;
 
 	#[derive(Default)]
 	struct SuccessMinerClientCore;
+	#[derive(Default)]
+	struct ErrorMinerClientCore;
 
 	impl MinerClientCoreApi for SuccessMinerClientCore {
 		fn get_block_template(&self) -> Result<miner::BlockTemplate, String> {
@@ -82,6 +113,41 @@ pub mod tests {
 				sigop_limit: 88,
 			})
 		}
+
+		fn block_arrival_information(&self, _window: u32) -> sync::BlockArrivalInformation {
+			sync::BlockArrivalInformation {
+				blocks: 10,
+				out_of_order: 2,
+				stale: 1,
+				orphan_rate: 0.1,
+				delay_median: 2,
+				delay_p90: 5,
+				delay_p99: 7,
+			}
+		}
+
+		fn accept_header(&self, _header: BlockHeader) -> Result<(), sync::HeaderRejection> {
+			Ok(())
+		}
+	}
+
+	impl MinerClientCoreApi for ErrorMinerClientCore {
+		fn get_block_template(&self) -> Result<miner::BlockTemplate, String> {
+			Err("error".into())
+		}
+
+		fn block_arrival_information(&self, _window: u32) -> sync::BlockArrivalInformation {
+			Default::default()
+		}
+
+		fn accept_header(&self, _header: BlockHeader) -> Result<(), sync::HeaderRejection> {
+			Err(sync::HeaderRejection::UnknownParent)
+		}
+	}
+
+	fn submitheader_request(header: &BlockHeader) -> String {
+		let header: Bytes = serialize(header).into();
+		format!(r#"{{"jsonrpc": "2.0", "method": "submitheader", "params": [{}], "id": 1}}"#, ::serde_json::to_string(&header).unwrap())
 	}
 
 	#[test]
@@ -102,4 +168,43 @@ pub mod tests {
 		// but client expects reverse hash
 		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bits":44,"coinbasetxn":{"data":"00000000000000000000","depends":null,"fee":null,"hash":null,"required":false,"sigops":null},"curtime":33,"finalsaplingroothash":"0000000000000000000000000000000000000000000000000000000000000000","height":55,"mintime":null,"mutable":null,"noncerange":null,"previousblockhash":"0000000000000000000000000000000000000000000000000000000000000001","sigoplimit":88,"sizelimit":77,"target":"0000000000000000000000000000000000000000000000000000000000000000","transactions":[{"data":"00000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000000000000101000000000000000000000000","depends":null,"fee":null,"hash":null,"required":false,"sigops":null}],"version":777},"id":1}"#);
 	}
+
+	#[test]
+	fn getblockarrivalstats_accepted() {
+		let client = MinerClient::new(SuccessMinerClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockarrivalstats",
+				"params": [],
+				"id": 1
+			}"#)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"window":3600,"blocks":10,"outoforder":2,"stale":1,"orphanrate":0.1,"delaymedian":2,"delayp90":5,"delayp99":7},"id":1}"#);
+	}
+
+	#[test]
+	fn submitheader_accepted() {
+		let client = MinerClient::new(SuccessMinerClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&submitheader_request(&test_data::block_h1().block_header)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":null,"id":1}"#);
+	}
+
+	#[test]
+	fn submitheader_rejected() {
+		let client = MinerClient::new(ErrorMinerClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&submitheader_request(&test_data::block_h1().block_header)).unwrap();
+
+		assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32100,"message":"Parent header is unknown","data":"prev-blk-not-found"},"id":1}"#);
+	}
 }
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use std::net::{SocketAddr, IpAddr};
 use v1::traits::Network as NetworkRpc;
-use v1::types::{AddNodeOperation, NodeInfo};
+use v1::types::{AddNodeOperation, NodeInfo, PeerConnectionInfo, CrawledPeerInfo};
 use jsonrpc_core::Error;
 use v1::helpers::errors;
 use p2p;
@@ -13,6 +13,9 @@ pub trait NetworkApi : Send + Sync + 'static {
 	fn node_info(&self, node_addr: IpAddr) -> Result<NodeInfo, p2p::NodeTableError>;
 	fn nodes_info(&self) -> Vec<NodeInfo>;
 	fn connection_count(&self) -> usize;
+	fn peer_info(&self) -> Vec<PeerConnectionInfo>;
+	fn reload_config(&self) -> Result<(), String>;
+	fn crawled_peers(&self) -> Option<Vec<CrawledPeerInfo>>;
 }
 
 impl<T> NetworkRpc for NetworkClient<T> where T: NetworkApi {
@@ -54,6 +57,21 @@ impl<T> NetworkRpc for NetworkClient<T> where T: NetworkApi {
 	fn connection_count(&self) -> Result<usize, Error> {
 		Ok(self.api.connection_count())
 	}
+
+	fn peer_info(&self) -> Result<Vec<PeerConnectionInfo>, Error> {
+		Ok(self.api.peer_info())
+	}
+
+	fn reload_config(&self) -> Result<(), Error> {
+		self.api.reload_config().map_err(errors::execution)
+	}
+
+	fn crawled_peers(&self, reachable_only: Option<bool>) -> Result<Vec<CrawledPeerInfo>, Error> {
+		let reachable_only = reachable_only.unwrap_or(false);
+		let peers = self.api.crawled_peers()
+			.ok_or_else(|| errors::execution("Node is not running in crawler mode"))?;
+		Ok(peers.into_iter().filter(|peer| !reachable_only || peer.reachable).collect())
+	}
 }
 
 pub struct NetworkClient<T: NetworkApi> {
@@ -129,4 +147,18 @@ impl NetworkApi for NetworkClientCore {
 	fn connection_count(&self) -> usize {
 		self.p2p.connections().count()
 	}
+
+	fn peer_info(&self) -> Vec<PeerConnectionInfo> {
+		let mut peers = self.p2p.connections().info_with_stats();
+		peers.sort_by_key(|&(ref info, _)| info.id);
+		peers.into_iter().map(Into::into).collect()
+	}
+
+	fn reload_config(&self) -> Result<(), String> {
+		self.p2p.reload_policy()
+	}
+
+	fn crawled_peers(&self) -> Option<Vec<CrawledPeerInfo>> {
+		self.p2p.crawled_nodes().map(|nodes| nodes.into_iter().map(Into::into).collect())
+	}
 }
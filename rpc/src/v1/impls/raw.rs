@@ -1,15 +1,20 @@
 use jsonrpc_core::Error;
-use ser::{Reader, serialize, deserialize};
+use ser::{serialize, deserialize_strict};
 use v1::traits::Raw;
 use v1::types::{RawTransaction, TransactionInput, TransactionOutput, TransactionOutputs, Transaction, GetRawTransactionResponse};
+use v1::types::{SignedTransactionInput, SignedTransactionOutput, TransactionInputScript, TransactionOutputScript};
 use v1::types::H256;
-use v1::helpers::errors::{execution, invalid_params};
+use v1::helpers::errors::{execution, invalid_params, block_not_found, transaction_not_found, transaction_rejected};
 use chain::{
 	SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID,
 	Transaction as GlobalTransaction, IndexedTransaction as GlobalIndexedTransaction,
 };
 use primitives::bytes::Bytes as GlobalBytes;
 use primitives::hash::H256 as GlobalH256;
+use global_script::Script;
+use keys::{self, Address};
+use network::Network;
+use storage;
 use sync;
 
 /// Default expiry height delta (best blocks number + height in blocks) for transactions
@@ -21,7 +26,7 @@ pub struct RawClient<T: RawClientCoreApi> {
 }
 
 pub trait RawClientCoreApi: Send + Sync + 'static {
-	fn accept_transaction(&self, transaction: GlobalTransaction) -> Result<GlobalH256, String>;
+	fn accept_transaction(&self, transaction: GlobalTransaction) -> Result<GlobalH256, sync::TransactionRejection>;
 	fn create_raw_transaction(
 		&self,
 		inputs: Vec<TransactionInput>,
@@ -29,19 +34,125 @@ pub trait RawClientCoreApi: Send + Sync + 'static {
 		lock_time: Option<u32>,
 		expiry_height: Option<u32>,
 	) -> Result<GlobalTransaction, String>;
+	fn transaction(&self, hash: GlobalH256, block_hash: Option<GlobalH256>) -> Result<GlobalTransaction, Error>;
+	fn verbose_transaction(&self, hash: GlobalH256, block_hash: Option<GlobalH256>) -> Result<Transaction, Error>;
 }
 
 pub struct RawClientCore {
+	network: Network,
 	local_sync_node: sync::LocalNodeRef,
+	storage: storage::SharedStore,
 }
 
 impl RawClientCore {
-	pub fn new(local_sync_node: sync::LocalNodeRef) -> Self {
+	pub fn new(network: Network, local_sync_node: sync::LocalNodeRef, storage: storage::SharedStore) -> Self {
 		RawClientCore {
+			network: network,
 			local_sync_node: local_sync_node,
+			storage: storage,
 		}
 	}
 
+	pub fn do_transaction(
+		storage: &storage::SharedStore,
+		hash: GlobalH256,
+		block_hash: Option<GlobalH256>,
+	) -> Result<GlobalTransaction, Error> {
+		let block_hash = match block_hash {
+			Some(block_hash) => block_hash,
+			// no block hash => use transactions index
+			None => return storage.transaction(&hash)
+				.map(|transaction| transaction.raw)
+				.ok_or_else(|| transaction_not_found(hash)),
+		};
+
+		// block hash is given => only look into this block, so that transactions index isn't required
+		if storage.block_header(block_hash.clone().into()).is_none() {
+			return Err(block_not_found(block_hash));
+		}
+
+		storage.block_transactions(block_hash.into())
+			.into_iter()
+			.find(|transaction| transaction.hash == hash)
+			.map(|transaction| transaction.raw)
+			.ok_or_else(|| transaction_not_found(hash))
+	}
+
+	/// Verbose form of the transaction. Hashes of the response are reversed. Confirmations are zero if the
+	/// containing block isn't in the canon chain.
+	pub fn do_verbose_transaction(
+		storage: &storage::SharedStore,
+		network: Network,
+		hash: GlobalH256,
+		block_hash: Option<GlobalH256>,
+	) -> Result<Transaction, Error> {
+		let transaction = RawClientCore::do_transaction(storage, hash.clone(), block_hash.clone())?;
+		let block_hash = block_hash.or_else(|| storage.transaction_meta(&hash)
+			.and_then(|meta| storage.block_hash(meta.height())));
+		let block_header = block_hash.and_then(|block_hash| storage.block_header(block_hash.into()));
+		let confirmations = block_header.as_ref()
+			.and_then(|header| storage.block_number(&header.hash))
+			.map(|number| storage.best_block().number.saturating_sub(number) + 1)
+			.unwrap_or_default();
+		let block_time = block_header.as_ref().map(|header| header.raw.time).unwrap_or_default();
+		let raw = serialize(&transaction);
+
+		Ok(Transaction {
+			txid: hash.reversed().into(),
+			hash: hash.reversed().into(),
+			size: raw.len(),
+			version: transaction.version,
+			locktime: transaction.lock_time as i32,
+			vin: transaction.inputs.iter()
+				.map(|input| {
+					let script: Script = input.script_sig.clone().into();
+					SignedTransactionInput {
+						txid: input.previous_output.hash.reversed().into(),
+						vout: input.previous_output.index,
+						script_sig: TransactionInputScript {
+							asm: format!("{}", script),
+							hex: input.script_sig.clone().into(),
+						},
+						sequence: input.sequence,
+					}
+				})
+				.collect(),
+			vout: transaction.outputs.iter()
+				.enumerate()
+				.map(|(index, output)| {
+					let script: Script = output.script_pubkey.clone().into();
+					SignedTransactionOutput {
+						value: 0.00000001f64 * output.value as f64,
+						n: index as u32,
+						script: TransactionOutputScript {
+							asm: format!("{}", script),
+							hex: output.script_pubkey.clone().into(),
+							req_sigs: script.num_signatures_required() as u32,
+							script_type: script.script_type().into(),
+							addresses: script.extract_destinations().unwrap_or_default().into_iter()
+								.map(|address| Address {
+									network: match network {
+										Network::Mainnet => keys::Network::Mainnet,
+										// there's no correct choices for Regtests && Other networks
+										// => let's just make Testnet key
+										_ => keys::Network::Testnet,
+									},
+									hash: address.hash,
+									kind: address.kind,
+								})
+								.collect(),
+						},
+					}
+				})
+				.collect(),
+			hex: raw.into(),
+			blockhash: block_header.as_ref().map(|header| header.hash.reversed()).unwrap_or_default().into(),
+			confirmations: confirmations,
+			time: block_time,
+			blocktime: block_time,
+		})
+	}
+
 	pub fn do_create_raw_transaction(
 		best_block_number: u32,
 		inputs: Vec<TransactionInput>,
@@ -75,31 +186,33 @@ impl RawClientCore {
 			}).collect();
 
 		// prepare outputs
-		let outputs: Vec<_> = outputs.outputs.into_iter()
+		let outputs = outputs.outputs.into_iter()
 			.map(|output| match output {
 					TransactionOutput::Address(with_address) => {
-						let amount_in_satoshis = (with_address.amount * (chain::constants::SATOSHIS_IN_COIN as f64)) as u64;
+						let amount_in_satoshis = chain::Amount::from_coins(with_address.amount)
+							.and_then(chain::Amount::to_u64)
+							.ok_or_else(|| format!("Invalid amount: {}", with_address.amount))?;
 						let script = match with_address.address.kind {
 							keys::Type::P2PKH => ScriptBuilder::build_p2pkh(&with_address.address.hash),
 							keys::Type::P2SH => ScriptBuilder::build_p2sh(&with_address.address.hash),
 						};
 
-						chain::TransactionOutput {
+						Ok(chain::TransactionOutput {
 							value: amount_in_satoshis,
 							script_pubkey: script.to_bytes(),
-						}
+						})
 					},
 					TransactionOutput::ScriptData(with_script_data) => {
 						let script = ScriptBuilder::default()
 							.return_bytes(&*with_script_data.script_data)
 							.into_script();
 
-						chain::TransactionOutput {
+						Ok(chain::TransactionOutput {
 							value: 0,
 							script_pubkey: script.to_bytes(),
-						}
+						})
 					},
-				}).collect();
+				}).collect::<Result<Vec<_>, String>>()?;
 
 		// now construct && serialize transaction
 		let transaction = GlobalTransaction {
@@ -118,7 +231,7 @@ impl RawClientCore {
 }
 
 impl RawClientCoreApi for RawClientCore {
-	fn accept_transaction(&self, transaction: GlobalTransaction) -> Result<GlobalH256, String> {
+	fn accept_transaction(&self, transaction: GlobalTransaction) -> Result<GlobalH256, sync::TransactionRejection> {
 		self.local_sync_node.accept_transaction(GlobalIndexedTransaction::from_raw(transaction))
 	}
 
@@ -137,6 +250,14 @@ impl RawClientCoreApi for RawClientCore {
 			expiry_height,
 		)
 	}
+
+	fn transaction(&self, hash: GlobalH256, block_hash: Option<GlobalH256>) -> Result<GlobalTransaction, Error> {
+		RawClientCore::do_transaction(&self.storage, hash, block_hash)
+	}
+
+	fn verbose_transaction(&self, hash: GlobalH256, block_hash: Option<GlobalH256>) -> Result<Transaction, Error> {
+		RawClientCore::do_verbose_transaction(&self.storage, self.network, hash, block_hash)
+	}
 }
 
 impl<T> RawClient<T> where T: RawClientCoreApi {
@@ -150,10 +271,10 @@ impl<T> RawClient<T> where T: RawClientCoreApi {
 impl<T> Raw for RawClient<T> where T: RawClientCoreApi {
 	fn send_raw_transaction(&self, raw_transaction: RawTransaction) -> Result<H256, Error> {
 		let raw_transaction_data: Vec<u8> = raw_transaction.into();
-		let transaction = try!(deserialize(Reader::new(&raw_transaction_data)).map_err(|e| invalid_params("tx", e)));
+		let transaction = try!(deserialize_strict(&raw_transaction_data as &[u8]).map_err(|e| invalid_params("tx", e)));
 		self.core.accept_transaction(transaction)
 			.map(|h| h.reversed().into())
-			.map_err(|e| execution(e))
+			.map_err(transaction_rejected)
 	}
 
 	fn create_raw_transaction(
@@ -180,18 +301,31 @@ impl<T> Raw for RawClient<T> where T: RawClientCoreApi {
 		rpc_unimplemented!()
 	}
 
-	fn get_raw_transaction(&self, _hash: H256, _verbose: Option<bool>) -> Result<GetRawTransactionResponse, Error> {
-		rpc_unimplemented!()
+	fn get_raw_transaction(&self, hash: H256, verbose: Option<bool>, block_hash: Option<H256>) -> Result<GetRawTransactionResponse, Error> {
+		let global_hash: GlobalH256 = hash.into();
+		let global_block_hash = block_hash.map(|block_hash| Into::<GlobalH256>::into(block_hash).reversed());
+		if verbose.unwrap_or_default() {
+			return self.core.verbose_transaction(global_hash.reversed(), global_block_hash)
+				.map(GetRawTransactionResponse::Verbose);
+		}
+
+		self.core.transaction(global_hash.reversed(), global_block_hash)
+			.map(|transaction| GetRawTransactionResponse::Raw(serialize(&transaction).into()))
 	}
 }
 
 #[cfg(test)]
 pub mod tests {
+	extern crate test_data;
+
+	use std::sync::Arc;
 	use jsonrpc_core::IoHandler;
+	use db::BlockChainDatabase;
+	use serde_json;
 	use chain::Transaction;
 	use primitives::hash::H256 as GlobalH256;
 	use v1::traits::Raw;
-	use v1::types::{TransactionInput, TransactionOutputs};
+	use v1::types::{TransactionInput, TransactionOutputs, Transaction as VerboseTransaction};
 	use super::*;
 
 	#[derive(Default)]
@@ -200,7 +334,7 @@ pub mod tests {
 	struct ErrorRawClientCore;
 
 	impl RawClientCoreApi for SuccessRawClientCore {
-		fn accept_transaction(&self, transaction: Transaction) -> Result<GlobalH256, String> {
+		fn accept_transaction(&self, transaction: Transaction) -> Result<GlobalH256, sync::TransactionRejection> {
 			Ok(transaction.hash())
 		}
 
@@ -213,11 +347,19 @@ pub mod tests {
 		) -> Result<Transaction, String> {
 			Ok("0100000001ad9d38823d95f31dc6c0cb0724c11a3cf5a466ca4147254a10cd94aade6eb5b3230000006b483045022100b7683165c3ecd57b0c44bf6a0fb258dc08c328458321c8fadc2b9348d4e66bd502204fd164c58d1a949a4d39bb380f8f05c9f6b3e9417f06bf72e5c068428ca3578601210391c35ac5ee7cf82c5015229dcff89507f83f9b8c952b8fecfa469066c1cb44ccffffffff0170f30500000000001976a914801da3cb2ed9e44540f4b982bde07cd3fbae264288ac00000000".into())
 		}
+
+		fn transaction(&self, _hash: GlobalH256, _block_hash: Option<GlobalH256>) -> Result<Transaction, Error> {
+			Ok(test_data::genesis().transactions[0].clone())
+		}
+
+		fn verbose_transaction(&self, _hash: GlobalH256, _block_hash: Option<GlobalH256>) -> Result<VerboseTransaction, Error> {
+			Ok(sample_verbose_transaction())
+		}
 	}
 
 	impl RawClientCoreApi for ErrorRawClientCore {
-		fn accept_transaction(&self, _transaction: Transaction) -> Result<GlobalH256, String> {
-			Err("error".to_owned())
+		fn accept_transaction(&self, _transaction: Transaction) -> Result<GlobalH256, sync::TransactionRejection> {
+			Err(sync::TransactionRejection::MissingInputs)
 		}
 
 		fn create_raw_transaction(
@@ -229,6 +371,31 @@ pub mod tests {
 		) -> Result<Transaction, String> {
 			Err("error".to_owned())
 		}
+
+		fn transaction(&self, hash: GlobalH256, _block_hash: Option<GlobalH256>) -> Result<Transaction, Error> {
+			Err(transaction_not_found(hash))
+		}
+
+		fn verbose_transaction(&self, hash: GlobalH256, _block_hash: Option<GlobalH256>) -> Result<VerboseTransaction, Error> {
+			Err(transaction_not_found(hash))
+		}
+	}
+
+	fn sample_verbose_transaction() -> VerboseTransaction {
+		VerboseTransaction {
+			hex: "01000000".into(),
+			txid: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b".into(),
+			hash: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b".into(),
+			size: 4,
+			version: 1,
+			locktime: 0,
+			vin: vec![],
+			vout: vec![],
+			blockhash: "0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283".into(),
+			confirmations: 1,
+			time: 1477641360,
+			blocktime: 1477641360,
+		}
 	}
 
 	#[test]
@@ -266,7 +433,7 @@ pub mod tests {
 			}"#)
 		).unwrap();
 
-		assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"Execution error.","data":"\"error\""},"id":1}"#, &sample);
+		assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32091,"message":"Missing transaction inputs"},"id":1}"#, &sample);
 	}
 
 	#[test]
@@ -322,4 +489,141 @@ pub mod tests {
 
 		assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"Execution error.","data":"\"error\""},"id":1}"#, &sample);
 	}
+
+	#[test]
+	fn getrawtransaction_success() {
+		let client = RawClient::new(SuccessRawClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getrawtransaction",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", false, "0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283"],
+				"id": 1
+			}"#)
+		).unwrap();
+
+		let raw_transaction: RawTransaction = serialize(&test_data::genesis().transactions[0]).into();
+		let expected = format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#,
+			serde_json::to_string(&raw_transaction).unwrap());
+		assert_eq!(expected, sample);
+	}
+
+	#[test]
+	fn getrawtransaction_error() {
+		let client = RawClient::new(ErrorRawClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getrawtransaction",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"],
+				"id": 1
+			}"#)
+		).unwrap();
+
+		assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32096,"message":"Transaction with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#, &sample);
+	}
+
+	#[test]
+	fn getrawtransaction_verbose_success() {
+		let client = RawClient::new(SuccessRawClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getrawtransaction",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", true],
+				"id": 1
+			}"#)
+		).unwrap();
+
+		let expected = format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#,
+			serde_json::to_string(&sample_verbose_transaction()).unwrap());
+		assert_eq!(expected, sample);
+	}
+
+	#[test]
+	fn getrawtransaction_verbose_error() {
+		let client = RawClient::new(ErrorRawClientCore::default());
+		let mut handler = IoHandler::new();
+		handler.extend_with(client.to_delegate());
+
+		let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getrawtransaction",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", true],
+				"id": 1
+			}"#)
+		).unwrap();
+
+		assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32096,"message":"Transaction with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#, &sample);
+	}
+
+	#[test]
+	fn getrawtransaction_verbose_contents() {
+		let storage: storage::SharedStore = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let genesis = test_data::genesis();
+		let block_h1 = test_data::block_h1();
+		let tx = block_h1.transactions[0].clone();
+		let tx_hash = tx.hash();
+
+		let verbose = RawClientCore::do_verbose_transaction(&storage, Network::Mainnet, tx_hash.clone(), None).unwrap();
+		assert_eq!(verbose.hex, serialize(&tx).into());
+		assert_eq!(verbose.txid, tx_hash.reversed().into());
+		assert_eq!(verbose.size, serialize(&tx).len());
+		assert_eq!(verbose.vin.len(), tx.inputs.len());
+		assert_eq!(verbose.vout.len(), tx.outputs.len());
+		assert_eq!(verbose.vout[0].value, 0.00000001f64 * tx.outputs[0].value as f64);
+		assert_eq!(verbose.blockhash, block_h1.hash().reversed().into());
+		assert_eq!(verbose.confirmations, 1);
+		assert_eq!(verbose.blocktime, block_h1.block_header.time);
+
+		// genesis transaction is one block deeper
+		let genesis_tx_hash = genesis.transactions[0].hash();
+		let verbose = RawClientCore::do_verbose_transaction(&storage, Network::Mainnet, genesis_tx_hash, Some(genesis.hash())).unwrap();
+		assert_eq!(verbose.blockhash, genesis.hash().reversed().into());
+		assert_eq!(verbose.confirmations, 2);
+
+		assert_eq!(RawClientCore::do_verbose_transaction(&storage, Network::Mainnet, tx_hash.clone(), Some(genesis.hash())),
+			Err(transaction_not_found(tx_hash)));
+	}
+
+	#[test]
+	fn getrawtransaction_contents() {
+		let storage: storage::SharedStore = Arc::new(BlockChainDatabase::init_test_chain(
+			vec![
+				test_data::genesis().into(),
+				test_data::block_h1().into(),
+			]
+		));
+
+		let genesis = test_data::genesis();
+		let block_h1 = test_data::block_h1();
+		let tx_hash = block_h1.transactions[0].hash();
+
+		// without block hash transactions index is used
+		assert_eq!(RawClientCore::do_transaction(&storage, tx_hash.clone(), None),
+			Ok(block_h1.transactions[0].clone()));
+		// with block hash only given block is searched
+		assert_eq!(RawClientCore::do_transaction(&storage, tx_hash.clone(), Some(block_h1.hash())),
+			Ok(block_h1.transactions[0].clone()));
+		assert_eq!(RawClientCore::do_transaction(&storage, tx_hash.clone(), Some(genesis.hash())),
+			Err(transaction_not_found(tx_hash.clone())));
+		assert_eq!(RawClientCore::do_transaction(&storage, tx_hash.clone(), Some(GlobalH256::from(1))),
+			Err(block_not_found(GlobalH256::from(1))));
+	}
 }
@@ -3,8 +3,19 @@ use jsonrpc_core::Error;
 
 use v1::types::{BlockRef, H256};
 use v1::types::GetBlockResponse;
+use v1::types::GetBlockChainInfoResponse;
+use v1::types::GetConsensusParamsResponse;
 use v1::types::GetTxOutResponse;
 use v1::types::GetTxOutSetInfoResponse;
+use v1::types::GetShieldedStatsResponse;
+use v1::types::GetBlockStatsResponse;
+use v1::types::GetFeeHistoryResponse;
+use v1::types::GetChainEventsResponse;
+use v1::types::GetValidationTimingsResponse;
+use v1::types::GetTreeStateResponse;
+use v1::types::{AddressRequest, GetAddressBalanceResponse};
+use v1::types::{SpentInfoRequest, GetSpentInfoResponse};
+use v1::types::GetDbInfoResponse;
 
 /// Parity-bitcoin blockchain data interface.
 #[rpc]
@@ -13,6 +24,14 @@ pub trait BlockChain {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getbestblockhash", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getbestblockhash")]
 	fn best_block_hash(&self) -> Result<H256, Error>;
+	/// Get information on the state of the blockchain, network upgrades and deployments.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockchaininfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getblockchaininfo")]
+	fn blockchain_info(&self) -> Result<GetBlockChainInfoResponse, Error>;
+	/// Get consensus parameters of the active network.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getconsensusparams", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getconsensusparams")]
+	fn consensus_params(&self) -> Result<GetConsensusParamsResponse, Error>;
 	/// Get height of best block.
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockcount", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getblockcount")]
@@ -38,4 +57,44 @@ pub trait BlockChain {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "gettxoutsetinfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "gettxoutsetinfo")]
 	fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error>;
+	/// Get statistics of shielded transactions within given range of canon blocks (up to the best block by default).
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getshieldedstats", "params": [419200, 420000], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getshieldedstats")]
+	fn shielded_stats(&self, u32, Option<u32>) -> Result<GetShieldedStatsResponse, Error>;
+	/// Get fee statistics of given canon block.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockstats", "params": ["0002a26c902619fc964443264feb16f1e3e2d71322fc53dcb81cc5d797e273ed"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getblockstats")]
+	fn block_stats(&self, BlockRef) -> Result<GetBlockStatsResponse, Error>;
+	/// Get feerates of given number of canon blocks, ending at given height (at the best block by default).
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getfeehistory", "params": [144], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getfeehistory")]
+	fn fee_history(&self, u32, Option<u32>) -> Result<GetFeeHistoryResponse, Error>;
+	/// Get up to given number of chain events (block connections and disconnections), starting from event with given sequence number.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getchainevents", "params": [1, 100], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getchainevents")]
+	fn chain_events(&self, u64, Option<u32>) -> Result<GetChainEventsResponse, Error>;
+	/// Get time, spent in every stage of block processing (deserialization, script verification, proof verification and database writes).
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getvalidationtimings", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getvalidationtimings")]
+	fn validation_timings(&self) -> Result<GetValidationTimingsResponse, Error>;
+	/// Get Sprout && Sapling note commitment tree states after given canon block.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "z_gettreestate", "params": ["419200"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "z_gettreestate")]
+	fn tree_state(&self, BlockRef) -> Result<GetTreeStateResponse, Error>;
+	/// Get balance of given transparent addresses. Requires address index.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getaddressbalance", "params": [{"addresses": ["t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe"]}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getaddressbalance")]
+	fn address_balance(&self, AddressRequest) -> Result<GetAddressBalanceResponse, Error>;
+	/// Get hashes of canon transactions, paying to given transparent addresses, ordered by height. Requires address index.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getaddresstxids", "params": [{"addresses": ["t1h8SqgtM3QM5e2M8EzhhT1yL2PXXtA6oqe"], "start": 1, "end": 1000}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getaddresstxids")]
+	fn address_tx_ids(&self, AddressRequest) -> Result<Vec<H256>, Error>;
+	/// Get canon transaction input, spending given output. Requires spent index.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getspentinfo", "params": [{"txid": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", "index": 0}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getspentinfo")]
+	fn spent_info(&self, SpentInfoRequest) -> Result<GetSpentInfoResponse, Error>;
+	/// Get settings and size of the database.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getdbinfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getdbinfo")]
+	fn db_info(&self) -> Result<GetDbInfoResponse, Error>;
 }
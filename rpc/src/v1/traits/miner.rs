@@ -1,6 +1,6 @@
 use jsonrpc_core::Error;
 
-use v1::types::{BlockTemplate, BlockTemplateRequest};
+use v1::types::{BlockTemplate, BlockTemplateRequest, BlockArrivalStats, Bytes};
 
 /// Parity-bitcoin miner data interface.
 #[rpc]
@@ -9,4 +9,12 @@ pub trait Miner {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblocktemplate", "params": [{"capabilities": ["coinbasetxn", "workid", "coinbase/append"]}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getblocktemplate")]
 	fn get_block_template(&self, BlockTemplateRequest) -> Result<BlockTemplate, Error>;
+	/// Get statistics of blocks received within given number of seconds (1 hour by default).
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockarrivalstats", "params": [3600], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getblockarrivalstats")]
+	fn block_arrival_stats(&self, Option<u32>) -> Result<BlockArrivalStats, Error>;
+	/// Validate serialized block header and accept it into the headers chain, without the block itself.
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "submitheader", "params": ["04000000..."], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "submitheader")]
+	fn submit_header(&self, Bytes) -> Result<(), Error>;
 }
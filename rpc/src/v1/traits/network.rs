@@ -1,5 +1,5 @@
 use jsonrpc_core::Error;
-use v1::types::{AddNodeOperation, NodeInfo};
+use v1::types::{AddNodeOperation, NodeInfo, PeerConnectionInfo, CrawledPeerInfo};
 
 /// Parity-bitcoin network interface
 #[rpc]
@@ -19,4 +19,16 @@ pub trait Network {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getconnectioncount", "params": [] }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getconnectioncount")]
 	fn connection_count(&self) -> Result<usize, Error>;
+	/// Query info on every connected peer, including purpose of the connection
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getpeerinfo", "params": [] }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getpeerinfo")]
+	fn peer_info(&self) -> Result<Vec<PeerConnectionInfo>, Error>;
+	/// Re-read network policy (ban thresholds and whitelist) from the policy file
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "reloadconfig", "params": [] }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "reloadconfig")]
+	fn reload_config(&self) -> Result<(), Error>;
+	/// Query results of probing known addresses in crawler mode (optionally, only reachable peers)
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getcrawledpeers", "params": [true] }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	#[rpc(name = "getcrawledpeers")]
+	fn crawled_peers(&self, Option<bool>) -> Result<Vec<CrawledPeerInfo>, Error>;
 }
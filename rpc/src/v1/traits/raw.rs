@@ -22,8 +22,11 @@ pub trait Raw {
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "decoderawtransaction", "params": ["01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "decoderawtransaction")]
 	fn decode_raw_transaction(&self, RawTransaction) -> Result<Transaction, Error>;
-	/// Return the raw transaction data.
+	/// Return the raw transaction data. If block hash is given, transaction is only searched for in this block.
+	/// If verbose is true, the JSON object describing the transaction is returned instead.
 	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getrawtransaction", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getrawtransaction", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", false, "0007bc227e1c57a4a70e237cad00e7b7ce565155ab49166bc57397a26d339283"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+	/// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getrawtransaction", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", true], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
 	#[rpc(name = "getrawtransaction")]
-	fn get_raw_transaction(&self, H256, Option<bool>) -> Result<GetRawTransactionResponse, Error>;
+	fn get_raw_transaction(&self, H256, Option<bool>, Option<H256>) -> Result<GetRawTransactionResponse, Error>;
 }
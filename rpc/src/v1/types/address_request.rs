@@ -0,0 +1,34 @@
+use keys::Address;
+use v1::types;
+
+/// Parameters of address index requests (getaddressbalance, getaddresstxids)
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AddressRequest {
+	/// Transparent addresses
+	#[serde(with = "types::address::vec")]
+	pub addresses: Vec<Address>,
+	/// Height of the first block of the range (getaddresstxids only)
+	pub start: Option<u32>,
+	/// Height of the last block of the range (getaddresstxids only)
+	pub end: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn address_request_deserialize() {
+		assert_eq!(serde_json::from_str::<AddressRequest>(r#"{"addresses":["t2UNzUUx8mWBCRYPRezvA363EYXyEpHokyi"]}"#).unwrap(), AddressRequest {
+			addresses: vec!["t2UNzUUx8mWBCRYPRezvA363EYXyEpHokyi".into()],
+			start: None,
+			end: None,
+		});
+		assert_eq!(serde_json::from_str::<AddressRequest>(r#"{"addresses":[],"start":10,"end":20}"#).unwrap(), AddressRequest {
+			addresses: vec![],
+			start: Some(10),
+			end: Some(20),
+		});
+	}
+}
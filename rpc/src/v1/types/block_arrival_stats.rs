@@ -0,0 +1,58 @@
+use sync;
+
+/// Statistics of recently received blocks
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BlockArrivalStats {
+	/// Length of the window (in seconds) the statistics is computed for
+	pub window: u32,
+	/// Number of blocks received within window
+	pub blocks: usize,
+	/// Number of blocks received before their parent
+	pub outoforder: usize,
+	/// Number of received blocks which haven't extended the best block
+	pub stale: usize,
+	/// Share of stale blocks among all received blocks
+	pub orphanrate: f64,
+	/// Median delay between block time and block receive time (in seconds)
+	pub delaymedian: u32,
+	/// 90th percentile of delay between block time and block receive time (in seconds)
+	pub delayp90: u32,
+	/// 99th percentile of delay between block time and block receive time (in seconds)
+	pub delayp99: u32,
+}
+
+impl BlockArrivalStats {
+	pub fn new(window: u32, info: sync::BlockArrivalInformation) -> Self {
+		BlockArrivalStats {
+			window: window,
+			blocks: info.blocks,
+			outoforder: info.out_of_order,
+			stale: info.stale,
+			orphanrate: info.orphan_rate,
+			delaymedian: info.delay_median,
+			delayp90: info.delay_p90,
+			delayp99: info.delay_p99,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::BlockArrivalStats;
+
+	#[test]
+	fn block_arrival_stats_serialize() {
+		let stats = BlockArrivalStats {
+			window: 3600,
+			blocks: 20,
+			outoforder: 2,
+			stale: 1,
+			orphanrate: 0.05,
+			delaymedian: 3,
+			delayp90: 10,
+			delayp99: 40,
+		};
+		assert_eq!(serde_json::to_string(&stats).unwrap(), r#"{"window":3600,"blocks":20,"outoforder":2,"stale":1,"orphanrate":0.05,"delaymedian":3,"delayp90":10,"delayp99":40}"#);
+	}
+}
@@ -0,0 +1,23 @@
+/// getaddressbalance response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetAddressBalanceResponse {
+	/// Sum of unspent outputs (in zatoshis), paying to the addresses
+	pub balance: u64,
+	/// Sum of all outputs (in zatoshis), paying to the addresses
+	pub received: u64,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn address_balance_response_serialize() {
+		let balance = GetAddressBalanceResponse {
+			balance: 1_000,
+			received: 5_000,
+		};
+		assert_eq!(serde_json::to_string(&balance).unwrap(), r#"{"balance":1000,"received":5000}"#);
+	}
+}
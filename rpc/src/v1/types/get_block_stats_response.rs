@@ -0,0 +1,51 @@
+use super::hash::H256;
+
+/// getblockstats response
+///
+/// All feerates are in zatoshis per 1000 bytes.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetBlockStatsResponse {
+	/// Block hash
+	pub hash: H256,
+	/// Block height
+	pub height: u32,
+	/// Number of transactions in the block (including coinbase)
+	pub txs: u32,
+	/// Sum of fees (in zatoshis) of all non-coinbase transactions
+	pub totalfee: u64,
+	/// Sum of sizes of all non-coinbase transactions
+	pub totalsize: u64,
+	/// Average feerate
+	pub avgfeerate: u64,
+	/// Minimal feerate
+	pub minfeerate: u64,
+	/// Median feerate
+	pub medianfeerate: u64,
+	/// 90th percentile feerate
+	pub feerate90: u64,
+	/// Maximal feerate
+	pub maxfeerate: u64,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn block_stats_response_serialize() {
+		let stats = GetBlockStatsResponse {
+			hash: H256::from(1),
+			height: 100,
+			txs: 3,
+			totalfee: 20_000,
+			totalsize: 500,
+			avgfeerate: 40_000,
+			minfeerate: 10_000,
+			medianfeerate: 10_000,
+			feerate90: 60_000,
+			maxfeerate: 60_000,
+		};
+		assert_eq!(serde_json::to_string(&stats).unwrap(), r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","height":100,"txs":3,"totalfee":20000,"totalsize":500,"avgfeerate":40000,"minfeerate":10000,"medianfeerate":10000,"feerate90":60000,"maxfeerate":60000}"#);
+	}
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use super::hash::H256;
+
+/// getblockchaininfo response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetBlockChainInfoResponse {
+	/// Current network name (main, test, regtest)
+	pub chain: String,
+	/// Number of blocks in the best chain
+	pub blocks: u32,
+	/// Hash of the best block
+	pub bestblockhash: H256,
+	/// Current difficulty
+	pub difficulty: f64,
+	/// Status of network upgrades, keyed by hex-encoded consensus branch id
+	pub upgrades: BTreeMap<String, NetworkUpgradeInfo>,
+	/// Status of BIP9 deployments, keyed by deployment name
+	pub softforks: BTreeMap<String, SoftforkInfo>,
+	/// Consensus branch ids of the best block and of the next block
+	pub consensus: ConsensusInfo,
+	/// Balances of the shielded value pools at the best block
+	#[serde(rename = "valuePools")]
+	pub value_pools: Vec<ValuePoolInfo>,
+}
+
+/// Network upgrade information
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct NetworkUpgradeInfo {
+	/// Name of the upgrade
+	pub name: String,
+	/// Block height of activation
+	pub activationheight: u32,
+	/// Status of the upgrade at the best block
+	pub status: NetworkUpgradeStatus,
+}
+
+/// Network upgrade status
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkUpgradeStatus {
+	/// Upgrade is not yet active
+	Pending,
+	/// Upgrade is active
+	Active,
+}
+
+impl Default for NetworkUpgradeStatus {
+	fn default() -> Self {
+		NetworkUpgradeStatus::Pending
+	}
+}
+
+/// BIP9 deployment information
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SoftforkInfo {
+	/// Deployment status (defined, started, locked_in, active, failed)
+	pub status: String,
+	/// Version bit used to signal this deployment
+	pub bit: u8,
+	/// Median time past at which signalling starts
+	#[serde(rename = "startTime")]
+	pub start_time: u32,
+	/// Median time past at which the deployment is considered failed
+	pub timeout: u32,
+}
+
+/// Consensus branch ids
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConsensusInfo {
+	/// Branch id used to validate the best block
+	pub chaintip: String,
+	/// Branch id used to validate the next block
+	pub nextblock: String,
+}
+
+/// Shielded value pool balance
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ValuePoolInfo {
+	/// Pool name (sprout, sapling, orchard)
+	pub id: String,
+	/// Is pool balance known (it isn't tracked for blocks, inserted by older database versions)
+	pub monitored: bool,
+	/// Pool balance (in ZEC)
+	#[serde(rename = "chainValue", default, skip_serializing_if = "Option::is_none")]
+	pub chain_value: Option<f64>,
+	/// Pool balance (in zatoshis)
+	#[serde(rename = "chainValueZat", default, skip_serializing_if = "Option::is_none")]
+	pub chain_value_zat: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn blockchain_info_response_serialize() {
+		let mut upgrades = BTreeMap::new();
+		upgrades.insert("5ba81b19".to_owned(), NetworkUpgradeInfo {
+			name: "Overwinter".to_owned(),
+			activationheight: 347500,
+			status: NetworkUpgradeStatus::Active,
+		});
+		let mut softforks = BTreeMap::new();
+		softforks.insert("csv".to_owned(), SoftforkInfo {
+			status: "started".to_owned(),
+			bit: 0,
+			start_time: 100,
+			timeout: 200,
+		});
+
+		let info = GetBlockChainInfoResponse {
+			chain: "main".to_owned(),
+			blocks: 347600,
+			bestblockhash: H256::from(0x56),
+			difficulty: 1.0,
+			upgrades: upgrades,
+			softforks: softforks,
+			consensus: ConsensusInfo {
+				chaintip: "5ba81b19".to_owned(),
+				nextblock: "5ba81b19".to_owned(),
+			},
+			value_pools: vec![ValuePoolInfo {
+				id: "sprout".to_owned(),
+				monitored: true,
+				chain_value: Some(0.5),
+				chain_value_zat: Some(50000000),
+			}, ValuePoolInfo {
+				id: "sapling".to_owned(),
+				monitored: false,
+				chain_value: None,
+				chain_value_zat: None,
+			}],
+		};
+		assert_eq!(serde_json::to_string(&info).unwrap(), r#"{"chain":"main","blocks":347600,"bestblockhash":"5600000000000000000000000000000000000000000000000000000000000000","difficulty":1.0,"upgrades":{"5ba81b19":{"name":"Overwinter","activationheight":347500,"status":"active"}},"softforks":{"csv":{"status":"started","bit":0,"startTime":100,"timeout":200}},"consensus":{"chaintip":"5ba81b19","nextblock":"5ba81b19"},"valuePools":[{"id":"sprout","monitored":true,"chainValue":0.5,"chainValueZat":50000000},{"id":"sapling","monitored":false}]}"#);
+	}
+}
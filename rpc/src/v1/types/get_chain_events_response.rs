@@ -0,0 +1,57 @@
+use super::hash::H256;
+
+/// getchainevents response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetChainEventsResponse {
+	/// Sequence number of the last event of the journal (0 if journal is empty)
+	pub lastsequence: u64,
+	/// Requested events, in journal order
+	pub events: Vec<ChainEventInfo>,
+}
+
+/// Canon chain modification
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainEventType {
+	/// Block has been appended to the canon chain
+	Connected,
+	/// Block has been removed from the canon chain
+	Disconnected,
+}
+
+/// Single event of the chain events journal
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChainEventInfo {
+	/// Sequence number of the event
+	pub sequence: u64,
+	/// Event type
+	#[serde(rename = "type")]
+	pub event_type: ChainEventType,
+	/// Block hash
+	pub hash: H256,
+	/// Block height
+	pub height: u32,
+	/// Hashes of block transactions
+	pub tx: Vec<H256>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn chain_events_response_serialize() {
+		let response = GetChainEventsResponse {
+			lastsequence: 2,
+			events: vec![ChainEventInfo {
+				sequence: 2,
+				event_type: ChainEventType::Disconnected,
+				hash: H256::from(1),
+				height: 10,
+				tx: vec![H256::from(2)],
+			}],
+		};
+		assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"lastsequence":2,"events":[{"sequence":2,"type":"disconnected","hash":"0100000000000000000000000000000000000000000000000000000000000000","height":10,"tx":["0200000000000000000000000000000000000000000000000000000000000000"]}]}"#);
+	}
+}
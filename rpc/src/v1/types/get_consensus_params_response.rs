@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use super::uint::U256;
+
+/// getconsensusparams response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetConsensusParamsResponse {
+	/// Current network name (main, test, regtest)
+	pub chain: String,
+	/// Maximal proof-of-work target
+	pub powlimit: U256,
+	/// Interval (in blocks) to calculate average work
+	pub powaveragingwindow: u32,
+	/// % of possible down adjustment of work
+	pub powmaxadjustdown: u32,
+	/// % of possible up adjustment of work
+	pub powmaxadjustup: u32,
+	/// Optimal blocks interval (in seconds) before Blossom activation
+	pub powtargetspacing: u32,
+	/// Optimal blocks interval (in seconds) after Blossom activation, if Blossom is scheduled
+	pub postblossompowtargetspacing: Option<u32>,
+	/// Height after which blocks are allowed to have minimal difficulty
+	pub powallowmindifficultyafterheight: Option<u32>,
+	/// Equihash N parameter
+	pub equihashn: Option<u32>,
+	/// Equihash K parameter
+	pub equihashk: Option<u32>,
+	/// 'Slow start' interval (in blocks)
+	pub subsidyslowstartinterval: u32,
+	/// Block subsidy halving interval (in blocks), before Blossom activation
+	pub subsidyhalvinginterval: u32,
+	/// Maximal block size (in bytes)
+	pub maxblocksize: usize,
+	/// Maximal number of signature operations in block
+	pub maxblocksigops: usize,
+	/// Maximal transaction value (in zatoshis)
+	pub maxtransactionvalue: i64,
+	/// Activation heights of network upgrades, keyed by hex-encoded consensus branch id
+	pub upgrades: BTreeMap<String, NetworkUpgradeParams>,
+	/// Funding streams, that replace founders reward after Canopy activation
+	pub fundingstreams: Vec<FundingStreamParams>,
+}
+
+/// Network upgrade parameters
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct NetworkUpgradeParams {
+	/// Name of the upgrade
+	pub name: String,
+	/// Block height of activation
+	pub activationheight: u32,
+}
+
+/// Funding stream parameters
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct FundingStreamParams {
+	/// Recipient of the stream
+	pub recipient: String,
+	/// Numerator of the block subsidy fraction that goes to the stream
+	pub numerator: u64,
+	/// Denominator of the block subsidy fraction that goes to the stream
+	pub denominator: u64,
+	/// Height of the first block that pays to the stream
+	pub startheight: u32,
+	/// Height of the first block that doesn't pay to the stream
+	pub endheight: u32,
+	/// Addresses of the stream, one for every address change interval
+	pub addresses: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn consensus_params_response_serialize() {
+		let mut upgrades = BTreeMap::new();
+		upgrades.insert("5ba81b19".to_owned(), NetworkUpgradeParams {
+			name: "Overwinter".to_owned(),
+			activationheight: 347500,
+		});
+
+		let params = GetConsensusParamsResponse {
+			chain: "main".to_owned(),
+			powlimit: U256::from(15),
+			powaveragingwindow: 17,
+			powmaxadjustdown: 32,
+			powmaxadjustup: 16,
+			powtargetspacing: 150,
+			postblossompowtargetspacing: Some(75),
+			powallowmindifficultyafterheight: None,
+			equihashn: Some(200),
+			equihashk: Some(9),
+			subsidyslowstartinterval: 20000,
+			subsidyhalvinginterval: 840000,
+			maxblocksize: 2000000,
+			maxblocksigops: 20000,
+			maxtransactionvalue: 2100000000000000,
+			upgrades: upgrades,
+			fundingstreams: vec![FundingStreamParams {
+				recipient: "Major Grants".to_owned(),
+				numerator: 8,
+				denominator: 100,
+				startheight: 1046400,
+				endheight: 2726400,
+				addresses: vec!["t3XyYW8yBFRuMnfvm5KLGFbEVz25kckZXym".to_owned()],
+			}],
+		};
+		assert_eq!(serde_json::to_string(&params).unwrap(), r#"{"chain":"main","powlimit":"f","powaveragingwindow":17,"powmaxadjustdown":32,"powmaxadjustup":16,"powtargetspacing":150,"postblossompowtargetspacing":75,"powallowmindifficultyafterheight":null,"equihashn":200,"equihashk":9,"subsidyslowstartinterval":20000,"subsidyhalvinginterval":840000,"maxblocksize":2000000,"maxblocksigops":20000,"maxtransactionvalue":2100000000000000,"upgrades":{"5ba81b19":{"name":"Overwinter","activationheight":347500}},"fundingstreams":[{"recipient":"Major Grants","numerator":8,"denominator":100,"startheight":1046400,"endheight":2726400,"addresses":["t3XyYW8yBFRuMnfvm5KLGFbEVz25kckZXym"]}]}"#);
+	}
+}
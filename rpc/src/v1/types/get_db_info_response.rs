@@ -0,0 +1,41 @@
+/// getdbinfo response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetDbInfoResponse {
+	/// Is the database stored on disk?
+	pub ondisk: bool,
+	/// Total size of key-value store files (in bytes)
+	pub size: u64,
+	/// Number of raw blocks files
+	pub blockfiles: usize,
+	/// Total size of raw blocks files (in bytes)
+	pub blockfilessize: u64,
+	/// Total size of column caches (in MiB)
+	pub cachesize: usize,
+	/// Size of the write buffer of every column (in MiB)
+	pub writebuffersize: usize,
+	/// Compaction style of the key-value store
+	pub compactionstyle: String,
+	/// Maximal number of files, opened by the key-value store
+	pub maxopenfiles: i32,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn db_info_response_serialize() {
+		let db_info = GetDbInfoResponse {
+			ondisk: true,
+			size: 1_000,
+			blockfiles: 2,
+			blockfilessize: 2_000,
+			cachesize: 512,
+			writebuffersize: 64,
+			compactionstyle: "universal".into(),
+			maxopenfiles: 512,
+		};
+		assert_eq!(serde_json::to_string(&db_info).unwrap(), r#"{"ondisk":true,"size":1000,"blockfiles":2,"blockfilessize":2000,"cachesize":512,"writebuffersize":64,"compactionstyle":"universal","maxopenfiles":512}"#);
+	}
+}
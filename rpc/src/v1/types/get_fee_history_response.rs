@@ -0,0 +1,50 @@
+/// getfeehistory response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetFeeHistoryResponse {
+	/// Height of the first block of the window
+	pub from: u32,
+	/// Height of the last block of the window
+	pub to: u32,
+	/// Feerates of every block of the window, starting from the oldest block
+	pub blocks: Vec<BlockFeeRates>,
+}
+
+/// Feerates (in zatoshis per 1000 bytes) of non-coinbase transactions of single block
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BlockFeeRates {
+	/// Block height
+	pub height: u32,
+	/// Number of non-coinbase transactions in the block
+	pub txs: u32,
+	/// Minimal feerate
+	pub minfeerate: u64,
+	/// Median feerate
+	pub medianfeerate: u64,
+	/// 90th percentile feerate
+	pub feerate90: u64,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn fee_history_response_serialize() {
+		let history = GetFeeHistoryResponse {
+			from: 10,
+			to: 11,
+			blocks: vec![
+				BlockFeeRates::default(),
+				BlockFeeRates {
+					height: 11,
+					txs: 2,
+					minfeerate: 1_000,
+					medianfeerate: 1_000,
+					feerate90: 5_000,
+				},
+			],
+		};
+		assert_eq!(serde_json::to_string(&history).unwrap(), r#"{"from":10,"to":11,"blocks":[{"height":0,"txs":0,"minfeerate":0,"medianfeerate":0,"feerate90":0},{"height":11,"txs":2,"minfeerate":1000,"medianfeerate":1000,"feerate90":5000}]}"#);
+	}
+}
@@ -0,0 +1,53 @@
+/// getshieldedstats response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetShieldedStatsResponse {
+	/// Height of the first block of the range
+	pub from: u32,
+	/// Height of the last block of the range
+	pub to: u32,
+	/// Sprout pool statistics
+	pub sprout: ShieldedPoolStats,
+	/// Sapling pool statistics
+	pub sapling: ShieldedPoolStats,
+	/// Number of transactions moving transparent value into shielded pools
+	pub shielding: u32,
+	/// Number of transactions moving shielded value into transparent outputs
+	pub deshielding: u32,
+	/// Number of transactions without transparent inputs and outputs
+	pub fullyshielded: u32,
+}
+
+/// Statistics of single shielded pool
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ShieldedPoolStats {
+	/// Number of transactions using the pool
+	pub transactions: u32,
+	/// Value (in ZEC) that has entered the pool
+	pub valuein: f64,
+	/// Value (in ZEC) that has left the pool
+	pub valueout: f64,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn shielded_stats_response_serialize() {
+		let stats = GetShieldedStatsResponse {
+			from: 10,
+			to: 20,
+			sprout: ShieldedPoolStats {
+				transactions: 2,
+				valuein: 1.5,
+				valueout: 0.5,
+			},
+			sapling: ShieldedPoolStats::default(),
+			shielding: 1,
+			deshielding: 1,
+			fullyshielded: 0,
+		};
+		assert_eq!(serde_json::to_string(&stats).unwrap(), r#"{"from":10,"to":20,"sprout":{"transactions":2,"valuein":1.5,"valueout":0.5},"sapling":{"transactions":0,"valuein":0.0,"valueout":0.0},"shielding":1,"deshielding":1,"fullyshielded":0}"#);
+	}
+}
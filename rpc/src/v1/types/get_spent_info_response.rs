@@ -0,0 +1,28 @@
+use super::hash::H256;
+
+/// getspentinfo response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetSpentInfoResponse {
+	/// Hash of the spending transaction
+	pub txid: H256,
+	/// Index of the spending input
+	pub index: u32,
+	/// Height of the block, containing the spending transaction
+	pub height: u32,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn spent_info_response_serialize() {
+		let spent_info = GetSpentInfoResponse {
+			txid: H256::from(1),
+			index: 2,
+			height: 100,
+		};
+		assert_eq!(serde_json::to_string(&spent_info).unwrap(), r#"{"txid":"0100000000000000000000000000000000000000000000000000000000000000","index":2,"height":100}"#);
+	}
+}
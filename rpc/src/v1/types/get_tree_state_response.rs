@@ -0,0 +1,58 @@
+use super::bytes::Bytes;
+use super::hash::H256;
+
+/// z_gettreestate response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetTreeStateResponse {
+	/// Block hash
+	pub hash: H256,
+	/// Block height
+	pub height: u32,
+	/// Block time
+	pub time: u32,
+	/// Sprout note commitment tree state after the block
+	pub sprout: TreeStateInfo,
+	/// Sapling note commitment tree state after the block
+	pub sapling: TreeStateInfo,
+}
+
+/// Note commitment tree state of single shielded pool
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TreeStateInfo {
+	/// Tree commitments
+	pub commitments: TreeCommitments,
+}
+
+/// Note commitment tree after the block
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TreeCommitments {
+	/// Tree root (anchor)
+	#[serde(rename = "finalRoot")]
+	pub final_root: H256,
+	/// Serialized tree state
+	#[serde(rename = "finalState")]
+	pub final_state: Bytes,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn tree_state_response_serialize() {
+		let tree_state = GetTreeStateResponse {
+			hash: H256::from(1),
+			height: 100,
+			time: 1_600_000_000,
+			sprout: TreeStateInfo {
+				commitments: TreeCommitments {
+					final_root: H256::from(2),
+					final_state: Bytes::new(vec![0, 0, 0]),
+				},
+			},
+			sapling: TreeStateInfo::default(),
+		};
+		assert_eq!(serde_json::to_string(&tree_state).unwrap(), r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","height":100,"time":1600000000,"sprout":{"commitments":{"finalRoot":"0200000000000000000000000000000000000000000000000000000000000000","finalState":"000000"}},"sapling":{"commitments":{"finalRoot":"0000000000000000000000000000000000000000000000000000000000000000","finalState":""}}}"#);
+	}
+}
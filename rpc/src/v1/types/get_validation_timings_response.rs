@@ -0,0 +1,69 @@
+use primitives::timings::{Timings, BLOCKS_PER_WINDOW};
+
+/// getvalidationtimings response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GetValidationTimingsResponse {
+	/// Number of blocks, the timings are aggregated for
+	pub windowblocks: usize,
+	/// Number of complete windows since the start
+	pub windows: usize,
+	/// Number of blocks, processed since the current window has started
+	pub currentblocks: usize,
+	/// Timings of every stage, in processing order
+	pub stages: Vec<StageTimings>,
+}
+
+/// Time (in milliseconds), spent in single stage of block processing
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StageTimings {
+	/// Stage name
+	pub name: String,
+	/// Time, spent since the current window has started
+	pub current: u64,
+	/// Time, spent during the last complete window
+	pub last: u64,
+	/// Time, spent since the start
+	pub total: u64,
+}
+
+impl GetValidationTimingsResponse {
+	pub fn new(timings: Timings) -> Self {
+		let millis = |duration: ::std::time::Duration| duration.as_secs() * 1_000 + (duration.subsec_nanos() / 1_000_000) as u64;
+		GetValidationTimingsResponse {
+			windowblocks: BLOCKS_PER_WINDOW,
+			windows: timings.windows,
+			currentblocks: timings.current_window_blocks,
+			stages: timings.stages.into_iter()
+				.map(|stage| StageTimings {
+					name: stage.stage.name().to_owned(),
+					current: millis(stage.current_window),
+					last: millis(stage.last_window),
+					total: millis(stage.total),
+				})
+				.collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use primitives::timings::{self, Stage, Timings};
+	use super::*;
+
+	#[test]
+	fn validation_timings_response_serialize() {
+		let response = GetValidationTimingsResponse::new(Timings {
+			windows: 3,
+			current_window_blocks: 20,
+			stages: vec![timings::StageTimings {
+				stage: Stage::ScriptVerification,
+				current_window: Duration::from_millis(1_500),
+				last_window: Duration::new(60, 999_999),
+				total: Duration::from_secs(200),
+			}],
+		});
+		assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"windowblocks":1000,"windows":3,"currentblocks":20,"stages":[{"name":"scriptverification","current":1500,"last":60000,"total":200000}]}"#);
+	}
+}
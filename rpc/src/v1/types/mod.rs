@@ -1,29 +1,59 @@
 pub mod address;
+mod address_request;
 mod block;
+mod block_arrival_stats;
 mod block_template;
 mod block_template_request;
 mod bytes;
+mod get_address_balance_response;
 mod get_block_response;
+mod get_block_stats_response;
+mod get_blockchain_info_response;
+mod get_chain_events_response;
+mod get_consensus_params_response;
+mod get_db_info_response;
+mod get_fee_history_response;
+mod get_shielded_stats_response;
+mod get_spent_info_response;
 mod get_tx_out_response;
 mod get_tx_out_set_info_response;
+mod get_tree_state_response;
+mod get_validation_timings_response;
 mod hash;
 mod script;
+mod spent_info_request;
 mod transaction;
 mod uint;
 mod nodes;
 
+pub use self::address_request::AddressRequest;
 pub use self::block::{BlockRef, RawBlock};
+pub use self::block_arrival_stats::BlockArrivalStats;
 pub use self::block_template::{BlockTemplate, BlockTemplateTransaction};
 pub use self::block_template_request::{BlockTemplateRequest, BlockTemplateRequestMode};
 pub use self::bytes::Bytes;
+pub use self::get_address_balance_response::GetAddressBalanceResponse;
 pub use self::get_block_response::{GetBlockResponse, VerboseBlock};
+pub use self::get_block_stats_response::GetBlockStatsResponse;
+pub use self::get_blockchain_info_response::{GetBlockChainInfoResponse, NetworkUpgradeInfo,
+	NetworkUpgradeStatus, SoftforkInfo, ConsensusInfo, ValuePoolInfo};
+pub use self::get_chain_events_response::{GetChainEventsResponse, ChainEventInfo, ChainEventType};
+pub use self::get_consensus_params_response::{GetConsensusParamsResponse, NetworkUpgradeParams,
+	FundingStreamParams};
+pub use self::get_db_info_response::GetDbInfoResponse;
+pub use self::get_fee_history_response::{GetFeeHistoryResponse, BlockFeeRates};
+pub use self::get_shielded_stats_response::{GetShieldedStatsResponse, ShieldedPoolStats};
+pub use self::get_spent_info_response::GetSpentInfoResponse;
 pub use self::get_tx_out_response::GetTxOutResponse;
 pub use self::get_tx_out_set_info_response::GetTxOutSetInfoResponse;
+pub use self::get_tree_state_response::{GetTreeStateResponse, TreeStateInfo, TreeCommitments};
+pub use self::get_validation_timings_response::{GetValidationTimingsResponse, StageTimings};
 pub use self::hash::{H160, H256};
 pub use self::script::ScriptType;
+pub use self::spent_info_request::SpentInfoRequest;
 pub use self::transaction::{RawTransaction, Transaction, TransactionInput, TransactionOutput,
 	TransactionOutputWithAddress, TransactionOutputWithScriptData, TransactionInputScript,
 	TransactionOutputScript, SignedTransactionInput, GetRawTransactionResponse,
 	SignedTransactionOutput, TransactionOutputs};
 pub use self::uint::U256;
-pub use self::nodes::{AddNodeOperation, NodeInfo};
+pub use self::nodes::{AddNodeOperation, NodeInfo, PeerConnectionInfo, PeerConnectionType, CrawledPeerInfo};
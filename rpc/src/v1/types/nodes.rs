@@ -1,7 +1,7 @@
 use std::fmt;
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde::de::Unexpected;
-use p2p::{Direction, PeerInfo};
+use p2p::{Direction, PeerInfo, ConnectionType, PeerStats, CrawledNode};
 
 #[derive(Debug, PartialEq)]
 pub enum AddNodeOperation {
@@ -62,6 +62,129 @@ pub struct NodeInfo {
 	pub addresses: Vec<NodeInfoAddress>,
 }
 
+/// getpeerinfo response entry
+#[derive(Serialize)]
+pub struct PeerConnectionInfo {
+	/// Peer index
+	pub id: usize,
+	/// Address of the peer
+	pub addr: String,
+	/// Protocol version of the peer
+	pub version: u32,
+	/// User agent of the peer
+	pub subver: String,
+	/// True if connection has been initiated by the peer
+	pub inbound: bool,
+	/// Purpose of the connection
+	pub connection_type: PeerConnectionType,
+	/// True if addresses are exchanged with the peer
+	pub addr_relay_enabled: bool,
+	/// Time of the last send (in seconds since epoch)
+	pub lastsend: u32,
+	/// Time of the last receive (in seconds since epoch)
+	pub lastrecv: u32,
+	/// Total number of sent bytes
+	pub bytessent: u64,
+	/// Total number of received bytes
+	pub bytesrecv: u64,
+	/// Average ping time (in seconds)
+	pub pingtime: f64,
+	/// Minimal ping time (in seconds)
+	pub minping: Option<f64>,
+}
+
+impl From<(PeerInfo, PeerStats)> for PeerConnectionInfo {
+	fn from((info, stats): (PeerInfo, PeerStats)) -> Self {
+		PeerConnectionInfo {
+			id: info.id,
+			addr: format!("{}", info.address),
+			version: info.version,
+			subver: info.user_agent,
+			inbound: info.direction == Direction::Inbound,
+			connection_type: info.connection_type.into(),
+			addr_relay_enabled: stats.is_addr_relay_active(),
+			lastsend: stats.last_send,
+			lastrecv: stats.last_recv,
+			bytessent: stats.total_send,
+			bytesrecv: stats.total_recv,
+			pingtime: stats.avg_ping,
+			minping: stats.min_ping,
+		}
+	}
+}
+
+/// getcrawledpeers response entry
+#[derive(Serialize)]
+pub struct CrawledPeerInfo {
+	/// Address of the peer
+	pub addr: String,
+	/// True if the last handshake with the peer has succeeded
+	pub reachable: bool,
+	/// Services, announced in the last successful handshake (hex)
+	pub services: String,
+	/// Protocol version of the peer
+	pub version: u32,
+	/// User agent of the peer
+	pub subver: String,
+	/// Best block height, announced in the last successful handshake
+	pub startingheight: i32,
+	/// Time of the last handshake attempt (in seconds since epoch)
+	pub lastattempt: i64,
+	/// Time of the last successful handshake (in seconds since epoch, 0 if none)
+	pub lastsuccess: i64,
+	/// Number of handshake attempts
+	pub attempts: u32,
+	/// Number of successful handshakes
+	pub successes: u32,
+}
+
+impl From<CrawledNode> for CrawledPeerInfo {
+	fn from(node: CrawledNode) -> Self {
+		CrawledPeerInfo {
+			addr: format!("{}", node.address),
+			reachable: node.is_reachable(),
+			services: format!("{:016x}", u64::from(node.services)),
+			version: node.version,
+			subver: node.user_agent,
+			startingheight: node.start_height,
+			lastattempt: node.last_attempt,
+			lastsuccess: node.last_success,
+			attempts: node.attempts,
+			successes: node.successes,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PeerConnectionType {
+	Inbound,
+	OutboundFullRelay,
+	Manual,
+	AddrFetch,
+}
+
+impl From<ConnectionType> for PeerConnectionType {
+	fn from(connection_type: ConnectionType) -> Self {
+		match connection_type {
+			ConnectionType::Inbound => PeerConnectionType::Inbound,
+			ConnectionType::OutboundFullRelay => PeerConnectionType::OutboundFullRelay,
+			ConnectionType::Manual => PeerConnectionType::Manual,
+			ConnectionType::AddrFetch => PeerConnectionType::AddrFetch,
+		}
+	}
+}
+
+impl Serialize for PeerConnectionType {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		match *self {
+			PeerConnectionType::Inbound => "inbound".serialize(serializer),
+			PeerConnectionType::OutboundFullRelay => "outbound-full-relay".serialize(serializer),
+			PeerConnectionType::Manual => "manual".serialize(serializer),
+			PeerConnectionType::AddrFetch => "addr-fetch".serialize(serializer),
+		}
+	}
+}
+
 pub enum NodeInfoAddressConnectionType {
 	Inbound,
 	Outbound,
@@ -75,3 +198,19 @@ impl Serialize for NodeInfoAddressConnectionType {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use p2p::ConnectionType;
+	use super::PeerConnectionType;
+
+	#[test]
+	fn peer_connection_type_serialize() {
+		let types = vec![ConnectionType::Inbound, ConnectionType::OutboundFullRelay, ConnectionType::Manual, ConnectionType::AddrFetch]
+			.into_iter()
+			.map(PeerConnectionType::from)
+			.collect::<Vec<_>>();
+		assert_eq!(serde_json::to_string(&types).unwrap(), r#"["inbound","outbound-full-relay","manual","addr-fetch"]"#);
+	}
+}
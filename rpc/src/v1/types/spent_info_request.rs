@@ -0,0 +1,24 @@
+use super::hash::H256;
+
+/// Parameters of getspentinfo request
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpentInfoRequest {
+	/// Hash of the transaction, containing the output
+	pub txid: H256,
+	/// Index of the output in the transaction
+	pub index: u32,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn spent_info_request_deserialize() {
+		assert_eq!(serde_json::from_str::<SpentInfoRequest>(r#"{"txid":"0100000000000000000000000000000000000000000000000000000000000000","index":2}"#).unwrap(), SpentInfoRequest {
+			txid: H256::from(1),
+			index: 2,
+		});
+	}
+}
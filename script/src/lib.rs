@@ -10,6 +10,8 @@ extern crate serialization as ser;
 extern crate serde_json;
 #[cfg(test)]
 extern crate rustc_hex as hex;
+#[cfg(test)]
+extern crate network;
 
 mod builder;
 mod error;
@@ -94,7 +94,7 @@ impl Sighash {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UnsignedTransactionInput {
 	pub previous_output: OutPoint,
 	pub sequence: u32,
@@ -110,7 +110,7 @@ impl From<TransactionInput> for UnsignedTransactionInput {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TransactionInputSigner {
 	pub overwintered: bool,
 	pub version: i32,
@@ -224,6 +224,7 @@ impl TransactionInputSigner {
 			overwintered: self.overwintered,
 			version: self.version,
 			version_group_id: self.version_group_id,
+			consensus_branch_id: 0,
 			inputs: inputs,
 			outputs: outputs,
 			lock_time: self.lock_time,
@@ -236,6 +237,7 @@ impl TransactionInputSigner {
 				}
 			}),
 			sapling: None,
+			orchard: None,
 		};
 
 		let mut stream = Stream::default();
@@ -245,7 +247,7 @@ impl TransactionInputSigner {
 		dhash256(&out)
 	}
 
-	/// Overwinter/sapling version of the signature.
+	/// Overwinter (ZIP-143) && Sapling (ZIP-243) version of the signature.
 	fn signature_hash_post_overwinter(
 		&self,
 		cache: &mut SighashCache,
@@ -311,9 +313,9 @@ impl TransactionInputSigner {
 		stream.append(&self.lock_time);
 		stream.append(&self.expiry_height);
 		if sapling {
-			if let Some(ref sapling) = self.sapling {
-				stream.append(&sapling.balancing_value);
-			}
+			// valueBalance is committed to even if transaction has no shielded spends && outputs
+			let balancing_value = self.sapling.as_ref().map(|sapling| sapling.balancing_value).unwrap_or_default();
+			stream.append(&balancing_value);
 		}
 
 		stream.append(&sighashtype);
@@ -480,7 +482,8 @@ mod tests {
 	use bytes::Bytes;
 	use hash::H256;
 	use keys::{KeyPair, Private, Address};
-	use chain::{OutPoint, TransactionOutput, Transaction};
+	use chain::{OutPoint, TransactionOutput, Transaction, Sapling, SAPLING_TX_VERSION, SAPLING_TX_VERSION_GROUP_ID};
+	use network::NetworkUpgrade;
 	use script::Script;
 	use ser::deserialize;
 	use super::{Sighash, UnsignedTransactionInput, TransactionInputSigner, SighashBase};
@@ -533,6 +536,28 @@ mod tests {
 		assert_eq!(hash, expected_signature_hash);
 	}
 
+	#[test]
+	fn test_sapling_signature_hash_commits_to_zero_value_balance() {
+		let signer = |sapling: Option<Sapling>| TransactionInputSigner {
+			overwintered: true,
+			version: SAPLING_TX_VERSION,
+			version_group_id: SAPLING_TX_VERSION_GROUP_ID,
+			lock_time: 0,
+			expiry_height: 0,
+			inputs: vec![UnsignedTransactionInput { previous_output: OutPoint { hash: 1.into(), index: 0 }, sequence: 0xffff_ffff }],
+			outputs: vec![TransactionOutput { value: 100, script_pubkey: "76a914df3bd30160e6c6145baaf2c88a8844c13a00d1d588ac".into() }],
+			join_split: None,
+			sapling: sapling,
+		};
+		let script: Script = "76a914df3bd30160e6c6145baaf2c88a8844c13a00d1d588ac".into();
+		let branch_id = NetworkUpgrade::Sapling.branch_id();
+		let hash = |signer: TransactionInputSigner| signer.signature_hash(&mut Default::default(), Some(0), 100, &script, SighashBase::All.into(), branch_id);
+
+		let without_sapling = hash(signer(None));
+		assert_eq!(without_sapling, hash(signer(Some(Default::default()))));
+		assert!(without_sapling != hash(signer(Some(Sapling { balancing_value: 1, ..Default::default() }))));
+	}
+
 	#[test]
 	fn test_sighash_forkid_from_u32() {
 		assert!(!Sighash::is_defined(0xFFFFFF82));
@@ -620,7 +645,7 @@ mod tests {
 			let output_index = spend_tx.inputs[input_index].previous_output.index as usize;
 
 			// prepare tx signature checker
-			let consensus_branch_id = 0x76b809bb; // all test cases are for sapling era
+			let consensus_branch_id = NetworkUpgrade::Sapling.branch_id(); // all test cases are for sapling era
 			let signer: TransactionInputSigner = spend_tx.clone().into();
 			let mut checker = TransactionSignatureChecker {
 				signer,
@@ -94,14 +94,19 @@ impl Serializable for CompactInteger {
 
 impl Deserializable for CompactInteger {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
-		let result = match try!(reader.read::<u8>()) {
-			i @ 0...0xfc => i.into(),
-			0xfd => try!(reader.read::<u16>()).into(),
-			0xfe => try!(reader.read::<u32>()).into(),
-			_ => try!(reader.read::<u64>()).into(),
+		// every value must be encoded using the shortest possible form (in strict mode)
+		let (result, min_value) = match try!(reader.read::<u8>()) {
+			i @ 0...0xfc => (i as u64, 0),
+			0xfd => (try!(reader.read::<u16>()) as u64, 0xfd),
+			0xfe => (try!(reader.read::<u32>()) as u64, 0x10000),
+			_ => (try!(reader.read::<u64>()), 0x1_0000_0000),
 		};
 
-		Ok(result)
+		if reader.is_strict() && result < min_value {
+			return Err(ReaderError::MalformedData);
+		}
+
+		Ok(result.into())
 	}
 }
 
@@ -158,4 +163,21 @@ mod tests {
 		assert_eq!(reader.read::<CompactInteger>().unwrap(), 0x1_0000_0000u64.into());
 		assert_eq!(reader.read::<CompactInteger>().unwrap_err(), ReaderError::UnexpectedEnd);
 	}
+
+	#[test]
+	fn test_compact_integer_strict_reader() {
+		let non_canonical: Vec<&[u8]> = vec![
+			&[0xfd, 0xfc, 0x00],
+			&[0xfe, 0xff, 0xff, 0x00, 0x00],
+			&[0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00],
+		];
+
+		for buffer in non_canonical {
+			assert!(Reader::new(buffer).read::<CompactInteger>().is_ok());
+			assert_eq!(Reader::new(buffer).strict().read::<CompactInteger>().unwrap_err(), ReaderError::MalformedData);
+		}
+
+		let canonical: &[u8] = &[0xfd, 0xfd, 0x00];
+		assert_eq!(Reader::new(canonical).strict().read::<CompactInteger>().unwrap(), 0xfdu64.into());
+	}
 }
@@ -1,6 +1,6 @@
 use std::io;
 use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
-use bytes::Bytes;
+use bytes::{Bytes, SharedBytes};
 use compact::Compact;
 use hash::{H32, H48, H96, H160, H256, H264, H512, H520};
 use compact_integer::CompactInteger;
@@ -228,10 +228,30 @@ impl Serializable for Bytes {
 
 impl Deserializable for Bytes {
 	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where T: io::Read {
-		let len = try!(reader.read::<CompactInteger>());
-		let mut bytes = Bytes::new_with_len(len.into());
-		try!(reader.read_slice(&mut bytes));
-		Ok(bytes)
+		let max_size = reader.limits().max_size;
+		let len = try!(reader.read_len(max_size, "max_size"));
+		reader.read_bytes(len)
+	}
+}
+
+impl Serializable for SharedBytes {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&CompactInteger::from(self.len()))
+			.append_slice(self);
+	}
+
+	#[inline]
+	fn serialized_size(&self) -> usize {
+		CompactInteger::from(self.len()).serialized_size() + self.len()
+	}
+}
+
+impl Deserializable for SharedBytes {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error> where T: io::Read {
+		let max_size = reader.limits().max_size;
+		let len = try!(reader.read_len(max_size, "max_size"));
+		reader.read_shared(len)
 	}
 }
 
@@ -271,8 +291,8 @@ impl<T: Deserializable + Sized> Deserializable for Option<T> {
 
 #[cfg(test)]
 mod tests {
-	use bytes::Bytes;
-	use {serialize, deserialize, deserialize_iterator, Stream, Reader, Error};
+	use bytes::{Bytes, SharedBytes};
+	use {serialize, deserialize, deserialize_iterator, Stream, Reader, Error, Limits};
 
 	#[test]
 	fn test_reader_read() {
@@ -340,6 +360,26 @@ mod tests {
 		assert_eq!(expected, serialize(&bytes));
 	}
 
+	#[test]
+	fn test_shared_bytes_deserialize() {
+		let raw: SharedBytes = "020145020607".into();
+		let mut reader = Reader::from_shared(raw.clone());
+		let first: SharedBytes = reader.read().unwrap();
+		assert_eq!(first, "0145".into());
+		// data is not copied
+		assert_eq!(first.as_ptr(), raw[1..].as_ptr());
+
+		// proxy receives all consumed bytes
+		let mut proxied = Vec::new();
+		let second: Bytes = reader.read_with_proxy(|bytes| proxied.extend_from_slice(bytes)).unwrap();
+		assert_eq!(second, "0607".into());
+		assert_eq!(proxied, vec![0x02, 0x06, 0x07]);
+		assert!(reader.is_finished());
+
+		let mut reader = Reader::from_shared("0201".into());
+		assert_eq!(reader.read::<SharedBytes>(), Err(Error::UnexpectedEnd));
+	}
+
 	#[test]
 	fn test_string_serialize() {
 		let expected: Bytes = "0776657273696f6e".into();
@@ -360,6 +400,28 @@ mod tests {
 		assert_eq!(expected, deserialize::<_, String>(raw.as_ref()).unwrap());
 	}
 
+	#[test]
+	fn test_declared_length_limits() {
+		// declares 0xffffffff bytes
+		let raw: Bytes = "feffffffff00".into();
+		assert_eq!(deserialize::<_, Bytes>(raw.as_ref()).unwrap_err(), Error::LimitExceeded("max_size"));
+		assert_eq!(Reader::new(raw.as_ref()).read_list::<u8>().unwrap_err(), Error::LimitExceeded("max_size"));
+
+		// declared length is within limits, but data is missing => nothing is allocated in advance
+		let raw: Bytes = "fe0000000100".into();
+		assert_eq!(deserialize::<_, Bytes>(raw.as_ref()).unwrap_err(), Error::UnexpectedEnd);
+		assert_eq!(Reader::new(raw.as_ref()).read_list::<u8>().unwrap_err(), Error::UnexpectedEnd);
+
+		let limits = Limits { max_size: 2, ..Default::default() };
+		let raw: Bytes = "03010203".into();
+		let mut reader = Reader::new(raw.as_ref()).with_limits(limits);
+		assert_eq!(reader.read::<Bytes>().unwrap_err(), Error::LimitExceeded("max_size"));
+		let mut reader = Reader::new(raw.as_ref()).with_limits(limits);
+		assert_eq!(reader.read_list_limited::<u8>(3, "test").unwrap(), vec![1, 2, 3]);
+		let mut reader = Reader::new(raw.as_ref()).with_limits(limits);
+		assert_eq!(reader.read_list_limited::<u8>(2, "test").unwrap_err(), Error::LimitExceeded("test"));
+	}
+
 	#[test]
 	fn test_steam_append_slice() {
 		let mut slice = [0u8; 4];
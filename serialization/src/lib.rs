@@ -5,15 +5,17 @@ extern crate rustc_hex as hex;
 mod compact_integer;
 mod fixed_array;
 mod impls;
+mod limits;
 mod list;
 mod reader;
 mod stream;
 
 pub use primitives::{hash, bytes, compact};
 pub use compact_integer::CompactInteger;
+pub use limits::Limits;
 pub use list::List;
 pub use reader::{
-	Reader, Deserializable, deserialize, deserialize_iterator, ReadIterator, Error,
+	Reader, Deserializable, deserialize, deserialize_strict, deserialize_iterator, ReadIterator, Error,
 };
 pub use stream::{
 	Stream, Serializable, serialize, serialize_list, serialized_list_size,
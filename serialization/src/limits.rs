@@ -0,0 +1,49 @@
+//! Limits on the lengths of variable-length fields, declared by the (untrusted) data.
+
+/// Maximal number of list items, space for which is reserved before items are read. Declared
+/// length comes from untrusted source, so longer lists grow while their items are read.
+pub const MAX_PREALLOCATED_ITEMS: usize = 1024;
+
+/// Maximal number of bytes, space for which is reserved before the byte string is read.
+pub const MAX_PREALLOCATED_BYTES: usize = 64 * 1024;
+
+/// Maximal length of any list or byte string (MAX_SIZE in zcashd).
+pub const MAX_SIZE: usize = 0x0200_0000;
+
+/// Limits, checked by the reader before anything is allocated. Data, declaring larger
+/// lengths, is rejected with `Error::LimitExceeded`.
+///
+/// Default limits follow from the maximal block size (2,000,000 bytes) && minimal sizes of
+/// the corresponding items, so valid data never exceeds them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+	/// Maximal number of transactions in block.
+	pub max_block_transactions: usize,
+	/// Maximal number of transaction inputs.
+	pub max_transaction_inputs: usize,
+	/// Maximal number of transaction outputs.
+	pub max_transaction_outputs: usize,
+	/// Maximal size of input or output script.
+	pub max_script_size: usize,
+	/// Maximal size of Equihash solution.
+	pub max_solution_size: usize,
+	/// Maximal length of any other list or byte string.
+	pub max_size: usize,
+}
+
+impl Default for Limits {
+	fn default() -> Self {
+		Limits {
+			// transaction is at least 10 bytes long
+			max_block_transactions: 200_000,
+			// input is at least 41 bytes long
+			max_transaction_inputs: 48_780,
+			// output is at least 9 bytes long
+			max_transaction_outputs: 222_222,
+			max_script_size: 2_000_000,
+			// for N=200, K=9 parameters, used by mainnet && testnet
+			max_solution_size: 1344,
+			max_size: MAX_SIZE,
+		}
+	}
+}
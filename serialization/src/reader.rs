@@ -1,8 +1,19 @@
-use std::{io, marker};
+use std::{cmp, io, marker};
+use std::io::Read;
+use bytes::{Bytes, SharedBytes};
 use compact_integer::CompactInteger;
+use limits::{Limits, MAX_PREALLOCATED_ITEMS, MAX_PREALLOCATED_BYTES};
 
 pub fn deserialize<R, T>(buffer: R) -> Result<T, Error> where R: io::Read, T: Deserializable {
-	let mut reader = Reader::from_read(buffer);
+	deserialize_with_reader(Reader::from_read(buffer))
+}
+
+/// Deserialize using strict reader. Should be used when decoding consensus data (blocks and transactions).
+pub fn deserialize_strict<R, T>(buffer: R) -> Result<T, Error> where R: io::Read, T: Deserializable {
+	deserialize_with_reader(Reader::from_read(buffer).strict())
+}
+
+fn deserialize_with_reader<R, T>(mut reader: Reader<R>) -> Result<T, Error> where R: io::Read, T: Deserializable {
 	let result = try!(reader.read());
 
 	if reader.is_finished() {
@@ -25,6 +36,8 @@ pub enum Error {
 	UnexpectedEnd,
 	UnreadData,
 	InvalidFormat(String),
+	/// Declared length exceeds the limit with given name.
+	LimitExceeded(&'static str),
 }
 
 impl From<io::Error> for Error {
@@ -38,18 +51,40 @@ pub trait Deserializable {
 }
 
 /// Bitcoin structures reader.
+///
+/// By default reader is lenient and accepts non-canonical encodings (e.g. compact sizes that
+/// are not minimally encoded). Strict reader rejects them, the same way zcashd does.
+///
+/// Reader, created over shared buffer, returns slices of this buffer from `read_shared`,
+/// instead of copying the data.
+///
+/// Lengths of lists and byte strings are checked against reader limits before reading.
 #[derive(Debug)]
 pub struct Reader<T> {
 	buffer: T,
 	peeked: Option<u8>,
+	strict: bool,
+	limits: Limits,
+	/// Unread part of the shared buffer. When set, `buffer` is not used.
+	shared: Option<SharedBytes>,
 }
 
 impl<'a> Reader<&'a [u8]> {
 	/// Convenient way of creating for slice of bytes
 	pub fn new(buffer: &'a [u8]) -> Self {
+		Reader::from_read(buffer)
+	}
+}
+
+impl Reader<io::Empty> {
+	/// Create reader over shared buffer.
+	pub fn from_shared(buffer: SharedBytes) -> Self {
 		Reader {
-			buffer: buffer,
+			buffer: io::empty(),
 			peeked: None,
+			strict: false,
+			limits: Limits::default(),
+			shared: Some(buffer),
 		}
 	}
 }
@@ -60,14 +95,14 @@ impl<T> io::Read for Reader<T> where T: io::Read {
 		// so to make it as efficient as possible, check it
 		// only once
 		match self.peeked.take() {
-			None => io::Read::read(&mut self.buffer, buf),
+			None => self.read_buffer(buf),
 			Some(peeked) if buf.is_empty() => {
 				self.peeked = Some(peeked);
 				Ok(0)
 			},
 			Some(peeked) => {
 				buf[0] = peeked;
-				io::Read::read(&mut self.buffer, &mut buf[1..]).map(|x| x + 1)
+				self.read_buffer(&mut buf[1..]).map(|x| x + 1)
 			},
 		}
 	}
@@ -78,24 +113,109 @@ impl<R> Reader<R> where R: io::Read {
 		Reader {
 			buffer: read,
 			peeked: None,
+			strict: false,
+			limits: Limits::default(),
+			shared: None,
 		}
 	}
 
+	/// Switch reader to the strict mode.
+	pub fn strict(mut self) -> Self {
+		self.strict = true;
+		self
+	}
+
+	/// Returns true if non-canonical encodings must be rejected.
+	pub fn is_strict(&self) -> bool {
+		self.strict
+	}
+
+	/// Replace default limits.
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	pub fn limits(&self) -> &Limits {
+		&self.limits
+	}
+
 	pub fn read<T>(&mut self) -> Result<T, Error> where T: Deserializable {
 		T::deserialize(self)
 	}
 
-	pub fn read_with_proxy<T, F>(&mut self, proxy: F) -> Result<T, Error> where T: Deserializable, F: FnMut(&[u8]) {
+	pub fn read_with_proxy<T, F>(&mut self, mut proxy: F) -> Result<T, Error> where T: Deserializable, F: FnMut(&[u8]) {
+		// shared buffer is read directly (so that shared slices could be returned) and
+		// then all consumed bytes are passed to the proxy at once
+		if self.peeked.is_none() {
+			if let Some(shared) = self.shared.clone() {
+				let mut reader = Reader::from_shared(shared.clone());
+				reader.strict = self.strict;
+				reader.limits = self.limits;
+				let result = T::deserialize(&mut reader)?;
+				let unread = reader.shared.as_ref().map(|unread| unread.len()).unwrap_or(0)
+					+ if reader.peeked.is_some() { 1 } else { 0 };
+				let consumed = shared.len() - unread;
+				proxy(&shared[..consumed]);
+				self.shared = Some(shared.slice(consumed, shared.len()));
+				return Ok(result);
+			}
+		}
+
+		let (strict, limits) = (self.strict, self.limits);
 		let mut reader = Reader::from_read(Proxy::new(self, proxy));
+		reader.strict = strict;
+		reader.limits = limits;
 		T::deserialize(&mut reader)
 	}
 
+	/// Read `len` bytes. If reader is created over shared buffer, slice of this buffer is returned.
+	/// Otherwise bytes are copied into the new buffer.
+	pub fn read_shared(&mut self, len: usize) -> Result<SharedBytes, Error> {
+		if self.peeked.is_none() {
+			if let Some(ref mut shared) = self.shared {
+				if shared.len() < len {
+					return Err(Error::UnexpectedEnd);
+				}
+				return Ok(shared.split_to(len));
+			}
+		}
+
+		self.read_bytes(len).map(Into::into)
+	}
+
+	/// Read `len` bytes. Space for the bytes is allocated while they're read, so `len` could
+	/// come from the untrusted source.
+	pub fn read_bytes(&mut self, len: usize) -> Result<Bytes, Error> {
+		let mut bytes = Vec::with_capacity(cmp::min(len, MAX_PREALLOCATED_BYTES));
+		try!(io::Read::take(&mut *self, len as u64).read_to_end(&mut bytes));
+		if bytes.len() != len {
+			return Err(Error::UnexpectedEnd);
+		}
+		Ok(bytes.into())
+	}
+
+	/// Read declared length of list or byte string and check it against given limit.
+	pub fn read_len(&mut self, max: usize, limit: &'static str) -> Result<usize, Error> {
+		let len: usize = try!(self.read::<CompactInteger>()).into();
+		if len > max {
+			return Err(Error::LimitExceeded(limit));
+		}
+		Ok(len)
+	}
+
+	/// Read list of at most `max` items.
+	pub fn read_list_limited<T>(&mut self, max: usize, limit: &'static str) -> Result<Vec<T>, Error> where T: Deserializable {
+		let len = try!(self.read_len(max, limit));
+		self.read_list_items(len)
+	}
+
 	pub fn skip_while(&mut self, predicate: &Fn(u8) -> bool) -> Result<(), Error> {
 		let mut next_buffer = [0u8];
 		loop {
 			let next = match self.peeked.take() {
 				Some(peeked) => peeked,
-				None => match self.buffer.read(&mut next_buffer)? {
+				None => match self.read_buffer(&mut next_buffer)? {
 					0 => return Ok(()),
 					_ => next_buffer[0],
 				},
@@ -113,14 +233,8 @@ impl<R> Reader<R> where R: io::Read {
 	}
 
 	pub fn read_list<T>(&mut self) -> Result<Vec<T>, Error> where T: Deserializable {
-		let len: usize = try!(self.read::<CompactInteger>()).into();
-		let mut result = Vec::with_capacity(len);
-
-		for _ in 0..len {
-			result.push(try!(self.read()));
-		}
-
-		Ok(result)
+		let max_size = self.limits.max_size;
+		self.read_list_limited(max_size, "max_size")
 	}
 
 	pub fn read_list_max<T>(&mut self, max: usize) -> Result<Vec<T>, Error> where T: Deserializable {
@@ -129,13 +243,7 @@ impl<R> Reader<R> where R: io::Read {
 			return Err(Error::MalformedData);
 		}
 
-		let mut result = Vec::with_capacity(len);
-
-		for _ in 0..len {
-			result.push(try!(self.read()));
-		}
-
-		Ok(result)
+		self.read_list_items(len)
 	}
 
 	pub fn read_list_exact<T>(&mut self, expected_len: usize) -> Result<Vec<T>, Error> where T: Deserializable {
@@ -144,7 +252,11 @@ impl<R> Reader<R> where R: io::Read {
 			return Err(Error::MalformedData);
 		}
 
-		let mut result = Vec::with_capacity(len);
+		self.read_list_items(len)
+	}
+
+	fn read_list_items<T>(&mut self, len: usize) -> Result<Vec<T>, Error> where T: Deserializable {
+		let mut result = Vec::with_capacity(cmp::min(len, MAX_PREALLOCATED_ITEMS));
 
 		for _ in 0..len {
 			result.push(try!(self.read()));
@@ -153,6 +265,17 @@ impl<R> Reader<R> where R: io::Read {
 		Ok(result)
 	}
 
+	fn read_buffer(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		match self.shared {
+			Some(ref mut shared) => {
+				let len = cmp::min(buf.len(), shared.len());
+				buf[..len].copy_from_slice(&shared.split_to(len));
+				Ok(len)
+			},
+			None => io::Read::read(&mut self.buffer, buf),
+		}
+	}
+
 	#[cfg_attr(feature="cargo-clippy", allow(wrong_self_convention))]
 	pub fn is_finished(&mut self) -> bool {
 		if self.peeked.is_some() {
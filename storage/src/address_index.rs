@@ -0,0 +1,67 @@
+//! Optional index of transparent outputs by their scripts
+
+use std::io;
+use hash::H256;
+use crypto::sha256;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+
+/// Canon chain output, paying to the indexed script.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AddressIndexEntry {
+	/// Height of the block, containing the transaction.
+	pub height: u32,
+	/// Hash of the transaction.
+	pub tx_hash: H256,
+	/// Index of the output in the transaction.
+	pub output_index: u32,
+	/// Value of the output.
+	pub value: u64,
+	/// Is output spent by the canon chain transaction?
+	pub spent: bool,
+}
+
+/// Key of the address index entries for outputs with given script.
+pub fn address_index_key(script_pubkey: &[u8]) -> H256 {
+	sha256(script_pubkey)
+}
+
+impl Serializable for AddressIndexEntry {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.height)
+			.append(&self.tx_hash)
+			.append(&self.output_index)
+			.append(&self.value)
+			.append(&self.spent);
+	}
+}
+
+impl Deserializable for AddressIndexEntry {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(AddressIndexEntry {
+			height: reader.read()?,
+			tx_hash: reader.read()?,
+			output_index: reader.read()?,
+			value: reader.read()?,
+			spent: reader.read()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{serialize, deserialize};
+	use super::AddressIndexEntry;
+
+	#[test]
+	fn test_address_index_entry_serialization() {
+		let entry = AddressIndexEntry {
+			height: 100,
+			tx_hash: 1.into(),
+			output_index: 2,
+			value: 50_000,
+			spent: true,
+		};
+		assert_eq!(deserialize::<_, AddressIndexEntry>(serialize(&entry).as_ref()).unwrap(), entry);
+	}
+}
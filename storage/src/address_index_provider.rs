@@ -0,0 +1,10 @@
+use hash::H256;
+use AddressIndexEntry;
+
+pub trait AddressIndexProvider: Send + Sync {
+	/// Is address index maintained by the storage?
+	fn is_address_index_enabled(&self) -> bool;
+
+	/// Canon chain outputs, paying to the script with given key (see `address_index_key`), ordered by height.
+	fn address_index_entries(&self, key: &H256) -> Vec<AddressIndexEntry>;
+}
@@ -0,0 +1,78 @@
+//! Data, required to disconnect canon block
+
+use std::io;
+use hash::H256;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader, CompactInteger};
+use {EpochRef, TransactionMeta, ValuePools};
+
+/// Changes of the chain state, made by the canon block, which can't be recovered from the block itself
+/// once the state of its parent is modified (e.g. pruned). Written when block is connected to the canon chain
+/// && consumed when it is disconnected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockUndo {
+	/// Nullifiers, revealed by the block.
+	pub nullifiers: Vec<EpochRef>,
+	/// Metas of previous transactions, modified by the block, as they were before the block.
+	pub spent_metas: Vec<(H256, TransactionMeta)>,
+	/// Sprout note commitment tree root after the parent block.
+	pub parent_sprout_root: H256,
+	/// Sapling note commitment tree root after the parent block.
+	pub parent_sapling_root: H256,
+	/// Value pools after the parent block.
+	pub parent_value_pools: ValuePools,
+}
+
+impl Serializable for BlockUndo {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append_list(&self.nullifiers)
+			.append(&CompactInteger::from(self.spent_metas.len()));
+		for &(ref hash, ref meta) in &self.spent_metas {
+			stream.append(hash).append(meta);
+		}
+		stream
+			.append(&self.parent_sprout_root)
+			.append(&self.parent_sapling_root)
+			.append(&self.parent_value_pools);
+	}
+}
+
+impl Deserializable for BlockUndo {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let nullifiers = reader.read_list()?;
+		let spent_metas_len: usize = reader.read::<CompactInteger>()?.into();
+		let mut spent_metas = Vec::with_capacity(spent_metas_len);
+		for _ in 0..spent_metas_len {
+			spent_metas.push((reader.read()?, reader.read()?));
+		}
+
+		Ok(BlockUndo {
+			nullifiers: nullifiers,
+			spent_metas: spent_metas,
+			parent_sprout_root: reader.read()?,
+			parent_sapling_root: reader.read()?,
+			parent_value_pools: reader.read()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{serialize, deserialize};
+	use {EpochTag, EpochRef, TransactionMeta, ValuePools};
+	use super::BlockUndo;
+
+	#[test]
+	fn test_block_undo_serialization() {
+		let mut meta = TransactionMeta::new(10, 2);
+		meta.denote_used(1);
+		let undo = BlockUndo {
+			nullifiers: vec![EpochRef::new(EpochTag::Sprout, 1.into()), EpochRef::new(EpochTag::Sapling, 2.into())],
+			spent_metas: vec![(3.into(), meta)],
+			parent_sprout_root: 4.into(),
+			parent_sapling_root: 5.into(),
+			parent_value_pools: ValuePools { sprout: 1, sapling: 2, orchard: 3 },
+		};
+		assert_eq!(deserialize::<_, BlockUndo>(serialize(&undo).as_ref()).unwrap(), undo);
+	}
+}
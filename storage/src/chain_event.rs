@@ -0,0 +1,87 @@
+//! Journal of canon chain modifications
+
+use std::io;
+use hash::H256;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+
+/// Kind of the canon chain modification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChainEventKind {
+	/// Block has been appended to the canon chain.
+	Connected,
+	/// Block has been removed from the canon chain (during reorganization).
+	Disconnected,
+}
+
+/// Entry of the chain events journal.
+///
+/// Events are never removed from the journal, so consumer that has processed all events up to
+/// given sequence number could resume from the next event, even if reorganizations happened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainEvent {
+	/// Sequence number of the event. First event has number 1.
+	pub sequence: u64,
+	pub kind: ChainEventKind,
+	pub block_hash: H256,
+	pub block_number: u32,
+	/// Hashes of block transactions, in block order.
+	pub transactions: Vec<H256>,
+}
+
+impl Serializable for ChainEvent {
+	fn serialize(&self, stream: &mut Stream) {
+		let kind: u8 = match self.kind {
+			ChainEventKind::Connected => 0,
+			ChainEventKind::Disconnected => 1,
+		};
+
+		stream
+			.append(&self.sequence)
+			.append(&kind)
+			.append(&self.block_hash)
+			.append(&self.block_number)
+			.append_list(&self.transactions);
+	}
+}
+
+impl Deserializable for ChainEvent {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		let sequence = reader.read()?;
+		let kind = match reader.read::<u8>()? {
+			0 => ChainEventKind::Connected,
+			1 => ChainEventKind::Disconnected,
+			_ => return Err(ReaderError::MalformedData),
+		};
+
+		Ok(ChainEvent {
+			sequence: sequence,
+			kind: kind,
+			block_hash: reader.read()?,
+			block_number: reader.read()?,
+			transactions: reader.read_list()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{serialize, deserialize, Error as ReaderError};
+	use super::{ChainEvent, ChainEventKind};
+
+	#[test]
+	fn test_chain_event_serialization() {
+		let event = ChainEvent {
+			sequence: 7,
+			kind: ChainEventKind::Disconnected,
+			block_hash: 1.into(),
+			block_number: 100,
+			transactions: vec![2.into(), 3.into()],
+		};
+		let serialized = serialize(&event);
+		assert_eq!(deserialize::<_, ChainEvent>(serialized.as_ref()).unwrap(), event);
+
+		let mut unknown_kind = serialized.to_vec();
+		unknown_kind[8] = 2;
+		assert_eq!(deserialize::<_, ChainEvent>(&unknown_kind as &[u8]), Err(ReaderError::MalformedData));
+	}
+}
@@ -0,0 +1,19 @@
+use ChainEvent;
+
+pub trait ChainEventProvider: Send + Sync {
+	/// Sequence number of the last event of the journal. 0 if journal is empty.
+	fn last_chain_event_sequence(&self) -> u64;
+
+	/// Event with given sequence number.
+	fn chain_event(&self, sequence: u64) -> Option<ChainEvent>;
+
+	/// Up to `max` consecutive events, starting from the event with given sequence number.
+	fn chain_events(&self, from_sequence: u64, max: usize) -> Vec<ChainEvent> {
+		(from_sequence..)
+			.take(max)
+			.map(|sequence| self.chain_event(sequence))
+			.take_while(Option::is_some)
+			.map(|event| event.expect("checked by take_while; qed"))
+			.collect()
+	}
+}
@@ -0,0 +1,257 @@
+//! Chain state snapshot
+//!
+//! Snapshot holds headers of the canon chain && the state, required to verify blocks, following the
+//! snapshot block: transactions with unspent outputs, nullifiers, note commitment trees, value pools && the
+//! chain history tree.
+//! Records are hashed while they're written, so corrupted (or truncated) snapshot is detected when read.
+
+use std::{io, mem};
+use hash::H256;
+use chain::{BlockHeader, Transaction, HistoryTree};
+use crypto::{DHash256, Digest};
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+use {TransactionMeta, EpochRef, SproutTreeState, SaplingTreeState, ValuePools};
+
+/// Magic bytes ("zsnp") of the snapshot file.
+const SNAPSHOT_MAGIC: u32 = 0x706e_737a;
+/// Version of the snapshot format.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot block && the digest of snapshot records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotInfo {
+	/// Height of the snapshot block.
+	pub height: u32,
+	/// Hash of the snapshot block.
+	pub block_hash: H256,
+	/// Double SHA256 of all snapshot records.
+	pub digest: H256,
+}
+
+/// Single record of the snapshot.
+#[derive(Debug, Clone)]
+pub enum SnapshotRecord {
+	/// Header of the next canon block. Headers are written in the canon chain order.
+	Header(BlockHeader),
+	/// Sprout note commitment tree state, that is root of some canon block. Anchors of Sprout JoinSplits.
+	SproutTree(SproutTreeState),
+	/// Sapling note commitment tree state, that is root of some canon block. Anchors of Sapling spends.
+	SaplingTree(SaplingTreeState),
+	/// Canon transaction with unspent outputs.
+	Transaction(TransactionMeta, Transaction),
+	/// Revealed nullifier.
+	Nullifier(EpochRef),
+	/// Value pools after the snapshot block.
+	ValuePools(ValuePools),
+	/// Chain history tree after the snapshot block. Only written for Heartwood && Canopy snapshot blocks.
+	HistoryTree(HistoryTree),
+}
+
+impl Serializable for SnapshotRecord {
+	fn serialize(&self, stream: &mut Stream) {
+		match *self {
+			SnapshotRecord::Header(ref header) => stream.append(&0u8).append(header),
+			SnapshotRecord::SproutTree(ref tree) => stream.append(&1u8).append(tree),
+			SnapshotRecord::SaplingTree(ref tree) => stream.append(&2u8).append(tree),
+			SnapshotRecord::Transaction(ref meta, ref tx) => stream.append(&3u8).append(meta).append(tx),
+			SnapshotRecord::Nullifier(ref nullifier) => stream.append(&4u8).append(nullifier),
+			SnapshotRecord::ValuePools(ref value_pools) => stream.append(&5u8).append(value_pools),
+			SnapshotRecord::HistoryTree(ref tree) => stream.append(&6u8).append(tree),
+		};
+	}
+}
+
+impl Deserializable for SnapshotRecord {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(match reader.read::<u8>()? {
+			0 => SnapshotRecord::Header(reader.read()?),
+			1 => SnapshotRecord::SproutTree(reader.read()?),
+			2 => SnapshotRecord::SaplingTree(reader.read()?),
+			3 => SnapshotRecord::Transaction(reader.read()?, reader.read()?),
+			4 => SnapshotRecord::Nullifier(reader.read()?),
+			5 => SnapshotRecord::ValuePools(reader.read()?),
+			6 => SnapshotRecord::HistoryTree(reader.read()?),
+			_ => return Err(ReaderError::MalformedData),
+		})
+	}
+}
+
+/// Writes snapshot records, computing their digest.
+pub struct SnapshotWriter<W> {
+	writer: W,
+	digest: DHash256,
+}
+
+impl<W> SnapshotWriter<W> where W: io::Write {
+	/// Start the snapshot of the chain, ending with given block.
+	pub fn new(mut writer: W, height: u32, block_hash: &H256) -> Result<Self, io::Error> {
+		let mut stream = Stream::default();
+		stream
+			.append(&SNAPSHOT_MAGIC)
+			.append(&SNAPSHOT_VERSION)
+			.append(&height)
+			.append(block_hash);
+		writer.write_all(&stream.out())?;
+
+		Ok(SnapshotWriter {
+			writer: writer,
+			digest: DHash256::new(),
+		})
+	}
+
+	pub fn write(&mut self, record: &SnapshotRecord) -> Result<(), io::Error> {
+		let mut stream = Stream::default();
+		stream.append(&1u8).append(record);
+		let bytes = stream.out();
+		self.digest.input(&bytes);
+		self.writer.write_all(&bytes)
+	}
+
+	/// Write the end marker && the digest of all records.
+	pub fn finish(mut self) -> Result<H256, io::Error> {
+		let digest = self.digest.finish();
+		let mut stream = Stream::default();
+		stream.append(&0u8).append(&digest);
+		self.writer.write_all(&stream.out())?;
+		self.writer.flush()?;
+		Ok(digest)
+	}
+}
+
+/// Reads snapshot records, checking their digest.
+pub struct SnapshotReader<R> {
+	reader: Reader<R>,
+	height: u32,
+	block_hash: H256,
+	digest: DHash256,
+	/// Digest of all records, set after the end marker is read && the digest is verified.
+	verified_digest: Option<H256>,
+}
+
+impl<R> SnapshotReader<R> where R: io::Read {
+	pub fn new(reader: R) -> Result<Self, ReaderError> {
+		let mut reader = Reader::from_read(reader);
+		if reader.read::<u32>()? != SNAPSHOT_MAGIC || reader.read::<u32>()? != SNAPSHOT_VERSION {
+			return Err(ReaderError::MalformedData);
+		}
+
+		Ok(SnapshotReader {
+			height: reader.read()?,
+			block_hash: reader.read()?,
+			reader: reader,
+			digest: DHash256::new(),
+			verified_digest: None,
+		})
+	}
+
+	/// Height of the snapshot block.
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Hash of the snapshot block.
+	pub fn block_hash(&self) -> &H256 {
+		&self.block_hash
+	}
+
+	/// Digest of all records. Only available after the last record is read.
+	pub fn digest(&self) -> Option<&H256> {
+		self.verified_digest.as_ref()
+	}
+
+	/// Read next record. Returns None after the last record, if the digest of all records is correct.
+	pub fn read(&mut self) -> Result<Option<SnapshotRecord>, ReaderError> {
+		if self.verified_digest.is_some() {
+			return Ok(None);
+		}
+
+		match self.reader.read::<u8>()? {
+			0 => (),
+			1 => {
+				let digest = &mut self.digest;
+				digest.input(&[1u8]);
+				return self.reader.read_with_proxy(|bytes| digest.input(bytes)).map(Some);
+			},
+			_ => return Err(ReaderError::MalformedData),
+		}
+
+		// end marker && the digest itself are not the part of the digest
+		let expected_digest: H256 = self.reader.read()?;
+		let digest = mem::replace(&mut self.digest, DHash256::new()).finish();
+		if digest != expected_digest {
+			return Err(ReaderError::MalformedData);
+		}
+
+		self.verified_digest = Some(digest);
+		Ok(None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::Transaction;
+	use hash::H256;
+	use {EpochTag, EpochRef, ValuePools, SaplingTreeState, TransactionMeta};
+	use super::{SnapshotWriter, SnapshotReader, SnapshotRecord};
+
+	fn write_snapshot() -> (Vec<u8>, H256) {
+		let mut out = Vec::new();
+		let digest = {
+			let mut writer = SnapshotWriter::new(&mut out, 1, &H256::from(1)).unwrap();
+			writer.write(&SnapshotRecord::SaplingTree(SaplingTreeState::new())).unwrap();
+			writer.write(&SnapshotRecord::Transaction(TransactionMeta::new(1, 2), Transaction { version: 1, ..Default::default() })).unwrap();
+			writer.write(&SnapshotRecord::Nullifier(EpochRef::new(EpochTag::Sapling, H256::from(2)))).unwrap();
+			writer.write(&SnapshotRecord::ValuePools(ValuePools { sprout: 1, sapling: 2, orchard: 3 })).unwrap();
+			writer.finish().unwrap()
+		};
+		(out, digest)
+	}
+
+	#[test]
+	fn snapshot_roundtrip() {
+		let (snapshot, digest) = write_snapshot();
+		let mut reader = SnapshotReader::new(&snapshot[..]).unwrap();
+		assert_eq!(reader.height(), 1);
+		assert_eq!(reader.block_hash(), &H256::from(1));
+
+		let mut records = Vec::new();
+		while let Some(record) = reader.read().unwrap() {
+			records.push(record);
+		}
+
+		assert_eq!(records.len(), 4);
+		match records[2] {
+			SnapshotRecord::Nullifier(ref nullifier) => assert_eq!(nullifier, &EpochRef::new(EpochTag::Sapling, H256::from(2))),
+			_ => panic!("expected nullifier record"),
+		}
+		match records[3] {
+			SnapshotRecord::ValuePools(ref value_pools) => assert_eq!(value_pools, &ValuePools { sprout: 1, sapling: 2, orchard: 3 }),
+			_ => panic!("expected value pools record"),
+		}
+		assert_eq!(reader.digest(), Some(&digest));
+	}
+
+	#[test]
+	fn corrupted_snapshot_is_rejected() {
+		let (mut snapshot, _) = write_snapshot();
+		// corrupt the sprout value pool
+		let len = snapshot.len();
+		snapshot[len - 33 - 24] ^= 1;
+
+		let mut reader = SnapshotReader::new(&snapshot[..]).unwrap();
+		let mut result = reader.read();
+		while let Ok(Some(_)) = result {
+			result = reader.read();
+		}
+		assert!(result.is_err());
+
+		// truncated snapshot
+		let (snapshot, _) = write_snapshot();
+		let mut reader = SnapshotReader::new(&snapshot[..snapshot.len() - 1]).unwrap();
+		let mut result = reader.read();
+		while let Ok(Some(_)) = result {
+			result = reader.read();
+		}
+		assert!(result.is_err());
+	}
+}
@@ -0,0 +1,10 @@
+use Error;
+
+/// Stores chain configuration, which is written once, when database is created.
+pub trait ConfigStore: Send + Sync {
+	/// Get name of the network the database has been created for.
+	fn network(&self) -> Result<Option<String>, Error>;
+
+	/// Set name of the network the database is created for.
+	fn set_network(&self, network: &str) -> Result<(), Error>;
+}
@@ -0,0 +1,20 @@
+/// Settings && size of the database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DatabaseInfo {
+	/// Is the database stored on disk? Settings && size of in-memory databases are zero.
+	pub on_disk: bool,
+	/// Total size of key-value store files (in bytes).
+	pub size: u64,
+	/// Number of raw blocks files.
+	pub block_files: usize,
+	/// Total size of raw blocks files (in bytes).
+	pub block_files_size: u64,
+	/// Total size of column caches (in MiB).
+	pub cache_size: usize,
+	/// Size of the write buffer of every column (in MiB).
+	pub write_buffer_size: usize,
+	/// Compaction style of the key-value store.
+	pub compaction_style: String,
+	/// Maximal number of files, opened by the key-value store.
+	pub max_open_files: i32,
+}
@@ -0,0 +1,6 @@
+use DatabaseInfo;
+
+pub trait DatabaseInfoProvider: Send + Sync {
+	/// Settings && current size of the database.
+	fn database_info(&self) -> DatabaseInfo;
+}
@@ -15,6 +15,12 @@ pub enum Error {
 	/// Invalid block
 	#[display(fmt = "Cannot decanonize block (invalid database state)")]
 	CannotDecanonize,
+	/// Database has been created (or upgraded) by the newer version of the node
+	#[display(fmt = "Database version {} is not supported (the latest supported version is {}), upgrade the node", _0, _1)]
+	UnsupportedDatabaseVersion(u32, u32),
+	/// Invalid chain state snapshot
+	#[display(fmt = "Invalid snapshot: {}", _0)]
+	InvalidSnapshot(String),
 }
 
 impl From<Error> for String {
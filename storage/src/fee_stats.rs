@@ -0,0 +1,197 @@
+//! Per-block statistics of transaction fees
+
+use std::io;
+use chain::{Amount, Transaction, fee_rate};
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+use TransactionOutputProvider;
+
+/// Fee statistics of non-coinbase transactions of the block.
+///
+/// All feerates are in zatoshis per 1000 bytes of serialized transaction.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeeStats {
+	/// Number of transactions, fees of which are included in the statistics.
+	pub transactions: u32,
+	/// Sum of fees of all transactions.
+	pub total_fee: u64,
+	/// Sum of serialized sizes of all transactions.
+	pub total_size: u64,
+	/// Minimal feerate.
+	pub min_feerate: u64,
+	/// Median feerate.
+	pub median_feerate: u64,
+	/// 90th percentile feerate.
+	pub percentile_90_feerate: u64,
+	/// Maximal feerate.
+	pub max_feerate: u64,
+}
+
+impl FeeStats {
+	/// Compute statistics of given (non-coinbase) transactions. Previous outputs are read from
+	/// the `store`. Transactions, fee of which can't be computed, are ignored.
+	pub fn from_transactions<'a, I>(store: &TransactionOutputProvider, transactions: I) -> Self where I: IntoIterator<Item=(usize, &'a Transaction)> {
+		FeeStats::from_fees(transactions.into_iter()
+			.filter_map(|(tx_index, tx)| transaction_fee(store, tx_index, tx).map(|fee| (fee, tx.size()))))
+	}
+
+	/// Compute statistics of given (fee, serialized size) pairs.
+	pub fn from_fees<I>(fees: I) -> Self where I: IntoIterator<Item=(u64, usize)> {
+		let mut stats = FeeStats::default();
+		let mut feerates = Vec::new();
+		for (fee, size) in fees {
+			stats.transactions += 1;
+			stats.total_fee += fee;
+			stats.total_size += size as u64;
+			feerates.push(fee_rate(fee, size));
+		}
+
+		if feerates.is_empty() {
+			return stats;
+		}
+
+		feerates.sort();
+		stats.min_feerate = feerates[0];
+		stats.median_feerate = percentile(&feerates, 50);
+		stats.percentile_90_feerate = percentile(&feerates, 90);
+		stats.max_feerate = feerates[feerates.len() - 1];
+		stats
+	}
+
+	/// Average feerate of all transactions.
+	pub fn average_feerate(&self) -> u64 {
+		if self.total_size == 0 {
+			return 0;
+		}
+
+		fee_rate(self.total_fee, self.total_size as usize)
+	}
+}
+
+/// Nearest-rank percentile of sorted non-empty values.
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+	let rank = (sorted.len() * percent + 99) / 100;
+	sorted[rank.saturating_sub(1)]
+}
+
+/// Fee (transparent + shielded inputs minus transparent + shielded outputs) of the transaction.
+fn transaction_fee(store: &TransactionOutputProvider, tx_index: usize, tx: &Transaction) -> Option<u64> {
+	let mut incoming = Amount::zero();
+	for input in &tx.inputs {
+		let prevout = store.transaction_output(&input.previous_output, tx_index)?;
+		incoming = incoming.checked_add(Amount::from_u64(prevout.value)?)?;
+	}
+
+	let mut spends = Amount::zero();
+	for output in &tx.outputs {
+		spends = spends.checked_add(Amount::from_u64(output.value)?)?;
+	}
+
+	if let Some(ref join_split) = tx.join_split {
+		for description in &join_split.descriptions {
+			incoming = incoming.checked_add(Amount::from_u64(description.value_pub_new)?)?;
+			spends = spends.checked_add(Amount::from_u64(description.value_pub_old)?)?;
+		}
+	}
+
+	let shielded_balances = tx.sapling.as_ref().map(|sapling| sapling.balancing_value).into_iter()
+		.chain(tx.orchard.as_ref().map(|orchard| orchard.value_balance));
+	for balance in shielded_balances {
+		let balance = Amount::from_zatoshis(balance)?;
+		if balance.is_negative() {
+			spends = spends.checked_add(-balance)?;
+		} else {
+			incoming = incoming.checked_add(balance)?;
+		}
+	}
+
+	incoming.checked_sub(spends).and_then(Amount::to_u64)
+}
+
+impl Serializable for FeeStats {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.transactions)
+			.append(&self.total_fee)
+			.append(&self.total_size)
+			.append(&self.min_feerate)
+			.append(&self.median_feerate)
+			.append(&self.percentile_90_feerate)
+			.append(&self.max_feerate);
+	}
+}
+
+impl Deserializable for FeeStats {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(FeeStats {
+			transactions: reader.read()?,
+			total_fee: reader.read()?,
+			total_size: reader.read()?,
+			min_feerate: reader.read()?,
+			median_feerate: reader.read()?,
+			percentile_90_feerate: reader.read()?,
+			max_feerate: reader.read()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, TransactionInput, TransactionOutput, OutPoint, Sapling};
+	use ser::{serialize, deserialize};
+	use duplex_store::NoopStore;
+	use super::FeeStats;
+
+	#[test]
+	fn test_fee_stats_from_fees() {
+		assert_eq!(FeeStats::from_fees(vec![]), FeeStats::default());
+
+		let stats = FeeStats::from_fees(vec![
+			(1_000, 250),
+			(100, 1_000),
+			(10_000, 500),
+			(0, 200),
+			(3_000, 1_000),
+		]);
+		assert_eq!(stats, FeeStats {
+			transactions: 5,
+			total_fee: 14_100,
+			total_size: 2_950,
+			min_feerate: 0,
+			median_feerate: 3_000,
+			percentile_90_feerate: 20_000,
+			max_feerate: 20_000,
+		});
+		assert_eq!(stats.average_feerate(), 4_779);
+
+		assert_eq!(deserialize::<_, FeeStats>(serialize(&stats).as_ref()).unwrap(), stats);
+	}
+
+	#[test]
+	fn test_fee_stats_from_transactions() {
+		let transactions = vec![
+			// deshielding sapling transaction: 50 enters, 40 leaves => fee is 10
+			Transaction {
+				outputs: vec![TransactionOutput { value: 40, ..Default::default() }],
+				sapling: Some(Sapling {
+					balancing_value: 50,
+					outputs: vec![Default::default()],
+					..Default::default()
+				}),
+				..Default::default()
+			},
+			// transaction with unknown input is ignored
+			Transaction {
+				inputs: vec![TransactionInput {
+					previous_output: OutPoint { hash: 1.into(), index: 0 },
+					..Default::default()
+				}],
+				..Default::default()
+			},
+		];
+
+		let stats = FeeStats::from_transactions(&NoopStore, transactions.iter().enumerate());
+		assert_eq!(stats.transactions, 1);
+		assert_eq!(stats.total_fee, 10);
+		assert_eq!(stats.total_size, serialize(&transactions[0]).len() as u64);
+	}
+}
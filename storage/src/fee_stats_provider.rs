@@ -0,0 +1,7 @@
+use hash::H256;
+use FeeStats;
+
+pub trait FeeStatsProvider: Send + Sync {
+	/// Fee statistics of the canon block with given hash.
+	fn block_fee_stats(&self, block_hash: &H256) -> Option<FeeStats>;
+}
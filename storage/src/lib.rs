@@ -12,6 +12,8 @@ extern crate bitcrypto as crypto;
 #[macro_use] extern crate lazy_static;
 extern crate network;
 
+mod address_index;
+mod address_index_provider;
 mod best_block;
 mod block_ancestors;
 mod block_chain;
@@ -20,17 +22,36 @@ mod block_iterator;
 mod block_origin;
 mod block_provider;
 mod block_ref;
+mod block_undo;
+mod chain_event;
+mod chain_snapshot;
+mod chain_event_provider;
+mod config_store;
+mod database_info;
+mod database_info_provider;
 mod duplex_store;
 mod error;
+mod fee_stats;
+mod fee_stats_provider;
 mod store;
 mod transaction_meta;
 mod transaction_provider;
 mod nullifier_tracker;
+mod read_snapshot;
+mod shielded_stats;
+mod shielded_stats_provider;
+mod snapshot_store;
+mod spent_index;
+mod spent_index_provider;
 mod tree_state;
 mod tree_state_provider;
+mod value_pools;
+mod value_pools_provider;
 
 pub use primitives::{hash, bytes};
 
+pub use address_index::{AddressIndexEntry, address_index_key};
+pub use address_index_provider::AddressIndexProvider;
 pub use best_block::BestBlock;
 pub use block_ancestors::BlockAncestors;
 pub use block_chain::{BlockChain, ForkChain, Forkable};
@@ -38,16 +59,33 @@ pub use block_iterator::BlockIterator;
 pub use block_origin::{BlockOrigin, SideChainOrigin};
 pub use block_provider::{BlockHeaderProvider, BlockProvider};
 pub use block_ref::BlockRef;
+pub use block_undo::BlockUndo;
+pub use chain_event::{ChainEvent, ChainEventKind};
+pub use chain_snapshot::{SnapshotInfo, SnapshotRecord, SnapshotWriter, SnapshotReader};
+pub use chain_event_provider::ChainEventProvider;
+pub use config_store::ConfigStore;
+pub use database_info::DatabaseInfo;
+pub use database_info_provider::DatabaseInfoProvider;
 pub use duplex_store::{DuplexTransactionOutputProvider, NoopStore};
 pub use error::Error;
+pub use fee_stats::FeeStats;
+pub use fee_stats_provider::FeeStatsProvider;
 pub use store::{AsSubstore, Store, SharedStore, CanonStore};
 pub use transaction_meta::TransactionMeta;
 pub use transaction_provider::{
 	TransactionProvider, TransactionOutputProvider, TransactionMetaProvider, CachedTransactionOutputProvider,
 };
-pub use nullifier_tracker::NullifierTracker;
+pub use nullifier_tracker::{NullifierTracker, transaction_nullifiers};
+pub use read_snapshot::ReadSnapshot;
+pub use shielded_stats::ShieldedStats;
+pub use shielded_stats_provider::ShieldedStatsProvider;
+pub use snapshot_store::SnapshotStore;
+pub use spent_index::SpentIndexEntry;
+pub use spent_index_provider::SpentIndexProvider;
 pub use tree_state::{TreeState, H32 as H32TreeDim, Dim as TreeDim, SproutTreeState, SaplingTreeState};
 pub use tree_state_provider::TreeStateProvider;
+pub use value_pools::{ValuePools, ValuePool};
+pub use value_pools_provider::ValuePoolsProvider;
 
 use hash::H256;
 
@@ -55,16 +93,18 @@ use hash::H256;
 ///
 /// Sprout and Sapling nullifiers/commitments are considered disjoint,
 /// even if they have the same bit pattern.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EpochTag {
 	/// Sprout epoch.
 	Sprout,
 	/// Sapling epoch.
 	Sapling,
+	/// Orchard epoch.
+	Orchard,
 }
 
 /// H256-reference to some object that is valid within single epoch (nullifiers, commitment trees, ...).
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct EpochRef {
 	epoch: EpochTag,
 	hash: H256,
@@ -98,3 +138,27 @@ impl From<(EpochTag, H256)> for EpochRef {
 		}
 	}
 }
+
+impl ser::Serializable for EpochRef {
+	fn serialize(&self, stream: &mut ser::Stream) {
+		let epoch: u8 = match self.epoch {
+			EpochTag::Sprout => 0,
+			EpochTag::Sapling => 1,
+			EpochTag::Orchard => 2,
+		};
+		stream.append(&epoch).append(&self.hash);
+	}
+}
+
+impl ser::Deserializable for EpochRef {
+	fn deserialize<T>(reader: &mut ser::Reader<T>) -> Result<Self, ser::Error> where T: ::std::io::Read {
+		let epoch = match reader.read::<u8>()? {
+			0 => EpochTag::Sprout,
+			1 => EpochTag::Sapling,
+			2 => EpochTag::Orchard,
+			_ => return Err(ser::Error::MalformedData),
+		};
+
+		Ok(EpochRef::new(epoch, reader.read()?))
+	}
+}
@@ -1,6 +1,15 @@
-use EpochRef;
+use chain::Transaction;
+use {EpochRef, EpochTag};
 
 /// Trait to query existing nullifier.
 pub trait NullifierTracker : Sync {
 	fn contains_nullifier(&self, nullifier: EpochRef) -> bool;
 }
+
+/// All nullifiers (Sprout, Sapling && Orchard), revealed by the transaction.
+pub fn transaction_nullifiers(tx: &Transaction) -> Vec<EpochRef> {
+	tx.sprout_nullifiers().into_iter().map(|nullifier| EpochRef::new(EpochTag::Sprout, nullifier))
+		.chain(tx.sapling_nullifiers().into_iter().map(|nullifier| EpochRef::new(EpochTag::Sapling, nullifier)))
+		.chain(tx.orchard_nullifiers().into_iter().map(|nullifier| EpochRef::new(EpochTag::Orchard, nullifier)))
+		.collect()
+}
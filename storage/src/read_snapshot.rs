@@ -0,0 +1,35 @@
+use parking_lot::RwLockReadGuard;
+use {BestBlock, AsSubstore};
+
+/// Consistent read snapshot of the canon chain.
+///
+/// Canon chain is not modified (blocks are neither canonized nor decanonized) while the snapshot
+/// is alive, so all reads made through the snapshot observe the same best block.
+pub struct ReadSnapshot<'a> {
+	/// Best block at the moment the snapshot has been taken.
+	best_block: BestBlock,
+	/// Storage, which canon chain is read.
+	store: &'a AsSubstore,
+	/// Guard, preventing canon chain modifications.
+	_guard: RwLockReadGuard<'a, ()>,
+}
+
+impl<'a> ReadSnapshot<'a> {
+	pub fn new(best_block: BestBlock, store: &'a AsSubstore, guard: RwLockReadGuard<'a, ()>) -> Self {
+		ReadSnapshot {
+			best_block: best_block,
+			store: store,
+			_guard: guard,
+		}
+	}
+
+	/// Best block of the snapshot.
+	pub fn best_block(&self) -> &BestBlock {
+		&self.best_block
+	}
+
+	/// Storage, read at the snapshot.
+	pub fn store(&self) -> &AsSubstore {
+		self.store
+	}
+}
@@ -0,0 +1,212 @@
+//! Per-block statistics of shielded transactions
+
+use std::io;
+use chain::Transaction;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+
+/// Shielded transactions statistics of the block (or of the range of blocks).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShieldedStats {
+	/// Number of transactions with Sprout JoinSplits.
+	pub sprout_transactions: u32,
+	/// Number of transactions with Sapling spends or outputs.
+	pub sapling_transactions: u32,
+	/// Number of transactions moving transparent value into shielded pools.
+	pub shielding_transactions: u32,
+	/// Number of transactions moving shielded value into transparent outputs.
+	pub deshielding_transactions: u32,
+	/// Number of transactions without transparent inputs and outputs.
+	pub fully_shielded_transactions: u32,
+	/// Transparent value that has entered Sprout pool.
+	pub sprout_value_in: u64,
+	/// Value that has left Sprout pool.
+	pub sprout_value_out: u64,
+	/// Transparent value that has entered Sapling pool.
+	pub sapling_value_in: u64,
+	/// Value that has left Sapling pool.
+	pub sapling_value_out: u64,
+}
+
+impl ShieldedStats {
+	/// Compute statistics of given transactions.
+	pub fn from_transactions<'a, I>(transactions: I) -> Self where I: IntoIterator<Item=&'a Transaction> {
+		let mut stats = ShieldedStats::default();
+		for transaction in transactions {
+			stats.note_transaction(transaction);
+		}
+		stats
+	}
+
+	/// Add statistics of other block(s).
+	pub fn add(&mut self, other: &ShieldedStats) {
+		self.sprout_transactions += other.sprout_transactions;
+		self.sapling_transactions += other.sapling_transactions;
+		self.shielding_transactions += other.shielding_transactions;
+		self.deshielding_transactions += other.deshielding_transactions;
+		self.fully_shielded_transactions += other.fully_shielded_transactions;
+		self.sprout_value_in += other.sprout_value_in;
+		self.sprout_value_out += other.sprout_value_out;
+		self.sapling_value_in += other.sapling_value_in;
+		self.sapling_value_out += other.sapling_value_out;
+	}
+
+	fn note_transaction(&mut self, transaction: &Transaction) {
+		let (mut sprout_in, mut sprout_out) = (0u64, 0u64);
+		let is_sprout = match transaction.join_split {
+			Some(ref join_split) if !join_split.descriptions.is_empty() => {
+				for description in &join_split.descriptions {
+					sprout_in += description.value_pub_old;
+					sprout_out += description.value_pub_new;
+				}
+				true
+			},
+			_ => false,
+		};
+
+		// negative balancing value means that value enters Sapling pool
+		let (mut sapling_in, mut sapling_out) = (0u64, 0u64);
+		let is_sapling = match transaction.sapling {
+			Some(ref sapling) if !sapling.spends.is_empty() || !sapling.outputs.is_empty() => {
+				if sapling.balancing_value < 0 {
+					sapling_in = sapling.balancing_value.wrapping_neg() as u64;
+				} else {
+					sapling_out = sapling.balancing_value as u64;
+				}
+				true
+			},
+			_ => false,
+		};
+
+		if !is_sprout && !is_sapling {
+			return;
+		}
+
+		if is_sprout {
+			self.sprout_transactions += 1;
+		}
+		if is_sapling {
+			self.sapling_transactions += 1;
+		}
+
+		self.sprout_value_in += sprout_in;
+		self.sprout_value_out += sprout_out;
+		self.sapling_value_in += sapling_in;
+		self.sapling_value_out += sapling_out;
+
+		let (pool_in, pool_out) = (sprout_in + sapling_in, sprout_out + sapling_out);
+		if transaction.inputs.is_empty() && transaction.outputs.is_empty() {
+			self.fully_shielded_transactions += 1;
+		} else if pool_in > pool_out {
+			self.shielding_transactions += 1;
+		} else if pool_out > pool_in {
+			self.deshielding_transactions += 1;
+		}
+	}
+}
+
+impl Serializable for ShieldedStats {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.sprout_transactions)
+			.append(&self.sapling_transactions)
+			.append(&self.shielding_transactions)
+			.append(&self.deshielding_transactions)
+			.append(&self.fully_shielded_transactions)
+			.append(&self.sprout_value_in)
+			.append(&self.sprout_value_out)
+			.append(&self.sapling_value_in)
+			.append(&self.sapling_value_out);
+	}
+}
+
+impl Deserializable for ShieldedStats {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(ShieldedStats {
+			sprout_transactions: reader.read()?,
+			sapling_transactions: reader.read()?,
+			shielding_transactions: reader.read()?,
+			deshielding_transactions: reader.read()?,
+			fully_shielded_transactions: reader.read()?,
+			sprout_value_in: reader.read()?,
+			sprout_value_out: reader.read()?,
+			sapling_value_in: reader.read()?,
+			sapling_value_out: reader.read()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, TransactionInput, TransactionOutput, JoinSplit, JoinSplitDescription, Sapling};
+	use ser::{serialize, deserialize};
+	use super::ShieldedStats;
+
+	fn join_split(value_pub_old: u64, value_pub_new: u64) -> Option<JoinSplit> {
+		Some(JoinSplit {
+			descriptions: vec![JoinSplitDescription {
+				value_pub_old: value_pub_old,
+				value_pub_new: value_pub_new,
+				..Default::default()
+			}],
+			..Default::default()
+		})
+	}
+
+	fn sapling(balancing_value: i64) -> Option<Sapling> {
+		Some(Sapling {
+			balancing_value: balancing_value,
+			outputs: vec![Default::default()],
+			..Default::default()
+		})
+	}
+
+	#[test]
+	fn test_shielded_stats() {
+		let transactions = vec![
+			// transparent transaction
+			Transaction {
+				inputs: vec![TransactionInput::default()],
+				outputs: vec![TransactionOutput::default()],
+				..Default::default()
+			},
+			// shielding sprout transaction
+			Transaction {
+				inputs: vec![TransactionInput::default()],
+				join_split: join_split(100, 0),
+				..Default::default()
+			},
+			// deshielding sapling transaction
+			Transaction {
+				outputs: vec![TransactionOutput::default()],
+				sapling: sapling(50),
+				..Default::default()
+			},
+			// fully shielded transaction
+			Transaction {
+				join_split: join_split(0, 10),
+				sapling: sapling(-10),
+				..Default::default()
+			},
+		];
+
+		let stats = ShieldedStats::from_transactions(&transactions);
+		assert_eq!(stats, ShieldedStats {
+			sprout_transactions: 2,
+			sapling_transactions: 2,
+			shielding_transactions: 1,
+			deshielding_transactions: 1,
+			fully_shielded_transactions: 1,
+			sprout_value_in: 100,
+			sprout_value_out: 10,
+			sapling_value_in: 10,
+			sapling_value_out: 50,
+		});
+
+		let mut total = stats.clone();
+		total.add(&stats);
+		assert_eq!(total.sprout_transactions, 4);
+		assert_eq!(total.sapling_value_out, 100);
+
+		assert_eq!(deserialize::<_, ShieldedStats>(serialize(&stats).as_ref()).unwrap(), stats);
+	}
+}
@@ -0,0 +1,7 @@
+use hash::H256;
+use ShieldedStats;
+
+pub trait ShieldedStatsProvider: Send + Sync {
+	/// Shielded transactions statistics of the block with given hash.
+	fn block_shielded_stats(&self, block_hash: &H256) -> Option<ShieldedStats>;
+}
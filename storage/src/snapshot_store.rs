@@ -0,0 +1,13 @@
+use std::io;
+use hash::H256;
+use {Error, SnapshotInfo};
+
+/// Exports && imports snapshots of the canon chain state.
+pub trait SnapshotStore: Send + Sync {
+	/// Write snapshot of the chain state after the best block.
+	fn export_snapshot(&self, writer: &mut io::Write) -> Result<SnapshotInfo, Error>;
+
+	/// Initialize empty database with the chain state from the snapshot. Chain must start with given genesis block.
+	/// Blocks, preceding the snapshot block, are imported as pruned: only their headers are known.
+	fn import_snapshot(&self, reader: &mut io::Read, genesis_hash: &H256) -> Result<SnapshotInfo, Error>;
+}
@@ -0,0 +1,51 @@
+//! Optional index of transaction inputs by the outputs they spend
+
+use std::io;
+use hash::H256;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+
+/// Canon chain input, spending the indexed output.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SpentIndexEntry {
+	/// Hash of the spending transaction.
+	pub tx_hash: H256,
+	/// Index of the input in the spending transaction.
+	pub input_index: u32,
+	/// Height of the block, containing the spending transaction.
+	pub height: u32,
+}
+
+impl Serializable for SpentIndexEntry {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.tx_hash)
+			.append(&self.input_index)
+			.append(&self.height);
+	}
+}
+
+impl Deserializable for SpentIndexEntry {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(SpentIndexEntry {
+			tx_hash: reader.read()?,
+			input_index: reader.read()?,
+			height: reader.read()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ser::{serialize, deserialize};
+	use super::SpentIndexEntry;
+
+	#[test]
+	fn test_spent_index_entry_serialization() {
+		let entry = SpentIndexEntry {
+			tx_hash: 1.into(),
+			input_index: 2,
+			height: 100,
+		};
+		assert_eq!(deserialize::<_, SpentIndexEntry>(serialize(&entry).as_ref()).unwrap(), entry);
+	}
+}
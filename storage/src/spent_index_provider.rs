@@ -0,0 +1,10 @@
+use chain::OutPoint;
+use SpentIndexEntry;
+
+pub trait SpentIndexProvider: Send + Sync {
+	/// Is spent index maintained by the storage?
+	fn is_spent_index_enabled(&self) -> bool;
+
+	/// Canon chain input, spending given output.
+	fn spent_index_entry(&self, outpoint: &OutPoint) -> Option<SpentIndexEntry>;
+}
@@ -2,10 +2,12 @@ use std::sync::Arc;
 use chain::IndexedBlockHeader;
 use {
 	BestBlock, BlockProvider, BlockHeaderProvider, TransactionProvider, TransactionMetaProvider,
-	TransactionOutputProvider, BlockChain, Forkable, NullifierTracker, TreeStateProvider,
+	TransactionOutputProvider, BlockChain, Forkable, NullifierTracker, TreeStateProvider, ConfigStore,
+	ReadSnapshot, ShieldedStatsProvider, FeeStatsProvider, ChainEventProvider, ValuePoolsProvider,
+	AddressIndexProvider, SpentIndexProvider, SnapshotStore, DatabaseInfoProvider,
 };
 
-pub trait CanonStore: Store + Forkable {
+pub trait CanonStore: Store + Forkable + ConfigStore + SnapshotStore {
 	fn as_store(&self) -> &Store;
 }
 
@@ -16,6 +18,9 @@ pub trait Store: AsSubstore {
 
 	/// get best header
 	fn best_header(&self) -> IndexedBlockHeader;
+
+	/// get consistent read snapshot of the canon chain
+	fn read_snapshot(&self) -> ReadSnapshot;
 }
 
 /// Allows casting Arc<Store> to reference to any substore type
@@ -25,7 +30,14 @@ pub trait AsSubstore:
 	TransactionProvider +
 	TransactionMetaProvider +
 	TransactionOutputProvider +
-	NullifierTracker
+	NullifierTracker +
+	ShieldedStatsProvider +
+	FeeStatsProvider +
+	ChainEventProvider +
+	ValuePoolsProvider +
+	AddressIndexProvider +
+	SpentIndexProvider +
+	DatabaseInfoProvider
 {
 	fn as_block_provider(&self) -> &BlockProvider;
 
@@ -40,6 +52,20 @@ pub trait AsSubstore:
 	fn as_nullifier_tracker(&self) -> &NullifierTracker;
 
 	fn as_tree_state_provider(&self) -> &TreeStateProvider;
+
+	fn as_shielded_stats_provider(&self) -> &ShieldedStatsProvider;
+
+	fn as_fee_stats_provider(&self) -> &FeeStatsProvider;
+
+	fn as_chain_event_provider(&self) -> &ChainEventProvider;
+
+	fn as_value_pools_provider(&self) -> &ValuePoolsProvider;
+
+	fn as_address_index_provider(&self) -> &AddressIndexProvider;
+
+	fn as_spent_index_provider(&self) -> &SpentIndexProvider;
+
+	fn as_database_info_provider(&self) -> &DatabaseInfoProvider;
 }
 
 impl<T> AsSubstore for T
@@ -49,7 +75,14 @@ impl<T> AsSubstore for T
 		TransactionMetaProvider +
 		TransactionOutputProvider +
 		NullifierTracker +
-		TreeStateProvider
+		TreeStateProvider +
+		ShieldedStatsProvider +
+		FeeStatsProvider +
+		ChainEventProvider +
+		ValuePoolsProvider +
+		AddressIndexProvider +
+		SpentIndexProvider +
+		DatabaseInfoProvider
 {
 	fn as_block_provider(&self) -> &BlockProvider {
 		&*self
@@ -78,6 +111,34 @@ impl<T> AsSubstore for T
 	fn as_tree_state_provider(&self) -> &TreeStateProvider {
 		&*self
 	}
+
+	fn as_shielded_stats_provider(&self) -> &ShieldedStatsProvider {
+		&*self
+	}
+
+	fn as_fee_stats_provider(&self) -> &FeeStatsProvider {
+		&*self
+	}
+
+	fn as_chain_event_provider(&self) -> &ChainEventProvider {
+		&*self
+	}
+
+	fn as_value_pools_provider(&self) -> &ValuePoolsProvider {
+		&*self
+	}
+
+	fn as_address_index_provider(&self) -> &AddressIndexProvider {
+		&*self
+	}
+
+	fn as_spent_index_provider(&self) -> &SpentIndexProvider {
+		&*self
+	}
+
+	fn as_database_info_provider(&self) -> &DatabaseInfoProvider {
+		&*self
+	}
 }
 
 pub type SharedStore = Arc<CanonStore + Send + Sync>;
@@ -6,7 +6,7 @@ use bytes::Bytes;
 use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
 
 /// structure for indexing transaction info
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TransactionMeta {
 	block_height: u32,
 	/// first bit indicate if transaction is a coinbase transaction
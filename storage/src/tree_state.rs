@@ -1,4 +1,5 @@
 use hash::H256;
+use bytes::Bytes;
 use crypto::{sha256_compress, pedersen_hash};
 
 lazy_static! {
@@ -266,6 +267,17 @@ impl<D: Dim, H: TreeHash> TreeState<D, H> {
 	pub fn empty_root() -> H256 {
 		H::empty()[D::HEIGHT]
 	}
+
+	/// Serialize tree state the way zcashd serializes its `IncrementalMerkleTree` (i.e. without trailing
+	/// empty parents). This is the `finalState` of `z_gettreestate` RPC.
+	pub fn to_zcashd_bytes(&self) -> Bytes {
+		let parents_len = self.parents.iter().rposition(Option::is_some).map(|index| index + 1).unwrap_or_default();
+		let mut stream = serialization::Stream::new();
+		stream.append(&self.left);
+		stream.append(&self.right);
+		stream.append_list(&self.parents[..parents_len]);
+		stream.out()
+	}
 }
 
 pub type SproutTreeState = TreeState<H29, SproutTreeHash>;
@@ -542,6 +554,21 @@ mod tests {
 		assert_eq!(deserialized_tree.root(), H256::from("0bf622cb9f901b7532433ea2e7c1b7632f5935899b62dcf897a71551997dc8cc"));
 	}
 
+	#[test]
+	fn zcashd_bytes() {
+		let mut tree = TestSproutTreeState::new();
+		assert_eq!(&*tree.to_zcashd_bytes(), &[0u8, 0, 0][..]);
+
+		tree.append(TEST_COMMITMENTS[0].clone()).unwrap();
+		tree.append(TEST_COMMITMENTS[1].clone()).unwrap();
+		tree.append(TEST_COMMITMENTS[2].clone()).unwrap();
+
+		// left && single (non-trailing) parent
+		let bytes = tree.to_zcashd_bytes();
+		assert_eq!(bytes.len(), 1 + 32 + 1 + 1 + 1 + 32);
+		assert_eq!(&bytes[1..33], &TEST_COMMITMENTS[2][..]);
+	}
+
 	#[test]
 	fn serde_empty() {
 		let tree = TestSproutTreeState::new();
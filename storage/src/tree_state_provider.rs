@@ -1,4 +1,5 @@
 use hash::H256;
+use chain::HistoryTree;
 use {SproutTreeState, SaplingTreeState};
 
 pub trait TreeStateProvider : Send + Sync {
@@ -10,6 +11,13 @@ pub trait TreeStateProvider : Send + Sync {
 
 	fn sapling_block_root(&self, block_hash: &H256) -> Option<H256>;
 
+	/// Height of the first canon block, which sapling tree has given root. None if root isn't a root of
+	/// any canon block (i.e. it isn't a valid anchor of sapling spends).
+	fn sapling_anchor_height(&self, root: &H256) -> Option<u32>;
+
+	/// Chain history tree after the block. Only known for Heartwood && Canopy blocks.
+	fn history_tree_at_block(&self, block_hash: &H256) -> Option<HistoryTree>;
+
 	fn sprout_tree_at_block(&self, block_hash: &H256) -> Option<SproutTreeState> {
 		self.sprout_block_root(block_hash).and_then(|h| self.sprout_tree_at(&h))
 	}
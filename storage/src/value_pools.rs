@@ -0,0 +1,155 @@
+//! Balances of the shielded value pools
+
+use std::io;
+use chain::Transaction;
+use ser::{Serializable, Deserializable, Error as ReaderError, Stream, Reader};
+
+/// Shielded value pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValuePool {
+	/// Sprout pool.
+	Sprout,
+	/// Sapling pool.
+	Sapling,
+	/// Orchard pool.
+	Orchard,
+}
+
+impl ValuePool {
+	/// Pool name, as reported by RPC.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			ValuePool::Sprout => "sprout",
+			ValuePool::Sapling => "sapling",
+			ValuePool::Orchard => "orchard",
+		}
+	}
+}
+
+/// Total value (in zatoshis), held by every shielded pool after some block.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ValuePools {
+	/// Value of Sprout pool.
+	pub sprout: u64,
+	/// Value of Sapling pool.
+	pub sapling: u64,
+	/// Value of Orchard pool.
+	pub orchard: u64,
+}
+
+impl ValuePools {
+	/// Balances of the pools after given transactions (of the single block) are applied.
+	///
+	/// Value can't be created in shielded pools, so the pool, which would be driven negative
+	/// (turnstile violation), is returned as error.
+	pub fn apply_transactions<'a, I>(&self, transactions: I) -> Result<ValuePools, ValuePool>
+		where I: IntoIterator<Item=&'a Transaction>
+	{
+		let (mut sprout, mut sapling, mut orchard) = (0i128, 0i128, 0i128);
+		for transaction in transactions {
+			if let Some(ref join_split) = transaction.join_split {
+				for description in &join_split.descriptions {
+					sprout += description.value_pub_old as i128 - description.value_pub_new as i128;
+				}
+			}
+
+			// positive balancing value means that value leaves shielded pool
+			if let Some(ref transaction_sapling) = transaction.sapling {
+				sapling -= transaction_sapling.balancing_value as i128;
+			}
+			if let Some(ref transaction_orchard) = transaction.orchard {
+				orchard -= transaction_orchard.value_balance as i128;
+			}
+		}
+
+		Ok(ValuePools {
+			sprout: apply_change(self.sprout, sprout, ValuePool::Sprout)?,
+			sapling: apply_change(self.sapling, sapling, ValuePool::Sapling)?,
+			orchard: apply_change(self.orchard, orchard, ValuePool::Orchard)?,
+		})
+	}
+}
+
+fn apply_change(value: u64, change: i128, pool: ValuePool) -> Result<u64, ValuePool> {
+	let value = value as i128 + change;
+	if value < 0 || value > u64::max_value() as i128 {
+		return Err(pool);
+	}
+
+	Ok(value as u64)
+}
+
+impl Serializable for ValuePools {
+	fn serialize(&self, stream: &mut Stream) {
+		stream
+			.append(&self.sprout)
+			.append(&self.sapling)
+			.append(&self.orchard);
+	}
+}
+
+impl Deserializable for ValuePools {
+	fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError> where T: io::Read {
+		Ok(ValuePools {
+			sprout: reader.read()?,
+			sapling: reader.read()?,
+			orchard: reader.read()?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chain::{Transaction, JoinSplit, JoinSplitDescription, Sapling, Orchard};
+	use ser::{serialize, deserialize};
+	use super::{ValuePools, ValuePool};
+
+	fn sprout_transaction(value_pub_old: u64, value_pub_new: u64) -> Transaction {
+		Transaction {
+			join_split: Some(JoinSplit {
+				descriptions: vec![JoinSplitDescription {
+					value_pub_old: value_pub_old,
+					value_pub_new: value_pub_new,
+					..Default::default()
+				}],
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn value_pools_apply_transactions() {
+		let sapling = Transaction {
+			sapling: Some(Sapling { balancing_value: -300, ..Default::default() }),
+			..Default::default()
+		};
+		let orchard = Transaction {
+			orchard: Some(Orchard { value_balance: -50, ..Default::default() }),
+			..Default::default()
+		};
+
+		let pools = ValuePools::default()
+			.apply_transactions(&[sprout_transaction(100, 0), sapling, orchard])
+			.unwrap();
+		assert_eq!(pools, ValuePools { sprout: 100, sapling: 300, orchard: 50 });
+
+		// value may leave the pool within the same block it has entered the pool
+		let pools = pools.apply_transactions(&[sprout_transaction(0, 150), sprout_transaction(60, 0)]).unwrap();
+		assert_eq!(pools.sprout, 10);
+
+		// but pool can't go negative
+		assert_eq!(pools.apply_transactions(&[sprout_transaction(0, 11)]), Err(ValuePool::Sprout));
+		let sapling = Transaction {
+			sapling: Some(Sapling { balancing_value: 301, ..Default::default() }),
+			..Default::default()
+		};
+		assert_eq!(pools.apply_transactions(&[sapling]), Err(ValuePool::Sapling));
+	}
+
+	#[test]
+	fn value_pools_serde() {
+		let pools = ValuePools { sprout: 1, sapling: 2, orchard: 3 };
+		assert_eq!(deserialize::<_, ValuePools>(&*serialize(&pools)).unwrap(), pools);
+	}
+}
@@ -0,0 +1,7 @@
+use hash::H256;
+use ValuePools;
+
+pub trait ValuePoolsProvider: Send + Sync {
+	/// Balances of the shielded value pools after the block with given hash.
+	fn block_value_pools(&self, block_hash: &H256) -> Option<ValuePools>;
+}
@@ -5,7 +5,7 @@ use chain;
 use storage;
 use network::ConsensusParams;
 use primitives::hash::H256;
-use super::Error;
+use super::{Error, TransactionRejection};
 use synchronization_chain::Chain;
 use synchronization_verifier::{
 	Verifier, SyncVerifier, VerificationTask, HeadersVerificationSink,
@@ -127,8 +127,8 @@ impl BlockVerificationSink for BlocksWriterSink {
 		None
 	}
 
-	fn on_block_verification_error(&self, err: &str, _hash: &H256) {
-		self.data.lock().err = Some(Error::Verification(err.into()));
+	fn on_block_verification_error(&self, err: &str, hash: &H256) {
+		self.data.lock().err = Some(Error::Verification(format!("block {}: {}", hash.to_reversed_str(), err)));
 	}
 }
 
@@ -137,7 +137,7 @@ impl TransactionVerificationSink for BlocksWriterSink {
 		unreachable!("not intended to verify transactions")
 	}
 
-	fn on_transaction_verification_error(&self, _err: &str, _hash: &H256) {
+	fn on_transaction_verification_error(&self, _err: &TransactionRejection, _hash: &H256) {
 		unreachable!("not intended to verify transactions")
 	}
 }
@@ -159,7 +159,7 @@ mod tests {
 	use std::sync::Arc;
 	use db::{BlockChainDatabase};
 	use network::{ConsensusParams, Network};
-	use verification::VerificationLevel;
+	use verification::{VerificationLevel, DEFAULT_SIGNATURE_CACHE_SIZE, DEFAULT_SCRIPT_CACHE_SIZE};
 	use super::super::Error;
 	use super::{BlocksWriter, MAX_ORPHANED_BLOCKS};
 	use VerificationParameters;
@@ -168,6 +168,9 @@ mod tests {
 		VerificationParameters {
 			verification_level: VerificationLevel::FULL,
 			verification_edge: 0u8.into(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		}
 	}
 
@@ -231,6 +234,9 @@ mod tests {
 		let mut blocks_target = BlocksWriter::new(db.clone(), ConsensusParams::new(Network::Testnet), VerificationParameters {
 			verification_level: VerificationLevel::NO_VERIFICATION,
 			verification_edge: 0u8.into(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		});
 		assert_eq!(blocks_target.append_block(b1.into()), Ok(()));
 		assert_eq!(blocks_target.append_block(b2.into()), Ok(()));
@@ -1,4 +1,5 @@
-use chain::{IndexedTransaction, IndexedBlock, IndexedBlockHeader};
+use chain::{IndexedTransaction, IndexedBlockHeader};
+use message::common::InventoryType;
 use message::types;
 use p2p::{InboundSyncConnection, InboundSyncConnectionRef, InboundSyncConnectionStateRef};
 use types::{PeersRef, LocalNodeRef, PeerIndex, RequestId};
@@ -55,6 +56,15 @@ impl InboundSyncConnection for InboundConnection {
 			return;
 		}
 
+		// peer knows about everything it announces => we should not announce it back
+		for item in &message.inventory {
+			match item.inv_type {
+				InventoryType::MessageTx => self.peers.hash_known_as(self.peer_index, item.hash.clone(), KnownHashType::Transaction),
+				InventoryType::MessageBlock => self.peers.hash_known_as(self.peer_index, item.hash.clone(), KnownHashType::Block),
+				_ => (),
+			}
+		}
+
 		self.node.on_inventory(self.peer_index, message);
 	}
 
@@ -87,7 +97,7 @@ impl InboundSyncConnection for InboundConnection {
 	}
 
 	fn on_block(&self, message: types::Block) {
-		let block = IndexedBlock::from_raw(message.block);
+		let block = message.block;
 		self.peers.hash_known_as(self.peer_index, block.hash().clone(), KnownHashType::Block);
 		self.node.on_block(self.peer_index, block);
 	}
@@ -163,7 +173,7 @@ pub mod tests {
 	use std::sync::Arc;
 	use parking_lot::Mutex;
 	use message::types;
-	use p2p::OutboundSyncConnection;
+	use p2p::{OutboundSyncConnection, Misbehaviour};
 	use types::RequestId;
 
 	pub struct DummyOutboundSyncConnection {
@@ -196,6 +206,7 @@ pub mod tests {
 		fn send_feefilter(&self, _message: &types::FeeFilter) { *self.messages.lock().entry("feefilter".to_owned()).or_insert(0) += 1; }
 		fn send_notfound(&self, _message: &types::NotFound) { *self.messages.lock().entry("notfound".to_owned()).or_insert(0) += 1; }
 		fn ignored(&self, _id: RequestId) {}
-		fn close(&self) {}
+		fn note_block_delivered(&self) {}
+		fn close(&self, _misbehaviour: Misbehaviour) {}
 	}
 }
@@ -40,10 +40,11 @@ mod utils;
 
 pub use types::LocalNodeRef;
 pub use types::PeersRef;
+pub use utils::BlockArrivalInformation;
 
 use std::sync::Arc;
 use parking_lot::RwLock;
-use network::{Network, ConsensusParams};
+use network::{Network, ConsensusParams, BlockLimits};
 use primitives::hash::H256;
 use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
 
@@ -58,6 +59,61 @@ pub enum Error {
 	Verification(String),
 }
 
+/// Reason of transaction rejection by memory pool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionRejection {
+	/// Node is not yet synchronized, so transactions can't be verified.
+	Synchronizing,
+	/// Some of transaction inputs are unknown (neither in the chain, nor in the memory pool).
+	MissingInputs,
+	/// Transaction fee is too low to accept transaction into memory pool.
+	InsufficientFee,
+	/// Transaction has expired.
+	Expired,
+	/// Transaction is valid, but doesn't satisfy memory pool policy.
+	NonStandard(String),
+	/// Transaction violates consensus rules.
+	Consensus(String),
+}
+
+impl From<verification::TransactionError> for TransactionRejection {
+	fn from(err: verification::TransactionError) -> Self {
+		use verification::TransactionError;
+
+		match err {
+			TransactionError::Input(_) | TransactionError::UnknownReference(_) => TransactionRejection::MissingInputs,
+			TransactionError::Expired => TransactionRejection::Expired,
+			err => TransactionRejection::Consensus(err.to_string()),
+		}
+	}
+}
+
+impl From<miner::PolicyError> for TransactionRejection {
+	fn from(err: miner::PolicyError) -> Self {
+		match err {
+			miner::PolicyError::InsufficientFee => TransactionRejection::InsufficientFee,
+			miner::PolicyError::NonStandard(reason) => TransactionRejection::NonStandard(reason.into()),
+		}
+	}
+}
+
+/// Reason of standalone block header rejection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderRejection {
+	/// Header is already known.
+	Duplicate,
+	/// Header is already known to be invalid (or to be a descendant of invalid block).
+	DuplicateInvalid,
+	/// Parent of the header is unknown.
+	UnknownParent,
+	/// Parent of the header is known to be invalid.
+	InvalidParent,
+	/// Header contradicts hardcoded checkpoint at given height.
+	CheckpointMismatch(u32),
+	/// Header fails context-free verification.
+	Invalid(String),
+}
+
 #[derive(Debug, Clone)]
 /// Verification parameters.
 pub struct VerificationParameters {
@@ -66,6 +122,14 @@ pub struct VerificationParameters {
 	/// Blocks verification edge: all blocks before this are validated using verification_level.
 	/// All blocks after this (inclusive) are validated using VerificationLevel::Full level.
 	pub verification_edge: H256,
+	/// If true, verification_level is only applied to blocks that are ancestors of the verification edge
+	/// in the headers chain. All other blocks (side chain blocks included) are validated using
+	/// VerificationLevel::Full level.
+	pub edge_ancestors_only: bool,
+	/// Maximal number of valid signatures, cached by verifiers.
+	pub signature_cache_size: usize,
+	/// Maximal number of successful script executions, cached by verifiers.
+	pub script_cache_size: usize,
 }
 
 /// Synchronization events listener
@@ -88,8 +152,9 @@ pub fn create_sync_peers() -> PeersRef {
 	Arc::new(PeersImpl::default())
 }
 
-/// Creates local sync node for given `db`
-pub fn create_local_sync_node(consensus: ConsensusParams, db: storage::SharedStore, peers: PeersRef, verification_params: VerificationParameters) -> LocalNodeRef {
+/// Creates local sync node for given `db`. `blocks_memory_budget` limits total size (in bytes) of downloaded blocks,
+/// which are waiting for verification && storage.
+pub fn create_local_sync_node(consensus: ConsensusParams, db: storage::SharedStore, peers: PeersRef, verification_params: VerificationParameters, blocks_memory_budget: usize, fee_policy: miner::FeePolicy, standardness_policy: miner::StandardnessPolicy, block_limits: BlockLimits) -> LocalNodeRef {
 	use miner::MemoryPool;
 	use synchronization_chain::Chain as SyncChain;
 	use synchronization_executor::LocalSynchronizationTaskExecutor as SyncExecutor;
@@ -105,14 +170,20 @@ pub fn create_local_sync_node(consensus: ConsensusParams, db: storage::SharedSto
 	let sync_client_config = SynchronizationConfig {
 		// during regtests, peer is providing us with bad blocks => we shouldn't close connection because of this
 		close_connection_on_bad_block: network != Network::Regtest,
+		checkpoints: network.checkpoints(),
+		verification_edge: verification_params.verification_edge.clone(),
+		blocks_memory_budget: blocks_memory_budget,
 	};
 
-	let memory_pool = Arc::new(RwLock::new(MemoryPool::new()));
+	let memory_pool = Arc::new(RwLock::new(MemoryPool::with_policies(fee_policy, standardness_policy)));
 	let sync_state = SynchronizationStateRef::new(SynchronizationState::with_storage(db.clone()));
 	let sync_chain = SyncChain::new(db.clone(), memory_pool.clone());
 
-	let light_chain_verifier = Arc::new(ChainVerifier::new(db.clone(), consensus.clone()));
-	let heavy_chain_verifier = Arc::new(ChainVerifier::new(db.clone(), consensus.clone()));
+	// verifiers are sharing caches, so scripts && signatures of memory pool transactions aren't verified again, when they're mined
+	let signature_cache = Arc::new(verification::SignatureCache::new(verification_params.signature_cache_size));
+	let script_cache = Arc::new(verification::ScriptCache::new(verification_params.script_cache_size));
+	let light_chain_verifier = Arc::new(ChainVerifier::with_caches(db.clone(), consensus.clone(), signature_cache.clone(), script_cache.clone()));
+	let heavy_chain_verifier = Arc::new(ChainVerifier::with_caches(db.clone(), consensus.clone(), signature_cache, script_cache));
 	let sync_executor = SyncExecutor::new(peers.clone());
 	let sync_server = Arc::new(ServerImpl::new(peers.clone(), db.clone(), memory_pool.clone(), sync_executor.clone()));
 	let sync_client_core = SynchronizationClientCore::new(sync_client_config, sync_state.clone(), peers.clone(), sync_executor.clone(), sync_chain);
@@ -134,7 +205,7 @@ pub fn create_local_sync_node(consensus: ConsensusParams, db: storage::SharedSto
 		verification_params,
 	);
 	let sync_client = SynchronizationClient::new(sync_state.clone(), sync_client_core, light_verifier, heavy_verifier);
-	Arc::new(SyncNode::new(consensus, db, memory_pool, peers, sync_state, sync_client, sync_server))
+	Arc::new(SyncNode::new(consensus, db, memory_pool, peers, sync_state, sync_client, sync_server, block_limits))
 }
 
 /// Create inbound synchronization connections factory for given local sync node.
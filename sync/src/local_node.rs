@@ -4,9 +4,10 @@ use time;
 use futures::{lazy, finished};
 use chain::{IndexedTransaction, IndexedBlock, IndexedBlockHeader};
 use keys::Address;
-use message::types;
+use message::{types, Payload};
+use p2p::Misbehaviour;
 use miner::BlockAssembler;
-use network::ConsensusParams;
+use network::{ConsensusParams, BlockLimits};
 use synchronization_client::{Client};
 use synchronization_server::{Server, ServerTask};
 use synchronization_verifier::{TransactionVerificationSink};
@@ -15,6 +16,9 @@ use miner::BlockTemplate;
 use synchronization_peers::{TransactionAnnouncementType, BlockAnnouncementType};
 use types::{PeerIndex, RequestId, StorageRef, MemoryPoolRef, PeersRef,
 	ClientRef, ServerRef, SynchronizationStateRef, SyncListenerRef, BlockHeight};
+use utils::{BlockArrivalStats, BlockArrivalKind, BlockArrivalInformation};
+use verification::HeaderVerifier;
+use {TransactionRejection, HeaderRejection};
 
 /// Local synchronization node
 pub struct LocalNode<U: Server, V: Client> {
@@ -32,6 +36,10 @@ pub struct LocalNode<U: Server, V: Client> {
 	client: ClientRef<V>,
 	/// Synchronization server
 	server: ServerRef<U>,
+	/// Recent block arrivals
+	block_arrivals: Mutex<BlockArrivalStats>,
+	/// Limits of blocks, assembled for mining
+	block_limits: BlockLimits,
 }
 
 /// Transaction accept verification sink
@@ -41,7 +49,7 @@ struct TransactionAcceptSink {
 
 #[derive(Default)]
 struct TransactionAcceptSinkData {
-	result: Mutex<Option<Result<H256, String>>>,
+	result: Mutex<Option<Result<H256, TransactionRejection>>>,
 	waiter: Condvar,
 }
 
@@ -49,7 +57,7 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 	/// Create new synchronization node
 	#[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
 	pub fn new(consensus: ConsensusParams, storage: StorageRef, memory_pool: MemoryPoolRef, peers: PeersRef,
-		state: SynchronizationStateRef, client: ClientRef<V>, server: ServerRef<U>) -> Self {
+		state: SynchronizationStateRef, client: ClientRef<V>, server: ServerRef<U>, block_limits: BlockLimits) -> Self {
 		LocalNode {
 			consensus: consensus,
 			storage: storage,
@@ -58,6 +66,8 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 			state: state,
 			client: client,
 			server: server,
+			block_arrivals: Mutex::default(),
+			block_limits: block_limits,
 		}
 	}
 
@@ -75,6 +85,14 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 			self.peers.set_transaction_announcement_type(peer_index, TransactionAnnouncementType::DoNotAnnounce);
 		}
 
+		// ask peer to not announce transactions, that won't be accepted to our memory pool
+		let min_relay_fee_rate = self.memory_pool.read().standardness_policy().min_relay_fee_rate;
+		if min_relay_fee_rate != 0 && version.version() >= types::FeeFilter::version() {
+			if let Some(connection) = self.peers.connection(peer_index) {
+				connection.send_feefilter(&types::FeeFilter::with_fee_rate(min_relay_fee_rate));
+			}
+		}
+
 		// start synchronization session with peer
 		self.client.on_connect(peer_index);
 	}
@@ -108,6 +126,7 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 	/// When block is received
 	pub fn on_block(&self, peer_index: PeerIndex, block: IndexedBlock) {
 		trace!(target: "sync", "Got `block` message from peer#{}. Block hash: {}", peer_index, block.header.hash.to_reversed_str());
+		self.note_block_arrival(&block);
 		self.client.on_block(peer_index, block);
 	}
 
@@ -185,29 +204,35 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 	pub fn on_merkleblock(&self, peer_index: PeerIndex, _message: types::MerkleBlock) {
 		trace!(target: "sync", "Got `merkleblock` message from peer#{}", peer_index);
 		// we never setup filter on connections => misbehaving
-		self.peers.misbehaving(peer_index, "Got unrequested 'merkleblock' message");
+		self.peers.misbehaving(peer_index, Misbehaviour::Protocol, "Got unrequested 'merkleblock' message");
 	}
 
 	/// Verify and then schedule new transaction
-	pub fn accept_transaction(&self, transaction: IndexedTransaction) -> Result<H256, String> {
+	pub fn accept_transaction(&self, transaction: IndexedTransaction) -> Result<H256, TransactionRejection> {
 		let sink_data = Arc::new(TransactionAcceptSinkData::default());
 		let sink = TransactionAcceptSink::new(sink_data.clone()).boxed();
 		{
 			if let Err(err) = self.client.accept_transaction(transaction, sink) {
-				return Err(err.into());
+				return Err(err);
 			}
 		}
 		sink_data.wait()
 	}
 
+	/// Verify standalone block header and accept it into the headers chain. Block of the header
+	/// is requested from peers.
+	pub fn accept_header(&self, header: IndexedBlockHeader) -> Result<(), HeaderRejection> {
+		let current_time = time::get_time().sec as u32;
+		HeaderVerifier::new(&header, &self.consensus, current_time).check()
+			.map_err(|err| HeaderRejection::Invalid(err.to_string()))?;
+		self.client.accept_header(header)
+	}
+
 	/// Get block template for mining
 	pub fn get_block_template(&self, miner_address: &Address) -> Result<BlockTemplate, String> {
-		let max_block_size = self.consensus.max_block_size();
-		let max_block_sigops = self.consensus.max_block_sigops();
 		let block_assembler = BlockAssembler {
 			miner_address: miner_address,
-			max_block_size: max_block_size as u32,
-			max_block_sigops: max_block_sigops as u32,
+			limits: self.block_limits,
 		};
 		let memory_pool = &*self.memory_pool.read();
 		block_assembler.create_new_block(&self.storage, memory_pool, time::get_time().sec as u32, &self.consensus)
@@ -218,10 +243,42 @@ impl<U, V> LocalNode<U, V> where U: Server, V: Client {
 		self.state.best_storage_block_height()
 	}
 
+	/// Get statistics of blocks, received within last `window` seconds
+	pub fn block_arrival_information(&self, window: u32) -> BlockArrivalInformation {
+		self.block_arrivals.lock().information(time::get_time().sec as u32, window)
+	}
+
 	/// Install synchronization events listener
 	pub fn install_sync_listener(&self, listener: SyncListenerRef) {
 		self.client.install_sync_listener(listener);
 	}
+
+	/// Are there any headers, blocks or transactions, which are currently verified?
+	pub fn is_verifying(&self) -> bool {
+		self.client.is_verifying()
+	}
+}
+
+impl<U, V> LocalNode<U, V> where U: Server, V: Client {
+	/// Remember when block has been received && how it relates to our best block.
+	/// Blocks received during initial synchronization are ignored, since they are requested
+	/// by us rather than relayed by network.
+	fn note_block_arrival(&self, block: &IndexedBlock) {
+		if self.state.synchronizing() || self.storage.contains_block(block.header.hash.clone().into()) {
+			return;
+		}
+
+		let parent_hash = &block.header.raw.previous_header_hash;
+		let kind = if *parent_hash == self.storage.best_block().hash {
+			BlockArrivalKind::InOrder
+		} else if self.storage.contains_block(parent_hash.clone().into()) {
+			BlockArrivalKind::Stale
+		} else {
+			BlockArrivalKind::OutOfOrder
+		};
+
+		self.block_arrivals.lock().note_arrival(time::get_time().sec as u32, block.header.raw.time, kind);
+	}
 }
 
 impl TransactionAcceptSink {
@@ -237,7 +294,7 @@ impl TransactionAcceptSink {
 }
 
 impl TransactionAcceptSinkData {
-	pub fn wait(&self) -> Result<H256, String> {
+	pub fn wait(&self) -> Result<H256, TransactionRejection> {
 		let mut lock = self.result.lock();
 		if lock.is_some() {
 			return lock.take().expect("checked line above");
@@ -254,8 +311,8 @@ impl TransactionVerificationSink for TransactionAcceptSink {
 		self.data.waiter.notify_all();
 	}
 
-	fn on_transaction_verification_error(&self, err: &str, _hash: &H256) {
-		*self.data.result.lock() = Some(Err(err.to_owned()));
+	fn on_transaction_verification_error(&self, err: &TransactionRejection, _hash: &H256) {
+		*self.data.result.lock() = Some(Err(err.clone()));
 		self.data.waiter.notify_all();
 	}
 }
@@ -269,7 +326,7 @@ pub mod tests {
 	use synchronization_executor::Task;
 	use synchronization_executor::tests::DummyTaskExecutor;
 	use synchronization_client::SynchronizationClient;
-	use synchronization_client_core::{Config, SynchronizationClientCore, CoreVerificationSink};
+	use synchronization_client_core::{Config, SynchronizationClientCore, CoreVerificationSink, DEFAULT_BLOCKS_MEMORY_BUDGET};
 	use synchronization_chain::Chain;
 	use message::types;
 	use message::common::{InventoryVector, InventoryType};
@@ -281,6 +338,7 @@ pub mod tests {
 	use synchronization_server::ServerTask;
 	use synchronization_server::tests::DummyServer;
 	use synchronization_verifier::tests::DummyVerifier;
+	use TransactionRejection;
 	use primitives::bytes::Bytes;
 	use std::iter::repeat;
 	use synchronization_peers::PeersImpl;
@@ -310,7 +368,7 @@ pub mod tests {
 		let sync_peers = Arc::new(PeersImpl::default());
 		let executor = DummyTaskExecutor::new();
 		let server = Arc::new(DummyServer::new());
-		let config = Config { close_connection_on_bad_block: true };
+		let config = Config { close_connection_on_bad_block: true, checkpoints: Vec::new(), verification_edge: 0u8.into(), blocks_memory_budget: DEFAULT_BLOCKS_MEMORY_BUDGET };
 		let client_core = SynchronizationClientCore::new(config, sync_state.clone(), sync_peers.clone(), executor.clone(), chain);
 		let mut light_verifier = DummyVerifier::default();
 		light_verifier.set_sink(Arc::new(CoreVerificationSink::new(client_core.clone())));
@@ -320,7 +378,9 @@ pub mod tests {
 		};
 		heavy_verifier.set_sink(Arc::new(CoreVerificationSink::new(client_core.clone())));
 		let client = SynchronizationClient::new(sync_state.clone(), client_core, light_verifier, heavy_verifier);
-		let local_node = LocalNode::new(ConsensusParams::new(Network::Mainnet), storage, memory_pool, sync_peers, sync_state, client, server.clone());
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let block_limits = consensus.block_limits;
+		let local_node = LocalNode::new(consensus, storage, memory_pool, sync_peers, sync_state, client, server.clone(), block_limits);
 		(executor, server, local_node)
 	}
 
@@ -378,7 +438,7 @@ pub mod tests {
 		executor.take_tasks();
 
 		let result = local_node.accept_transaction(transaction.into());
-		assert_eq!(result, Err("simulated".to_owned()));
+		assert_eq!(result, Err(TransactionRejection::Consensus("simulated".to_owned())));
 
 		assert_eq!(executor.take_tasks(), vec![]);
 	}
@@ -6,6 +6,7 @@ use storage;
 use miner::{MemoryPoolOrderingStrategy, MemoryPoolInformation, FeeCalculator};
 use primitives::bytes::Bytes;
 use primitives::hash::H256;
+use primitives::timings::{self, Stage};
 use utils::{BestHeadersChain, BestHeadersChainInformation, HashQueueChain, HashPosition};
 use types::{BlockHeight, StorageRef, MemoryPoolRef};
 
@@ -177,6 +178,13 @@ impl Chain {
 		}
 	}
 
+	/// Are there any headers, blocks or transactions, which are currently verified?
+	pub fn is_verifying(&self) -> bool {
+		!self.verifying_headers.is_empty()
+			|| self.hash_chain.len_of(VERIFYING_QUEUE) != 0
+			|| !self.verifying_transactions.is_empty()
+	}
+
 	/// Get storage
 	pub fn storage(&self) -> StorageRef {
 		self.storage.clone()
@@ -363,6 +371,7 @@ impl Chain {
 		assert_eq!(Some(self.storage.best_block().hash), self.storage.block_hash(self.storage.best_block().number));
 		let block_origin = self.storage.block_origin(&block.header)?;
 		trace!(target: "sync", "insert_best_block {:?} origin: {:?}", block.hash().reversed(), block_origin);
+		timings::note_block();
 		match block_origin {
 			storage::BlockOrigin::KnownBlock => {
 				// there should be no known blocks at this point
@@ -370,8 +379,11 @@ impl Chain {
 			},
 			// case 1: block has been added to the main branch
 			storage::BlockOrigin::CanonChain { .. } => {
-				self.storage.insert(block.clone())?;
-				self.storage.canonize(block.hash())?;
+				{
+					let _timer = timings::start(Stage::DatabaseWrite);
+					self.storage.insert(block.clone())?;
+					self.storage.canonize(block.hash())?;
+				}
 
 				// remember new best block hash
 				self.best_storage_block = self.storage.as_store().best_block();
@@ -394,6 +406,10 @@ impl Chain {
 						memory_pool.remove_by_prevout(&tx_input.previous_output);
 					}
 				}
+				// + also remove transactions which can't be included in the next block, because they have expired
+				for tx in memory_pool.remove_expired(self.best_storage_block.number + 1) {
+					self.verifying_transactions.remove(&tx.hash);
+				}
 				// no transactions to reverify, because we have just appended new transactions to the blockchain
 
 				Ok(BlockInsertionResult {
@@ -403,10 +419,13 @@ impl Chain {
 			},
 			// case 2: block has been added to the side branch with reorganization to this branch
 			storage::BlockOrigin::SideChainBecomingCanonChain(origin) => {
-				let fork = self.storage.fork(origin.clone())?;
-				fork.store().insert(block.clone())?;
-				fork.store().canonize(block.hash())?;
-				self.storage.switch_to_fork(fork)?;
+				{
+					let _timer = timings::start(Stage::DatabaseWrite);
+					let fork = self.storage.fork(origin.clone())?;
+					fork.store().insert(block.clone())?;
+					fork.store().canonize(block.hash())?;
+					self.storage.switch_to_fork(fork)?;
+				}
 
 				// remember new best block hash
 				self.best_storage_block = self.storage.best_block();
@@ -471,7 +490,10 @@ impl Chain {
 			// case 3: block has been added to the side branch without reorganization to this branch
 			storage::BlockOrigin::SideChain(_origin) => {
 				let block_hash = block.hash().clone();
-				self.storage.insert(block)?;
+				{
+					let _timer = timings::start(Stage::DatabaseWrite);
+					self.storage.insert(block)?;
+				}
 
 				// remove inserted block + handle possible reorganization in headers chain
 				// TODO: mk, not sure if it's needed here at all
@@ -783,6 +805,7 @@ mod tests {
 		assert_eq!(chain.information().requested, 0);
 		assert_eq!(chain.information().verifying, 0);
 		assert_eq!(chain.information().stored, 1);
+		assert!(!chain.is_verifying());
 		assert_eq!(chain.length_of_blocks_state(BlockState::Scheduled), 0);
 		assert_eq!(chain.length_of_blocks_state(BlockState::Requested), 0);
 		assert_eq!(chain.length_of_blocks_state(BlockState::Verifying), 0);
@@ -827,11 +850,13 @@ mod tests {
 		assert_eq!(chain.forget_block_with_state(&hashes[0], BlockState::Requested), HashPosition::Front);
 		assert!(chain.information().scheduled == 3 && chain.information().requested == 1
 			&& chain.information().verifying == 0 && chain.information().stored == 1);
+		assert!(!chain.is_verifying());
 		// mark 0 & 1 as verifying
 		chain.verify_block(headers[0].clone().into());
 		chain.verify_block(headers[1].clone().into());
 		assert!(chain.information().scheduled == 3 && chain.information().requested == 1
 			&& chain.information().verifying == 2 && chain.information().stored == 1);
+		assert!(chain.is_verifying());
 
 		// mark block 0 as verified
 		assert_eq!(chain.forget_block_with_state(&hashes[0], BlockState::Verifying), HashPosition::Front);
@@ -1153,6 +1178,27 @@ mod tests {
 		assert_eq!(chain.information().transactions.transactions_count, 0);
 	}
 
+	#[test]
+	fn expired_transaction_is_removed_from_memory_pool_when_block_is_inserted() {
+		let genesis = test_data::genesis();
+		let b0 = test_data::block_builder().header().nonce(1.into()).parent(genesis.hash()).build()
+			.transaction().coinbase().build()
+			.build(); // genesis -> b0
+		// tx1 can be included in b0, but not in the block after b0
+		let tx1: Transaction = test_data::TransactionBuilder::with_output(10).set_overwintered(true).set_expiry_height(1).into();
+		let tx2: Transaction = test_data::TransactionBuilder::with_output(20).set_overwintered(true).set_expiry_height(2).into();
+		let (tx1_hash, tx2_hash) = (tx1.hash(), tx2.hash());
+
+		let db = Arc::new(BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]));
+		let mut chain = Chain::new(db, Arc::new(RwLock::new(MemoryPool::new())));
+		chain.insert_verified_transaction(tx1.into());
+		chain.insert_verified_transaction(tx2.into());
+		chain.insert_best_block(b0.into()).expect("no error");
+		// => tx1 is removed from memory pool, but tx2 remains
+		assert_eq!(chain.transaction_state(&tx1_hash), TransactionState::Unknown);
+		assert_eq!(chain.transaction_state(&tx2_hash), TransactionState::InMemory);
+	}
+
 	#[test]
 	fn update_memory_pool_transaction() {
 		use self::test_data::{ChainBuilder, TransactionBuilder};
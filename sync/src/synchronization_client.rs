@@ -6,6 +6,7 @@ use synchronization_executor::TaskExecutor;
 use synchronization_verifier::{Verifier, TransactionVerificationSink};
 use synchronization_client_core::{ClientCore, SynchronizationClientCore};
 use types::{PeerIndex, ClientCoreRef, SynchronizationStateRef, EmptyBoxFuture, SyncListenerRef};
+use {TransactionRejection, HeaderRejection};
 
 #[cfg_attr(feature="cargo-clippy", allow(doc_markdown))]
 ///! TODO: update with headers-first corrections
@@ -129,8 +130,10 @@ pub trait Client : Send + Sync + 'static {
 	fn on_transaction(&self, peer_index: PeerIndex, transaction: IndexedTransaction);
 	fn on_notfound(&self, peer_index: PeerIndex, message: types::NotFound);
 	fn after_peer_nearly_blocks_verified(&self, peer_index: PeerIndex, future: EmptyBoxFuture);
-	fn accept_transaction(&self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<(), String>;
+	fn accept_transaction(&self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<(), TransactionRejection>;
+	fn accept_header(&self, header: IndexedBlockHeader) -> Result<(), HeaderRejection>;
 	fn install_sync_listener(&self, listener: SyncListenerRef);
+	fn is_verifying(&self) -> bool;
 }
 
 /// Synchronization client facade
@@ -219,7 +222,7 @@ impl<T, U> Client for SynchronizationClient<T, U> where T: TaskExecutor, U: Veri
 		self.core.lock().after_peer_nearly_blocks_verified(peer_index, future);
 	}
 
-	fn accept_transaction(&self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<(), String> {
+	fn accept_transaction(&self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<(), TransactionRejection> {
 		let mut transactions_to_verify = try!(self.core.lock().accept_transaction(transaction, sink));
 
 		let next_block_height = self.shared_state.best_storage_block_height() + 1;
@@ -229,9 +232,17 @@ impl<T, U> Client for SynchronizationClient<T, U> where T: TaskExecutor, U: Veri
 		Ok(())
 	}
 
+	fn accept_header(&self, header: IndexedBlockHeader) -> Result<(), HeaderRejection> {
+		self.core.lock().accept_header(header)
+	}
+
 	fn install_sync_listener(&self, listener: SyncListenerRef) {
 		self.core.lock().install_sync_listener(listener);
 	}
+
+	fn is_verifying(&self) -> bool {
+		self.core.lock().chain().is_verifying()
+	}
 }
 
 impl<T, U> SynchronizationClient<T, U> where T: TaskExecutor, U: Verifier {
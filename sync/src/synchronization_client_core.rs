@@ -9,6 +9,7 @@ use time::precise_time_s;
 use chain::{IndexedBlockHeader, IndexedTransaction, IndexedBlock};
 use message::types;
 use message::common::{InventoryType, InventoryVector};
+use p2p::Misbehaviour;
 use miner::transaction_fee_rate;
 use primitives::hash::H256;
 use synchronization_chain::{Chain, BlockState, TransactionState, BlockInsertionResult};
@@ -21,6 +22,7 @@ use synchronization_verifier::{
 };
 use types::{BlockHeight, ClientCoreRef, PeersRef, PeerIndex, SynchronizationStateRef, EmptyBoxFuture, SyncListenerRef};
 use utils::{AverageSpeedMeter, OrphanBlocksPool, OrphanTransactionsPool, HashPosition};
+use {TransactionRejection, HeaderRejection};
 #[cfg(test)] use synchronization_peers_tasks::{Information as PeersTasksInformation};
 #[cfg(test)] use synchronization_chain::{Information as ChainInformation};
 
@@ -50,6 +52,8 @@ const MIN_HEADERS_DUPLICATION_INTERVAL_S: f64 = 2_f64;
 const MAX_BLOCKS_IN_DUPLICATE_REQUEST: BlockHeight = 4;
 /// Minimal number of blocks in duplicate requests.
 const MIN_BLOCKS_IN_DUPLICATE_REQUEST: BlockHeight = 8;
+/// Default limit of total size of downloaded blocks, which are waiting for verification && storage.
+pub const DEFAULT_BLOCKS_MEMORY_BUDGET: usize = 512 * 1024 * 1024;
 
 /// Information on current synchronization state.
 #[cfg(test)]
@@ -77,7 +81,8 @@ pub trait ClientCore {
 	fn on_transaction(&mut self, peer_index: PeerIndex, transaction: IndexedTransaction) -> Option<VecDeque<IndexedTransaction>>;
 	fn on_notfound(&mut self, peer_index: PeerIndex, message: types::NotFound);
 	fn after_peer_nearly_blocks_verified(&mut self, peer_index: PeerIndex, future: EmptyBoxFuture);
-	fn accept_transaction(&mut self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<VecDeque<IndexedTransaction>, String>;
+	fn accept_transaction(&mut self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<VecDeque<IndexedTransaction>, TransactionRejection>;
+	fn accept_header(&mut self, header: IndexedBlockHeader) -> Result<(), HeaderRejection>;
 	fn install_sync_listener(&mut self, listener: SyncListenerRef);
 	fn execute_synchronization_tasks(&mut self, forced_blocks_requests: Option<Vec<H256>>, final_blocks_requests: Option<Vec<H256>>);
 	fn try_switch_to_saturated_state(&mut self) -> bool;
@@ -88,6 +93,13 @@ pub trait ClientCore {
 pub struct Config {
 	/// If true, connection to peer who has provided us with bad block is closed
 	pub close_connection_on_bad_block: bool,
+	/// Hardcoded (height, hash) checkpoints. Peers providing headers that contradict checkpoints are disconnected.
+	pub checkpoints: Vec<(BlockHeight, H256)>,
+	/// Verification edge. Ancestors of this block in the headers chain are verified with the reduced verification level.
+	pub verification_edge: H256,
+	/// Limit of total size of orphaned && verifying blocks (in bytes). When the limit is reached,
+	/// new blocks aren't requested until verification frees some memory.
+	pub blocks_memory_budget: usize,
 }
 
 /// Synchronization client.
@@ -112,6 +124,10 @@ pub struct SynchronizationClientCore<T: TaskExecutor> {
 	orphaned_transactions_pool: OrphanTransactionsPool,
 	/// Verifying blocks by peer
 	verifying_blocks_by_peer: HashMap<H256, PeerIndex>,
+	/// Sizes of verifying blocks
+	verifying_blocks_sizes: HashMap<H256, usize>,
+	/// Total size of verifying blocks
+	verifying_blocks_memory: usize,
 	/// Verifying blocks futures
 	verifying_blocks_futures: HashMap<PeerIndex, (HashSet<H256>, Vec<EmptyBoxFuture>)>,
 	/// Verifying transactions futures
@@ -226,7 +242,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 						BlockState::Unknown => !self.orphaned_blocks_pool.contains_unknown_block(&item.hash),
 						BlockState::DeadEnd if !self.config.close_connection_on_bad_block => true,
 						BlockState::DeadEnd if self.config.close_connection_on_bad_block => {
-							self.peers.misbehaving(peer_index, &format!("Provided dead-end block {:?}", item.hash.to_reversed_str()));
+							self.peers.misbehaving(peer_index, Misbehaviour::Consensus, &format!("Provided dead-end block {:?}", item.hash.to_reversed_str()));
 							false
 						},
 						_ => false,
@@ -235,7 +251,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 					InventoryType::MessageFilteredBlock => false,
 					// unknown inventory type
 					InventoryType::Error => {
-						self.peers.misbehaving(peer_index, &format!("Provided unknown inventory type {:?}", item.hash.to_reversed_str()));
+						self.peers.misbehaving(peer_index, Misbehaviour::Protocol, &format!("Provided unknown inventory type {:?}", item.hash.to_reversed_str()));
 						false
 					}
 				}
@@ -276,7 +292,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 			// there could be competing chains that are running the network with the same magic (like Zcash vs ZelCash)
 			// => providing unknown headers. Penalize node so that it'll disconnect
 			if self.peers_tasks.penalize(peer_index) {
-				self.peers.misbehaving(peer_index, "Too many failures.");
+				self.peers.misbehaving(peer_index, Misbehaviour::Resource, "Too many failures.");
 			}
 
 			return None;
@@ -296,15 +312,18 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 		// validate blocks headers before scheduling
 		let mut last_known_hash = headers[0].raw.previous_header_hash;
 		if self.config.close_connection_on_bad_block && self.chain.block_state(&last_known_hash) == BlockState::DeadEnd {
-			self.peers.misbehaving(peer_index, &format!("Provided after dead-end block {}", last_known_hash.to_reversed_str()));
+			self.peers.misbehaving(peer_index, Misbehaviour::Consensus, &format!("Provided after dead-end block {}", last_known_hash.to_reversed_str()));
 			return None;
 		}
 
+		// height is unknown if parent is a side chain block => checkpoints are checked during verification
+		let first_header_height = self.chain.block_number(&last_known_hash).map(|height| height + 1);
 		for (header_index, header) in headers.iter().enumerate() {
 			// check that this header is direct child of previous header
 			if header.raw.previous_header_hash != last_known_hash {
 				self.peers.misbehaving(
 					peer_index,
+					Misbehaviour::Protocol,
 					&format!(
 						"Neighbour headers in `headers` message are unlinked: Prev: {}, PrevLink: {}, Curr: {}",
 						last_known_hash.to_reversed_str(),
@@ -315,6 +334,22 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 				return None;
 			}
 
+			// check that this header does not contradict checkpoints
+			if let Some(header_height) = first_header_height.map(|height| height + header_index as BlockHeight) {
+				if self.contradicts_checkpoint(header_height, &header.hash) {
+					self.peers.misbehaving(
+						peer_index,
+						Misbehaviour::Consensus,
+						&format!(
+							"Provided header {} that contradicts checkpoint at height {}",
+							header.hash.to_reversed_str(),
+							header_height,
+						),
+					);
+					return None;
+				}
+			}
+
 			// check that we do not know all blocks in range [first_unknown_index..]
 			// if we know some block => there has been verification error => all headers should be ignored
 			// see when_previous_block_verification_failed_fork_is_not_requested for details
@@ -323,6 +358,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 				BlockState::DeadEnd if self.config.close_connection_on_bad_block => {
 					self.peers.misbehaving(
 						peer_index,
+						Misbehaviour::Consensus,
 						&format!(
 							"Provided dead-end block {:?}",
 							header.hash.to_reversed_str(),
@@ -372,6 +408,12 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 		// prepare list of blocks to verify + make all required changes to the chain
 		let mut result = None;
 		let block_state = self.chain.block_state(&block.header.hash);
+		if block_state == BlockState::Requested {
+			// remember that peer has delivered requested block
+			if let Some(connection) = self.peers.connection(peer_index) {
+				connection.note_block_delivered();
+			}
+		}
 		match block_state {
 			BlockState::VerifyingHeader | BlockState::Verifying | BlockState::Stored => {
 				// remember peer as useful
@@ -381,7 +423,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 			BlockState::Unknown | BlockState::Scheduled | BlockState::Requested | BlockState::DeadEnd => {
 				if block_state == BlockState::DeadEnd {
 					if self.config.close_connection_on_bad_block {
-						self.peers.misbehaving(peer_index, &format!("Provided dead-end block {}", block.header.hash.to_reversed_str()));
+						self.peers.misbehaving(peer_index, Misbehaviour::Consensus, &format!("Provided dead-end block {}", block.header.hash.to_reversed_str()));
 						return None;
 					}
 					warn!(target: "sync", "Peer#{} has provided dead-end block {}", peer_index, block.header.hash.to_reversed_str());
@@ -393,7 +435,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 					BlockState::Unknown | BlockState::DeadEnd => {
 						if parent_block_state == BlockState::DeadEnd {
 							if self.config.close_connection_on_bad_block {
-								self.peers.misbehaving(peer_index, &format!("Provided dead-end block {}", block.header.hash.to_reversed_str()));
+								self.peers.misbehaving(peer_index, Misbehaviour::Consensus, &format!("Provided dead-end block {}", block.header.hash.to_reversed_str()));
 								return None;
 							}
 							warn!(target: "sync", "Peer#{} has provided dead-end block {}", peer_index, block.header.hash.to_reversed_str());
@@ -443,11 +485,16 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 						blocks_to_verify_hashes.extend(orphaned_blocks.iter().map(IndexedBlock::hash).cloned());
 						self.chain.forget_blocks_leave_header(&blocks_to_verify_hashes);
 						// remember that we are verifying these blocks
+						for block in ::std::iter::once(&block).chain(orphaned_blocks.iter()) {
+							self.on_block_verification_scheduled(block);
+						}
 						let blocks_to_verify = ::std::iter::once(block).chain(orphaned_blocks)
-							.map(|block| if self.chain.verify_block(block.header.clone()) {
-								PartiallyVerifiedBlock::HeaderPreVerified(block)
-							} else {
+							.map(|block| if !self.chain.verify_block(block.header.clone()) {
 								PartiallyVerifiedBlock::NotVerified(block)
+							} else if self.is_verification_edge_ancestor(&block.header.hash) {
+								PartiallyVerifiedBlock::EdgeAncestor(block)
+							} else {
+								PartiallyVerifiedBlock::HeaderPreVerified(block)
 							})
 							.collect::<VecDeque<_>>();
 						// remember that we are verifying block from this peer
@@ -532,7 +579,7 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 			let removed_tasks = self.peers_tasks.reset_blocks_tasks(peer_index);
 			self.peers_tasks.unuseful_peer(peer_index);
 			if self.state.is_synchronizing() {
-				self.peers.misbehaving(peer_index, &format!("Responded with NotFound(unrequested_block)"));
+				self.peers.misbehaving(peer_index, Misbehaviour::Protocol, &format!("Responded with NotFound(unrequested_block)"));
 			}
 
 			// if peer has had some blocks tasks, rerequest these blocks
@@ -558,11 +605,11 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 		}
 	}
 
-	fn accept_transaction(&mut self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<VecDeque<IndexedTransaction>, String> {
+	fn accept_transaction(&mut self, transaction: IndexedTransaction, sink: Box<TransactionVerificationSink>) -> Result<VecDeque<IndexedTransaction>, TransactionRejection> {
 		let hash = transaction.hash;
 		match self.try_append_transaction(transaction, true) {
-			Err(AppendTransactionError::Orphan(_)) => Err("Cannot append transaction as its inputs are unknown".to_owned()),
-			Err(AppendTransactionError::Synchronizing) => Err("Cannot append transaction as node is not yet fully synchronized".to_owned()),
+			Err(AppendTransactionError::Orphan(_)) => Err(TransactionRejection::MissingInputs),
+			Err(AppendTransactionError::Synchronizing) => Err(TransactionRejection::Synchronizing),
 			Ok(transactions) => {
 				self.verifying_transactions_sinks.insert(hash, sink);
 				Ok(transactions)
@@ -570,6 +617,35 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 		}
 	}
 
+	/// Accept header, which has passed context-free verification, as if it has been received
+	/// from peer. Block of the header is requested from peers later.
+	fn accept_header(&mut self, header: IndexedBlockHeader) -> Result<(), HeaderRejection> {
+		match self.chain.block_state(&header.raw.previous_header_hash) {
+			BlockState::Unknown => return Err(HeaderRejection::UnknownParent),
+			BlockState::DeadEnd => return Err(HeaderRejection::InvalidParent),
+			_ => (),
+		}
+
+		match self.chain.block_state(&header.hash) {
+			BlockState::Unknown => (),
+			BlockState::DeadEnd => return Err(HeaderRejection::DuplicateInvalid),
+			_ => return Err(HeaderRejection::Duplicate),
+		}
+
+		// height is unknown if parent is a side chain block => checkpoints are checked during verification
+		if let Some(height) = self.chain.block_number(&header.raw.previous_header_hash).map(|height| height + 1) {
+			if self.contradicts_checkpoint(height, &header.hash) {
+				return Err(HeaderRejection::CheckpointMismatch(height));
+			}
+		}
+
+		trace!(target: "sync", "Accepting submitted header {}", header.hash.to_reversed_str());
+		self.new_headers_receival_timestamp = precise_time_s();
+		self.chain.verify_headers(&[header.clone()]);
+		self.on_headers_verification_success(vec![header]);
+		Ok(())
+	}
+
 	fn install_sync_listener(&mut self, listener: SyncListenerRef) {
 		// currently single, single-setup listener is supported
 		assert!(self.listener.is_none());
@@ -716,7 +792,8 @@ impl<T> ClientCore for SynchronizationClientCore<T> where T: TaskExecutor {
 				{
 					// TODO: only request minimal number of blocks, if other urgent blocks are requested
 					let scheduled_hashes_len = self.chain.length_of_blocks_state(BlockState::Scheduled);
-					if requested_hashes_len + verifying_hashes_len < MAX_REQUESTED_BLOCKS + MAX_VERIFYING_BLOCKS && scheduled_hashes_len != 0 {
+					if requested_hashes_len + verifying_hashes_len < MAX_REQUESTED_BLOCKS + MAX_VERIFYING_BLOCKS && scheduled_hashes_len != 0
+						&& !self.is_blocks_memory_budget_exhausted(requested_hashes_len, verifying_hashes_len) {
 						let chunk_size = min(limits.max_blocks_in_request, max(scheduled_hashes_len / blocks_idle_peers_len, limits.min_blocks_in_request));
 						let hashes_to_request_len = chunk_size * blocks_idle_peers_len;
 						let hashes_to_request = self.chain.request_blocks_hashes(hashes_to_request_len);
@@ -796,7 +873,7 @@ impl<T> TransactionVerificationSink for CoreVerificationSink<T> where T: TaskExe
 	}
 
 	/// Process failed transaction verification
-	fn on_transaction_verification_error(&self, err: &str, hash: &H256) {
+	fn on_transaction_verification_error(&self, err: &TransactionRejection, hash: &H256) {
 		self.core.lock().on_transaction_verification_error(err, hash)
 	}
 }
@@ -816,6 +893,8 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 				orphaned_blocks_pool: OrphanBlocksPool::new(),
 				orphaned_transactions_pool: OrphanTransactionsPool::new(),
 				verifying_blocks_by_peer: HashMap::new(),
+				verifying_blocks_sizes: HashMap::new(),
+				verifying_blocks_memory: 0,
 				verifying_blocks_futures: HashMap::new(),
 				verifying_transactions_sinks: HashMap::new(),
 				do_not_relay: HashSet::new(),
@@ -890,12 +969,13 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 			if timestamp_diff >= 60.0 || blocks_diff >= 1000 {
 				self.state = State::Synchronizing(precise_time_s(), new_num_of_blocks);
 				let blocks_speed = blocks_diff as f64 / timestamp_diff;
-				info!(target: "sync", "Processed {} blocks in {:.2} seconds ({:.2} blk/s).\tPeers: {:?}.\tChain: {:?}"
+				info!(target: "sync", "Processed {} blocks in {:.2} seconds ({:.2} blk/s).\tPeers: {:?}.\tChain: {:?}.\tQueued blocks: {:.2} MB"
 					, blocks_diff
 					, timestamp_diff
 					, blocks_speed
 					, self.peers_tasks.information()
-					, self.chain.information());
+					, self.chain.information()
+					, self.blocks_memory_usage() as f64 / (1024 * 1024) as f64);
 			}
 		}
 	}
@@ -1060,6 +1140,19 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		}
 	}
 
+	/// Returns true if block is an ancestor of the verification edge in the best headers chain.
+	fn is_verification_edge_ancestor(&self, hash: &H256) -> bool {
+		match (self.chain.block_number(hash), self.chain.block_number(&self.config.verification_edge)) {
+			(Some(number), Some(edge_number)) => number < edge_number,
+			_ => false,
+		}
+	}
+
+	fn contradicts_checkpoint(&self, height: BlockHeight, hash: &H256) -> bool {
+		self.config.checkpoints.iter()
+			.any(|&(checkpoint_height, ref checkpoint_hash)| checkpoint_height == height && checkpoint_hash != hash)
+	}
+
 	fn find_unknown_headers(&self, mut headers: Vec<IndexedBlockHeader>) -> Vec<IndexedBlockHeader> {
 		// find first unknown header position
 		// optimization: normally, the first header will be unknown
@@ -1113,8 +1206,9 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		if self.config.close_connection_on_bad_block {
 			self.peers.misbehaving(
 				peer,
+				Misbehaviour::Consensus,
 				&format!(
-					"Error verifying header {} from `headers`: {:?}",
+					"Error verifying header {} from `headers`: {}",
 					hash.to_reversed_str(),
 					error,
 				),
@@ -1136,6 +1230,9 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		// update block processing speed
 		self.block_speed_meter.checkpoint();
 
+		// block is no longer occupying verification queue
+		self.on_block_verification_completed(block.hash());
+
 		// remove flags
 		let needs_relay = !self.do_not_relay.remove(block.hash());
 
@@ -1198,7 +1295,10 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 	}
 
 	fn on_block_verification_error(&mut self, err: &str, hash: &H256) {
-		warn!(target: "sync", "Block {:?} verification failed with error {:?}", hash.to_reversed_str(), err);
+		warn!(target: "sync", "Block {} verification failed: {}", hash.to_reversed_str(), err);
+
+		// block is no longer occupying verification queue
+		self.on_block_verification_completed(hash);
 
 		// remove flags
 		self.do_not_relay.remove(hash);
@@ -1206,7 +1306,7 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		// close connection with this peer
 		if let Some(peer_index) = self.verifying_blocks_by_peer.get(hash) {
 			if self.config.close_connection_on_bad_block {
-				self.peers.dos(*peer_index, &format!("Provided wrong block {}", hash.to_reversed_str()))
+				self.peers.misbehaving(*peer_index, Misbehaviour::Consensus, &format!("Provided wrong block {}: {}", hash.to_reversed_str(), err))
 			} else {
 				warn!(target: "sync", "Peer#{} has provided wrong block {:?}", peer_index, hash.to_reversed_str());
 			}
@@ -1254,7 +1354,7 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		}
 	}
 
-	fn on_transaction_verification_error(&mut self, err: &str, hash: &H256) {
+	fn on_transaction_verification_error(&mut self, err: &TransactionRejection, hash: &H256) {
 		warn!(target: "sync", "Transaction {} verification failed with error {:?}", hash.to_reversed_str(), err);
 
 		// remove flags
@@ -1269,6 +1369,48 @@ impl<T> SynchronizationClientCore<T> where T: TaskExecutor {
 		}
 	}
 
+	/// Total size of blocks, which have been received, but not yet stored
+	pub fn blocks_memory_usage(&self) -> usize {
+		self.verifying_blocks_memory + self.orphaned_blocks_pool.memory_usage()
+	}
+
+	/// Remember size of block, which is sent to verification
+	fn on_block_verification_scheduled(&mut self, block: &IndexedBlock) {
+		let size = block.size();
+		if let Some(previous_size) = self.verifying_blocks_sizes.insert(block.header.hash.clone(), size) {
+			self.verifying_blocks_memory -= previous_size;
+		}
+		self.verifying_blocks_memory += size;
+	}
+
+	/// Forget size of block, which has left verification queue
+	fn on_block_verification_completed(&mut self, hash: &H256) {
+		if let Some(size) = self.verifying_blocks_sizes.remove(hash) {
+			self.verifying_blocks_memory -= size;
+		}
+	}
+
+	/// Check if blocks that are already in memory, plus blocks that are currently requested, exceed memory budget.
+	/// Size of requested blocks is estimated using average size of blocks in memory.
+	/// Budget is never considered exhausted when verification queue is empty, because nothing would free memory then.
+	fn is_blocks_memory_budget_exhausted(&self, requested_hashes_len: BlockHeight, verifying_hashes_len: BlockHeight) -> bool {
+		if verifying_hashes_len == 0 {
+			return false;
+		}
+
+		let memory_usage = self.blocks_memory_usage();
+		let blocks_in_memory = self.verifying_blocks_sizes.len() + self.orphaned_blocks_pool.blocks_count();
+		let average_block_size = if blocks_in_memory == 0 { 0 } else { memory_usage / blocks_in_memory };
+		let expected_memory_usage = memory_usage + average_block_size * requested_hashes_len as usize;
+		if expected_memory_usage < self.config.blocks_memory_budget {
+			return false;
+		}
+
+		trace!(target: "sync", "Not requesting new blocks: {} bytes of blocks are queued, {} blocks are requested, budget is {} bytes",
+			memory_usage, requested_hashes_len, self.config.blocks_memory_budget);
+		true
+	}
+
 	/// Execute futures, which were waiting for this block verification
 	fn awake_waiting_threads(&mut self, hash: &H256) {
 		// find a peer, which has supplied us with this block
@@ -1337,7 +1479,7 @@ pub mod tests {
 
 	use std::sync::Arc;
 	use parking_lot::{Mutex, RwLock};
-	use chain::{Block, Transaction, IndexedBlock};
+	use chain::{Block, Transaction, IndexedBlock, IndexedBlockHeader};
 	use db::BlockChainDatabase;
 	use message::common::InventoryVector;
 	use message::{Services, types};
@@ -1354,8 +1496,8 @@ pub mod tests {
 	use synchronization_verifier::tests::DummyVerifier;
 	use utils::SynchronizationState;
 	use types::{PeerIndex, StorageRef, SynchronizationStateRef, ClientCoreRef};
-	use super::{Config, SynchronizationClientCore, ClientCore, CoreVerificationSink};
-	use super::super::SyncListener;
+	use super::{Config, SynchronizationClientCore, ClientCore, CoreVerificationSink, DEFAULT_BLOCKS_MEMORY_BUDGET};
+	use super::super::{SyncListener, HeaderRejection};
 
 	#[derive(Default)]
 	struct DummySyncListenerData {
@@ -1399,7 +1541,7 @@ pub mod tests {
 		let memory_pool = Arc::new(RwLock::new(MemoryPool::new()));
 		let chain = Chain::new(storage.clone(), memory_pool.clone());
 		let executor = DummyTaskExecutor::new();
-		let config = Config { close_connection_on_bad_block: true };
+		let config = Config { close_connection_on_bad_block: true, checkpoints: Vec::new(), verification_edge: 0u8.into(), blocks_memory_budget: DEFAULT_BLOCKS_MEMORY_BUDGET };
 
 		let chain_verifier = Arc::new(ChainVerifier::new(storage.clone(), ConsensusParams::new(Network::Unitest)));
 		let client_core = SynchronizationClientCore::new(config, sync_state.clone(), sync_peers.clone(), executor.clone(), chain);
@@ -2237,6 +2379,48 @@ pub mod tests {
 		assert!(!core.lock().peers.enumerate().contains(&0));
 	}
 
+	#[test]
+	fn collection_closed_on_header_contradicting_checkpoint() {
+		let genesis = test_data::genesis();
+		let b0 = test_data::block_builder().header().parent(genesis.hash()).build().build();
+		let b1 = test_data::block_builder().header().parent(b0.hash()).build().build();
+		let b2 = test_data::block_builder().header().parent(b1.hash()).build().build();
+
+		let (_, core, sync) = create_sync(None, None);
+		core.lock().config.checkpoints = vec![(2, H256::from(2))];
+
+		core.lock().peers.insert(0, Services::default(), DummyOutboundSyncConnection::new());
+		assert!(core.lock().peers.enumerate().contains(&0));
+
+		sync.on_headers(0, vec![b0.block_header.into(), b1.block_header.into(), b2.block_header.into()]);
+
+		assert!(!core.lock().peers.enumerate().contains(&0));
+	}
+
+	#[test]
+	fn only_verification_edge_ancestors_are_marked() {
+		let genesis = test_data::genesis();
+		let b1 = test_data::block_builder().header().parent(genesis.hash()).build().build();
+		let b2 = test_data::block_builder().header().parent(b1.hash()).build().build();
+		let b3 = test_data::block_builder().header().parent(b2.hash()).build().build();
+		let fork = test_data::block_builder().header().nonce(1.into()).parent(genesis.hash()).build().build();
+
+		let (_, core, sync) = create_sync(None, None);
+		core.lock().config.verification_edge = b3.hash();
+
+		// edge isn't in the headers chain yet
+		sync.on_headers(0, vec![b1.block_header.clone().into(), b2.block_header.clone().into()]);
+		assert!(!core.lock().is_verification_edge_ancestor(&b1.hash()));
+
+		sync.on_headers(0, vec![b3.block_header.clone().into()]);
+		let core = core.lock();
+		assert!(core.is_verification_edge_ancestor(&b1.hash()));
+		assert!(core.is_verification_edge_ancestor(&b2.hash()));
+		assert!(!core.is_verification_edge_ancestor(&b3.hash()));
+		// fork block isn't an ancestor of the edge
+		assert!(!core.is_verification_edge_ancestor(&fork.hash()));
+	}
+
 	#[test]
 	fn collection_closed_on_in_middle_dead_end_block_header() {
 		let genesis = test_data::genesis();
@@ -2546,4 +2730,59 @@ pub mod tests {
 		assert_eq!(sync.chain().block_state(&hash1), BlockState::Stored);
 		assert_eq!(sync.chain().block_state(&hash2), BlockState::Stored); // pre-fix: Verifying
 	}
+
+	#[test]
+	fn blocks_memory_usage_is_tracked_until_verification_ends() {
+		let (_, sync, _) = create_sync(None, None);
+		let mut sync = sync.lock();
+
+		let block1: IndexedBlock = test_data::block_h1().into();
+		let block2: IndexedBlock = test_data::block_h2().into();
+		let (size1, size2) = (block1.size(), block2.size());
+		sync.config.blocks_memory_budget = size1 + size2 + 1;
+
+		// verification queue is empty => budget is never exhausted
+		assert!(!sync.is_blocks_memory_budget_exhausted(100, 0));
+
+		// [block2] is orphaned
+		sync.on_block(0, block2.clone());
+		assert_eq!(sync.blocks_memory_usage(), size2);
+
+		// [block1] received => [block1, block2] verification starts
+		sync.on_block(0, block1.clone());
+		assert_eq!(sync.chain().block_state(block1.hash()), BlockState::Verifying);
+		assert_eq!(sync.blocks_memory_usage(), size1 + size2);
+		assert!(!sync.is_blocks_memory_budget_exhausted(0, 2));
+		// requested blocks are expected to have an average size
+		assert!(sync.is_blocks_memory_budget_exhausted(1, 2));
+
+		// [block1] is stored => its memory is freed
+		sync.on_block_verification_success(block1);
+		assert_eq!(sync.blocks_memory_usage(), size2);
+
+		// [block2] verification fails => its memory is freed
+		sync.on_block_verification_error("error", block2.hash());
+		assert_eq!(sync.blocks_memory_usage(), 0);
+	}
+
+	#[test]
+	fn submitted_header_is_accepted_into_headers_chain() {
+		let (_, sync, _) = create_sync(None, None);
+		let mut sync = sync.lock();
+
+		let header1 = test_data::block_h1().block_header.into();
+		let header2: IndexedBlockHeader = test_data::block_h2().block_header.into();
+
+		// parent of [header2] is unknown
+		assert_eq!(sync.accept_header(header2.clone()), Err(HeaderRejection::UnknownParent));
+
+		// [header1] is accepted && its block is scheduled
+		assert_eq!(sync.accept_header(header1), Ok(()));
+		assert_eq!(sync.chain().block_state(&test_data::block_h1().hash()), BlockState::Scheduled);
+		assert_eq!(sync.accept_header(test_data::block_h1().block_header.into()), Err(HeaderRejection::Duplicate));
+
+		// [header2] now follows known header
+		assert_eq!(sync.accept_header(header2), Ok(()));
+		assert_eq!(sync.chain().block_state(&test_data::block_h2().hash()), BlockState::Scheduled);
+	}
 }
@@ -89,9 +89,7 @@ impl LocalSynchronizationTaskExecutor {
 		if let Some(connection) = self.peers.connection(peer_index) {
 			trace!(target: "sync", "Sending block {} to peer#{}", block.hash().to_reversed_str(), peer_index);
 			self.peers.hash_known_as(peer_index, block.hash().clone(), KnownHashType::Block);
-			let block = types::Block {
-				block: block.to_raw_block(),
-			};
+			let block = types::Block::with_block(block);
 			connection.send_block(&block);
 		}
 	}
@@ -143,11 +141,13 @@ impl LocalSynchronizationTaskExecutor {
 		for peer_index in self.peers.enumerate() {
 			match self.peers.filter_block(peer_index, &block) {
 				BlockAnnouncementType::SendInventory => {
+					self.peers.hash_known_as(peer_index, block.hash().clone(), KnownHashType::Block);
 					self.execute_inventory(peer_index, types::Inv::with_inventory(vec![
 						InventoryVector::block(block.hash().clone()),
 					]));
 				},
 				BlockAnnouncementType::SendHeaders => {
+					self.peers.hash_known_as(peer_index, block.hash().clone(), KnownHashType::Block);
 					self.execute_headers(peer_index, types::Headers::with_headers(vec![
 						block.header.raw.clone(),
 					]), None);
@@ -160,9 +160,12 @@ impl LocalSynchronizationTaskExecutor {
 	fn execute_relay_transaction(&self, transaction: IndexedTransaction, fee_rate: u64) {
 		for peer_index in self.peers.enumerate() {
 			match self.peers.filter_transaction(peer_index, &transaction, Some(fee_rate)) {
-				TransactionAnnouncementType::SendInventory => self.execute_inventory(peer_index, types::Inv::with_inventory(vec![
-					InventoryVector::tx(transaction.hash.clone()),
-				])),
+				TransactionAnnouncementType::SendInventory => {
+					self.peers.hash_known_as(peer_index, transaction.hash.clone(), KnownHashType::Transaction);
+					self.execute_inventory(peer_index, types::Inv::with_inventory(vec![
+						InventoryVector::tx(transaction.hash.clone()),
+					]));
+				},
 				TransactionAnnouncementType::DoNotAnnounce => (),
 			}
 		}
@@ -260,6 +263,28 @@ pub mod tests {
 		assert_eq!(*c2.messages.lock().entry("headers".to_owned()).or_insert(0), 1);
 	}
 
+	#[test]
+	fn relay_known_items_only_once() {
+		let peers = Arc::new(PeersImpl::default());
+		let executor = LocalSynchronizationTaskExecutor::new(peers.clone());
+
+		let c1 = DummyOutboundSyncConnection::new();
+		peers.insert(1, Services::default(), c1.clone());
+		let c2 = DummyOutboundSyncConnection::new();
+		peers.insert(2, Services::default(), c2.clone());
+		// peer#2 has announced transaction to us
+		let tx: IndexedTransaction = test_data::genesis().transactions[0].clone().into();
+		peers.hash_known_as(2, tx.hash.clone(), KnownHashType::Transaction);
+
+		executor.execute(Task::RelayNewTransaction(tx.clone(), 0));
+		executor.execute(Task::RelayNewTransaction(tx, 0));
+		executor.execute(Task::RelayNewBlock(test_data::genesis().into()));
+		executor.execute(Task::RelayNewBlock(test_data::genesis().into()));
+
+		assert_eq!(*c1.messages.lock().entry("inventory".to_owned()).or_insert(0), 2);
+		assert_eq!(*c2.messages.lock().entry("inventory".to_owned()).or_insert(0), 1);
+	}
+
 	#[test]
 	fn relay_new_transaction_with_bloom_filter() {
 		let peers = Arc::new(PeersImpl::default());
@@ -4,6 +4,7 @@ use std::thread;
 use std::time::Duration;
 use parking_lot::{Mutex, Condvar};
 use time::precise_time_s;
+use p2p::Misbehaviour;
 use primitives::hash::H256;
 use synchronization_client_core::{ClientCore, SynchronizationClientCore};
 use synchronization_executor::TaskExecutor;
@@ -206,7 +207,7 @@ pub fn manage_synchronization_peers_blocks(config: &ManagePeersConfig, peers: Pe
 		if peers_tasks.on_peer_block_failure(worst_peer_index) {
 			warn!(target: "sync", "Too many failures for peer#{}. Excluding from synchronization.", worst_peer_index);
 			peers_tasks.unuseful_peer(worst_peer_index);
-			peers.misbehaving(worst_peer_index, &format!("Too many failures."));
+			peers.misbehaving(worst_peer_index, Misbehaviour::Resource, &format!("Too many failures."));
 		}
 	}
 
@@ -235,7 +236,7 @@ pub fn manage_synchronization_peers_headers(config: &ManagePeersConfig, peers: P
 		// if peer failed many times => forget it
 		if peers_tasks.on_peer_headers_failure(worst_peer_index) {
 			warn!(target: "sync", "Too many header failures for peer#{}. Excluding from synchronization.", worst_peer_index);
-			peers.misbehaving(worst_peer_index, &format!("Too many header failures."));
+			peers.misbehaving(worst_peer_index, Misbehaviour::Resource, &format!("Too many header failures."));
 		}
 	}
 }
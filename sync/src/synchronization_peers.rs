@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use parking_lot::RwLock;
 use chain::{IndexedBlock, IndexedTransaction};
 use message::{types, Services};
-use p2p::OutboundSyncConnectionRef;
+use p2p::{OutboundSyncConnectionRef, Misbehaviour};
 use primitives::hash::H256;
 use types::PeerIndex;
 use utils::{KnownHashType, ConnectionFilter};
@@ -51,7 +51,7 @@ pub trait PeersContainer {
 	/// Remove peer connection
 	fn remove(&self, peer_index: PeerIndex);
 	/// Close and remove peer connection due to misbehaving
-	fn misbehaving(&self, peer_index: PeerIndex, reason: &str);
+	fn misbehaving(&self, peer_index: PeerIndex, misbehaviour: Misbehaviour, reason: &str);
 	/// Close and remove peer connection due to detected DOS attempt
 	fn dos(&self, peer_index: PeerIndex, reason: &str);
 }
@@ -142,17 +142,17 @@ impl PeersContainer for PeersImpl {
 		}
 	}
 
-	fn misbehaving(&self, peer_index: PeerIndex, reason: &str) {
+	fn misbehaving(&self, peer_index: PeerIndex, misbehaviour: Misbehaviour, reason: &str) {
 		if let Some(peer) = self.peers.write().remove(&peer_index) {
-			warn!(target: "sync", "Disconnecting from peer#{} due to misbehavior: {}", peer_index, reason);
-			peer.connection.close();
+			warn!(target: "sync", "Disconnecting from peer#{} due to {:?} misbehavior: {}", peer_index, misbehaviour, reason);
+			peer.connection.close(misbehaviour);
 		}
 	}
 
 	fn dos(&self, peer_index: PeerIndex, reason: &str) {
 		if let Some(peer) = self.peers.write().remove(&peer_index) {
 			warn!(target: "sync", "Disconnecting from peer#{} due to DoS: {}", peer_index, reason);
-			peer.connection.close();
+			peer.connection.close(Misbehaviour::Resource);
 		}
 	}
 }
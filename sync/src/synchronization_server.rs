@@ -6,6 +6,7 @@ use std::thread;
 use parking_lot::{Mutex, Condvar};
 use chain::IndexedTransaction;
 use message::{types, common};
+use p2p::Misbehaviour;
 use primitives::hash::H256;
 use synchronization_executor::{Task, TaskExecutor};
 use types::{PeerIndex, RequestId, BlockHeight, StorageRef, ExecutorRef, MemoryPoolRef, PeersRef};
@@ -319,7 +320,7 @@ impl<TExecutor> ServerTaskExecutor<TExecutor> where TExecutor: TaskExecutor {
 				trace!(target: "sync", "'getblocks' request from peer#{} is ignored as there are no new blocks for peer", peer_index);
 			}
 		} else {
-			self.peers.misbehaving(peer_index, "Got 'getblocks' message without known blocks");
+			self.peers.misbehaving(peer_index, Misbehaviour::Protocol, "Got 'getblocks' message without known blocks");
 			return;
 		}
 	}
@@ -340,7 +341,7 @@ impl<TExecutor> ServerTaskExecutor<TExecutor> where TExecutor: TaskExecutor {
 			trace!(target: "sync", "'getheaders' response to peer#{} is ready with {} headers", peer_index, headers.len());
 			self.executor.execute(Task::Headers(peer_index, types::Headers::with_headers(headers), Some(request_id)));
 		} else {
-			self.peers.misbehaving(peer_index, "Got 'headers' message without known blocks");
+			self.peers.misbehaving(peer_index, Misbehaviour::Protocol, "Got 'headers' message without known blocks");
 			return;
 		}
 	}
@@ -742,7 +743,9 @@ pub mod tests {
 			let mut index = 0;
 			let tasks = sync_executor.take_tasks();
 			match tasks[index] {
-				Task::MerkleBlock(_, _, _) => {
+				Task::MerkleBlock(_, _, ref merkleblock) => {
+					let expected_hashes = if get_tx1 { vec![tx1_hash.clone()] } else { vec![] };
+					assert_eq!(merkleblock.matched_hashes(), Ok(expected_hashes));
 					if get_tx1 {
 						index += 1;
 						match tasks[index] {
@@ -756,7 +759,9 @@ pub mod tests {
 			index += 1;
 
 			match tasks[index] {
-				Task::MerkleBlock(_, _, _) => {
+				Task::MerkleBlock(_, _, ref merkleblock) => {
+					let expected_hashes = if get_tx2 { vec![tx2_hash.clone()] } else { vec![] };
+					assert_eq!(merkleblock.matched_hashes(), Ok(expected_hashes));
 					if get_tx2 {
 						index += 1;
 						match tasks[index] {
@@ -5,14 +5,15 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use parking_lot::Mutex;
 use time::get_time;
+use miner::transaction_fee;
 use chain::{IndexedBlockHeader, IndexedBlock, IndexedTransaction};
 use network::ConsensusParams;
 use primitives::hash::H256;
 use verification::{BackwardsCompatibleChainVerifier as ChainVerifier, Verify as VerificationVerify,
-	Error as VerificationError, VerificationLevel};
+	Error as VerificationError, VerificationLevel, SignatureCache, ScriptCache};
 use types::{PeerIndex, BlockHeight, StorageRef, MemoryPoolRef};
 use utils::MemoryPoolTransactionOutputProvider;
-use VerificationParameters;
+use {VerificationParameters, TransactionRejection};
 
 //// Block that is (possibly) partially verified.
 #[derive(Debug)]
@@ -21,6 +22,8 @@ pub enum PartiallyVerifiedBlock {
 	NotVerified(IndexedBlock),
 	/// Block that has its header pre-verified (mind that AcceptHeader isn't called).
 	HeaderPreVerified(IndexedBlock),
+	/// Block that has its header pre-verified && is an ancestor of the verification edge in the headers chain.
+	EdgeAncestor(IndexedBlock),
 }
 
 /// Headers verification events sink
@@ -44,7 +47,7 @@ pub trait TransactionVerificationSink : Send + Sync + 'static {
 	/// When transaction verification has completed successfully.
 	fn on_transaction_verification_success(&self, transaction: IndexedTransaction);
 	/// When transaction verification has failed.
-	fn on_transaction_verification_error(&self, err: &str, hash: &H256);
+	fn on_transaction_verification_error(&self, err: &TransactionRejection, hash: &H256);
 }
 
 /// Verification events sink
@@ -97,7 +100,8 @@ impl PartiallyVerifiedBlock {
 	pub fn hash(&self) -> &H256 {
 		match *self {
 			PartiallyVerifiedBlock::NotVerified(ref block)
-				| PartiallyVerifiedBlock::HeaderPreVerified(ref block) => block.hash(),
+				| PartiallyVerifiedBlock::HeaderPreVerified(ref block)
+				| PartiallyVerifiedBlock::EdgeAncestor(ref block) => block.hash(),
 		}
 	}
 }
@@ -107,6 +111,7 @@ impl From<PartiallyVerifiedBlock> for IndexedBlock {
 		match block {
 			PartiallyVerifiedBlock::NotVerified(block) => block,
 			PartiallyVerifiedBlock::HeaderPreVerified(block) => block,
+			PartiallyVerifiedBlock::EdgeAncestor(block) => block,
 		}
 	}
 }
@@ -144,16 +149,19 @@ impl ChainVerifierWrapper {
 		};
 
 		// select base verification level
-		let mut verification_level = if enforce_full_verification {
-			VerificationLevel::FULL
-		} else {
-			self.verification_params.verification_level
+		let mut verification_level = match *block {
+			_ if enforce_full_verification => VerificationLevel::FULL,
+			PartiallyVerifiedBlock::EdgeAncestor(_) => self.verification_params.verification_level,
+			// blocks that aren't known to be ancestors of the edge could be side chain blocks
+			_ if self.verification_params.edge_ancestors_only => VerificationLevel::FULL,
+			_ => self.verification_params.verification_level,
 		};
 
 		// update verification level with hints, if necessary
 		let block = match *block {
 			PartiallyVerifiedBlock::NotVerified(ref block) => block,
-			PartiallyVerifiedBlock::HeaderPreVerified(ref block) => {
+			PartiallyVerifiedBlock::HeaderPreVerified(ref block)
+				| PartiallyVerifiedBlock::EdgeAncestor(ref block) => {
 				verification_level.insert(VerificationLevel::HINT_HEADER_PRE_VERIFIED);
 				block
 			},
@@ -236,7 +244,7 @@ impl AsyncVerifier {
 							.map_err(|error| (error, header.hash)));
 					match result {
 						Ok(_) => sink.on_headers_verification_success(headers),
-						Err((error, hash)) => sink.on_headers_verification_error(peer, format!("{:?}", error), hash, headers),
+						Err((error, hash)) => sink.on_headers_verification_error(peer, error.to_string(), hash, headers),
 					}
 				},
 				VerificationTask::VerifyBlock(block) => {
@@ -248,7 +256,7 @@ impl AsyncVerifier {
 							}
 						},
 						Err(e) => {
-							sink.on_block_verification_error(&format!("{:?}", e), block.hash())
+							sink.on_block_verification_error(&e.to_string(), block.hash())
 						}
 					}
 				},
@@ -256,14 +264,22 @@ impl AsyncVerifier {
 					// output provider must check previous outputs in both storage && memory pool
 					match MemoryPoolTransactionOutputProvider::for_transaction(storage.clone(), memory_pool, &transaction.raw) {
 						Err(e) => {
-							sink.on_transaction_verification_error(&format!("{:?}", e), &transaction.hash);
+							sink.on_transaction_verification_error(&e.into(), &transaction.hash);
 							continue; // with new verification sub-task
 						},
 						Ok(tx_output_provider) => {
 							let time: u32 = get_time().sec as u32;
 							match verifier.verifier.verify_mempool_transaction(storage.as_block_header_provider(), &tx_output_provider, height, time, &transaction) {
-								Ok(_) => sink.on_transaction_verification_success(transaction.into()),
-								Err(e) => sink.on_transaction_verification_error(&format!("{:?}", e), &transaction.hash),
+								Err(e) => sink.on_transaction_verification_error(&e.into(), &transaction.hash),
+								// valid transaction must also satisfy memory pool policies
+								Ok(_) => {
+									let fee = transaction_fee(&tx_output_provider, &transaction.raw);
+									let policy_result = memory_pool.read().check_policies(&transaction.raw, fee);
+									match policy_result {
+										Ok(_) => sink.on_transaction_verification_success(transaction.into()),
+										Err(e) => sink.on_transaction_verification_error(&e.into(), &transaction.hash),
+									}
+								},
 							}
 						},
 					};
@@ -320,7 +336,9 @@ pub struct SyncVerifier<T: VerificationSink> {
 impl<T> SyncVerifier<T> where T: VerificationSink {
 	/// Create new sync verifier
 	pub fn new(consensus: ConsensusParams, storage: StorageRef, sink: Arc<T>, verification_params: VerificationParameters) -> Self {
-		let verifier = ChainVerifier::new(storage.clone(), consensus);
+		let signature_cache = Arc::new(SignatureCache::new(verification_params.signature_cache_size));
+		let script_cache = Arc::new(ScriptCache::new(verification_params.script_cache_size));
+		let verifier = ChainVerifier::with_caches(storage.clone(), consensus, signature_cache, script_cache);
 		let verifier = ChainVerifierWrapper::new(Arc::new(verifier), &storage, verification_params);
 		SyncVerifier {
 			verifier: verifier,
@@ -344,7 +362,7 @@ impl<T> Verifier for SyncVerifier<T> where T: VerificationSink {
 				// => we could ignore decanonized transactions
 				self.sink.on_block_verification_success(block.into());
 			},
-			Err(e) => self.sink.on_block_verification_error(&format!("{:?}", e), block.hash()),
+			Err(e) => self.sink.on_block_verification_error(&e.to_string(), block.hash()),
 		}
 	}
 
@@ -363,7 +381,8 @@ pub mod tests {
 	use std::collections::{HashSet, HashMap};
 	use db::BlockChainDatabase;
 	use network::{Network, ConsensusParams};
-	use verification::{VerificationLevel, BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError, TransactionError};
+	use verification::{VerificationLevel, BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError, TransactionError,
+		DEFAULT_SIGNATURE_CACHE_SIZE, DEFAULT_SCRIPT_CACHE_SIZE};
 	use script::Error as ScriptError;
 	use synchronization_client_core::CoreVerificationSink;
 	use synchronization_executor::tests::DummyTaskExecutor;
@@ -372,7 +391,7 @@ pub mod tests {
 	use super::{Verifier, HeadersVerificationSink, BlockVerificationSink, TransactionVerificationSink,
 		AsyncVerifier, VerificationTask, ChainVerifierWrapper, PartiallyVerifiedBlock};
 	use types::{PeerIndex, BlockHeight, StorageRef, MemoryPoolRef};
-	use VerificationParameters;
+	use {VerificationParameters, TransactionRejection};
 
 	#[derive(Default)]
 	pub struct DummyVerifier {
@@ -401,6 +420,9 @@ pub mod tests {
 			self.verifier = Some(ChainVerifierWrapper::new(verifier, self.storage.as_ref().unwrap(), VerificationParameters {
 				verification_level: VerificationLevel::FULL,
 				verification_edge: 0u8.into(),
+				edge_ancestors_only: false,
+				signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+				script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 			}));
 		}
 
@@ -440,7 +462,7 @@ pub mod tests {
 		fn verify_transaction(&self, _height: BlockHeight, transaction: IndexedTransaction) {
 			match self.sink {
 				Some(ref sink) => match self.errors.get(&transaction.hash) {
-					Some(err) => sink.on_transaction_verification_error(&err, &transaction.hash),
+					Some(err) => sink.on_transaction_verification_error(&TransactionRejection::Consensus(err.clone()), &transaction.hash),
 					None => {
 						if self.actual_checks.contains(&transaction.hash) {
 							let next_block_height = self.storage.as_ref().unwrap().best_block().number + 1;
@@ -464,12 +486,18 @@ pub mod tests {
 		assert_eq!(ChainVerifierWrapper::new(verifier.clone(), &storage, VerificationParameters {
 			verification_level: VerificationLevel::NO_VERIFICATION,
 			verification_edge: test_data::genesis().hash(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		}).enforce_full_verification.load(Ordering::Relaxed), true);
 
 		// switching to full verification when block with given hash is coming
 		let wrapper = ChainVerifierWrapper::new(verifier, &storage, VerificationParameters {
 			verification_level: VerificationLevel::NO_VERIFICATION,
 			verification_edge: test_data::block_h1().hash(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		});
 		assert_eq!(wrapper.enforce_full_verification.load(Ordering::Relaxed), false);
 		let block: IndexedBlock = test_data::block_h1().into();
@@ -525,15 +553,35 @@ pub mod tests {
 		let wrapper = ChainVerifierWrapper::new(verifier.clone(), &storage, VerificationParameters {
 			verification_level: VerificationLevel::HEADER,
 			verification_edge: 1.into(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		});
 		assert_eq!(wrapper.verify_block(&bad_transaction_block.clone().into()), Ok(()));
 
 		// Error when tx script is checked
-		let wrapper = ChainVerifierWrapper::new(verifier, &storage, VerificationParameters {
+		let wrapper = ChainVerifierWrapper::new(verifier.clone(), &storage, VerificationParameters {
 			verification_level: VerificationLevel::FULL,
 			verification_edge: 1.into(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
+		});
+		assert_eq!(wrapper.verify_block(&bad_transaction_block.clone().into()), Err(VerificationError::Transaction(1, TransactionError::Signature(0, ScriptError::InvalidStackOperation))));
+
+		// when level is only applied to edge ancestors, tx script of the fork (non-ancestor) block is checked
+		let wrapper = ChainVerifierWrapper::new(verifier, &storage, VerificationParameters {
+			verification_level: VerificationLevel::HEADER,
+			verification_edge: 1.into(),
+			edge_ancestors_only: true,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		});
-		assert_eq!(wrapper.verify_block(&bad_transaction_block.into()), Err(VerificationError::Transaction(1, TransactionError::Signature(0, ScriptError::InvalidStackOperation))));
+		assert_eq!(wrapper.verify_block(&PartiallyVerifiedBlock::EdgeAncestor(bad_transaction_block.clone())), Ok(()));
+		assert_eq!(wrapper.verify_block(&PartiallyVerifiedBlock::HeaderPreVerified(bad_transaction_block.clone())),
+			Err(VerificationError::Transaction(1, TransactionError::Signature(0, ScriptError::InvalidStackOperation))));
+		assert_eq!(wrapper.verify_block(&PartiallyVerifiedBlock::NotVerified(bad_transaction_block)),
+			Err(VerificationError::Transaction(1, TransactionError::Signature(0, ScriptError::InvalidStackOperation))));
 	}
 
 	#[test]
@@ -546,6 +594,9 @@ pub mod tests {
 		let wrapper = ChainVerifierWrapper::new(verifier.clone(), &storage, VerificationParameters {
 			verification_level: VerificationLevel::NO_VERIFICATION,
 			verification_edge: 1.into(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		});
 		assert_eq!(wrapper.verify_block(&bad_block.clone().into()), Ok(()));
 
@@ -553,6 +604,9 @@ pub mod tests {
 		let wrapper = ChainVerifierWrapper::new(verifier, &storage, VerificationParameters {
 			verification_level: VerificationLevel::FULL,
 			verification_edge: 1.into(),
+			edge_ancestors_only: false,
+			signature_cache_size: DEFAULT_SIGNATURE_CACHE_SIZE,
+			script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
 		});
 		assert_eq!(wrapper.verify_block(&bad_block.into()), Err(VerificationError::Empty));
 	}
@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+
+/// Maximal number of block arrivals to remember
+const MAX_ARRIVALS: usize = 4096;
+
+/// How received block relates to the current best block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockArrivalKind {
+	/// Block extends the best block
+	InOrder,
+	/// Block parent is unknown at the moment of arrival
+	OutOfOrder,
+	/// Block parent is known, but it is not the best block => block is (at least currently) stale
+	Stale,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BlockArrival {
+	/// Block receive time (seconds since epoch)
+	received: u32,
+	/// Difference between receive time and block header time
+	delay: u32,
+	/// Arrival kind
+	kind: BlockArrivalKind,
+}
+
+/// Block arrival statistics over the window
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BlockArrivalInformation {
+	/// Number of blocks received within window
+	pub blocks: usize,
+	/// Number of blocks received before their parent
+	pub out_of_order: usize,
+	/// Number of blocks which haven't extended best block
+	pub stale: usize,
+	/// Share of stale blocks among all received blocks
+	pub orphan_rate: f64,
+	/// Median propagation delay (in seconds)
+	pub delay_median: u32,
+	/// 90th percentile of propagation delay (in seconds)
+	pub delay_p90: u32,
+	/// 99th percentile of propagation delay (in seconds)
+	pub delay_p99: u32,
+}
+
+/// Recent block arrivals
+#[derive(Debug, Default)]
+pub struct BlockArrivalStats {
+	arrivals: VecDeque<BlockArrival>,
+}
+
+impl BlockArrivalStats {
+	/// Record new block arrival
+	pub fn note_arrival(&mut self, received: u32, block_time: u32, kind: BlockArrivalKind) {
+		if self.arrivals.len() == MAX_ARRIVALS {
+			self.arrivals.pop_front();
+		}
+
+		self.arrivals.push_back(BlockArrival {
+			received: received,
+			delay: received.saturating_sub(block_time),
+			kind: kind,
+		});
+	}
+
+	/// Get statistics of blocks received within last `window` seconds
+	pub fn information(&self, now: u32, window: u32) -> BlockArrivalInformation {
+		let since = now.saturating_sub(window);
+		let arrivals: Vec<_> = self.arrivals.iter()
+			.filter(|arrival| arrival.received >= since)
+			.collect();
+		if arrivals.is_empty() {
+			return BlockArrivalInformation::default();
+		}
+
+		let mut delays: Vec<_> = arrivals.iter().map(|arrival| arrival.delay).collect();
+		delays.sort();
+
+		let count_kind = |kind| arrivals.iter().filter(|arrival| arrival.kind == kind).count();
+		let stale = count_kind(BlockArrivalKind::Stale);
+		BlockArrivalInformation {
+			blocks: arrivals.len(),
+			out_of_order: count_kind(BlockArrivalKind::OutOfOrder),
+			stale: stale,
+			orphan_rate: stale as f64 / arrivals.len() as f64,
+			delay_median: percentile(&delays, 50),
+			delay_p90: percentile(&delays, 90),
+			delay_p99: percentile(&delays, 99),
+		}
+	}
+}
+
+/// Nearest-rank percentile of sorted non-empty values
+fn percentile(sorted: &[u32], percent: usize) -> u32 {
+	let rank = (percent * sorted.len() + 99) / 100;
+	sorted[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{BlockArrivalStats, BlockArrivalKind, BlockArrivalInformation};
+
+	#[test]
+	fn block_arrival_stats_empty() {
+		let stats = BlockArrivalStats::default();
+		assert_eq!(stats.information(1000, 100), BlockArrivalInformation::default());
+	}
+
+	#[test]
+	fn block_arrival_stats_window() {
+		let mut stats = BlockArrivalStats::default();
+		stats.note_arrival(100, 90, BlockArrivalKind::Stale);
+		for i in 0..10 {
+			stats.note_arrival(1000 + i, 1000, BlockArrivalKind::InOrder);
+		}
+		stats.note_arrival(1010, 1000, BlockArrivalKind::OutOfOrder);
+		stats.note_arrival(1010, 1020, BlockArrivalKind::Stale);
+
+		let info = stats.information(1010, 100);
+		assert_eq!(info.blocks, 12);
+		assert_eq!(info.out_of_order, 1);
+		assert_eq!(info.stale, 1);
+		assert_eq!(info.orphan_rate, 1f64 / 12f64);
+		// delays: 0, 0, 1, 2, ..., 9, 10
+		assert_eq!(info.delay_median, 4);
+		assert_eq!(info.delay_p90, 9);
+		assert_eq!(info.delay_p99, 10);
+
+		let info = stats.information(1010, 1000);
+		assert_eq!(info.blocks, 13);
+		assert_eq!(info.stale, 2);
+	}
+}
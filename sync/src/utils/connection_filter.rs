@@ -1,10 +1,10 @@
 use bit_vec::BitVec;
-use chain::{IndexedBlock, IndexedTransaction};
+use chain::{IndexedBlock, IndexedTransaction, build_partial_merkle_tree};
 use message::types;
 use primitives::bytes::Bytes;
 use primitives::hash::H256;
 use synchronization_peers::MerkleBlockArtefacts;
-use utils::{KnownHashFilter, KnownHashType, BloomFilter, FeeRateFilter, build_partial_merkle_tree};
+use utils::{KnownHashFilter, KnownHashType, BloomFilter, FeeRateFilter};
 
 /// Filter, which controls data relayed over connection.
 #[derive(Debug, Default)]
@@ -93,19 +93,8 @@ impl ConnectionFilter {
 
 		// build partial merkle tree
 		let partial_merkle_tree = build_partial_merkle_tree(all_hashes, all_flags);
+		result.merkleblock.flags = partial_merkle_tree.flag_bytes().into();
 		result.merkleblock.hashes.extend(partial_merkle_tree.hashes);
-		// to_bytes() converts [true, false, true] to 0b10100000
-		// while protocol requires [true, false, true] to be serialized as 0x00000101
-		result.merkleblock.flags = partial_merkle_tree.flags.to_bytes().into_iter()
-			.map(|b|
-				((b & 0b10000000) >> 7) |
-				((b & 0b01000000) >> 5) |
-				((b & 0b00100000) >> 3) |
-				((b & 0b00010000) >> 1) |
-				((b & 0b00001000) << 1) |
-				((b & 0b00000100) << 3) |
-				((b & 0b00000010) << 5) |
-				((b & 0b00000001) << 7)).collect::<Vec<u8>>().into();
 		Some(result)
 	}
 }
@@ -1,8 +1,12 @@
-use linked_hash_map::LinkedHashMap;
 use primitives::hash::H256;
+use utils::RollingBloomFilter;
 
-/// Maximal number of hashes to store in known-hashes filter
-pub const MAX_KNOWN_HASHES_LEN: usize = 2048;
+/// Number of latest transactions hashes to remember in known-hashes filter
+pub const MAX_KNOWN_TRANSACTIONS_LEN: usize = 50000;
+/// Number of latest blocks hashes to remember in known-hashes filter
+pub const MAX_KNOWN_BLOCKS_LEN: usize = 10000;
+/// False positive rate of known-hashes filter
+const KNOWN_HASHES_FALSE_POSITIVE_RATE: f64 = 0.000001;
 
 /// Hash-knowledge type
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -14,56 +18,57 @@ pub enum KnownHashType {
 }
 
 /// Known-hashes filter
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct KnownHashFilter {
-	/// Insertion-time ordered known hashes
-	known_hashes: LinkedHashMap<H256, KnownHashType>,
+	/// Recently known transactions hashes
+	known_transactions: RollingBloomFilter,
+	/// Recently known blocks hashes
+	known_blocks: RollingBloomFilter,
+}
+
+impl Default for KnownHashFilter {
+	fn default() -> Self {
+		KnownHashFilter {
+			known_transactions: RollingBloomFilter::new(MAX_KNOWN_TRANSACTIONS_LEN, KNOWN_HASHES_FALSE_POSITIVE_RATE),
+			known_blocks: RollingBloomFilter::new(MAX_KNOWN_BLOCKS_LEN, KNOWN_HASHES_FALSE_POSITIVE_RATE),
+		}
+	}
 }
 
 impl KnownHashFilter {
 	/// Insert known hash
 	pub fn insert(&mut self, hash: H256, hash_type: KnownHashType) {
-		if !self.known_hashes.contains_key(&hash) {
-			self.known_hashes.insert(hash, hash_type);
-			// remove oldest-known hash, if limits overflow
-			if self.known_hashes.len() > MAX_KNOWN_HASHES_LEN {
-				self.known_hashes.pop_front();
+		if !self.contains(&hash, hash_type) {
+			match hash_type {
+				KnownHashType::Transaction => self.known_transactions.insert(&*hash),
+				KnownHashType::Block => self.known_blocks.insert(&*hash),
 			}
 		}
 	}
 
-	/// Returns number of known hashes
-	#[cfg(test)]
-	pub fn len(&self) -> usize {
-		self.known_hashes.len()
-	}
-
-	/// Returns true if peer knows about this hash with this type
+	/// Returns true if peer (probably) knows about this hash with this type
 	pub fn contains(&self, hash: &H256, hash_type: KnownHashType) -> bool {
-		self.known_hashes.get(hash)
-			.map(|stored_hash_type| *stored_hash_type == hash_type)
-			.unwrap_or(false)
+		match hash_type {
+			KnownHashType::Transaction => self.known_transactions.contains(&**hash),
+			KnownHashType::Block => self.known_blocks.contains(&**hash),
+		}
 	}
 
 	/// Filter block using its hash
 	pub fn filter_block(&self, hash: &H256) -> bool {
-		self.known_hashes.get(hash)
-			.map(|stored_hash_type| *stored_hash_type != KnownHashType::Block)
-			.unwrap_or(true)
+		!self.contains(hash, KnownHashType::Block)
 	}
 
 	/// Filter transaction using its hash
 	pub fn filter_transaction(&self, hash: &H256) -> bool {
-		self.known_hashes.get(hash)
-			.map(|stored_hash_type| *stored_hash_type != KnownHashType::Transaction)
-			.unwrap_or(true)
+		!self.contains(hash, KnownHashType::Transaction)
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use primitives::hash::H256;
-	use super::{KnownHashFilter, KnownHashType, MAX_KNOWN_HASHES_LEN};
+	use super::{KnownHashFilter, KnownHashType, MAX_KNOWN_BLOCKS_LEN};
 
 	#[test]
 	fn known_hash_filter_empty() {
@@ -114,29 +119,20 @@ mod tests {
 	fn known_hash_filter_insert() {
 		let mut hash_data = [0u8; 32];
 		let mut filter = KnownHashFilter::default();
-		assert_eq!(filter.len(), 0);
-		// insert new hash
-		filter.insert(H256::from(hash_data.clone()), KnownHashType::Block);
-		assert_eq!(filter.len(), 1);
-		// insert already known hash => nothing should change
-		filter.insert(H256::from(hash_data.clone()), KnownHashType::Block);
-		assert_eq!(filter.len(), 1);
-		// insert MAX_KNOWN_HASHES_LEN
-		for i in 1..MAX_KNOWN_HASHES_LEN {
+		// insert MAX_KNOWN_BLOCKS_LEN hashes
+		for i in 0..MAX_KNOWN_BLOCKS_LEN {
 			hash_data[0] = (i % 255) as u8;
 			hash_data[1] = ((i / 255) % 255) as u8;
 			filter.insert(H256::from(hash_data.clone()), KnownHashType::Block);
-			assert_eq!(filter.len(), i + 1);
 		}
-		// insert new unknown hash => nothing should change as we already have max number of hashes
-		hash_data[0] = ((MAX_KNOWN_HASHES_LEN + 1) % 255) as u8;
-		hash_data[1] = (((MAX_KNOWN_HASHES_LEN + 1) / 255) % 255) as u8;
-		filter.insert(H256::from(hash_data.clone()), KnownHashType::Block);
-		assert_eq!(filter.len(), MAX_KNOWN_HASHES_LEN);
-		// check that oldest known hash has been removed
+		// check that all of them are still known
+		for i in 0..MAX_KNOWN_BLOCKS_LEN {
+			hash_data[0] = (i % 255) as u8;
+			hash_data[1] = ((i / 255) % 255) as u8;
+			assert!(filter.contains(&H256::from(hash_data.clone()), KnownHashType::Block));
+		}
+		// check that the same hash is not known as transaction
 		hash_data[0] = 0; hash_data[1] = 0;
-		assert!(!filter.contains(&H256::from(hash_data.clone()), KnownHashType::Block));
-		hash_data[0] = 1; hash_data[1] = 0;
-		assert!(filter.contains(&H256::from(hash_data.clone()), KnownHashType::Block));
+		assert!(!filter.contains(&H256::from(hash_data.clone()), KnownHashType::Transaction));
 	}
 }
@@ -1,5 +1,6 @@
 mod average_speed_meter;
 mod best_headers_chain;
+mod block_arrival_stats;
 mod bloom_filter;
 mod connection_filter;
 mod fee_rate_filter;
@@ -8,11 +9,12 @@ mod known_hash_filter;
 mod memory_pool_transaction_provider;
 mod orphan_blocks_pool;
 mod orphan_transactions_pool;
-mod partial_merkle_tree;
+mod rolling_bloom_filter;
 mod synchronization_state;
 
 pub use self::average_speed_meter::AverageSpeedMeter;
 pub use self::best_headers_chain::{BestHeadersChain, Information as BestHeadersChainInformation};
+pub use self::block_arrival_stats::{BlockArrivalStats, BlockArrivalKind, BlockArrivalInformation};
 pub use self::bloom_filter::BloomFilter;
 pub use self::connection_filter::ConnectionFilter;
 pub use self::fee_rate_filter::FeeRateFilter;
@@ -21,7 +23,7 @@ pub use self::known_hash_filter::{KnownHashType, KnownHashFilter};
 pub use self::memory_pool_transaction_provider::MemoryPoolTransactionOutputProvider;
 pub use self::orphan_blocks_pool::OrphanBlocksPool;
 pub use self::orphan_transactions_pool::{OrphanTransactionsPool, OrphanTransaction};
-pub use self::partial_merkle_tree::{PartialMerkleTree, build_partial_merkle_tree};
+pub use self::rolling_bloom_filter::RollingBloomFilter;
 pub use self::synchronization_state::SynchronizationState;
 
 /// Block height type
@@ -13,6 +13,8 @@ pub struct OrphanBlocksPool {
 	orphaned_blocks: HashMap<H256, HashMap<H256, IndexedBlock>>,
 	/// Blocks that we have received without requesting with receiving time.
 	unknown_blocks: LinkedHashMap<H256, f64>,
+	/// Total serialized size of all blocks in pool.
+	memory_usage: usize,
 }
 
 impl OrphanBlocksPool {
@@ -21,6 +23,7 @@ impl OrphanBlocksPool {
 		OrphanBlocksPool {
 			orphaned_blocks: HashMap::new(),
 			unknown_blocks: LinkedHashMap::new(),
+			memory_usage: 0,
 		}
 	}
 
@@ -29,6 +32,16 @@ impl OrphanBlocksPool {
 		self.orphaned_blocks.len()
 	}
 
+	/// Get total number of blocks in pool
+	pub fn blocks_count(&self) -> usize {
+		self.orphaned_blocks.values().map(HashMap::len).sum()
+	}
+
+	/// Get total serialized size of blocks in pool
+	pub fn memory_usage(&self) -> usize {
+		self.memory_usage
+	}
+
 	/// Check if block with given hash is stored as unknown in this pool
 	pub fn contains_unknown_block(&self, hash: &H256) -> bool {
 		self.unknown_blocks.contains_key(hash)
@@ -41,10 +54,14 @@ impl OrphanBlocksPool {
 
 	/// Insert orphaned block, for which we have already requested its parent block
 	pub fn insert_orphaned_block(&mut self, block: IndexedBlock) {
-		self.orphaned_blocks
+		self.memory_usage += block.size();
+		let replaced = self.orphaned_blocks
 			.entry(block.header.raw.previous_header_hash.clone())
 			.or_insert_with(HashMap::new)
 			.insert(block.header.hash.clone(), block);
+		if let Some(replaced) = replaced {
+			self.memory_usage -= replaced.size();
+		}
 	}
 
 	/// Insert unknown block, for which we know nothing about its parent block
@@ -78,6 +95,7 @@ impl OrphanBlocksPool {
 					self.unknown_blocks.remove(orphaned_hash);
 				}
 				queue.extend(orphaned.keys().cloned());
+				self.memory_usage -= orphaned.values().map(IndexedBlock::size).sum::<usize>();
 				removed.extend(orphaned.into_iter().map(|(_, b)| b));
 			}
 		}
@@ -96,10 +114,10 @@ impl OrphanBlocksPool {
 					let orphans_keys: HashSet<H256> = orphans.keys().cloned().collect();
 					for orphan_to_remove in orphans_keys.intersection(hashes) {
 						self.unknown_blocks.remove(orphan_to_remove);
-						removed.push(
-							orphans.remove(orphan_to_remove)
-								.expect("iterating by intersection of orphans keys with hashes; removing from orphans; qed")
-						);
+						let orphan = orphans.remove(orphan_to_remove)
+							.expect("iterating by intersection of orphans keys with hashes; removing from orphans; qed");
+						self.memory_usage -= orphan.size();
+						removed.push(orphan);
 					}
 					orphans.is_empty()
 				};
@@ -124,6 +142,7 @@ mod tests {
 	extern crate test_data;
 
 	use std::collections::HashSet;
+	use chain::IndexedBlock;
 	use primitives::hash::H256;
 	use super::OrphanBlocksPool;
 
@@ -241,4 +260,28 @@ mod tests {
 
 		assert_eq!(pool.len(), 1);
 	}
+
+	#[test]
+	fn orphan_block_pool_memory_usage() {
+		let mut pool = OrphanBlocksPool::new();
+		let b1: IndexedBlock = test_data::block_h1().into();
+		let b2: IndexedBlock = test_data::block_h2().into();
+		let b3: IndexedBlock = test_data::block_h169().into();
+		let (b1_size, b2_size, b3_size) = (b1.size(), b2.size(), b3.size());
+
+		pool.insert_orphaned_block(b1.clone());
+		pool.insert_orphaned_block(b2);
+		pool.insert_unknown_block(b3);
+		assert_eq!(pool.memory_usage(), b1_size + b2_size + b3_size);
+
+		// reinserting the same block doesn't change memory usage
+		pool.insert_orphaned_block(b1);
+		assert_eq!(pool.memory_usage(), b1_size + b2_size + b3_size);
+
+		pool.remove_blocks_for_parent(&test_data::genesis().hash());
+		assert_eq!(pool.memory_usage(), b3_size);
+
+		pool.remove_blocks(&vec![test_data::block_h169().hash()].into_iter().collect());
+		assert_eq!(pool.memory_usage(), 0);
+	}
 }
@@ -0,0 +1,124 @@
+use std::f64::consts::LN_2;
+use bit_vec::BitVec;
+use murmur3::murmur3_32;
+use rand::{thread_rng, Rng};
+
+/// Constant optimized to create large differences in the seed for different values of `hash_functions_num`.
+const SEED_OFFSET: u32 = 0xFBA4C795;
+
+/// Probabilistic filter, which remembers (at least) last `capacity` inserted items.
+///
+/// Filter consists of two bloom filters (generations) of `capacity` items each. Items are inserted into
+/// the current generation. When current generation is full, it becomes previous generation, and previous
+/// generation is cleared and becomes current generation.
+#[derive(Debug)]
+pub struct RollingBloomFilter {
+	/// Current generation
+	current: BitVec,
+	/// Previous generation
+	previous: BitVec,
+	/// Number of items inserted into current generation
+	current_len: usize,
+	/// Maximal number of items in single generation
+	generation_capacity: usize,
+	/// Number of hash functions to use in bloom filter
+	hash_functions_num: u32,
+	/// Value to add to Murmur3 hash seed when calculating hash
+	tweak: u32,
+}
+
+impl RollingBloomFilter {
+	/// Create filter, which remembers last `capacity` items with given false positive rate
+	pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+		let generation_capacity = ::std::cmp::max(capacity, 1);
+		// both generations are checked => each of them must have half of false positive rate
+		let false_positive_rate = false_positive_rate / 2.0;
+		let bits = (-(generation_capacity as f64) * false_positive_rate.ln() / (LN_2 * LN_2)).ceil() as usize;
+		let hash_functions_num = ((bits as f64 / generation_capacity as f64) * LN_2).round() as u32;
+
+		RollingBloomFilter {
+			current: BitVec::from_elem(bits, false),
+			previous: BitVec::from_elem(bits, false),
+			current_len: 0,
+			generation_capacity: generation_capacity,
+			hash_functions_num: ::std::cmp::max(hash_functions_num, 1),
+			tweak: thread_rng().gen(),
+		}
+	}
+
+	/// True if filter (probably) contains given bytes
+	pub fn contains(&self, data: &[u8]) -> bool {
+		let indices: Vec<_> = self.indices(data).collect();
+		contains_all(&self.current, &indices) || contains_all(&self.previous, &indices)
+	}
+
+	/// Add bytes to the filter
+	pub fn insert(&mut self, data: &[u8]) {
+		if self.current_len == self.generation_capacity {
+			::std::mem::swap(&mut self.current, &mut self.previous);
+			self.current.clear();
+			self.current_len = 0;
+		}
+
+		let indices: Vec<_> = self.indices(data).collect();
+		for index in indices {
+			self.current.set(index, true);
+		}
+		self.current_len += 1;
+	}
+
+	/// Bit indices of given bytes
+	fn indices<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item=usize> + 'a {
+		let bits = self.current.len();
+		(0..self.hash_functions_num).map(move |hash_function_idx| {
+			let murmur_seed = hash_function_idx.overflowing_mul(SEED_OFFSET).0.overflowing_add(self.tweak).0;
+			murmur3_32(&mut data.as_ref(), murmur_seed) as usize % bits
+		})
+	}
+}
+
+fn contains_all(filter: &BitVec, indices: &[usize]) -> bool {
+	indices.iter().all(|index| filter.get(*index).expect("index is result of mod operation by filter len; qed"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::RollingBloomFilter;
+
+	fn item(i: usize) -> [u8; 4] {
+		[i as u8, (i >> 8) as u8, (i >> 16) as u8, (i >> 24) as u8]
+	}
+
+	#[test]
+	fn rolling_bloom_filter_empty() {
+		let filter = RollingBloomFilter::new(100, 0.000001);
+		assert!(!filter.contains(&item(0)));
+		assert!(!filter.contains(&item(1)));
+	}
+
+	#[test]
+	fn rolling_bloom_filter_insert() {
+		let mut filter = RollingBloomFilter::new(100, 0.000001);
+		filter.insert(&item(0));
+		filter.insert(&item(1));
+		assert!(filter.contains(&item(0)));
+		assert!(filter.contains(&item(1)));
+		assert!(!filter.contains(&item(2)));
+	}
+
+	#[test]
+	fn rolling_bloom_filter_remembers_last_items() {
+		let mut filter = RollingBloomFilter::new(100, 0.000001);
+		for i in 0..1000 {
+			filter.insert(&item(i));
+			// last `capacity` items are always remembered
+			for j in i.saturating_sub(99)..i + 1 {
+				assert!(filter.contains(&item(j)));
+			}
+		}
+
+		// oldest items are forgotten
+		assert!(!filter.contains(&item(0)));
+		assert!(!filter.contains(&item(1)));
+	}
+}
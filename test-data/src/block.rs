@@ -483,7 +483,7 @@ impl<F> TransactionOutputBuilder<F> where F: Invoke<chain::TransactionOutput> {
 	}
 
 	pub fn founder_reward(mut self, consensus: &ConsensusParams, height: u32) -> Self {
-		self.script_pubkey = ScriptBuilder::build_p2sh(&consensus.founder_address(height).unwrap().hash).into();
+		self.script_pubkey = consensus.founders_reward_address(height).unwrap().to_script_pubkey();
 		self.value = consensus.founder_reward(height);
 		self
 	}
@@ -116,6 +116,11 @@ impl TransactionBuilder {
 		self
 	}
 
+	pub fn set_consensus_branch_id(mut self, consensus_branch_id: u32) -> TransactionBuilder {
+		self.transaction.consensus_branch_id = consensus_branch_id;
+		self
+	}
+
 	pub fn add_output(mut self, value: u64) -> TransactionBuilder {
 		self.transaction.outputs.push(TransactionOutput {
 			value: value,
@@ -1,9 +1,10 @@
+use chain::{Amount, BlockCommitment, HistoryTree, auth_data_root, block_commitments_hash};
 use keys::Address;
-use network::{ConsensusParams};
+use network::{ConsensusParams, FundingStreamOutput};
 use storage::{DuplexTransactionOutputProvider, TransactionOutputProvider, BlockHeaderProvider,
-	TreeStateProvider, SaplingTreeState};
-use script::{self, Builder};
-use sigops::transaction_sigops;
+	TreeStateProvider, SaplingTreeState, ValuePools, ValuePoolsProvider};
+use script;
+use sigops::ValidationCost;
 use deployments::BlockDeployments;
 use canon::CanonBlock;
 use error::Error;
@@ -17,14 +18,18 @@ pub struct BlockAcceptor<'a> {
 	pub sigops: BlockSigops<'a>,
 	pub miner_reward: BlockCoinbaseMinerReward<'a>,
 	pub founder_reward: BlockFounderReward<'a>,
+	pub funding_streams: BlockFundingStreams<'a>,
 	pub coinbase_script: BlockCoinbaseScript<'a>,
 	pub sapling_root: BlockSaplingRoot<'a>,
+	pub history_commitment: BlockHistoryCommitment<'a>,
+	pub value_pools: BlockValuePools<'a>,
 }
 
 impl<'a> BlockAcceptor<'a> {
 	pub fn new(
 		tx_out_store: &'a TransactionOutputProvider,
 		tree_state_store: &'a TreeStateProvider,
+		value_pools_store: &'a ValuePoolsProvider,
 		consensus: &'a ConsensusParams,
 		block: CanonBlock<'a>,
 		height: u32,
@@ -37,8 +42,11 @@ impl<'a> BlockAcceptor<'a> {
 			coinbase_script: BlockCoinbaseScript::new(block, consensus, height),
 			miner_reward: BlockCoinbaseMinerReward::new(block, tx_out_store, consensus, height),
 			founder_reward: BlockFounderReward::new(block, consensus, height),
+			funding_streams: BlockFundingStreams::new(block, consensus, height),
 			sigops: BlockSigops::new(block, tx_out_store, consensus),
 			sapling_root: BlockSaplingRoot::new(block, tree_state_store, consensus, height),
+			history_commitment: BlockHistoryCommitment::new(block, tree_state_store, consensus, height),
+			value_pools: BlockValuePools::new(block, value_pools_store),
 		}
 	}
 
@@ -48,8 +56,11 @@ impl<'a> BlockAcceptor<'a> {
 		self.serialized_size.check()?;
 		self.miner_reward.check()?;
 		self.founder_reward.check()?;
+		self.funding_streams.check()?;
 		self.coinbase_script.check()?;
 		self.sapling_root.check()?;
+		self.history_commitment.check()?;
+		self.value_pools.check()?;
 		Ok(())
 	}
 }
@@ -97,7 +108,7 @@ impl<'a> BlockSerializedSize<'a> {
 	fn new(block: CanonBlock<'a>, consensus: &'a ConsensusParams) -> Self {
 		BlockSerializedSize {
 			block: block,
-			max_block_size: consensus.max_block_size(),
+			max_block_size: consensus.block_limits.max_block_size,
 		}
 	}
 
@@ -131,15 +142,13 @@ impl<'a> BlockSigops<'a> {
 			block: block,
 			store: store,
 			bip16_active,
-			max_block_sigops: consensus.max_block_sigops(),
+			max_block_sigops: consensus.block_limits.max_block_sigops,
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
 		let store = DuplexTransactionOutputProvider::new(self.store, &*self.block);
-		let sigops = self.block.transactions.iter()
-			.map(|tx| transaction_sigops(&tx.raw, &store, self.bip16_active))
-			.fold(0, |acc, tx_sigops| (acc + tx_sigops));
+		let sigops = ValidationCost::block(&*self.block, &store, self.bip16_active).sigops;
 
 		if sigops > self.max_block_sigops {
 			return Err(Error::MaximumSigops);
@@ -165,34 +174,28 @@ impl<'a> BlockCoinbaseMinerReward<'a> {
 		BlockCoinbaseMinerReward {
 			block: block,
 			store: store,
-			max_reward: consensus.block_reward(height),
+			max_reward: consensus.block_subsidy(height),
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
 		let store = DuplexTransactionOutputProvider::new(self.store, &*self.block);
 
-		let mut fees: u64 = 0;
+		let mut fees = Amount::zero();
 
 		for (tx_idx, tx) in self.block.transactions.iter().enumerate().skip(1) {
 			let tx_fee = checked_transaction_fee(&store, tx_idx, &tx.raw)
 				.map_err(|tx_err| Error::Transaction(tx_idx, tx_err))?;
 
-			// Adding to total fees (with possible overflow)
-			let (sum, overflow) = fees.overflowing_add(tx_fee);
-			if overflow {
-				return Err(Error::TransactionFeesOverflow)
-			}
-
-			fees = sum;
+			fees = fees.checked_add(tx_fee).ok_or(Error::TransactionFeesOverflow)?;
 		}
 
 		let claim = self.block.transactions[0].raw.total_spends();
 
-		let (max_reward, overflow) = fees.overflowing_add(self.max_reward);
-		if overflow {
-			return Err(Error::TransactionFeeAndRewardOverflow);
-		}
+		let max_reward = Amount::from_u64(self.max_reward)
+			.and_then(|max_reward| fees.checked_add(max_reward))
+			.and_then(Amount::to_u64)
+			.ok_or(Error::TransactionFeeAndRewardOverflow)?;
 
 		if claim > max_reward {
 			Err(Error::CoinbaseOverspend { expected_max: max_reward, actual: claim })
@@ -249,17 +252,17 @@ impl<'a> BlockFounderReward<'a> {
 	fn new(block: CanonBlock<'a>, consensus_params: &ConsensusParams, height: u32) -> Self {
 		BlockFounderReward {
 			block: block,
-			founder_address: consensus_params.founder_address(height),
+			founder_address: consensus_params.founders_reward_address(height),
 			founder_reward: consensus_params.founder_reward(height),
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
 		if let Some(ref founder_address) = self.founder_address {
-			let script = Builder::build_p2sh(&founder_address.hash);
+			let script = founder_address.to_script_pubkey();
 			let has_founder_reward = self.block.transactions.first()
 				.map(|tx| tx.raw.outputs.iter().any(|output|
-					**output.script_pubkey == *script &&
+					output.script_pubkey == script &&
 					output.value == self.founder_reward))
 				.unwrap_or(false);
 
@@ -272,10 +275,44 @@ impl<'a> BlockFounderReward<'a> {
 	}
 }
 
+/// After Canopy activation, coinbase must pay to every active funding stream (instead of founders).
+/// https://zips.z.cash/zip-0207
+pub struct BlockFundingStreams<'a> {
+	block: CanonBlock<'a>,
+	outputs: Vec<FundingStreamOutput>,
+}
+
+impl<'a> BlockFundingStreams<'a> {
+	fn new(block: CanonBlock<'a>, consensus_params: &ConsensusParams, height: u32) -> Self {
+		BlockFundingStreams {
+			block: block,
+			outputs: consensus_params.funding_stream_outputs(height),
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		for output in &self.outputs {
+			let script = output.address.to_script_pubkey();
+			let has_output = self.block.transactions.first()
+				.map(|tx| tx.raw.outputs.iter().any(|tx_output|
+					tx_output.script_pubkey == script &&
+					tx_output.value == output.value))
+				.unwrap_or(false);
+
+			if !has_output {
+				return Err(Error::MissingFundingStreamOutput(output.receiver));
+			}
+		}
+
+		Ok(())
+	}
+}
+
 pub struct BlockSaplingRoot<'a> {
 	block: CanonBlock<'a>,
 	tree_state_store: &'a TreeStateProvider,
-	is_sapling_active: bool,
+	/// Sapling root is only committed by headers of Sapling and Blossom blocks.
+	is_sapling_root_committed: bool,
 }
 
 impl<'a> BlockSaplingRoot<'a> {
@@ -288,12 +325,15 @@ impl<'a> BlockSaplingRoot<'a> {
 		BlockSaplingRoot {
 			block: block,
 			tree_state_store: tree_state_store,
-			is_sapling_active: consensus_params.is_sapling_active(height),
+			is_sapling_root_committed: match consensus_params.block_commitment(&block.header.raw, height) {
+				BlockCommitment::FinalSaplingRoot(_) => true,
+				_ => false,
+			},
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
-		if !self.is_sapling_active {
+		if !self.is_sapling_root_committed {
 			return Ok(());
 		}
 
@@ -325,17 +365,97 @@ impl<'a> BlockSaplingRoot<'a> {
 	}
 }
 
+pub struct BlockValuePools<'a> {
+	block: CanonBlock<'a>,
+	value_pools_store: &'a ValuePoolsProvider,
+}
+
+impl<'a> BlockValuePools<'a> {
+	fn new(block: CanonBlock<'a>, value_pools_store: &'a ValuePoolsProvider) -> Self {
+		BlockValuePools {
+			block: block,
+			value_pools_store: value_pools_store,
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		let previous_header_hash = &self.block.header.raw.previous_header_hash;
+		let value_pools = if previous_header_hash.is_zero() {
+			ValuePools::default()
+		} else {
+			self.value_pools_store.block_value_pools(previous_header_hash)
+				.ok_or(Error::MissingValuePools)?
+		};
+
+		value_pools.apply_transactions(self.block.transactions.iter().map(|tx| &tx.raw))
+			.map(|_| ())
+			.map_err(Error::NegativeValuePool)
+	}
+}
+
+pub struct BlockHistoryCommitment<'a> {
+	block: CanonBlock<'a>,
+	commitment: BlockCommitment,
+	/// Chain history tree of the network upgrade epoch, ending at the parent block.
+	/// None if the tree is unknown.
+	history_tree: Option<HistoryTree>,
+}
+
+impl<'a> BlockHistoryCommitment<'a> {
+	fn new(block: CanonBlock<'a>, tree_state_store: &'a TreeStateProvider, consensus_params: &ConsensusParams, height: u32) -> Self {
+		// history tree is reset at every network upgrade => it is empty at the activation block
+		let history_tree = if consensus_params.is_activation_height(height) {
+			Some(HistoryTree::new(consensus_params.consensus_branch_id(height)))
+		} else {
+			tree_state_store.history_tree_at_block(&block.header.raw.previous_header_hash)
+		};
+
+		BlockHistoryCommitment {
+			block: block,
+			commitment: consensus_params.block_commitment(&block.header.raw, height),
+			history_tree: history_tree,
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		let expected = match (&self.commitment, &self.history_tree) {
+			(&BlockCommitment::Reserved(_), _) | (&BlockCommitment::FinalSaplingRoot(_), _) => return Ok(()),
+			(&BlockCommitment::ChainHistoryRoot(_), &Some(ref history_tree)) => history_tree.root(),
+			(&BlockCommitment::ChainHistoryRoot(_), &None) => return Err(Error::MissingHistoryTree),
+			(&BlockCommitment::BlockCommitments(_), &Some(ref history_tree)) => block_commitments_hash(
+				&history_tree.root(),
+				&auth_data_root(self.block.transactions.iter().map(|tx| &tx.raw)),
+			),
+			// trees of NU5 blocks commit to orchard note commitment tree roots, which aren't tracked
+			// => only commitments of the NU5 activation block (with the empty tree) are checked
+			(&BlockCommitment::BlockCommitments(_), &None) => return Ok(()),
+		};
+
+		if expected != *self.commitment.hash() {
+			return Err(Error::InvalidBlockCommitment {
+				expected: expected,
+				actual: *self.commitment.hash(),
+			});
+		}
+
+		Ok(())
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	extern crate test_data;
 
 	use std::collections::HashMap;
-	use chain::{OutPoint, TransactionOutput};
+	use chain::{OutPoint, TransactionOutput, IndexedBlock, BlockCommitment, HistoryTree, HistoryNode, auth_data_root,
+		block_commitments_hash, Transaction, Sapling};
 	use db::BlockChainDatabase;
-	use network::{ConsensusParams, Network};
-	use storage::{SaplingTreeState, TransactionOutputProvider};
+	use network::{ConsensusParams, Network, FundingStreamReceiver};
+	use primitives::hash::H256;
+	use storage::{SaplingTreeState, TransactionOutputProvider, ValuePools, ValuePoolsProvider, ValuePool};
 	use {Error, CanonBlock};
-	use super::{BlockCoinbaseScript, BlockSaplingRoot, BlockCoinbaseMinerReward};
+	use super::{BlockCoinbaseScript, BlockSaplingRoot, BlockHistoryCommitment, BlockCoinbaseMinerReward, BlockValuePools,
+		BlockFounderReward, BlockFundingStreams};
 
 	#[test]
 	fn test_block_coinbase_script() {
@@ -375,7 +495,7 @@ mod tests {
 		assert_eq!(BlockSaplingRoot {
 			block: CanonBlock::new(&block),
 			tree_state_store: &storage,
-			is_sapling_active: false,
+			is_sapling_root_committed: false,
 		}.check(), Ok(()));
 
 		// when sapling is active and root matches
@@ -386,7 +506,7 @@ mod tests {
 		assert_eq!(BlockSaplingRoot {
 			block: CanonBlock::new(&block),
 			tree_state_store: &storage,
-			is_sapling_active: true,
+			is_sapling_root_committed: true,
 		}.check(), Ok(()));
 
 		// when sapling is active and root mismatches
@@ -394,13 +514,145 @@ mod tests {
 		assert_eq!(BlockSaplingRoot {
 			block: CanonBlock::new(&block),
 			tree_state_store: &storage,
-			is_sapling_active: true,
+			is_sapling_root_committed: true,
 		}.check(), Err(Error::InvalidFinalSaplingRootHash {
 			expected: "fbc2f4300c01f0b7820d00e3347c8da4ee614674376cbc45359daa54f9b5493e".into(),
 			actual: "0000000000000000000000000000000000000000000000000000000000000000".into(),
 		}));
 	}
 
+	#[test]
+	fn test_block_history_commitment() {
+		let block: IndexedBlock = test_data::block_builder().header().build().build().into();
+		let empty_tree = HistoryTree::new(0xf5b9230b);
+
+		// history root of the network upgrade activation block is all zeroes
+		assert_eq!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::ChainHistoryRoot(Default::default()),
+			history_tree: Some(empty_tree.clone()),
+		}.check(), Ok(()));
+		assert_eq!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::ChainHistoryRoot(1.into()),
+			history_tree: Some(empty_tree.clone()),
+		}.check(), Err(Error::InvalidBlockCommitment {
+			expected: Default::default(),
+			actual: 1.into(),
+		}));
+
+		// NU5 headers also commit to transactions authorizing data
+		let commitments = block_commitments_hash(&Default::default(), &auth_data_root(block.transactions.iter().map(|tx| &tx.raw)));
+		assert_eq!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::BlockCommitments(commitments),
+			history_tree: Some(empty_tree.clone()),
+		}.check(), Ok(()));
+		assert!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::BlockCommitments(Default::default()),
+			history_tree: Some(empty_tree),
+		}.check().is_err());
+
+		// pre-Heartwood commitments are not history commitments
+		assert_eq!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::FinalSaplingRoot(1.into()),
+			history_tree: Some(HistoryTree::new(0x76b809bb)),
+		}.check(), Ok(()));
+
+		// history tree of the parent must be known after Heartwood activation
+		assert_eq!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::ChainHistoryRoot(Default::default()),
+			history_tree: None,
+		}.check(), Err(Error::MissingHistoryTree));
+
+		// root of the non-empty tree is committed to
+		let mut history_tree = HistoryTree::new(0xf5b9230b);
+		history_tree.append(HistoryNode::leaf(&block, 903000, Default::default(), None));
+		assert_eq!(BlockHistoryCommitment {
+			block: CanonBlock::new(&block),
+			commitment: BlockCommitment::ChainHistoryRoot(history_tree.root()),
+			history_tree: Some(history_tree),
+		}.check(), Ok(()));
+	}
+
+	#[test]
+	fn test_block_founder_reward_and_funding_streams_at_canopy_activation() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let coinbase = |height, founder_reward: bool, funding_streams: &[usize]| {
+			let mut outputs = Vec::new();
+			if founder_reward {
+				outputs.push(TransactionOutput {
+					value: consensus.founder_reward(height),
+					script_pubkey: consensus.founders_reward_address(height).unwrap().to_script_pubkey().into(),
+				});
+			}
+			let funding_stream_outputs = consensus.funding_stream_outputs(height);
+			for index in funding_streams {
+				outputs.push(TransactionOutput {
+					value: funding_stream_outputs[*index].value,
+					script_pubkey: funding_stream_outputs[*index].address.to_script_pubkey().into(),
+				});
+			}
+
+			let block: IndexedBlock = test_data::block_builder()
+				.with_transaction(Transaction { outputs: outputs, ..Default::default() })
+				.header().build()
+				.build()
+				.into();
+			block
+		};
+		let check = |block: &IndexedBlock, height| BlockFounderReward::new(CanonBlock::new(block), &consensus, height).check()
+			.and_then(|_| BlockFundingStreams::new(CanonBlock::new(block), &consensus, height).check());
+
+		// the last block before Canopy pays founders reward
+		let canopy_height = 1_046_400;
+		assert_eq!(consensus.funding_stream_outputs(canopy_height - 1).len(), 0);
+		assert_eq!(check(&coinbase(canopy_height - 1, true, &[]), canopy_height - 1), Ok(()));
+		assert_eq!(check(&coinbase(canopy_height - 1, false, &[]), canopy_height - 1), Err(Error::MissingFoundersReward));
+
+		// the Canopy activation block pays to funding streams
+		assert_eq!(consensus.founders_reward_address(canopy_height), None);
+		assert_eq!(consensus.funding_stream_outputs(canopy_height).len(), 3);
+		assert_eq!(check(&coinbase(canopy_height, false, &[0, 1, 2]), canopy_height), Ok(()));
+		assert_eq!(check(&coinbase(canopy_height, false, &[]), canopy_height),
+			Err(Error::MissingFundingStreamOutput(FundingStreamReceiver::Ecc)));
+		assert_eq!(check(&coinbase(canopy_height, false, &[0, 2]), canopy_height),
+			Err(Error::MissingFundingStreamOutput(FundingStreamReceiver::ZcashFoundation)));
+	}
+
+	#[test]
+	fn test_block_value_pools() {
+		struct Store(Option<ValuePools>);
+
+		impl ValuePoolsProvider for Store {
+			fn block_value_pools(&self, _block_hash: &H256) -> Option<ValuePools> {
+				self.0
+			}
+		}
+
+		let unshield = |value| Transaction {
+			sapling: Some(Sapling { balancing_value: value, ..Default::default() }),
+			..Default::default()
+		};
+		let block: IndexedBlock = test_data::block_builder()
+			.with_transaction(unshield(100))
+			.header().parent(1.into()).build()
+			.build()
+			.into();
+
+		let store = Store(Some(ValuePools { sprout: 0, sapling: 100, orchard: 0 }));
+		assert_eq!(BlockValuePools::new(CanonBlock::new(&block), &store).check(), Ok(()));
+
+		let store = Store(Some(ValuePools { sprout: 1000, sapling: 99, orchard: 0 }));
+		assert_eq!(BlockValuePools::new(CanonBlock::new(&block), &store).check(), Err(Error::NegativeValuePool(ValuePool::Sapling)));
+
+		// pools of the parent block are unknown
+		assert_eq!(BlockValuePools::new(CanonBlock::new(&block), &Store(None)).check(), Err(Error::MissingValuePools));
+	}
+
 	#[test]
 	fn test_coinbase_overspend_b419221() {
 		struct Store(HashMap<OutPoint, TransactionOutput>);
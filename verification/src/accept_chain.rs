@@ -1,7 +1,7 @@
 use rayon::prelude::{IntoParallelRefIterator, IndexedParallelIterator, ParallelIterator};
 use storage::{
 	DuplexTransactionOutputProvider, TransactionOutputProvider, TransactionMetaProvider,
-	BlockHeaderProvider, TreeStateProvider, NullifierTracker,
+	BlockHeaderProvider, TreeStateProvider, NullifierTracker, ValuePoolsProvider,
 };
 use network::ConsensusParams;
 use error::Error;
@@ -10,6 +10,8 @@ use accept_block::BlockAcceptor;
 use accept_header::HeaderAcceptor;
 use accept_transaction::TransactionAcceptor;
 use deployments::BlockDeployments;
+use signature_cache::SignatureCache;
+use script_cache::ScriptCache;
 use VerificationLevel;
 
 pub struct ChainAcceptor<'a> {
@@ -24,6 +26,7 @@ impl<'a> ChainAcceptor<'a> {
 		tx_meta_provider: &'a TransactionMetaProvider,
 		header_provider: &'a BlockHeaderProvider,
 		tree_state_provider: &'a TreeStateProvider,
+		value_pools_provider: &'a ValuePoolsProvider,
 		nullifier_tracker: &'a NullifierTracker,
 		consensus: &'a ConsensusParams,
 		verification_level: VerificationLevel,
@@ -31,6 +34,8 @@ impl<'a> ChainAcceptor<'a> {
 		height: u32,
 		time: u32,
 		deployments: &'a BlockDeployments,
+		signature_cache: &'a SignatureCache,
+		script_cache: &'a ScriptCache,
 	) -> Self {
 		trace!(target: "verification", "Block verification {}", block.hash().to_reversed_str());
 		let output_store = DuplexTransactionOutputProvider::new(tx_out_provider, block.raw());
@@ -39,6 +44,7 @@ impl<'a> ChainAcceptor<'a> {
 			block: BlockAcceptor::new(
 				tx_out_provider,
 				tree_state_provider,
+				value_pools_provider,
 				consensus,
 				block,
 				height,
@@ -61,6 +67,8 @@ impl<'a> ChainAcceptor<'a> {
 						tx_index,
 						deployments,
 						tree_state_provider,
+						signature_cache,
+						script_cache,
 				))
 				.collect(),
 		}
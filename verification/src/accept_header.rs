@@ -10,6 +10,7 @@ pub struct HeaderAcceptor<'a> {
 	pub version: HeaderVersion<'a>,
 	pub work: HeaderWork<'a>,
 	pub median_timestamp: HeaderMedianTimestamp<'a>,
+	pub checkpoint: HeaderCheckpoint<'a>,
 }
 
 impl<'a> HeaderAcceptor<'a> {
@@ -26,6 +27,7 @@ impl<'a> HeaderAcceptor<'a> {
 			work: HeaderWork::new(header, store, height, time, consensus),
 			median_timestamp: HeaderMedianTimestamp::new(header, store, csv_active),
 			version: HeaderVersion::new(header, height, consensus),
+			checkpoint: HeaderCheckpoint::new(header, height, consensus),
 		}
 	}
 
@@ -33,6 +35,7 @@ impl<'a> HeaderAcceptor<'a> {
 		self.version.check()?;
 		self.work.check()?;
 		self.median_timestamp.check()?;
+		self.checkpoint.check()?;
 		Ok(())
 	}
 }
@@ -116,3 +119,54 @@ impl<'a> HeaderMedianTimestamp<'a> {
 		}
 	}
 }
+
+/// Block at checkpointed height must have checkpointed hash.
+pub struct HeaderCheckpoint<'a> {
+	header: CanonHeader<'a>,
+	height: u32,
+	consensus: &'a ConsensusParams,
+}
+
+impl<'a> HeaderCheckpoint<'a> {
+	fn new(header: CanonHeader<'a>, height: u32, consensus: &'a ConsensusParams) -> Self {
+		HeaderCheckpoint {
+			header: header,
+			height: height,
+			consensus: consensus,
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		match self.consensus.network.checkpoint(self.height) {
+			Some(ref checkpoint_hash) if checkpoint_hash != &self.header.hash => Err(Error::CheckpointMismatch {
+				expected: checkpoint_hash.clone(),
+				actual: self.header.hash.clone(),
+			}),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use chain::IndexedBlockHeader;
+	use network::{ConsensusParams, Network};
+	use {Error, CanonHeader};
+	use super::HeaderCheckpoint;
+
+	#[test]
+	fn test_header_checkpoint() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let genesis: IndexedBlockHeader = test_data::genesis().block_header.into();
+		let other: IndexedBlockHeader = test_data::block_builder().header().build().build().block_header.into();
+
+		assert_eq!(HeaderCheckpoint::new(CanonHeader::new(&genesis), 0, &consensus).check(), Ok(()));
+		assert_eq!(HeaderCheckpoint::new(CanonHeader::new(&other), 1, &consensus).check(), Ok(()));
+		assert_eq!(HeaderCheckpoint::new(CanonHeader::new(&other), 0, &consensus).check(), Err(Error::CheckpointMismatch {
+			expected: genesis.hash.clone(),
+			actual: other.hash.clone(),
+		}));
+	}
+}
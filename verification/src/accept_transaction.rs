@@ -1,19 +1,24 @@
-use ser::Serializable;
+use rayon::prelude::{IntoParallelIterator, IndexedParallelIterator, ParallelIterator};
 use crypto::Groth16VerifyingKey;
 use storage::{EpochRef, NullifierTracker, EpochTag, TransactionMetaProvider, TransactionOutputProvider,
-	DuplexTransactionOutputProvider, TreeStateProvider};
+	DuplexTransactionOutputProvider, TreeStateProvider, BlockHeaderProvider};
 use network::{ConsensusParams};
 use script::{Script, verify_script, VerificationFlags, TransactionSignatureChecker, TransactionInputSigner, SighashBase};
 use deployments::BlockDeployments;
 use sapling::accept_sapling;
 use sigops::transaction_sigops;
 use canon::CanonTransaction;
-use chain::{OVERWINTER_TX_VERSION, SAPLING_TX_VERSION, OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID};
-use constants::COINBASE_MATURITY;
+use chain::{OVERWINTER_TX_VERSION, SAPLING_TX_VERSION, NU5_TX_VERSION, OVERWINTER_TX_VERSION_GROUP_ID,
+	SAPLING_TX_VERSION_GROUP_ID, NU5_TX_VERSION_GROUP_ID};
+use constants::{COINBASE_MATURITY, MAX_TRANSACTION_EXPIRY_HEIGHT};
 use error::TransactionError;
 use primitives::hash::H256;
+use primitives::timings::{self, Stage};
 use {checked_transaction_fee, VerificationLevel};
 use tree_cache::TreeCache;
+use signature_cache::{SignatureCache, CachedSignatureChecker};
+use script_cache::{ScriptCache, ScriptCacheKey};
+use timestamp::median_timestamp_at;
 
 pub struct TransactionAcceptor<'a> {
 	pub version: TransactionVersion<'a>,
@@ -22,10 +27,12 @@ pub struct TransactionAcceptor<'a> {
 	pub bip30: TransactionBip30<'a>,
 	pub missing_inputs: TransactionMissingInputs<'a>,
 	pub maturity: TransactionMaturity<'a>,
+	pub coinbase_spend: TransactionCoinbaseSpend<'a>,
 	pub double_spent: TransactionDoubleSpend<'a>,
 	pub eval: TransactionEval<'a>,
 	pub join_split: JoinSplitVerification<'a>,
 	pub sapling: SaplingVerification<'a>,
+	pub orchard_nullifiers: OrchardNullifiers<'a>,
 }
 
 impl<'a> TransactionAcceptor<'a> {
@@ -44,6 +51,8 @@ impl<'a> TransactionAcceptor<'a> {
 		transaction_index: usize,
 		deployments: &'a BlockDeployments<'a>,
 		tree_state_provider: &'a TreeStateProvider,
+		signature_cache: &'a SignatureCache,
+		script_cache: &'a ScriptCache,
 	) -> Self {
 		trace!(target: "verification", "Tx verification {}", transaction.hash.to_reversed_str());
 		TransactionAcceptor {
@@ -53,15 +62,19 @@ impl<'a> TransactionAcceptor<'a> {
 			bip30: TransactionBip30::new_for_sync(transaction, meta_store),
 			missing_inputs: TransactionMissingInputs::new(transaction, output_store, transaction_index),
 			maturity: TransactionMaturity::new(transaction, meta_store, height),
+			coinbase_spend: TransactionCoinbaseSpend::new(transaction, meta_store, consensus),
 			double_spent: TransactionDoubleSpend::new(transaction, output_store),
-			eval: TransactionEval::new(transaction, output_store, consensus, verification_level, height, time, deployments),
-			join_split: JoinSplitVerification::new(consensus, transaction, nullifier_tracker, tree_state_provider),
+			eval: TransactionEval::new(transaction, output_store, consensus, verification_level, height, time, deployments, signature_cache, script_cache),
+			join_split: JoinSplitVerification::new(consensus, transaction, nullifier_tracker, tree_state_provider, verification_level),
 			sapling: SaplingVerification::new(
 				nullifier_tracker,
+				tree_state_provider,
 				consensus.sapling_spend_verifying_key,
 				consensus.sapling_output_verifying_key,
 				transaction,
+				verification_level,
 			),
+			orchard_nullifiers: OrchardNullifiers::new(nullifier_tracker, transaction),
 		}
 	}
 
@@ -72,6 +85,7 @@ impl<'a> TransactionAcceptor<'a> {
 		self.bip30.check()?;
 		self.missing_inputs.check()?;
 		self.maturity.check()?;
+		self.coinbase_spend.check()?;
 		self.double_spent.check()?;
 
 		// to make sure we're using the sighash-cache, let's make all sighash-related
@@ -79,6 +93,7 @@ impl<'a> TransactionAcceptor<'a> {
 		let sighash = self.eval.check()?;
 		self.join_split.check(sighash)?;
 		self.sapling.check(sighash)?;
+		self.orchard_nullifiers.check()?;
 
 		Ok(())
 	}
@@ -88,14 +103,17 @@ pub struct MemoryPoolTransactionAcceptor<'a> {
 	pub version: TransactionVersion<'a>,
 	pub size: TransactionSize<'a>,
 	pub expiry: TransactionExpiry<'a>,
+	pub finality: TransactionFinality<'a>,
 	pub missing_inputs: TransactionMissingInputs<'a>,
 	pub maturity: TransactionMaturity<'a>,
+	pub coinbase_spend: TransactionCoinbaseSpend<'a>,
 	pub overspent: TransactionOverspent<'a>,
 	pub sigops: TransactionSigops<'a>,
 	pub double_spent: TransactionDoubleSpend<'a>,
 	pub eval: TransactionEval<'a>,
 	pub join_split: JoinSplitVerification<'a>,
 	pub sapling: SaplingVerification<'a>,
+	pub orchard_nullifiers: OrchardNullifiers<'a>,
 }
 
 impl<'a> MemoryPoolTransactionAcceptor<'a> {
@@ -110,28 +128,36 @@ impl<'a> MemoryPoolTransactionAcceptor<'a> {
 		height: u32,
 		time: u32,
 		deployments: &'a BlockDeployments<'a>,
+		header_provider: &'a BlockHeaderProvider,
 		tree_state_provider: &'a TreeStateProvider,
+		signature_cache: &'a SignatureCache,
+		script_cache: &'a ScriptCache,
 	) -> Self {
 		trace!(target: "verification", "Mempool-Tx verification {}", transaction.hash.to_reversed_str());
 		let transaction_index = 0;
-		let max_block_sigops = consensus.max_block_sigops();
+		let max_block_sigops = consensus.block_limits.max_block_sigops;
 		MemoryPoolTransactionAcceptor {
 			version: TransactionVersion::new(transaction, consensus, height),
 			size: TransactionSize::new(transaction, consensus, height),
 			expiry: TransactionExpiry::new(transaction, consensus, height),
+			finality: TransactionFinality::new(transaction, header_provider, height),
 			missing_inputs: TransactionMissingInputs::new(transaction, output_store, transaction_index),
 			maturity: TransactionMaturity::new(transaction, meta_store, height),
+			coinbase_spend: TransactionCoinbaseSpend::new(transaction, meta_store, consensus),
 			overspent: TransactionOverspent::new(transaction, output_store),
 			sigops: TransactionSigops::new(transaction, output_store, consensus, max_block_sigops, time),
 			double_spent: TransactionDoubleSpend::new(transaction, output_store),
-			eval: TransactionEval::new(transaction, output_store, consensus, VerificationLevel::FULL, height, time, deployments),
-			join_split: JoinSplitVerification::new(consensus, transaction, nullifier_tracker, tree_state_provider),
+			eval: TransactionEval::new(transaction, output_store, consensus, VerificationLevel::FULL, height, time, deployments, signature_cache, script_cache),
+			join_split: JoinSplitVerification::new(consensus, transaction, nullifier_tracker, tree_state_provider, VerificationLevel::FULL),
 			sapling: SaplingVerification::new(
 				nullifier_tracker,
+				tree_state_provider,
 				consensus.sapling_spend_verifying_key,
 				consensus.sapling_output_verifying_key,
 				transaction,
+				VerificationLevel::FULL,
 			),
+			orchard_nullifiers: OrchardNullifiers::new(nullifier_tracker, transaction),
 		}
 	}
 
@@ -141,8 +167,10 @@ impl<'a> MemoryPoolTransactionAcceptor<'a> {
 		self.version.check()?;
 		self.size.check()?;
 		self.expiry.check()?;
+		self.finality.check()?;
 		self.missing_inputs.check()?;
 		self.maturity.check()?;
+		self.coinbase_spend.check()?;
 		self.overspent.check()?;
 		self.sigops.check()?;
 		self.double_spent.check()?;
@@ -152,6 +180,7 @@ impl<'a> MemoryPoolTransactionAcceptor<'a> {
 		let sighash = self.eval.check()?;
 		self.join_split.check(sighash)?;
 		self.sapling.check(sighash)?;
+		self.orchard_nullifiers.check()?;
 
 		Ok(())
 	}
@@ -253,6 +282,70 @@ impl<'a> TransactionMaturity<'a> {
 	}
 }
 
+/// Check that transparent coinbase outputs are only spent by transactions without transparent outputs
+/// (i.e. coinbase must be shielded before it could be spent to transparent address).
+pub struct TransactionCoinbaseSpend<'a> {
+	transaction: CanonTransaction<'a>,
+	store: &'a TransactionMetaProvider,
+	coinbase_must_be_shielded: bool,
+}
+
+impl<'a> TransactionCoinbaseSpend<'a> {
+	fn new(transaction: CanonTransaction<'a>, store: &'a TransactionMetaProvider, consensus: &'a ConsensusParams) -> Self {
+		TransactionCoinbaseSpend {
+			transaction: transaction,
+			store: store,
+			coinbase_must_be_shielded: consensus.coinbase_must_be_shielded,
+		}
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		if !self.coinbase_must_be_shielded || self.transaction.raw.outputs.is_empty() {
+			return Ok(());
+		}
+
+		let coinbase_spend = self.transaction.raw.inputs.iter()
+			.position(|input| match self.store.transaction_meta(&input.previous_output.hash) {
+				Some(ref meta) => meta.is_coinbase(),
+				None => false,
+			});
+
+		match coinbase_spend {
+			Some(index) => Err(TransactionError::UnshieldedCoinbaseSpend(index)),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Check that transaction is final in the next block.
+///
+/// Like zcashd (which has BIP113 in its standard lock-time verification flags), lock time
+/// of memory pool transactions is compared to the median time past of the chain, instead of
+/// the current time. Blocks are still checked against their own time.
+pub struct TransactionFinality<'a> {
+	transaction: CanonTransaction<'a>,
+	height: u32,
+	time_cutoff: u32,
+}
+
+impl<'a> TransactionFinality<'a> {
+	fn new(transaction: CanonTransaction<'a>, headers: &'a BlockHeaderProvider, height: u32) -> Self {
+		TransactionFinality {
+			transaction: transaction,
+			height: height,
+			time_cutoff: median_timestamp_at(height.saturating_sub(1), headers),
+		}
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		if self.transaction.raw.is_final_in_block(self.height, self.time_cutoff) {
+			Ok(())
+		} else {
+			Err(TransactionError::NonFinal)
+		}
+	}
+}
+
 pub struct TransactionOverspent<'a> {
 	transaction: CanonTransaction<'a>,
 	store: DuplexTransactionOutputProvider<'a>,
@@ -306,6 +399,10 @@ impl<'a> TransactionSigops<'a> {
 	}
 }
 
+/// Minimal number of inputs, verified by the single job of the verification pool.
+/// Smaller jobs aren't worth the cost of cloning the signer.
+const MIN_INPUTS_PER_JOB: usize = 8;
+
 pub struct TransactionEval<'a> {
 	transaction: CanonTransaction<'a>,
 	store: DuplexTransactionOutputProvider<'a>,
@@ -319,6 +416,8 @@ pub struct TransactionEval<'a> {
 	verify_sigpushonly: bool,
 	verify_cleanstack: bool,
 	consensus_branch_id: u32,
+	signature_cache: &'a SignatureCache,
+	script_cache: &'a ScriptCache,
 }
 
 impl<'a> TransactionEval<'a> {
@@ -330,6 +429,8 @@ impl<'a> TransactionEval<'a> {
 		height: u32,
 		time: u32,
 		deployments: &'a BlockDeployments,
+		signature_cache: &'a SignatureCache,
+		script_cache: &'a ScriptCache,
 	) -> Self {
 		let verify_p2sh = time >= params.bip16_time;
 		let verify_strictenc = false;
@@ -355,6 +456,8 @@ impl<'a> TransactionEval<'a> {
 			verify_sigpushonly: verify_sigpushonly,
 			verify_cleanstack: verify_cleanstack,
 			consensus_branch_id: consensus_branch_id,
+			signature_cache: signature_cache,
+			script_cache: script_cache,
 		}
 	}
 
@@ -372,9 +475,11 @@ impl<'a> TransactionEval<'a> {
 		};
 
 		// generate sighash that is not associated with a transparent input
+		// (for v5 transactions it is the same as the transaction id - see ZIP 244)
 		let require_no_input_sighash = self.transaction.raw.join_split.is_some()
 			|| self.transaction.raw.sapling.is_some();
 		let no_input_sighash = match require_no_input_sighash {
+			true if self.transaction.raw.is_v5() => self.transaction.hash.clone(),
 			true => checker.signer.signature_hash(
 				&mut checker.cache,
 				None,
@@ -394,31 +499,76 @@ impl<'a> TransactionEval<'a> {
 			return Ok(no_input_sighash);
 		}
 
-		for (index, input) in self.transaction.raw.inputs.iter().enumerate() {
-			let output = self.store.transaction_output(&input.previous_output, usize::max_value())
-				.ok_or_else(|| TransactionError::UnknownReference(input.previous_output.hash.clone()))?;
-
-			checker.input_index = index;
-			checker.input_amount = output.value;
+		// inputs are verified concurrently, every job has its own checker (&& sighash cache);
+		// error of the input with the lowest index is returned, regardless of jobs completion order
+		let _timer = timings::start(Stage::ScriptVerification);
+		let new_checker = || CachedSignatureChecker {
+			checker: TransactionSignatureChecker {
+				signer: checker.signer.clone(),
+				input_index: 0,
+				input_amount: 0,
+				consensus_branch_id: self.consensus_branch_id,
+				cache: Default::default(),
+			},
+			cache: self.signature_cache,
+		};
+		(0..self.transaction.raw.inputs.len()).into_par_iter()
+			.with_min_len(MIN_INPUTS_PER_JOB)
+			.fold(|| (new_checker(), Ok(())), |(mut checker, result), index| {
+				let result = result.and_then(|_| self.check_input(&mut checker, index));
+				(checker, result)
+			})
+			.map(|(_, result)| result)
+			.reduce(|| Ok(()), |acc, check| acc.and(check))?;
 
-			let input: Script = input.script_sig.clone().into();
-			let output: Script = output.script_pubkey.into();
+		Ok(no_input_sighash)
+	}
 
-			let flags = VerificationFlags::default()
-				.verify_p2sh(self.verify_p2sh)
-				.verify_strictenc(self.verify_strictenc)
-				.verify_locktime(self.verify_locktime)
-				.verify_checksequence(self.verify_checksequence)
-				.verify_dersig(self.verify_dersig)
-				.verify_nulldummy(self.verify_nulldummy)
-				.verify_sigpushonly(self.verify_sigpushonly)
-				.verify_cleanstack(self.verify_cleanstack);
+	fn check_input(&self, checker: &mut CachedSignatureChecker, index: usize) -> Result<(), TransactionError> {
+		let input = &self.transaction.raw.inputs[index];
+		let output = self.store.transaction_output(&input.previous_output, usize::max_value())
+			.ok_or_else(|| TransactionError::UnknownReference(input.previous_output.hash.clone()))?;
 
-			verify_script(&input, &output, &flags, &mut checker)
-				.map_err(|e| TransactionError::Signature(index, e))?;
+		// inputs of memory pool transactions are verified with the same flags, when transaction is mined
+		let cache_key = ScriptCacheKey::new(&self.transaction.hash, index, &input.script_sig, self.flags_bits(), self.consensus_branch_id);
+		if self.script_cache.contains(&cache_key) {
+			return Ok(());
 		}
 
-		Ok(no_input_sighash)
+		checker.checker.input_index = index;
+		checker.checker.input_amount = output.value;
+
+		let input: Script = input.script_sig.clone().into();
+		let output: Script = output.script_pubkey.into();
+
+		let flags = VerificationFlags::default()
+			.verify_p2sh(self.verify_p2sh)
+			.verify_strictenc(self.verify_strictenc)
+			.verify_locktime(self.verify_locktime)
+			.verify_checksequence(self.verify_checksequence)
+			.verify_dersig(self.verify_dersig)
+			.verify_nulldummy(self.verify_nulldummy)
+			.verify_sigpushonly(self.verify_sigpushonly)
+			.verify_cleanstack(self.verify_cleanstack);
+
+		verify_script(&input, &output, &flags, checker)
+			.map_err(|e| TransactionError::Signature(index, e))?;
+		self.script_cache.insert(cache_key);
+		Ok(())
+	}
+
+	/// Script verification flags, packed into integer.
+	fn flags_bits(&self) -> u32 {
+		[
+			self.verify_p2sh,
+			self.verify_strictenc,
+			self.verify_locktime,
+			self.verify_checksequence,
+			self.verify_dersig,
+			self.verify_nulldummy,
+			self.verify_sigpushonly,
+			self.verify_cleanstack,
+		].iter().enumerate().fold(0, |bits, (index, flag)| bits | ((*flag as u32) << index))
 	}
 }
 
@@ -467,7 +617,7 @@ impl<'a> TransactionSize<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let size = self.transaction.raw.serialized_size();
+		let size = self.transaction.raw.size();
 		if size > self.max_size {
 			Err(TransactionError::MaxSize)
 		} else {
@@ -476,10 +626,14 @@ impl<'a> TransactionSize<'a> {
 	}
 }
 
-/// Check that transaction isn't expired.
+/// Check that transaction isn't expired && its expiry height isn't too high.
+///
+/// Since NU5, expiry height of coinbase transaction must be equal to the block height.
+/// https://zips.z.cash/zip-0203
 pub struct TransactionExpiry<'a> {
 	transaction: CanonTransaction<'a>,
 	is_overwinter_active: bool,
+	is_nu5_active: bool,
 	height: u32,
 }
 
@@ -488,12 +642,24 @@ impl<'a> TransactionExpiry<'a> {
 		TransactionExpiry {
 			transaction,
 			is_overwinter_active: consensus.is_overwinter_active(height),
+			is_nu5_active: consensus.is_nu5_active(height),
 			height,
 		}
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
+		if self.is_nu5_active && self.transaction.raw.is_coinbase() && self.transaction.raw.expiry_height != self.height {
+			return Err(TransactionError::CoinbaseExpiryHeight {
+				expected: self.height,
+				actual: self.transaction.raw.expiry_height,
+			});
+		}
+
 		if self.is_overwinter_active {
+			if self.transaction.raw.overwintered && self.transaction.raw.expiry_height > MAX_TRANSACTION_EXPIRY_HEIGHT {
+				return Err(TransactionError::ExpiryHeightTooHigh);
+			}
+
 			if self.transaction.raw.expiry_height != 0 && !self.transaction.raw.is_coinbase() {
 				if self.height > self.transaction.raw.expiry_height {
 					return Err(TransactionError::Expired);
@@ -510,6 +676,8 @@ pub struct TransactionVersion<'a> {
 	transaction: CanonTransaction<'a>,
 	is_overwinter_active: bool,
 	is_sapling_active: bool,
+	is_nu5_active: bool,
+	consensus_branch_id: u32,
 }
 
 impl<'a> TransactionVersion<'a> {
@@ -518,6 +686,8 @@ impl<'a> TransactionVersion<'a> {
 			transaction,
 			is_overwinter_active: consensus.is_overwinter_active(height),
 			is_sapling_active: consensus.is_sapling_active(height),
+			is_nu5_active: consensus.is_nu5_active(height),
+			consensus_branch_id: consensus.consensus_branch_id(height),
 		}
 	}
 
@@ -529,6 +699,19 @@ impl<'a> TransactionVersion<'a> {
 			return Err(TransactionError::InvalidOverwintered);
 		}
 
+		// when nu5 is active, v5 transactions are also allowed
+		// v5 transaction must commit to the consensus branch id of the block
+		if self.is_nu5_active && self.transaction.raw.version_group_id == NU5_TX_VERSION_GROUP_ID {
+			if self.transaction.raw.version != NU5_TX_VERSION {
+				return Err(TransactionError::InvalidVersion);
+			}
+			if self.transaction.raw.consensus_branch_id != self.consensus_branch_id {
+				return Err(TransactionError::InvalidConsensusBranchId);
+			}
+
+			return Ok(());
+		}
+
 		if self.is_overwinter_active {
 			// when sapling is active, version group id must be set to sapling
 			// when sapling is inactive, version group id must be set to overwinter
@@ -561,14 +744,23 @@ pub struct JoinSplitProof<'a> {
 	transaction: CanonTransaction<'a>,
 	consensus_params: &'a ConsensusParams,
 	tree_state_provider: &'a TreeStateProvider,
+	verify_proofs: bool,
 }
 
 impl<'a> JoinSplitProof<'a> {
-	fn new(transaction: CanonTransaction<'a>, consensus_params: &'a ConsensusParams, tree_state_provider: &'a TreeStateProvider) -> Self {
+	fn new(
+		transaction: CanonTransaction<'a>,
+		consensus_params: &'a ConsensusParams,
+		tree_state_provider: &'a TreeStateProvider,
+		verification_level: VerificationLevel,
+	) -> Self {
 		JoinSplitProof {
 			transaction,
 			consensus_params,
 			tree_state_provider,
+			verify_proofs: !verification_level.intersects(
+				VerificationLevel::HEADER | VerificationLevel::NO_VERIFICATION | VerificationLevel::NO_SPROUT_PROOFS
+			),
 		}
 	}
 
@@ -579,12 +771,15 @@ impl<'a> JoinSplitProof<'a> {
 			let mut index = 0;
 			let mut tree_cache = TreeCache::new(self.tree_state_provider);
 			for desc in join_split.descriptions.iter() {
-				sprout::verify(
-					&desc,
-					&join_split,
-					&self.consensus_params.joinsplit_verification_key,
-					&self.consensus_params.joinsplit_groth16_verification_key,
-				).map_err(|_e| TransactionError::InvalidJoinSplit(index))?;
+				if self.verify_proofs {
+					let _timer = timings::start(Stage::ProofVerification);
+					sprout::verify(
+						&desc,
+						&join_split,
+						&self.consensus_params.joinsplit_verification_key,
+						&self.consensus_params.joinsplit_groth16_verification_key,
+					).map_err(|_e| TransactionError::InvalidJoinSplit(index))?;
+				}
 
 				tree_cache.continue_root(&desc.anchor.into(), &desc.commitments)?;
 
@@ -608,15 +803,9 @@ impl<'a> JoinSplitNullifiers<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		if let Some(ref join_split) = self.transaction.raw.join_split {
-			for description in join_split.descriptions.iter() {
-				for nullifier in &description.nullifiers[..] {
-					let check = EpochRef::new(EpochTag::Sprout, H256::from(&nullifier[..]));
-
-					if self.tracker.contains_nullifier(check) {
-						return Err(TransactionError::JoinSplitDeclared(*check.hash()))
-					}
-				}
+		for nullifier in self.transaction.raw.sprout_nullifiers() {
+			if self.tracker.contains_nullifier(EpochRef::new(EpochTag::Sprout, nullifier)) {
+				return Err(TransactionError::JoinSplitDeclared(nullifier))
 			}
 		}
 
@@ -636,11 +825,12 @@ impl<'a> JoinSplitVerification<'a> {
 		consensus_params: &'a ConsensusParams,
 		transaction: CanonTransaction<'a>,
 		tracker: &'a NullifierTracker,
-		tree_state_provider: &'a TreeStateProvider)
+		tree_state_provider: &'a TreeStateProvider,
+		verification_level: VerificationLevel)
 		-> Self
 	{
 		JoinSplitVerification {
-			proof: JoinSplitProof::new(transaction, consensus_params, tree_state_provider),
+			proof: JoinSplitProof::new(transaction, consensus_params, tree_state_provider, verification_level),
 			nullifiers: JoinSplitNullifiers::new(tracker, transaction),
 			transaction: transaction,
 		}
@@ -668,13 +858,56 @@ impl<'a> SaplingNullifiers<'a> {
 		SaplingNullifiers { tracker: tracker, transaction: transaction }
 	}
 
+	fn check(&self) -> Result<(), TransactionError> {
+		for nullifier in self.transaction.raw.sapling_nullifiers() {
+			if self.tracker.contains_nullifier(EpochRef::new(EpochTag::Sapling, nullifier)) {
+				return Err(TransactionError::SaplingDeclared(nullifier))
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Check if Orchard nullifiers are unique
+pub struct OrchardNullifiers<'a> {
+	tracker: &'a NullifierTracker,
+	transaction: CanonTransaction<'a>,
+}
+
+impl<'a> OrchardNullifiers<'a> {
+	fn new(tracker: &'a NullifierTracker, transaction: CanonTransaction<'a>) -> Self {
+		OrchardNullifiers { tracker: tracker, transaction: transaction }
+	}
+
+	fn check(&self) -> Result<(), TransactionError> {
+		for nullifier in self.transaction.raw.orchard_nullifiers() {
+			if self.tracker.contains_nullifier(EpochRef::new(EpochTag::Orchard, nullifier)) {
+				return Err(TransactionError::OrchardDeclared(nullifier))
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Check that every Sapling spend refers to the root of the Sapling note commitment tree of some canon block.
+pub struct SaplingAnchors<'a> {
+	tree_state_provider: &'a TreeStateProvider,
+	transaction: CanonTransaction<'a>,
+}
+
+impl<'a> SaplingAnchors<'a> {
+	fn new(tree_state_provider: &'a TreeStateProvider, transaction: CanonTransaction<'a>) -> Self {
+		SaplingAnchors { tree_state_provider: tree_state_provider, transaction: transaction }
+	}
+
 	fn check(&self) -> Result<(), TransactionError> {
 		if let Some(ref sapling) = self.transaction.raw.sapling {
 			for spend in &sapling.spends {
-				let check = EpochRef::new(EpochTag::Sapling, H256::from(&spend.nullifier[..]));
-
-				if self.tracker.contains_nullifier(check) {
-					return Err(TransactionError::SaplingDeclared(*check.hash()))
+				let anchor = H256::from(&spend.anchor[..]);
+				if self.tree_state_provider.sapling_anchor_height(&anchor).is_none() {
+					return Err(TransactionError::UnknownSaplingAnchor(anchor));
 				}
 			}
 		}
@@ -683,12 +916,12 @@ impl<'a> SaplingNullifiers<'a> {
 	}
 }
 
-
 /// Checks that sapling signatures/proofs are valid.
 pub struct SaplingProof<'a> {
 	spend_vk: &'a Groth16VerifyingKey,
 	output_vk: &'a Groth16VerifyingKey,
 	transaction: CanonTransaction<'a>,
+	verify_proofs: bool,
 }
 
 impl<'a> SaplingProof<'a> {
@@ -696,16 +929,23 @@ impl<'a> SaplingProof<'a> {
 		spend_vk: &'a Groth16VerifyingKey,
 		output_vk: &'a Groth16VerifyingKey,
 		transaction: CanonTransaction<'a>,
+		verification_level: VerificationLevel,
 	) -> Self {
 		SaplingProof {
 			spend_vk,
 			output_vk,
 			transaction: transaction,
+			verify_proofs: !verification_level.intersects(VerificationLevel::HEADER | VerificationLevel::NO_VERIFICATION),
 		}
 	}
 
 	fn check(&self, sighash: H256) -> Result<(), TransactionError> {
+		if !self.verify_proofs {
+			return Ok(());
+		}
+
 		if let Some(sapling) = self.transaction.raw.sapling.as_ref() {
+			let _timer = timings::start(Stage::ProofVerification);
 			accept_sapling(self.spend_vk, self.output_vk, &sighash, sapling)
 				.map_err(|_| TransactionError::InvalidSapling)?;
 		}
@@ -718,25 +958,30 @@ impl<'a> SaplingProof<'a> {
 pub struct SaplingVerification<'a> {
 	proof: SaplingProof<'a>,
 	nullifiers: SaplingNullifiers<'a>,
+	anchors: SaplingAnchors<'a>,
 }
 
 impl<'a> SaplingVerification<'a> {
 	pub fn new(
 		tracker: &'a NullifierTracker,
+		tree_state_provider: &'a TreeStateProvider,
 		spend_vk: &'a Groth16VerifyingKey,
 		output_vk: &'a Groth16VerifyingKey,
-		transaction: CanonTransaction<'a>
+		transaction: CanonTransaction<'a>,
+		verification_level: VerificationLevel,
 	) -> Self
 	{
 		SaplingVerification {
-			proof: SaplingProof::new(spend_vk, output_vk, transaction),
+			proof: SaplingProof::new(spend_vk, output_vk, transaction, verification_level),
 			nullifiers: SaplingNullifiers::new(tracker, transaction),
+			anchors: SaplingAnchors::new(tree_state_provider, transaction),
 		}
 	}
 
 	pub fn check(&self, sighash: H256) -> Result<(), TransactionError> {
 		self.proof.check(sighash)?;
-		self.nullifiers.check()
+		self.nullifiers.check()?;
+		self.anchors.check()
 	}
 }
 
@@ -745,9 +990,11 @@ mod tests {
 	extern crate test_data;
 
 
-	use chain::{BTC_TX_VERSION, Transaction, Sapling};
+	use chain::{BTC_TX_VERSION, Transaction, IndexedTransaction, Sapling, SaplingSpendDescription,
+		SaplingOutputDescription, Orchard, OrchardAction, JoinSplit, JoinSplitDescription, IndexedBlock, TransactionInput, TransactionOutput, OutPoint};
 	use db::BlockChainDatabase;
-	use network::{Network, ConsensusParams};
+	use storage::{SproutTreeState, SaplingTreeState};
+	use network::{Network, NetworkUpgrade, ConsensusParams};
 	use script::{Script, VerificationFlags, TransactionSignatureChecker, TransactionInputSigner, verify_script};
 	use super::*;
 
@@ -781,6 +1028,32 @@ mod tests {
 		assert_eq!(verify_script(&input_script, &output_script, &flags, &mut checker), Ok(()));
 	}
 
+	#[test]
+	fn join_split_proof_is_not_verified_when_skipped() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+
+		// default proof is invalid
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_join_split(JoinSplit {
+			descriptions: vec![JoinSplitDescription {
+				anchor: SproutTreeState::empty_root().take(),
+				..Default::default()
+			}],
+			..Default::default()
+		}).into();
+
+		assert_eq!(
+			JoinSplitProof::new(CanonTransaction::new(&tx), &consensus, &storage, VerificationLevel::FULL).check(),
+			Err(TransactionError::InvalidJoinSplit(0))
+		);
+		assert_eq!(
+			JoinSplitProof::new(
+				CanonTransaction::new(&tx), &consensus, &storage, VerificationLevel::FULL | VerificationLevel::NO_SPROUT_PROOFS
+			).check(),
+			Ok(())
+		);
+	}
+
 	#[test]
 	fn sapling_nullifiers_works() {
 		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
@@ -811,14 +1084,121 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn orchard_nullifiers_works() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+
+		let tx = Transaction {
+			orchard: Some(Orchard {
+				actions: vec![OrchardAction { nullifier: [1; 32], ..Default::default() }],
+				..Default::default()
+			}),
+			..Default::default()
+		};
+		let block = test_data::block_builder()
+			.header().parent(test_data::genesis().hash()).build()
+			.transaction().coinbase().build()
+			.with_transaction(tx.clone())
+			.build();
+		let tx = tx.into();
+		let block_hash = block.hash();
+
+		// when nullifier is not in the db
+		assert_eq!(OrchardNullifiers::new(&storage, CanonTransaction::new(&tx)).check(), Ok(()));
+
+		// insert nullifier into db
+		storage.insert(block.into()).unwrap();
+		storage.canonize(&block_hash).unwrap();
+
+		// when nullifier is in the db
+		assert_eq!(
+			OrchardNullifiers::new(&storage, CanonTransaction::new(&tx)).check(),
+			Err(TransactionError::OrchardDeclared([1u8; 32].into()))
+		);
+	}
+
+	#[test]
+	fn sapling_anchors_works() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+		let spend_tx = |anchor: H256| -> IndexedTransaction {
+			test_data::TransactionBuilder::with_sapling(Sapling {
+				spends: vec![SaplingSpendDescription { anchor: *anchor, ..Default::default() }],
+				..Default::default()
+			}).into()
+		};
+
+		// root of the empty tree is known since genesis
+		let tx = spend_tx(SaplingTreeState::empty_root());
+		assert_eq!(SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(), Ok(()));
+
+		// root of the tree after the output is appended is unknown until the block is canonized
+		let mut tree = SaplingTreeState::new();
+		tree.append([1; 32].into()).unwrap();
+		let tx = spend_tx(tree.root());
+		assert_eq!(
+			SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(),
+			Err(TransactionError::UnknownSaplingAnchor(tree.root()))
+		);
+
+		let block = test_data::block_builder()
+			.header().parent(test_data::genesis().hash()).build()
+			.transaction().coinbase().build()
+			.with_transaction(test_data::TransactionBuilder::with_sapling(Sapling {
+				outputs: vec![SaplingOutputDescription { note_commitment: [1; 32], ..Default::default() }],
+				..Default::default()
+			}).into())
+			.build();
+		let block_hash = block.hash();
+		storage.insert(block.into()).unwrap();
+		assert_eq!(
+			SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(),
+			Err(TransactionError::UnknownSaplingAnchor(tree.root()))
+		);
+
+		storage.canonize(&block_hash).unwrap();
+		assert_eq!(SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(), Ok(()));
+
+		// root of the decanonized block isn't valid anchor anymore
+		storage.decanonize().unwrap();
+		assert_eq!(
+			SaplingAnchors::new(&storage, CanonTransaction::new(&tx)).check(),
+			Err(TransactionError::UnknownSaplingAnchor(tree.root()))
+		);
+	}
+
+	#[test]
+	fn transaction_finality_uses_median_time_past() {
+		let genesis = test_data::genesis();
+		let b1 = test_data::block_builder()
+			.header().parent(genesis.hash()).time(genesis.block_header.time + 100).build()
+			.transaction().coinbase().output().value(50).build().build()
+			.build();
+		let median_time_past = b1.block_header.time;
+		let storage = BlockChainDatabase::init_test_chain(vec![genesis.into(), b1.into()]);
+		let locked_tx = |lock_time: u32| -> IndexedTransaction {
+			let mut tx: Transaction = test_data::TransactionBuilder::with_version(1).add_default_input(0).into();
+			tx.inputs[0].sequence = 0;
+			tx.lock_time = lock_time;
+			tx.into()
+		};
+
+		// lock time is compared to the median time past of the chain, not to the time of the next block
+		let tx = locked_tx(median_time_past);
+		assert_eq!(TransactionFinality::new(CanonTransaction::new(&tx), &storage, 2).check(),
+			Err(TransactionError::NonFinal));
+		let tx = locked_tx(median_time_past - 1);
+		assert_eq!(TransactionFinality::new(CanonTransaction::new(&tx), &storage, 2).check(), Ok(()));
+	}
+
 	#[test]
 	fn transaction_expiry_works() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
+		let overwinter_height = consensus.upgrades.activation_height(NetworkUpgrade::Overwinter).unwrap();
 
 		// when overwinter isn't active, expiry height is ignored
 		let tx = test_data::TransactionBuilder::overwintered().set_expiry_height(1).into();
 		assert_eq!(TransactionExpiry::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height - 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height - 1
 		).check(), Ok(()));
 
 		// when overwinter is active && we check coinbase tx, expiry height is ignored
@@ -826,50 +1206,100 @@ mod tests {
 			.set_overwintered(true)
 			.set_expiry_height(1).into();
 		assert_eq!(TransactionExpiry::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
+		).check(), Ok(()));
+
+		// when nu5 is active, coinbase expiry height must be equal to the block height
+		let nu5_height = consensus.upgrades.activation_height(NetworkUpgrade::Nu5).unwrap();
+		let tx = test_data::TransactionBuilder::coinbase()
+			.set_overwintered(true)
+			.set_expiry_height(nu5_height).into();
+		assert_eq!(TransactionExpiry::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height
 		).check(), Ok(()));
+		assert_eq!(TransactionExpiry::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height + 1
+		).check(), Err(TransactionError::CoinbaseExpiryHeight { expected: nu5_height + 1, actual: nu5_height }));
+		let tx = test_data::TransactionBuilder::coinbase()
+			.set_overwintered(true).into();
+		assert_eq!(TransactionExpiry::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height
+		).check(), Err(TransactionError::CoinbaseExpiryHeight { expected: nu5_height, actual: 0 }));
 
 		// when overwinter is active && expiry height check passes
 		let tx = test_data::TransactionBuilder::overwintered()
-			.set_expiry_height(consensus.overwinter_height + 100).into();
+			.set_expiry_height(overwinter_height + 100).into();
 		assert_eq!(TransactionExpiry::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
 		).check(), Ok(()));
 
 		// when overwinter is active && expiry height check fails
 		let tx = test_data::TransactionBuilder::overwintered()
-			.set_expiry_height(consensus.overwinter_height + 1).into();
+			.set_expiry_height(overwinter_height + 1).into();
 		assert_eq!(TransactionExpiry::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 100
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 100
 		).check(), Err(TransactionError::Expired));
+
+		// when overwinter is active && expiry height is above the ZIP-203 limit
+		let tx = test_data::TransactionBuilder::overwintered()
+			.set_expiry_height(MAX_TRANSACTION_EXPIRY_HEIGHT).into();
+		assert_eq!(TransactionExpiry::new(
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
+		).check(), Ok(()));
+		let tx = test_data::TransactionBuilder::overwintered()
+			.set_expiry_height(MAX_TRANSACTION_EXPIRY_HEIGHT + 1).into();
+		assert_eq!(TransactionExpiry::new(
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
+		).check(), Err(TransactionError::ExpiryHeightTooHigh));
+	}
+
+	#[test]
+	fn transaction_coinbase_spend_works() {
+		let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into(), test_data::block_h1().into()]);
+		let coinbase = test_data::block_h1().transactions[0].clone();
+		let mainnet = ConsensusParams::new(Network::Mainnet);
+		let regtest = ConsensusParams::new(Network::Regtest);
+
+		// coinbase spend with transparent outputs
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_input(&coinbase, 0).set_output(10).into();
+		assert_eq!(TransactionCoinbaseSpend::new(CanonTransaction::new(&tx), &storage, &mainnet).check(),
+			Err(TransactionError::UnshieldedCoinbaseSpend(0)));
+		assert_eq!(TransactionCoinbaseSpend::new(CanonTransaction::new(&tx), &storage, &regtest).check(), Ok(()));
+
+		// coinbase spend without transparent outputs
+		let tx: IndexedTransaction = test_data::TransactionBuilder::with_input(&coinbase, 0).into();
+		assert_eq!(TransactionCoinbaseSpend::new(CanonTransaction::new(&tx), &storage, &mainnet).check(), Ok(()));
 	}
 
 	#[test]
 	fn transaction_version_works() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
+		let overwinter_height = consensus.upgrades.activation_height(NetworkUpgrade::Overwinter).unwrap();
+		let sapling_height = consensus.upgrades.activation_height(NetworkUpgrade::Sapling).unwrap();
+		let nu5_height = consensus.upgrades.activation_height(NetworkUpgrade::Nu5).unwrap();
 
 		// when overwinter is active, but transaction isn't overwintered
 		let tx = test_data::TransactionBuilder::default().into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
 		).check(), Err(TransactionError::InvalidOverwintered));
 
 		// when overwinter isn't active, but transaction is overwintered
 		let tx = test_data::TransactionBuilder::overwintered().into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height - 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height - 1
 		).check(), Err(TransactionError::InvalidOverwintered));
 
 		// when sapling is active, but version group id isn't set to sapling
 		let tx = test_data::TransactionBuilder::overwintered().into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.sapling_height + 1
+			CanonTransaction::new(&tx), &consensus, sapling_height + 1
 		).check(), Err(TransactionError::InvalidVersionGroup));
 
 		// when overwinter is active, but version group id isn't set to overwinter
 		let tx = test_data::TransactionBuilder::overwintered().into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
 		).check(), Err(TransactionError::InvalidVersionGroup));
 
 		// when sapling is active, but version is post-sapling
@@ -878,7 +1308,7 @@ mod tests {
 			.set_version(SAPLING_TX_VERSION + 1)
 			.into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.sapling_height + 1
+			CanonTransaction::new(&tx), &consensus, sapling_height + 1
 		).check(), Err(TransactionError::InvalidVersion));
 
 		// when overwinter is active, but version is post-overwinter
@@ -887,13 +1317,13 @@ mod tests {
 			.set_version(OVERWINTER_TX_VERSION + 1)
 			.into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
 		).check(), Err(TransactionError::InvalidVersion));
 
 		// sprout tx passes check
 		let tx = test_data::TransactionBuilder::default().set_version(BTC_TX_VERSION).into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height - 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height - 1
 		).check(), Ok(()));
 
 		// overwinter tx passes check
@@ -902,7 +1332,7 @@ mod tests {
 			.set_version_group_id(OVERWINTER_TX_VERSION_GROUP_ID)
 			.into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.overwinter_height + 1
+			CanonTransaction::new(&tx), &consensus, overwinter_height + 1
 		).check(), Ok(()));
 
 		// sapling tx passes check
@@ -911,7 +1341,81 @@ mod tests {
 			.set_version_group_id(SAPLING_TX_VERSION_GROUP_ID)
 			.into();
 		assert_eq!(TransactionVersion::new(
-			CanonTransaction::new(&tx), &consensus, consensus.sapling_height + 1
+			CanonTransaction::new(&tx), &consensus, sapling_height + 1
+		).check(), Ok(()));
+
+		// sapling tx passes check when nu5 is active
+		assert_eq!(TransactionVersion::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height + 1
+		).check(), Ok(()));
+
+		// when nu5 isn't active, but transaction is v5
+		let tx = test_data::TransactionBuilder::overwintered()
+			.set_version(NU5_TX_VERSION)
+			.set_version_group_id(NU5_TX_VERSION_GROUP_ID)
+			.set_consensus_branch_id(NetworkUpgrade::Nu5.branch_id())
+			.into();
+		assert_eq!(TransactionVersion::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height - 1
+		).check(), Err(TransactionError::InvalidVersionGroup));
+
+		// nu5 tx passes check
+		assert_eq!(TransactionVersion::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height + 1
 		).check(), Ok(()));
+
+		// when nu5 is active, but v5 transaction commits to other consensus branch id
+		let tx = test_data::TransactionBuilder::overwintered()
+			.set_version(NU5_TX_VERSION)
+			.set_version_group_id(NU5_TX_VERSION_GROUP_ID)
+			.set_consensus_branch_id(NetworkUpgrade::Canopy.branch_id())
+			.into();
+		assert_eq!(TransactionVersion::new(
+			CanonTransaction::new(&tx), &consensus, nu5_height + 1
+		).check(), Err(TransactionError::InvalidConsensusBranchId));
+	}
+
+	#[test]
+	fn transaction_eval_returns_error_of_the_first_invalid_input() {
+		// outputs #3 && #15 of the previous transaction can't be spent
+		let previous_transaction = Transaction {
+			outputs: (0..20).map(|index| TransactionOutput {
+				value: 1,
+				script_pubkey: if index == 3 || index == 15 { vec![0x00] } else { vec![0x51] }.into(),
+			}).collect(),
+			..Default::default()
+		};
+		let block: IndexedBlock = test_data::block_builder()
+			.with_transaction(previous_transaction)
+			.header().build()
+			.build()
+			.into();
+		let tx: IndexedTransaction = Transaction {
+			inputs: (0..20).map(|index| TransactionInput {
+				previous_output: OutPoint { hash: block.transactions[0].hash.clone(), index: index },
+				..Default::default()
+			}).collect(),
+			..Default::default()
+		}.into();
+
+		let eval = TransactionEval {
+			transaction: CanonTransaction::new(&tx),
+			store: DuplexTransactionOutputProvider::new(&block, &block),
+			verification_level: VerificationLevel::FULL,
+			verify_p2sh: false,
+			verify_strictenc: false,
+			verify_locktime: false,
+			verify_checksequence: false,
+			verify_dersig: false,
+			verify_nulldummy: false,
+			verify_sigpushonly: false,
+			verify_cleanstack: false,
+			consensus_branch_id: 0,
+			signature_cache: &SignatureCache::new(0),
+			script_cache: &ScriptCache::new(0),
+		};
+		for _ in 0..10 {
+			assert_matches!(eval.check(), Err(TransactionError::Signature(3, _)));
+		}
 	}
 }
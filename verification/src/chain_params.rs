@@ -0,0 +1,95 @@
+//! Self-test of the chain parameters, performed before node is started.
+
+use network::{ConsensusParams, Network};
+use equihash::verify_block_equihash_solution;
+use work::is_valid_proof_of_work_hash;
+
+/// Check that chain parameters are consistent: genesis block hashes to the expected value,
+/// it is valid with respect to the configured proof-of-work parameters and the network
+/// magic doesn't collide with the magic of other network.
+/// Returns human-readable description of the first problem found.
+pub fn verify_chain_params(consensus: &ConsensusParams) -> Result<(), String> {
+	let network = consensus.network;
+	let genesis = network.genesis_block();
+
+	if let Some(expected_hash) = network.checkpoint(0) {
+		if *genesis.hash() != expected_hash {
+			return Err(format!("Genesis block hash mismatch: expected {}, got {}",
+				expected_hash.to_reversed_str(), genesis.hash().to_reversed_str()));
+		}
+	}
+
+	let merkle_root = genesis.merkle_root();
+	if merkle_root != genesis.header.raw.merkle_root_hash {
+		return Err(format!("Genesis block merkle root mismatch: header has {}, transactions have {}",
+			genesis.header.raw.merkle_root_hash.to_reversed_str(), merkle_root.to_reversed_str()));
+	}
+
+	if !is_valid_proof_of_work_hash(genesis.header.raw.bits, genesis.hash()) {
+		return Err(format!("Genesis block {} doesn't satisfy its own proof-of-work target",
+			genesis.hash().to_reversed_str()));
+	}
+
+	if let Some((n, k)) = consensus.equihash_params {
+		if !verify_block_equihash_solution((n, k), &genesis.header.raw) {
+			return Err(format!("Genesis block has invalid Equihash solution for parameters n={}, k={}", n, k));
+		}
+	}
+
+	match (network, Network::from_magic(network.magic())) {
+		(Network::Custom(spec), Some(other)) =>
+			return Err(format!("Custom network {} uses magic {} of {:?} network", spec.name, spec.magic, other)),
+		(Network::Custom(_), None) => (),
+		// unitest network shares magic with regtest
+		(Network::Unitest, Some(Network::Regtest)) => (),
+		(network, other) if other != Some(network) =>
+			return Err(format!("Magic {} doesn't belong to {:?} network", network.magic(), network)),
+		_ => (),
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use hex::ToHex;
+	use network::{ConsensusParams, Network, ChainSpec, BaseNetwork, Magic};
+	use ser::serialize;
+	use super::verify_chain_params;
+
+	fn regtest_spec(magic: Magic) -> ChainSpec {
+		ChainSpec {
+			name: "research".into(),
+			base: BaseNetwork::Regtest,
+			magic: magic,
+			port: 28233,
+			rpc_port: 28232,
+			genesis_block: serialize(&Network::Regtest.genesis_block().to_raw_block()).to_hex(),
+			max_bits: "0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f0f".into(),
+			dns_seeds: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn verify_chain_params_of_predefined_networks() {
+		assert_eq!(verify_chain_params(&ConsensusParams::new(Network::Mainnet)), Ok(()));
+		assert_eq!(verify_chain_params(&ConsensusParams::new(Network::Testnet)), Ok(()));
+		assert_eq!(verify_chain_params(&ConsensusParams::new(Network::Regtest)), Ok(()));
+		assert_eq!(verify_chain_params(&ConsensusParams::new(Network::Unitest)), Ok(()));
+	}
+
+	#[test]
+	fn verify_chain_params_of_custom_network() {
+		let network = Network::custom(regtest_spec(Magic(0x12345678)));
+		assert_eq!(verify_chain_params(&ConsensusParams::new(network)), Ok(()));
+
+		// magic of mainnet
+		let network = Network::custom(regtest_spec(Network::Mainnet.magic()));
+		assert!(verify_chain_params(&ConsensusParams::new(network)).is_err());
+
+		// regtest genesis block with mainnet Equihash parameters
+		let mut consensus = ConsensusParams::new(Network::custom(regtest_spec(Magic(0x12345678))));
+		consensus.equihash_params = Some((200, 9));
+		assert!(verify_chain_params(&consensus).is_err());
+	}
+}
@@ -1,5 +1,6 @@
 //! Bitcoin chain verifier
 
+use std::sync::Arc;
 use chain::{IndexedBlock, IndexedBlockHeader, IndexedTransaction};
 use storage::{SharedStore, TransactionOutputProvider, BlockHeaderProvider, BlockOrigin,
 	DuplexTransactionOutputProvider, NoopStore, CachedTransactionOutputProvider};
@@ -12,20 +13,36 @@ use verify_transaction::MemoryPoolTransactionVerifier;
 use accept_chain::ChainAcceptor;
 use accept_transaction::MemoryPoolTransactionAcceptor;
 use deployments::{Deployments, BlockDeployments};
+use signature_cache::SignatureCache;
+use script_cache::ScriptCache;
 use {Verify, VerificationLevel};
 
 pub struct BackwardsCompatibleChainVerifier {
 	store: SharedStore,
 	consensus: ConsensusParams,
 	deployments: Deployments,
+	signature_cache: Arc<SignatureCache>,
+	script_cache: Arc<ScriptCache>,
 }
 
 impl BackwardsCompatibleChainVerifier {
 	pub fn new(store: SharedStore, consensus: ConsensusParams) -> Self {
+		BackwardsCompatibleChainVerifier::with_caches(store, consensus, Default::default(), Default::default())
+	}
+
+	/// Creates verifier, that shares caches of valid signatures && successful script executions with other verifiers.
+	pub fn with_caches(
+		store: SharedStore,
+		consensus: ConsensusParams,
+		signature_cache: Arc<SignatureCache>,
+		script_cache: Arc<ScriptCache>,
+	) -> Self {
 		BackwardsCompatibleChainVerifier {
 			store: store,
 			consensus: consensus,
 			deployments: Deployments::new(),
+			signature_cache: signature_cache,
+			script_cache: script_cache,
 		}
 	}
 
@@ -60,6 +77,7 @@ impl BackwardsCompatibleChainVerifier {
 				let tx_meta_provider = self.store.as_store().as_transaction_meta_provider();
 				let header_provider = self.store.as_store().as_block_header_provider();
 				let tree_state_provider = self.store.as_store().as_tree_state_provider();
+				let value_pools_provider = self.store.as_store().as_value_pools_provider();
 				let nullifier_tracker = self.store.as_store().as_nullifier_tracker();
 				let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
 				let chain_acceptor = ChainAcceptor::new(
@@ -67,6 +85,7 @@ impl BackwardsCompatibleChainVerifier {
 					tx_meta_provider,
 					header_provider,
 					tree_state_provider,
+					value_pools_provider,
 					nullifier_tracker,
 					&self.consensus,
 					verification_level,
@@ -74,6 +93,8 @@ impl BackwardsCompatibleChainVerifier {
 					block_number,
 					block.header.raw.time,
 					&deployments,
+					&self.signature_cache,
+					&self.script_cache,
 				);
 				chain_acceptor.check()?;
 			},
@@ -84,6 +105,7 @@ impl BackwardsCompatibleChainVerifier {
 				let tx_meta_provider = fork.store().as_transaction_meta_provider();
 				let header_provider = fork.store().as_block_header_provider();
 				let tree_state_provider = fork.store().as_tree_state_provider();
+				let value_pools_provider = fork.store().as_value_pools_provider();
 				let nullifier_tracker = fork.store().as_nullifier_tracker();
 				let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
 				let chain_acceptor = ChainAcceptor::new(
@@ -91,6 +113,7 @@ impl BackwardsCompatibleChainVerifier {
 					tx_meta_provider,
 					header_provider,
 					tree_state_provider,
+					value_pools_provider,
 					nullifier_tracker,
 					&self.consensus,
 					verification_level,
@@ -98,6 +121,8 @@ impl BackwardsCompatibleChainVerifier {
 					block_number,
 					block.header.raw.time,
 					&deployments,
+					&self.signature_cache,
+					&self.script_cache,
 				);
 				chain_acceptor.check()?;
 			},
@@ -108,6 +133,7 @@ impl BackwardsCompatibleChainVerifier {
 				let tx_meta_provider = fork.store().as_transaction_meta_provider();
 				let header_provider = fork.store().as_block_header_provider();
 				let tree_state_provider = fork.store().as_tree_state_provider();
+				let value_pools_provider = fork.store().as_value_pools_provider();
 				let nullifier_tracker = fork.store().as_nullifier_tracker();
 				let deployments = BlockDeployments::new(&self.deployments, block_number, header_provider, &self.consensus);
 				let chain_acceptor = ChainAcceptor::new(
@@ -115,6 +141,7 @@ impl BackwardsCompatibleChainVerifier {
 					tx_meta_provider,
 					header_provider,
 					tree_state_provider,
+					value_pools_provider,
 					nullifier_tracker,
 					&self.consensus,
 					verification_level,
@@ -122,6 +149,8 @@ impl BackwardsCompatibleChainVerifier {
 					block_number,
 					block.header.raw.time,
 					&deployments,
+					&self.signature_cache,
+					&self.script_cache,
 				);
 				chain_acceptor.check()?;
 			},
@@ -166,7 +195,10 @@ impl BackwardsCompatibleChainVerifier {
 			height,
 			time,
 			&deployments,
+			block_header_provider,
 			self.store.as_tree_state_provider(),
+			&self.signature_cache,
+			&self.script_cache,
 		);
 		tx_acceptor.check()
 	}
@@ -6,8 +6,9 @@ use hash::H256;
 use storage::{BlockHeaderProvider, BlockRef, BlockAncestors, BlockIterator};
 use timestamp::median_timestamp;
 
+/// BIP9 deployment state
 #[derive(Debug, PartialEq, Clone, Copy)]
-enum ThresholdState {
+pub enum ThresholdState {
 	Defined,
 	Started,
 	LockedIn,
@@ -35,6 +36,17 @@ impl ThresholdState {
 			_ => false,
 		}
 	}
+
+	/// Returns BIP9 name of the state
+	pub fn name(&self) -> &'static str {
+		match *self {
+			ThresholdState::Defined => "defined",
+			ThresholdState::Started => "started",
+			ThresholdState::LockedIn => "locked_in",
+			ThresholdState::Active => "active",
+			ThresholdState::Failed => "failed",
+		}
+	}
 }
 
 /// Threshold state at given point of time
@@ -71,13 +83,16 @@ impl Deployments {
 	/// Returns true if csv deployment is active
 	pub fn csv(&self, number: u32, headers: &BlockHeaderProvider, consensus: &ConsensusParams) -> bool {
 		match consensus.csv_deployment {
-			Some(csv) => {
-				let mut cache = self.cache.lock();
-				threshold_state(&mut cache, csv, number, headers, consensus.miner_confirmation_window, consensus.rule_change_activation_threshold).is_active()
-			},
+			Some(csv) => self.threshold_state(csv, number, headers, consensus).is_active(),
 			None => false
 		}
 	}
+
+	/// Returns state of given deployment for the block with given number
+	pub fn threshold_state(&self, deployment: Deployment, number: u32, headers: &BlockHeaderProvider, consensus: &ConsensusParams) -> ThresholdState {
+		let mut cache = self.cache.lock();
+		threshold_state(&mut cache, deployment, number, headers, consensus.miner_confirmation_window, consensus.rule_change_activation_threshold)
+	}
 }
 
 impl<'a> BlockDeployments<'a> {
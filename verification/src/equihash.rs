@@ -3,16 +3,15 @@ use crypto::Blake2b;
 
 /// Verify equihash solution of the block header.
 pub fn verify_block_equihash_solution(params: (u32, u32), header: &BlockHeader) -> bool {
-	debug_assert_eq!(
-		params,
-		(OnChainEquihash::N, OnChainEquihash::K),
-		"Wrong equihash parameters specified in consensus",
-	);
-
-	verify_equihash_solution::<OnChainEquihash>(
-		&header.equihash_input(),
-		header.solution.as_ref(),
-	)
+	let input = header.equihash_input();
+	let solution = header.solution.as_ref();
+	if params == (OnChainEquihash::N, OnChainEquihash::K) {
+		verify_equihash_solution::<OnChainEquihash>(&input, solution)
+	} else if params == (RegtestEquihash::N, RegtestEquihash::K) {
+		verify_equihash_solution::<RegtestEquihash>(&input, solution)
+	} else {
+		false
+	}
 }
 
 /// Equihash algorithm instance.
@@ -76,11 +75,31 @@ impl Equihash for OnChainEquihash {
 	type Hash = self::on_chain_equihash::Hash;
 }
 
+/// Equihash algorithm instance that is used by regtest chain.
+struct RegtestEquihash;
+
+impl Equihash for RegtestEquihash {
+	const N: u32 = 48;
+	const K: u32 = 5;
+	const BLAKE2B_PERSONALIZATION: [u8; 16] = [
+		0x5a, 0x63, 0x61, 0x73, 0x68, 0x50, 0x6f, 0x57,		// b"ZcashPoW"
+		0x30, 0x00, 0x00, 0x00,								// LE(N)
+		0x05, 0x00, 0x00, 0x00,								// LE(K)
+	];
+
+	type Hash = self::regtest_equihash::Hash;
+}
+
 /// Verify equihash solution.
 fn verify_equihash_solution<Algorithm: Equihash>(
 	input: &[u8],
 	solution: &[u8],
 ) -> bool {
+	// solution length depends on the algorithm parameters
+	if solution.len() != Algorithm::SOLUTION_COMPRESSED_SIZE {
+		return false;
+	}
+
 	// prepare Blake2b context with personalization
 	let mut context = Blake2b::with_params(Algorithm::HASH_SIZE, &[], &[], &Algorithm::BLAKE2B_PERSONALIZATION);
 	context.update(input);
@@ -176,7 +195,7 @@ fn for_each_solution_index<Algorithm, ForEach>(solution: &[u8], for_each: &mut F
 		Algorithm: Equihash,
 		ForEach: FnMut(u32),
 {
-	// consensus parameters enforces this
+	// verify_equihash_solution enforces this
 	debug_assert_eq!(
 		solution.len(),
 		Algorithm::SOLUTION_COMPRESSED_SIZE,
@@ -311,11 +330,28 @@ mod on_chain_equihash {
 	}
 }
 
+mod regtest_equihash {
+	pub struct Hash(pub [u8; 60]);
+
+	impl Default for Hash {
+		fn default() -> Self { Hash([0; 60]) }
+	}
+
+	impl AsRef<[u8]> for Hash {
+		fn as_ref(&self) -> &[u8] { &self.0 }
+	}
+
+	impl AsMut<[u8]> for Hash {
+		fn as_mut(&mut self) -> &mut [u8] { &mut self.0 }
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
 	use primitives::bigint::U256;
+	use network::Network;
 
 	struct TestEquihash;
 
@@ -415,4 +451,12 @@ mod tests {
 		let block = test_data::block_h170();
 		assert!(verify_block_equihash_solution((200, 9), &block.block_header));
 	}
+
+	#[test]
+	fn test_equihash_on_regtest_genesis() {
+		let genesis = Network::Regtest.genesis_block();
+		assert!(verify_block_equihash_solution((48, 5), &genesis.header.raw));
+		assert!(!verify_block_equihash_solution((200, 9), &genesis.header.raw));
+		assert!(!verify_block_equihash_solution((96, 5), &genesis.header.raw));
+	}
 }
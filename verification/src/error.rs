@@ -1,6 +1,8 @@
+use std::fmt;
 use hash::H256;
 use compact::Compact;
-use storage::Error as DBError;
+use storage::{Error as DBError, ValuePool};
+use network::FundingStreamReceiver;
 use script::Error as SignatureError;
 
 #[derive(Debug, PartialEq)]
@@ -55,16 +57,99 @@ pub enum Error {
 	Database(DBError),
 	/// Invalid equihash solution
 	InvalidEquihashSolution,
+	/// Equihash solution has size that doesn't match network Equihash parameters
+	InvalidEquihashSolutionSize { expected: usize, actual: usize },
 	/// Invalid block version
 	InvalidVersion,
 	/// Block' coinbase is missing founders reward output.
 	MissingFoundersReward,
+	/// Block' coinbase is missing output that pays to the funding stream.
+	MissingFundingStreamOutput(FundingStreamReceiver),
 	/// Failed to read sapling commitment tree state from parent block.
 	MissingSaplingCommitmentTree,
 	/// Failed to append commitment note to sapling commitment tree.
 	FailedToAppendSaplingCommitmentNote(String),
 	/// Invalid value of sapling final root hash in the block header.
 	InvalidFinalSaplingRootHash { expected: H256, actual: H256 },
+	/// Invalid value of chain history root or block commitments hash in the block header.
+	InvalidBlockCommitment { expected: H256, actual: H256 },
+	/// Block hash differs from the hash of checkpointed block at the same height.
+	CheckpointMismatch { expected: H256, actual: H256 },
+	/// Block drives the shielded value pool negative (turnstile violation).
+	NegativeValuePool(ValuePool),
+	/// Failed to read value pools after the parent block.
+	MissingValuePools,
+	/// Failed to read chain history tree after the parent block.
+	MissingHistoryTree,
+}
+
+impl Error {
+	/// Identifier of the violated consensus rule.
+	pub fn rule(&self) -> &'static str {
+		match *self {
+			Error::Duplicate => "Duplicate",
+			Error::DuplicatedTransactions => "DuplicatedTransactions",
+			Error::Empty => "Empty",
+			Error::Pow => "Pow",
+			Error::FuturisticTimestamp => "FuturisticTimestamp",
+			Error::Timestamp => "Timestamp",
+			Error::Coinbase => "Coinbase",
+			Error::Transaction(_, ref err) => err.rule(),
+			Error::Difficulty { .. } => "Difficulty",
+			Error::MerkleRoot => "MerkleRoot",
+			Error::CoinbaseOverspend { .. } => "CoinbaseOverspend",
+			Error::CoinbaseScript => "CoinbaseScript",
+			Error::MaximumSigops => "MaximumSigops",
+			Error::MaximumSigopsCost => "MaximumSigopsCost",
+			Error::CoinbaseSignatureLength(_) => "CoinbaseSignatureLength",
+			Error::Size(_) => "Size",
+			Error::Weight => "Weight",
+			Error::NonFinalBlock => "NonFinalBlock",
+			Error::OldVersionBlock => "OldVersionBlock",
+			Error::TransactionFeeAndRewardOverflow => "TransactionFeeAndRewardOverflow",
+			Error::TransactionFeesOverflow => "TransactionFeesOverflow",
+			Error::NonCanonicalTransactionOrdering => "NonCanonicalTransactionOrdering",
+			Error::Database(_) => "Database",
+			Error::InvalidEquihashSolution => "InvalidEquihashSolution",
+			Error::InvalidEquihashSolutionSize { .. } => "InvalidEquihashSolutionSize",
+			Error::InvalidVersion => "InvalidVersion",
+			Error::MissingFoundersReward => "MissingFoundersReward",
+			Error::MissingFundingStreamOutput(_) => "MissingFundingStreamOutput",
+			Error::MissingSaplingCommitmentTree => "MissingSaplingCommitmentTree",
+			Error::FailedToAppendSaplingCommitmentNote(_) => "FailedToAppendSaplingCommitmentNote",
+			Error::InvalidFinalSaplingRootHash { .. } => "InvalidFinalSaplingRootHash",
+			Error::InvalidBlockCommitment { .. } => "InvalidBlockCommitment",
+			Error::CheckpointMismatch { .. } => "CheckpointMismatch",
+			Error::NegativeValuePool(_) => "NegativeValuePool",
+			Error::MissingValuePools => "MissingValuePools",
+			Error::MissingHistoryTree => "MissingHistoryTree",
+		}
+	}
+
+	/// Index of the block transaction, which violates consensus rule.
+	pub fn transaction_index(&self) -> Option<usize> {
+		match *self {
+			Error::Transaction(index, _) => Some(index),
+			_ => None,
+		}
+	}
+
+	/// Index of the transaction input, which violates consensus rule.
+	pub fn input_index(&self) -> Option<usize> {
+		match *self {
+			Error::Transaction(_, ref err) => err.input_index(),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Transaction(index, ref err) => write!(f, "tx #{} {}", index, err),
+			_ => write!(f, "[{}] {:?}", self.rule(), self),
+		}
+	}
 }
 
 impl From<DBError> for Error {
@@ -94,6 +179,10 @@ pub enum TransactionError {
 	Input(usize),
 	/// Referenced coinbase output for the transaction input is not mature enough
 	Maturity,
+	/// Transaction input spends transparent coinbase output, but transaction has transparent outputs
+	UnshieldedCoinbaseSpend(usize),
+	/// Transaction lock time isn't yet reached
+	NonFinal,
 	/// Signature invalid for given input
 	Signature(usize, SignatureError),
 	/// Unknown previous transaction referenced
@@ -146,12 +235,116 @@ pub enum TransactionError {
 	InvalidSapling,
 	/// Sapling nullifier already revealed earlier in the chain.
 	SaplingDeclared(H256),
+	/// Orchard nullifier already revealed earlier in the chain.
+	OrchardDeclared(H256),
 	/// Transaction is expired.
 	Expired,
+	/// Coinbase expiry height doesn't match height of the block (since NU5).
+	CoinbaseExpiryHeight { expected: u32, actual: u32 },
 	/// Transaction overwintered flag is invalid.
 	InvalidOverwintered,
+	/// Consensus branch id of v5 transaction doesn't match consensus branch id of the block.
+	InvalidConsensusBranchId,
 	/// Invalid joinsplit statement
 	InvalidJoinSplit(usize),
 	/// Unknown anchor used in join split
 	UnknownAnchor(H256),
+	/// Sapling spend anchor is not a root of any historical sapling note commitment tree
+	UnknownSaplingAnchor(H256),
+}
+
+impl TransactionError {
+	/// Identifier of the violated consensus rule.
+	pub fn rule(&self) -> &'static str {
+		match *self {
+			TransactionError::Empty => "Empty",
+			TransactionError::NullNonCoinbase => "NullNonCoinbase",
+			TransactionError::CoinbaseSignatureLength(_) => "CoinbaseSignatureLength",
+			TransactionError::MaxSize => "MaxSize",
+			TransactionError::MinSize => "MinSize",
+			TransactionError::MaxSigops => "MaxSigops",
+			TransactionError::MemoryPoolCoinbase => "MemoryPoolCoinbase",
+			TransactionError::Input(_) => "Input",
+			TransactionError::Maturity => "Maturity",
+			TransactionError::UnshieldedCoinbaseSpend(_) => "UnshieldedCoinbaseSpend",
+			TransactionError::NonFinal => "NonFinal",
+			TransactionError::Signature(_, _) => "Signature",
+			TransactionError::UnknownReference(_) => "UnknownReference",
+			TransactionError::Overspend => "Overspend",
+			TransactionError::SignatureMallformed(_) => "SignatureMallformed",
+			TransactionError::Sigops(_) => "Sigops",
+			TransactionError::SigopsP2SH(_) => "SigopsP2SH",
+			TransactionError::MisplacedCoinbase => "MisplacedCoinbase",
+			TransactionError::UnspentTransactionWithTheSameHash => "UnspentTransactionWithTheSameHash",
+			TransactionError::UsingSpentOutput(_, _) => "UsingSpentOutput",
+			TransactionError::NonTransparentCoinbase => "NonTransparentCoinbase",
+			TransactionError::InvalidVersion => "InvalidVersion",
+			TransactionError::InvalidVersionGroup => "InvalidVersionGroup",
+			TransactionError::OutputValueOverflow => "OutputValueOverflow",
+			TransactionError::InputValueOverflow => "InputValueOverflow",
+			TransactionError::ExpiryHeightTooHigh => "ExpiryHeightTooHigh",
+			TransactionError::EmptySaplingHasBalance => "EmptySaplingHasBalance",
+			TransactionError::JoinSplitBothPubsNonZero => "JoinSplitBothPubsNonZero",
+			TransactionError::DuplicateInput(_, _) => "DuplicateInput",
+			TransactionError::DuplicateJoinSplitNullifier(_, _) => "DuplicateJoinSplitNullifier",
+			TransactionError::DuplicateSaplingSpendNullifier(_, _) => "DuplicateSaplingSpendNullifier",
+			TransactionError::JoinSplitDeclared(_) => "JoinSplitDeclared",
+			TransactionError::JoinSplitVersionInvalid => "JoinSplitVersionInvalid",
+			TransactionError::JoinSplitSignature(_) => "JoinSplitSignature",
+			TransactionError::InvalidSapling => "InvalidSapling",
+			TransactionError::SaplingDeclared(_) => "SaplingDeclared",
+			TransactionError::OrchardDeclared(_) => "OrchardDeclared",
+			TransactionError::Expired => "Expired",
+			TransactionError::CoinbaseExpiryHeight { .. } => "CoinbaseExpiryHeight",
+			TransactionError::InvalidOverwintered => "InvalidOverwintered",
+			TransactionError::InvalidConsensusBranchId => "InvalidConsensusBranchId",
+			TransactionError::InvalidJoinSplit(_) => "InvalidJoinSplit",
+			TransactionError::UnknownAnchor(_) => "UnknownAnchor",
+			TransactionError::UnknownSaplingAnchor(_) => "UnknownSaplingAnchor",
+		}
+	}
+
+	/// Index of the transaction input, which violates consensus rule.
+	pub fn input_index(&self) -> Option<usize> {
+		match *self {
+			TransactionError::Input(index)
+				| TransactionError::Signature(index, _)
+				| TransactionError::UnshieldedCoinbaseSpend(index)
+				| TransactionError::DuplicateInput(_, index) => Some(index),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for TransactionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.input_index() {
+			Some(index) => write!(f, "input #{} [{}] {:?}", index, self.rule(), self),
+			None => write!(f, "[{}] {:?}", self.rule(), self),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use script::Error as SignatureError;
+	use super::{Error, TransactionError};
+
+	#[test]
+	fn error_context() {
+		let err = Error::Transaction(2, TransactionError::Signature(1, SignatureError::EvalFalse));
+		assert_eq!(err.rule(), "Signature");
+		assert_eq!(err.transaction_index(), Some(2));
+		assert_eq!(err.input_index(), Some(1));
+		assert_eq!(err.to_string(), "tx #2 input #1 [Signature] Signature(1, EvalFalse)");
+
+		let err = Error::Transaction(0, TransactionError::Overspend);
+		assert_eq!(err.input_index(), None);
+		assert_eq!(err.to_string(), "tx #0 [Overspend] Overspend");
+
+		let err = Error::Size(100);
+		assert_eq!(err.rule(), "Size");
+		assert_eq!(err.transaction_index(), None);
+		assert_eq!(err.to_string(), "[Size] Size(100)");
+	}
 }
@@ -1,75 +1,60 @@
-use chain::Transaction;
+use chain::{Amount, Transaction};
 use storage::TransactionOutputProvider;
 use TransactionError;
 
 /// Compute miner fee for given transaction.
 ///
-/// Returns None if overflow/underflow happens during computation. Missed prevout
-/// is treated as 0-value.
-pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize, tx: &Transaction) -> Result<u64, TransactionError> {
+/// Returns error if any value or partial sum is out of money range, if previous
+/// output is unknown or if transaction spends more than it has.
+pub fn checked_transaction_fee(store: &TransactionOutputProvider, tx_idx: usize, tx: &Transaction) -> Result<Amount, TransactionError> {
+	let input_overflow = || TransactionError::InputValueOverflow;
+	let output_overflow = || TransactionError::OutputValueOverflow;
+
 	// (1) Total sum of all transparent + shielded inputs
-	let mut incoming: u64 = 0;
+	let mut incoming = Amount::zero();
 	for (input_idx, input) in tx.inputs.iter().enumerate() {
-		let prevout = match store.transaction_output(&input.previous_output, tx_idx) {
-			Some(prevout) => prevout,
-			None => return Err(TransactionError::Input(input_idx)),
-		};
-		incoming = match incoming.checked_add(prevout.value) {
-			Some(incoming) => incoming,
-			None => return Err(TransactionError::InputValueOverflow),
-		};
+		let prevout = store.transaction_output(&input.previous_output, tx_idx)
+			.ok_or(TransactionError::Input(input_idx))?;
+		incoming = Amount::from_u64(prevout.value)
+			.and_then(|value| incoming.checked_add(value))
+			.ok_or_else(input_overflow)?;
 	}
 
-	if let Some(ref join_split) = tx.join_split {
-		for js_desc in &join_split.descriptions {
-			incoming = match incoming.checked_add(js_desc.value_pub_new) {
-				Some(incoming) => incoming,
-				None => return Err(TransactionError::InputValueOverflow),
-			};
-		}
+	// (2) Total sum of all transparent + shielded outputs
+	let mut spends = Amount::zero();
+	for output in &tx.outputs {
+		spends = Amount::from_u64(output.value)
+			.and_then(|value| spends.checked_add(value))
+			.ok_or_else(output_overflow)?;
 	}
 
-	if let Some(ref sapling) = tx.sapling {
-		if sapling.balancing_value > 0 {
-			let balancing_value = sapling.balancing_value as u64;
-
-			incoming = match incoming.checked_add(balancing_value) {
-				Some(incoming) => incoming,
-				None => return Err(TransactionError::InputValueOverflow),
-			};
-		}
-	}
-
-	// (2) Total sum of all outputs
-	let mut spends = tx.total_spends();
-
 	if let Some(ref join_split) = tx.join_split {
 		for js_desc in &join_split.descriptions {
-			spends = match spends.checked_add(js_desc.value_pub_old) {
-				Some(spends) => spends,
-				None => return Err(TransactionError::OutputValueOverflow),
-			};
+			incoming = Amount::from_u64(js_desc.value_pub_new)
+				.and_then(|value| incoming.checked_add(value))
+				.ok_or_else(input_overflow)?;
+			spends = Amount::from_u64(js_desc.value_pub_old)
+				.and_then(|value| spends.checked_add(value))
+				.ok_or_else(output_overflow)?;
 		}
 	}
 
-	if let Some(ref sapling) = tx.sapling {
-		if sapling.balancing_value < 0 {
-			let balancing_value = match sapling.balancing_value.checked_neg() {
-				Some(balancing_value) => balancing_value as u64,
-				None => return Err(TransactionError::OutputValueOverflow),
-			};
-			
-			spends = match spends.checked_add(balancing_value) {
-				Some(spends) => spends,
-				None => return Err(TransactionError::OutputValueOverflow),
-			};
+	// positive balance moves value from shielded pool to transparent pool
+	let shielded_balances = tx.sapling.as_ref().map(|sapling| sapling.balancing_value).into_iter()
+		.chain(tx.orchard.as_ref().map(|orchard| orchard.value_balance));
+	for balance in shielded_balances {
+		let balance = Amount::from_zatoshis(balance).ok_or_else(output_overflow)?;
+		if balance.is_negative() {
+			spends = spends.checked_add(-balance).ok_or_else(output_overflow)?;
+		} else {
+			incoming = incoming.checked_add(balance).ok_or_else(input_overflow)?;
 		}
 	}
 
 	// (3) Fee is the difference between (1) and (2)
 	match incoming.checked_sub(spends) {
-		Some(fee) => Ok(fee),
-		None => Err(TransactionError::Overspend),
+		Some(fee) if !fee.is_negative() => Ok(fee),
+		_ => Err(TransactionError::Overspend),
 	}
 }
 
@@ -105,6 +90,6 @@ mod tests {
 		let store = db.as_transaction_output_provider();
 
 		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx0), Err(TransactionError::Overspend));
-		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx2), Ok(500_000));
+		assert_eq!(checked_transaction_fee(store, ::std::usize::MAX, &tx2), Ok(Amount::from_u64(500_000).unwrap()));
 	}
 }
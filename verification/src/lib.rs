@@ -55,6 +55,7 @@ extern crate time;
 #[macro_use]
 extern crate log;
 extern crate parking_lot;
+extern crate lru_cache;
 extern crate rayon;
 extern crate byteorder;
 #[cfg(test)]
@@ -82,13 +83,18 @@ extern crate assert_matches;
 
 pub mod constants;
 mod canon;
+mod chain_params;
 mod deployments;
 mod equihash;
 mod error;
 mod fee;
 mod sapling;
+mod script_cache;
+mod signature_cache;
 mod sigops;
 mod sprout;
+mod supply;
+mod thread_pool;
 mod timestamp;
 mod work;
 
@@ -112,6 +118,7 @@ mod tree_cache;
 pub use primitives::{bigint, hash, compact};
 
 pub use canon::{CanonBlock, CanonHeader, CanonTransaction};
+pub use chain_params::verify_chain_params;
 pub use accept_block::BlockAcceptor;
 pub use accept_chain::ChainAcceptor;
 pub use accept_header::HeaderAcceptor;
@@ -125,10 +132,14 @@ pub use verify_transaction::{TransactionVerifier, MemoryPoolTransactionVerifier}
 pub use chain_verifier::BackwardsCompatibleChainVerifier;
 pub use error::{Error, TransactionError};
 pub use fee::checked_transaction_fee;
-pub use sigops::transaction_sigops;
-pub use timestamp::{median_timestamp, median_timestamp_inclusive};
+pub use script_cache::{ScriptCache, DEFAULT_SCRIPT_CACHE_SIZE};
+pub use signature_cache::{SignatureCache, DEFAULT_SIGNATURE_CACHE_SIZE};
+pub use sigops::{transaction_sigops, ValidationCost};
+pub use supply::{audit_supply, SupplyAudit};
+pub use thread_pool::init_thread_pool;
+pub use timestamp::{median_timestamp, median_timestamp_inclusive, median_timestamp_at};
 pub use work::{work_required, is_valid_proof_of_work, is_valid_proof_of_work_hash};
-pub use deployments::Deployments;
+pub use deployments::{Deployments, ThresholdState};
 pub use tree_cache::TreeCache;
 
 bitflags! {
@@ -141,6 +152,9 @@ bitflags! {
 		/// Base level: no blocks verification at all.
 		const NO_VERIFICATION = 0x00000004;
 
+		/// Modifier: Sprout JoinSplit proofs are not verified (other transaction checks are performed as usual).
+		const NO_SPROUT_PROOFS = 0x00000100;
+
 		/// This bit is set if header pre-verification (non-context) has already been performed for the block.
 		const HINT_HEADER_PRE_VERIFIED = 0x10000000;
 	}
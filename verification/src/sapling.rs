@@ -296,6 +296,7 @@ mod tests {
 	extern crate test_data;
 
 	use chain::Transaction;
+	use network::NetworkUpgrade;
 	use script::{TransactionInputSigner, SighashBase};
 	use super::*;
 
@@ -306,7 +307,7 @@ mod tests {
 
 	fn compute_sighash(tx: Transaction) -> [u8; 32] {
 		let signer: TransactionInputSigner = tx.into();
-		signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), 0x76b809bb).into()
+		signer.signature_hash(&mut Default::default(), None, 0, &From::from(vec![]), SighashBase::All.into(), NetworkUpgrade::Sapling.branch_id()).into()
 	}
 
 	fn run_accept_sapling(tx: Transaction) -> Result<(), Error> {
@@ -0,0 +1,93 @@
+//! Cache of successful script executions.
+//!
+//! Inputs of memory pool transactions are verified when transactions are accepted to the
+//! memory pool. When the same transaction is included in the block, its inputs are verified
+//! with the same flags, so the block verifier only needs to check that execution is cached.
+
+use lru_cache::LruCache;
+use parking_lot::Mutex;
+use crypto::dhash256;
+use primitives::hash::H256;
+use ser::Stream;
+
+/// Default number of cached script executions.
+pub const DEFAULT_SCRIPT_CACHE_SIZE: usize = 100_000;
+
+/// Bounded (least recently used entries are evicted first) set of successful script executions.
+pub struct ScriptCache {
+	entries: Mutex<LruCache<H256, ()>>,
+}
+
+/// Identifies script execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptCacheKey(H256);
+
+impl ScriptCacheKey {
+	/// Key of the execution of given transaction input script. Signature script is a part of the key,
+	/// because it isn't committed to by v5 transaction id.
+	pub fn new(txid: &H256, input_index: usize, script_sig: &[u8], flags: u32, consensus_branch_id: u32) -> Self {
+		let mut stream = Stream::default();
+		stream
+			.append(txid)
+			.append(&(input_index as u32))
+			.append(&flags)
+			.append(&consensus_branch_id)
+			.append_slice(script_sig);
+		ScriptCacheKey(dhash256(&stream.out()))
+	}
+}
+
+impl ScriptCache {
+	/// Creates cache, holding up to `size` executions. Cache of zero size is never hit.
+	pub fn new(size: usize) -> Self {
+		ScriptCache {
+			entries: Mutex::new(LruCache::new(size)),
+		}
+	}
+
+	/// Returns true if the script has been successfully executed before.
+	pub fn contains(&self, key: &ScriptCacheKey) -> bool {
+		self.entries.lock().get_mut(&key.0).is_some()
+	}
+
+	/// Remembers successful script execution.
+	pub fn insert(&self, key: ScriptCacheKey) {
+		self.entries.lock().insert(key.0, ());
+	}
+
+	/// Number of cached executions.
+	pub fn len(&self) -> usize {
+		self.entries.lock().len()
+	}
+}
+
+impl Default for ScriptCache {
+	fn default() -> Self {
+		ScriptCache::new(DEFAULT_SCRIPT_CACHE_SIZE)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ScriptCache, ScriptCacheKey};
+
+	#[test]
+	fn script_cache_works() {
+		let cache = ScriptCache::new(2);
+		let key = ScriptCacheKey::new(&1.into(), 0, &[1, 2, 3], 0, 0);
+		cache.insert(key.clone());
+		assert!(cache.contains(&key));
+
+		// every part of the key matters
+		assert!(!cache.contains(&ScriptCacheKey::new(&2.into(), 0, &[1, 2, 3], 0, 0)));
+		assert!(!cache.contains(&ScriptCacheKey::new(&1.into(), 1, &[1, 2, 3], 0, 0)));
+		assert!(!cache.contains(&ScriptCacheKey::new(&1.into(), 0, &[1, 2], 0, 0)));
+		assert!(!cache.contains(&ScriptCacheKey::new(&1.into(), 0, &[1, 2, 3], 1, 0)));
+		assert!(!cache.contains(&ScriptCacheKey::new(&1.into(), 0, &[1, 2, 3], 0, 1)));
+
+		cache.insert(ScriptCacheKey::new(&2.into(), 0, &[], 0, 0));
+		cache.insert(ScriptCacheKey::new(&3.into(), 0, &[], 0, 0));
+		assert_eq!(cache.len(), 2);
+		assert!(!cache.contains(&key));
+	}
+}
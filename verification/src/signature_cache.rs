@@ -0,0 +1,128 @@
+//! Cache of valid transparent signatures.
+//!
+//! Transactions are usually verified twice: when they're accepted to the memory pool
+//! and when they're included in the block. The cache is shared by all chain verifiers,
+//! so the second verification doesn't need to verify the same signatures again.
+
+use lru_cache::LruCache;
+use parking_lot::Mutex;
+use crypto::dhash256;
+use keys::{Public, Signature, Message};
+use primitives::hash::H256;
+use script::{Script, Num, SignatureChecker, TransactionSignatureChecker};
+
+/// Default number of cached signatures.
+pub const DEFAULT_SIGNATURE_CACHE_SIZE: usize = 100_000;
+
+/// Bounded (least recently used entries are evicted first) set of valid signatures.
+pub struct SignatureCache {
+	entries: Mutex<LruCache<H256, ()>>,
+}
+
+impl SignatureCache {
+	/// Creates cache, holding up to `size` signatures. Cache of zero size is never hit.
+	pub fn new(size: usize) -> Self {
+		SignatureCache {
+			entries: Mutex::new(LruCache::new(size)),
+		}
+	}
+
+	/// Returns true if the signature is known to be valid.
+	pub fn contains(&self, signature: &Signature, public: &Public, hash: &Message) -> bool {
+		self.entries.lock().get_mut(&entry_key(signature, public, hash)).is_some()
+	}
+
+	/// Remembers valid signature.
+	pub fn insert(&self, signature: &Signature, public: &Public, hash: &Message) {
+		self.entries.lock().insert(entry_key(signature, public, hash), ());
+	}
+
+	/// Number of cached signatures.
+	pub fn len(&self) -> usize {
+		self.entries.lock().len()
+	}
+}
+
+impl Default for SignatureCache {
+	fn default() -> Self {
+		SignatureCache::new(DEFAULT_SIGNATURE_CACHE_SIZE)
+	}
+}
+
+/// Signature checker, that only verifies signatures, missing from the cache.
+pub struct CachedSignatureChecker<'a> {
+	pub checker: TransactionSignatureChecker,
+	pub cache: &'a SignatureCache,
+}
+
+impl<'a> SignatureChecker for CachedSignatureChecker<'a> {
+	fn verify_signature(&self, signature: &Signature, public: &Public, hash: &Message) -> bool {
+		if self.cache.contains(signature, public, hash) {
+			return true;
+		}
+
+		let is_valid = self.checker.verify_signature(signature, public, hash);
+		if is_valid {
+			self.cache.insert(signature, public, hash);
+		}
+		is_valid
+	}
+
+	fn check_signature(&mut self, signature: &Signature, public: &Public, script_code: &Script, sighashtype: u32) -> bool {
+		let hash = self.checker.signer.signature_hash(
+			&mut self.checker.cache,
+			Some(self.checker.input_index),
+			self.checker.input_amount,
+			script_code,
+			sighashtype,
+			self.checker.consensus_branch_id,
+		);
+		self.verify_signature(signature, public, &hash)
+	}
+
+	fn check_lock_time(&self, lock_time: Num) -> bool {
+		self.checker.check_lock_time(lock_time)
+	}
+
+	fn check_sequence(&self, sequence: Num) -> bool {
+		self.checker.check_sequence(sequence)
+	}
+}
+
+fn entry_key(signature: &Signature, public: &Public, hash: &Message) -> H256 {
+	let mut data = Vec::with_capacity(signature.len() + public.len() + 32);
+	data.extend_from_slice(&**signature);
+	data.extend_from_slice(&**public);
+	data.extend_from_slice(&**hash);
+	dhash256(&data)
+}
+
+#[cfg(test)]
+mod tests {
+	use keys::{Public, Signature};
+	use super::SignatureCache;
+
+	#[test]
+	fn signature_cache_works() {
+		let public = Public::from_slice(&[2u8; 33]).unwrap();
+		let signature = |byte| Signature::from(vec![byte; 71]);
+		let cache = SignatureCache::new(2);
+
+		cache.insert(&signature(1), &public, &1.into());
+		cache.insert(&signature(2), &public, &1.into());
+		assert!(cache.contains(&signature(1), &public, &1.into()));
+		assert!(!cache.contains(&signature(1), &public, &2.into()));
+
+		// signature(2) is the least recently used entry
+		cache.insert(&signature(3), &public, &1.into());
+		assert_eq!(cache.len(), 2);
+		assert!(cache.contains(&signature(1), &public, &1.into()));
+		assert!(!cache.contains(&signature(2), &public, &1.into()));
+		assert!(cache.contains(&signature(3), &public, &1.into()));
+
+		// cache of zero size is disabled
+		let cache = SignatureCache::new(0);
+		cache.insert(&signature(1), &public, &1.into());
+		assert!(!cache.contains(&signature(1), &public, &1.into()));
+	}
+}
@@ -1,6 +1,50 @@
-use chain::Transaction;
+use chain::{Transaction, IndexedBlock};
+use network::BlockLimits;
 use storage::TransactionOutputProvider;
 use script::Script;
+use error::Error;
+
+/// Consensus-limited resources (serialized size and signature operations), consumed
+/// by the transaction or block. Used by both block verifier and block assembler, so that
+/// they always agree on whether transactions fit the block.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ValidationCost {
+	/// Serialized size.
+	pub size: usize,
+	/// Number of signature operations.
+	pub sigops: usize,
+}
+
+impl ValidationCost {
+	/// Cost of the transaction. Signature operations are counted with `transaction_sigops`.
+	pub fn transaction(transaction: &Transaction, store: &TransactionOutputProvider, bip16_active: bool) -> Self {
+		ValidationCost {
+			size: transaction.size(),
+			sigops: transaction_sigops(transaction, store, bip16_active),
+		}
+	}
+
+	/// Cost of the block: its serialized size (including header) and signature operations of all its transactions.
+	pub fn block(block: &IndexedBlock, store: &TransactionOutputProvider, bip16_active: bool) -> Self {
+		ValidationCost {
+			size: block.size(),
+			sigops: block.transactions.iter()
+				.map(|tx| transaction_sigops(&tx.raw, store, bip16_active))
+				.sum(),
+		}
+	}
+
+	/// Check that the block with this cost fits given limits.
+	pub fn check_block_limits(&self, limits: &BlockLimits) -> Result<(), Error> {
+		if self.size > limits.max_block_size {
+			return Err(Error::Size(self.size));
+		}
+		if self.sigops > limits.max_block_sigops {
+			return Err(Error::MaximumSigops);
+		}
+		Ok(())
+	}
+}
 
 /// Counts signature operations in given transaction
 /// bip16_active flag indicates if we should also count signature operations
@@ -39,3 +83,37 @@ pub fn transaction_sigops(
 
 	input_sigops + output_sigops + bip16_sigops
 }
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use chain::IndexedBlock;
+	use network::{ConsensusParams, Network};
+	use storage::NoopStore;
+	use error::Error;
+	use super::ValidationCost;
+
+	#[test]
+	fn test_block_validation_cost() {
+		let block: IndexedBlock = test_data::block_h1().into();
+		let cost = ValidationCost::block(&block, &NoopStore, false);
+		let transactions_cost = block.transactions.iter()
+			.map(|tx| ValidationCost::transaction(&tx.raw, &NoopStore, false))
+			.fold(ValidationCost::default(), |acc, cost| ValidationCost {
+				size: acc.size + cost.size,
+				sigops: acc.sigops + cost.sigops,
+			});
+
+		assert_eq!(cost.size, block.size());
+		assert!(cost.size > transactions_cost.size);
+		assert_eq!(cost.sigops, transactions_cost.sigops);
+
+		let limits = ConsensusParams::new(Network::Mainnet).block_limits;
+		assert_eq!(cost.check_block_limits(&limits), Ok(()));
+		assert_eq!(ValidationCost { size: limits.max_block_size + 1, sigops: 0 }.check_block_limits(&limits),
+			Err(Error::Size(limits.max_block_size + 1)));
+		assert_eq!(ValidationCost { size: 0, sigops: limits.max_block_sigops + 1 }.check_block_limits(&limits),
+			Err(Error::MaximumSigops));
+	}
+}
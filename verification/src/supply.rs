@@ -0,0 +1,225 @@
+//! Audit of the chain value supply.
+
+use chain::Transaction;
+use network::ConsensusParams;
+use storage::{Store, BlockRef, TransactionOutputProvider, DuplexTransactionOutputProvider};
+
+/// Value supply of the canon chain, split by pools.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SupplyAudit {
+	/// Height of the last audited block.
+	pub height: u32,
+	/// Sum of block subsidies, scheduled by consensus rules up to the audited block.
+	pub scheduled_subsidy: u64,
+	/// Sum of fees, paid by all non-coinbase transactions.
+	pub fees: u64,
+	/// Value of all unspent transparent outputs.
+	pub transparent_pool: u64,
+	/// Value, stored in Sprout pool.
+	pub sprout_pool: u64,
+	/// Value, stored in Sapling pool.
+	pub sapling_pool: u64,
+	/// Value, stored in Orchard pool.
+	pub orchard_pool: u64,
+}
+
+impl SupplyAudit {
+	/// Total monetary supply.
+	pub fn total_supply(&self) -> u64 {
+		self.transparent_pool + self.sprout_pool + self.sapling_pool + self.orchard_pool
+	}
+
+	/// Part of the scheduled subsidy, which has never been claimed by miners.
+	pub fn unclaimed_subsidy(&self) -> u64 {
+		self.scheduled_subsidy - self.total_supply()
+	}
+}
+
+/// Value that enters and leaves every pool in single transaction.
+#[derive(Debug, Default)]
+struct ValueFlows {
+	transparent_in: u64,
+	transparent_out: u64,
+	sprout_in: u64,
+	sprout_out: u64,
+	sapling_in: u64,
+	sapling_out: u64,
+	orchard_in: u64,
+	orchard_out: u64,
+}
+
+impl ValueFlows {
+	fn new(store: &TransactionOutputProvider, tx_index: usize, tx: &Transaction) -> Result<Self, String> {
+		let mut flows = ValueFlows::default();
+		if !tx.is_coinbase() {
+			for input in &tx.inputs {
+				let prevout = store.transaction_output(&input.previous_output, tx_index)
+					.ok_or_else(|| format!("Unknown previous output {}:{}",
+						input.previous_output.hash.to_reversed_str(), input.previous_output.index))?;
+				flows.transparent_out = add(flows.transparent_out, prevout.value)?;
+			}
+		}
+		for output in &tx.outputs {
+			flows.transparent_in = add(flows.transparent_in, output.value)?;
+		}
+
+		if let Some(ref join_split) = tx.join_split {
+			for description in &join_split.descriptions {
+				flows.sprout_in = add(flows.sprout_in, description.value_pub_old)?;
+				flows.sprout_out = add(flows.sprout_out, description.value_pub_new)?;
+			}
+		}
+
+		// positive balance means that value leaves shielded pool
+		if let Some(ref sapling) = tx.sapling {
+			split_balance(sapling.balancing_value, &mut flows.sapling_in, &mut flows.sapling_out)?;
+		}
+		if let Some(ref orchard) = tx.orchard {
+			split_balance(orchard.value_balance, &mut flows.orchard_in, &mut flows.orchard_out)?;
+		}
+
+		Ok(flows)
+	}
+
+	/// Value that enters all pools.
+	fn total_in(&self) -> Result<u64, String> {
+		add(add(add(self.transparent_in, self.sprout_in)?, self.sapling_in)?, self.orchard_in)
+	}
+
+	/// Value that leaves all pools.
+	fn total_out(&self) -> Result<u64, String> {
+		add(add(add(self.transparent_out, self.sprout_out)?, self.sapling_out)?, self.orchard_out)
+	}
+}
+
+/// Walk the canon chain from genesis to the block at given height, checking that every block
+/// creates no more value than allowed by the subsidy schedule and that value of every pool
+/// never becomes negative.
+/// Returns human-readable description of the first problem found.
+pub fn audit_supply(store: &Store, consensus: &ConsensusParams, height: u32) -> Result<SupplyAudit, String> {
+	let mut audit = SupplyAudit::default();
+	for block_number in 0..height + 1 {
+		let block = store.block(BlockRef::Number(block_number))
+			.ok_or_else(|| format!("Block {} is not in the canon chain", block_number))?;
+		let block_hash = block.hash().to_reversed_str();
+		let subsidy = consensus.block_subsidy(block_number);
+		let block_store = DuplexTransactionOutputProvider::new(store.as_transaction_output_provider(), &block);
+
+		let mut block_fees = 0u64;
+		let mut coinbase_claim = 0u64;
+		for (tx_index, tx) in block.transactions.iter().enumerate() {
+			let flows = ValueFlows::new(&block_store, tx_index, &tx.raw)
+				.map_err(|err| format!("Transaction {} of block {}: {}", tx.hash.to_reversed_str(), block_hash, err))?;
+			let (value_in, value_out) = (flows.total_in()?, flows.total_out()?);
+			if tx.raw.is_coinbase() {
+				coinbase_claim = add(coinbase_claim, value_in.saturating_sub(value_out))?;
+			} else {
+				let fee = value_out.checked_sub(value_in)
+					.ok_or_else(|| format!("Transaction {} of block {} creates {} zatoshis",
+						tx.hash.to_reversed_str(), block_hash, value_in - value_out))?;
+				block_fees = add(block_fees, fee)?;
+			}
+
+			audit.transparent_pool = apply_flows(audit.transparent_pool, flows.transparent_in, flows.transparent_out)
+				.ok_or_else(|| format!("Transparent pool becomes negative at block {}", block_hash))?;
+			audit.sprout_pool = apply_flows(audit.sprout_pool, flows.sprout_in, flows.sprout_out)
+				.ok_or_else(|| format!("Sprout pool becomes negative at block {}", block_hash))?;
+			audit.sapling_pool = apply_flows(audit.sapling_pool, flows.sapling_in, flows.sapling_out)
+				.ok_or_else(|| format!("Sapling pool becomes negative at block {}", block_hash))?;
+			audit.orchard_pool = apply_flows(audit.orchard_pool, flows.orchard_in, flows.orchard_out)
+				.ok_or_else(|| format!("Orchard pool becomes negative at block {}", block_hash))?;
+		}
+
+		let max_claim = add(subsidy, block_fees)?;
+		if coinbase_claim > max_claim {
+			return Err(format!("Coinbase of block {} at height {} claims {} zatoshis, while only {} are allowed",
+				block_hash, block_number, coinbase_claim, max_claim));
+		}
+
+		audit.height = block_number;
+		audit.fees = add(audit.fees, block_fees)?;
+		audit.scheduled_subsidy = add(audit.scheduled_subsidy, subsidy)?;
+		if audit.total_supply() > audit.scheduled_subsidy {
+			return Err(format!("Total supply {} exceeds scheduled subsidy {} at block {}",
+				audit.total_supply(), audit.scheduled_subsidy, block_hash));
+		}
+	}
+
+	Ok(audit)
+}
+
+fn add(a: u64, b: u64) -> Result<u64, String> {
+	a.checked_add(b).ok_or_else(|| "Value overflow".to_owned())
+}
+
+fn apply_flows(pool: u64, value_in: u64, value_out: u64) -> Option<u64> {
+	pool.checked_add(value_in).and_then(|pool| pool.checked_sub(value_out))
+}
+
+fn split_balance(balance: i64, value_in: &mut u64, value_out: &mut u64) -> Result<(), String> {
+	if balance > 0 {
+		*value_out = add(*value_out, balance as u64)?;
+	} else {
+		let balance = balance.checked_neg().ok_or_else(|| "Value overflow".to_owned())?;
+		*value_in = add(*value_in, balance as u64)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use db::BlockChainDatabase;
+	use network::{ConsensusParams, Network};
+	use super::audit_supply;
+
+	#[test]
+	fn audit_supply_of_valid_chain() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let genesis = test_data::genesis();
+		let b1 = test_data::block_builder()
+			.transaction().coinbase()
+				.output().value(consensus.block_subsidy(1)).build()
+				.build()
+			.merkled_header().parent(genesis.hash()).build()
+			.build();
+		let b2 = test_data::block_builder()
+			.transaction().coinbase()
+				.output().value(consensus.block_subsidy(2) / 2).build()
+				.build()
+			.transaction()
+				.input().hash(b1.transactions()[0].hash()).build()
+				.output().value(consensus.block_subsidy(1) - 100).build()
+				.build()
+			.merkled_header().parent(b1.hash()).build()
+			.build();
+		let storage = BlockChainDatabase::init_test_chain(vec![genesis.into(), b1.into(), b2.into()]);
+
+		let audit = audit_supply(&storage, &consensus, 2).unwrap();
+		assert_eq!(audit.height, 2);
+		assert_eq!(audit.fees, 100);
+		// there's no subsidy for the genesis block
+		assert_eq!(audit.scheduled_subsidy, consensus.block_subsidy(1) + consensus.block_subsidy(2));
+		assert_eq!(audit.total_supply(), consensus.block_subsidy(1) + consensus.block_subsidy(2) / 2 - 100);
+		assert_eq!(audit.unclaimed_subsidy(), consensus.block_subsidy(2) - consensus.block_subsidy(2) / 2 + 100);
+
+		assert!(audit_supply(&storage, &consensus, 3).is_err());
+	}
+
+	#[test]
+	fn audit_supply_detects_inflation() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let genesis = test_data::genesis();
+		let b1 = test_data::block_builder()
+			.transaction().coinbase()
+				.output().value(consensus.block_subsidy(1) + 1).build()
+				.build()
+			.merkled_header().parent(genesis.hash()).build()
+			.build();
+		let storage = BlockChainDatabase::init_test_chain(vec![genesis.into(), b1.into()]);
+
+		assert!(audit_supply(&storage, &consensus, 0).is_ok());
+		assert!(audit_supply(&storage, &consensus, 1).is_err());
+	}
+}
@@ -0,0 +1,16 @@
+//! Thread pool, used to verify transactions (and transaction inputs) of the block concurrently.
+//!
+//! Verification jobs are scheduled on the global rayon pool, so idle threads are stealing
+//! jobs (i.e. inputs of large transactions) from busy threads.
+
+use rayon::ThreadPoolBuilder;
+
+/// Sets the number of verification threads. Must be called before the first block is verified,
+/// otherwise the pool is created with one thread per CPU.
+pub fn init_thread_pool(threads: usize) -> Result<(), String> {
+	ThreadPoolBuilder::new()
+		.num_threads(threads)
+		.thread_name(|index| format!("verification-{}", index))
+		.build_global()
+		.map_err(|err| format!("Cannot create verification thread pool: {}", err))
+}
@@ -1,5 +1,5 @@
 use chain::BlockHeader;
-use storage::{BlockHeaderProvider, BlockAncestors};
+use storage::{BlockHeaderProvider, BlockAncestors, BlockRef};
 use primitives::hash::H256;
 
 /// Returns median timestamp, of given header ancestors.
@@ -26,3 +26,11 @@ pub fn median_timestamp_inclusive(previous_header_hash: H256, store: &BlockHeade
 
 	timestamps[timestamps.len() / 2]
 }
+
+/// Returns median timestamp of the block at given height + its ancestors.
+/// Returns 0 if block is unknown.
+pub fn median_timestamp_at(height: u32, store: &BlockHeaderProvider) -> u32 {
+	store.block_header(BlockRef::Number(height))
+		.map(|header| median_timestamp_inclusive(header.hash, store))
+		.unwrap_or_default()
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use chain::hash::H256;
+use chain::HistoryTree;
 use storage::{TreeStateProvider, SproutTreeState, SaplingTreeState};
 use error::TransactionError;
 
@@ -23,6 +24,10 @@ impl TreeStateProvider for NoPersistentStorage {
 	fn sprout_block_root(&self, _block_hash: &H256) -> Option<H256> { None }
 
 	fn sapling_block_root(&self, _block_hash: &H256) -> Option<H256> { None }
+
+	fn sapling_anchor_height(&self, _root: &H256) -> Option<u32> { None }
+
+	fn history_tree_at_block(&self, _block_hash: &H256) -> Option<HistoryTree> { None }
 }
 
 impl<'a> TreeCache<'a> {
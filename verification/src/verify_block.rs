@@ -1,8 +1,8 @@
 use std::collections::HashSet;
 use chain::IndexedBlock;
 use network::ConsensusParams;
-use sigops::transaction_sigops;
-use storage::NoopStore;
+use sigops::ValidationCost;
+use storage::{NoopStore, EpochTag, transaction_nullifiers};
 use error::{Error, TransactionError};
 
 pub struct BlockVerifier<'a> {
@@ -13,6 +13,7 @@ pub struct BlockVerifier<'a> {
 	pub transactions_uniqueness: BlockTransactionsUniqueness<'a>,
 	pub sigops: BlockSigops<'a>,
 	pub merkle_root: BlockMerkleRoot<'a>,
+	pub nullifiers_uniqueness: BlockNullifiersUniqueness<'a>,
 }
 
 impl<'a> BlockVerifier<'a> {
@@ -25,6 +26,7 @@ impl<'a> BlockVerifier<'a> {
 			transactions_uniqueness: BlockTransactionsUniqueness::new(block),
 			sigops: BlockSigops::new(block, consensus),
 			merkle_root: BlockMerkleRoot::new(block),
+			nullifiers_uniqueness: BlockNullifiersUniqueness::new(block),
 		}
 	}
 
@@ -36,6 +38,7 @@ impl<'a> BlockVerifier<'a> {
 		self.transactions_uniqueness.check()?;
 		self.sigops.check()?;
 		self.merkle_root.check()?;
+		self.nullifiers_uniqueness.check()?;
 		Ok(())
 	}
 }
@@ -69,7 +72,7 @@ impl<'a> BlockSerializedSize<'a> {
 	fn new(block: &'a IndexedBlock, consensus: &'a ConsensusParams) -> Self {
 		BlockSerializedSize {
 			block: block,
-			max_size: consensus.max_block_size(),
+			max_size: consensus.block_limits.max_block_size,
 		}
 	}
 
@@ -156,15 +159,13 @@ impl<'a> BlockSigops<'a> {
 	fn new(block: &'a IndexedBlock, consensus: &'a ConsensusParams) -> Self {
 		BlockSigops {
 			block: block,
-			max_sigops: consensus.max_block_sigops(),
+			max_sigops: consensus.block_limits.max_block_sigops,
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
 		// We cannot know if bip16 is enabled at this point so we disable it.
-		let sigops = self.block.transactions.iter()
-			.map(|tx| transaction_sigops(&tx.raw, &NoopStore, false))
-			.sum::<usize>();
+		let sigops = ValidationCost::block(self.block, &NoopStore, false).sigops;
 
 		if sigops > self.max_sigops {
 			Err(Error::MaximumSigops)
@@ -193,3 +194,89 @@ impl<'a> BlockMerkleRoot<'a> {
 		}
 	}
 }
+
+/// Check that every nullifier is revealed at most once within the block. Nullifiers, revealed
+/// within the same transaction, are checked by the transaction verifier.
+pub struct BlockNullifiersUniqueness<'a> {
+	block: &'a IndexedBlock,
+}
+
+impl<'a> BlockNullifiersUniqueness<'a> {
+	fn new(block: &'a IndexedBlock) -> Self {
+		BlockNullifiersUniqueness {
+			block: block,
+		}
+	}
+
+	fn check(&self) -> Result<(), Error> {
+		let mut revealed = HashSet::new();
+		for (index, tx) in self.block.transactions.iter().enumerate() {
+			let nullifiers = transaction_nullifiers(&tx.raw);
+			if let Some(nullifier) = nullifiers.iter().find(|nullifier| revealed.contains(*nullifier)) {
+				let error = match nullifier.epoch() {
+					EpochTag::Sprout => TransactionError::JoinSplitDeclared(*nullifier.hash()),
+					EpochTag::Sapling => TransactionError::SaplingDeclared(*nullifier.hash()),
+					EpochTag::Orchard => TransactionError::OrchardDeclared(*nullifier.hash()),
+				};
+				return Err(Error::Transaction(index, error));
+			}
+
+			revealed.extend(nullifiers);
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate test_data;
+
+	use chain::{IndexedBlock, Transaction, JoinSplit, JoinSplitDescription, Sapling, SaplingSpendDescription};
+	use primitives::hash::H256;
+	use error::{Error, TransactionError};
+	use super::BlockNullifiersUniqueness;
+
+	fn sprout_tx(nullifier: [u8; 32]) -> Transaction {
+		Transaction {
+			join_split: Some(JoinSplit {
+				descriptions: vec![JoinSplitDescription { nullifiers: [nullifier, [0xff; 32]], ..Default::default() }],
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	fn sapling_tx(nullifier: [u8; 32]) -> Transaction {
+		Transaction {
+			sapling: Some(Sapling {
+				spends: vec![SaplingSpendDescription { nullifier: nullifier, ..Default::default() }],
+				..Default::default()
+			}),
+			..Default::default()
+		}
+	}
+
+	fn block_with(transactions: Vec<Transaction>) -> IndexedBlock {
+		test_data::block_builder()
+			.with_transactions(transactions)
+			.merkled_header().build()
+			.build()
+			.into()
+	}
+
+	#[test]
+	fn block_nullifiers_uniqueness_works() {
+		// sprout && sapling nullifiers are disjoint, even if they have the same bit pattern
+		let block = block_with(vec![sprout_tx([1; 32]), sapling_tx([1; 32]), sapling_tx([2; 32])]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block).check(), Ok(()));
+
+		let block = block_with(vec![sapling_tx([1; 32]), sprout_tx([2; 32]), sapling_tx([1; 32])]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block).check(),
+			Err(Error::Transaction(2, TransactionError::SaplingDeclared(H256::from(&[1; 32][..])))));
+
+		let block = block_with(vec![sprout_tx([1; 32]), sprout_tx([1; 32])]);
+		assert_eq!(BlockNullifiersUniqueness::new(&block).check(),
+			Err(Error::Transaction(1, TransactionError::JoinSplitDeclared(H256::from(&[1; 32][..])))));
+	}
+}
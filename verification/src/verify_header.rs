@@ -103,6 +103,7 @@ impl<'a> HeaderVersion<'a> {
 pub struct HeaderEquihashSolution<'a> {
 	header: &'a IndexedBlockHeader,
 	equihash_params: Option<(u32, u32)>,
+	solution_size: Option<usize>,
 }
 
 impl<'a> HeaderEquihashSolution<'a> {
@@ -110,10 +111,18 @@ impl<'a> HeaderEquihashSolution<'a> {
 		HeaderEquihashSolution {
 			header,
 			equihash_params: consensus.equihash_params,
+			solution_size: consensus.equihash_solution_size(),
 		}
 	}
 
 	fn check(&self) -> Result<(), Error> {
+		if let Some(solution_size) = self.solution_size {
+			let actual = self.header.raw.solution.as_ref().len();
+			if actual != solution_size {
+				return Err(Error::InvalidEquihashSolutionSize { expected: solution_size, actual: actual });
+			}
+		}
+
 		if let Some(equihash_params) = self.equihash_params {
 			if !verify_block_equihash_solution(equihash_params, &self.header.raw) {
 				return Err(Error::InvalidEquihashSolution);
@@ -130,7 +139,7 @@ mod tests {
 
 	use network::{Network, ConsensusParams};
 	use error::Error;
-	use super::HeaderVersion;
+	use super::{HeaderVersion, HeaderEquihashSolution};
 
 	#[test]
 	fn header_version_works() {
@@ -143,4 +152,20 @@ mod tests {
 		assert_eq!(HeaderVersion::new(&test_data::block_builder().header().version(consensus.min_block_version() + 1)
 			.build().build().block_header.into(), &consensus).check(), Ok(()));
 	}
+
+	#[test]
+	fn header_equihash_solution_size_works() {
+		// default solution has mainnet size and is invalid
+		let header = test_data::block_builder().header().build().build().block_header.into();
+
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		assert_eq!(HeaderEquihashSolution::new(&header, &consensus).check(), Err(Error::InvalidEquihashSolution));
+
+		let consensus = ConsensusParams::new(Network::Regtest);
+		assert_eq!(HeaderEquihashSolution::new(&header, &consensus).check(),
+			Err(Error::InvalidEquihashSolutionSize { expected: 36, actual: 1344 }));
+
+		let consensus = ConsensusParams::new(Network::Unitest);
+		assert_eq!(HeaderEquihashSolution::new(&header, &consensus).check(), Ok(()));
+	}
 }
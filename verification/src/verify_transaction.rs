@@ -1,12 +1,11 @@
 use std::{collections::HashMap, ops};
-use ser::Serializable;
-use chain::{IndexedTransaction, BTC_TX_VERSION, OVERWINTER_TX_VERSION,
-	OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID};
+use chain::{Amount, IndexedTransaction, BTC_TX_VERSION, OVERWINTER_TX_VERSION,
+	OVERWINTER_TX_VERSION_GROUP_ID, SAPLING_TX_VERSION_GROUP_ID, NU5_TX_VERSION_GROUP_ID};
 use network::{ConsensusParams};
 use storage::NoopStore;
 use sigops::transaction_sigops;
 use error::TransactionError;
-use constants::{MIN_COINBASE_SIZE, MAX_COINBASE_SIZE};
+use constants::{MIN_COINBASE_SIZE, MAX_COINBASE_SIZE, MAX_TRANSACTION_EXPIRY_HEIGHT};
 
 pub struct TransactionVerifier<'a> {
 	pub version: TransactionVersion<'a>,
@@ -30,7 +29,7 @@ impl<'a> TransactionVerifier<'a> {
 		trace!(target: "verification", "Tx pre-verification {}", transaction.hash.to_reversed_str());
 		TransactionVerifier {
 			version: TransactionVersion::new(transaction),
-			expiry: TransactionExpiry::new(transaction, consensus),
+			expiry: TransactionExpiry::new(transaction),
 			empty: TransactionEmpty::new(transaction),
 			null_non_coinbase: TransactionNullNonCoinbase::new(transaction),
 			oversized_coinbase: TransactionOversizedCoinbase::new(transaction, MIN_COINBASE_SIZE..MAX_COINBASE_SIZE),
@@ -87,12 +86,12 @@ impl<'a> MemoryPoolTransactionVerifier<'a> {
 		trace!(target: "verification", "Mempool-Tx pre-verification {}", transaction.hash.to_reversed_str());
 		MemoryPoolTransactionVerifier {
 			version: TransactionVersion::new(transaction),
-			expiry: TransactionExpiry::new(transaction, consensus),
+			expiry: TransactionExpiry::new(transaction),
 			empty: TransactionEmpty::new(transaction),
 			null_non_coinbase: TransactionNullNonCoinbase::new(transaction),
 			is_coinbase: TransactionMemoryPoolCoinbase::new(transaction),
 			size: TransactionAbsoluteSize::new(transaction, consensus),
-			sigops: TransactionSigops::new(transaction, consensus.max_block_sigops()),
+			sigops: TransactionSigops::new(transaction, consensus.block_limits.max_block_sigops),
 			sapling: TransactionSapling::new(transaction),
 			join_split: TransactionJoinSplit::new(transaction),
 			output_value_overflow: TransactionOutputValueOverflow::new(transaction, consensus),
@@ -238,7 +237,7 @@ impl<'a> TransactionAbsoluteSize<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let size = self.transaction.raw.serialized_size();
+		let size = self.transaction.raw.size();
 		if size > self.absoute_max_size {
 			Err(TransactionError::MaxSize)
 		} else {
@@ -296,7 +295,8 @@ impl<'a> TransactionVersion<'a> {
 
 		let is_overwinter_group = self.transaction.raw.version_group_id == OVERWINTER_TX_VERSION_GROUP_ID;
 		let is_sapling_group = self.transaction.raw.version_group_id == SAPLING_TX_VERSION_GROUP_ID;
-		if !is_overwinter_group && !is_sapling_group {
+		let is_nu5_group = self.transaction.raw.version_group_id == NU5_TX_VERSION_GROUP_ID;
+		if !is_overwinter_group && !is_sapling_group && !is_nu5_group {
 			return Err(TransactionError::InvalidVersionGroup);
 		}
 
@@ -314,6 +314,10 @@ impl<'a> TransactionVersion<'a> {
 
 /// A coinbase transaction MUST NOT have any JoinSplit descriptions.
 /// A coinbase transaction cannot have spend descriptions or output descriptions.
+/// A coinbase transaction cannot enable Orchard spends or have Orchard actions.
+///
+/// Since Heartwood (ZIP-213), shielded coinbase outputs are allowed if they could be decrypted with
+/// the all-zero outgoing viewing key. Note decryption isn't implemented, so they're still rejected.
 pub struct TransactionNonTransparentCoinbase<'a> {
 	transaction: &'a IndexedTransaction,
 }
@@ -335,6 +339,11 @@ impl<'a> TransactionNonTransparentCoinbase<'a> {
 					return Err(TransactionError::NonTransparentCoinbase);
 				}
 			}
+			if let Some(ref orchard) = self.transaction.raw.orchard {
+				if orchard.spends_enabled() || !orchard.actions.is_empty() {
+					return Err(TransactionError::NonTransparentCoinbase);
+				}
+			}
 		}
 
 		Ok(())
@@ -398,7 +407,7 @@ impl<'a> TransactionJoinSplit<'a> {
 /// Check for overflow of output values.
 pub struct TransactionOutputValueOverflow<'a> {
 	transaction: &'a IndexedTransaction,
-	max_value: i64,
+	max_value: Amount,
 }
 
 impl<'a> TransactionOutputValueOverflow<'a> {
@@ -410,50 +419,32 @@ impl<'a> TransactionOutputValueOverflow<'a> {
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let mut total_output = 0i64;
+		let overflow = || TransactionError::OutputValueOverflow;
+		let mut total_output = Amount::zero();
 
 		// each output should be less than max_value
 		// the sum of all outputs should be less than max value
 		for output in &self.transaction.raw.outputs {
-			if output.value > self.max_value as u64 {
-				return Err(TransactionError::OutputValueOverflow)
-			}
-
-			total_output = match total_output.checked_add(output.value as i64) {
-				Some(total_output) if total_output <= self.max_value => total_output,
-				_ => return Err(TransactionError::OutputValueOverflow),
-			};
-		}
-
-		if let Some(ref sapling) = self.transaction.raw.sapling {
-			// check that sapling amount is within limits
-			if sapling.balancing_value < -self.max_value || sapling.balancing_value > self.max_value {
-				return Err(TransactionError::OutputValueOverflow);
-			}
-
-			// negative sapling amount takes value from transparent pool
-			if sapling.balancing_value < 0 {
-				total_output = match total_output.checked_add(-sapling.balancing_value) {
-					Some(total_output) if total_output <= self.max_value => total_output,
-					_ => return Err(TransactionError::OutputValueOverflow),
-				};
+			let value = money_range(Amount::from_u64(output.value), self.max_value).ok_or_else(overflow)?;
+			total_output = money_range(total_output.checked_add(value), self.max_value).ok_or_else(overflow)?;
+		}
+
+		// negative shielded balance takes value from transparent pool
+		let sapling_balance = self.transaction.raw.sapling.as_ref().map(|sapling| sapling.balancing_value);
+		let orchard_balance = self.transaction.raw.orchard.as_ref().map(|orchard| orchard.value_balance);
+		for balance in sapling_balance.into_iter().chain(orchard_balance) {
+			// check that shielded amount is within limits
+			let balance = money_range(Amount::from_zatoshis(balance), self.max_value).ok_or_else(overflow)?;
+			if balance.is_negative() {
+				total_output = money_range(total_output.checked_add(-balance), self.max_value).ok_or_else(overflow)?;
 			}
 		}
 
 		if let Some(ref join_split) = self.transaction.raw.join_split {
 			for desc in &join_split.descriptions {
-				if desc.value_pub_old > self.max_value as u64 {
-					return Err(TransactionError::OutputValueOverflow);
-				}
-
-				if desc.value_pub_new > self.max_value as u64 {
-					return Err(TransactionError::OutputValueOverflow);
-				}
-
-				total_output = match total_output.checked_add(desc.value_pub_old as i64) {
-					Some(total_output) if total_output <= self.max_value => total_output,
-					_ => return Err(TransactionError::OutputValueOverflow),
-				};
+				let value_pub_old = money_range(Amount::from_u64(desc.value_pub_old), self.max_value).ok_or_else(overflow)?;
+				money_range(Amount::from_u64(desc.value_pub_new), self.max_value).ok_or_else(overflow)?;
+				total_output = money_range(total_output.checked_add(value_pub_old), self.max_value).ok_or_else(overflow)?;
 			}
 		}
 
@@ -464,19 +455,20 @@ impl<'a> TransactionOutputValueOverflow<'a> {
 /// Check for overflow of (known) input values.
 pub struct TransactionInputValueOverflow<'a> {
 	transaction: &'a IndexedTransaction,
-	max_value: u64,
+	max_value: Amount,
 }
 
 impl<'a> TransactionInputValueOverflow<'a> {
 	fn new(transaction: &'a IndexedTransaction, consensus: &'a ConsensusParams) -> Self {
 		TransactionInputValueOverflow {
 			transaction,
-			max_value: consensus.max_transaction_value() as u64,
+			max_value: consensus.max_transaction_value(),
 		}
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		let mut total_input = 0u64;
+		let overflow = || TransactionError::InputValueOverflow;
+		let mut total_input = Amount::zero();
 
 		// inputs values are unknown at verification stage
 
@@ -484,24 +476,18 @@ impl<'a> TransactionInputValueOverflow<'a> {
 		// their sum should be within money range
 		if let Some(ref join_split) = self.transaction.raw.join_split {
 			for desc in &join_split.descriptions {
-				if desc.value_pub_new > self.max_value {
-					return Err(TransactionError::InputValueOverflow);
-				}
-
-				total_input = match total_input.checked_add(desc.value_pub_new) {
-					Some(total_input) if total_input <= self.max_value => total_input,
-					_ => return Err(TransactionError::InputValueOverflow),
-				};
+				let value_pub_new = money_range(Amount::from_u64(desc.value_pub_new), self.max_value).ok_or_else(overflow)?;
+				total_input = money_range(total_input.checked_add(value_pub_new), self.max_value).ok_or_else(overflow)?;
 			}
 		}
 
-		if let Some(ref sapling) = self.transaction.raw.sapling {
-			// positive sapling amount adds value to the transparent pool
-			if sapling.balancing_value > 0 {
-				match total_input.checked_add(sapling.balancing_value as u64) {
-					Some(total_input) if total_input <= self.max_value => (),
-					_ => return Err(TransactionError::InputValueOverflow),
-				};
+		// positive shielded balance adds value to the transparent pool
+		let sapling_balance = self.transaction.raw.sapling.as_ref().map(|sapling| sapling.balancing_value);
+		let orchard_balance = self.transaction.raw.orchard.as_ref().map(|orchard| orchard.value_balance);
+		for balance in sapling_balance.into_iter().chain(orchard_balance) {
+			let balance = money_range(Amount::from_zatoshis(balance), self.max_value).ok_or_else(overflow)?;
+			if !balance.is_negative() {
+				total_input = money_range(total_input.checked_add(balance), self.max_value).ok_or_else(overflow)?;
 			}
 		}
 
@@ -509,22 +495,25 @@ impl<'a> TransactionInputValueOverflow<'a> {
 	}
 }
 
-/// Check that transaction expiry height is too high.
+/// Amount, if its absolute value doesn't exceed `max_value`.
+fn money_range(amount: Option<Amount>, max_value: Amount) -> Option<Amount> {
+	amount.and_then(|amount| if amount <= max_value && -amount <= max_value { Some(amount) } else { None })
+}
+
+/// Check that transaction expiry height isn't too high.
 pub struct TransactionExpiry<'a> {
 	transaction: &'a IndexedTransaction,
-	height_threshold: u32,
 }
 
 impl<'a> TransactionExpiry<'a> {
-	fn new(transaction: &'a IndexedTransaction, consensus: &'a ConsensusParams) -> Self {
+	fn new(transaction: &'a IndexedTransaction) -> Self {
 		TransactionExpiry {
 			transaction,
-			height_threshold: consensus.transaction_expiry_height_threshold(),
 		}
 	}
 
 	fn check(&self) -> Result<(), TransactionError> {
-		if self.transaction.raw.overwintered && self.transaction.raw.expiry_height >= self.height_threshold {
+		if self.transaction.raw.overwintered && self.transaction.raw.expiry_height > MAX_TRANSACTION_EXPIRY_HEIGHT {
 			return Err(TransactionError::ExpiryHeightTooHigh);
 		}
 
@@ -616,9 +605,11 @@ mod tests {
 	extern crate test_data;
 
 	use chain::{BTC_TX_VERSION, OVERWINTER_TX_VERSION, OVERWINTER_TX_VERSION_GROUP_ID,
-		SAPLING_TX_VERSION_GROUP_ID, Sapling, JoinSplit, JoinSplitDescription};
+		SAPLING_TX_VERSION_GROUP_ID, Sapling, JoinSplit, JoinSplitDescription, Transaction, Orchard,
+		ORCHARD_FLAG_SPENDS_ENABLED, ORCHARD_FLAG_OUTPUTS_ENABLED};
 	use network::{Network, ConsensusParams};
 	use error::TransactionError;
+	use constants::MAX_TRANSACTION_EXPIRY_HEIGHT;
 	use super::{TransactionEmpty, TransactionVersion, TransactionNonTransparentCoinbase,
 		TransactionOutputValueOverflow, TransactionExpiry, TransactionSapling, TransactionJoinSplit,
 		TransactionInputValueOverflow, TransactionDuplicateInputs, TransactionDuplicateJoinSplitNullifiers,
@@ -695,6 +686,16 @@ mod tests {
 			.set_sapling(Sapling { outputs: vec![Default::default()], ..Default::default() }).into()).check(),
 			Err(TransactionError::NonTransparentCoinbase));
 
+		let mut coinbase: Transaction = test_data::TransactionBuilder::coinbase().into();
+		coinbase.orchard = Some(Orchard { flags: ORCHARD_FLAG_SPENDS_ENABLED, ..Default::default() });
+		assert_eq!(TransactionNonTransparentCoinbase::new(&coinbase.clone().into()).check(),
+			Err(TransactionError::NonTransparentCoinbase));
+		coinbase.orchard = Some(Orchard { flags: ORCHARD_FLAG_OUTPUTS_ENABLED, actions: vec![Default::default()], ..Default::default() });
+		assert_eq!(TransactionNonTransparentCoinbase::new(&coinbase.clone().into()).check(),
+			Err(TransactionError::NonTransparentCoinbase));
+		coinbase.orchard = Some(Orchard { flags: ORCHARD_FLAG_OUTPUTS_ENABLED, ..Default::default() });
+		assert_eq!(TransactionNonTransparentCoinbase::new(&coinbase.into()).check(), Ok(()));
+
 		assert_eq!(TransactionNonTransparentCoinbase::new(&test_data::TransactionBuilder::coinbase()
 			.set_sapling(Default::default()).into()).check(),
 			Ok(()));
@@ -712,7 +713,7 @@ mod tests {
 	#[test]
 	fn transaction_output_value_overflow_works() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
-		let max_value = consensus.max_transaction_value();
+		let max_value = consensus.max_transaction_value().zatoshis();
 
 		assert_eq!(TransactionOutputValueOverflow::new(&test_data::TransactionBuilder::with_output(max_value as u64 + 1)
 			.into(), &consensus).check(), Err(TransactionError::OutputValueOverflow));
@@ -790,7 +791,7 @@ mod tests {
 	#[test]
 	fn transaction_input_value_overflow_works() {
 		let consensus = ConsensusParams::new(Network::Mainnet);
-		let max_value = consensus.max_transaction_value();
+		let max_value = consensus.max_transaction_value().zatoshis();
 
 		assert_eq!(TransactionInputValueOverflow::new(&test_data::TransactionBuilder::with_join_split(JoinSplit {
 				descriptions: vec![JoinSplitDescription {
@@ -832,14 +833,12 @@ mod tests {
 
 	#[test]
 	fn transaction_expiry_works() {
-		let consensus = ConsensusParams::new(Network::Mainnet);
-
 		assert_eq!(TransactionExpiry::new(&test_data::TransactionBuilder::overwintered()
-			.set_expiry_height(consensus.transaction_expiry_height_threshold() - 1).into(), &consensus).check(),
+			.set_expiry_height(MAX_TRANSACTION_EXPIRY_HEIGHT).into()).check(),
 			Ok(()));
 
 		assert_eq!(TransactionExpiry::new(&test_data::TransactionBuilder::overwintered()
-			.set_expiry_height(consensus.transaction_expiry_height_threshold()).into(), &consensus).check(),
+			.set_expiry_height(MAX_TRANSACTION_EXPIRY_HEIGHT + 1).into()).check(),
 			Err(TransactionError::ExpiryHeightTooHigh));
 	}
 
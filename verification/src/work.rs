@@ -33,7 +33,10 @@ pub fn is_valid_proof_of_work(max_work_bits: Compact, bits: Compact, hash: &H256
 	target <= maximum && value <= target
 }
 
-/// Returns work required for given header
+/// Returns work required for given header.
+/// Zcash adjusts difficulty on every block (DigiShield v3): the mean target of the averaging window
+/// is scaled by the damped ratio of the actual window timespan (between median times past) to the expected one.
+/// https://zips.z.cash/protocol/protocol.pdf#diffadjustment
 pub fn work_required(parent_hash: H256, time: u32, height: u32, store: &BlockHeaderProvider, consensus: &ConsensusParams) -> Compact {
 	let max_bits = consensus.network.max_bits().into();
 
@@ -45,22 +48,23 @@ pub fn work_required(parent_hash: H256, time: u32, height: u32, store: &BlockHea
 	let parent_header = store.block_header(parent_hash.clone().into()).expect("self.height != 0; qed");
 
 	// Special difficulty rule for testnet:
-	// If the new block's timestamp is more than 6 * 2.5 minutes
+	// If the new block's timestamp is more than 6 target spacings after the parent's
 	// then allow mining of a min-difficulty block.
 	if let Some(allow_min_difficulty_after_height) = consensus.pow_allow_min_difficulty_after_height {
-		if height >= allow_min_difficulty_after_height {
-			if time > parent_header.raw.time + consensus.pow_target_spacing * 6 {
+		if height > allow_min_difficulty_after_height {
+			if time > parent_header.raw.time + consensus.target_spacing(height) * 6 {
 				return max_bits;
 			}
 		}
 	}
 
-	// Find the first block in the averaging interval + calculate total difficulty for blocks in the interval
-	let (count, oldest_hash, bits_total) = BlockAncestors::new(parent_header.raw.previous_header_hash.into(), store)
-		.take(consensus.pow_averaging_window as usize - 1)
-		.fold((1, Default::default(), U256::from(parent_header.raw.bits)), |(count, _, bits_total), header|
+	// Find the block before the averaging window + calculate total difficulty for blocks in the window.
+	// Median time past of the block before the window is the start of the window => it must be known
+	let (count, oldest_hash, bits_total) = BlockAncestors::new(parent_hash.clone().into(), store)
+		.take(consensus.pow_averaging_window as usize)
+		.fold((0, Default::default(), U256::zero()), |(count, _, bits_total), header|
 			(count + 1, header.raw.previous_header_hash, bits_total.overflowing_add(header.raw.bits.into()).0));
-	if count != consensus.pow_averaging_window {
+	if count != consensus.pow_averaging_window || store.block_header(oldest_hash.clone().into()).is_none() {
 		return max_bits;
 	}
 
@@ -68,27 +72,29 @@ pub fn work_required(parent_hash: H256, time: u32, height: u32, store: &BlockHea
 	let parent_mtp = median_timestamp_inclusive(parent_hash, store);
 	let oldest_mtp = median_timestamp_inclusive(oldest_hash, store);
 
-	calculate_work_required(bits_avg, parent_mtp, oldest_mtp, consensus, max_bits)
+	calculate_work_required(bits_avg, parent_mtp, oldest_mtp, height, consensus, max_bits)
 }
 
-fn calculate_work_required(bits_avg: U256, parent_mtp: u32, oldest_mtp: u32, consensus: &ConsensusParams, max_bits: Compact) -> Compact {
+fn calculate_work_required(bits_avg: U256, parent_mtp: u32, oldest_mtp: u32, height: u32, consensus: &ConsensusParams, max_bits: Compact) -> Compact {
+	// expected timespan is halved after Blossom
+	let averaging_window_timespan = consensus.averaging_window_timespan(height) as i64;
+
 	// Limit adjustment step
 	// Use medians to prevent time-warp attacks
-	let actual_timespan = parent_mtp - oldest_mtp;
+	let actual_timespan = parent_mtp as i64 - oldest_mtp as i64;
 
-	let mut actual_timespan = consensus.averaging_window_timespan() as i64 +
-		(actual_timespan as i64 - consensus.averaging_window_timespan() as i64) / 4;
+	let mut actual_timespan = averaging_window_timespan + (actual_timespan - averaging_window_timespan) / 4;
 
-	if actual_timespan < consensus.min_actual_timespan() as i64 {
-		actual_timespan = consensus.min_actual_timespan() as i64;
+	if actual_timespan < consensus.min_actual_timespan(height) as i64 {
+		actual_timespan = consensus.min_actual_timespan(height) as i64;
 	}
-	if actual_timespan > consensus.max_actual_timespan() as i64 {
-		actual_timespan = consensus.max_actual_timespan() as i64;
+	if actual_timespan > consensus.max_actual_timespan(height) as i64 {
+		actual_timespan = consensus.max_actual_timespan(height) as i64;
 	}
 
 	// Retarget
 	let actual_timespan = actual_timespan as u32;
-	let mut bits_new = bits_avg / consensus.averaging_window_timespan().into();
+	let mut bits_new = bits_avg / (averaging_window_timespan as u32).into();
 	bits_new = bits_new * actual_timespan.into();
 
 	if bits_new > max_bits.into() {
@@ -174,6 +180,90 @@ mod tests {
 		assert_eq!(expected, actual);
 	}
 
+	#[test]
+	fn testnet_min_difficulty_work_required_works() {
+		let consensus = ConsensusParams::new(Network::Testnet);
+		let max_bits: Compact = Network::Testnet.max_bits().into();
+
+		// evenly-spaced blocks with equal difficulty
+		let mut header_provider = MemoryBlockHeaderProvider::default();
+		for i in 0..consensus.pow_averaging_window + 1 {
+			let header = BlockHeader {
+				time: 1_500_000_000 + i * consensus.pow_target_spacing,
+				bits: Compact::new(0x1e0fffff),
+				version: 4,
+				previous_header_hash: header_provider.by_height.last().map(|h| h.hash()).unwrap_or_default(),
+				merkle_root_hash: 0.into(),
+				nonce: 0.into(),
+				final_sapling_root: Default::default(),
+				solution: Default::default(),
+			};
+			header_provider.insert(header);
+		}
+
+		let parent_hash = header_provider.last().hash();
+		let parent_time = header_provider.last().time;
+		let work = |time, height| work_required(parent_hash.clone(), time, height, &header_provider, &consensus);
+
+		// min difficulty is allowed after block 299187
+		assert_eq!(work(parent_time + 150 * 6 + 1, 299_188), max_bits);
+		assert!(work(parent_time + 150 * 6, 299_188) != max_bits);
+		assert!(work(parent_time + 150 * 6 + 1, 299_187) != max_bits);
+
+		// target spacing is halved after Blossom
+		assert_eq!(work(parent_time + 75 * 6 + 1, 584_000), max_bits);
+		assert!(work(parent_time + 75 * 6 + 1, 583_999) != max_bits);
+	}
+
+	#[test]
+	fn work_required_needs_block_before_averaging_window() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let max_bits: Compact = Network::Mainnet.max_bits().into();
+
+		let mut header_provider = MemoryBlockHeaderProvider::default();
+		for i in 0..consensus.pow_averaging_window + 1 {
+			let header = BlockHeader {
+				time: 1_500_000_000 + i * consensus.pow_target_spacing,
+				bits: Compact::new(0x1e0fffff),
+				version: 4,
+				previous_header_hash: header_provider.by_height.last().map(|h| h.hash()).unwrap_or_default(),
+				merkle_root_hash: 0.into(),
+				nonce: 0.into(),
+				final_sapling_root: Default::default(),
+				solution: Default::default(),
+			};
+			header_provider.insert(header);
+		}
+
+		// window of blocks [1; 17] is preceded by genesis
+		let work = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
+			&header_provider, &consensus);
+		assert!(work != max_bits);
+
+		// window of blocks [0; 16] has no preceding block
+		let parent_hash = header_provider.by_height[consensus.pow_averaging_window as usize - 1].hash();
+		let work = work_required(parent_hash, header_provider.last().time, consensus.pow_averaging_window, &header_provider, &consensus);
+		assert_eq!(work, max_bits);
+	}
+
+	#[test]
+	fn work_required_uses_blossom_target_spacing() {
+		let consensus = ConsensusParams::new(Network::Mainnet);
+		let max_bits: Compact = Network::Mainnet.max_bits().into();
+		let bits_avg: U256 = Compact::new(0x1e0fffff).into();
+		let post_blossom_timespan = consensus.pow_averaging_window * 75;
+
+		// blocks are mined every 75 seconds => difficulty is unchanged after Blossom
+		let mut bits_expected = bits_avg / post_blossom_timespan.into();
+		bits_expected = bits_expected * post_blossom_timespan.into();
+		assert_eq!(calculate_work_required(bits_avg, 10_000 + post_blossom_timespan, 10_000, 653_600, &consensus, max_bits),
+			bits_expected.into());
+
+		// ...but is increased before Blossom
+		let work: U256 = calculate_work_required(bits_avg, 10_000 + post_blossom_timespan, 10_000, 653_599, &consensus, max_bits).into();
+		assert!(work < bits_avg);
+	}
+
 	// original test link:
 	// https://github.com/Bitcoin-ABC/bitcoin-abc/blob/d8eac91f8d16716eed0ad11ccac420122280bb13/src/test/pow_tests.cpp#L193
 	#[test]
@@ -217,15 +307,15 @@ mod tests {
 		let expected = calculate_work_required(bits_avg,
 			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
 			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
-			&consensus, max_bits.into());
+			header_provider.next_height(), &consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
 		assert_eq!(actual, expected);
 
 		// Result should be unchanged, modulo integer division precision loss
 		let mut bits_expected: U256 = Compact::new(0x1e7fffff).into();
-		bits_expected = bits_expected / consensus.averaging_window_timespan().into();
-		bits_expected = bits_expected * consensus.averaging_window_timespan().into();
+		bits_expected = bits_expected / consensus.averaging_window_timespan(last_block + 1).into();
+		bits_expected = bits_expected * consensus.averaging_window_timespan(last_block + 1).into();
 		assert_eq!(work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus),
 			bits_expected.into());
@@ -241,7 +331,7 @@ mod tests {
 		let expected = calculate_work_required(bits_avg,
 			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
 			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
-			&consensus, max_bits.into());
+			header_provider.next_height(), &consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
 		assert_eq!(actual, expected);
@@ -261,7 +351,7 @@ mod tests {
 		let expected = calculate_work_required(bits_avg.into(),
 			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
 			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
-			&consensus, max_bits.into());
+			header_provider.next_height(), &consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
 		assert!(actual != expected);
@@ -271,7 +361,7 @@ mod tests {
 		let expected = calculate_work_required(bits_avg,
 			median_timestamp_inclusive(header_provider.by_height[last_block as usize].hash(), &header_provider),
 			median_timestamp_inclusive(header_provider.by_height[first_block as usize].hash(), &header_provider),
-			&consensus, max_bits.into());
+			header_provider.next_height(), &consensus, max_bits.into());
 		let actual = work_required(header_provider.last().hash(), header_provider.next_time(), header_provider.next_height(),
 			&header_provider, &consensus);
 		assert_eq!(actual, expected);
@@ -0,0 +1,33 @@
+use clap::ArgMatches;
+use config::Config;
+use util::init_db;
+use verification;
+
+pub fn audit_supply(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+	try!(init_db(&cfg));
+
+	let best_block_number = cfg.db.best_block().number;
+	let height = match matches.value_of("HEIGHT") {
+		Some(height) => height.parse().map_err(|e| format!("Invalid block number: {}", e))?,
+		None => best_block_number,
+	};
+	if height > best_block_number {
+		return Err(format!("Block {} is above the best block {}", height, best_block_number));
+	}
+
+	info!("Auditing value supply up to block {}", height);
+	let audit = verification::audit_supply(cfg.db.as_store(), &cfg.consensus, height)
+		.map_err(|err| format!("Supply audit has failed: {}", err))?;
+
+	info!("Supply audit has passed at block {}", audit.height);
+	info!("Scheduled subsidy: {} zatoshis", audit.scheduled_subsidy);
+	info!("Unclaimed subsidy: {} zatoshis", audit.unclaimed_subsidy());
+	info!("Total fees: {} zatoshis", audit.fees);
+	info!("Total supply: {} zatoshis", audit.total_supply());
+	info!("  transparent: {} zatoshis", audit.transparent_pool);
+	info!("  sprout: {} zatoshis", audit.sprout_pool);
+	info!("  sapling: {} zatoshis", audit.sapling_pool);
+	info!("  orchard: {} zatoshis", audit.orchard_pool);
+
+	Ok(())
+}
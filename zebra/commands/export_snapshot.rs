@@ -0,0 +1,21 @@
+use std::fs::File;
+use std::io::BufWriter;
+use clap::ArgMatches;
+use config::Config;
+use util::init_db;
+
+pub fn export_snapshot(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+	try!(init_db(&cfg));
+
+	let path = matches.value_of("PATH").expect("PATH is required in cli.yml; qed");
+	let file = File::create(path).map_err(|err| format!("Failed to create snapshot file: {}", err))?;
+
+	info!("Exporting snapshot of the chain state at block {}", cfg.db.best_block().number);
+	let snapshot = cfg.db.export_snapshot(&mut BufWriter::new(file))
+		.map_err(|err| format!("Snapshot export has failed: {}", err))?;
+
+	info!("Exported snapshot at block {} ({})", snapshot.height, snapshot.block_hash.reversed());
+	info!("Snapshot digest: {}", snapshot.digest.reversed());
+
+	Ok(())
+}
@@ -0,0 +1,30 @@
+use std::fs::File;
+use std::io::BufReader;
+use clap::ArgMatches;
+use config::Config;
+use util::{init_db, check_db_network};
+
+pub fn load_snapshot(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+	try!(check_db_network(&cfg));
+	if !matches.is_present("trust-snapshot") {
+		return Err("Blocks before the snapshot block are not validated, so the snapshot must be explicitly trusted with --trust-snapshot".into());
+	}
+	if cfg.db.block_hash(0).is_some() {
+		return Err("Snapshot can only be loaded into the empty database".into());
+	}
+
+	let path = matches.value_of("PATH").expect("PATH is required in cli.yml; qed");
+	let file = File::open(path).map_err(|err| format!("Failed to open snapshot file: {}", err))?;
+
+	info!("Loading snapshot of the chain state");
+	let genesis_hash = cfg.network.genesis_block().hash().clone();
+	let snapshot = cfg.db.import_snapshot(&mut BufReader::new(file), &genesis_hash)
+		.map_err(|err| format!("Snapshot loading has failed (the database directory must be removed before retrying): {}", err))?;
+
+	info!("Loaded snapshot at block {} ({})", snapshot.height, snapshot.block_hash.reversed());
+	info!("Snapshot digest: {}", snapshot.digest.reversed());
+	info!("Blocks before the snapshot block are not verified by the node, compare the digest with the trusted one");
+
+	// genesis block is checked against the snapshot chain
+	init_db(&cfg)
+}
@@ -1,7 +1,17 @@
+mod audit_supply;
+mod export_snapshot;
 mod import;
+mod load_snapshot;
+mod reindex;
 mod start;
 mod rollback;
+mod replay;
 
+pub use self::audit_supply::audit_supply;
+pub use self::export_snapshot::export_snapshot;
 pub use self::import::import;
+pub use self::load_snapshot::load_snapshot;
+pub use self::reindex::reindex;
 pub use self::start::start;
-pub use self::rollback::rollback;
\ No newline at end of file
+pub use self::rollback::rollback;
+pub use self::replay::replay;
\ No newline at end of file
@@ -0,0 +1,62 @@
+use std::fs;
+use chain::IndexedBlock;
+use db;
+use storage::BlockProvider;
+use sync::{create_sync_blocks_writer, Error};
+use config::{Config, Reindex, ReindexMode};
+use util::init_db;
+
+/// Rebuild the database by verifying && appending blocks of the moved aside database. The moved database is
+/// removed when all blocks are appended.
+pub fn reindex(cfg: &Config, reindex: &Reindex) -> Result<(), String> {
+	try!(init_db(cfg));
+
+	let mut writer = create_sync_blocks_writer(cfg.db.clone(), cfg.consensus.clone(), cfg.verification_params.clone());
+	let mut counter = 0;
+	{
+		let mut append_block = |block: IndexedBlock| {
+			let block_hash = block.hash().reversed();
+			match writer.append_block(block) {
+				Ok(_) => {
+					counter += 1;
+					if counter % 1000 == 0 {
+						info!(target: "sync", "Reindexed {} blocks", counter);
+					}
+					Ok(())
+				},
+				Err(Error::TooManyOrphanBlocks) => Err("Too many orphan (unordered) blocks".to_owned()),
+				Err(err) => Err(format!("Cannot append block: {:?}. Block: {}", err, block_hash)),
+			}
+		};
+
+		match reindex.mode {
+			ReindexMode::Full => {
+				info!("Reindexing all blocks of raw blocks files");
+				let blocks = db::BlockChainDatabase::stored_blocks(&reindex.source)
+					.map_err(|err| format!("Failed to open raw blocks files: {}", err))?;
+				for block in blocks {
+					append_block(block.map_err(|err| format!("Cannot read block: {}", err))?)?;
+				}
+			},
+			ReindexMode::ChainState => {
+				let source = db::BlockChainDatabase::open_at_path(&reindex.source, cfg.db_settings.clone())
+					.map_err(|err| format!("Failed to open reindexed database: {}", err))?;
+
+				let best_block = source.best_block();
+				info!("Reindexing {} blocks of the canon chain", best_block.number + 1);
+				for number in 0..best_block.number + 1 {
+					let block = source.block_hash(number)
+						.and_then(|hash| source.stored_block(&hash))
+						.ok_or_else(|| format!("Block {} is missing in the reindexed database", number))?;
+					append_block(block)?;
+				}
+			},
+		}
+	}
+
+	fs::remove_dir_all(&reindex.source)
+		.map_err(|err| format!("Failed to remove reindexed database at {}: {}", reindex.source.display(), err))?;
+	info!("Finished reindex of {} blocks", counter);
+
+	Ok(())
+}
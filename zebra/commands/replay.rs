@@ -0,0 +1,200 @@
+use std::{thread, time};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use clap::ArgMatches;
+use message::types;
+use p2p::{self, InboundSyncConnectionRef, OutboundSyncConnection, Misbehaviour, PeerId, RecordedEvent};
+use sync::{create_sync_peers, create_local_sync_node, create_sync_connection_factory, LocalNodeRef};
+use config::Config;
+use util::init_db;
+
+/// Interval between checks of the verification queue.
+const VERIFICATION_POLL_INTERVAL_MS: u64 = 10;
+
+/// Outbound connection of the replayed session. Nothing is sent anywhere, since all
+/// responses of the remote peer are already in the recording.
+struct ReplayOutboundConnection {
+	peer: PeerId,
+	closed: AtomicBool,
+}
+
+impl ReplayOutboundConnection {
+	fn new(peer: PeerId) -> Self {
+		ReplayOutboundConnection {
+			peer: peer,
+			closed: AtomicBool::new(false),
+		}
+	}
+
+	fn is_closed(&self) -> bool {
+		self.closed.load(Ordering::SeqCst)
+	}
+}
+
+impl OutboundSyncConnection for ReplayOutboundConnection {
+	fn send_inventory(&self, _message: &types::Inv) {
+		trace!(target: "replay", "Ignoring inv to recorded peer#{}", self.peer);
+	}
+
+	fn send_getdata(&self, _message: &types::GetData) {
+		trace!(target: "replay", "Ignoring getdata to recorded peer#{}", self.peer);
+	}
+
+	fn send_getblocks(&self, _message: &types::GetBlocks) {
+		trace!(target: "replay", "Ignoring getblocks to recorded peer#{}", self.peer);
+	}
+
+	fn send_getheaders(&self, _message: &types::GetHeaders) {
+		trace!(target: "replay", "Ignoring getheaders to recorded peer#{}", self.peer);
+	}
+
+	fn send_transaction(&self, _message: &types::Tx) {
+		trace!(target: "replay", "Ignoring tx to recorded peer#{}", self.peer);
+	}
+
+	fn send_block(&self, _message: &types::Block) {
+		trace!(target: "replay", "Ignoring block to recorded peer#{}", self.peer);
+	}
+
+	fn send_headers(&self, _message: &types::Headers) {
+		trace!(target: "replay", "Ignoring headers to recorded peer#{}", self.peer);
+	}
+
+	fn respond_headers(&self, _message: &types::Headers, _id: u32) {
+		trace!(target: "replay", "Ignoring headers response to recorded peer#{}", self.peer);
+	}
+
+	fn send_mempool(&self, _message: &types::MemPool) {
+		trace!(target: "replay", "Ignoring mempool to recorded peer#{}", self.peer);
+	}
+
+	fn send_filterload(&self, _message: &types::FilterLoad) {
+		trace!(target: "replay", "Ignoring filterload to recorded peer#{}", self.peer);
+	}
+
+	fn send_filteradd(&self, _message: &types::FilterAdd) {
+		trace!(target: "replay", "Ignoring filteradd to recorded peer#{}", self.peer);
+	}
+
+	fn send_filterclear(&self, _message: &types::FilterClear) {
+		trace!(target: "replay", "Ignoring filterclear to recorded peer#{}", self.peer);
+	}
+
+	fn send_merkleblock(&self, _message: &types::MerkleBlock) {
+		trace!(target: "replay", "Ignoring merkleblock to recorded peer#{}", self.peer);
+	}
+
+	fn send_sendheaders(&self, _message: &types::SendHeaders) {
+		trace!(target: "replay", "Ignoring sendheaders to recorded peer#{}", self.peer);
+	}
+
+	fn send_feefilter(&self, _message: &types::FeeFilter) {
+		trace!(target: "replay", "Ignoring feefilter to recorded peer#{}", self.peer);
+	}
+
+	fn send_notfound(&self, _message: &types::NotFound) {
+		trace!(target: "replay", "Ignoring notfound to recorded peer#{}", self.peer);
+	}
+
+	fn ignored(&self, _id: u32) {
+	}
+
+	fn note_block_delivered(&self) {
+	}
+
+	fn close(&self, misbehaviour: Misbehaviour) {
+		warn!(target: "replay", "Recorded peer#{} is disconnected because of {:?} misbehaviour", self.peer, misbehaviour);
+		self.closed.store(true, Ordering::SeqCst);
+	}
+}
+
+/// Replayed sync session.
+struct ReplaySession {
+	inbound: InboundSyncConnectionRef,
+	outbound: Arc<ReplayOutboundConnection>,
+	version: u32,
+}
+
+/// Waits until all headers, blocks and transactions, received so far, are verified, so that
+/// every message is processed in the same state on every replay.
+fn wait_verification(node: &LocalNodeRef) {
+	while node.is_verifying() {
+		thread::sleep(time::Duration::from_millis(VERIFICATION_POLL_INTERVAL_MS));
+	}
+}
+
+pub fn replay(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+	try!(init_db(&cfg));
+
+	let path = matches.value_of("PATH").expect("PATH is required in cli.yml; qed");
+	let (magic, events) = p2p::read_recording(path)?;
+	if magic != cfg.consensus.magic() {
+		return Err(format!("Recording has been made on the network with magic {}, but node is configured for {}",
+			magic, cfg.consensus.magic()));
+	}
+
+	let sync_peers = create_sync_peers();
+	let local_sync_node = create_local_sync_node(cfg.consensus.clone(), cfg.db.clone(), sync_peers.clone(), cfg.verification_params, cfg.blocks_memory_budget * 1024 * 1024, cfg.fee_policy, cfg.standardness_policy, cfg.block_limits);
+	let sync_connection_factory = create_sync_connection_factory(sync_peers, local_sync_node.clone());
+
+	let mut sessions: HashMap<PeerId, ReplaySession> = HashMap::new();
+	let mut messages = 0;
+	let mut failed = 0;
+	for event in events {
+		match event {
+			RecordedEvent::Connect { peer, name, version, version_message } => {
+				let outbound = Arc::new(ReplayOutboundConnection::new(peer));
+				let inbound = sync_connection_factory.create_sync_session(0, version_message.services(), outbound.clone());
+				inbound.start_sync_session(name, version_message);
+				sessions.insert(peer, ReplaySession {
+					inbound: inbound,
+					outbound: outbound,
+					version: version,
+				});
+			},
+			RecordedEvent::Message { peer, command, payload } => {
+				let is_closed = match sessions.get(&peer) {
+					Some(session) => session.outbound.is_closed(),
+					None => {
+						warn!(target: "replay", "Ignoring {} message from unknown recorded peer#{}", command, peer);
+						continue;
+					},
+				};
+
+				// node has closed the connection => remaining messages of the peer would never be received
+				if is_closed {
+					if let Some(session) = sessions.remove(&peer) {
+						session.inbound.close_session();
+					}
+					continue;
+				}
+
+				let session = sessions.get(&peer).expect("checked above; qed");
+				let state = session.inbound.sync_state();
+				messages += 1;
+				// responses are ignored by replay outbound connection => any id would do
+				if let Err(err) = p2p::dispatch_sync_message(&*session.inbound, &*state, &command, &payload, session.version, || 0) {
+					warn!(target: "replay", "Failed to process {} message from recorded peer#{}: {:?}", command, peer, err);
+					failed += 1;
+				}
+
+				wait_verification(&local_sync_node);
+			},
+			RecordedEvent::Disconnect { peer } => {
+				if let Some(session) = sessions.remove(&peer) {
+					session.inbound.close_session();
+				}
+			},
+		}
+	}
+
+	for (_, session) in sessions.drain() {
+		session.inbound.close_session();
+	}
+	wait_verification(&local_sync_node);
+
+	info!("Replayed {} p2p messages ({} failed). Best block: {}", messages, failed, cfg.db.best_block().number);
+
+	Ok(())
+}
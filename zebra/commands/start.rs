@@ -1,12 +1,15 @@
+use std::io;
 use std::net::SocketAddr;
 use std::thread;
 use std::sync::Arc;
 use std::sync::mpsc::{channel, Sender, Receiver};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use sync::{create_sync_peers, create_local_sync_node, create_sync_connection_factory, SyncListener};
 use primitives::hash::H256;
-use util::{init_db, node_table_path};
-use {config, p2p, ZCASH_PROTOCOL_VERSION, ZCASH_PROTOCOL_MINIMUM};
+use message::Services;
+use util::{init_db, node_table_path, crawler_dump_path, bind_permission_denied_hint};
+use {config, p2p, shutdown, verification, ZCASH_PROTOCOL_VERSION, ZCASH_PROTOCOL_MINIMUM};
 use super::super::rpc;
 
 enum BlockNotifierTask {
@@ -81,22 +84,75 @@ impl Drop for BlockNotifier {
 	}
 }
 
+/// Set by SIGHUP handler and cleared by the policy reload thread.
+static POLICY_RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Interval between checks of the policy reload request.
+const POLICY_RELOAD_POLL_INTERVAL_MS: u64 = 500;
+
+/// Reload network policy when the node receives SIGHUP. Signal handler only sets the flag, policy
+/// file is read by the separate thread.
+#[cfg(unix)]
+fn install_policy_reload_handler(p2p_context: Arc<p2p::Context>) {
+	extern "C" fn on_sighup(_signal: ::libc::c_int) {
+		POLICY_RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+	}
+
+	unsafe {
+		::libc::signal(::libc::SIGHUP, on_sighup as ::libc::sighandler_t);
+	}
+
+	thread::Builder::new()
+		.name("Policy reload thread".to_owned())
+		.spawn(move || loop {
+			thread::sleep(Duration::from_millis(POLICY_RELOAD_POLL_INTERVAL_MS));
+			if POLICY_RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+				if let Err(err) = p2p_context.reload_policy() {
+					error!(target: "zebra", "Failed to reload network policy: {}", err);
+				}
+			}
+		})
+		.expect("Error creating policy reload thread");
+}
+
+/// There's no SIGHUP on this platform => policy could only be reloaded with reloadconfig RPC.
+#[cfg(not(unix))]
+fn install_policy_reload_handler(_p2p_context: Arc<p2p::Context>) {
+}
+
 pub fn start(cfg: config::Config) -> Result<(), String> {
+	verification::verify_chain_params(&cfg.consensus)
+		.map_err(|err| format!("Chain parameters self-test has failed: {}", err))?;
+
 	let mut el = p2p::event_loop();
 
 	init_db(&cfg)?;
 
-	let nodes_path = node_table_path(&cfg);
+	let nodes_path = node_table_path(&cfg)?;
+	let crawler_dump = match cfg.crawler_dump {
+		Some(ref path) => Some(path.clone()),
+		None if cfg.crawler => Some(crawler_dump_path(&cfg)?),
+		None => None,
+	};
+	let listen_address = SocketAddr::new(cfg.host, cfg.port);
 
 	let p2p_cfg = p2p::Config {
 		threads: cfg.p2p_threads,
+		listen: cfg.listen,
 		inbound_connections: cfg.inbound_connections,
 		outbound_connections: cfg.outbound_connections,
+		inbound_connections_per_ip: cfg.inbound_connections_per_ip,
+		inbound_attempts_per_subnet: cfg.inbound_attempts_per_subnet,
+		whitelist: cfg.whitelist,
 		connection: p2p::NetConfig {
 			protocol_version: ZCASH_PROTOCOL_VERSION,
 			protocol_minimum: ZCASH_PROTOCOL_MINIMUM,
 			magic: cfg.consensus.magic(),
-			local_address: SocketAddr::new(cfg.host, cfg.port),
+			// when not listening, unspecified address is sent to peers in version message
+			local_address: match cfg.listen {
+				true => listen_address,
+				false => SocketAddr::from(([0, 0, 0, 0], 0)),
+			},
 			services: cfg.services,
 			user_agent: cfg.user_agent,
 			start_height: 0,
@@ -105,12 +161,18 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
 		peers: cfg.connect.map_or_else(|| vec![], |x| vec![x]),
 		seeds: cfg.seednodes,
 		node_table_path: nodes_path,
-		preferable_services: cfg.services,
+		// pruned node still prefers peers, serving all blocks
+		preferable_services: Services::default().with_network(true),
 		internet_protocol: cfg.internet_protocol,
+		misbehaviour: cfg.misbehaviour,
+		policy_path: cfg.policy_path.clone(),
+		record_path: cfg.record_p2p,
+		crawler: cfg.crawler,
+		crawler_dump_path: crawler_dump,
 	};
 
 	let sync_peers = create_sync_peers();
-	let local_sync_node = create_local_sync_node(cfg.consensus.clone(), cfg.db.clone(), sync_peers.clone(), cfg.verification_params);
+	let local_sync_node = create_local_sync_node(cfg.consensus.clone(), cfg.db.clone(), sync_peers.clone(), cfg.verification_params, cfg.blocks_memory_budget * 1024 * 1024, cfg.fee_policy, cfg.standardness_policy, cfg.block_limits);
 	let sync_connection_factory = create_sync_connection_factory(sync_peers.clone(), local_sync_node.clone());
 
 	if let Some(block_notify_command) = cfg.block_notify_command {
@@ -118,6 +180,9 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
 	}
 
 	let p2p = try!(p2p::P2P::new(p2p_cfg, sync_connection_factory, el.handle()).map_err(|x| x.to_string()));
+	if cfg.policy_path.is_some() {
+		install_policy_reload_handler(p2p.context().clone());
+	}
 	let rpc_deps = rpc::Dependencies {
 		consensus: cfg.consensus,
 		storage: cfg.db,
@@ -127,7 +192,15 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
 	};
 	let _rpc_server = try!(rpc::new_http(cfg.rpc_config, rpc_deps));
 
-	try!(p2p.run().map_err(|_| "Failed to start p2p module"));
-	el.run(p2p::forever()).unwrap();
+	try!(p2p.run().map_err(|err| match err.downcast_ref::<io::Error>() {
+		Some(err) if err.kind() == io::ErrorKind::PermissionDenied =>
+			format!("Failed to listen for peers on {}: {}", listen_address, bind_permission_denied_hint(listen_address.port())),
+		_ => format!("Failed to start p2p module: {}", err),
+	}));
+
+	shutdown::install_signal_handlers();
+	// shutdown thread never drops the sender => error is impossible
+	let _ = el.run(shutdown::requested());
+	info!(target: "zebra", "Shutting down");
 	Ok(())
 }
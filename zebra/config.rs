@@ -1,18 +1,19 @@
-use std::net;
+use std::{fs, net, path};
 use clap;
+use db;
 use storage;
 use keys::Address;
 use message::Services;
-use network::{Network, ConsensusParams};
-use p2p::InternetProtocol;
-use seednodes::{zcash_seednodes, zcash_testnet_seednodes};
+use network::{Network, ConsensusParams, ChainSpec, BlockLimits};
+use p2p::{InternetProtocol, MisbehaviourPolicy};
 use rpc_apis::ApiSet;
 use {USER_AGENT, REGTEST_USER_AGENT};
 use primitives::hash::H256;
 use rpc::HttpConfiguration as RpcHttpConfig;
-use verification::VerificationLevel;
+use verification::{VerificationLevel, DEFAULT_SIGNATURE_CACHE_SIZE, DEFAULT_SCRIPT_CACHE_SIZE};
 use sync::VerificationParameters;
-use util::open_db;
+use miner::{FeePolicy, StandardnessPolicy};
+use util::{open_db, prepare_reindex};
 
 pub struct Config {
 	pub network: Network,
@@ -23,21 +24,57 @@ pub struct Config {
 	pub host: net::IpAddr,
 	pub seednodes: Vec<String>,
 	pub quiet: bool,
+	pub listen: bool,
 	pub inbound_connections: u32,
 	pub outbound_connections: u32,
+	pub inbound_connections_per_ip: u32,
+	pub inbound_attempts_per_subnet: u32,
+	pub whitelist: Vec<net::IpAddr>,
+	pub misbehaviour: MisbehaviourPolicy,
+	pub policy_path: Option<path::PathBuf>,
 	pub p2p_threads: usize,
-	pub db_cache: usize,
+	pub db_settings: db::DatabaseSettings,
+	pub blocks_memory_budget: usize,
 	pub data_dir: Option<String>,
 	pub user_agent: String,
 	pub internet_protocol: InternetProtocol,
 	pub rpc_config: RpcHttpConfig,
 	pub block_notify_command: Option<String>,
+	pub record_p2p: Option<path::PathBuf>,
+	pub crawler: bool,
+	pub crawler_dump: Option<path::PathBuf>,
 	pub verification_params: VerificationParameters,
+	pub verification_threads: Option<usize>,
 	pub db: storage::SharedStore,
+	pub override_network: bool,
 	pub miner_address: Option<Address>,
+	pub fee_policy: FeePolicy,
+	pub standardness_policy: StandardnessPolicy,
+	pub block_limits: BlockLimits,
+	pub reindex: Option<Reindex>,
+}
+
+/// What is rebuilt by the reindex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReindexMode {
+	/// All blocks of raw blocks files (side chain blocks included) are verified && appended.
+	Full,
+	/// Only canon chain blocks are verified && appended.
+	ChainState,
+}
+
+/// Database, moved aside to be rebuilt.
+pub struct Reindex {
+	pub mode: ReindexMode,
+	/// Path of the moved database, which blocks are reindexed.
+	pub source: path::PathBuf,
 }
 
 pub const DEFAULT_DB_CACHE: usize = 512;
+pub const DEFAULT_BLOCKS_MEMORY_BUDGET: usize = 512;
+pub const DEFAULT_INBOUND_CONNECTIONS_PER_IP: u32 = 3;
+pub const DEFAULT_INBOUND_ATTEMPTS_PER_SUBNET: u32 = 10;
+pub const DEFAULT_CRAWLER_CONNECTIONS: u32 = 64;
 
 pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 	let db_cache = match matches.value_of("db-cache") {
@@ -45,36 +82,148 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 		None => DEFAULT_DB_CACHE,
 	};
 
+	let mut db_settings = db::DatabaseSettings::with_cache(db_cache);
+	if let Some(s) = matches.value_of("db-write-buffer") {
+		db_settings.write_buffer_size = match s.parse() {
+			Ok(size) if size != 0 => size,
+			_ => return Err("Invalid database write buffer size - should be positive number in MB".into()),
+		};
+	}
+	if let Some(s) = matches.value_of("db-compaction-profile") {
+		db_settings.compaction = match s {
+			"ssd" => db::kv::CompactionProfile::ssd(),
+			"hdd" => db::kv::CompactionProfile::hdd(),
+			_ => return Err("Invalid database compaction profile - should be ssd or hdd".into()),
+		};
+	}
+	if let Some(s) = matches.value_of("db-compaction") {
+		db_settings.compaction.style = match s {
+			"universal" => db::kv::CompactionStyle::Universal,
+			"level" => db::kv::CompactionStyle::Level,
+			_ => return Err("Invalid database compaction style - should be universal or level".into()),
+		};
+	}
+	if let Some(s) = matches.value_of("db-max-open-files") {
+		db_settings.max_open_files = match s.parse() {
+			Ok(files) if files > 0 => files,
+			_ => return Err("Invalid database open files limit - should be positive number".into()),
+		};
+	}
+
+	let blocks_memory_budget = match matches.value_of("sync-memory") {
+		Some(s) => s.parse().map_err(|_| "Invalid sync memory size - should be number in MB".to_owned())?,
+		None => DEFAULT_BLOCKS_MEMORY_BUDGET,
+	};
+
 	let data_dir = match matches.value_of("data-dir") {
 		Some(s) => Some(s.parse().map_err(|_| "Invalid data-dir".to_owned())?),
 		None => None,
 	};
 
-	let db = open_db(&data_dir, db_cache);
+	let prune = match matches.value_of("prune") {
+		Some(s) => match s.parse() {
+			Ok(keep_blocks) if keep_blocks >= db::MIN_BLOCKS_TO_KEEP => Some(keep_blocks),
+			_ => return Err(format!("Invalid prune - should be number of blocks, not less than {}", db::MIN_BLOCKS_TO_KEEP)),
+		},
+		None => None,
+	};
+	let undo_horizon = match matches.value_of("undo-horizon") {
+		Some(s) => match s.parse() {
+			Ok(undo_horizon) if undo_horizon >= db::MIN_UNDO_HORIZON => Some(undo_horizon),
+			_ => return Err(format!("Invalid undo-horizon - should be number of blocks, not less than {}", db::MIN_UNDO_HORIZON)),
+		},
+		None => None,
+	};
+	// pruned blocks can't be indexed
+	if prune.is_some() && (matches.is_present("addressindex") || matches.is_present("spentindex")) {
+		return Err("Pruning is incompatible with addressindex and spentindex options".into());
+	}
+
+	let reindex_mode = match (matches.is_present("reindex"), matches.is_present("reindex-chainstate")) {
+		(false, false) => None,
+		(true, false) => Some(ReindexMode::Full),
+		(false, true) => Some(ReindexMode::ChainState),
+		(true, true) => return Err("Only one of reindex and reindex-chainstate options can be used".into()),
+	};
+	// reindexed blocks are read from the database files, which are removed by pruning
+	if reindex_mode.is_some() && prune.is_some() {
+		return Err("Reindex is incompatible with prune option".into());
+	}
+
+	let reindex = prepare_reindex(&data_dir, &db_settings, reindex_mode)?;
+	let override_network = matches.is_present("override");
 
 	let quiet = matches.is_present("quiet");
-	let network = match (matches.is_present("testnet"), matches.is_present("regtest")) {
-		(true, false) => Network::Testnet,
-		(false, true) => Network::Regtest,
-		(false, false) => Network::Mainnet,
-		(true, true) => return Err("Only one testnet option can be used".into()),
+	let network = match (matches.is_present("testnet"), matches.is_present("regtest"), matches.value_of("chain-spec")) {
+		(true, false, None) => Network::Testnet,
+		(false, true, None) => Network::Regtest,
+		(false, false, None) => Network::Mainnet,
+		(false, false, Some(path)) => {
+			let json = fs::read_to_string(path)
+				.map_err(|err| format!("Unable to read chain specification {}: {}", path, err))?;
+			Network::custom(ChainSpec::from_json(&json)?)
+		},
+		_ => return Err("Only one network option can be used".into()),
+	};
+
+	let mut consensus = ConsensusParams::new(network);
+	if let Some(params_dir) = matches.value_of("sapling-params") {
+		consensus.load_sapling_verifying_keys(path::Path::new(params_dir))?;
+	}
+
+	let db = open_db(&data_dir, &db_settings, &consensus, matches.is_present("addressindex"), matches.is_present("spentindex"), prune, undo_horizon)?;
+
+	let listen = match matches.value_of("listen") {
+		Some("0") => false,
+		Some("1") | None => true,
+		Some(_) => return Err("Invalid listen option - should be 0 or 1".into()),
 	};
 
-	let consensus = ConsensusParams::new(network);
+	let crawler = matches.is_present("crawler");
+	let crawler_dump = matches.value_of("crawler-dump").map(path::PathBuf::from);
 
 	let (in_connections, out_connections) = match network {
-		Network::Testnet | Network::Mainnet | Network::Other(_) => (10, 10),
+		Network::Testnet | Network::Mainnet | Network::Custom(_) => (10, 10),
 		Network::Regtest | Network::Unitest => (1, 0),
 	};
+	// crawler connections are short-lived => many of them are opened at once
+	let out_connections = if crawler { DEFAULT_CRAWLER_CONNECTIONS } else { out_connections };
+
+	// outbound connections are preferred over inbound connections
+	let (in_connections, out_connections) = match matches.value_of("maxconnections") {
+		Some(s) => {
+			let max_connections: u32 = s.parse().map_err(|_| "Invalid maxconnections".to_owned())?;
+			let out_connections = ::std::cmp::min(max_connections, out_connections);
+			(max_connections - out_connections, out_connections)
+		},
+		None => (in_connections, out_connections),
+	};
+	let in_connections = if listen { in_connections } else { 0 };
+
+	let inbound_connections_per_ip = match matches.value_of("inbound-connections-per-ip") {
+		Some(s) => match s.parse::<u32>() {
+			Ok(n) if n != 0 => n,
+			_ => return Err("Invalid inbound-connections-per-ip - should be positive number".into()),
+		},
+		None => DEFAULT_INBOUND_CONNECTIONS_PER_IP,
+	};
+
+	let inbound_attempts_per_subnet = match matches.value_of("inbound-attempts-per-subnet") {
+		Some(s) => match s.parse::<u32>() {
+			Ok(n) if n != 0 => n,
+			_ => return Err("Invalid inbound-attempts-per-subnet - should be positive number".into()),
+		},
+		None => DEFAULT_INBOUND_ATTEMPTS_PER_SUBNET,
+	};
 
 	let p2p_threads = match network {
-		Network::Testnet | Network::Mainnet | Network::Other(_) => 4,
+		Network::Testnet | Network::Mainnet | Network::Custom(_) => 4,
 		Network::Regtest | Network::Unitest => 1,
 	};
 
 	// to skip idiotic 30 seconds delay in test-scripts
 	let user_agent = match network {
-		Network::Testnet | Network::Mainnet | Network::Unitest | Network::Other(_)
+		Network::Testnet | Network::Mainnet | Network::Unitest | Network::Custom(_)
 			=> USER_AGENT.into(),
 		Network::Regtest => REGTEST_USER_AGENT.into(),
 	};
@@ -96,13 +245,28 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 
 	let seednodes: Vec<String> = match matches.value_of("seednode") {
 		Some(s) => vec![s.parse().map_err(|_| "Invalid seednode".to_owned())?],
-		None => match network {
-			Network::Mainnet => zcash_seednodes().into_iter().map(Into::into).collect(),
-			Network::Testnet => zcash_testnet_seednodes().into_iter().map(Into::into).collect(),
-			Network::Other(_) | Network::Regtest | Network::Unitest => Vec::new(),
-		},
+		None => network.dns_seeds().into_iter()
+			.map(|seed| format!("{}:{}", seed, network.port()))
+			.collect(),
+	};
+
+	let whitelist = match matches.values_of("whitelist") {
+		Some(values) => values
+			.map(|s| s.parse::<net::IpAddr>().map_err(|_| format!("Invalid whitelist address: {}", s)))
+			.collect::<Result<Vec<_>, _>>()?,
+		None => Vec::new(),
 	};
 
+	let mut misbehaviour = MisbehaviourPolicy::default();
+	if let Some(s) = matches.value_of("ban-score") {
+		misbehaviour.ban_score = s.parse().map_err(|_| "Invalid ban-score".to_owned())?;
+	}
+	if let Some(s) = matches.value_of("ban-decay") {
+		misbehaviour.decay_interval = s.parse().map_err(|_| "Invalid ban-decay".to_owned())?;
+	}
+
+	let policy_path = matches.value_of("policy-config").map(path::PathBuf::from);
+
 	let only_net = match matches.value_of("only-net") {
 		Some(s) => s.parse()?,
 		None => InternetProtocol::default(),
@@ -123,14 +287,47 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 		None => None,
 	};
 
-	let services = Services::default().with_network(true);
+	let record_p2p = matches.value_of("record-p2p").map(path::PathBuf::from);
+
+	let signature_cache_size = match matches.value_of("sig-cache") {
+		Some(s) => s.parse().map_err(|_| "Invalid sig-cache - should be number of signatures".to_owned())?,
+		None => DEFAULT_SIGNATURE_CACHE_SIZE,
+	};
+
+	let script_cache_size = match matches.value_of("script-cache") {
+		Some(s) => s.parse().map_err(|_| "Invalid script-cache - should be number of script executions".to_owned())?,
+		None => DEFAULT_SCRIPT_CACHE_SIZE,
+	};
+
+	let verification_threads = match matches.value_of("verification-threads") {
+		Some(s) => match s.parse() {
+			Ok(threads) if threads != 0 => Some(threads),
+			_ => return Err("Invalid verification-threads - should be positive number".to_owned()),
+		},
+		None => None,
+	};
+
+	// pruned node only serves last blocks
+	let services = match prune {
+		Some(_) => Services::default().with_network_limited(true),
+		None => Services::default().with_network(true),
+	};
 
 	let verification_level = match matches.value_of("verification-level") {
 		Some(s) if s == "full" => VerificationLevel::FULL,
 		Some(s) if s == "header" => VerificationLevel::HEADER,
 		Some(s) if s == "none" => VerificationLevel::NO_VERIFICATION,
 		Some(s) => return Err(format!("Invalid verification level: {}", s)),
-		None => VerificationLevel::FULL,
+		// scripts && proofs of the verification edge ancestors (in the headers chain) aren't verified by default,
+		// all other blocks are fully verified
+		None => VerificationLevel::HEADER,
+	};
+	// explicitly given verification level is applied to all blocks below the verification edge
+	let edge_ancestors_only = !matches.is_present("verification-level");
+	let verification_level = if matches.is_present("skip-sprout-proofs") {
+		verification_level | VerificationLevel::NO_SPROUT_PROOFS
+	} else {
+		verification_level
 	};
 
 	let verification_edge = match matches.value_of("verification-edge") {
@@ -141,6 +338,30 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 		_ => network.default_verification_edge(),
 	};
 
+	let fee_policy = match matches.value_of("fee-policy") {
+		Some("nonzero") | None => FeePolicy::NonZero,
+		#[cfg(feature = "zip317")]
+		Some("zip317") => FeePolicy::Zip317,
+		Some(s) => return Err(format!("Invalid fee policy: {}", s)),
+	};
+
+	let mut standardness_policy = StandardnessPolicy::default();
+	standardness_policy.accept_non_standard = matches.is_present("acceptnonstdtxn");
+	if let Some(s) = matches.value_of("datacarriersize") {
+		standardness_policy.max_data_carrier_size = s.parse().map_err(|_| "Invalid datacarriersize - should be number of bytes".to_owned())?;
+	}
+	if let Some(s) = matches.value_of("minrelaytxfee") {
+		standardness_policy.min_relay_fee_rate = s.parse().map_err(|_| "Invalid minrelaytxfee - should be zatoshis per 1000 bytes".to_owned())?;
+	}
+	if let Some(s) = matches.value_of("dustrelayfee") {
+		standardness_policy.dust_relay_fee_rate = s.parse().map_err(|_| "Invalid dustrelayfee - should be zatoshis per 1000 bytes".to_owned())?;
+	}
+
+	let block_limits = match matches.value_of("blockmaxsize") {
+		Some(s) => consensus.block_limits.with_max_block_size(s.parse().map_err(|_| "Invalid blockmaxsize - should be number of bytes".to_owned())?),
+		None => consensus.block_limits,
+	};
+
 	let miner_address = match matches.value_of("miner-address") {
 		Some(s) => Some(s.parse().map_err(|_| "Invalid miner-address command".to_owned())?),
 		None => None,
@@ -148,6 +369,7 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 
 	let config = Config {
 		quiet: quiet,
+		listen: listen,
 		network: network,
 		consensus: consensus,
 		services: services,
@@ -157,19 +379,37 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
 		seednodes: seednodes,
 		inbound_connections: in_connections,
 		outbound_connections: out_connections,
+		inbound_connections_per_ip: inbound_connections_per_ip,
+		inbound_attempts_per_subnet: inbound_attempts_per_subnet,
+		whitelist: whitelist,
+		misbehaviour: misbehaviour,
+		policy_path: policy_path,
 		p2p_threads: p2p_threads,
-		db_cache: db_cache,
+		db_settings: db_settings,
+		blocks_memory_budget: blocks_memory_budget,
 		data_dir: data_dir,
 		user_agent: user_agent,
 		internet_protocol: only_net,
 		rpc_config: rpc_config,
 		block_notify_command: block_notify_command,
+		record_p2p: record_p2p,
+		crawler: crawler,
+		crawler_dump: crawler_dump,
 		verification_params: VerificationParameters {
 			verification_level: verification_level,
 			verification_edge: verification_edge,
+			edge_ancestors_only: edge_ancestors_only,
+			signature_cache_size: signature_cache_size,
+			script_cache_size: script_cache_size,
 		},
+		verification_threads: verification_threads,
 		db: db,
+		override_network: override_network,
 		miner_address: miner_address,
+		fee_policy: fee_policy,
+		standardness_policy: standardness_policy,
+		block_limits: block_limits,
+		reindex: reindex,
 	};
 
 	Ok(config)
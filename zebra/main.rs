@@ -4,7 +4,11 @@ extern crate clap;
 extern crate log;
 extern crate env_logger;
 extern crate app_dirs;
+extern crate futures;
 extern crate libc;
+#[cfg(windows)]
+#[macro_use]
+extern crate windows_service;
 
 extern crate storage;
 extern crate db;
@@ -14,19 +18,25 @@ extern crate logs;
 extern crate script;
 extern crate message;
 extern crate network;
+extern crate miner;
 extern crate p2p;
 extern crate sync;
 extern crate import;
 extern crate rpc as ethcore_rpc;
 extern crate primitives;
 extern crate verification;
+#[cfg(test)]
+extern crate tempdir;
+#[cfg(test)]
+extern crate test_data;
 
 mod commands;
 mod config;
-mod seednodes;
 mod util;
 mod rpc;
 mod rpc_apis;
+mod service;
+mod shutdown;
 
 use app_dirs::AppInfo;
 
@@ -51,7 +61,17 @@ fn main() {
 fn run() -> Result<(), String> {
 	let yaml = load_yaml!("cli.yml");
 	let matches = clap::App::from_yaml(yaml).get_matches();
-	let cfg = try!(config::parse(&matches));
+
+	// the node is started by the service dispatcher, after the process is connected to the SCM
+	if matches.subcommand_name() == Some("service") {
+		return service::run();
+	}
+
+	run_with_matches(&matches)
+}
+
+fn run_with_matches(matches: &clap::ArgMatches) -> Result<(), String> {
+	let cfg = try!(config::parse(matches));
 
 	if !cfg.quiet {
 		if cfg!(windows) {
@@ -63,9 +83,21 @@ fn run() -> Result<(), String> {
 		env_logger::init();
 	}
 
+	if let Some(threads) = cfg.verification_threads {
+		verification::init_thread_pool(threads)?;
+	}
+
+	if let Some(ref reindex) = cfg.reindex {
+		commands::reindex(&cfg, reindex)?;
+	}
+
 	match matches.subcommand() {
 		("import", Some(import_matches)) => commands::import(cfg, import_matches),
+		("audit-supply", Some(audit_matches)) => commands::audit_supply(cfg, audit_matches),
 		("rollback", Some(rollback_matches)) => commands::rollback(cfg, rollback_matches),
+		("replay", Some(replay_matches)) => commands::replay(cfg, replay_matches),
+		("export-snapshot", Some(export_matches)) => commands::export_snapshot(cfg, export_matches),
+		("load-snapshot", Some(load_matches)) => commands::load_snapshot(cfg, load_matches),
 		_ => commands::start(cfg),
 	}
 }
@@ -8,6 +8,7 @@ use keys::Address;
 use sync;
 use storage;
 use p2p;
+use util::bind_permission_denied_hint;
 
 pub struct Dependencies {
 	pub consensus: ConsensusParams,
@@ -63,6 +64,9 @@ pub fn setup_http_rpc_server(
 		Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => {
 			Err(format!("RPC address {} is already in use, make sure that another instance of a Bitcoin node is not running or change the address using the --jsonrpc-port and --jsonrpc-interface options.", url))
 		},
+		Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {
+			Err(format!("Failed to bind RPC server to {}: {}", url, bind_permission_denied_hint(url.port())))
+		},
 		Err(e) => Err(format!("RPC error: {:?}", e)),
 		Ok(server) => Ok(server),
 	}
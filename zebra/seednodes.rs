@@ -1,13 +0,0 @@
-pub fn zcash_seednodes() -> Vec<&'static str> {
-	vec![
-		"dnsseed.z.cash:8233",
-		"dnsseed.str4d.xyz:8233",
-		"dnsseed.znodes.org:8233",
-	]
-}
-
-pub fn zcash_testnet_seednodes() -> Vec<&'static str> {
-	vec![
-		"dnsseed.testnet.z.cash:18233",
-	]
-}
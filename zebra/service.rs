@@ -0,0 +1,68 @@
+//! Running the node as a Windows service.
+
+/// Name of the service, as it is registered in the service control manager.
+#[cfg(windows)]
+const SERVICE_NAME: &'static str = "zebra";
+
+/// Connect to the service control manager and run the node until the service is stopped.
+/// Must be called from the process, started by the service control manager.
+#[cfg(windows)]
+pub fn run() -> Result<(), String> {
+	::windows_service::service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+		.map_err(|err| format!("Failed to connect to the service control manager: {}", err))
+}
+
+#[cfg(not(windows))]
+pub fn run() -> Result<(), String> {
+	Err("Running as a service is only supported on Windows".into())
+}
+
+#[cfg(windows)]
+define_windows_service!(ffi_service_main, service_main);
+
+#[cfg(windows)]
+fn service_main(_arguments: Vec<::std::ffi::OsString>) {
+	if let Err(err) = run_service() {
+		error!(target: "zebra", "Service has stopped with error: {}", err);
+	}
+}
+
+#[cfg(windows)]
+fn run_service() -> Result<(), String> {
+	use std::time::Duration;
+	use windows_service::service::{ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType};
+	use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+	use shutdown;
+
+	let status_handle = service_control_handler::register(SERVICE_NAME, |control| match control {
+		ServiceControl::Stop | ServiceControl::Shutdown => {
+			shutdown::request();
+			ServiceControlHandlerResult::NoError
+		},
+		ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+		_ => ServiceControlHandlerResult::NotImplemented,
+	}).map_err(|err| format!("Failed to register service control handler: {}", err))?;
+
+	let set_status = |state, controls_accepted, exit_code| status_handle.set_service_status(ServiceStatus {
+		service_type: ServiceType::OWN_PROCESS,
+		current_state: state,
+		controls_accepted: controls_accepted,
+		exit_code: exit_code,
+		checkpoint: 0,
+		wait_hint: Duration::default(),
+	}).map_err(|err| format!("Failed to update service status: {}", err));
+
+	set_status(ServiceState::Running, ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN, ServiceExitCode::Win32(0))?;
+
+	// service is started with the same command line as the node itself
+	let yaml = load_yaml!("cli.yml");
+	let matches = ::clap::App::from_yaml(yaml).get_matches();
+	let result = ::run_with_matches(&matches);
+
+	let exit_code = match result {
+		Ok(_) => ServiceExitCode::Win32(0),
+		Err(_) => ServiceExitCode::ServiceSpecific(1),
+	};
+	set_status(ServiceState::Stopped, ServiceControlAccept::empty(), exit_code)?;
+	result
+}
@@ -0,0 +1,45 @@
+//! Graceful shutdown of the node.
+
+use std::thread;
+use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use futures::sync::oneshot;
+
+/// Set by signal handlers (or by the service control handler on Windows) and checked by the shutdown thread.
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Interval between checks of the shutdown request.
+const SHUTDOWN_POLL_INTERVAL_MS: u64 = 200;
+
+/// Ask the node to stop.
+pub fn request() {
+	SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Request shutdown when the node receives SIGINT or SIGTERM. Both signals are supported on every
+/// platform: on Windows, CTRL+C in console is delivered as SIGINT.
+pub fn install_signal_handlers() {
+	extern "C" fn on_signal(_signal: ::libc::c_int) {
+		request();
+	}
+
+	unsafe {
+		::libc::signal(::libc::SIGINT, on_signal as ::libc::sighandler_t);
+		::libc::signal(::libc::SIGTERM, on_signal as ::libc::sighandler_t);
+	}
+}
+
+/// Returns future that is resolved when shutdown is requested.
+pub fn requested() -> oneshot::Receiver<()> {
+	let (tx, rx) = oneshot::channel();
+	thread::Builder::new()
+		.name("Shutdown thread".to_owned())
+		.spawn(move || {
+			while !SHUTDOWN_REQUESTED.load(Ordering::SeqCst) {
+				thread::sleep(Duration::from_millis(SHUTDOWN_POLL_INTERVAL_MS));
+			}
+			let _ = tx.send(());
+		})
+		.expect("Error creating shutdown thread");
+	rx
+}
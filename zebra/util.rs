@@ -1,29 +1,106 @@
 use std::sync::Arc;
 use std::path::PathBuf;
-use std::fs::create_dir_all;
+use std::fs::{create_dir_all, remove_dir_all, rename};
 use app_dirs::{app_dir, AppDataType};
 use {storage, APP_INFO};
+use network::{Network, ConsensusParams};
 use db;
-use config::Config;
+use config::{Config, Reindex, ReindexMode};
 
-pub fn open_db(data_dir: &Option<String>, db_cache: usize) -> storage::SharedStore {
-	let db_path = match *data_dir {
-		Some(ref data_dir) => custom_path(&data_dir, "db"),
-		None => app_dir(AppDataType::UserData, &APP_INFO, "db").expect("Failed to get app dir"),
+/// Ports below this number could only be bound by privileged users on Unix.
+const FIRST_UNPRIVILEGED_PORT: u16 = 1024;
+/// Directory (next to the database directory), where the database is moved while it is reindexed.
+const REINDEX_SOURCE_DIR: &'static str = "db-reindex";
+
+pub fn open_db(data_dir: &Option<String>, db_settings: &db::DatabaseSettings, consensus: &ConsensusParams, address_index: bool, spent_index: bool, prune: Option<u32>, undo_horizon: Option<u32>) -> Result<storage::SharedStore, String> {
+	let db_path = data_path(data_dir, "db")?;
+	let db = db::BlockChainDatabase::open_at_path(db_path, db_settings.clone())
+		.map_err(|err| format!("Failed to open database: {}", err))?
+		.with_chain_history(consensus.clone());
+	let db = if address_index { db.with_address_index() } else { db };
+	let db = if spent_index { db.with_spent_index() } else { db };
+	let db = match prune {
+		Some(keep_blocks) => db.with_pruning(keep_blocks),
+		None => db,
+	};
+	let db = match undo_horizon {
+		Some(undo_horizon) => db.with_undo_horizon(undo_horizon),
+		None => db,
 	};
-	Arc::new(db::BlockChainDatabase::open_at_path(db_path, db_cache).expect("Failed to open database"))
+	Ok(Arc::new(db))
 }
 
-pub fn node_table_path(cfg: &Config) -> PathBuf {
-	let mut node_table = match cfg.data_dir {
-		Some(ref data_dir) => custom_path(&data_dir, "p2p"),
-		None => app_dir(AppDataType::UserData, &APP_INFO, "p2p").expect("Failed to get app dir"),
+/// Move the database aside, so that the fresh database could be rebuilt from its blocks. If the previous reindex
+/// has been interrupted, its partially rebuilt database is removed && the reindex is started over. Pruned database
+/// is never moved, since blocks of its removed raw blocks files can't be reindexed.
+pub fn prepare_reindex(data_dir: &Option<String>, db_settings: &db::DatabaseSettings, mode: Option<ReindexMode>) -> Result<Option<Reindex>, String> {
+	let db_path = data_path(data_dir, "db")?;
+	let source = db_path.with_file_name(REINDEX_SOURCE_DIR);
+	let mode = match mode {
+		Some(mode) => mode,
+		None if source.exists() => return Err(format!("Reindex of the database has been interrupted (its blocks are at {}), restart with --reindex or --reindex-chainstate", source.display())),
+		None => return Ok(None),
 	};
+
+	if source.exists() {
+		remove_dir_all(&db_path)
+			.map_err(|err| format!("Failed to remove partially reindexed database: {}", err))?;
+	} else {
+		let pruned_height = db::BlockChainDatabase::open_at_path(&db_path, db_settings.clone())
+			.map_err(|err| format!("Failed to open database: {}", err))?
+			.pruned_height();
+		if pruned_height != 0 {
+			return Err("Pruned database can't be reindexed".into());
+		}
+
+		rename(&db_path, &source)
+			.map_err(|err| format!("Failed to move database to {}: {}", source.display(), err))?;
+	}
+
+	Ok(Some(Reindex {
+		mode: mode,
+		source: source,
+	}))
+}
+
+pub fn node_table_path(cfg: &Config) -> Result<PathBuf, String> {
+	let mut node_table = data_path(&cfg.data_dir, "p2p")?;
 	node_table.push("nodes.csv");
-	node_table
+	Ok(node_table)
+}
+
+pub fn crawler_dump_path(cfg: &Config) -> Result<PathBuf, String> {
+	let mut crawler_dump = data_path(&cfg.data_dir, "p2p")?;
+	crawler_dump.push("crawler.dump");
+	Ok(crawler_dump)
+}
+
+/// Directory for the node data of given kind. Unless `--data-dir` is specified, directory is created in the
+/// data directory of the current user: `%APPDATA%` on Windows, `~/Library/Application Support` on macOS and
+/// `$XDG_DATA_HOME` (`~/.local/share`) on other platforms. Services are running under separate accounts, so
+/// they are using their own directories.
+fn data_path(data_dir: &Option<String>, sub_dir: &str) -> Result<PathBuf, String> {
+	match *data_dir {
+		Some(ref data_dir) => Ok(custom_path(&data_dir, sub_dir)),
+		None => app_dir(AppDataType::UserData, &APP_INFO, sub_dir)
+			.map_err(|err| format!("Failed to get user data directory ({}), use --data-dir to specify it", err)),
+	}
+}
+
+/// Explains why the node is not permitted to bind to the port.
+pub fn bind_permission_denied_hint(port: u16) -> String {
+	if cfg!(windows) {
+		format!("port {} may be reserved by the system (see `netsh interface ipv4 show excludedportrange protocol=tcp`), choose another port", port)
+	} else if port < FIRST_UNPRIVILEGED_PORT {
+		format!("ports below {} could only be bound by privileged users, choose another port or allow the node to bind privileged ports", FIRST_UNPRIVILEGED_PORT)
+	} else {
+		format!("binding to port {} is not permitted", port)
+	}
 }
 
 pub fn init_db(cfg: &Config) -> Result<(), String> {
+	check_db_network(cfg)?;
+
 	// insert genesis block if db is empty
 	let genesis_block = cfg.network.genesis_block();
 	match cfg.db.block_hash(0) {
@@ -38,9 +115,85 @@ pub fn init_db(cfg: &Config) -> Result<(), String> {
 	}
 }
 
+pub fn check_db_network(cfg: &Config) -> Result<(), String> {
+	// remember network in the fresh database && refuse to open database of other network
+	let network = network_name(cfg.network);
+	match cfg.db.network()? {
+		Some(ref db_network) if *db_network != network && !cfg.override_network =>
+			Err(format!("Trying to open database of {} network with {} network. Use --override to open it anyway", db_network, network)),
+		Some(ref db_network) if *db_network != network => {
+			warn!("Opening database of {} network with {} network", db_network, network);
+			Ok(())
+		},
+		Some(_) => Ok(()),
+		None => cfg.db.set_network(&network).map_err(Into::into),
+	}
+}
+
+fn network_name(network: Network) -> String {
+	match network {
+		Network::Mainnet => "mainnet".into(),
+		Network::Testnet => "testnet".into(),
+		Network::Regtest => "regtest".into(),
+		Network::Unitest => "unitest".into(),
+		Network::Custom(spec) => format!("custom-{}", spec.name),
+	}
+}
+
 fn custom_path(data_dir: &str, sub_dir: &str) -> PathBuf {
 	let mut path = PathBuf::from(data_dir);
 	path.push(sub_dir);
 	create_dir_all(&path).expect("Failed to get app dir");
 	path
 }
+
+#[cfg(test)]
+mod tests {
+	use tempdir::TempDir;
+	use db::{self, BlockChainDatabase};
+	use test_data;
+	use config::ReindexMode;
+	use super::{prepare_reindex, REINDEX_SOURCE_DIR};
+
+	#[test]
+	fn pruned_database_is_not_moved_by_reindex() {
+		let tempdir = TempDir::new("").unwrap();
+		let data_dir = Some(tempdir.path().to_str().unwrap().to_owned());
+		let settings = db::DatabaseSettings::with_cache(16);
+
+		// database, initialized with the snapshot, has no blocks below the snapshot block
+		let chain = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into(), test_data::block_h1().into()]);
+		let mut snapshot = Vec::new();
+		chain.export_snapshot(&mut snapshot).unwrap();
+		{
+			let store = BlockChainDatabase::open_at_path(tempdir.path().join("db"), settings.clone()).unwrap();
+			store.import_snapshot(&snapshot[..], &test_data::genesis().hash()).unwrap();
+			assert_ne!(store.pruned_height(), 0);
+		}
+
+		assert_eq!(prepare_reindex(&data_dir, &settings, Some(ReindexMode::Full)).err(),
+			Some("Pruned database can't be reindexed".to_owned()));
+		assert!(!tempdir.path().join(REINDEX_SOURCE_DIR).exists());
+		// next start isn't treated as interrupted reindex
+		assert!(prepare_reindex(&data_dir, &settings, None).unwrap().is_none());
+	}
+
+	#[test]
+	fn unpruned_database_is_moved_by_reindex() {
+		let tempdir = TempDir::new("").unwrap();
+		let data_dir = Some(tempdir.path().to_str().unwrap().to_owned());
+		let settings = db::DatabaseSettings::with_cache(16);
+		{
+			let store = BlockChainDatabase::open_at_path(tempdir.path().join("db"), settings.clone()).unwrap();
+			let genesis = test_data::genesis();
+			let genesis_hash = genesis.hash();
+			store.insert(genesis.into()).unwrap();
+			store.canonize(&genesis_hash).unwrap();
+		}
+
+		let reindex = prepare_reindex(&data_dir, &settings, Some(ReindexMode::Full)).unwrap().unwrap();
+		assert_eq!(reindex.mode, ReindexMode::Full);
+		assert_eq!(reindex.source, tempdir.path().join(REINDEX_SOURCE_DIR));
+		assert!(reindex.source.exists());
+	}
+}